@@ -0,0 +1,213 @@
+//! `hermes-cli`, a command-line companion working on the same workspaces.
+//!
+//! It reads and writes notes through `hermes-core`, the app's own storage
+//! code: entries are appended under the workspace write lock and re-indexed,
+//! searches query the same indexes, and exports go through the same exporters
+//! and redaction. The
+//! workspace is `--workspace`, else `HERMES_WORKSPACE`, else the default
+//! `~/Documents/Hermes`; settings kept in the app's store, such as the inbox
+//! tab or a workspace marked read-only, aren't visible here.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use hermes_core::redact::Redaction;
+use hermes_core::workspace::default_workspace;
+use hermes_core::{canonical_workspace, capture, export, projects, search, validate_tab_key};
+
+const USAGE: &str = "Usage: hermes-cli [--workspace PATH] <command>
+
+Commands:
+  add TEXT [--project PROJECT] [--tab TAB] [--source SOURCE]
+      Append TEXT as a timestamped entry, to the inbox tab by default.
+  search QUERY [--project PROJECT] [--limit N]
+      List the notes containing every word of QUERY, best matches first.
+  export --tab TAB [--project PROJECT] [--format markdown|html]
+         [--redaction placeholder|strip|off] [--out FILE]
+      Render a note, to FILE or to standard output.
+  rpc [--port PORT]
+      Pass JSON-RPC calls, one per line, to the running app's local API,
+      for editor plugins. The port defaults to 47615, or HERMES_API_PORT.
+
+Projects are paths below the workspace, such as clients/acme.";
+
+/// Exit status for a command line that doesn't parse.
+const USAGE_ERROR: i32 = 2;
+
+/// The words and `--name value` options of a command line.
+struct Args {
+    words: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut words = Vec::new();
+        let mut options = HashMap::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some("help") => words.insert(0, "help".to_string()),
+                Some(name) => {
+                    let value = args.next().ok_or_else(|| format!("--{name} needs a value."))?;
+                    options.insert(name.to_string(), value);
+                }
+                None => words.push(arg),
+            }
+        }
+        Ok(Args { words, options })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /// Fails on options the command doesn't take.
+    fn check_options(&self, allowed: &[&str]) -> Result<(), String> {
+        match self.options.keys().find(|name| !allowed.contains(&name.as_str())) {
+            Some(name) => Err(format!("Unknown option --{name}.")),
+            None => Ok(()),
+        }
+    }
+
+    /// The words after the command, joined, so unquoted text works too.
+    fn text(&self) -> String {
+        self.words[1..].join(" ")
+    }
+}
+
+fn workspace(args: &Args) -> Result<String, String> {
+    let workspace = match args.option("workspace").map(str::to_string).or_else(|| std::env::var("HERMES_WORKSPACE").ok()) {
+        Some(path) if !path.trim().is_empty() => path,
+        _ => default_workspace()?,
+    };
+    let workspace = canonical_workspace(workspace.trim_end_matches(['/', '\\']));
+    if !Path::new(&workspace).is_dir() {
+        return Err(format!("{workspace} is not a folder."));
+    }
+    // So notes of its projects get the workspace's tab file names.
+    projects::register_root(&workspace);
+    Ok(workspace)
+}
+
+/// The folder of `--project` in `workspace`, or the workspace itself.
+fn folder(args: &Args, workspace: &str) -> Result<String, String> {
+    let Some(project) = args.option("project").filter(|project| !project.is_empty()) else {
+        return Ok(workspace.to_string());
+    };
+    projects::check_project(project)?;
+    let folder = Path::new(workspace).join(project);
+    if !folder.is_dir() {
+        return Err(format!("Project '{project}' does not exist in {workspace}"));
+    }
+    Ok(folder.to_string_lossy().to_string())
+}
+
+fn add(args: &Args) -> Result<(), String> {
+    args.check_options(&["workspace", "project", "tab", "source"])?;
+    let workspace = workspace(args)?;
+    let folder = folder(args, &workspace)?;
+    let tab = args.option("tab").unwrap_or(capture::DEFAULT_INBOX_TAB);
+    capture::append_entry(&folder, tab, &args.text(), Some(args.option("source").unwrap_or("hermes-cli")))?;
+    println!("Added to {tab} in {folder}");
+    Ok(())
+}
+
+fn search(args: &Args) -> Result<(), String> {
+    args.check_options(&["workspace", "project", "limit"])?;
+    let query = args.text();
+    if query.trim().is_empty() {
+        return Err("Nothing to search for.".to_string());
+    }
+    let limit = args
+        .option("limit")
+        .map(|limit| limit.parse::<usize>().map_err(|_| format!("Invalid --limit '{limit}'.")))
+        .transpose()?;
+    let workspace = workspace(args)?;
+    let projects = projects::read_project_names(&workspace)?;
+    let scope = args.option("project").map(str::to_string);
+    let hits = search::search_projects(&workspace, projects, &query, scope, limit)?;
+    for hit in &hits {
+        let place = if hit.project.is_empty() { hit.tab.clone() } else { format!("{}/{}", hit.project, hit.tab) };
+        println!("{place}\t{}", hit.title);
+        println!("    {}", hit.snippet.replace('\n', " "));
+    }
+    if hits.is_empty() {
+        eprintln!("No notes match.");
+    }
+    Ok(())
+}
+
+fn export(args: &Args) -> Result<(), String> {
+    args.check_options(&["workspace", "project", "tab", "format", "redaction", "out"])?;
+    let tab = args.option("tab").ok_or_else(|| "export needs --tab.".to_string())?;
+    validate_tab_key(tab)?;
+    let redaction = match args.option("redaction") {
+        Some("placeholder") | None => Redaction::Placeholder,
+        Some("strip") => Redaction::Strip,
+        Some("off") => Redaction::Off,
+        Some(redaction) => return Err(format!("Unknown redaction '{redaction}'; use placeholder, strip or off.")),
+    };
+    let workspace = workspace(args)?;
+    let folder = folder(args, &workspace)?;
+    let format = args.option("format").unwrap_or("markdown");
+    match args.option("out") {
+        Some(out) => {
+            let written = export::export_to(&folder, tab, format, out, Some(redaction))?;
+            eprintln!("Exported to {written}");
+        }
+        None => print!("{}", export::render_note(&folder, tab, format, redaction)?),
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn rpc(args: &Args) -> Result<(), String> {
+    args.check_options(&["port"])?;
+    let port = match args.option("port").map(str::to_string).or_else(|| std::env::var("HERMES_API_PORT").ok()) {
+        Some(port) => Some(port.parse::<u16>().map_err(|_| format!("Invalid port '{port}'."))?),
+        None => None,
+    };
+    hermes_core::local_api::bridge_stdio(port)
+}
+
+/// Runs the command line `args`, without the program name, and returns the exit status.
+fn run(args: impl IntoIterator<Item = String>) -> i32 {
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return USAGE_ERROR;
+        }
+    };
+    let result = match args.words.first().map(String::as_str) {
+        Some("add") => add(&args),
+        Some("search") => search(&args),
+        Some("export") => export(&args),
+        #[cfg(not(any(target_os = "android", target_os = "ios")))]
+        Some("rpc") => rpc(&args),
+        Some("help") => {
+            println!("{USAGE}");
+            return 0;
+        }
+        Some(other) => {
+            eprintln!("Unknown command '{other}'.\n\n{USAGE}");
+            return USAGE_ERROR;
+        }
+        None => {
+            eprintln!("{USAGE}");
+            return USAGE_ERROR;
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+fn main() {
+    std::process::exit(run(std::env::args().skip(1)))
+}
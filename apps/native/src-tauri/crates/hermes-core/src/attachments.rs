@@ -0,0 +1,30 @@
+//! Where each project keeps its attachments, relative to its notes.
+//!
+//! The folder is a setting of the app; it is mirrored here so project
+//! listings and merges, which run without the app, skip and carry it. Tools
+//! that can't read the app's settings see the default.
+
+use std::sync::RwLock;
+
+pub const DEFAULT_FOLDER: &str = ".hermes/assets";
+
+static FOLDER: RwLock<String> = RwLock::new(String::new());
+
+/// Sets the attachments folder, already checked, with `/` separators.
+pub fn set_folder(folder: String) {
+    *FOLDER.write().unwrap() = folder;
+}
+
+/// The attachments folder relative to a project's notes, with `/` separators.
+pub fn relative() -> String {
+    let folder = FOLDER.read().unwrap();
+    match folder.is_empty() {
+        true => DEFAULT_FOLDER.to_string(),
+        false => folder.clone(),
+    }
+}
+
+/// Whether `name`, a folder at the top of a project, holds the attachments.
+pub fn is_top_folder(name: &str) -> bool {
+    relative().split('/').next() == Some(name)
+}
@@ -0,0 +1,56 @@
+//! Quick capture: short snippets appended to a note with a timestamp.
+
+use std::fs;
+
+use crate::workspace::{note_path, read_workspace_pages};
+use crate::{note_lock, validate_tab_key, workspace_lock};
+
+/// The tab captures go to unless the app's settings name another.
+pub const DEFAULT_INBOX_TAB: &str = "coral";
+
+fn format_entry(text: &str, source: Option<&str>) -> String {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M");
+    let mut entry = format!("### {timestamp}\n\n{}\n", text.trim());
+    if let Some(source) = source.map(str::trim).filter(|source| !source.is_empty()) {
+        entry.push_str(&format!("\n_via {source}_\n"));
+    }
+    entry
+}
+
+/// Appends `text` as a timestamped entry to the note in `tab` of
+/// `workspace_path` under the workspace write lock, then re-indexes it.
+/// Returns the entry as appended, after a blank line.
+pub fn append_entry(workspace_path: &str, tab: &str, text: &str, source: Option<&str>) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to capture.".to_string());
+    }
+    validate_tab_key(tab)?;
+    let file_path = note_path(workspace_path, tab);
+    if let Some(dir) = file_path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    let write_lock = workspace_lock::acquire(workspace_path)?;
+
+    let existing = if file_path.exists() {
+        fs::read_to_string(&file_path).map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?
+    } else {
+        String::new()
+    };
+    if note_lock::is_locked(&existing) {
+        return Err(format!("The {tab} note is locked."));
+    }
+
+    let mut content = existing.trim_end().to_string();
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    let entry = format_entry(text, source);
+    content.push_str(&entry);
+
+    fs::write(&file_path, content).map_err(|err| format!("Failed writing {}: {err}", file_path.display()))?;
+    drop(write_lock);
+
+    // Re-reading the workspace refreshes the index for the tab.
+    read_workspace_pages(workspace_path)?;
+    Ok(entry)
+}
@@ -0,0 +1,26 @@
+//! The chat of a workspace, kept as the JSON array of its messages in
+//! `chat.json` at the top of the folder.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CHAT_FILE: &str = "chat.json";
+
+pub fn chat_path(workspace_path: &str) -> PathBuf {
+    Path::new(workspace_path).join(CHAT_FILE)
+}
+
+/// The chat of `workspace_path`, `[]` when it has none yet.
+pub fn load(workspace_path: &str) -> io::Result<String> {
+    match fs::read_to_string(chat_path(workspace_path)) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok("[]".to_string()),
+        result => result,
+    }
+}
+
+/// Replaces the chat of `workspace_path`, creating the folder if needed.
+pub fn save(workspace_path: &str, chat_json: &str) -> io::Result<()> {
+    fs::create_dir_all(workspace_path)?;
+    fs::write(chat_path(workspace_path), chat_json)
+}
@@ -0,0 +1,153 @@
+//! Mermaid diagrams in exports.
+//!
+//! ```` ```mermaid ```` blocks are rendered to SVG with the Mermaid CLI
+//! (`mmdc`, from `npm install -g @mermaid-js/mermaid-cli`) when it is
+//! installed. An export written to a file gets the diagrams as SVG files in a
+//! folder beside it, named by a hash of their source so unchanged diagrams
+//! keep their names; printed exports inline them. Without the CLI, or when a
+//! diagram doesn't parse, its block stays as code.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use sha2::{Digest, Sha256};
+
+use crate::programs;
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Where rendered diagrams go.
+pub enum Output<'a> {
+    /// SVG markup in the document itself.
+    Inline,
+    /// SVG files in `dir`, linked as `link/<file>`.
+    Assets { dir: &'a Path, link: &'a str },
+}
+
+struct Diagram {
+    /// Byte range of the whole code block.
+    block: std::ops::Range<usize>,
+    source: String,
+}
+
+fn mermaid_blocks(content: &str) -> Vec<Diagram> {
+    let mut diagrams = Vec::new();
+    let mut current: Option<Diagram> = None;
+    for (event, range) in Parser::new_ext(content, Options::empty()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                if info.split_whitespace().next() == Some("mermaid") =>
+            {
+                current = Some(Diagram { block: range, source: String::new() });
+            }
+            Event::Text(text) => {
+                if let Some(diagram) = current.as_mut() {
+                    diagram.source.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => diagrams.extend(current.take()),
+            _ => {}
+        }
+    }
+    diagrams
+}
+
+fn mmdc() -> Option<PathBuf> {
+    // npm installs a `.cmd` shim on Windows rather than an executable.
+    #[cfg(windows)]
+    if let Some(shim) = std::env::var_os("PATH")
+        .into_iter()
+        .flat_map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .map(|dir| dir.join("mmdc.cmd"))
+        .find(|path| path.is_file())
+    {
+        return Some(shim);
+    }
+    programs::find_program(None, "mmdc")
+}
+
+/// The SVG for one diagram, written to `output`.
+fn render_svg(mmdc: &Path, source: &str, output: &Path) -> Result<(), String> {
+    let input = output.with_extension("mmd");
+    fs::write(&input, source).map_err(|err| format!("Failed writing {}: {err}", input.display()))?;
+    let mut command = Command::new(mmdc);
+    command
+        .arg("--quiet")
+        .args(["--backgroundColor", "transparent"])
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let result = command.output();
+    let _ = fs::remove_file(&input);
+    let result = result.map_err(|err| format!("Failed to run mmdc: {err}"))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(format!("mmdc failed: {}", stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("unknown error")));
+    }
+    Ok(())
+}
+
+/// SVG markup that can sit in a markdown HTML block: no XML prolog and no
+/// blank lines, which would end the block.
+fn inline_svg(svg: &str) -> String {
+    let svg = svg.trim_start();
+    let svg = match svg.strip_prefix("<?xml") {
+        Some(rest) => rest.split_once("?>").map_or(rest, |(_, rest)| rest).trim_start(),
+        None => svg,
+    };
+    let lines: Vec<&str> = svg.lines().filter(|line| !line.trim().is_empty()).collect();
+    format!("<div class=\"mermaid-diagram\">\n{}\n</div>\n", lines.join("\n"))
+}
+
+/// `content` with its Mermaid blocks replaced by the rendered diagrams.
+pub fn render_mermaid(content: &str, output: Output) -> String {
+    let diagrams = mermaid_blocks(content);
+    if diagrams.is_empty() {
+        return content.to_string();
+    }
+    let Some(mmdc) = mmdc() else {
+        tracing::warn!("Mermaid CLI (mmdc) not found; exporting diagrams as code");
+        return content.to_string();
+    };
+    let scratch = std::env::temp_dir();
+    let mut rendered = content.to_string();
+    // From the end, so earlier ranges stay put.
+    for diagram in diagrams.iter().rev() {
+        let hash: String = Sha256::digest(diagram.source.as_bytes())
+            .iter()
+            .take(6)
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let name = format!("mermaid-{hash}.svg");
+        let replacement = match &output {
+            Output::Assets { dir, link } => fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed creating {}: {err}", dir.display()))
+                .and_then(|_| render_svg(&mmdc, &diagram.source, &dir.join(&name)))
+                .map(|_| format!("![Diagram](<{link}/{name}>)\n")),
+            Output::Inline => {
+                let path = scratch.join(format!("hermes-{}-{name}", std::process::id()));
+                let svg = render_svg(&mmdc, &diagram.source, &path)
+                    .and_then(|_| fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display())));
+                let _ = fs::remove_file(&path);
+                svg.map(|svg| inline_svg(&svg))
+            }
+        };
+        match replacement {
+            Ok(replacement) => rendered.replace_range(diagram.block.clone(), &replacement),
+            Err(err) => tracing::warn!("Failed to render a Mermaid diagram: {err}"),
+        }
+    }
+    rendered
+}
@@ -0,0 +1,141 @@
+//! Note exporters shared by the app's Export menu and `hermes-cli export`.
+
+use std::fs;
+use std::path::Path;
+
+use pulldown_cmark::{html, Options, Parser};
+use serde::Serialize;
+
+use crate::index::extract_title;
+use crate::redact::{self, Redaction};
+use crate::workspace::note_path;
+use crate::{finder_tags, validate_tab_key};
+
+#[derive(Clone, Copy, Serialize)]
+pub struct Exporter {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub extension: &'static str,
+}
+
+pub const EXPORTERS: [Exporter; 2] = [
+    Exporter {
+        id: "markdown",
+        label: "Markdown",
+        extension: "md",
+    },
+    Exporter {
+        id: "html",
+        label: "HTML",
+        extension: "html",
+    },
+];
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders markdown into a standalone HTML document.
+pub fn render_html(content: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let mut body = String::new();
+    html::push_html(&mut body, Parser::new_ext(content, options));
+
+    let title = extract_title(content);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>body{{max-width:42rem;margin:3rem auto;padding:0 1rem;font-family:Georgia,serif;line-height:1.6}}\
+         pre{{overflow-x:auto}}img,svg{{max-width:100%}}table{{border-collapse:collapse}}td,th{{border:1px solid #ccc;padding:4px 8px}}</style>\n\
+         </head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(if title.is_empty() { "Hermes note" } else { &title }),
+    )
+}
+
+/// `content` with its Mermaid diagrams rendered: as SVG files in `assets`
+/// when given, else inline.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn with_diagrams(content: &str, assets: Option<&Path>) -> String {
+    use crate::diagrams::{render_mermaid, Output};
+
+    let link = assets.and_then(|dir| dir.file_name()).map(|name| name.to_string_lossy().to_string());
+    match (assets, &link) {
+        (Some(dir), Some(link)) => render_mermaid(content, Output::Assets { dir, link }),
+        _ => render_mermaid(content, Output::Inline),
+    }
+}
+
+/// Mermaid needs its CLI, which only desktops have; diagrams stay as code.
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn with_diagrams(content: &str, _assets: Option<&Path>) -> String {
+    content.to_string()
+}
+
+/// Redacts private content, then renders it with the exporter for `format`.
+pub fn render(format: &str, content: &str, tags: &[String], redaction: Redaction) -> Result<String, String> {
+    render_to(format, content, tags, redaction, None)
+}
+
+/// Like `render`, writing the diagrams of an HTML export into `assets` when given.
+fn render_to(
+    format: &str,
+    content: &str,
+    tags: &[String],
+    redaction: Redaction,
+    assets: Option<&Path>,
+) -> Result<String, String> {
+    let content = redact::redact(content, tags, redaction);
+    match format {
+        "markdown" => Ok(content),
+        "html" => Ok(render_html(&with_diagrams(&content, assets))),
+        other => Err(format!("Unknown export format '{other}'")),
+    }
+}
+
+/// One tab's content and Finder tags.
+fn read_note(workspace_path: &str, tab_key: &str) -> Result<(String, Vec<String>), String> {
+    validate_tab_key(tab_key)?;
+    let source = note_path(workspace_path, tab_key);
+    let content = fs::read_to_string(&source).map_err(|err| format!("Failed reading {}: {err}", source.display()))?;
+    let tags = finder_tags::read_tags(&source).unwrap_or_default();
+    Ok((content, tags))
+}
+
+/// Reads one tab and renders it with the exporter for `format`.
+pub fn render_note(workspace_path: &str, tab_key: &str, format: &str, redaction: Redaction) -> Result<String, String> {
+    let (content, tags) = read_note(workspace_path, tab_key)?;
+    render(format, &content, &tags, redaction)
+}
+
+/// Exports one tab to `destination`, returning the path that was written.
+/// Private content is replaced with placeholders unless `redaction` says
+/// otherwise; diagrams go in a `<name>-diagrams` folder beside the export.
+pub fn export_to(
+    workspace_path: &str,
+    tab_key: &str,
+    format: &str,
+    destination: &str,
+    redaction: Option<Redaction>,
+) -> Result<String, String> {
+    let (content, tags) = read_note(workspace_path, tab_key)?;
+    let destination = Path::new(destination);
+    let stem = destination.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let assets = destination.with_file_name(format!("{stem}-diagrams"));
+    let rendered = render_to(format, &content, &tags, redaction.unwrap_or_default(), Some(&assets))?;
+    write(destination, &rendered)
+}
+
+/// Writes an export, creating its folder, and returns the path that was written.
+pub fn write(destination: &Path, contents: &str) -> Result<String, String> {
+    if let Some(dir) = destination.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    fs::write(destination, contents).map_err(|err| format!("Failed writing {}: {err}", destination.display()))?;
+    Ok(destination.to_string_lossy().to_string())
+}
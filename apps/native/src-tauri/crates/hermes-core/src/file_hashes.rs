@@ -0,0 +1,77 @@
+//! Hashes of note files as Hermes last wrote or read them.
+//!
+//! Autosave sends every tab whether or not it was edited; rewriting identical
+//! files churns mtimes and wakes sync clients. A save is skipped when the new
+//! content hashes the same as the file's remembered content and the file's
+//! size and mtime show nothing else has written it since.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+struct Known {
+    hash: String,
+    len: u64,
+    modified: SystemTime,
+}
+
+static KNOWN: Mutex<Option<HashMap<PathBuf, Known>>> = Mutex::new(None);
+
+/// Hex SHA-256 of `content`.
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn stat(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Remembers `content` as what `path` now holds on disk.
+pub fn remember(path: &Path, content: &str) {
+    let mut known = KNOWN.lock().unwrap();
+    let known = known.get_or_insert_with(HashMap::new);
+    match stat(path) {
+        Some((len, modified)) => {
+            let hash = content_hash(content.as_bytes());
+            known.insert(path.to_path_buf(), Known { hash, len, modified });
+        }
+        None => {
+            known.remove(path);
+        }
+    }
+}
+
+pub fn forget(path: &Path) {
+    if let Some(known) = KNOWN.lock().unwrap().as_mut() {
+        known.remove(path);
+    }
+}
+
+/// Forgets every file under `dir`.
+pub fn forget_under(dir: &Path) {
+    if let Some(known) = KNOWN.lock().unwrap().as_mut() {
+        known.retain(|path, _| !path.starts_with(dir));
+    }
+}
+
+fn current_hash(path: &Path) -> Option<String> {
+    let known = KNOWN.lock().unwrap();
+    let entry = known.as_ref()?.get(path)?;
+    let (len, modified) = stat(path)?;
+    (len == entry.len && modified == entry.modified).then(|| entry.hash.clone())
+}
+
+/// Whether `path` is still as Hermes last wrote or read it, so it holds text.
+pub fn is_known(path: &Path) -> bool {
+    current_hash(path).is_some()
+}
+
+/// Whether `path` already holds `content`, as far as the remembered hash tells.
+pub fn is_unchanged(path: &Path, content: &str) -> bool {
+    current_hash(path).is_some_and(|hash| hash == content_hash(content.as_bytes()))
+}
@@ -0,0 +1,100 @@
+//! Finder tags (`_kMDItemUserTags`) on note files, mirrored into the index as Hermes tags.
+
+use std::path::Path;
+
+use crate::index::sql_escape;
+
+#[cfg(target_os = "macos")]
+const USER_TAGS_ATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+/// Source value for `note_tags` rows that mirror Finder tags.
+pub const FINDER_TAG_SOURCE: &str = "finder";
+
+/// Finder stores tags as `"Name\n<color index>"`; Hermes only cares about the name.
+#[cfg(target_os = "macos")]
+fn tag_name(raw: &str) -> &str {
+    raw.split('\n').next().unwrap_or(raw).trim()
+}
+
+#[cfg(target_os = "macos")]
+fn read_raw_tags(path: &Path) -> Result<Vec<String>, String> {
+    let value = crate::xattr::read_plist(path, USER_TAGS_ATTR)?;
+    Ok(value
+        .and_then(|value| value.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| item.as_str().map(str::to_string))
+        .collect())
+}
+
+/// Returns the Finder tag names set on `path` (empty on other platforms).
+pub fn read_tags(path: &Path) -> Result<Vec<String>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        return Ok(read_raw_tags(path)?
+            .iter()
+            .map(|raw| tag_name(raw).to_string())
+            .filter(|name| !name.is_empty())
+            .collect());
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = path;
+        Ok(Vec::new())
+    }
+}
+
+/// Replaces the Finder tags on `path`, keeping the color of tags that were already set.
+pub fn write_tags(path: &Path, tags: &[String]) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let existing = read_raw_tags(path)?;
+        let mut raw_tags: Vec<String> = Vec::new();
+        for tag in tags {
+            let name = tag.trim();
+            if name.is_empty() || raw_tags.iter().any(|raw| tag_name(raw) == name) {
+                continue;
+            }
+            let raw = existing
+                .iter()
+                .find(|raw| tag_name(raw) == name)
+                .cloned()
+                .unwrap_or_else(|| name.to_string());
+            raw_tags.push(raw);
+        }
+
+        if raw_tags.is_empty() {
+            return crate::xattr::remove(path, USER_TAGS_ATTR);
+        }
+        return crate::xattr::write_plist(path, USER_TAGS_ATTR, &serde_json::json!(raw_tags));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (path, tags);
+        Err("Finder tags are only available on macOS.".to_string())
+    }
+}
+
+pub const NOTE_TAGS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS note_tags (\n\
+       tab_key TEXT NOT NULL,\n\
+       tag TEXT NOT NULL,\n\
+       source TEXT NOT NULL,\n\
+       PRIMARY KEY (tab_key, tag, source)\n\
+     );\n";
+
+/// SQL that replaces the Finder-sourced tag rows for one tab.
+pub fn index_script(tab: &str, tags: &[String]) -> String {
+    let escaped_tab = sql_escape(tab);
+    let mut script = format!(
+        "DELETE FROM note_tags WHERE tab_key = '{escaped_tab}' AND source = '{FINDER_TAG_SOURCE}';\n"
+    );
+    for tag in tags {
+        script.push_str(&format!(
+            "INSERT OR IGNORE INTO note_tags(tab_key, tag, source) VALUES ('{escaped_tab}', '{}', '{FINDER_TAG_SOURCE}');\n",
+            sql_escape(tag),
+        ));
+    }
+    script
+}
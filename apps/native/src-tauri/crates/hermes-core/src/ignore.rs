@@ -0,0 +1,208 @@
+//! `.hermesignore`: glob patterns for notes and project folders that must not
+//! be indexed, snapshotted or synced.
+//!
+//! The syntax follows `.gitignore`: one pattern per line, `#` comments, `*`,
+//! `?` and `**` wildcards, a trailing `/` to match only folders, a `/` inside
+//! the pattern to anchor it to the ignore file's folder, and `!` to re-include
+//! a path. Patterns apply below the folder holding the file; a workspace's
+//! file also covers its project folders.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hermes_dir;
+
+pub const IGNORE_FILE: &str = ".hermesignore";
+const GIT_IGNORE_FILE: &str = ".gitignore";
+
+struct Rule {
+    segments: Vec<String>,
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+fn parse(text: &str) -> Vec<Rule> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+            let anchored = line.contains('/');
+            let segments: Vec<String> = line
+                .trim_start_matches('/')
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_string)
+                .collect();
+            (!segments.is_empty()).then_some(Rule {
+                segments,
+                anchored,
+                dir_only,
+                negated,
+            })
+        })
+        .collect()
+}
+
+/// `*` and `?` wildcard match within one path segment.
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| glob(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && glob(rest, &text[1..]),
+        Some((ch, rest)) => text.first() == Some(ch) && glob(rest, &text[1..]),
+    }
+}
+
+fn glob_segment(pattern: &str, segment: &str) -> bool {
+    glob(&pattern.chars().collect::<Vec<_>>(), &segment.chars().collect::<Vec<_>>())
+}
+
+fn glob_path(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| glob_path(rest, &path[skip..])),
+        Some((first, rest)) => {
+            !path.is_empty() && glob_segment(first, path[0]) && glob_path(rest, &path[1..])
+        }
+    }
+}
+
+impl Rule {
+    fn matches(&self, path: &[&str], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_path(&self.segments, path)
+        } else {
+            path.last().is_some_and(|name| glob_segment(&self.segments[0], name))
+        }
+    }
+}
+
+/// Whether `rules` exclude `relative`, a folder when `is_dir`; like git, a
+/// path inside an excluded folder can't be re-included.
+fn excludes(rules: &[Rule], relative: &Path, is_dir: bool) -> bool {
+    let segments: Vec<&str> = relative.iter().filter_map(|part| part.to_str()).collect();
+    (1..=segments.len()).any(|depth| {
+        let is_dir = is_dir || depth < segments.len();
+        rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&segments[..depth], is_dir))
+            .is_some_and(|rule| !rule.negated)
+    })
+}
+
+fn excluded_by(folder: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(folder) else {
+        return false;
+    };
+    fs::read_to_string(folder.join(IGNORE_FILE)).is_ok_and(|text| excludes(&parse(&text), relative, false))
+}
+
+/// Whether the note at `file_path` is excluded by its workspace's or project's
+/// `.hermesignore`.
+pub fn is_ignored(file_path: &Path) -> bool {
+    let Some(folder) = file_path.parent() else {
+        return false;
+    };
+    if excluded_by(folder, file_path) {
+        return true;
+    }
+    // A project folder's parent is its workspace, which has Hermes metadata.
+    folder
+        .parent()
+        .filter(|parent| hermes_dir(&parent.to_string_lossy()).is_dir())
+        .is_some_and(|workspace| excluded_by(workspace, file_path))
+}
+
+/// The `.hermesignore` and `.gitignore` rules of one folder, for walking a tree.
+pub struct FolderRules {
+    folder: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl FolderRules {
+    /// Reads both ignore files of `folder`; `None` when it has no rules.
+    pub fn read(folder: &Path) -> Option<FolderRules> {
+        let text: Vec<String> = [GIT_IGNORE_FILE, IGNORE_FILE]
+            .iter()
+            .filter_map(|name| fs::read_to_string(folder.join(name)).ok())
+            .collect();
+        let rules = parse(&text.join("\n"));
+        (!rules.is_empty()).then(|| FolderRules {
+            folder: folder.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Whether these rules exclude the folder `dir`.
+    pub fn excludes_dir(&self, dir: &Path) -> bool {
+        dir.strip_prefix(&self.folder)
+            .is_ok_and(|relative| excludes(&self.rules, relative, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excluded(rules: &str, path: &str, is_dir: bool) -> bool {
+        excludes(&parse(rules), Path::new(path), is_dir)
+    }
+
+    #[test]
+    fn matches_like_gitignore() {
+        let rules = "# drafts\n*.tmp\n!keep.tmp\nprivate/\n/journal/*.md\n";
+        assert!(excluded(rules, "scratch.tmp", false));
+        assert!(excluded(rules, "project/scratch.tmp", false));
+        assert!(!excluded(rules, "keep.tmp", false));
+        assert!(excluded(rules, "private", true));
+        assert!(!excluded(rules, "private", false));
+        assert!(excluded(rules, "private/note.md", false));
+        assert!(excluded(rules, "journal/monday.md", false));
+        assert!(!excluded(rules, "project/journal/monday.md", false));
+        assert!(!excluded(rules, "sage.md", false));
+    }
+
+    #[test]
+    fn double_star_spans_folders() {
+        assert!(excluded("archive/**/old.md", "archive/2020/q1/old.md", false));
+        assert!(excluded("archive/**/old.md", "archive/old.md", false));
+        assert!(!excluded("archive/**/old.md", "notes/old.md", false));
+        assert!(excluded("sa?e.md", "sage.md", false));
+    }
+
+    #[test]
+    fn excluded_folders_cannot_be_reincluded() {
+        assert!(excluded("private/\n!private/open.md\n", "private/open.md", false));
+    }
+
+    #[test]
+    fn ignore_files_cover_their_folder_and_projects() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(hermes_dir(&root.to_string_lossy())).unwrap();
+        fs::create_dir_all(root.join("blog")).unwrap();
+        fs::write(root.join(IGNORE_FILE), "amber.md\n").unwrap();
+        fs::write(root.join("blog").join(IGNORE_FILE), "sky.md\n").unwrap();
+
+        assert!(is_ignored(&root.join("amber.md")));
+        assert!(is_ignored(&root.join("blog").join("amber.md")));
+        assert!(is_ignored(&root.join("blog").join("sky.md")));
+        assert!(!is_ignored(&root.join("sky.md")));
+        assert!(!is_ignored(&root.join("blog").join("sage.md")));
+
+        let rules = FolderRules::read(root).unwrap();
+        assert!(!rules.excludes_dir(&root.join("blog")));
+        assert!(FolderRules::read(&root.join("missing")).is_none());
+    }
+}
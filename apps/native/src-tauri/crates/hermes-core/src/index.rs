@@ -0,0 +1,177 @@
+//! The per-folder SQLite index, `.hermes/index.sqlite`.
+//!
+//! Indexes are read and written through the `sqlite3` command-line tool.
+//! `note_index` holds one row per tab with its title, body and counts, and
+//! `note_fts` searches it; `workspace::index_tabs` keeps them, with the
+//! tables for tags, tasks, versions and locks alongside.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+pub fn sql_escape(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+pub fn word_count(content: &str) -> usize {
+    content.split_whitespace().filter(|word| !word.is_empty()).count()
+}
+
+pub fn extract_title(content: &str) -> String {
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let without_heading = trimmed.trim_start_matches('#').trim();
+        if without_heading.is_empty() {
+            continue;
+        }
+        return without_heading.chars().take(120).collect();
+    }
+    String::new()
+}
+
+pub fn first_line(content: &str) -> String {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.chars().take(120).collect())
+        .unwrap_or_default()
+}
+
+pub fn run_sqlite_script(path: &Path, script: &str) -> Result<(), String> {
+    let output = Command::new("sqlite3")
+        .arg(path)
+        .arg(script)
+        .output()
+        .map_err(|err| format!("Failed to run sqlite3: {err}"))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(format!(
+        "sqlite3 error while updating {}: {}",
+        path.display(),
+        if stderr.is_empty() { "unknown error" } else { &stderr }
+    ))
+}
+
+/// Runs a read-only query and returns each row as a JSON object keyed by column name.
+pub fn run_sqlite_query(path: &Path, sql: &str) -> Result<Vec<serde_json::Value>, String> {
+    let output = Command::new("sqlite3")
+        .arg("-json")
+        .arg("-readonly")
+        .arg(path)
+        .arg(sql)
+        .output()
+        .map_err(|err| format!("Failed to run sqlite3: {err}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!(
+            "sqlite3 error while querying {}: {}",
+            path.display(),
+            if stderr.is_empty() { "unknown error" } else { &stderr }
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&stdout).map_err(|err| format!("Failed parsing sqlite3 output: {err}"))
+}
+
+/// Full-text index over `note_index` itself, so note bodies are stored once;
+/// triggers keep it in step with every change to `note_index`.
+pub const NOTE_FTS_SCHEMA: &str = "CREATE VIRTUAL TABLE IF NOT EXISTS note_fts USING fts5(\n\
+       tab_key UNINDEXED, title, body, content='note_index', content_rowid='rowid'\n\
+     );\n\
+     CREATE TRIGGER IF NOT EXISTS note_index_fts_insert AFTER INSERT ON note_index BEGIN\n\
+       INSERT INTO note_fts(rowid, tab_key, title, body) VALUES (new.rowid, new.tab_key, new.title, new.body);\n\
+     END;\n\
+     CREATE TRIGGER IF NOT EXISTS note_index_fts_delete AFTER DELETE ON note_index BEGIN\n\
+       INSERT INTO note_fts(note_fts, rowid, tab_key, title, body) VALUES ('delete', old.rowid, old.tab_key, old.title, old.body);\n\
+     END;\n\
+     CREATE TRIGGER IF NOT EXISTS note_index_fts_update AFTER UPDATE ON note_index BEGIN\n\
+       INSERT INTO note_fts(note_fts, rowid, tab_key, title, body) VALUES ('delete', old.rowid, old.tab_key, old.title, old.body);\n\
+       INSERT INTO note_fts(rowid, tab_key, title, body) VALUES (new.rowid, new.tab_key, new.title, new.body);\n\
+     END;\n";
+
+/// Per-note version vectors, which the app's sync keeps.
+pub const NOTE_VERSIONS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS note_versions (\n\
+       tab_key TEXT PRIMARY KEY,\n\
+       vector TEXT NOT NULL,\n\
+       content_hash TEXT NOT NULL\n\
+     );\n";
+
+/// Rebuilds `note_fts` from `note_index`. `note_index` has no integer primary
+/// key, so VACUUM may renumber its rows and this must follow it.
+pub const REBUILD_FTS: &str = "INSERT INTO note_fts(note_fts) VALUES('rebuild');\n";
+
+/// Indexes already checked for a schema from before the current one.
+static MIGRATED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// Brings an index from an earlier version up to date, once per index per run:
+/// adds `note_index.project_path` and replaces a `note_fts` that kept its own
+/// copy of every body.
+pub fn migrate_index(db_path: &Path) -> Result<(), String> {
+    if !db_path.exists() || !MIGRATED.lock().unwrap().get_or_insert_with(HashSet::new).insert(db_path.to_path_buf()) {
+        return Ok(());
+    }
+    let columns = run_sqlite_query(db_path, "SELECT name FROM pragma_table_info('note_index');")?;
+    let has_column = |name: &str| columns.iter().any(|row| row.get("name").and_then(|value| value.as_str()) == Some(name));
+    if !columns.is_empty() && !has_column("project_path") {
+        run_sqlite_script(db_path, "ALTER TABLE note_index ADD COLUMN project_path TEXT NOT NULL DEFAULT '';\n")?;
+    }
+
+    let rows = run_sqlite_query(db_path, "SELECT sql FROM sqlite_master WHERE name = 'note_fts';")?;
+    let outdated = rows
+        .first()
+        .and_then(|row| row.get("sql"))
+        .and_then(|sql| sql.as_str())
+        .is_some_and(|sql| !sql.contains("content="));
+    if !outdated {
+        return Ok(());
+    }
+    run_sqlite_script(
+        db_path,
+        &format!("BEGIN IMMEDIATE;\nDROP TABLE note_fts;\n{NOTE_FTS_SCHEMA}{REBUILD_FTS}COMMIT;\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_quotes_for_sql() {
+        assert_eq!(sql_escape("it's"), "it''s");
+        assert_eq!(sql_escape("plain"), "plain");
+    }
+
+    #[test]
+    fn counts_words() {
+        assert_eq!(word_count("  one two\n\tthree  "), 3);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn title_is_the_first_line_with_text() {
+        assert_eq!(extract_title("\n#\n## Plans for *May*\nbody"), "Plans for *May*");
+        assert_eq!(extract_title("no heading\nmore"), "no heading");
+        assert_eq!(extract_title("  \n"), "");
+        assert_eq!(extract_title(&format!("# {}", "é".repeat(200))).chars().count(), 120);
+    }
+
+    #[test]
+    fn first_line_keeps_hashes() {
+        assert_eq!(first_line("\n  # not a heading  \nrest"), "# not a heading");
+        assert_eq!(first_line(""), "");
+    }
+}
@@ -0,0 +1,76 @@
+//! SQLite tuning of the workspace index for big workspaces.
+//!
+//! The app keeps the tuning in its settings and sets it here at startup and
+//! whenever it changes; tools that can't read the settings use SQLite's
+//! defaults.
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::volume;
+
+const SYNCHRONOUS_LEVELS: &[&str] = &["off", "normal", "full", "extra"];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IndexTuning {
+    page_size: u32,
+    cache_size_kib: u32,
+    mmap_size_mib: u32,
+    /// `off`, `normal`, `full` or `extra`; empty keeps SQLite's default.
+    synchronous: String,
+}
+
+impl IndexTuning {
+    /// SQLite's own defaults.
+    const DEFAULT: IndexTuning = IndexTuning {
+        page_size: 4096,
+        cache_size_kib: 2000,
+        mmap_size_mib: 0,
+        synchronous: String::new(),
+    };
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.page_size.is_power_of_two() || !(512..=65536).contains(&self.page_size) {
+            return Err("The page size must be a power of two from 512 to 65536 bytes.".to_string());
+        }
+        if !self.synchronous.is_empty() && !SYNCHRONOUS_LEVELS.contains(&self.synchronous.as_str()) {
+            return Err(format!("Unknown synchronous level '{}'", self.synchronous));
+        }
+        Ok(())
+    }
+}
+
+impl Default for IndexTuning {
+    fn default() -> Self {
+        IndexTuning::DEFAULT
+    }
+}
+
+static TUNING: RwLock<IndexTuning> = RwLock::new(IndexTuning::DEFAULT);
+
+/// Uses `tuning` for the index scripts from now on.
+pub fn set(tuning: IndexTuning) {
+    *TUNING.write().unwrap() = tuning;
+}
+
+/// Connection pragmas opening every index script; the journal mode depends on the volume.
+pub fn pragmas(workspace_path: &str) -> String {
+    let tuning = TUNING.read().unwrap();
+    // The page size must come before anything that creates the database file.
+    let mut pragmas = format!(
+        "PRAGMA page_size={};\n\
+         PRAGMA journal_mode={};\n\
+         PRAGMA cache_size=-{};\n\
+         PRAGMA mmap_size={};\n",
+        tuning.page_size,
+        volume::journal_mode(workspace_path),
+        tuning.cache_size_kib,
+        u64::from(tuning.mmap_size_mib) * 1024 * 1024,
+    );
+    if !tuning.synchronous.is_empty() {
+        pragmas.push_str(&format!("PRAGMA synchronous={};\n", tuning.synchronous));
+    }
+    pragmas
+}
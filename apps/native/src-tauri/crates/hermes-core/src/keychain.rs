@@ -0,0 +1,120 @@
+//! Secrets kept in the platform's credential store rather than the settings
+//! file: the Keychain on macOS, Credential Manager on Windows and the Secret
+//! Service (through `secret-tool`) on Linux.
+
+const SERVICE: &str = "Hermes";
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use security_framework::passwords::{delete_generic_password, get_generic_password, set_generic_password};
+
+    use super::SERVICE;
+
+    pub fn store(account: &str, secret: &str) -> Result<(), String> {
+        set_generic_password(SERVICE, account, secret.as_bytes())
+            .map_err(|err| format!("Failed saving to the Keychain: {err}"))
+    }
+
+    pub fn load(account: &str) -> Option<String> {
+        String::from_utf8(get_generic_password(SERVICE, account).ok()?).ok()
+    }
+
+    pub fn delete(account: &str) {
+        let _ = delete_generic_password(SERVICE, account);
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::core::{HSTRING, PWSTR};
+    use windows::Win32::Security::Credentials::{
+        CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+    };
+
+    use super::SERVICE;
+
+    fn target(account: &str) -> HSTRING {
+        HSTRING::from(format!("{SERVICE}/{account}"))
+    }
+
+    pub fn store(account: &str, secret: &str) -> Result<(), String> {
+        let mut target_name: Vec<u16> = format!("{SERVICE}/{account}").encode_utf16().chain([0]).collect();
+        let mut blob = secret.as_bytes().to_vec();
+        let credential = CREDENTIALW {
+            Type: CRED_TYPE_GENERIC,
+            TargetName: PWSTR(target_name.as_mut_ptr()),
+            CredentialBlobSize: blob.len() as u32,
+            CredentialBlob: blob.as_mut_ptr(),
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            ..Default::default()
+        };
+        unsafe { CredWriteW(&credential, 0) }.map_err(|err| format!("Failed saving to Credential Manager: {err}"))
+    }
+
+    pub fn load(account: &str) -> Option<String> {
+        let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+        unsafe {
+            CredReadW(&target(account), CRED_TYPE_GENERIC, None, &mut credential).ok()?;
+            let blob = std::slice::from_raw_parts((*credential).CredentialBlob, (*credential).CredentialBlobSize as usize);
+            let secret = String::from_utf8(blob.to_vec()).ok();
+            CredFree(credential as *const _);
+            secret
+        }
+    }
+
+    pub fn delete(account: &str) {
+        let _ = unsafe { CredDeleteW(&target(account), CRED_TYPE_GENERIC, None) };
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use super::SERVICE;
+
+    pub fn store(account: &str, secret: &str) -> Result<(), String> {
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label", &format!("{SERVICE} {account}"), "service", SERVICE, "account", account])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Failed to run secret-tool: {err}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(secret.as_bytes())
+                .map_err(|err| format!("Failed sending secret to secret-tool: {err}"))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|err| format!("Failed to run secret-tool: {err}"))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        Err(format!(
+            "Failed saving to the keyring: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+
+    pub fn load(account: &str) -> Option<String> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "account", account])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+            .filter(|secret| !secret.is_empty())
+    }
+
+    pub fn delete(account: &str) {
+        let _ = Command::new("secret-tool")
+            .args(["clear", "service", SERVICE, "account", account])
+            .output();
+    }
+}
+
+pub use platform::{delete, load, store};
@@ -0,0 +1,114 @@
+//! The parts of Hermes that need no app around them: where a workspace keeps
+//! its notes and metadata, reading them and keeping the SQLite index, the
+//! chat file, the write lock, projects, ignore rules, path checks, text
+//! decoding, captures, search, exports and redaction.
+//!
+//! The Tauri app builds its commands on these, and `hermes-cli` and other
+//! shells can use them on the same workspaces without pulling in Tauri.
+//! Settings kept in the app's store are mirrored into statics here at startup,
+//! so tools that can't read the store get the defaults.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub mod attachments;
+pub mod capture;
+pub mod chat;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub mod diagrams;
+pub mod export;
+pub mod file_hashes;
+pub mod finder_tags;
+pub mod ignore;
+pub mod index;
+pub mod index_tuning;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub mod keychain;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub mod local_api;
+pub mod note_lock;
+pub mod path_safety;
+pub mod programs;
+pub mod projects;
+pub mod redact;
+pub mod search;
+pub mod tab_files;
+pub mod tasks;
+pub mod text_encoding;
+pub mod volume;
+pub mod workspace;
+pub mod workspace_lock;
+#[cfg(target_os = "macos")]
+pub mod xattr;
+
+pub const TAB_KEYS: [&str; 5] = ["coral", "amber", "sage", "sky", "lavender"];
+
+/// An io error from `action` ("reading", "writing", ...) on `path`, for
+/// callers that explain access errors their own way.
+#[derive(Debug)]
+pub struct IoError {
+    pub action: &'static str,
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl IoError {
+    pub fn new(action: &'static str, path: &Path, source: io::Error) -> IoError {
+        IoError { action, path: path.to_path_buf(), source }
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Failed {} {}: {}", self.action, self.path.display(), self.source)
+    }
+}
+
+impl From<IoError> for String {
+    fn from(err: IoError) -> String {
+        err.to_string()
+    }
+}
+
+/// `path` with symlinks and `..` resolved, or as given when it doesn't exist.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    let Ok(canonical) = fs::canonicalize(path) else {
+        return path.to_path_buf();
+    };
+    // Windows canonicalizes to `\\?\C:\...`, which nothing else in Hermes writes.
+    #[cfg(windows)]
+    if let Some(plain) = canonical.to_str().and_then(|text| text.strip_prefix(r"\\?\")) {
+        if !plain.starts_with("UNC\\") {
+            return PathBuf::from(plain);
+        }
+    }
+    canonical
+}
+
+/// The one spelling of a workspace root used for settings, recents, queues and
+/// indexes, so a workspace opened through a symlink is the same workspace.
+pub fn canonical_workspace(workspace_path: &str) -> String {
+    canonical_path(Path::new(workspace_path.trim())).to_string_lossy().to_string()
+}
+
+pub fn notes_dir(workspace_path: &str) -> PathBuf {
+    path_safety::long_path(Path::new(workspace_path))
+}
+
+pub fn hermes_dir(workspace_path: &str) -> PathBuf {
+    notes_dir(workspace_path).join(".hermes")
+}
+
+pub fn sqlite_path(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("index.sqlite")
+}
+
+pub fn validate_tab_key(tab_key: &str) -> Result<(), String> {
+    if TAB_KEYS.contains(&tab_key) {
+        Ok(())
+    } else {
+        Err(format!("Unknown tab '{tab_key}'"))
+    }
+}
@@ -0,0 +1,64 @@
+//! The client side of the app's local API, for `hermes-cli rpc`.
+//!
+//! The app serves JSON-RPC on `127.0.0.1`; calls carry the API token, which
+//! the app keeps in the platform's credential store.
+
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::keychain;
+
+pub const DEFAULT_PORT: u16 = 47_615;
+/// Credential-store account holding the API token.
+pub const KEYCHAIN_ACCOUNT: &str = "local-api";
+
+/// A JSON-RPC error reply, with the HTTP status it stands for when there is one.
+pub fn rpc_error(id: Value, code: i64, message: impl Into<String>, status: Option<u16>) -> Value {
+    let mut error = json!({ "code": code, "message": message.into() });
+    if let Some(status) = status {
+        error["data"] = json!({ "status": status });
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+/// Passes newline-delimited JSON-RPC from standard input to the running
+/// app's `/v1/rpc` and prints each reply on its own line, for `hermes-cli rpc`.
+/// The token is `HERMES_API_TOKEN`, else the one in the credential store.
+pub fn bridge_stdio(port: Option<u16>) -> Result<(), String> {
+    let token = std::env::var("HERMES_API_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .or_else(|| keychain::load(KEYCHAIN_ACCOUNT))
+        .ok_or_else(|| "Turn on the local API in Hermes settings first.".to_string())?;
+    let url = format!("http://127.0.0.1:{}/v1/rpc", port.unwrap_or(DEFAULT_PORT));
+    // reqwest is built without a bundled TLS provider; install ring's, as the app does.
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|err| format!("Failed creating HTTP client: {err}"))?;
+    let mut stdout = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.map_err(|err| format!("Failed reading standard input: {err}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let sent = client.post(&url).bearer_auth(&token).header("Content-Type", "application/json").body(line.clone()).send();
+        let reply = match sent.and_then(|response| response.error_for_status()).and_then(|response| response.text()) {
+            Ok(reply) => reply,
+            Err(err) => {
+                let id = serde_json::from_str::<Value>(&line).ok().and_then(|call| call.get("id").cloned()).unwrap_or(Value::Null);
+                rpc_error(id, -32001, format!("Hermes didn't answer; is it running with the local API on? {err}"), None).to_string()
+            }
+        };
+        if reply.trim() == "null" || reply.trim() == "[]" {
+            continue;
+        }
+        writeln!(stdout, "{}", reply.trim()).and_then(|_| stdout.flush()).map_err(|err| format!("Failed writing standard output: {err}"))?;
+    }
+    Ok(())
+}
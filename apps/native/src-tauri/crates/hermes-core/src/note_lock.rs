@@ -0,0 +1,54 @@
+//! How a password-protected note looks on disk and in the index.
+//!
+//! A locked note's file starts with `LOCKED_MARKER`, followed by its
+//! encrypted text; the app does the encrypting. The index keeps a row with
+//! `LOCKED_TITLE` and no text for it, and lists it in `note_locks`.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::index::sql_escape;
+
+pub const LOCKED_MARKER: &str = "<!-- hermes:locked -->";
+pub const LOCKED_TITLE: &str = "Locked note";
+
+pub const NOTE_LOCKS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS note_locks (\n\
+       tab_key TEXT PRIMARY KEY,\n\
+       locked_unix INTEGER NOT NULL\n\
+     );\n";
+
+pub fn is_locked(content: &str) -> bool {
+    content.starts_with(LOCKED_MARKER)
+}
+
+pub fn is_locked_file(path: &Path) -> bool {
+    let mut head = [0; LOCKED_MARKER.len()];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut head))
+        .is_ok_and(|_| head == LOCKED_MARKER.as_bytes())
+}
+
+/// Index rows for a tab: locked tabs keep a placeholder entry without text.
+pub fn index_script(tab: &str, file_name: &str, now_unix: i64) -> String {
+    let escaped_tab = sql_escape(tab);
+    let escaped_file_name = sql_escape(file_name);
+    format!(
+        "INSERT INTO note_index(tab_key, file_path, title, body, word_count, char_count, updated_unix)\n\
+         VALUES ('{escaped_tab}', '{escaped_file_name}', '{LOCKED_TITLE}', '', 0, 0, {now_unix})\n\
+         ON CONFLICT(tab_key) DO UPDATE SET\n\
+           file_path=excluded.file_path,\n\
+           title=excluded.title,\n\
+           body=excluded.body,\n\
+           word_count=0,\n\
+           char_count=0,\n\
+           updated_unix=excluded.updated_unix;\n\
+         DELETE FROM note_tasks WHERE tab_key = '{escaped_tab}';\n\
+         DELETE FROM note_tags WHERE tab_key = '{escaped_tab}';\n\
+         INSERT OR IGNORE INTO note_locks(tab_key, locked_unix) VALUES ('{escaped_tab}', {now_unix});\n",
+    )
+}
+
+pub fn unlocked_index_script(tab: &str) -> String {
+    format!("DELETE FROM note_locks WHERE tab_key = '{}';\n", sql_escape(tab))
+}
@@ -0,0 +1,111 @@
+//! Paths Windows would refuse or silently change.
+//!
+//! Ordinary Windows paths stop at 260 characters, so notes deep in a project
+//! tree failed to save. Workspace folders are opened through the `\\?\` form,
+//! which has no such limit; elsewhere paths are left as they are. Project
+//! folder names Windows can't hold, such as `CON` or a name ending in a dot,
+//! are refused when the folder is created, on every platform, since synced
+//! workspaces end up on Windows machines too.
+
+use std::path::{Path, PathBuf};
+
+/// Device names Windows reserves, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// `path` in the form Windows opens past 260 characters.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let text = path.to_string_lossy();
+    if text.starts_with(r"\\?\") || !path.is_absolute() {
+        return path.to_path_buf();
+    }
+    // The `\\?\` form skips Windows' own cleanup: no `.`, `..` or forward slashes.
+    let mut clean = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            other => clean.push(other),
+        }
+    }
+    let clean = clean.to_string_lossy().replace('/', "\\");
+    match clean.strip_prefix(r"\\") {
+        Some(share) => PathBuf::from(format!(r"\\?\UNC\{share}")),
+        None => PathBuf::from(format!(r"\\?\{clean}")),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Refuses a new folder name that Windows can't create or would rename.
+pub fn check_folder_name(name: &str) -> Result<(), String> {
+    check_name(name, "folder", "rename the project")
+}
+
+/// Refuses a file name that Windows can't create or would rename.
+pub fn check_file_name(name: &str) -> Result<(), String> {
+    check_name(name, "file", "choose another name")
+}
+
+fn check_name(name: &str, kind: &str, advice: &str) -> Result<(), String> {
+    if name.ends_with(['.', ' ']) {
+        return Err(format!("\"{name}\" can't end with a dot or space on Windows; {advice}."));
+    }
+    if name.chars().any(|c| c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')) {
+        return Err(format!("\"{name}\" contains characters Windows doesn't allow in {kind} names; {advice}."));
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        return Err(format!("\"{name}\" is a reserved name on Windows; {advice}."));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_names_windows_cannot_hold() {
+        assert!(check_folder_name("Essays").is_ok());
+        assert!(check_folder_name("v1.2 drafts").is_ok());
+        assert!(check_folder_name("CON").is_err());
+        assert!(check_folder_name("lpt9").is_err());
+        assert!(check_file_name("nul.txt").is_err());
+        assert!(check_folder_name("notes.").is_err());
+        assert!(check_folder_name("notes ").is_err());
+        assert!(check_folder_name("a:b").is_err());
+        assert!(check_file_name("what?.md").is_err());
+        assert!(check_file_name("tab\tname").is_err());
+    }
+
+    #[test]
+    fn reserved_names_only_match_the_whole_stem() {
+        assert!(check_folder_name("CONTENT").is_ok());
+        assert!(check_file_name("console.md").is_ok());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_uses_the_verbatim_form() {
+        assert_eq!(long_path(Path::new(r"C:\notes\.\a\..\b")), PathBuf::from(r"\\?\C:\notes\b"));
+        assert_eq!(long_path(Path::new(r"\\server\share\notes")), PathBuf::from(r"\\?\UNC\server\share\notes"));
+        assert_eq!(long_path(Path::new("relative")), PathBuf::from("relative"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn long_path_leaves_other_platforms_alone() {
+        assert_eq!(long_path(Path::new("/notes/a/../b")), PathBuf::from("/notes/a/../b"));
+    }
+}
@@ -0,0 +1,23 @@
+//! Finding the command-line tools Hermes runs, such as `mmdc` and `whisper`.
+
+use std::path::PathBuf;
+
+#[cfg(target_os = "macos")]
+const EXTRA_DIRS: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin"];
+#[cfg(not(target_os = "macos"))]
+const EXTRA_DIRS: &[&str] = &[];
+
+/// The program called `name` in `folder`, else the first found on `PATH` or,
+/// on macOS, in Homebrew's folders, which apps started from the Dock don't have on `PATH`.
+pub fn find_program(folder: Option<PathBuf>, name: &str) -> Option<PathBuf> {
+    let file = if cfg!(windows) { format!("{name}.exe") } else { name.to_string() };
+    let on_path: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    folder
+        .into_iter()
+        .chain(on_path)
+        .chain(EXTRA_DIRS.iter().map(PathBuf::from))
+        .map(|dir| dir.join(&file))
+        .find(|path| path.is_file())
+}
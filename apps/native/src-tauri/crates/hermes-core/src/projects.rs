@@ -0,0 +1,161 @@
+//! Projects below a workspace root.
+//!
+//! A project is named by its path below the workspace root, `/`-separated:
+//! every folder of the root is a project, and a folder inside a project is a
+//! sub-project, such as `clients/acme/website`, once it holds notes or Hermes
+//! metadata; other folders in a project are its assets. Roots are registered
+//! as they are opened, so an index can record which project its folder is and
+//! a project follows its workspace's tab file names.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::workspace::note_path;
+use crate::{attachments, canonical_path, hermes_dir, IoError, TAB_KEYS};
+
+/// How deep below the workspace root a project may sit.
+pub const MAX_PROJECT_DEPTH: usize = 8;
+/// Dependency and build folders that never hold notes.
+pub const SKIPPED_FOLDERS: &[&str] = &["node_modules", "target", "vendor", "dist", "build", "__pycache__", "venv"];
+
+/// Workspace roots listed or opened this run.
+static ROOTS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Records `workspace_path` as a workspace root.
+pub fn register_root(workspace_path: &str) {
+    ROOTS.lock().unwrap().insert(canonical_path(Path::new(workspace_path)));
+}
+
+/// The deepest registered root holding `folder`, which may be `folder` itself.
+pub fn root_of(folder: &Path) -> Option<PathBuf> {
+    let folder = canonical_path(folder);
+    let roots = ROOTS.lock().unwrap();
+    roots.iter().filter(|root| folder.starts_with(root)).max_by_key(|root| root.components().count()).cloned()
+}
+
+/// The project `folder` is, below the deepest registered root holding it;
+/// empty for a root, `None` when no registered root holds it.
+pub fn project_of(folder: &str) -> Option<String> {
+    let folder = canonical_path(Path::new(folder));
+    let root = root_of(&folder)?;
+    let relative = folder.strip_prefix(&root).ok()?;
+    Some(relative.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+}
+
+/// Refuses a project path that isn't a plain relative path of visible folders.
+pub fn check_project(project: &str) -> Result<(), String> {
+    let parts: Vec<&str> = project.split(['/', '\\']).collect();
+    let plain = Path::new(project).components().all(|part| matches!(part, Component::Normal(_)));
+    if project.is_empty() || !plain || parts.iter().any(|part| part.is_empty() || part.starts_with('.')) {
+        return Err(format!("Invalid project name '{project}'"));
+    }
+    if parts.len() > MAX_PROJECT_DEPTH {
+        return Err(format!("'{project}' is nested more than {MAX_PROJECT_DEPTH} folders deep."));
+    }
+    Ok(())
+}
+
+/// Whether `project` is `scope` or one of its sub-projects; every project is in the empty scope.
+pub fn in_scope(project: &str, scope: &str) -> bool {
+    scope.is_empty() || project == scope || project.strip_prefix(scope).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Whether a folder inside a project is a sub-project rather than assets.
+pub fn is_subproject(dir: &Path) -> bool {
+    let folder = dir.to_string_lossy();
+    hermes_dir(&folder).is_dir() || TAB_KEYS.iter().any(|tab| note_path(&folder, tab).exists())
+}
+
+/// Adds the sub-projects below the project `dir`, named `relative`, to
+/// `names`, and every folder walked to `walked`.
+pub fn subprojects(dir: &Path, relative: &str, depth: usize, names: &mut Vec<String>, walked: &mut Vec<PathBuf>) {
+    walked.push(dir.to_path_buf());
+    if depth >= MAX_PROJECT_DEPTH {
+        return;
+    }
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        // Symlinked folders are skipped so a link cycle can't recurse forever.
+        if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || SKIPPED_FOLDERS.contains(&name.as_str()) {
+            continue;
+        }
+        let path = entry.path();
+        let child = format!("{relative}/{name}");
+        if is_subproject(&path) {
+            names.push(child.clone());
+        }
+        subprojects(&path, &child, depth + 1, names, walked);
+    }
+}
+
+/// Project paths of `workspace_path`, sorted, with the folders walked to find them.
+pub fn walk_projects(workspace_path: &str) -> Result<(Vec<String>, Vec<PathBuf>), IoError> {
+    let root = Path::new(workspace_path);
+    if !root.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let entries = fs::read_dir(root).map_err(|err| IoError::new("reading workspace directory", root, err))?;
+    let mut top = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|err| IoError::new("reading entry in", root, err))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Skip hidden directories like .hermes, and the attachments folder
+        if entry.path().is_dir() && !name.starts_with('.') && !attachments::is_top_folder(&name) {
+            top.push(name);
+        }
+    }
+
+    let mut walked = vec![root.to_path_buf()];
+    let mut names = Vec::new();
+    for name in top {
+        subprojects(&root.join(&name), &name, 1, &mut names, &mut walked);
+        names.push(name);
+    }
+    names.sort();
+    Ok((names, walked))
+}
+
+/// Paths of the projects and sub-projects below `workspace_path`, sorted.
+pub fn read_project_names(workspace_path: &str) -> Result<Vec<String>, IoError> {
+    walk_projects(workspace_path).map(|(names, _)| names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_paths_must_be_plain_and_shallow() {
+        assert!(check_project("clients/acme").is_ok());
+        assert!(check_project("").is_err());
+        assert!(check_project("../outside").is_err());
+        assert!(check_project("clients//acme").is_err());
+        assert!(check_project(".hermes").is_err());
+        assert!(check_project(&["a"; MAX_PROJECT_DEPTH + 1].join("/")).is_err());
+    }
+
+    #[test]
+    fn scope_holds_its_subprojects() {
+        assert!(in_scope("clients/acme", ""));
+        assert!(in_scope("clients/acme", "clients"));
+        assert!(in_scope("clients", "clients"));
+        assert!(!in_scope("clientside", "clients"));
+    }
+
+    #[test]
+    fn walk_finds_subprojects_and_skips_assets() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(root.join("clients/acme/.hermes")).unwrap();
+        fs::create_dir_all(root.join("clients/images")).unwrap();
+        fs::create_dir_all(root.join("blog/node_modules/.hermes")).unwrap();
+        fs::create_dir_all(root.join(".hermes")).unwrap();
+        let names = read_project_names(&root.to_string_lossy()).unwrap();
+        assert_eq!(names, ["blog", "clients", "clients/acme"]);
+    }
+}
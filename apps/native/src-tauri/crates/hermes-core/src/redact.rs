@@ -0,0 +1,79 @@
+//! Redaction applied to a note before any exporter renders it.
+//!
+//! Text between `<!--private-->` and `<!--/private-->` is removed, and a note
+//! tagged `#private` (inline or as a Finder tag) is withheld entirely. An
+//! unterminated private span runs to the end of the note.
+
+use serde::Deserialize;
+
+const PRIVATE_TAG: &str = "private";
+const CLOSE_MARKER: &str = "/private";
+const PLACEHOLDER: &str = "[redacted]";
+const PRIVATE_NOTE_PLACEHOLDER: &str = "*This note is private.*\n";
+
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Redaction {
+    /// Remove private content without a trace.
+    Strip,
+    /// Replace private content with a visible marker.
+    #[default]
+    Placeholder,
+    Off,
+}
+
+/// Finds the next `<!-- name -->` comment at or after `from`, as a byte range.
+fn find_marker(content: &str, from: usize, name: &str) -> Option<(usize, usize)> {
+    let mut search = from;
+    while let Some(offset) = content[search..].find("<!--") {
+        let start = search + offset;
+        let inner_start = start + 4;
+        let inner_len = content[inner_start..].find("-->")?;
+        let end = inner_start + inner_len + 3;
+        if content[inner_start..inner_start + inner_len].trim().eq_ignore_ascii_case(name) {
+            return Some((start, end));
+        }
+        search = end;
+    }
+    None
+}
+
+fn has_private_hashtag(content: &str) -> bool {
+    content.split_whitespace().any(|word| {
+        word.strip_prefix('#')
+            .map(|tag| tag.trim_end_matches(|ch: char| ch.is_ascii_punctuation()))
+            .is_some_and(|tag| tag.eq_ignore_ascii_case(PRIVATE_TAG))
+    })
+}
+
+pub fn is_private_note(content: &str, tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag.eq_ignore_ascii_case(PRIVATE_TAG)) || has_private_hashtag(content)
+}
+
+/// The note as it may leave Hermes under `mode`.
+pub fn redact(content: &str, tags: &[String], mode: Redaction) -> String {
+    if mode == Redaction::Off {
+        return content.to_string();
+    }
+    if is_private_note(content, tags) {
+        return match mode {
+            Redaction::Placeholder => PRIVATE_NOTE_PLACEHOLDER.to_string(),
+            _ => String::new(),
+        };
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut position = 0;
+    while let Some((open_start, open_end)) = find_marker(content, position, PRIVATE_TAG) {
+        output.push_str(&content[position..open_start]);
+        if mode == Redaction::Placeholder {
+            output.push_str(PLACEHOLDER);
+        }
+        match find_marker(content, open_end, CLOSE_MARKER) {
+            Some((_, close_end)) => position = close_end,
+            None => return output,
+        }
+    }
+    output.push_str(&content[position..]);
+    output
+}
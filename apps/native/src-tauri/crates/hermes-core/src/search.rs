@@ -0,0 +1,112 @@
+//! Full-text search over the notes of a workspace or one project's subtree.
+//!
+//! Every folder has its own index, so a search queries the index of each
+//! folder in scope and ranks the hits of all of them together by `bm25`.
+//! Hits name their project by the `project_path` its index stored, falling
+//! back to the folder's path for rows indexed before projects were recorded.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::index::{run_sqlite_query, sql_escape};
+use crate::{projects, sqlite_path};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    /// Path of the project below the workspace root; empty for the root.
+    pub project: String,
+    pub tab: String,
+    pub title: String,
+    /// The matching text, with matches between `[` and `]`.
+    pub snippet: String,
+    #[serde(skip)]
+    rank: f64,
+}
+
+/// `query` as an FTS5 expression matching notes that contain every word of it.
+fn match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn search_folder(folder: &str, project: &str, expression: &str, limit: usize) -> Vec<SearchHit> {
+    let db_path = sqlite_path(folder);
+    if !db_path.exists() {
+        return Vec::new();
+    }
+    let sql = format!(
+        "SELECT n.tab_key, n.title, n.project_path, snippet(note_fts, 2, '[', ']', '…', 12) AS snippet, bm25(note_fts) AS rank\n\
+         FROM note_fts JOIN note_index n ON n.rowid = note_fts.rowid\n\
+         WHERE note_fts MATCH '{}' ORDER BY rank LIMIT {limit};",
+        sql_escape(expression)
+    );
+    let rows = match run_sqlite_query(&db_path, &sql) {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Vec::new();
+        }
+    };
+    rows.into_iter()
+        .filter_map(|row| {
+            let text = |column: &str| row.get(column).and_then(|value| value.as_str()).unwrap_or_default().to_string();
+            let stored = text("project_path");
+            Some(SearchHit {
+                project: if stored.is_empty() { project.to_string() } else { stored },
+                tab: row.get("tab_key")?.as_str()?.to_string(),
+                title: text("title"),
+                snippet: text("snippet"),
+                rank: row.get("rank").and_then(|value| value.as_f64()).unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+/// Notes containing every word of `query`, best matches first, in the
+/// `projects` of `workspace_path`: all of them, or the one at `scope` and its
+/// sub-projects.
+pub fn search_projects(
+    workspace_path: &str,
+    projects: Vec<String>,
+    query: &str,
+    scope: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let scope = scope.unwrap_or_default();
+    if !scope.is_empty() {
+        projects::check_project(&scope)?;
+    }
+    let expression = match_expression(query);
+    if expression.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut folders = Vec::new();
+    if scope.is_empty() {
+        folders.push(String::new());
+    }
+    folders.extend(projects.into_iter().filter(|project| projects::in_scope(project, &scope)));
+
+    let mut hits: Vec<SearchHit> = folders
+        .iter()
+        .flat_map(|project| {
+            let folder = match project.as_str() {
+                "" => workspace_path.to_string(),
+                project => Path::new(workspace_path).join(project).to_string_lossy().to_string(),
+            };
+            search_folder(&folder, project, &expression, limit)
+        })
+        .collect();
+    hits.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+    hits.truncate(limit);
+    Ok(hits)
+}
@@ -0,0 +1,154 @@
+//! File names of the tab notes.
+//!
+//! A tab's note is `<tab>.md` unless the workspace's `.hermes/tabs.json` maps
+//! the tab to a name of its own, such as `coral` to `inbox` for tools that
+//! expect an inbox file. Projects and sub-projects follow the map of the
+//! registered workspace they are in.
+//!
+//! A note may also be `.markdown` or `.txt`, or have any extension in the
+//! config's list: the first one a tab's file exists with is its note, which
+//! keeps that extension when saved, and new notes get the first in the list.
+//! `.txt` notes are indexed as plain text.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hermes_dir, notes_dir, projects, TAB_KEYS};
+
+pub const CONFIG_FILE: &str = "tabs.json";
+pub const DEFAULT_EXTENSIONS: [&str; 3] = ["md", "markdown", "txt"];
+const PLAIN_TEXT_EXTENSIONS: [&str; 1] = ["txt"];
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabConfig {
+    /// File name without its extension by tab; tabs left out use their key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub filenames: BTreeMap<String, String>,
+    /// Note extensions without the dot, in order of preference; empty for the defaults.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+}
+
+impl TabConfig {
+    pub fn stem<'a>(&'a self, tab: &'a str) -> &'a str {
+        self.filenames.get(tab).map_or(tab, String::as_str)
+    }
+
+    pub fn extensions(&self) -> Vec<&str> {
+        match self.extensions.is_empty() {
+            true => DEFAULT_EXTENSIONS.to_vec(),
+            false => self.extensions.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+struct CachedConfig {
+    read_at: Option<SystemTime>,
+    config: TabConfig,
+}
+
+/// The map read from each config file, with the file time it was read at.
+static CACHE: Mutex<BTreeMap<PathBuf, CachedConfig>> = Mutex::new(BTreeMap::new());
+
+/// The config of the workspace holding `folder`, or of `folder` when no registered workspace does.
+fn config_path(folder: &str) -> PathBuf {
+    let root = projects::root_of(Path::new(folder))
+        .map(|root| root.to_string_lossy().to_string())
+        .unwrap_or_else(|| folder.to_string());
+    hermes_dir(&root).join(CONFIG_FILE)
+}
+
+pub fn read_config(path: &Path) -> TabConfig {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            tracing::warn!("Failed parsing {}: {err}", path.display());
+            TabConfig::default()
+        }),
+        Err(err) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed reading {}: {err}", path.display());
+            }
+            TabConfig::default()
+        }
+    }
+}
+
+/// The config that applies to `folder`.
+pub fn config(folder: &str) -> TabConfig {
+    let path = config_path(folder);
+    let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+    let mut cache = CACHE.lock().unwrap();
+    if let Some(cached) = cache.get(&path) {
+        if cached.read_at == modified {
+            return cached.config.clone();
+        }
+    }
+    let config = match modified {
+        Some(_) => read_config(&path),
+        None => TabConfig::default(),
+    };
+    cache.insert(
+        path,
+        CachedConfig {
+            read_at: modified,
+            config: config.clone(),
+        },
+    );
+    config
+}
+
+/// The file name of `tab`'s note in `folder`, without its extension.
+pub fn stem(folder: &str, tab: &str) -> String {
+    config(folder).stem(tab).to_string()
+}
+
+/// The file name of `tab`'s note in `folder`: the first note extension it
+/// exists with, or the preferred one for a new note.
+pub fn file_name(folder: &str, tab: &str) -> String {
+    let config = config(folder);
+    let stem = config.stem(tab);
+    let extensions = config.extensions();
+    let dir = notes_dir(folder);
+    let extension = extensions
+        .iter()
+        .find(|extension| dir.join(format!("{stem}.{extension}")).exists())
+        .unwrap_or(&extensions[0]);
+    format!("{stem}.{extension}")
+}
+
+/// `name` without its extension, when that is a note extension of `folder`.
+pub fn note_stem<'a>(folder: &str, name: &'a str) -> Option<&'a str> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    config(folder).extensions().contains(&extension).then_some(stem)
+}
+
+/// The tab whose note in `folder` is named `name`, if any.
+pub fn tab_of(folder: &str, name: &str) -> Option<&'static str> {
+    let stem = note_stem(folder, name)?;
+    let config = config(folder);
+    let tab = TAB_KEYS.iter().copied().find(|tab| config.stem(tab) == stem)?;
+    (file_name(folder, tab) == name).then_some(tab)
+}
+
+/// Whether the note at `path` is indexed as plain text rather than markdown.
+pub fn is_plain_text(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| PLAIN_TEXT_EXTENSIONS.iter().any(|plain| plain.eq_ignore_ascii_case(extension)))
+}
+
+pub fn write_config(path: &Path, config: &TabConfig) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|err| format!("Failed encoding tab file names: {err}"))?;
+    fs::write(path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+    CACHE.lock().unwrap().remove(path);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! Tasks index: open `- [ ]` items with a due date, kept in `note_tasks`.
+//!
+//! A due date is written as `due:2026-10-15` or `📅 2026-10-15` anywhere on the
+//! task line. Undated tasks are not indexed since nothing is reported for them.
+
+use chrono::NaiveDate;
+
+use crate::index::sql_escape;
+
+pub const NOTE_TASKS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS note_tasks (\n\
+       tab_key TEXT NOT NULL,\n\
+       line INTEGER NOT NULL,\n\
+       text TEXT NOT NULL,\n\
+       due TEXT NOT NULL,\n\
+       PRIMARY KEY (tab_key, line)\n\
+     );\n\
+     CREATE INDEX IF NOT EXISTS idx_note_tasks_due ON note_tasks(due);\n";
+
+struct Task {
+    line: usize,
+    text: String,
+    due: NaiveDate,
+}
+
+fn parse_due(text: &str) -> Option<NaiveDate> {
+    ["due:", "📅"].iter().find_map(|marker| {
+        let (_, rest) = text.split_once(marker)?;
+        let date = rest.trim_start().get(..10)?;
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    })
+}
+
+fn open_tasks(content: &str) -> Vec<Task> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let text = line.trim_start().strip_prefix("- [ ]")?.trim();
+            Some(Task {
+                line: index + 1,
+                text: text.to_string(),
+                due: parse_due(text)?,
+            })
+        })
+        .collect()
+}
+
+/// SQL that replaces the task rows for one tab.
+pub fn index_script(tab: &str, content: &str) -> String {
+    let escaped_tab = sql_escape(tab);
+    let mut script = format!("DELETE FROM note_tasks WHERE tab_key = '{escaped_tab}';\n");
+    for task in open_tasks(content) {
+        script.push_str(&format!(
+            "INSERT INTO note_tasks(tab_key, line, text, due) VALUES ('{escaped_tab}', {}, '{}', '{}');\n",
+            task.line,
+            sql_escape(&task.text),
+            task.due.format("%Y-%m-%d"),
+        ));
+    }
+    script
+}
@@ -0,0 +1,132 @@
+//! Decoding note files that weren't saved as UTF-8 by Hermes.
+//!
+//! Files dropped into a workspace by other tools may be UTF-16 with a byte
+//! order mark or a legacy single-byte encoding; those are transcoded and
+//! written back as UTF-8 on the next save. Files that look binary are not
+//! notes at all: they are skipped on load and never overwritten or deleted.
+
+use std::fs;
+use std::path::Path;
+
+use encoding_rs::{Encoding, WINDOWS_1252};
+
+/// Control characters other than tab, newline, form feed and carriage return
+/// allowed per 1000 bytes before a file counts as binary.
+const MAX_CONTROL_PER_MILLE: usize = 10;
+
+pub enum Decoded {
+    Text(String),
+    /// Decoded from the named encoding rather than UTF-8.
+    Transcoded(String, &'static str),
+    Binary,
+}
+
+fn looks_binary(bytes: &[u8]) -> bool {
+    let control = bytes
+        .iter()
+        .filter(|&&byte| byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\x0c' | b'\r'))
+        .count();
+    bytes.contains(&0) || control * 1000 > bytes.len() * MAX_CONTROL_PER_MILLE
+}
+
+pub fn decode(bytes: Vec<u8>) -> Decoded {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(&bytes) {
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return match (had_errors, encoding == encoding_rs::UTF_8) {
+            (true, _) => Decoded::Binary,
+            (false, true) => Decoded::Text(text.into_owned()),
+            (false, false) => Decoded::Transcoded(text.into_owned(), encoding.name()),
+        };
+    }
+    if looks_binary(&bytes) {
+        return Decoded::Binary;
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => Decoded::Text(text),
+        // Latin-1 and Windows editors' default; every byte maps to a character.
+        Err(err) => {
+            let (text, _) = WINDOWS_1252.decode_without_bom_handling(err.as_bytes());
+            Decoded::Transcoded(text.into_owned(), WINDOWS_1252.name())
+        }
+    }
+}
+
+/// Whether the file at `path` exists and isn't text Hermes can edit.
+pub fn is_binary_file(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|bytes| matches!(decode(bytes), Decoded::Binary))
+}
+
+/// Whether the file at `path` is UTF-8 text without a byte order mark, which
+/// can be read piecewise as it is on disk.
+pub fn is_plain_utf8_file(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|bytes| {
+        Encoding::for_bom(&bytes).is_none() && !looks_binary(&bytes) && std::str::from_utf8(&bytes).is_ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(decoded: Decoded) -> (String, Option<&'static str>) {
+        match decoded {
+            Decoded::Text(text) => (text, None),
+            Decoded::Transcoded(text, encoding) => (text, Some(encoding)),
+            Decoded::Binary => panic!("decoded as binary"),
+        }
+    }
+
+    #[test]
+    fn utf8_passes_through() {
+        assert_eq!(text(decode("café\r\n".as_bytes().to_vec())), ("café\r\n".to_string(), None));
+        assert_eq!(text(decode(b"\xEF\xBB\xBFhi".to_vec())), ("hi".to_string(), None));
+    }
+
+    #[test]
+    fn utf16_round_trips() {
+        let note = "Grüße — 日本語\n";
+        let mut little: Vec<u8> = vec![0xFF, 0xFE];
+        little.extend(note.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(text(decode(little)), (note.to_string(), Some("UTF-16LE")));
+
+        let mut big: Vec<u8> = vec![0xFE, 0xFF];
+        big.extend(note.encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(text(decode(big)), (note.to_string(), Some("UTF-16BE")));
+    }
+
+    #[test]
+    fn legacy_bytes_decode_as_windows_1252() {
+        let (encoded, _, _) = WINDOWS_1252.encode("café – 5€");
+        assert_eq!(text(decode(encoded.into_owned())), ("café – 5€".to_string(), Some("windows-1252")));
+    }
+
+    #[test]
+    fn binary_files_are_not_notes() {
+        assert!(matches!(decode(b"PNG\0\x01\x02".to_vec()), Decoded::Binary));
+        assert!(matches!(decode(vec![0x01; 100]), Decoded::Binary));
+        assert!(matches!(decode(b"tabs\tand\x0cfeeds\n".to_vec()), Decoded::Text(_)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("sage.md");
+        fs::write(&image, b"\x89PNG\r\n\x1a\n\0\0").unwrap();
+        assert!(is_binary_file(&image));
+        fs::write(&image, "# Sage\n").unwrap();
+        assert!(!is_binary_file(&image));
+        assert!(!is_binary_file(&dir.path().join("missing.md")));
+    }
+
+    #[test]
+    fn only_bare_utf8_is_plain() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("sage.md");
+        fs::write(&note, "café\n").unwrap();
+        assert!(is_plain_utf8_file(&note));
+        fs::write(&note, b"\xEF\xBB\xBFcaf\xC3\xA9").unwrap();
+        assert!(!is_plain_utf8_file(&note));
+        fs::write(&note, b"caf\xE9").unwrap();
+        assert!(!is_plain_utf8_file(&note));
+        fs::write(&note, b"PNG\0").unwrap();
+        assert!(!is_plain_utf8_file(&note));
+        assert!(!is_plain_utf8_file(&dir.path().join("missing.md")));
+    }
+}
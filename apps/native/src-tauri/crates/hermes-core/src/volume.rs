@@ -0,0 +1,134 @@
+//! What kind of volume a workspace lives on.
+//!
+//! SQLite's write-ahead log relies on shared memory that network filesystems
+//! and cloud-synced folders don't provide faithfully, so the index falls back to
+//! a rollback journal there.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeKind {
+    Local,
+    /// SMB, NFS, AFP, WebDAV, FUSE mounts and the like.
+    Network,
+    /// A folder a sync client (iCloud Drive, OneDrive, Dropbox...) rewrites behind our back.
+    Cloud,
+}
+
+fn is_cloud_folder(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    ["/Library/Mobile Documents/", "/Library/CloudStorage/", "/Dropbox/", "\\Dropbox\\"]
+        .iter()
+        .any(|marker| path.contains(marker))
+        || std::env::var("OneDrive").is_ok_and(|one_drive| !one_drive.is_empty() && path.starts_with(&one_drive))
+}
+
+#[cfg(target_os = "macos")]
+fn is_network_volume(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    stat.f_flags & libc::MNT_LOCAL as u32 == 0
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_network_volume(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    // f_type magic numbers of filesystems served over the network.
+    const NETWORK_FILESYSTEMS: [u32; 8] = [
+        0x0000_6969, // NFS
+        0x0000_517b, // SMB
+        0xff53_4d42, // CIFS
+        0xfe53_4d42, // SMB2
+        0x6573_5546, // FUSE (sshfs, rclone, ...)
+        0x0102_1997, // 9P
+        0x5346_414f, // AFS
+        0x00c3_6400, // Ceph
+    ];
+
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    NETWORK_FILESYSTEMS.contains(&(stat.f_type as u32))
+}
+
+#[cfg(windows)]
+fn is_network_volume(path: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{GetDriveTypeW, GetVolumePathNameW};
+
+    const DRIVE_REMOTE: u32 = 4;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+    let mut root = [0u16; 261];
+    if unsafe { GetVolumePathNameW(PCWSTR(wide.as_ptr()), &mut root) }.is_err() {
+        return false;
+    }
+    unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) == DRIVE_REMOTE }
+}
+
+/// Bytes free for the current user on the volume holding `path`.
+#[cfg(unix)]
+pub fn free_bytes(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Bytes free for the current user on the volume holding `path`.
+#[cfg(windows)]
+pub fn free_bytes(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+    let mut free = 0u64;
+    unsafe { GetDiskFreeSpaceExW(PCWSTR(wide.as_ptr()), Some(&mut free), None, None) }.ok()?;
+    Some(free)
+}
+
+pub fn kind(workspace_path: &str) -> VolumeKind {
+    let path = Path::new(workspace_path);
+    if is_network_volume(path) {
+        VolumeKind::Network
+    } else if is_cloud_folder(path) {
+        VolumeKind::Cloud
+    } else {
+        VolumeKind::Local
+    }
+}
+
+/// Journal mode for the workspace index: WAL only on local disks.
+pub fn journal_mode(workspace_path: &str) -> &'static str {
+    match kind(workspace_path) {
+        VolumeKind::Local => "WAL",
+        VolumeKind::Network | VolumeKind::Cloud => "DELETE",
+    }
+}
@@ -0,0 +1,191 @@
+//! Reading a workspace folder's notes and keeping its index.
+//!
+//! The note files are the source of truth; the index is a cache for titles,
+//! search, tags, tasks and locks that every read of the folder refreshes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::index::{
+    extract_title, first_line, migrate_index, run_sqlite_script, sql_escape, word_count, NOTE_FTS_SCHEMA, NOTE_VERSIONS_SCHEMA,
+};
+use crate::{
+    file_hashes, finder_tags, hermes_dir, ignore, index_tuning, note_lock, notes_dir, projects, sqlite_path, tab_files, tasks,
+    text_encoding, IoError, TAB_KEYS,
+};
+
+/// The file of `tab`'s note in the folder `workspace_path`.
+pub fn note_path(workspace_path: &str, tab: &str) -> PathBuf {
+    notes_dir(workspace_path).join(tab_files::file_name(workspace_path, tab))
+}
+
+/// Refreshes the index rows of every tab from `pages`.
+pub fn sync_workspace_index(workspace_path: &str, pages: &HashMap<String, String>) -> Result<(), String> {
+    let tabs = TAB_KEYS
+        .iter()
+        .map(|tab| (*tab, pages.get(*tab).map(String::as_str).unwrap_or_default()));
+    index_tabs(workspace_path, tabs)
+}
+
+/// Refreshes the index rows of the given tabs; empty content drops a tab's row.
+#[tracing::instrument(skip_all, fields(workspace = workspace_path))]
+pub fn index_tabs<'a>(workspace_path: &str, tabs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<(), String> {
+    let hermes = hermes_dir(workspace_path);
+    fs::create_dir_all(&hermes)
+        .map_err(|err| format!("Failed creating Hermes metadata directory {}: {err}", hermes.display()))?;
+
+    let db_path = sqlite_path(workspace_path);
+    migrate_index(&db_path)?;
+    let notes_root = notes_dir(workspace_path);
+    let now_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+
+    // WAL is unsafe on network and cloud-synced volumes; the rest follows the user's tuning.
+    let mut script = index_tuning::pragmas(workspace_path);
+    script.push_str(
+        "CREATE TABLE IF NOT EXISTS note_index (\n\
+           tab_key TEXT PRIMARY KEY,\n\
+           file_path TEXT NOT NULL,\n\
+           title TEXT NOT NULL,\n\
+           body TEXT NOT NULL,\n\
+           word_count INTEGER NOT NULL,\n\
+           char_count INTEGER NOT NULL,\n\
+           updated_unix INTEGER NOT NULL,\n\
+           project_path TEXT NOT NULL DEFAULT ''\n\
+         );\n\
+         CREATE INDEX IF NOT EXISTS idx_note_index_updated ON note_index(updated_unix DESC);\n",
+    );
+    script.push_str(NOTE_FTS_SCHEMA);
+    script.push_str(finder_tags::NOTE_TAGS_SCHEMA);
+    script.push_str(tasks::NOTE_TASKS_SCHEMA);
+    script.push_str(NOTE_VERSIONS_SCHEMA);
+    script.push_str(note_lock::NOTE_LOCKS_SCHEMA);
+    script.push_str("BEGIN IMMEDIATE;\n");
+    // Which project the folder is, so queries can be scoped to a subtree; it
+    // stays as it was for a folder outside every workspace opened this run.
+    let project = projects::project_of(workspace_path);
+    let escaped_project = sql_escape(project.as_deref().unwrap_or_default());
+
+    for (tab, content) in tabs {
+        let file_name = tab_files::file_name(workspace_path, tab);
+        let file_path = notes_root.join(&file_name);
+        // Ignored notes are dropped from the index like deleted ones.
+        let ignored = ignore::is_ignored(&file_path);
+        if !ignored && note_lock::is_locked_file(&file_path) {
+            script.push_str(&note_lock::index_script(tab, &file_name, now_unix));
+            continue;
+        }
+        script.push_str(&note_lock::unlocked_index_script(tab));
+
+        let content = if ignored { "" } else { content };
+        if content.trim().is_empty() {
+            script.push_str(&format!(
+                "DELETE FROM note_index WHERE tab_key = '{}';\n\
+                 DELETE FROM note_tags WHERE tab_key = '{}';\n\
+                 DELETE FROM note_tasks WHERE tab_key = '{}';\n",
+                sql_escape(tab),
+                sql_escape(tab),
+                sql_escape(tab),
+            ));
+            continue;
+        }
+
+        // Plain text notes have no headings or task lists to read.
+        let plain_text = tab_files::is_plain_text(&file_path);
+        let title = if plain_text { first_line(content) } else { extract_title(content) };
+        let escaped_tab = sql_escape(tab);
+        let escaped_title = sql_escape(&title);
+        let escaped_body = sql_escape(content);
+        // Relative to the workspace, so the row holds however the folder is reached.
+        let escaped_file_path = sql_escape(&file_name);
+
+        script.push_str(&format!(
+            "INSERT INTO note_index(tab_key, file_path, title, body, word_count, char_count, updated_unix, project_path)\n\
+             VALUES ('{escaped_tab}', '{escaped_file_path}', '{escaped_title}', '{escaped_body}', {}, {}, {}, '{escaped_project}')\n\
+             ON CONFLICT(tab_key) DO UPDATE SET\n\
+               file_path=excluded.file_path,\n\
+               title=excluded.title,\n\
+               body=excluded.body,\n\
+               word_count=excluded.word_count,\n\
+               char_count=excluded.char_count,\n\
+               updated_unix=excluded.updated_unix;\n",
+            word_count(content),
+            content.chars().count(),
+            now_unix,
+        ));
+        script.push_str(&tasks::index_script(tab, if plain_text { "" } else { content }));
+
+        match finder_tags::read_tags(&file_path) {
+            Ok(tags) => script.push_str(&finder_tags::index_script(tab, &tags)),
+            Err(err) => tracing::warn!("{}", err),
+        }
+    }
+
+    if project.is_some() {
+        script.push_str(&format!(
+            "UPDATE note_index SET project_path = '{escaped_project}' WHERE project_path <> '{escaped_project}';\n"
+        ));
+    }
+    script.push_str("COMMIT;\n");
+    run_sqlite_script(&db_path, &script)
+}
+
+/// Reads every tab's file, leaving the index alone.
+pub fn read_workspace_files(workspace_path: &str) -> Result<HashMap<String, String>, IoError> {
+    let mut pages = HashMap::new();
+    let dir = notes_dir(workspace_path);
+
+    if dir.exists() {
+        for tab in TAB_KEYS {
+            let file_path = note_path(workspace_path, tab);
+            if !file_path.exists() {
+                continue;
+            }
+
+            let bytes = fs::read(&file_path).map_err(|err| IoError::new("reading", &file_path, err))?;
+            let content = match text_encoding::decode(bytes) {
+                text_encoding::Decoded::Text(content) => {
+                    file_hashes::remember(&file_path, &content);
+                    content
+                }
+                text_encoding::Decoded::Transcoded(content, encoding) => {
+                    tracing::warn!("Read {} as {encoding}; it is saved as UTF-8 from now on", file_path.display());
+                    content
+                }
+                text_encoding::Decoded::Binary => {
+                    tracing::warn!("Skipping {}: not a text file", file_path.display());
+                    continue;
+                }
+            };
+            pages.insert(tab.to_string(), content);
+        }
+    }
+    Ok(pages)
+}
+
+/// Reads every tab and refreshes the index right away; ungated so background
+/// captures and repairs can reindex.
+pub fn read_workspace_pages(workspace_path: &str) -> Result<HashMap<String, String>, IoError> {
+    let pages = read_workspace_files(workspace_path)?;
+
+    // Markdown files remain source of truth; index is best-effort metadata/search cache.
+    if let Err(err) = sync_workspace_index(workspace_path, &pages) {
+        tracing::warn!("{}", err);
+    }
+
+    Ok(pages)
+}
+
+/// `~/Documents/Hermes`, created if needed.
+pub fn default_workspace() -> Result<String, String> {
+    let home = std::env::var("HOME")
+        .map_err(|_| "Could not determine home directory".to_string())?;
+    let docs = Path::new(&home).join("Documents").join("Hermes");
+    fs::create_dir_all(&docs)
+        .map_err(|err| format!("Failed creating default workspace {}: {err}", docs.display()))?;
+    Ok(docs.to_string_lossy().to_string())
+}
@@ -0,0 +1,239 @@
+//! Advisory lock serializing writes to a workspace folder.
+//!
+//! Writers create `.hermes/write.lock` exclusively and remove it when done, so
+//! two windows, two Hermes processes or an external tool following the same
+//! protocol never interleave partial writes. A lock left behind by a crashed
+//! writer is taken over once it is older than any write should take; the
+//! holder keeps touching the file so a long write is never mistaken for one.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::hermes_dir;
+
+const LOCK_FILE: &str = "write.lock";
+const STALE_AFTER: Duration = Duration::from_secs(30);
+/// How often a held lock's file is touched, well within `STALE_AFTER`.
+const REFRESH_EVERY: Duration = Duration::from_secs(10);
+const WAIT_FOR: Duration = Duration::from_secs(10);
+const POLL_EVERY: Duration = Duration::from_millis(50);
+
+/// Holds the workspace write lock until dropped.
+pub struct WriteLock {
+    path: PathBuf,
+    token: String,
+    /// Dropping it stops the refresher.
+    stop: Option<Sender<()>>,
+    refresher: Option<JoinHandle<()>>,
+}
+
+impl Drop for WriteLock {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(refresher) = self.refresher.take() {
+            let _ = refresher.join();
+        }
+        // Leave the file alone if another writer took it over as stale.
+        if holds(&self.path, &self.token) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn holds(path: &Path, token: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content == token)
+}
+
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_AFTER)
+}
+
+/// Keeps the lock file's modification time fresh while the lock is held, so
+/// a long write isn't taken for a crashed one.
+fn refresh(path: PathBuf, token: String) -> (Sender<()>, JoinHandle<()>) {
+    let (stop, stopped) = mpsc::channel::<()>();
+    let refresher = thread::spawn(move || {
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(REFRESH_EVERY) {
+            if !holds(&path, &token) {
+                break;
+            }
+            let touched = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            if let Err(err) = touched {
+                tracing::warn!("Failed refreshing {}: {err}", path.display());
+            }
+        }
+    });
+    (stop, refresher)
+}
+
+/// Removes the stale lock at `path`, returning whether it is gone. It is
+/// moved aside first and only deleted if it is still the stale lock that was
+/// seen, so a writer that took it over in the meantime keeps its fresh one.
+fn take_over(path: &Path, nonce: u128) -> bool {
+    let Ok(seen) = fs::read_to_string(path) else {
+        return !path.exists();
+    };
+    let aside = path.with_extension(format!("stale-{}-{nonce}", std::process::id()));
+    if fs::rename(path, &aside).is_err() {
+        return !path.exists();
+    }
+    if fs::read_to_string(&aside).is_ok_and(|content| content == seen) && is_stale(&aside) {
+        tracing::info!("Taking over stale lock {}", path.display());
+        let _ = fs::remove_file(&aside);
+        return true;
+    }
+    restore(&aside, path);
+    false
+}
+
+/// Puts back a live lock that was moved aside at `aside`, unless a writer
+/// created a new lock at `path` in the meantime, which is left in place.
+fn restore(aside: &Path, path: &Path) {
+    let restored = fs::read(aside).and_then(|content| {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(&content))
+    });
+    match restored {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+            tracing::warn!("Not restoring lock {}: another writer took it meanwhile", path.display());
+        }
+        Err(err) => tracing::warn!("Failed restoring lock {}: {err}", path.display()),
+    }
+    let _ = fs::remove_file(aside);
+}
+
+/// Waits for and takes the write lock of `workspace_path`.
+pub fn acquire(workspace_path: &str) -> Result<WriteLock, String> {
+    let dir = hermes_dir(workspace_path);
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    let path = dir.join(LOCK_FILE);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    // Identifies the holder for anyone inspecting the file, and lets the guard
+    // recognise its own lock.
+    let token = format!(
+        "{{\"pid\":{},\"host\":{:?},\"acquired\":{nanos}}}",
+        std::process::id(),
+        gethostname::gethostname().to_string_lossy()
+    );
+
+    let deadline = SystemTime::now() + WAIT_FOR;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(token.as_bytes())
+                    .map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+                let (stop, refresher) = refresh(path.clone(), token.clone());
+                return Ok(WriteLock { path, token, stop: Some(stop), refresher: Some(refresher) });
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                if is_stale(&path) && take_over(&path, nanos) {
+                    continue;
+                }
+                if SystemTime::now() > deadline {
+                    return Err("The workspace is busy: another Hermes window or process is saving it.".to_string());
+                }
+                thread::sleep(POLL_EVERY);
+            }
+            Err(err) => return Err(format!("Failed creating {}: {err}", path.display())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_path(workspace: &Path) -> PathBuf {
+        hermes_dir(&workspace.to_string_lossy()).join(LOCK_FILE)
+    }
+
+    fn age(path: &Path, by: Duration) {
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - by).unwrap();
+    }
+
+    #[test]
+    fn acquire_writes_the_lock_and_drop_removes_it() {
+        let workspace = tempfile::tempdir().unwrap();
+        let lock = acquire(&workspace.path().to_string_lossy()).unwrap();
+        let path = lock_path(workspace.path());
+        assert!(fs::read_to_string(&path).unwrap().contains(&format!("\"pid\":{}", std::process::id())));
+        drop(lock);
+        assert!(!path.exists());
+        drop(acquire(&workspace.path().to_string_lossy()).unwrap());
+    }
+
+    #[test]
+    fn drop_leaves_a_lock_taken_over_by_another_writer() {
+        let workspace = tempfile::tempdir().unwrap();
+        let lock = acquire(&workspace.path().to_string_lossy()).unwrap();
+        let path = lock_path(workspace.path());
+        fs::write(&path, "someone else").unwrap();
+        drop(lock);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "someone else");
+    }
+
+    #[test]
+    fn stale_lock_is_taken_over() {
+        let workspace = tempfile::tempdir().unwrap();
+        let path = lock_path(workspace.path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "crashed writer").unwrap();
+        age(&path, STALE_AFTER * 2);
+        assert!(is_stale(&path));
+
+        let lock = acquire(&workspace.path().to_string_lossy()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), lock.token);
+    }
+
+    #[test]
+    fn take_over_keeps_a_fresh_lock() {
+        let workspace = tempfile::tempdir().unwrap();
+        let path = lock_path(workspace.path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "live writer").unwrap();
+        assert!(!is_stale(&path));
+
+        assert!(!take_over(&path, 1));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "live writer");
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn restore_leaves_a_lock_taken_meanwhile() {
+        let workspace = tempfile::tempdir().unwrap();
+        let path = lock_path(workspace.path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let aside = path.with_extension("stale-1-1");
+        fs::write(&aside, "moved aside").unwrap();
+        fs::write(&path, "new writer").unwrap();
+
+        restore(&aside, &path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new writer");
+        assert!(!aside.exists());
+
+        fs::write(&aside, "moved aside").unwrap();
+        fs::remove_file(&path).unwrap();
+        restore(&aside, &path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "moved aside");
+        assert!(!aside.exists());
+    }
+}
@@ -0,0 +1,150 @@
+//! Property-list extended attributes on macOS (`com.apple.metadata:*`).
+//!
+//! Spotlight and Finder expect these attributes to hold binary plists, so values
+//! are round-tripped through `plutil` and written with `xattr -x` (hex).
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn plist_value_xml(value: &serde_json::Value, out: &mut String) -> Result<(), String> {
+    match value {
+        serde_json::Value::String(text) => {
+            out.push_str(&format!("<string>{}</string>", escape_xml(text)));
+        }
+        serde_json::Value::Bool(flag) => out.push_str(if *flag { "<true/>" } else { "<false/>" }),
+        serde_json::Value::Number(number) if number.is_i64() => {
+            out.push_str(&format!("<integer>{number}</integer>"));
+        }
+        serde_json::Value::Number(number) => out.push_str(&format!("<real>{number}</real>")),
+        serde_json::Value::Array(items) => {
+            out.push_str("<array>");
+            for item in items {
+                plist_value_xml(item, out)?;
+            }
+            out.push_str("</array>");
+        }
+        serde_json::Value::Object(map) => {
+            out.push_str("<dict>");
+            for (key, item) in map {
+                out.push_str(&format!("<key>{}</key>", escape_xml(key)));
+                plist_value_xml(item, out)?;
+            }
+            out.push_str("</dict>");
+        }
+        serde_json::Value::Null => return Err("Property lists cannot contain null values".to_string()),
+    }
+    Ok(())
+}
+
+fn run_plutil(args: &[&str], input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("plutil")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to run plutil: {err}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .map_err(|err| format!("Failed writing to plutil: {err}"))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed waiting for plutil: {err}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("plutil failed: {stderr}"));
+    }
+    Ok(output.stdout)
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<u8> = hex.bytes().filter(|byte| byte.is_ascii_hexdigit()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("Malformed hex output from xattr".to_string());
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let text = std::str::from_utf8(pair).map_err(|err| err.to_string())?;
+            u8::from_str_radix(text, 16).map_err(|err| err.to_string())
+        })
+        .collect()
+}
+
+/// Reads a plist-valued attribute as JSON, returning `None` when it is not set.
+pub fn read_plist(path: &Path, name: &str) -> Result<Option<serde_json::Value>, String> {
+    let output = Command::new("xattr")
+        .arg("-px")
+        .arg(name)
+        .arg(path)
+        .output()
+        .map_err(|err| format!("Failed to run xattr: {err}"))?;
+
+    if !output.status.success() {
+        // xattr exits non-zero when the attribute is missing.
+        return Ok(None);
+    }
+
+    let bytes = decode_hex(&String::from_utf8_lossy(&output.stdout))?;
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+
+    let json = run_plutil(&["-convert", "json", "-o", "-", "-"], &bytes)?;
+    serde_json::from_slice(&json)
+        .map(Some)
+        .map_err(|err| format!("Failed parsing {name} on {}: {err}", path.display()))
+}
+
+/// Writes `value` as a binary plist into the named attribute.
+pub fn write_plist(path: &Path, name: &str, value: &serde_json::Value) -> Result<(), String> {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">",
+    );
+    plist_value_xml(value, &mut xml)?;
+    xml.push_str("</plist>\n");
+
+    let binary = run_plutil(&["-convert", "binary1", "-o", "-", "-"], xml.as_bytes())?;
+    let hex: String = binary.iter().map(|byte| format!("{byte:02x}")).collect();
+
+    let output = Command::new("xattr")
+        .arg("-wx")
+        .arg(name)
+        .arg(hex)
+        .arg(path)
+        .output()
+        .map_err(|err| format!("Failed to run xattr: {err}"))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(format!("xattr failed writing {name} on {}: {stderr}", path.display()))
+}
+
+/// Removes the named attribute; missing attributes are not an error.
+pub fn remove(path: &Path, name: &str) -> Result<(), String> {
+    Command::new("xattr")
+        .arg("-d")
+        .arg(name)
+        .arg(path)
+        .output()
+        .map(|_| ())
+        .map_err(|err| format!("Failed to run xattr: {err}"))
+}
@@ -0,0 +1,47 @@
+package com.dearhermes.mobile
+
+import android.app.Activity
+import androidx.work.Constraints
+import androidx.work.ExistingPeriodicWorkPolicy
+import androidx.work.NetworkType
+import androidx.work.PeriodicWorkRequestBuilder
+import androidx.work.WorkManager
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.Plugin
+import java.util.concurrent.TimeUnit
+
+/** The app's `backgroundSync` setting. */
+@InvokeArg
+class BackgroundSyncArgs {
+  var enabled: Boolean = false
+  var unmeteredOnly: Boolean = true
+  var chargingOnly: Boolean = false
+  var intervalMinutes: Long = 60
+}
+
+@TauriPlugin
+class HermesMobilePlugin(private val activity: Activity) : Plugin(activity) {
+  /** Replaces the periodic `SyncWorker` job with one for `args`, or cancels it. */
+  @Command
+  fun scheduleBackgroundSync(invoke: Invoke) {
+    val args = invoke.parseArgs(BackgroundSyncArgs::class.java)
+    val workManager = WorkManager.getInstance(activity.applicationContext)
+    if (!args.enabled) {
+      workManager.cancelUniqueWork(SyncWorker.NAME)
+      invoke.resolve()
+      return
+    }
+    val constraints = Constraints.Builder()
+      .setRequiredNetworkType(if (args.unmeteredOnly) NetworkType.UNMETERED else NetworkType.CONNECTED)
+      .setRequiresCharging(args.chargingOnly)
+      .build()
+    val request = PeriodicWorkRequestBuilder<SyncWorker>(args.intervalMinutes, TimeUnit.MINUTES)
+      .setConstraints(constraints)
+      .build()
+    workManager.enqueueUniquePeriodicWork(SyncWorker.NAME, ExistingPeriodicWorkPolicy.UPDATE, request)
+    invoke.resolve()
+  }
+}
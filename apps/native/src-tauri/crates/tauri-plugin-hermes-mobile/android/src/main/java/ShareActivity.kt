@@ -0,0 +1,125 @@
+package com.dearhermes.mobile
+
+import android.app.Activity
+import android.content.Intent
+import android.content.pm.PackageManager
+import android.net.Uri
+import android.os.Build
+import android.os.Bundle
+import android.provider.OpenableColumns
+import android.util.Log
+import android.util.Patterns
+import android.webkit.MimeTypeMap
+import org.json.JSONObject
+import java.io.File
+
+private const val TAG = "HermesShare"
+// Must match `QUEUE_FOLDER` in share_intake.rs.
+private const val QUEUE_FOLDER = "ShareInbox"
+
+/**
+ * Receives `ACTION_SEND` and `ACTION_SEND_MULTIPLE` shares, queues each item
+ * for `share_intake` in the app's data folder and opens Hermes, which takes
+ * the queue into the inbox note when it comes to the foreground.
+ *
+ * Each `content://` stream is copied next to its `<id>.json` before the JSON
+ * is written, so the app never sees an item whose file is still arriving.
+ */
+class ShareActivity : Activity() {
+  override fun onCreate(savedInstanceState: Bundle?) {
+    super.onCreate(savedInstanceState)
+    try {
+      queue(intent)
+    } catch (e: Exception) {
+      Log.e(TAG, "Failed queueing share", e)
+    }
+    packageManager.getLaunchIntentForPackage(packageName)?.let {
+      it.addFlags(Intent.FLAG_ACTIVITY_NEW_TASK or Intent.FLAG_ACTIVITY_CLEAR_TOP)
+      startActivity(it)
+    }
+    finish()
+  }
+
+  private fun queueDir(): File {
+    // The folder Tauri resolves `app_data_dir` to.
+    val root = if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.N) dataDir else File(applicationInfo.dataDir)
+    return File(root, QUEUE_FOLDER).apply { mkdirs() }
+  }
+
+  private fun queue(intent: Intent) {
+    val dir = queueDir()
+    val id = System.currentTimeMillis()
+    val text = intent.getStringExtra(Intent.EXTRA_TEXT)?.trim()?.takeIf { it.isNotEmpty() }
+    val subject = intent.getStringExtra(Intent.EXTRA_SUBJECT)?.trim()?.takeIf { it.isNotEmpty() }
+    val streams = streams(intent)
+    if (streams.isEmpty()) {
+      text?.let { write(dir, "$id-000", textItem(it, subject)) }
+      return
+    }
+    streams.forEachIndexed { index, uri ->
+      val name = "$id-${"%03d".format(index)}"
+      try {
+        val item = streamItem(dir, name, uri) ?: return@forEachIndexed
+        // A caption sent with the streams goes with the first of them.
+        if (index == 0 && text != null) item.put("caption", text)
+        write(dir, name, item)
+      } catch (e: Exception) {
+        Log.e(TAG, "Failed copying shared $uri", e)
+      }
+    }
+  }
+
+  @Suppress("DEPRECATION")
+  private fun streams(intent: Intent): List<Uri> = when (intent.action) {
+    Intent.ACTION_SEND -> listOfNotNull(intent.getParcelableExtra<Uri>(Intent.EXTRA_STREAM))
+    Intent.ACTION_SEND_MULTIPLE -> intent.getParcelableArrayListExtra<Uri>(Intent.EXTRA_STREAM).orEmpty()
+    else -> emptyList()
+  }
+
+  private fun textItem(text: String, subject: String?): JSONObject {
+    if (Patterns.WEB_URL.matcher(text).matches() && text.contains("://")) {
+      return item("url").put("url", text).apply { subject?.let { put("title", it) } }
+    }
+    return item("text").put("text", subject?.let { "$it\n\n$text" } ?: text)
+  }
+
+  /** Copies `uri` into the queue as `<name>.<ext>` and describes it, or `null` when it can't be read. */
+  private fun streamItem(dir: File, name: String, uri: Uri): JSONObject? {
+    val type = contentResolver.getType(uri).orEmpty()
+    val displayName = displayName(uri)
+    val extension = displayName?.substringAfterLast('.', "")?.takeIf { it.isNotEmpty() }
+      ?: MimeTypeMap.getSingleton().getExtensionFromMimeType(type)
+      ?: "bin"
+    val file = "$name.$extension"
+    val input = contentResolver.openInputStream(uri) ?: return null
+    input.use { source -> File(dir, file).outputStream().use { source.copyTo(it) } }
+    val kind = if (type.startsWith("image/")) "image" else "file"
+    return item(kind).put("file", file).apply { displayName?.let { put("name", it) } }
+  }
+
+  private fun displayName(uri: Uri): String? =
+    contentResolver.query(uri, arrayOf(OpenableColumns.DISPLAY_NAME), null, null, null)?.use {
+      if (it.moveToFirst()) it.getString(0) else null
+    }
+
+  private fun item(kind: String): JSONObject = JSONObject()
+    .put("kind", kind)
+    .put("sharedAt", System.currentTimeMillis())
+    .apply { sourceApp()?.let { put("sourceApp", it) } }
+
+  /** The label of the app the share came from, when Android tells. */
+  private fun sourceApp(): String? {
+    val source = referrer?.takeIf { it.scheme == "android-app" }?.host ?: return null
+    return try {
+      packageManager.getApplicationLabel(packageManager.getApplicationInfo(source, 0)).toString()
+    } catch (e: PackageManager.NameNotFoundException) {
+      source
+    }
+  }
+
+  private fun write(dir: File, name: String, item: JSONObject) {
+    val partial = File(dir, "$name.json.partial")
+    partial.writeText(item.toString())
+    partial.renameTo(File(dir, "$name.json"))
+  }
+}
@@ -0,0 +1,46 @@
+package com.dearhermes.mobile
+
+import android.content.Context
+import android.util.Log
+import androidx.work.Worker
+import androidx.work.WorkerParameters
+
+/**
+ * One background sync pass, run by WorkManager within the constraints
+ * `HermesMobilePlugin` scheduled it with.
+ *
+ * The pass runs in the app's Rust core through `runSync`, which needs the
+ * app to have started this process; a process WorkManager started on its
+ * own skips the pass and leaves it to the next period.
+ */
+class SyncWorker(context: Context, params: WorkerParameters) : Worker(context, params) {
+  override fun doWork(): Result {
+    try {
+      System.loadLibrary(LIBRARY)
+    } catch (e: UnsatisfiedLinkError) {
+      Log.e(TAG, "Failed loading $LIBRARY", e)
+      return Result.failure()
+    }
+    return when (runSync()) {
+      SYNCED -> Result.success()
+      NOT_RUNNING -> {
+        Log.i(TAG, "Hermes isn't running; skipping this background sync")
+        Result.success()
+      }
+      else -> Result.retry()
+    }
+  }
+
+  companion object {
+    const val NAME = "hermes-background-sync"
+    private const val TAG = "HermesSync"
+    // The app's Rust library, `hermes_lib` in its Cargo.toml.
+    private const val LIBRARY = "hermes_lib"
+    // Must match the values `background_sync.rs` returns.
+    private const val SYNCED = 1
+    private const val NOT_RUNNING = 2
+
+    @JvmStatic
+    external fun runSync(): Int
+  }
+}
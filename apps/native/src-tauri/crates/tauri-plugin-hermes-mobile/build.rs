@@ -0,0 +1,20 @@
+const COMMANDS: &[&str] = &[];
+/// Shared with the share extension, which queues items in its container.
+#[cfg(target_os = "macos")]
+const APP_GROUP: &str = "group.com.dearhermes.app";
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS).android_path("android").ios_path("ios").build();
+
+    // Only does anything while Tauri builds the generated Xcode project.
+    #[cfg(target_os = "macos")]
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("ios") {
+        tauri_plugin::mobile::update_entitlements(|entitlements| {
+            entitlements.insert(
+                "com.apple.security.application-groups".to_string(),
+                plist::Value::Array(vec![APP_GROUP.into()]),
+            );
+        })
+        .expect("failed adding the app group entitlement");
+    }
+}
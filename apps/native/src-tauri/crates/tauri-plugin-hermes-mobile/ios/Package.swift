@@ -0,0 +1,27 @@
+// swift-tools-version:5.5
+
+import PackageDescription
+
+let package = Package(
+  name: "tauri-plugin-hermes-mobile",
+  platforms: [
+    .iOS(.v13),
+  ],
+  products: [
+    .library(
+      name: "tauri-plugin-hermes-mobile",
+      type: .static,
+      targets: ["tauri-plugin-hermes-mobile"])
+  ],
+  dependencies: [
+    .package(name: "Tauri", path: "../.tauri/tauri-api")
+  ],
+  targets: [
+    .target(
+      name: "tauri-plugin-hermes-mobile",
+      dependencies: [
+        .byName(name: "Tauri")
+      ],
+      path: "Sources")
+  ]
+)
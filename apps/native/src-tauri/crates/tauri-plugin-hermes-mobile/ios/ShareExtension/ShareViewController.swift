@@ -0,0 +1,119 @@
+import UIKit
+import UniformTypeIdentifiers
+
+/// Must match `APP_GROUP` and `QUEUE_FOLDER` in share_intake.rs.
+let appGroup = "group.com.dearhermes.app"
+let queueFolder = "ShareInbox"
+
+/// Queues what was shared for `share_intake` in the app group container and
+/// closes; Hermes takes the queue into the inbox note the next time it comes
+/// to the foreground. An image or file is copied next to its `<id>.json`
+/// before the JSON is written, so the app never sees an item whose file is
+/// still arriving. iOS doesn't tell an extension which app shared, so items
+/// carry only when they were shared.
+class ShareViewController: UIViewController {
+  override func viewDidLoad() {
+    super.viewDidLoad()
+    view.isHidden = true
+  }
+
+  override func viewDidAppear(_ animated: Bool) {
+    super.viewDidAppear(animated)
+    guard let dir = queueDir() else {
+      finish()
+      return
+    }
+    let items = extensionContext?.inputItems as? [NSExtensionItem] ?? []
+    let id = Int64(Date().timeIntervalSince1970 * 1000)
+    let group = DispatchGroup()
+    var index = 0
+    for item in items {
+      let caption = item.attributedContentText?.string.trimmingCharacters(in: .whitespacesAndNewlines)
+      for provider in item.attachments ?? [] {
+        let name = String(format: "%lld-%03ld", id, index)
+        index += 1
+        group.enter()
+        queue(provider, caption: caption, name: name, dir: dir) { group.leave() }
+      }
+    }
+    group.notify(queue: .main) { self.finish() }
+  }
+
+  private func finish() {
+    extensionContext?.completeRequest(returningItems: nil)
+  }
+
+  private func queueDir() -> URL? {
+    guard let container = FileManager.default.containerURL(forSecurityApplicationGroupIdentifier: appGroup) else {
+      return nil
+    }
+    let dir = container.appendingPathComponent(queueFolder, isDirectory: true)
+    try? FileManager.default.createDirectory(at: dir, withIntermediateDirectories: true)
+    return dir
+  }
+
+  private func queue(_ provider: NSItemProvider, caption: String?, name: String, dir: URL, done: @escaping () -> Void) {
+    let nonEmptyCaption = caption.flatMap { $0.isEmpty ? nil : $0 }
+    if provider.hasItemConformingToTypeIdentifier(UTType.image.identifier) {
+      copy(provider, type: .image, kind: "image", caption: nonEmptyCaption, name: name, dir: dir, done: done)
+    } else if provider.hasItemConformingToTypeIdentifier(UTType.fileURL.identifier) {
+      copy(provider, type: .data, kind: "file", caption: nonEmptyCaption, name: name, dir: dir, done: done)
+    } else if provider.hasItemConformingToTypeIdentifier(UTType.url.identifier) {
+      _ = provider.loadObject(ofClass: URL.self) { url, _ in
+        if let url = url {
+          var item = self.item("url")
+          item["url"] = url.absoluteString
+          item["title"] = nonEmptyCaption
+          self.write(item, name: name, dir: dir)
+        }
+        done()
+      }
+    } else if provider.hasItemConformingToTypeIdentifier(UTType.plainText.identifier) {
+      _ = provider.loadObject(ofClass: String.self) { text, _ in
+        if let text = text?.trimmingCharacters(in: .whitespacesAndNewlines), !text.isEmpty {
+          var item = self.item("text")
+          item["text"] = text
+          self.write(item, name: name, dir: dir)
+        }
+        done()
+      }
+    } else {
+      done()
+    }
+  }
+
+  /// Copies the shared file into the queue as `<name>.<ext>` and queues it.
+  private func copy(_ provider: NSItemProvider, type: UTType, kind: String, caption: String?, name: String, dir: URL, done: @escaping () -> Void) {
+    _ = provider.loadFileRepresentation(forTypeIdentifier: type.identifier) { source, _ in
+      defer { done() }
+      guard let source = source else { return }
+      let ext = source.pathExtension.isEmpty ? "bin" : source.pathExtension
+      let file = "\(name).\(ext)"
+      do {
+        try FileManager.default.copyItem(at: source, to: dir.appendingPathComponent(file))
+      } catch {
+        NSLog("Failed copying shared \(source): \(error)")
+        return
+      }
+      var item = self.item(kind)
+      item["file"] = file
+      item["name"] = source.lastPathComponent
+      item["caption"] = caption
+      self.write(item, name: name, dir: dir)
+    }
+  }
+
+  private func item(_ kind: String) -> [String: Any] {
+    return ["kind": kind, "sharedAt": Int64(Date().timeIntervalSince1970 * 1000)]
+  }
+
+  private func write(_ item: [String: Any], name: String, dir: URL) {
+    do {
+      let data = try JSONSerialization.data(withJSONObject: item)
+      // Written whole, so the app never reads half of it.
+      try data.write(to: dir.appendingPathComponent("\(name).json"), options: .atomic)
+    } catch {
+      NSLog("Failed queueing share \(name): \(error)")
+    }
+  }
+}
@@ -0,0 +1,97 @@
+import BackgroundTasks
+import Network
+import Tauri
+import UIKit
+import WebKit
+
+/// Must match `BGTaskSchedulerPermittedIdentifiers` in Info.ios.plist.
+let syncTaskIdentifier = "com.dearhermes.app.sync"
+
+/// One background sync pass in the app's Rust core; see `background_sync.rs`.
+@_silgen_name("hermes_background_sync")
+func hermesBackgroundSync() -> Bool
+
+/// The app's `backgroundSync` setting.
+struct BackgroundSyncArgs: Codable {
+  var enabled = false
+  var unmeteredOnly = true
+  var chargingOnly = false
+  var intervalMinutes = 60
+}
+
+class HermesMobilePlugin: Plugin {
+  private var settings = BackgroundSyncArgs()
+  private let pathMonitor = NWPathMonitor()
+
+  override init() {
+    super.init()
+    pathMonitor.start(queue: DispatchQueue(label: "com.dearhermes.app.network"))
+    // Tasks must be registered before the app finishes launching, which is
+    // still ahead when Tauri sets up its plugins.
+    BGTaskScheduler.shared.register(forTaskWithIdentifier: syncTaskIdentifier, using: nil) { task in
+      self.run(task as! BGAppRefreshTask)
+    }
+  }
+
+  /// Replaces the pending refresh request with one for the new settings, or cancels it.
+  @objc public func scheduleBackgroundSync(_ invoke: Invoke) throws {
+    settings = try invoke.parseArgs(BackgroundSyncArgs.self)
+    schedule()
+    invoke.resolve()
+  }
+
+  private func schedule() {
+    BGTaskScheduler.shared.cancel(taskRequestWithIdentifier: syncTaskIdentifier)
+    guard settings.enabled else { return }
+    let request = BGAppRefreshTaskRequest(identifier: syncTaskIdentifier)
+    request.earliestBeginDate = Date(timeIntervalSinceNow: TimeInterval(settings.intervalMinutes * 60))
+    do {
+      try BGTaskScheduler.shared.submit(request)
+    } catch {
+      Logger.error("Failed scheduling background sync: \(error)")
+    }
+  }
+
+  /// Whether the battery and network constraints allow a pass now; refresh
+  /// requests can't carry them, unlike Android's jobs.
+  private func allowed() -> Bool {
+    if settings.chargingOnly {
+      UIDevice.current.isBatteryMonitoringEnabled = true
+      let state = UIDevice.current.batteryState
+      if state != .charging && state != .full { return false }
+    }
+    let path = pathMonitor.currentPath
+    if path.status != .satisfied { return false }
+    return !settings.unmeteredOnly || !(path.isExpensive || path.isConstrained)
+  }
+
+  private func run(_ task: BGAppRefreshTask) {
+    // Each request fires once; the next one keeps the passes going.
+    schedule()
+    guard allowed() else {
+      task.setTaskCompleted(success: true)
+      return
+    }
+    // The pass can't be interrupted midway; running over only ends the task
+    // early, and iOS suspends the app until it is next in the foreground.
+    let lock = NSLock()
+    var completed = false
+    let complete = { (success: Bool) in
+      lock.lock()
+      defer { lock.unlock() }
+      if !completed {
+        completed = true
+        task.setTaskCompleted(success: success)
+      }
+    }
+    task.expirationHandler = { complete(false) }
+    DispatchQueue.global(qos: .utility).async {
+      complete(hermesBackgroundSync())
+    }
+  }
+}
+
+@_cdecl("init_plugin_hermes_mobile")
+func initPlugin() -> Plugin {
+  return HermesMobilePlugin()
+}
@@ -0,0 +1,32 @@
+//! Native pieces of the Hermes app on iOS and Android.
+//!
+//! The app's generated mobile projects aren't kept in the tree, so what has
+//! to live in them comes in through this plugin: its Android library is
+//! merged into the app, bringing `ShareActivity`, which queues `ACTION_SEND`
+//! and `ACTION_SEND_MULTIPLE` shares for the app's `share_intake`, and
+//! `SyncWorker`, the WorkManager job behind background sync; its Swift
+//! package registers the `BGAppRefreshTask` that does the same on iOS. The
+//! iOS share extension's sources are kept alongside, in `ios/ShareExtension`,
+//! for the generated Xcode project to include. On desktop the plugin does
+//! nothing.
+
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::Runtime;
+
+#[cfg(mobile)]
+mod mobile;
+
+#[cfg(mobile)]
+pub use mobile::{HermesMobile, HermesMobileExt};
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("hermes-mobile")
+        .setup(|app, api| {
+            #[cfg(mobile)]
+            mobile::register(app, api)?;
+            #[cfg(desktop)]
+            let _ = (app, api);
+            Ok(())
+        })
+        .build()
+}
@@ -0,0 +1,38 @@
+use serde::Serialize;
+use tauri::plugin::{PluginApi, PluginHandle};
+use tauri::{AppHandle, Manager, Runtime};
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_hermes_mobile);
+
+/// The native half of the plugin.
+pub struct HermesMobile<R: Runtime>(PluginHandle<R>);
+
+impl<R: Runtime> HermesMobile<R> {
+    /// Schedules background sync passes with `settings`, the app's
+    /// `backgroundSync` setting, or cancels them when it isn't enabled.
+    pub fn schedule_background_sync<S: Serialize>(&self, settings: &S) -> Result<(), String> {
+        self.0
+            .run_mobile_plugin::<()>("scheduleBackgroundSync", settings)
+            .map_err(|err| format!("Failed scheduling background sync: {err}"))
+    }
+}
+
+pub trait HermesMobileExt<R: Runtime> {
+    fn hermes_mobile(&self) -> &HermesMobile<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> HermesMobileExt<R> for T {
+    fn hermes_mobile(&self) -> &HermesMobile<R> {
+        self.state::<HermesMobile<R>>().inner()
+    }
+}
+
+pub fn register<R: Runtime>(app: &AppHandle<R>, api: PluginApi<R, ()>) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(target_os = "android")]
+    let handle = api.register_android_plugin("com.dearhermes.mobile", "HermesMobilePlugin")?;
+    #[cfg(target_os = "ios")]
+    let handle = api.register_ios_plugin(init_plugin_hermes_mobile)?;
+    app.manage(HermesMobile(handle));
+    Ok(())
+}
@@ -0,0 +1,195 @@
+//! App lock: workspace content stays hidden until the user passes Touch ID
+//! (or the account password) on macOS, or Windows Hello on Windows.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::note_lock;
+
+/// Settings-store key; when true Hermes starts locked.
+const APP_LOCK_SETTING: &str = "appLock";
+/// Settings-store key; minutes without input before locking, 0 or missing disables it.
+const AUTO_LOCK_SETTING: &str = "autoLockMinutes";
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+pub const LOCKED_EVENT: &str = "app-locked";
+pub const UNLOCKED_EVENT: &str = "app-unlocked";
+#[cfg(any(target_os = "macos", windows))]
+const UNLOCK_REASON: &str = "unlock your Hermes workspace";
+
+#[derive(Default)]
+pub struct AppLock(Mutex<bool>);
+
+/// Time of the last input or focus change in any Hermes window.
+pub struct LastActivity(Mutex<Instant>);
+
+impl Default for LastActivity {
+    fn default() -> Self {
+        LastActivity(Mutex::new(Instant::now()))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn verify_user() -> Result<(), String> {
+    use std::sync::mpsc;
+
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_local_authentication::{LAContext, LAPolicy};
+
+    // Biometrics with the account password as fallback, so a Mac without Touch ID can still unlock.
+    let policy = LAPolicy::DeviceOwnerAuthentication;
+    // SAFETY: `LAContext` is a plain Foundation object; the calls below follow its documented contract.
+    let context = unsafe { LAContext::new() };
+    unsafe { context.canEvaluatePolicy_error(policy) }
+        .map_err(|err| format!("Device authentication is unavailable: {}", err.localizedDescription()))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let reply = RcBlock::new(move |success: Bool, error: *mut NSError| {
+        // SAFETY: LocalAuthentication passes either null or a valid error.
+        let message = unsafe { error.as_ref() }.map(|error| error.localizedDescription().to_string());
+        let _ = sender.send(if success.as_bool() {
+            Ok(())
+        } else {
+            Err(message.unwrap_or_else(|| "Authentication failed.".to_string()))
+        });
+    });
+    unsafe { context.evaluatePolicy_localizedReason_reply(policy, &NSString::from_str(UNLOCK_REASON), &reply) };
+
+    receiver
+        .recv()
+        .map_err(|_| "Authentication was interrupted.".to_string())?
+}
+
+#[cfg(windows)]
+fn verify_user() -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{UserConsentVerificationResult, UserConsentVerifier};
+
+    let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(format!("Hermes wants to {UNLOCK_REASON}.")))
+        .and_then(|operation| operation.join())
+        .map_err(|err| format!("Windows Hello is unavailable: {err}"))?;
+    match result {
+        UserConsentVerificationResult::Verified => Ok(()),
+        UserConsentVerificationResult::Canceled => Err("Authentication was canceled.".to_string()),
+        UserConsentVerificationResult::DeviceNotPresent | UserConsentVerificationResult::NotConfiguredForUser => {
+            Err("Set up Windows Hello to use the app lock.".to_string())
+        }
+        _ => Err("Authentication failed.".to_string()),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn verify_user() -> Result<(), String> {
+    Err("App lock requires Touch ID or Windows Hello.".to_string())
+}
+
+fn supported() -> bool {
+    cfg!(any(target_os = "macos", windows))
+}
+
+pub fn is_locked(app: &AppHandle) -> bool {
+    *app.state::<AppLock>().0.lock().unwrap()
+}
+
+/// Refuses to hand out workspace content while the app is locked.
+pub fn ensure_unlocked(app: &AppHandle) -> Result<(), String> {
+    if is_locked(app) {
+        return Err("Hermes is locked.".to_string());
+    }
+    Ok(())
+}
+
+pub fn touch(app: &AppHandle) {
+    *app.state::<LastActivity>().0.lock().unwrap() = Instant::now();
+}
+
+fn auto_lock_after(app: &AppHandle) -> Option<Duration> {
+    let minutes = crate::read_setting(app, AUTO_LOCK_SETTING)?.as_u64()?;
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+/// Locks once the configured idle time has passed without input.
+fn watch_idle(app: AppHandle) {
+    loop {
+        thread::sleep(IDLE_CHECK_INTERVAL);
+        let Some(limit) = auto_lock_after(&app) else {
+            continue;
+        };
+        let idle = app.state::<LastActivity>().0.lock().unwrap().elapsed();
+        if idle >= limit && !is_locked(&app) {
+            if let Err(err) = lock(&app) {
+                tracing::warn!("{}", err);
+            }
+        }
+    }
+}
+
+/// Starts locked when the app lock is enabled and begins idle tracking; call once during setup.
+pub fn init(app: &AppHandle) {
+    if !supported() {
+        return;
+    }
+    let enabled = crate::read_setting(app, APP_LOCK_SETTING)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if enabled {
+        *app.state::<AppLock>().0.lock().unwrap() = true;
+    }
+
+    let handle = app.clone();
+    thread::spawn(move || watch_idle(handle));
+}
+
+pub fn lock(app: &AppHandle) -> Result<(), String> {
+    if !supported() {
+        return Err("App lock requires Touch ID or Windows Hello.".to_string());
+    }
+    // Edits to unlocked notes can't be saved once their keys are gone.
+    if let Err(err) = crate::autosave::flush(app, None) {
+        tracing::warn!("{}", String::from(err));
+    }
+    *app.state::<AppLock>().0.lock().unwrap() = true;
+    note_lock::relock_all(app);
+    if let Err(err) = app.emit(LOCKED_EVENT, ()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_app(app: AppHandle) -> Result<(), String> {
+    lock(&app)
+}
+
+/// Prompts for biometrics; async so the prompt doesn't block the main thread.
+#[tauri::command]
+pub async fn unlock_app(app: AppHandle) -> Result<(), String> {
+    if !is_locked(&app) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn_blocking(verify_user)
+        .await
+        .map_err(|err| format!("Authentication task failed: {err}"))??;
+
+    *app.state::<AppLock>().0.lock().unwrap() = false;
+    touch(&app);
+    if let Err(err) = app.emit(UNLOCKED_EVENT, ()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(())
+}
+
+/// Called by the frontend (throttled) on keyboard and pointer input.
+#[tauri::command]
+pub fn record_activity(app: AppHandle) {
+    touch(&app);
+}
+
+#[tauri::command]
+pub fn is_app_locked(app: AppHandle) -> bool {
+    is_locked(&app)
+}
@@ -0,0 +1,175 @@
+//! Moving Hermes to another machine: the settings store exported to a single
+//! JSON archive and imported back.
+//!
+//! Secrets never leave the machine. Provider API keys and the relay credentials
+//! are recorded only as settings to re-prompt for, along with the parts that
+//! aren't secret (the relay URL). Settings tied to this device — its id, LAN
+//! pairings and macOS folder bookmarks — are left out; bookmarked folders are
+//! listed instead so the new machine can ask for access again. So are the
+//! grants that let code run or reach notes — trusted hooks, enabled plugins
+//! and the local API — which the user gives again on the new machine; an
+//! archive that sets them could otherwise run commands without asking.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::progress::Progress;
+use crate::{app_lock, recents, SETTINGS_STORE_FILE};
+
+const ARCHIVE_FORMAT: &str = "hermes-app-state";
+const ARCHIVE_VERSION: u32 = 1;
+/// Belong to this device and are never exported.
+const DEVICE_SETTINGS: &[&str] = &["deviceId", "lanSyncDeviceId", "lanSyncPeers", "workspaceBookmarks"];
+/// Hold credentials; only their non-secret parts are exported.
+const SECRET_SETTINGS: &[&str] = &["anthropicApiKey", "openaiApiKey", "relaySync"];
+/// Grant code access to notes or the shell; never exported or imported.
+const TRUST_SETTINGS: &[&str] = &["trustedHooks", "plugins", "localApi"];
+const WORKSPACE_SETTINGS: &[&str] = &["workspacePath", "recentWorkspaces", "workspaceBookmarks"];
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Archive {
+    format: String,
+    version: u32,
+    exported_unix: u64,
+    settings: BTreeMap<String, Value>,
+    /// Secret settings that were set, with whatever of them isn't secret.
+    reprompt: BTreeMap<String, Value>,
+    workspaces: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    restored: Vec<String>,
+    reprompt: BTreeMap<String, Value>,
+    /// Folders to reopen, granting access again where the platform requires it.
+    workspaces: Vec<String>,
+}
+
+/// Whether a setting moves to another machine as it is.
+fn is_portable(key: &str) -> bool {
+    ![DEVICE_SETTINGS, SECRET_SETTINGS, TRUST_SETTINGS].iter().any(|keys| keys.contains(&key))
+}
+
+fn is_set(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::String(text) => !text.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => true,
+    }
+}
+
+fn public_part(key: &str, value: &Value) -> Value {
+    match key {
+        "relaySync" => json!({ "url": value.get("url").cloned().unwrap_or(Value::Null) }),
+        _ => Value::Null,
+    }
+}
+
+fn workspace_paths(key: &str, value: &Value) -> Vec<String> {
+    match (key, value) {
+        ("workspaceBookmarks", Value::Object(bookmarks)) => bookmarks.keys().cloned().collect(),
+        (_, Value::Array(paths)) => paths.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+        (_, Value::String(path)) => vec![path.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// Writes every portable setting to the archive at `path`.
+#[tauri::command]
+pub fn export_app_state(app: AppHandle, path: String, operation_id: Option<String>) -> Result<(), String> {
+    app_lock::ensure_unlocked(&app)?;
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("Failed opening settings store: {err}"))?;
+
+    let progress = Progress::new(&app, "export", operation_id);
+    let entries = store.entries();
+    let total = entries.len();
+    let mut settings = BTreeMap::new();
+    let mut reprompt = BTreeMap::new();
+    let mut workspaces = BTreeSet::new();
+    for (done, (key, value)) in entries.into_iter().enumerate() {
+        progress.report("settings", done, total);
+        if WORKSPACE_SETTINGS.contains(&key.as_str()) {
+            workspaces.extend(workspace_paths(&key, &value).into_iter().filter(|path| !path.is_empty()));
+        }
+        if SECRET_SETTINGS.contains(&key.as_str()) {
+            if is_set(&value) {
+                reprompt.insert(key.clone(), public_part(&key, &value));
+            }
+        } else if is_portable(&key) {
+            settings.insert(key, value);
+        }
+    }
+
+    workspaces.extend(recents::list(&app));
+
+    let archive = Archive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_unix: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0),
+        settings,
+        reprompt,
+        workspaces: workspaces.into_iter().collect(),
+    };
+    let json =
+        serde_json::to_string_pretty(&archive).map_err(|err| format!("Failed encoding app state: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {path}: {err}"))?;
+    progress.finish(total);
+    Ok(())
+}
+
+/// Applies an archive written by `export_app_state` over this machine's settings.
+/// Settings read only at launch, such as global shortcuts, apply after a restart.
+#[tauri::command]
+pub fn import_app_state(app: AppHandle, path: String, operation_id: Option<String>) -> Result<ImportReport, String> {
+    app_lock::ensure_unlocked(&app)?;
+    let json = fs::read_to_string(&path).map_err(|err| format!("Failed reading {path}: {err}"))?;
+    let archive: Archive = serde_json::from_str(&json)
+        .ok()
+        .filter(|archive: &Archive| archive.format == ARCHIVE_FORMAT)
+        .ok_or_else(|| "This file is not a Hermes settings export.".to_string())?;
+    if archive.version > ARCHIVE_VERSION {
+        return Err("This export comes from a newer version of Hermes; update Hermes to import it.".to_string());
+    }
+
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("Failed opening settings store: {err}"))?;
+    let progress = Progress::new(&app, "import", operation_id);
+    let total = archive.settings.len();
+    let mut restored = Vec::new();
+    for (done, (key, value)) in archive.settings.into_iter().enumerate() {
+        progress.report("settings", done, total);
+        // A hand-edited archive must not overwrite this device's identity or
+        // secrets, or approve hooks and plugins.
+        if !is_portable(&key) {
+            continue;
+        }
+        store.set(key.clone(), value);
+        restored.push(key);
+    }
+    store.save().map_err(|err| format!("Failed saving settings: {err}"))?;
+    progress.finish(total);
+
+    #[cfg(desktop)]
+    crate::menu::refresh(&app);
+
+    Ok(ImportReport {
+        restored,
+        reprompt: archive.reprompt,
+        workspaces: archive.workspaces,
+    })
+}
@@ -0,0 +1,95 @@
+//! One-off requests to the assistant in the `hermes-server` sidecar.
+//!
+//! Backend features that need a model post a single message to the sidecar's
+//! chat endpoint, as the chat panel does, with the model from the settings and
+//! the user's API key for its provider, and collect the streamed reply. The
+//! reply streams as server-sent events: `text` events carry `{ chunk }`, and
+//! `done` or `error` end it.
+
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+const CHAT_URL: &str = "http://127.0.0.1:3003/api/assistant/chat";
+const TIMEOUT: Duration = Duration::from_secs(180);
+
+/// The provider serving `model`, and the name of the setting with its key.
+fn provider(model: &str) -> (&'static str, &'static str, &'static str) {
+    match model.starts_with("gpt-") {
+        true => ("openai", "OpenAI", "openaiApiKey"),
+        false => ("anthropic", "Anthropic", "anthropicApiKey"),
+    }
+}
+
+/// Sends `prompt` to the assistant with the model set for `workspace_path`
+/// and returns the whole reply.
+pub fn ask(app: &AppHandle, workspace_path: Option<&str>, prompt: &str) -> Result<String, String> {
+    let model = crate::settings::resolved(app, workspace_path)?.settings.model;
+    let (provider, label, key_setting) = provider(&model);
+    let api_key = crate::read_setting(app, key_setting)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| format!("Add your {label} API key in Settings first."))?;
+
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|err| format!("Failed creating HTTP client: {err}"))?;
+    let body = json!({
+        "message": prompt,
+        "pages": {},
+        "provider": provider,
+        "model": model,
+        "apiKey": api_key,
+        "conversationHistory": [],
+    });
+    let response = client
+        .post(CHAT_URL)
+        .json(&body)
+        .send()
+        .map_err(|err| format!("Failed reaching the assistant: {err}"))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let message = response
+            .json::<Value>()
+            .ok()
+            .and_then(|body| body.get("error").or_else(|| body.get("message")).and_then(Value::as_str).map(str::to_string))
+            .unwrap_or_else(|| status.to_string());
+        return Err(format!("The assistant failed: {message}"));
+    }
+
+    let mut reply = String::new();
+    let mut event = "text".to_string();
+    for line in BufReader::new(response).lines() {
+        let line = line.map_err(|err| format!("Failed reading the assistant's reply: {err}"))?;
+        if let Some(name) = line.strip_prefix("event: ") {
+            event = name.trim().to_string();
+            continue;
+        }
+        let Some(data) = line.strip_prefix("data: ").and_then(|data| serde_json::from_str::<Value>(data).ok()) else {
+            continue;
+        };
+        match event.as_str() {
+            "text" => reply.push_str(data.get("chunk").and_then(Value::as_str).unwrap_or_default()),
+            "done" => break,
+            "error" => {
+                let message = data.get("message").or_else(|| data.get("error")).and_then(Value::as_str);
+                return Err(format!("The assistant failed: {}", message.unwrap_or("unknown error")));
+            }
+            _ => {}
+        }
+    }
+    Ok(reply)
+}
+
+/// The JSON object in a reply, which models sometimes wrap in a code fence or prose.
+pub fn json_in(reply: &str) -> Result<Value, String> {
+    let (start, end) = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => (start, end),
+        _ => return Err("The assistant didn't reply with JSON.".to_string()),
+    };
+    serde_json::from_str(&reply[start..=end]).map_err(|err| format!("The assistant's reply wasn't valid JSON: {err}"))
+}
@@ -0,0 +1,140 @@
+//! Where pasted images and files brought in with an imported note are kept.
+//!
+//! The `attachmentsFolder` setting is a folder path relative to each project's
+//! notes: `.hermes/assets` by default, or `attachments` to match Obsidian.
+//! Notes link to their attachments by that relative path, so moving a note,
+//! merging a workspace and importing a markdown file carry the links along.
+//! Merging and listing run without an `AppHandle`, so the setting is mirrored
+//! in hermes-core at startup and whenever `set_attachments_folder` changes it.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use hermes_core::attachments::{set_folder, DEFAULT_FOLDER};
+pub use hermes_core::attachments::{is_top_folder, relative};
+use tauri::AppHandle;
+
+use crate::workspace_merge::free_name;
+use crate::{canonical_workspace, note_move, notes_dir, path_safety, CommandError};
+
+const ATTACHMENTS_SETTING: &str = "attachmentsFolder";
+const MAX_FOLDER_DEPTH: usize = 3;
+
+/// Trims `folder` to a relative path with `/` separators and checks it.
+fn normalize(folder: &str) -> Result<String, String> {
+    let folder = folder.trim().replace('\\', "/");
+    let folder = folder.trim_matches('/');
+    if folder.is_empty() {
+        return Ok(DEFAULT_FOLDER.to_string());
+    }
+    let parts: Vec<&str> = folder.split('/').collect();
+    let plain = Path::new(folder).components().all(|part| matches!(part, Component::Normal(_)));
+    if !plain || parts.len() > MAX_FOLDER_DEPTH || parts.iter().any(|part| part.is_empty()) {
+        return Err(format!("Invalid attachments folder '{folder}'; use a folder inside the project."));
+    }
+    for part in &parts {
+        path_safety::check_folder_name(part)?;
+    }
+    if parts[0] == ".hermes" && folder != DEFAULT_FOLDER {
+        return Err(format!("'{folder}' is where Hermes keeps its own files; choose another folder."));
+    }
+    Ok(folder.to_string())
+}
+
+fn read(app: &AppHandle) -> String {
+    crate::read_setting(app, ATTACHMENTS_SETTING)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .and_then(|folder| normalize(&folder).ok())
+        .unwrap_or_else(|| DEFAULT_FOLDER.to_string())
+}
+
+/// Loads the attachments setting.
+pub fn init(app: &AppHandle) {
+    set_folder(read(app));
+}
+
+/// The attachments folder of the project at `folder`.
+pub fn dir(folder: &str) -> PathBuf {
+    notes_dir(folder).join(relative())
+}
+
+/// Writes a file into the attachments of `folder` under a free name like
+/// `name` and returns the path a note in `folder` links to it by.
+fn store(folder: &str, name: &str, write: impl FnOnce(&Path) -> std::io::Result<()>) -> Result<String, String> {
+    let dir = dir(folder);
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    // Spaces would need escaping in a markdown link.
+    let name = name.trim().replace(' ', "-");
+    let name = if name.is_empty() || name.starts_with('.') || path_safety::check_file_name(&name).is_err() {
+        "attachment".to_string()
+    } else {
+        name
+    };
+    let free = free_name(&name, |candidate| dir.join(candidate).exists());
+    let target = dir.join(&free);
+    write(&target).map_err(|err| format!("Failed writing {}: {err}", target.display()))?;
+    Ok(format!("{}/{free}", relative()))
+}
+
+/// Copies the files `content` links to next to `from_dir` into the
+/// attachments of `folder` and returns the content linking to the copies.
+pub fn import_linked(content: &str, from_dir: &Path, folder: &str) -> Result<String, String> {
+    let mut content = content.to_string();
+    for target in note_move::local_targets(&content, from_dir) {
+        let source = from_dir.join(&target);
+        let name = Path::new(&target).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let link = store(folder, &name, |path| fs::copy(&source, path).map(|_| ()))?;
+        content = content.replace(&format!("]({target}"), &format!("]({link}"));
+    }
+    Ok(content)
+}
+
+/// Copies the file at `source` into the attachments of `folder` under a free
+/// name like `name` and returns the path a note in `folder` links to it by.
+#[cfg(mobile)]
+pub fn import_file(folder: &str, source: &Path, name: &str) -> Result<String, String> {
+    store(folder, name, |path| fs::copy(source, path).map(|_| ()))
+}
+
+/// Creates an empty file in the attachments of `folder` under a free name like
+/// `name`, for a recorder to write into, and returns its path and the path to link it by.
+#[cfg(desktop)]
+pub fn create_file(folder: &str, name: &str) -> Result<(PathBuf, String), String> {
+    let link = store(folder, name, |path| fs::File::create(path).map(|_| ()))?;
+    Ok((notes_dir(folder).join(&link), link))
+}
+
+#[tauri::command]
+pub fn get_attachments_folder() -> String {
+    relative()
+}
+
+/// Sets where new attachments go; those already saved stay where they are.
+#[tauri::command]
+pub fn set_attachments_folder(app: AppHandle, folder: String) -> Result<String, String> {
+    let folder = normalize(&folder)?;
+    crate::write_setting(&app, ATTACHMENTS_SETTING, serde_json::json!(folder))?;
+    set_folder(folder.clone());
+    Ok(folder)
+}
+
+/// Saves a pasted or dropped file into the attachments of `workspace_path`
+/// and returns the path to link it by.
+#[tauri::command]
+pub fn save_attachment(
+    app: AppHandle,
+    workspace_path: String,
+    file_name: String,
+    bytes: Vec<u8>,
+) -> Result<String, CommandError> {
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path)?;
+    let link = store(&workspace_path, &file_name, |path| fs::write(path, &bytes))?;
+    tracing::info!("Saved attachment {link} in {workspace_path}");
+    let change = crate::changes::Change::AttachmentSaved {
+        workspace_path,
+        path: link.clone(),
+    };
+    crate::changes::emit(&app, change);
+    Ok(link)
+}
@@ -0,0 +1,213 @@
+//! Autosave, scheduled by the backend rather than editor timers.
+//!
+//! The editor reports every edit with `mark_dirty`; the content waits here
+//! until typing has paused for the `autosaveIdleMs` setting, or has waited
+//! `autosaveMaxMs` while typing goes on, and then goes to the save queue.
+//! Closing an editor window, quitting and the system going to sleep write
+//! everything at once, so no edit depends on a JS timer getting to run.
+
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{read_only, save_queue, settings, validate_tab_key, CommandError};
+
+struct Dirty {
+    pages: HashMap<String, String>,
+    first_edit: Instant,
+    last_edit: Instant,
+    idle: Duration,
+    max_wait: Duration,
+}
+
+impl Dirty {
+    fn due_at(&self) -> Instant {
+        (self.last_edit + self.idle).min(self.first_edit + self.max_wait)
+    }
+}
+
+/// Unsaved edits by workspace path.
+#[derive(Default)]
+pub struct Autosave {
+    dirty: Mutex<HashMap<String, Dirty>>,
+    wake: Condvar,
+}
+
+impl Autosave {
+    /// Records `content` as the latest edit of `tab_key`; `timing` is only
+    /// called for a workspace without pending edits.
+    fn record(&self, workspace_path: String, tab_key: String, content: String, timing: impl FnOnce() -> (Duration, Duration)) {
+        let mut dirty = self.dirty.lock().unwrap();
+        let now = Instant::now();
+        let entry = dirty.entry(workspace_path).or_insert_with(|| {
+            let (idle, max_wait) = timing();
+            Dirty {
+                pages: HashMap::new(),
+                first_edit: now,
+                last_edit: now,
+                idle,
+                max_wait,
+            }
+        });
+        entry.pages.insert(tab_key, content);
+        entry.last_edit = now;
+        self.wake.notify_one();
+    }
+
+    /// Drops the pending edits of `tabs`, which were just saved another way.
+    fn forget<'a>(&self, workspace_path: &str, tabs: impl IntoIterator<Item = &'a str>) {
+        let mut dirty = self.dirty.lock().unwrap();
+        let Some(entry) = dirty.get_mut(workspace_path) else {
+            return;
+        };
+        for tab in tabs {
+            entry.pages.remove(tab);
+        }
+        if entry.pages.is_empty() {
+            dirty.remove(workspace_path);
+        }
+    }
+}
+
+/// Takes the edits of a workspace that are due at `now`.
+fn take_due(dirty: &mut HashMap<String, Dirty>, now: Instant) -> Option<(String, HashMap<String, String>)> {
+    let path = dirty.iter().find(|(_, entry)| entry.due_at() <= now).map(|(path, _)| path.clone())?;
+    dirty.remove(&path).map(|entry| (path, entry.pages))
+}
+
+/// The workspace's autosave timing, read once per batch of edits.
+fn policy(app: &AppHandle, workspace_path: &str) -> (Duration, Duration) {
+    let settings = settings::resolved(app, Some(workspace_path))
+        .map(|resolved| resolved.settings)
+        .unwrap_or_else(|err| {
+            tracing::warn!("{}", err);
+            settings::Settings::default()
+        });
+    (
+        Duration::from_millis(settings.autosave_idle_ms),
+        Duration::from_millis(settings.autosave_max_ms),
+    )
+}
+
+/// Hands `pages` to the save queue, reporting failures like its own writes.
+fn save(app: &AppHandle, workspace_path: String, pages: HashMap<String, String>) -> Result<(), CommandError> {
+    let tabs: Vec<String> = pages.keys().cloned().collect();
+    save_queue::save(app, &workspace_path, pages).map_err(|err| {
+        let error = String::from(err);
+        save_queue::report_failure(app, workspace_path, tabs, error.clone());
+        CommandError::from(error)
+    })
+}
+
+/// Writes unsaved edits of `workspace_path`, or of every workspace, and then
+/// everything the save queue holds for it.
+pub fn flush(app: &AppHandle, workspace_path: Option<&str>) -> Result<(), CommandError> {
+    let state = app.state::<Autosave>();
+    let taken: Vec<(String, Dirty)> = {
+        let mut dirty = state.dirty.lock().unwrap();
+        let paths: Vec<String> = dirty
+            .keys()
+            .filter(|path| workspace_path.is_none_or(|workspace_path| workspace_path == path.as_str()))
+            .cloned()
+            .collect();
+        paths
+            .into_iter()
+            .filter_map(|path| dirty.remove(&path).map(|entry| (path, entry)))
+            .collect()
+    };
+    let mut result = Ok(());
+    for (path, entry) in taken {
+        if let Err(err) = save(app, path, entry.pages) {
+            if result.is_ok() {
+                result = Err(err);
+            }
+        }
+    }
+    save_queue::flush(app, workspace_path).and(result)
+}
+
+/// Drops pending edits of `tabs` in `workspace_path` once newer content of
+/// them is saved directly, so a later autosave can't write the older edits
+/// over it.
+pub fn discard<'a>(app: &AppHandle, workspace_path: &str, tabs: impl IntoIterator<Item = &'a str>) {
+    app.state::<Autosave>().forget(&crate::canonical_workspace(workspace_path), tabs);
+}
+
+/// Workspaces with edits not yet handed to the save queue.
+pub fn dirty_workspaces(app: &AppHandle) -> Vec<String> {
+    app.state::<Autosave>().dirty.lock().unwrap().keys().cloned().collect()
+}
+
+/// Blocks until some workspace's edits are due, then takes them.
+fn next_due(state: &Autosave) -> (String, HashMap<String, String>) {
+    let mut dirty = state.dirty.lock().unwrap();
+    loop {
+        let now = Instant::now();
+        if let Some(entry) = take_due(&mut dirty, now) {
+            return entry;
+        }
+        dirty = match dirty.values().map(Dirty::due_at).min() {
+            Some(due_at) => state.wake.wait_timeout(dirty, due_at - now).unwrap().0,
+            None => state.wake.wait(dirty).unwrap(),
+        };
+    }
+}
+
+/// Starts saving edits once they are due.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        let (workspace_path, pages) = next_due(&app.state::<Autosave>());
+        // Failures are already reported to the frontend.
+        let _ = save(&app, workspace_path, pages);
+    });
+}
+
+/// Records the latest content of an edited tab; the backend decides when to write it.
+#[tauri::command]
+pub fn mark_dirty(app: AppHandle, workspace_path: String, tab_key: String, content: String) -> Result<(), CommandError> {
+    validate_tab_key(&tab_key)?;
+    read_only::ensure_writable(&app, &workspace_path)?;
+    let workspace_path = crate::canonical_workspace(&workspace_path);
+    let state = app.state::<Autosave>();
+    // Settings are read from disk, so not while holding the lock.
+    let known = state.dirty.lock().unwrap().contains_key(&workspace_path);
+    let timing = (!known).then(|| policy(&app, &workspace_path));
+    state.record(workspace_path.clone(), tab_key, content, || {
+        timing.unwrap_or_else(|| policy(&app, &workspace_path))
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn direct_save_drops_the_older_edit() {
+        let state = Autosave::default();
+        state.record("/ws".into(), "sage".into(), "small draft".into(), || (IDLE, IDLE * 10));
+        state.record("/ws".into(), "coral".into(), "other tab".into(), || (IDLE, IDLE * 10));
+        // The large note is saved directly, with newer content than the edit above.
+        state.forget("/ws", ["sage"]);
+        thread::sleep(IDLE * 2);
+        let (path, pages) = take_due(&mut state.dirty.lock().unwrap(), Instant::now()).unwrap();
+        assert_eq!(path, "/ws");
+        assert_eq!(pages, HashMap::from([("coral".to_string(), "other tab".to_string())]));
+    }
+
+    #[test]
+    fn forgetting_every_tab_leaves_nothing_due() {
+        let state = Autosave::default();
+        state.record("/ws".into(), "sage".into(), "small draft".into(), || (IDLE, IDLE * 10));
+        state.forget("/ws", ["sage"]);
+        thread::sleep(IDLE * 2);
+        assert!(take_due(&mut state.dirty.lock().unwrap(), Instant::now()).is_none());
+        assert!(state.dirty.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+//! Syncing with the relay while the app is in the background on iOS and Android.
+//!
+//! The system decides when a backgrounded app may run: iOS through a
+//! `BGAppRefreshTask` registered as `com.dearhermes.app.sync`, Android through
+//! a periodic WorkManager job, both in the `hermes-mobile` plugin. They are
+//! scheduled with the `backgroundSync` setting at startup and whenever it
+//! changes, and call into Rust when they fire: `hermes_background_sync` on
+//! iOS, `SyncWorker.runSync` on Android. That writes unsaved edits and runs
+//! one incremental relay pull/push.
+//!
+//! Going to the background also starts a pass right away, but only when no
+//! constraint is set: the app can't tell the network or battery state itself,
+//! so constrained passes are left to the scheduler, which can.
+//!
+//! A WorkManager job can start the app's process without the app, and so
+//! without a handle to sync with; that pass is skipped.
+
+use std::sync::OnceLock;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_hermes_mobile::HermesMobileExt;
+
+use crate::relay_sync;
+
+const BACKGROUND_SYNC_SETTING: &str = "backgroundSync";
+/// Neither system runs periodic work more often than this.
+const MIN_INTERVAL_MINUTES: u32 = 15;
+
+/// The handle the native schedulers' calls run against, set at startup.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackgroundSync {
+    pub enabled: bool,
+    /// Only on Wi-Fi or another unmetered network.
+    pub unmetered_only: bool,
+    pub charging_only: bool,
+    pub interval_minutes: u32,
+}
+
+impl Default for BackgroundSync {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            unmetered_only: true,
+            charging_only: false,
+            interval_minutes: 60,
+        }
+    }
+}
+
+fn read(app: &AppHandle) -> BackgroundSync {
+    crate::read_setting(app, BACKGROUND_SYNC_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Keeps the handle for scheduled passes and schedules them.
+pub fn init(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+    if let Err(err) = app.hermes_mobile().schedule_background_sync(&read(app)) {
+        tracing::warn!("{}", err);
+    }
+}
+
+/// One pass: unsaved edits first, then the relay. Returns the note keys that changed.
+fn run(app: &AppHandle) -> Result<Vec<String>, String> {
+    if !read(app).enabled || !relay_sync::is_configured(app) {
+        return Ok(Vec::new());
+    }
+    crate::autosave::flush(app, None).map_err(String::from)?;
+    relay_sync::run(app)
+}
+
+fn run_logged(app: &AppHandle) -> bool {
+    match run(app) {
+        Ok(notes) => {
+            tracing::info!("Background sync updated {} notes", notes.len());
+            true
+        }
+        Err(err) => {
+            tracing::warn!("Background sync failed: {err}");
+            false
+        }
+    }
+}
+
+/// Starts a pass as the app goes to the background, when nothing constrains it.
+pub fn app_suspended(app: &AppHandle) {
+    let settings = read(app);
+    if !settings.enabled || settings.unmetered_only || settings.charging_only {
+        return;
+    }
+    let app = app.clone();
+    thread::spawn(move || run_logged(&app));
+}
+
+/// Runs a scheduled pass; `None` when the app isn't running in this process.
+fn run_scheduled() -> Option<bool> {
+    let app = APP.get()?;
+    Some(run_logged(app))
+}
+
+/// Called by the `BGAppRefreshTask` handler; returns whether the pass succeeded.
+#[cfg(target_os = "ios")]
+#[no_mangle]
+pub extern "C" fn hermes_background_sync() -> bool {
+    // iOS launches the whole app for a refresh task, so it is always running.
+    run_scheduled().unwrap_or(false)
+}
+
+/// `SyncWorker.runSync`, called by the WorkManager job: 1 when the pass
+/// succeeded, 0 when it failed and 2 when the app isn't running.
+#[cfg(target_os = "android")]
+#[no_mangle]
+pub extern "system" fn Java_com_dearhermes_mobile_SyncWorker_runSync(
+    _env: *mut std::ffi::c_void,
+    _class: *mut std::ffi::c_void,
+) -> i32 {
+    match run_scheduled() {
+        Some(synced) => i32::from(synced),
+        None => 2,
+    }
+}
+
+#[tauri::command]
+pub fn get_background_sync(app: AppHandle) -> BackgroundSync {
+    read(&app)
+}
+
+/// Saves the background sync settings and reschedules the passes with them.
+#[tauri::command]
+pub fn set_background_sync(app: AppHandle, mut settings: BackgroundSync) -> Result<BackgroundSync, String> {
+    settings.interval_minutes = settings.interval_minutes.max(MIN_INTERVAL_MINUTES);
+    let value = serde_json::to_value(&settings).map_err(|err| format!("Failed encoding background sync settings: {err}"))?;
+    crate::write_setting(&app, BACKGROUND_SYNC_SETTING, value)?;
+    app.hermes_mobile().schedule_background_sync(&settings)?;
+    Ok(settings)
+}
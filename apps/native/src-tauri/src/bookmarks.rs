@@ -0,0 +1,226 @@
+//! Security-scoped bookmarks for workspace folders in sandboxed macOS builds.
+//!
+//! The App Store sandbox only grants access to a folder the user picked in an
+//! open panel, and only until the app quits. A bookmark saved right after the
+//! pick reopens the folder on later launches; bookmarks that no longer resolve
+//! are reported so the UI can ask the user to pick the folder again.
+
+use std::sync::Mutex;
+
+#[cfg(target_os = "macos")]
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+use tauri::Manager;
+#[cfg(target_os = "macos")]
+use tauri::Emitter;
+#[cfg(target_os = "macos")]
+use tauri_plugin_store::StoreExt;
+
+#[cfg(target_os = "macos")]
+use crate::SETTINGS_STORE_FILE;
+
+/// Settings-store key holding `{ folder path: hex-encoded bookmark }`.
+#[cfg(target_os = "macos")]
+const BOOKMARKS_SETTING: &str = "workspaceBookmarks";
+#[cfg(target_os = "macos")]
+pub const ACCESS_NEEDED_EVENT: &str = "workspace-access-needed";
+
+/// Folders whose bookmark could not be resolved on launch.
+#[derive(Default)]
+pub struct StaleBookmarks(pub Mutex<Vec<String>>);
+
+#[cfg(target_os = "macos")]
+mod native {
+    use objc2::runtime::Bool;
+    use objc2_foundation::{
+        NSData, NSString, NSURLBookmarkCreationOptions, NSURLBookmarkResolutionOptions, NSURL,
+    };
+
+    pub fn create(path: &str) -> Result<Vec<u8>, String> {
+        let url = NSURL::fileURLWithPath_isDirectory(&NSString::from_str(path), true);
+        url.bookmarkDataWithOptions_includingResourceValuesForKeys_relativeToURL_error(
+            NSURLBookmarkCreationOptions::WithSecurityScope,
+            None,
+            None,
+        )
+        .map(|data| data.to_vec())
+        .map_err(|err| format!("Failed creating bookmark for {path}: {}", err.localizedDescription()))
+    }
+
+    /// Resolves a bookmark and starts accessing it, returning the folder path and
+    /// whether the bookmark should be recreated.
+    pub fn resolve(bookmark: &[u8]) -> Result<(String, bool), String> {
+        let data = NSData::with_bytes(bookmark);
+        let mut stale = Bool::NO;
+        // SAFETY: `stale` is a valid pointer for the duration of the call.
+        let url = unsafe {
+            NSURL::URLByResolvingBookmarkData_options_relativeToURL_bookmarkDataIsStale_error(
+                &data,
+                NSURLBookmarkResolutionOptions::WithSecurityScope,
+                None,
+                &mut stale,
+            )
+        }
+        .map_err(|err| format!("Failed resolving bookmark: {}", err.localizedDescription()))?;
+
+        let path = url
+            .path()
+            .map(|path| path.to_string())
+            .ok_or_else(|| "Bookmark does not point at a file URL".to_string())?;
+        // Access is deliberately never stopped: the workspace stays open until the app quits.
+        // SAFETY: `url` is a resolved security-scoped file URL.
+        if !unsafe { url.startAccessingSecurityScopedResource() } {
+            return Err(format!("Access to {path} was not granted"));
+        }
+        Ok((path, stale.as_bool()))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(target_os = "macos")]
+fn decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn stored_bookmarks(app: &AppHandle) -> Map<String, Value> {
+    crate::read_setting(app, BOOKMARKS_SETTING)
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn save_bookmarks(app: &AppHandle, bookmarks: Map<String, Value>) -> Result<(), String> {
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("Failed opening settings store: {err}"))?;
+    store.set(BOOKMARKS_SETTING, Value::Object(bookmarks));
+    store.save().map_err(|err| format!("Failed saving settings: {err}"))
+}
+
+#[cfg(target_os = "macos")]
+fn store_bookmark(app: &AppHandle, path: &str, bookmark: &[u8]) -> Result<(), String> {
+    let mut bookmarks = stored_bookmarks(app);
+    bookmarks.insert(path.to_string(), Value::String(encode(bookmark)));
+    save_bookmarks(app, bookmarks)
+}
+
+/// Reopens every bookmarked folder; failures are queued for the re-grant prompt.
+pub fn restore(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    {
+        let mut stale = Vec::new();
+        for (path, value) in stored_bookmarks(app) {
+            let resolved = value
+                .as_str()
+                .and_then(decode)
+                .ok_or_else(|| format!("Corrupt bookmark for {path}"))
+                .and_then(|bookmark| native::resolve(&bookmark));
+            match resolved {
+                Ok((_, false)) => {}
+                // The folder moved or was renamed; refresh the bookmark while we still have access.
+                Ok((resolved_path, true)) => {
+                    let refreshed = native::create(&resolved_path).and_then(|bookmark| {
+                        let mut bookmarks = stored_bookmarks(app);
+                        bookmarks.remove(&path);
+                        bookmarks.insert(resolved_path.clone(), Value::String(encode(&bookmark)));
+                        save_bookmarks(app, bookmarks)
+                    });
+                    if let Err(err) = refreshed {
+                        tracing::warn!("{}", err);
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!("{}: {}", path, err);
+                    stale.push(path);
+                }
+            }
+        }
+
+        if stale.is_empty() {
+            return;
+        }
+        app.state::<StaleBookmarks>().0.lock().unwrap().extend(stale.iter().cloned());
+        if let Err(err) = app.emit(ACCESS_NEEDED_EVENT, stale) {
+            tracing::warn!("Failed to emit event: {err}");
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+}
+
+/// Bookmarks a folder the user just picked; must run while the picker's grant
+/// is still active. A no-op outside macOS.
+pub fn remember(app: &AppHandle, path: &str) -> Result<(), String> {
+    let path = path.trim().trim_end_matches('/');
+    if path.is_empty() {
+        return Err("No folder was selected.".to_string());
+    }
+
+    #[cfg(target_os = "macos")]
+    store_bookmark(app, path, &native::create(path)?)?;
+
+    app.state::<StaleBookmarks>().0.lock().unwrap().retain(|stale| stale != path);
+    Ok(())
+}
+
+/// Drops the bookmark for a folder the user no longer wants listed.
+pub fn forget(app: &AppHandle, path: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let mut bookmarks = stored_bookmarks(app);
+        if bookmarks.remove(path).is_some() {
+            save_bookmarks(app, bookmarks)?;
+        }
+    }
+
+    app.state::<StaleBookmarks>().0.lock().unwrap().retain(|stale| stale != path);
+    Ok(())
+}
+
+/// Follows a folder moved from `old_path` to `new_path`. The new location is
+/// bookmarked while the app can still reach it; otherwise the folder has to
+/// be picked again.
+pub fn relocate(app: &AppHandle, old_path: &str, new_path: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        let mut bookmarks = stored_bookmarks(app);
+        if bookmarks.remove(old_path).is_some() {
+            match native::create(new_path) {
+                Ok(bookmark) => {
+                    bookmarks.insert(new_path.to_string(), Value::String(encode(&bookmark)));
+                }
+                Err(err) => tracing::warn!("{}: {}", new_path, err),
+            }
+            if let Err(err) = save_bookmarks(app, bookmarks) {
+                tracing::warn!("{}", err);
+            }
+        }
+    }
+
+    app.state::<StaleBookmarks>().0.lock().unwrap().retain(|stale| stale != old_path);
+    #[cfg(not(target_os = "macos"))]
+    let _ = new_path;
+}
+
+#[tauri::command]
+pub fn grant_workspace_access(app: AppHandle, path: String) -> Result<(), String> {
+    remember(&app, &path)
+}
+
+/// Folders that need to be picked again before Hermes can read them.
+#[tauri::command]
+pub fn list_stale_workspace_access(state: tauri::State<'_, StaleBookmarks>) -> Vec<String> {
+    state.0.lock().unwrap().clone()
+}
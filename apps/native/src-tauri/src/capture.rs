@@ -0,0 +1,109 @@
+//! Quick capture: short snippets appended to the inbox note with a timestamp,
+//! through `hermes_core::capture`.
+
+pub use hermes_core::capture::{append_entry, DEFAULT_INBOX_TAB};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+#[cfg(desktop)]
+use tauri::{WebviewUrl, WebviewWindowBuilder};
+
+use crate::{file_open, validate_tab_key};
+
+/// Settings-store key naming the tab that receives captures.
+pub const INBOX_TAB_SETTING: &str = "inboxTab";
+
+pub const INBOX_UPDATED_EVENT: &str = "inbox-updated";
+pub const QUICK_CAPTURE_LABEL: &str = "quick-capture";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InboxUpdated {
+    workspace_path: String,
+    tab: String,
+    /// The entry appended, for windows showing the note to append as well.
+    entry: String,
+}
+
+/// The inbox is a tab in the workspace root so it is reachable from every project.
+pub fn inbox_location(app: &AppHandle) -> Result<(String, String), String> {
+    let workspace = file_open::configured_workspace(app)
+        .ok_or_else(|| "No workspace is configured for captures.".to_string())?;
+    let tab = crate::read_setting(app, INBOX_TAB_SETTING)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_INBOX_TAB.to_string());
+    validate_tab_key(&tab)?;
+    Ok((workspace.to_string_lossy().to_string(), tab))
+}
+
+/// Appends `text` to the inbox note, re-indexes it and notifies open windows,
+/// which append the entry to the note as they show it.
+pub fn append_to_inbox(app: &AppHandle, text: &str, source: Option<&str>) -> Result<(), String> {
+    let (workspace_path, tab) = inbox_location(app)?;
+    crate::read_only::ensure_writable(app, &workspace_path).map_err(String::from)?;
+    // Edits still waiting in the editor go first, or its next save would drop the entry.
+    crate::autosave::flush(app, Some(&crate::canonical_workspace(&workspace_path))).map_err(String::from)?;
+    let entry = append_entry(&workspace_path, &tab, text, source)?;
+    crate::changes::note_saved(app, &workspace_path, &tab);
+
+    if let Err(err) = app.emit(INBOX_UPDATED_EVENT, InboxUpdated { workspace_path, tab, entry }) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(())
+}
+
+/// Shows the small always-on-top capture window, creating it on first use.
+#[cfg(desktop)]
+pub fn open_quick_capture_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        QUICK_CAPTURE_LABEL,
+        WebviewUrl::App("index.html?window=quick-capture".into()),
+    )
+    .title("Quick capture")
+    .inner_size(480.0, 180.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build()
+    .map(|_| ())
+    .map_err(|err| format!("Failed to open quick capture: {err}"))
+}
+
+#[tauri::command]
+pub fn show_quick_capture(app: AppHandle) -> Result<(), String> {
+    #[cfg(desktop)]
+    {
+        open_quick_capture_window(&app)
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = app;
+        Err("Quick capture windows are only available on desktop.".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn close_quick_capture(app: AppHandle) {
+    if let Some(window) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        let _ = window.close();
+    }
+}
+
+#[tauri::command]
+pub fn submit_quick_capture(app: AppHandle, text: String) -> Result<(), String> {
+    append_to_inbox(&app, &text, None)?;
+    close_quick_capture(app.clone());
+    #[cfg(desktop)]
+    crate::tray::refresh(&app);
+    Ok(())
+}
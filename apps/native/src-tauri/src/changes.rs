@@ -0,0 +1,71 @@
+//! One channel for everything the backend changes on disk.
+//!
+//! Every command that writes notes, the index, projects, chat or workspace
+//! metadata sends a `Change` on `CHANGED_EVENT` once the write is done, so
+//! other windows, and later plugins, can refresh what they show without
+//! polling. The narrower events, such as `INDEX_SYNCED_EVENT`, are still sent
+//! alongside for the listeners that only care about one kind of change.
+//! `workspace_path` is the folder the change happened in, a project's folder
+//! for its notes, as in the commands themselves.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+pub const CHANGED_EVENT: &str = "hermes://changed";
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Change {
+    /// Tabs whose files were written or, when emptied, deleted.
+    NotesSaved {
+        workspace_path: String,
+        tabs: Vec<String>,
+        deleted: Vec<String>,
+    },
+    /// Index rows refreshed for these tabs.
+    IndexUpdated { workspace_path: String, tabs: Vec<String> },
+    /// A project folder made by its first save.
+    ProjectCreated { workspace_path: String },
+    /// A project folder moved to the Bin; only macOS can do this so far.
+    #[cfg(target_os = "macos")]
+    ProjectTrashed { workspace_path: String, project: String },
+    ProjectMetaChanged { workspace_path: String, project: String },
+    WorkspaceMetaChanged { workspace_path: String },
+    /// A note moved or copied between projects; the empty project is the workspace root.
+    NoteRelocated {
+        workspace_path: String,
+        src_project: String,
+        src_tab: String,
+        dest_project: String,
+        dest_tab: String,
+        moved: bool,
+    },
+    ChatSaved { workspace_path: String },
+    /// Note files renamed to new tab file names.
+    TabFilesRenamed { workspace_path: String },
+    /// `path` is the link to the saved file, relative to the notes.
+    AttachmentSaved { workspace_path: String, path: String },
+    /// Another workspace was imported into this one.
+    WorkspaceMerged { workspace_path: String, from: String },
+}
+
+/// Tells every window about `change`.
+pub fn emit(app: &AppHandle, change: Change) {
+    #[cfg(desktop)]
+    if let Change::NotesSaved { workspace_path, tabs, .. } = &change {
+        crate::hooks::notes_saved(app, workspace_path, tabs);
+    }
+    if let Err(err) = app.emit(CHANGED_EVENT, change) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+/// Sends `NotesSaved` for one tab written outside the save queue.
+pub fn note_saved(app: &AppHandle, workspace_path: &str, tab: &str) {
+    let change = Change::NotesSaved {
+        workspace_path: workspace_path.to_string(),
+        tabs: vec![tab.to_string()],
+        deleted: Vec::new(),
+    };
+    emit(app, change);
+}
@@ -0,0 +1,186 @@
+//! Pasting web pages and documents as markdown.
+//!
+//! `convert_clipboard_to_markdown` reads the HTML the copying app put on the
+//! clipboard (the HTML pasteboard type on macOS, `HTML Format` on Windows,
+//! `text/html` through `wl-paste` or `xclip` on Linux) and converts it to
+//! markdown, keeping headings, emphasis, lists, tables, links and images.
+//! What only matters to the page is dropped first: scripts, styles, forms,
+//! hidden elements, tracking pixels, inline `data:` images and the tracking
+//! parameters marketing tools add to links.
+
+use dom_query::Document;
+use tauri::Url;
+
+/// Elements left out along with everything in them.
+const SKIPPED_TAGS: &[&str] = &[
+    "head", "meta", "link", "script", "style", "noscript", "template", "iframe", "object", "embed", "svg", "canvas",
+    "form", "button", "input", "select", "textarea",
+];
+const HIDDEN: &str = "[hidden], [aria-hidden=\"true\"], [style*=\"display:none\"], [style*=\"display: none\"]";
+/// Query parameters that only identify the campaign or the click.
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_hsenc", "_hsmi",
+    "mkt_tok", "ref_src", "oly_anon_id", "oly_enc_id", "vero_id",
+];
+
+/// `url` without tracking parameters; anything that isn't an http(s) URL is left as it is.
+fn clean_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url.trim()) else {
+        return url.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.query().is_none() {
+        return url.to_string();
+    }
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name.as_ref()))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    parsed.to_string()
+}
+
+/// Whether an image is a tracking pixel or inlines its data, which would bloat the note.
+fn is_junk_image(src: &str, width: Option<&str>, height: Option<&str>) -> bool {
+    let tiny = |size: Option<&str>| size.is_some_and(|size| matches!(size.trim().trim_end_matches("px"), "0" | "1"));
+    src.trim_start().starts_with("data:") || tiny(width) || tiny(height)
+}
+
+/// Markdown for an HTML fragment.
+fn html_to_markdown(html: &str) -> String {
+    let document = Document::from(html);
+    document.select(HIDDEN).remove();
+    for image in document.select("img").nodes() {
+        let src = image.attr("src").map(|src| src.to_string()).unwrap_or_default();
+        let width = image.attr("width").map(|width| width.to_string());
+        let height = image.attr("height").map(|height| height.to_string());
+        if src.is_empty() || is_junk_image(&src, width.as_deref(), height.as_deref()) {
+            image.remove_from_parent();
+        } else {
+            image.set_attr("src", &clean_url(&src));
+        }
+    }
+    for link in document.select("a[href]").nodes() {
+        let href = link.attr("href").map(|href| href.to_string()).unwrap_or_default();
+        let href = href.trim();
+        // Fragment and script links go nowhere once pasted; keep their text.
+        if href.is_empty() || href.starts_with('#') || href.to_ascii_lowercase().starts_with("javascript:") {
+            link.remove_attr("href");
+        } else {
+            link.set_attr("href", &clean_url(href));
+        }
+    }
+
+    let markdown = document.md(Some(SKIPPED_TAGS)).to_string();
+    // Nested blocks leave runs of blank lines behind.
+    let mut clean = String::new();
+    let mut blank = 0;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        blank = if line.is_empty() { blank + 1 } else { 0 };
+        if blank < 2 {
+            clean.push_str(line);
+            clean.push('\n');
+        }
+    }
+    clean.trim().to_string()
+}
+
+/// The HTML on the clipboard, if the copying app put any there.
+#[cfg(target_os = "macos")]
+fn clipboard_html() -> Result<Option<String>, String> {
+    use objc2_app_kit::{NSPasteboard, NSPasteboardTypeHTML};
+
+    // SAFETY: the pasteboard type is a constant AppKit exports.
+    let html = NSPasteboard::generalPasteboard().stringForType(unsafe { NSPasteboardTypeHTML });
+    Ok(html.map(|html| html.to_string()))
+}
+
+/// The HTML on the clipboard, if the copying app put any there. `HTML Format`
+/// starts with a header giving the byte offsets of the copied fragment.
+#[cfg(windows)]
+fn clipboard_html() -> Result<Option<String>, String> {
+    use windows::core::w;
+    use windows::Win32::Foundation::HGLOBAL;
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard, RegisterClipboardFormatW,
+    };
+    use windows::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+
+    // SAFETY: the clipboard is opened and closed around the read, and the
+    // locked memory is copied out before it is unlocked.
+    let bytes = unsafe {
+        let format = RegisterClipboardFormatW(w!("HTML Format"));
+        if format == 0 || IsClipboardFormatAvailable(format).is_err() {
+            return Ok(None);
+        }
+        OpenClipboard(None).map_err(|err| format!("Failed opening the clipboard: {err}"))?;
+        let bytes = GetClipboardData(format).ok().and_then(|handle| {
+            let memory = HGLOBAL(handle.0);
+            let data = GlobalLock(memory) as *const u8;
+            if data.is_null() {
+                return None;
+            }
+            let bytes = std::slice::from_raw_parts(data, GlobalSize(memory)).to_vec();
+            let _ = GlobalUnlock(memory);
+            Some(bytes)
+        });
+        let _ = CloseClipboard();
+        bytes
+    };
+    let Some(bytes) = bytes else {
+        return Ok(None);
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let offset = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().parse::<usize>().ok())
+    };
+    let fragment = match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) if start < end && end <= bytes.len() => String::from_utf8_lossy(&bytes[start..end]).to_string(),
+        _ => text.trim_end_matches('\0').to_string(),
+    };
+    Ok(Some(fragment))
+}
+
+/// The HTML on the clipboard, if the copying app put any there, read with the
+/// Wayland or X11 clipboard tool.
+#[cfg(all(desktop, not(any(target_os = "macos", windows))))]
+fn clipboard_html() -> Result<Option<String>, String> {
+    use std::process::{Command, Stdio};
+
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let (program, args): (&str, &[&str]) = match wayland {
+        true => ("wl-paste", &["--no-newline", "--type", "text/html"]),
+        false => ("xclip", &["-selection", "clipboard", "-target", "text/html", "-out"]),
+    };
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| format!("Failed to run {program}; install it to paste formatted text: {err}"))?;
+    // Both exit with an error when the clipboard has no HTML.
+    if !output.status.success() || output.stdout.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// The HTML on the clipboard as markdown, or `None` when there is no HTML
+/// and the plain text should be pasted as usual.
+#[tauri::command]
+pub async fn convert_clipboard_to_markdown() -> Result<Option<String>, String> {
+    let html = tauri::async_runtime::spawn_blocking(clipboard_html)
+        .await
+        .map_err(|err| format!("Clipboard task failed: {err}"))??;
+    Ok(html
+        .filter(|html| !html.trim().is_empty())
+        .map(|html| html_to_markdown(&html))
+        .filter(|markdown| !markdown.is_empty()))
+}
@@ -0,0 +1,394 @@
+//! Workspace sync through a cloud storage API.
+//!
+//! Providers (Dropbox, ...) only list changes, move files and report each
+//! file's revision; this module decides what moves where. The content and
+//! revision last agreed with the provider are kept per note under
+//! `.hermes/<provider>/`, along with the provider's change cursor, so a sync
+//! only fetches what changed remotely and only sends what changed locally.
+//! Notes changed on both sides go through the three-way merge and fall back to
+//! a conflicted copy of the remote version.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::keychain;
+use crate::{hermes_dir, ignore, merge, note_lock, note_path, read_project_names, validate_tab_key, workspace_lock, TAB_KEYS};
+
+/// A remote note that changed since the last cursor.
+pub struct RemoteChange {
+    /// `tab` or `project/tab`.
+    pub key: String,
+    /// The provider's revision or content hash; `None` when the file was deleted.
+    pub revision: Option<String>,
+}
+
+pub trait CloudProvider {
+    /// Lowercase id, used for the state folder.
+    fn id(&self) -> &'static str;
+    /// Display name, used in conflicted copy names.
+    fn name(&self) -> &'static str;
+    /// Changes since `cursor`, or every note when there is none, with the cursor to resume from.
+    fn changes(&mut self, cursor: Option<&str>) -> Result<(Vec<RemoteChange>, String), String>;
+    fn download(&mut self, key: &str) -> Result<String, String>;
+    /// Stores a note and returns its new revision.
+    fn upload(&mut self, key: &str, content: &str) -> Result<String, String>;
+    fn delete(&mut self, key: &str) -> Result<(), String>;
+    /// The revision the provider would report for `content`, if it can be computed locally.
+    fn content_revision(&self, _content: &str) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct SyncState {
+    cursor: Option<String>,
+    revisions: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncReport {
+    workspace_path: String,
+    /// The provider could not be reached; `pending` waits for the next attempt.
+    offline: bool,
+    pending: Vec<String>,
+    uploaded: Vec<String>,
+    downloaded: Vec<String>,
+    conflicts: Vec<String>,
+}
+
+impl CloudSyncReport {
+    /// The notes left in conflict, or why the sync could not finish.
+    pub fn outcome(&self) -> Result<Vec<String>, String> {
+        if self.offline {
+            return Err(format!("Offline; {} changes wait for the next sync.", self.pending.len()));
+        }
+        Ok(self.conflicts.clone())
+    }
+}
+
+/// A provider's OAuth tokens, kept in the keychain.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_unix: u64,
+}
+
+#[derive(Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_in: u64,
+}
+
+pub fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+impl OAuthTokens {
+    /// Tokens from a first authorization, which must include a refresh token.
+    pub fn granted(response: TokenResponse) -> Result<Self, String> {
+        Ok(OAuthTokens {
+            refresh_token: response
+                .refresh_token
+                .ok_or_else(|| "The provider did not grant offline access.".to_string())?,
+            access_token: response.access_token,
+            expires_unix: now_unix() + response.expires_in,
+        })
+    }
+
+    pub fn load(account: &str) -> Option<Self> {
+        serde_json::from_str(&keychain::load(account)?).ok()
+    }
+
+    pub fn save(&self, account: &str) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|err| format!("Failed encoding tokens: {err}"))?;
+        keychain::store(account, &json)
+    }
+
+    /// Whether the access token outlives the next minute, so a request never races the expiry.
+    pub fn is_fresh(&self) -> bool {
+        self.expires_unix > now_unix() + 60
+    }
+
+    pub fn refresh(&mut self, response: TokenResponse, account: &str) -> Result<(), String> {
+        self.access_token = response.access_token;
+        self.expires_unix = now_unix() + response.expires_in;
+        if let Some(refresh_token) = response.refresh_token {
+            self.refresh_token = refresh_token;
+        }
+        self.save(account)
+    }
+}
+
+/// POSTs a form, as OAuth token endpoints expect.
+pub fn post_form(client: &Client, url: &str, form: &[(&str, &str)]) -> Result<Response, String> {
+    let body = form
+        .iter()
+        .map(|(name, value)| format!("{name}={}", utf8_percent_encode(value, NON_ALPHANUMERIC)))
+        .collect::<Vec<_>>()
+        .join("&");
+    client
+        .post(url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .send()
+        .map_err(|err| format!("Could not reach {url}: {err}"))
+}
+
+/// Parses a path relative to the synced folder, like `Work/coral.md`, into a note key.
+pub fn key_for_path(relative: &str) -> Option<String> {
+    let stem = relative.trim_start_matches('/').strip_suffix(".md")?;
+    let (project, tab) = match stem.split_once('/') {
+        Some((project, tab)) => (Some(project), tab),
+        None => (None, stem),
+    };
+    validate_tab_key(tab).ok()?;
+    match project {
+        Some(project) if project.is_empty() || project.starts_with('.') || project.contains('/') => None,
+        Some(project) => Some(format!("{project}/{tab}")),
+        None => Some(tab.to_string()),
+    }
+}
+
+fn state_dir(workspace_path: &str, provider: &str) -> PathBuf {
+    hermes_dir(workspace_path).join(provider)
+}
+
+fn load_state(dir: &Path) -> SyncState {
+    fs::read_to_string(dir.join("state.json"))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(dir: &Path, state: &SyncState) -> Result<(), String> {
+    let json = serde_json::to_string(state).map_err(|err| format!("Failed encoding sync state: {err}"))?;
+    write(&dir.join("state.json"), &json)
+}
+
+/// Forgets the sync state, so the next sync starts from a full listing.
+pub fn reset(workspace_path: &str, provider: &str) {
+    let _ = fs::remove_dir_all(state_dir(workspace_path, provider));
+}
+
+fn local_file(workspace_path: &str, key: &str) -> PathBuf {
+    match key.split_once('/') {
+        Some((project, tab)) => note_path(&Path::new(workspace_path).join(project).to_string_lossy(), tab),
+        None => note_path(workspace_path, key),
+    }
+}
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    fs::write(path, content).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+fn base_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join("base").join(format!("{key}.md"))
+}
+
+fn set_base(dir: &Path, key: &str, content: Option<&str>) -> Result<(), String> {
+    match content {
+        Some(content) => write(&base_path(dir, key), content),
+        None => {
+            let _ = fs::remove_file(base_path(dir, key));
+            Ok(())
+        }
+    }
+}
+
+/// Whether a note may leave or enter this workspace.
+fn syncable(workspace_path: &str, key: &str) -> bool {
+    let file = local_file(workspace_path, key);
+    // Locked notes would sync as ciphertext the merge can't handle.
+    !ignore::is_ignored(&file) && !note_lock::is_locked_file(&file)
+}
+
+/// Local notes whose content differs from what the provider last had.
+fn local_changes(workspace_path: &str, dir: &Path) -> BTreeSet<String> {
+    let mut keys: Vec<String> = TAB_KEYS.iter().map(|tab| tab.to_string()).collect();
+    for project in read_project_names(workspace_path).unwrap_or_default() {
+        keys.extend(TAB_KEYS.iter().map(|tab| format!("{project}/{tab}")));
+    }
+    keys.into_iter()
+        .filter(|key| syncable(workspace_path, key))
+        .filter(|key| read(&local_file(workspace_path, key)) != read(&base_path(dir, key)))
+        .collect()
+}
+
+fn conflicted_copy_path(file: &Path, provider: &str) -> PathBuf {
+    let stem = file.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    file.with_file_name(format!("{stem} ({provider} conflicted copy).md"))
+}
+
+/// Pulls remote changes, merges them with local edits and pushes the result;
+/// `progress` hears how many notes of the current pass are done.
+pub fn sync(
+    workspace_path: &str,
+    provider: &mut dyn CloudProvider,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<CloudSyncReport, String> {
+    let dir = state_dir(workspace_path, provider.id());
+    let mut state = load_state(&dir);
+    let mut report = CloudSyncReport {
+        workspace_path: workspace_path.to_string(),
+        ..Default::default()
+    };
+
+    let (changes, cursor) = match provider.changes(state.cursor.as_deref()) {
+        Ok(changes) => changes,
+        Err(err) => {
+            tracing::warn!(provider = provider.id(), "{}", err);
+            report.offline = true;
+            report.pending = local_changes(workspace_path, &dir).into_iter().collect();
+            return Ok(report);
+        }
+    };
+
+    // Remote content of every note that really changed; `None` when deleted.
+    let mut remote: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let total = changes.len();
+    for (done, change) in changes.into_iter().enumerate() {
+        progress(done, total);
+        if !syncable(workspace_path, &change.key) {
+            continue;
+        }
+        let Some(revision) = change.revision else {
+            if state.revisions.contains_key(&change.key) || base_path(&dir, &change.key).exists() {
+                remote.insert(change.key, None);
+            }
+            continue;
+        };
+        // Our own uploads come back as changes.
+        if state.revisions.get(&change.key) == Some(&revision) {
+            continue;
+        }
+        let base = read(&base_path(&dir, &change.key));
+        let local = read(&local_file(workspace_path, &change.key));
+        let content = match (base, local) {
+            (Some(base), _) if provider.content_revision(&base).as_ref() == Some(&revision) => base,
+            (_, Some(local)) if provider.content_revision(&local).as_ref() == Some(&revision) => local,
+            _ => provider.download(&change.key)?,
+        };
+        state.revisions.insert(change.key.clone(), revision);
+        remote.insert(change.key, Some(content));
+    }
+
+    let mut keys = local_changes(workspace_path, &dir);
+    keys.extend(remote.keys().cloned());
+
+    let mut uploads: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let mut changed_folders = BTreeSet::new();
+    {
+        let _write_lock = workspace_lock::acquire(workspace_path)?;
+        for key in keys {
+            let file = local_file(workspace_path, &key);
+            let local = read(&file);
+            let base = read(&base_path(&dir, &key));
+            let remote = match remote.remove(&key) {
+                Some(remote) => remote,
+                None => base.clone(),
+            };
+
+            if local == remote {
+                set_base(&dir, &key, local.as_deref())?;
+                continue;
+            }
+            if remote == base {
+                uploads.insert(key, local);
+                continue;
+            }
+            changed_folders.insert(file.parent().map(Path::to_path_buf).unwrap_or_default());
+            if local == base {
+                match &remote {
+                    Some(content) => write(&file, content)?,
+                    None => {
+                        let _ = fs::remove_file(&file);
+                    }
+                }
+                set_base(&dir, &key, remote.as_deref())?;
+                report.downloaded.push(key);
+                continue;
+            }
+
+            // Changed on both sides; the remote side is now the base to upload over.
+            set_base(&dir, &key, remote.as_deref())?;
+            let kept = match (local, remote) {
+                (Some(local), Some(remote)) => {
+                    let outcome = merge::merge(base.as_deref().unwrap_or_default(), &local, &remote);
+                    match outcome.merged {
+                        Some(merged) => merged,
+                        None => {
+                            write(&conflicted_copy_path(&file, provider.name()), &remote)?;
+                            report.conflicts.push(key.clone());
+                            local
+                        }
+                    }
+                }
+                // Edited on one side, deleted on the other: keep the surviving edit.
+                (Some(content), None) | (None, Some(content)) => content,
+                (None, None) => continue,
+            };
+            write(&file, &kept)?;
+            uploads.insert(key, Some(kept));
+        }
+    }
+    // Remote changes are applied locally, so later syncs can resume after them.
+    state.cursor = Some(cursor);
+    save_state(&dir, &state)?;
+
+    // Push local changes; their bases only move once the provider has them.
+    let total = uploads.len();
+    let mut uploads = uploads.into_iter().enumerate();
+    for (done, (key, content)) in uploads.by_ref() {
+        progress(done, total);
+        let sent = match &content {
+            Some(content) => provider.upload(&key, content).map(Some),
+            None => provider.delete(&key).map(|_| None),
+        };
+        match sent {
+            Ok(revision) => {
+                match revision {
+                    Some(revision) => state.revisions.insert(key.clone(), revision),
+                    None => state.revisions.remove(&key),
+                };
+                set_base(&dir, &key, content.as_deref())?;
+                report.uploaded.push(key);
+            }
+            Err(err) => {
+                tracing::warn!(provider = provider.id(), "{}", err);
+                report.offline = true;
+                report.pending.push(key);
+                break;
+            }
+        }
+    }
+    report.pending.extend(uploads.map(|(_, (key, _))| key));
+    save_state(&dir, &state)?;
+
+    for folder in changed_folders {
+        if let Err(err) = crate::read_workspace_pages(&folder.to_string_lossy()) {
+            tracing::warn!(provider = provider.id(), "{}", String::from(err));
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,184 @@
+//! Conflicted copies left by sync services next to a tab file.
+//!
+//! Dropbox writes `coral (conflicted copy).md` / `coral (Jo's conflicted copy 2026-01-02).md`,
+//! iCloud Drive `coral 2.md`, and Syncthing `coral.sync-conflict-20260102-101010-ABC1234.md`.
+//! They are reported on load with both versions; resolving archives the copy in
+//! `.hermes/resolved-conflicts` instead of deleting it.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{deep_link, hermes_dir, note_path, notes_dir, notifications, tab_files, validate_tab_key, TAB_KEYS};
+
+pub const CONFLICT_EVENT: &str = "workspace://conflict";
+
+/// Conflicted copies already announced with a notification this session.
+#[derive(Default)]
+pub struct ReportedConflicts(Mutex<HashSet<PathBuf>>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    workspace_path: String,
+    tab: String,
+    conflict_path: String,
+    /// The tab file's current content.
+    ours: String,
+    /// The conflicted copy's content.
+    theirs: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Keep {
+    Ours,
+    Theirs,
+}
+
+/// The tab a conflicted-copy file name in `workspace_path` belongs to, if it is one.
+fn conflicted_tab(workspace_path: &str, file_name: &str) -> Option<&'static str> {
+    let stem = tab_files::note_stem(workspace_path, file_name)?;
+    // A tab named like another tab's conflicted copy is still a note.
+    if tab_files::tab_of(workspace_path, file_name).is_some() {
+        return None;
+    }
+    TAB_KEYS.iter().copied().find(|tab| {
+        let Some(rest) = stem.strip_prefix(tab_files::stem(workspace_path, tab).as_str()) else {
+            return false;
+        };
+        if rest.starts_with(".sync-conflict-") {
+            return true;
+        }
+        let Some(rest) = rest.strip_prefix(' ') else {
+            return false;
+        };
+        (rest.starts_with('(') && rest.ends_with(')') && rest.contains("conflicted copy"))
+            || (!rest.is_empty() && rest.chars().all(|ch| ch.is_ascii_digit()))
+    })
+}
+
+pub fn find_conflicts(workspace_path: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(notes_dir(workspace_path)) else {
+        return Vec::new();
+    };
+    let mut conflicts: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let tab = conflicted_tab(workspace_path, &name)?;
+            Some((tab.to_string(), entry.path()))
+        })
+        .collect();
+    conflicts.sort();
+    conflicts
+}
+
+/// Emits a conflict event for every conflicted copy in the workspace; the first
+/// sighting of each also raises a notification.
+pub fn check(app: &AppHandle, workspace_path: &str, pages: &HashMap<String, String>) {
+    let conflicts = find_conflicts(workspace_path);
+    if conflicts.is_empty() {
+        return;
+    }
+
+    let state = app.state::<ReportedConflicts>();
+    for (tab, conflict_path) in conflicts {
+        let theirs = match fs::read_to_string(&conflict_path) {
+            Ok(content) => content,
+            Err(err) => {
+                tracing::warn!("Failed reading {}: {err}", conflict_path.display());
+                continue;
+            }
+        };
+
+        if state.0.lock().unwrap().insert(conflict_path.clone()) {
+            let link = deep_link::get_note_deep_link(workspace_path.to_string(), tab.clone(), None).ok();
+            let body = format!("The {tab} tab has a conflicting copy from another device.");
+            if let Err(err) = notifications::notify(app, "Sync conflict", &body, link.as_deref()) {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        let conflict = SyncConflict {
+            workspace_path: workspace_path.to_string(),
+            ours: pages.get(&tab).cloned().unwrap_or_default(),
+            tab,
+            conflict_path: conflict_path.to_string_lossy().to_string(),
+            theirs,
+        };
+        if let Err(err) = app.emit(CONFLICT_EVENT, conflict) {
+            tracing::warn!("Failed to emit event: {err}");
+        }
+    }
+}
+
+/// Checks that `conflict_path` is a conflicted copy of `tab_key` inside the workspace.
+fn validated_conflict(workspace_path: &str, tab_key: &str, conflict_path: &str) -> Result<PathBuf, String> {
+    validate_tab_key(tab_key)?;
+    let path = PathBuf::from(conflict_path);
+    let in_workspace = path.parent() == Some(notes_dir(workspace_path).as_path());
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if !in_workspace || conflicted_tab(workspace_path, name) != Some(tab_key) {
+        return Err(format!("{conflict_path} is not a conflicted copy of the {tab_key} tab"));
+    }
+    if !path.is_file() {
+        return Err(format!("{conflict_path} no longer exists"));
+    }
+    Ok(path)
+}
+
+fn archive(app: &AppHandle, workspace_path: &str, conflict: &Path) -> Result<(), String> {
+    let dir = hermes_dir(workspace_path).join("resolved-conflicts");
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let name = conflict.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let target = dir.join(format!("{stamp} {name}"));
+    fs::rename(conflict, &target)
+        .map_err(|err| format!("Failed moving {} to {}: {err}", conflict.display(), target.display()))?;
+    app.state::<ReportedConflicts>().0.lock().unwrap().remove(conflict);
+    Ok(())
+}
+
+fn write_tab(workspace_path: &str, tab_key: &str, content: &str) -> Result<(), String> {
+    let target = note_path(workspace_path, tab_key);
+    fs::write(&target, content).map_err(|err| format!("Failed writing {}: {err}", target.display()))
+}
+
+/// Keeps one side of a conflict; the frontend reloads the workspace afterwards.
+#[tauri::command]
+pub fn resolve_conflict(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    conflict_path: String,
+    keep: Keep,
+) -> Result<(), String> {
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let conflict = validated_conflict(&workspace_path, &tab_key, &conflict_path)?;
+    if let Keep::Theirs = keep {
+        let theirs = fs::read_to_string(&conflict)
+            .map_err(|err| format!("Failed reading {}: {err}", conflict.display()))?;
+        write_tab(&workspace_path, &tab_key, &theirs)?;
+    }
+    archive(&app, &workspace_path, &conflict)
+}
+
+/// Replaces the tab with `merged` content and archives the conflicted copy.
+#[tauri::command]
+pub fn merge_conflict(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    conflict_path: String,
+    merged: String,
+) -> Result<(), String> {
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let conflict = validated_conflict(&workspace_path, &tab_key, &conflict_path)?;
+    write_tab(&workspace_path, &tab_key, &merged)?;
+    archive(&app, &workspace_path, &conflict)
+}
@@ -0,0 +1,139 @@
+//! Crash reports written when Hermes panics, offered to the user next launch.
+//!
+//! A panic hook writes each report to `crash-reports/` in app data before the
+//! process aborts; reports stay pending until `dismiss_crash_reports` removes
+//! them, and diagnostics bundles include them. Only Rust panics are caught:
+//! native crashes (webview, signals) would need a minidump handler, which the
+//! build doesn't ship.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const CRASH_REPORTS_DIR: &str = "crash-reports";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    /// File name of the report, used to dismiss it.
+    #[serde(default)]
+    id: String,
+    crashed_unix: u64,
+    app_version: String,
+    os: String,
+    thread: String,
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+}
+
+/// Where crash reports are kept; `None` when app data can't be resolved.
+pub fn reports_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join(CRASH_REPORTS_DIR))
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    let path = dir.join(&report.id);
+    let json = serde_json::to_vec_pretty(report).map_err(|err| format!("Failed encoding crash report: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+/// Installs a panic hook that saves a report, then runs the previous hook.
+pub fn init(app: &AppHandle) {
+    let Some(dir) = reports_dir(app) else {
+        return;
+    };
+    let app_version = app.package_info().version.to_string();
+    let os = format!("{} {} {}", tauri_plugin_os::type_(), tauri_plugin_os::version(), tauri_plugin_os::arch());
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let crashed_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let report = CrashReport {
+            id: format!("crash-{crashed_unix}-{}.json", std::process::id()),
+            crashed_unix,
+            app_version: app_version.clone(),
+            os: os.clone(),
+            thread: thread::current().name().unwrap_or("unnamed").to_string(),
+            message: panic_message(info),
+            location: info.location().map(|location| location.to_string()),
+            backtrace: Backtrace::force_capture().to_string(),
+        };
+        tracing::error!("Panic on thread {}: {}", report.thread, report.message);
+        if let Err(err) = write_report(&dir, &report) {
+            eprintln!("[crash-reports] {err}");
+        }
+        previous(info);
+    }));
+}
+
+/// Crash reports from earlier sessions the user hasn't dismissed, newest first.
+#[tauri::command]
+pub fn get_pending_crash_reports(app: AppHandle) -> Vec<CrashReport> {
+    let Some(dir) = reports_dir(&app) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<CrashReport> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let text = fs::read_to_string(entry.path()).ok()?;
+            let mut report: CrashReport = serde_json::from_str(&text).ok()?;
+            report.id = id;
+            Some(report)
+        })
+        .collect();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.crashed_unix));
+    reports
+}
+
+/// Deletes the given crash reports, or all of them when `ids` is `None`.
+#[tauri::command]
+pub fn dismiss_crash_reports(app: AppHandle, ids: Option<Vec<String>>) -> Result<(), String> {
+    let Some(dir) = reports_dir(&app) else {
+        return Ok(());
+    };
+    let ids = match ids {
+        Some(ids) => ids,
+        None => fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect(),
+    };
+    for id in ids {
+        // Ids are bare file names; anything else could reach outside the folder.
+        if id.contains(['/', '\\']) || id.starts_with('.') {
+            return Err(format!("Invalid crash report id {id}"));
+        }
+        let path = dir.join(&id);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("Failed removing {}: {err}", path.display())),
+        }
+    }
+    Ok(())
+}
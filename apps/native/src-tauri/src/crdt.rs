@@ -0,0 +1,204 @@
+//! CRDT shadow documents for tabs.
+//!
+//! Each tab has a Yjs document in `.hermes/crdt/<tab>.yrs` whose text is kept
+//! equal to the markdown file: every save (and every external change noticed
+//! on load) is applied as character-level insert/delete operations, so updates
+//! exchanged between devices merge without conflicts. The markdown file stays
+//! the source of truth users see; it is rewritten from the document after a
+//! remote update is merged.
+
+use std::fs;
+use std::path::PathBuf;
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use tauri::AppHandle;
+use yrs::updates::decoder::Decode;
+use yrs::{Doc, GetString, ReadTxn, StateVector, Text, Transact, Update};
+
+use crate::{hermes_dir, note_path, validate_tab_key};
+
+const TEXT_NAME: &str = "markdown";
+
+fn doc_path(workspace_path: &str, tab: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("crdt").join(format!("{tab}.yrs"))
+}
+
+fn load_doc(workspace_path: &str, tab: &str) -> Result<Doc, String> {
+    let doc = Doc::new();
+    let path = doc_path(workspace_path, tab);
+    if path.exists() {
+        let bytes = fs::read(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+        apply_update(&doc, &bytes)?;
+    }
+    Ok(doc)
+}
+
+fn save_doc(workspace_path: &str, tab: &str, doc: &Doc) -> Result<(), String> {
+    let path = doc_path(workspace_path, tab);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    let state = doc.transact().encode_state_as_update_v1(&StateVector::default());
+    fs::write(&path, state).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+fn apply_update(doc: &Doc, bytes: &[u8]) -> Result<(), String> {
+    let update = Update::decode_v1(bytes).map_err(|err| format!("Invalid CRDT update: {err}"))?;
+    doc.transact_mut()
+        .apply_update(update)
+        .map_err(|err| format!("Failed applying CRDT update: {err}"))
+}
+
+/// Byte offset of every char boundary, including the end of the string.
+fn char_offsets(text: &str) -> Vec<u32> {
+    text.char_indices()
+        .map(|(offset, _)| offset as u32)
+        .chain(std::iter::once(text.len() as u32))
+        .collect()
+}
+
+/// Turns the document text into `content` with minimal character edits.
+fn apply_edit(doc: &Doc, content: &str) {
+    let text = doc.get_or_insert_text(TEXT_NAME);
+    let mut txn = doc.transact_mut();
+    let current = text.get_string(&txn);
+    if current == content {
+        return;
+    }
+
+    let old_chars: Vec<char> = current.chars().collect();
+    let new_chars: Vec<char> = content.chars().collect();
+    let old_offsets = char_offsets(&current);
+    let new_offsets = char_offsets(content);
+
+    // Offsets are in bytes (the document's default) and shift as edits are applied.
+    let mut position = 0;
+    for op in capture_diff_slices(Algorithm::Myers, &old_chars, &new_chars) {
+        match op {
+            DiffOp::Equal { old_index, len, .. } => {
+                position += old_offsets[old_index + len] - old_offsets[old_index];
+            }
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => {
+                text.remove_range(&mut txn, position, old_offsets[old_index + old_len] - old_offsets[old_index]);
+            }
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => {
+                let inserted = &content[new_offsets[new_index] as usize..new_offsets[new_index + new_len] as usize];
+                text.insert(&mut txn, position, inserted);
+                position += inserted.len() as u32;
+            }
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                text.remove_range(&mut txn, position, old_offsets[old_index + old_len] - old_offsets[old_index]);
+                let inserted = &content[new_offsets[new_index] as usize..new_offsets[new_index + new_len] as usize];
+                text.insert(&mut txn, position, inserted);
+                position += inserted.len() as u32;
+            }
+        }
+    }
+}
+
+/// Records the tab's current markdown in its CRDT document (best effort).
+pub fn record(workspace_path: &str, tab: &str, content: &str) {
+    let result = load_doc(workspace_path, tab).and_then(|doc| {
+        apply_edit(&doc, content);
+        save_doc(workspace_path, tab, &doc)
+    });
+    if let Err(err) = result {
+        tracing::warn!("{}", err);
+    }
+}
+
+pub fn remove(workspace_path: &str, tab: &str) {
+    let _ = fs::remove_file(doc_path(workspace_path, tab));
+}
+
+fn doc_text(doc: &Doc) -> String {
+    doc.get_or_insert_text(TEXT_NAME).get_string(&doc.transact())
+}
+
+/// Text of a document encoded as a full-state update.
+pub fn render(update: &[u8]) -> Result<String, String> {
+    let doc = Doc::new();
+    apply_update(&doc, update)?;
+    Ok(doc_text(&doc))
+}
+
+/// Whether merging `update` into the tab is safe. Documents created
+/// independently from the same markdown share no operations, so merging them
+/// would duplicate the text.
+pub fn shares_history(workspace_path: &str, tab: &str, update: &[u8]) -> Result<bool, String> {
+    let remote = Update::decode_v1(update)
+        .map_err(|err| format!("Invalid CRDT update: {err}"))?
+        .state_vector();
+    let local_doc = load_doc(workspace_path, tab)?;
+    let local = local_doc.transact().state_vector();
+
+    if local.is_empty() {
+        let file_path = note_path(workspace_path, tab);
+        let on_disk = fs::read_to_string(&file_path).unwrap_or_default();
+        return Ok(on_disk.is_empty() || remote.is_empty());
+    }
+    Ok(remote.is_empty() || remote.iter().any(|(client, _)| local.contains_client(client)))
+}
+
+/// Replaces the tab's document and markdown with `update`, discarding local history.
+pub fn replace(workspace_path: &str, tab: &str, update: &[u8]) -> Result<String, String> {
+    let doc = Doc::new();
+    apply_update(&doc, update)?;
+    save_doc(workspace_path, tab, &doc)?;
+
+    let content = doc_text(&doc);
+    let file_path = note_path(workspace_path, tab);
+    fs::write(&file_path, &content).map_err(|err| format!("Failed writing {}: {err}", file_path.display()))?;
+    Ok(content)
+}
+
+/// Full document state, for sending to another device.
+pub fn state(workspace_path: &str, tab_key: &str) -> Result<Vec<u8>, String> {
+    validate_tab_key(tab_key)?;
+    let doc = load_doc(workspace_path, tab_key)?;
+    let state = doc.transact().encode_state_as_update_v1(&StateVector::default());
+    Ok(state)
+}
+
+#[tauri::command]
+pub fn get_note_crdt_state(app: AppHandle, workspace_path: String, tab_key: String) -> Result<Vec<u8>, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    state(&workspace_path, &tab_key)
+}
+
+/// Merges an update from another device and rewrites the markdown projection.
+#[tauri::command]
+pub fn merge_note_crdt_update(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    update: Vec<u8>,
+) -> Result<String, String> {
+    validate_tab_key(&tab_key)?;
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let doc = load_doc(&workspace_path, &tab_key)?;
+
+    // Fold in any on-disk edit the document hasn't seen before merging.
+    let file_path = note_path(&workspace_path, &tab_key);
+    if file_path.exists() {
+        let content =
+            fs::read_to_string(&file_path).map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?;
+        apply_edit(&doc, &content);
+    }
+
+    apply_update(&doc, &update)?;
+    save_doc(&workspace_path, &tab_key, &doc)?;
+
+    let merged = doc_text(&doc);
+    fs::write(&file_path, &merged).map_err(|err| format!("Failed writing {}: {err}", file_path.display()))?;
+    Ok(merged)
+}
@@ -0,0 +1,122 @@
+//! `hermes://workspace/<id>/note/<tab>?line=42` deep links.
+//!
+//! `<id>` is the percent-encoded workspace path handed to `load_workspace_pages`,
+//! so a link survives app restarts without any extra registry.
+
+use std::sync::Mutex;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+use crate::file_open::{self, OpenFileTarget};
+use crate::{focus_main_window, note_path, validate_tab_key};
+
+pub const SCHEME: &str = "hermes";
+pub const NAVIGATE_EVENT: &str = "navigate-to-note";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteLink {
+    /// Workspace root the frontend should switch to.
+    pub workspace_path: String,
+    /// Project folder inside the workspace, when the link points into one.
+    pub project: Option<String>,
+    pub tab: String,
+    pub line: Option<u32>,
+}
+
+pub struct PendingNavigation(pub Mutex<Vec<NoteLink>>);
+
+/// Splits a link into workspace path, tab and optional line.
+pub fn parse(url: &Url) -> Result<(String, String, Option<u32>), String> {
+    if url.scheme() != SCHEME || url.host_str() != Some("workspace") {
+        return Err(format!("Not a Hermes note link: {url}"));
+    }
+
+    let segments: Vec<&str> = url.path_segments().map(|segments| segments.collect()).unwrap_or_default();
+    let [id, "note", tab] = segments.as_slice() else {
+        return Err(format!("Expected hermes://workspace/<id>/note/<tab>, got {url}"));
+    };
+
+    let workspace_path = percent_decode_str(id)
+        .decode_utf8()
+        .map_err(|err| format!("Invalid workspace id in {url}: {err}"))?
+        .to_string();
+    validate_tab_key(tab)?;
+
+    let line = url
+        .query_pairs()
+        .find(|(key, _)| key == "line")
+        .and_then(|(_, value)| value.parse().ok());
+
+    Ok((workspace_path, tab.to_string(), line))
+}
+
+fn resolve(app: &AppHandle, url: &Url) -> Result<NoteLink, String> {
+    let (workspace_path, tab, line) = parse(url)?;
+    let configured = file_open::configured_workspace(app);
+
+    // Links into a project of the configured workspace switch projects, anything
+    // else switches the whole workspace.
+    let link = match file_open::resolve(&note_path(&workspace_path, &tab), configured.as_deref()) {
+        OpenFileTarget::Note {
+            workspace_path,
+            project,
+            ..
+        } => NoteLink {
+            workspace_path,
+            project,
+            tab,
+            line,
+        },
+        OpenFileTarget::External { .. } => NoteLink {
+            workspace_path,
+            project: None,
+            tab,
+            line,
+        },
+    };
+    Ok(link)
+}
+
+/// Queues navigation for every valid link, focuses the window and notifies the frontend.
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    let mut links = Vec::new();
+    for url in urls {
+        match resolve(app, &url) {
+            Ok(link) => links.push(link),
+            Err(err) => tracing::warn!("{}", err),
+        }
+    }
+
+    if links.is_empty() {
+        return;
+    }
+
+    let state = app.state::<PendingNavigation>();
+    state.0.lock().unwrap().extend(links.iter().cloned());
+
+    focus_main_window(app);
+    for link in links {
+        if let Err(err) = app.emit(NAVIGATE_EVENT, link) {
+            tracing::warn!("Failed to emit event: {err}");
+        }
+    }
+}
+
+#[tauri::command]
+pub fn take_pending_navigation(state: tauri::State<'_, PendingNavigation>) -> Vec<NoteLink> {
+    std::mem::take(&mut *state.0.lock().unwrap())
+}
+
+#[tauri::command]
+pub fn get_note_deep_link(workspace_path: String, tab_key: String, line: Option<u32>) -> Result<String, String> {
+    validate_tab_key(&tab_key)?;
+    let id = utf8_percent_encode(workspace_path.trim_end_matches('/'), NON_ALPHANUMERIC);
+    let mut link = format!("{SCHEME}://workspace/{id}/note/{tab_key}");
+    if let Some(line) = line {
+        link.push_str(&format!("?line={line}"));
+    }
+    Ok(link)
+}
@@ -0,0 +1,45 @@
+//! Identity of this Hermes install, shared by every sync transport.
+
+use rand::RngCore;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::AppHandle;
+
+const DEVICE_ID_SETTING: &str = "deviceId";
+/// Where LAN sync kept the ID before it was shared; reused so pairings survive.
+const LEGACY_DEVICE_ID_SETTING: &str = "lanSyncDeviceId";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    device_id: String,
+    name: String,
+}
+
+/// Random ID generated on first use and kept in the settings store.
+pub fn id(app: &AppHandle) -> Result<String, String> {
+    let stored = |key| crate::read_setting(app, key).and_then(|value| value.as_str().map(str::to_string));
+    if let Some(id) = stored(DEVICE_ID_SETTING) {
+        return Ok(id);
+    }
+
+    let id = stored(LEGACY_DEVICE_ID_SETTING).unwrap_or_else(|| {
+        let mut bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    });
+    crate::write_setting(app, DEVICE_ID_SETTING, Value::String(id.clone()))?;
+    Ok(id)
+}
+
+pub fn name() -> String {
+    tauri_plugin_os::hostname()
+}
+
+#[tauri::command]
+pub fn get_device_info(app: AppHandle) -> Result<DeviceInfo, String> {
+    Ok(DeviceInfo {
+        device_id: id(&app)?,
+        name: name(),
+    })
+}
@@ -0,0 +1,230 @@
+//! A zip of everything support needs to look into a bug report.
+//!
+//! The bundle holds the app and server logs, pending crash reports, the
+//! settings with every string value redacted, the schema and row counts of the
+//! workspace's indexes, and platform details. Note contents never go in: indexes are described through
+//! `sqlite_master` and `COUNT(*)` only, and no markdown file is read.
+
+#[cfg(desktop)]
+use std::fs::OpenOptions;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::progress::Progress;
+use crate::{
+    crash_reports, file_open, index_compaction, read_project_names, run_sqlite_query, sqlite_path, workspace_health,
+    SETTINGS_STORE_FILE,
+};
+
+#[cfg(desktop)]
+pub const SERVER_LOG_FILE: &str = "hermes-server.log";
+/// Logs are cut to their last `MAX_LOG_BYTES` in the bundle, and the server
+/// log starts over once it grows past it.
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+
+const README: &str = "Hermes diagnostics bundle.\n\n\
+    logs/          app and server logs, most recent part only\n\
+    crashes/       crash reports not yet dismissed\n\
+    settings.json  settings with all text values redacted\n\
+    indexes.json   schema and row counts of the workspace indexes\n\
+    system.json    app, OS and sqlite versions\n\n\
+    No note contents are included.\n";
+
+fn log_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_log_dir().ok()
+}
+
+/// Opens the server's log for appending, starting it over when it is too large.
+#[cfg(desktop)]
+pub fn open_server_log(app: &AppHandle) -> Option<File> {
+    let dir = log_dir(app)?;
+    fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(SERVER_LOG_FILE);
+    let oversized = fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_LOG_BYTES);
+    OpenOptions::new()
+        .create(true)
+        .append(!oversized)
+        .write(true)
+        .truncate(oversized)
+        .open(&path)
+        .map_err(|err| tracing::warn!("Failed opening {}: {err}", path.display()))
+        .ok()
+}
+
+/// Appends one line of server output to its log, if it could be opened.
+#[cfg(desktop)]
+pub fn log_server_line(log: &mut Option<File>, line: &str) {
+    if let Some(file) = log.as_mut() {
+        if writeln!(file, "{}", line.trim_end()).is_err() {
+            *log = None;
+        }
+    }
+}
+
+/// The last `MAX_LOG_BYTES` of a log file.
+fn log_tail(path: &Path) -> Result<Vec<u8>, String> {
+    let mut file = File::open(path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+    let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    file.seek(SeekFrom::Start(len.saturating_sub(MAX_LOG_BYTES)))
+        .map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)
+        .map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+    Ok(tail)
+}
+
+/// Keeps the shape of a setting but replaces its text, which may hold paths,
+/// hosts or account names.
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::String(_) => Value::String("<redacted>".to_string()),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        Value::Object(fields) => Value::Object(fields.iter().map(|(key, value)| (key.clone(), redact(value))).collect()),
+        other => other.clone(),
+    }
+}
+
+fn sanitized_settings(app: &AppHandle) -> Value {
+    let Ok(store) = app.store(SETTINGS_STORE_FILE) else {
+        return Value::Object(Map::new());
+    };
+    Value::Object(store.entries().iter().map(|(key, value)| (key.clone(), redact(value))).collect())
+}
+
+/// Schema, pragmas and row counts of one index; nothing from the notes themselves.
+fn index_info(folder: &str) -> Value {
+    let db_path = sqlite_path(folder);
+    if !db_path.exists() {
+        return json!({ "exists": false });
+    }
+    let query = |sql: &str| run_sqlite_query(&db_path, sql).unwrap_or_else(|err| vec![json!({ "error": err })]);
+    let tables = query("SELECT type, name, sql FROM sqlite_master ORDER BY name;");
+    let mut counts = Map::new();
+    for row in &tables {
+        if row.get("type").and_then(|value| value.as_str()) != Some("table") {
+            continue;
+        }
+        let Some(name) = row.get("name").and_then(|value| value.as_str()) else {
+            continue;
+        };
+        // FTS shadow tables hold note text under names SQLite picks; count the known ones only.
+        if name.starts_with("note_fts_") || name.starts_with("sqlite_") {
+            continue;
+        }
+        let count = query(&format!("SELECT COUNT(*) AS count FROM \"{name}\";"))
+            .first()
+            .and_then(|row| row.get("count").cloned())
+            .unwrap_or(Value::Null);
+        counts.insert(name.to_string(), count);
+    }
+    json!({
+        "exists": true,
+        "bytes": index_compaction::index_size(&db_path),
+        "pragmas": query("SELECT * FROM pragma_page_size, pragma_journal_mode, pragma_user_version;"),
+        "schema": tables,
+        "rowCounts": counts,
+    })
+}
+
+/// Index details for the configured workspace and each of its projects, which
+/// are numbered rather than named.
+fn indexes(app: &AppHandle) -> Value {
+    let Some(workspace) = file_open::configured_workspace(app) else {
+        return json!({ "workspace": null });
+    };
+    let root = workspace.to_string_lossy().to_string();
+    let projects: Vec<Value> = read_project_names(&root)
+        .unwrap_or_default()
+        .iter()
+        .map(|project| index_info(&workspace.join(project).to_string_lossy()))
+        .collect();
+    json!({
+        "workspace": index_info(&root),
+        "projects": projects,
+    })
+}
+
+fn system(app: &AppHandle) -> Value {
+    let package = app.package_info();
+    json!({
+        "appName": package.name,
+        "appVersion": package.version.to_string(),
+        "tauriVersion": tauri::VERSION,
+        "os": tauri_plugin_os::type_().to_string(),
+        "osVersion": tauri_plugin_os::version().to_string(),
+        "platform": tauri_plugin_os::platform(),
+        "family": tauri_plugin_os::family(),
+        "arch": tauri_plugin_os::arch(),
+        "locale": tauri_plugin_os::locale(),
+        "sqlite": workspace_health::sqlite_version().unwrap_or_else(|err| err),
+        "debugBuild": cfg!(debug_assertions),
+    })
+}
+
+/// Steps reported while bundling: system, settings, indexes, logs, crashes.
+const BUNDLE_STEPS: usize = 5;
+
+fn write_bundle(app: &AppHandle, dest: &Path, progress: &Progress) -> Result<(), String> {
+    let file = File::create(dest).map_err(|err| format!("Failed creating {}: {err}", dest.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options)
+            .and_then(|_| zip.write_all(bytes).map_err(Into::into))
+            .map_err(|err| format!("Failed writing {name} to {}: {err}", dest.display()))
+    };
+
+    add("README.txt", README.as_bytes())?;
+    let mut add_json = |name: &str, value: Value| -> Result<(), String> {
+        let text = serde_json::to_string_pretty(&value).map_err(|err| format!("Failed encoding {name}: {err}"))?;
+        add(name, text.as_bytes())
+    };
+    progress.report("system", 0, BUNDLE_STEPS);
+    add_json("system.json", system(app))?;
+    progress.report("settings", 1, BUNDLE_STEPS);
+    add_json("settings.json", sanitized_settings(app))?;
+    progress.report("indexes", 2, BUNDLE_STEPS);
+    add_json("indexes.json", indexes(app))?;
+    progress.report("logs", 3, BUNDLE_STEPS);
+    if let Some(dir) = log_dir(app) {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().is_none_or(|extension| extension != "log") {
+                continue;
+            }
+            match log_tail(&path) {
+                Ok(tail) => add(&format!("logs/{}", entry.file_name().to_string_lossy()), &tail)?,
+                Err(err) => tracing::warn!("{}", err),
+            }
+        }
+    }
+    progress.report("crashes", 4, BUNDLE_STEPS);
+    if let Some(dir) = crash_reports::reports_dir(app) {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            match fs::read(entry.path()) {
+                Ok(report) => add(&format!("crashes/{}", entry.file_name().to_string_lossy()), &report)?,
+                Err(err) => tracing::warn!("Failed reading {}: {err}", entry.path().display()),
+            }
+        }
+    }
+    zip.finish()
+        .map_err(|err| format!("Failed writing {}: {err}", dest.display()))?;
+    progress.finish(BUNDLE_STEPS);
+    Ok(())
+}
+
+/// Writes a diagnostics zip for bug reports to `dest`; note contents are never included.
+#[tauri::command]
+pub async fn export_diagnostics(app: AppHandle, dest: String, operation_id: Option<String>) -> Result<(), String> {
+    let progress = Progress::new(&app, "export", operation_id);
+    tauri::async_runtime::spawn_blocking(move || write_bundle(&app, Path::new(&dest), &progress))
+        .await
+        .map_err(|err| format!("Diagnostics task failed: {err}"))?
+}
@@ -0,0 +1,384 @@
+//! Local dictation through whisper.cpp.
+//!
+//! Speech never leaves the machine: whisper.cpp's `whisper-stream` listens to
+//! the microphone and `whisper-cli` transcribes recordings, both run as child
+//! processes against a ggml model kept under `<app data>/whisper`. Hermes
+//! doesn't bundle whisper.cpp; it runs the binaries in the folder the
+//! `dictation` setting names, else the first found on `PATH` or, on macOS, in
+//! Homebrew's folders. Models are downloaded from the whisper.cpp repository
+//! on Hugging Face when the user picks one.
+//!
+//! `whisper-stream` runs with voice activity detection, so it prints a segment
+//! once the speaker pauses instead of revising a sliding window; each segment
+//! becomes a `dictation://segment` event.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use hermes_core::programs::find_program;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::progress::Progress;
+
+pub const SEGMENT_EVENT: &str = "dictation://segment";
+pub const STOPPED_EVENT: &str = "dictation://stopped";
+
+const DICTATION_SETTING: &str = "dictation";
+const MODELS_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+/// Models offered for download, with their rough size in megabytes.
+const MODELS: [(&str, u32); 8] = [
+    ("tiny.en", 75),
+    ("tiny", 75),
+    ("base.en", 142),
+    ("base", 142),
+    ("small.en", 466),
+    ("small", 466),
+    ("medium", 1_500),
+    ("large-v3-turbo", 1_620),
+];
+const DEFAULT_MODEL: &str = "base.en";
+
+/// The running `whisper-stream`, if dictation is listening.
+static LISTENER: Mutex<Option<Child>> = Mutex::new(None);
+/// Bumped whenever listening starts or stops, so a stream that is still
+/// winding down doesn't report into the next one.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Settings {
+    model: Option<String>,
+    /// A whisper language code, or `auto` to detect it.
+    language: Option<String>,
+    /// Folder holding `whisper-cli` and `whisper-stream`.
+    whisper_folder: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhisperModel {
+    name: &'static str,
+    approx_mb: u32,
+    installed: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictationStatus {
+    /// Whether the whisper.cpp binaries were found.
+    available: bool,
+    whisper_folder: Option<String>,
+    model: String,
+    language: String,
+    models: Vec<WhisperModel>,
+    listening: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct Segment {
+    text: String,
+}
+
+fn read(app: &AppHandle) -> Settings {
+    crate::read_setting(app, DICTATION_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("whisper"))
+        .map_err(|err| format!("Failed resolving app data folder: {err}"))
+}
+
+fn check_model(name: &str) -> Result<&'static str, String> {
+    MODELS
+        .iter()
+        .map(|(model, _)| *model)
+        .find(|model| *model == name)
+        .ok_or_else(|| format!("Unknown whisper model '{name}'"))
+}
+
+fn model_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    Ok(models_dir(app)?.join(format!("ggml-{}.bin", check_model(name)?)))
+}
+
+fn binary(app: &AppHandle, name: &str) -> Option<PathBuf> {
+    find_program(read(app).whisper_folder.map(PathBuf::from), name)
+}
+
+/// The configured model and language, checking the model is downloaded.
+fn model_and_language(app: &AppHandle) -> Result<(PathBuf, String), String> {
+    let settings = read(app);
+    let name = settings.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let path = model_path(app, &name)?;
+    if !path.is_file() {
+        return Err(format!("Download the {name} whisper model first."));
+    }
+    // English-only models reject any other language.
+    let language = match name.ends_with(".en") {
+        true => "en".to_string(),
+        false => settings.language.unwrap_or_else(|| "auto".to_string()),
+    };
+    Ok((path, language))
+}
+
+fn threads() -> String {
+    thread::available_parallelism()
+        .map(|count| count.get().min(8))
+        .unwrap_or(4)
+        .to_string()
+}
+
+/// The spoken text of one line of whisper output, without terminal escapes,
+/// timestamps, headers or markers such as `[BLANK_AUDIO]`.
+fn segment_text(line: &str) -> Option<String> {
+    let mut plain = String::new();
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\u{1b}' {
+            // Skip a CSI sequence up to its final letter.
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if ch != '\r' {
+            plain.push(ch);
+        }
+    }
+    let mut text = plain.trim();
+    if text.starts_with("###") || text.starts_with("[Start speaking]") {
+        return None;
+    }
+    if text.starts_with('[') && text.contains("-->") {
+        text = text.split_once(']').map_or("", |(_, rest)| rest).trim();
+    }
+    let marker = |open: char, close: char| text.starts_with(open) && text.ends_with(close);
+    if text.is_empty() || marker('[', ']') || marker('(', ')') || marker('*', '*') {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+/// Whether recordings can be transcribed: whisper-cli is found and the model is downloaded.
+pub fn is_available(app: &AppHandle) -> bool {
+    binary(app, "whisper-cli").is_some() && model_and_language(app).is_ok()
+}
+
+/// Transcribes an audio file (WAV, MP3, FLAC or Ogg) with `whisper-cli`.
+pub fn transcribe(app: &AppHandle, audio: &Path) -> Result<String, String> {
+    let cli = binary(app, "whisper-cli").ok_or_else(|| "whisper.cpp's whisper-cli was not found.".to_string())?;
+    let (model, language) = model_and_language(app)?;
+    let output = Command::new(cli)
+        .arg("-m")
+        .arg(&model)
+        .arg("-f")
+        .arg(audio)
+        .args(["-l", &language, "-t", &threads(), "--no-timestamps", "--no-prints"])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("Failed to run whisper-cli: {err}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        return Err(format!("whisper-cli failed on {}: {}", audio.display(), reason.trim()));
+    }
+    let text = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(segment_text)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(text)
+}
+
+fn download(app: &AppHandle, name: &str, progress: &Progress) -> Result<(), String> {
+    let target = model_path(app, name)?;
+    let dir = models_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+
+    // reqwest is built without a bundled TLS provider; reuse the updater's ring backend.
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+    // Models run to gigabytes, so only connecting is timed.
+    let client = Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .timeout(None)
+        .build()
+        .map_err(|err| format!("Failed creating HTTP client: {err}"))?;
+    let url = format!("{MODELS_URL}/ggml-{name}.bin");
+    let mut response = client
+        .get(&url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Failed downloading the {name} model: {err}"))?;
+    let total = response.content_length().unwrap_or(0) as usize;
+
+    let partial = target.with_extension("bin.part");
+    let mut file = File::create(&partial).map_err(|err| format!("Failed creating {}: {err}", partial.display()))?;
+    let mut buffer = vec![0; 1024 * 1024];
+    let mut done = 0;
+    loop {
+        let read = response
+            .read(&mut buffer)
+            .map_err(|err| format!("Failed downloading the {name} model: {err}"))?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])
+            .map_err(|err| format!("Failed writing {}: {err}", partial.display()))?;
+        done += read;
+        progress.report("downloading", done, total.max(done));
+    }
+    drop(file);
+    if total > 0 && done != total {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("The {name} model download stopped early."));
+    }
+    fs::rename(&partial, &target).map_err(|err| format!("Failed moving {} into place: {err}", target.display()))?;
+    progress.finish(done);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_dictation(app: AppHandle) -> DictationStatus {
+    let settings = read(&app);
+    let installed = |name: &str| model_path(&app, name).is_ok_and(|path| path.is_file());
+    DictationStatus {
+        available: binary(&app, "whisper-cli").is_some() && binary(&app, "whisper-stream").is_some(),
+        whisper_folder: settings.whisper_folder,
+        model: settings.model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        language: settings.language.unwrap_or_else(|| "auto".to_string()),
+        models: MODELS
+            .iter()
+            .map(|(name, approx_mb)| WhisperModel {
+                name,
+                approx_mb: *approx_mb,
+                installed: installed(name),
+            })
+            .collect(),
+        listening: LISTENER.lock().unwrap().is_some(),
+    }
+}
+
+/// Picks the model, language and whisper.cpp folder; empty values go back to the defaults.
+#[tauri::command]
+pub fn set_dictation(
+    app: AppHandle,
+    model: Option<String>,
+    language: Option<String>,
+    whisper_folder: Option<String>,
+) -> Result<DictationStatus, String> {
+    let clean = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+    let settings = Settings {
+        model: clean(model).map(|model| check_model(&model).map(str::to_string)).transpose()?,
+        language: clean(language),
+        whisper_folder: clean(whisper_folder),
+    };
+    if let Some(language) = &settings.language {
+        if language.len() > 8 || !language.chars().all(|ch| ch.is_ascii_alphabetic()) {
+            return Err(format!("'{language}' is not a whisper language code."));
+        }
+    }
+    let value = serde_json::to_value(&settings).map_err(|err| format!("Failed encoding dictation settings: {err}"))?;
+    crate::write_setting(&app, DICTATION_SETTING, value)?;
+    Ok(get_dictation(app))
+}
+
+/// Downloads a whisper model, reporting progress in bytes.
+#[tauri::command]
+pub async fn download_whisper_model(app: AppHandle, name: String, operation_id: Option<String>) -> Result<(), String> {
+    let progress = Progress::new(&app, "download", operation_id);
+    tauri::async_runtime::spawn_blocking(move || download(&app, &name, &progress))
+        .await
+        .map_err(|err| format!("Model download task failed: {err}"))?
+}
+
+#[tauri::command]
+pub fn delete_whisper_model(app: AppHandle, name: String) -> Result<(), String> {
+    let path = model_path(&app, &name)?;
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed deleting {}: {err}", path.display())),
+        _ => Ok(()),
+    }
+}
+
+/// Starts listening to the microphone; segments arrive as `dictation://segment`
+/// events until `stop_dictation`.
+#[tauri::command]
+pub fn start_dictation(app: AppHandle) -> Result<(), String> {
+    let mut listener = LISTENER.lock().unwrap();
+    if listener.is_some() {
+        return Err("Dictation is already listening.".to_string());
+    }
+    let stream =
+        binary(&app, "whisper-stream").ok_or_else(|| "whisper.cpp's whisper-stream was not found.".to_string())?;
+    let (model, language) = model_and_language(&app)?;
+    let mut child = Command::new(stream)
+        .arg("-m")
+        .arg(&model)
+        .args(["-l", &language, "-t", &threads(), "--step", "0", "--length", "30000", "-vth", "0.6"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Failed to start whisper-stream: {err}"))?;
+    let stdout = child.stdout.take().ok_or_else(|| "whisper-stream has no stdout".to_string())?;
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let current = move || GENERATION.load(Ordering::SeqCst) == generation;
+
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let Some(text) = segment_text(&line) else {
+                continue;
+            };
+            if !current() {
+                break;
+            }
+            if let Err(err) = app.emit(SEGMENT_EVENT, Segment { text }) {
+                tracing::warn!("Failed to emit event: {err}");
+            }
+        }
+        // The stream ended on its own, say because the microphone went away.
+        if current() {
+            if let Some(mut child) = LISTENER.lock().unwrap().take() {
+                let _ = child.wait();
+            }
+            if let Err(err) = app.emit(STOPPED_EVENT, ()) {
+                tracing::warn!("Failed to emit event: {err}");
+            }
+        }
+    });
+    *listener = Some(child);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_dictation(app: AppHandle) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Some(mut child) = LISTENER.lock().unwrap().take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    if let Err(err) = app.emit(STOPPED_EVENT, ()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+/// Transcribes a recording into text for the note.
+#[tauri::command]
+pub async fn transcribe_audio_file(app: AppHandle, path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || transcribe(&app, Path::new(&path)))
+        .await
+        .map_err(|err| format!("Transcription task failed: {err}"))?
+}
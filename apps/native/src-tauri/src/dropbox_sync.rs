@@ -0,0 +1,361 @@
+//! Workspace sync through the Dropbox HTTP API, for people who don't run the
+//! Dropbox desktop client.
+//!
+//! Hermes authorizes with OAuth 2 and PKCE: the user opens the authorization
+//! page, approves, and pastes the code back. The refresh token lives in the
+//! platform keychain. Changes are listed with `list_folder` cursors, and
+//! Dropbox's content hash is compared with local text so identical notes are
+//! never transferred.
+//!
+//! Builds need a Dropbox app key in `HERMES_DROPBOX_APP_KEY`.
+
+use std::sync::Mutex;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cloud_sync::{self, CloudProvider, CloudSyncReport, OAuthTokens, RemoteChange, TokenResponse};
+use crate::{keychain, sync_status};
+
+/// Settings-store key holding `{ workspacePath, remotePath }`.
+const DROPBOX_SETTING: &str = "dropboxSync";
+const KEYCHAIN_ACCOUNT: &str = "dropbox";
+pub const DROPBOX_SYNC_EVENT: &str = "dropbox-sync-completed";
+const APP_KEY: Option<&str> = option_env!("HERMES_DROPBOX_APP_KEY");
+const AUTHORIZE_URL: &str = "https://www.dropbox.com/oauth2/authorize";
+const TOKEN_URL: &str = "https://api.dropboxapi.com/oauth2/token";
+const API_URL: &str = "https://api.dropboxapi.com/2";
+const CONTENT_URL: &str = "https://content.dropboxapi.com/2";
+const DEFAULT_REMOTE_PATH: &str = "/Hermes";
+/// Dropbox hashes content in blocks of this size.
+const HASH_BLOCK_BYTES: usize = 4 * 1024 * 1024;
+
+/// PKCE verifier of the authorization in progress.
+#[derive(Default)]
+pub struct DropboxAuth(Mutex<Option<String>>);
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DropboxConfig {
+    workspace_path: String,
+    remote_path: String,
+}
+
+fn app_key() -> Result<&'static str, String> {
+    APP_KEY.ok_or_else(|| "This build of Hermes has no Dropbox app key.".to_string())
+}
+
+fn read_config(app: &AppHandle) -> Option<DropboxConfig> {
+    serde_json::from_value(crate::read_setting(app, DROPBOX_SETTING)?).ok()
+}
+
+/// Dropbox's content hash: SHA-256 over the SHA-256 of each 4 MiB block.
+fn content_hash(content: &[u8]) -> String {
+    let mut overall = Sha256::new();
+    for block in content.chunks(HASH_BLOCK_BYTES) {
+        overall.update(Sha256::digest(block));
+    }
+    overall.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// JSON for the `Dropbox-API-Arg` header, which must be ASCII.
+fn api_arg(value: &Value) -> String {
+    let mut header = String::new();
+    for ch in value.to_string().chars() {
+        if ch.is_ascii() {
+            header.push(ch);
+        } else {
+            let mut units = [0; 2];
+            for unit in ch.encode_utf16(&mut units) {
+                header.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+    header
+}
+
+fn exchange_token(client: &Client, form: &[(&str, &str)]) -> Result<TokenResponse, String> {
+    let response = cloud_sync::post_form(client, TOKEN_URL, form)?;
+    if !response.status().is_success() {
+        return Err(format!("Dropbox refused the authorization ({})", response.status()));
+    }
+    response.json().map_err(|err| format!("Unexpected Dropbox response: {err}"))
+}
+
+struct Dropbox {
+    client: Client,
+    tokens: OAuthTokens,
+    remote_path: String,
+}
+
+impl Dropbox {
+    fn connect(config: &DropboxConfig) -> Result<Self, String> {
+        let tokens = OAuthTokens::load(KEYCHAIN_ACCOUNT).ok_or_else(|| "Connect Dropbox again to keep syncing.".to_string())?;
+        Ok(Dropbox {
+            client: Client::new(),
+            tokens,
+            remote_path: config.remote_path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn access_token(&mut self) -> Result<String, String> {
+        if self.tokens.is_fresh() {
+            return Ok(self.tokens.access_token.clone());
+        }
+        let refreshed = exchange_token(
+            &self.client,
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &self.tokens.refresh_token),
+                ("client_id", app_key()?),
+            ],
+        )?;
+        self.tokens.refresh(refreshed, KEYCHAIN_ACCOUNT)?;
+        Ok(self.tokens.access_token.clone())
+    }
+
+    fn send(&mut self, request: RequestBuilder) -> Result<Response, String> {
+        let response = request
+            .bearer_auth(self.access_token()?)
+            .send()
+            .map_err(|err| format!("Could not reach Dropbox: {err}"))?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        Err(format!("Dropbox request failed ({status}): {body}"))
+    }
+
+    fn rpc(&mut self, endpoint: &str, body: Value) -> Result<Value, String> {
+        let request = self.client.post(format!("{API_URL}/{endpoint}")).json(&body);
+        self.send(request)?
+            .json()
+            .map_err(|err| format!("Unexpected Dropbox response: {err}"))
+    }
+
+    fn path(&self, key: &str) -> String {
+        format!("{}/{key}.md", self.remote_path)
+    }
+
+    fn ensure_folder(&mut self) -> Result<(), String> {
+        let request = self
+            .client
+            .post(format!("{API_URL}/files/create_folder_v2"))
+            .json(&json!({ "path": self.remote_path, "autorename": false }))
+            .bearer_auth(self.access_token()?);
+        let response = request.send().map_err(|err| format!("Could not reach Dropbox: {err}"))?;
+        // 409 means the folder already exists.
+        if response.status().is_success() || response.status() == StatusCode::CONFLICT {
+            Ok(())
+        } else {
+            Err(format!("Failed creating the Dropbox folder ({})", response.status()))
+        }
+    }
+}
+
+impl CloudProvider for Dropbox {
+    fn id(&self) -> &'static str {
+        "dropbox"
+    }
+
+    fn name(&self) -> &'static str {
+        "Dropbox"
+    }
+
+    fn changes(&mut self, cursor: Option<&str>) -> Result<(Vec<RemoteChange>, String), String> {
+        let mut page = match cursor {
+            Some(cursor) => self.rpc("files/list_folder/continue", json!({ "cursor": cursor }))?,
+            None => {
+                self.ensure_folder()?;
+                self.rpc("files/list_folder", json!({ "path": self.remote_path, "recursive": true }))?
+            }
+        };
+
+        let root = format!("{}/", self.remote_path);
+        let mut changes = Vec::new();
+        loop {
+            for entry in page["entries"].as_array().cloned().unwrap_or_default() {
+                let tag = entry[".tag"].as_str().unwrap_or_default();
+                // Paths compare case-insensitively on Dropbox.
+                let Some(relative) = entry["path_display"]
+                    .as_str()
+                    .filter(|path| path.get(..root.len()).is_some_and(|head| head.eq_ignore_ascii_case(&root)))
+                    .and_then(|path| path.get(root.len()..))
+                else {
+                    continue;
+                };
+                let Some(key) = cloud_sync::key_for_path(relative) else {
+                    continue;
+                };
+                match tag {
+                    "file" => changes.push(RemoteChange {
+                        key,
+                        revision: entry["content_hash"].as_str().map(str::to_string),
+                    }),
+                    "deleted" => changes.push(RemoteChange { key, revision: None }),
+                    _ => {}
+                }
+            }
+            let cursor = page["cursor"].as_str().unwrap_or_default().to_string();
+            if !page["has_more"].as_bool().unwrap_or(false) {
+                return Ok((changes, cursor));
+            }
+            page = self.rpc("files/list_folder/continue", json!({ "cursor": cursor }))?;
+        }
+    }
+
+    fn download(&mut self, key: &str) -> Result<String, String> {
+        let request = self
+            .client
+            .post(format!("{CONTENT_URL}/files/download"))
+            .header("Dropbox-API-Arg", api_arg(&json!({ "path": self.path(key) })));
+        self.send(request)?
+            .text()
+            .map_err(|err| format!("Failed downloading {key} from Dropbox: {err}"))
+    }
+
+    fn upload(&mut self, key: &str, content: &str) -> Result<String, String> {
+        let arg = json!({ "path": self.path(key), "mode": "overwrite", "mute": true });
+        let request = self
+            .client
+            .post(format!("{CONTENT_URL}/files/upload"))
+            .header("Dropbox-API-Arg", api_arg(&arg))
+            .header("Content-Type", "application/octet-stream")
+            .body(content.to_string());
+        let metadata: Value = self
+            .send(request)?
+            .json()
+            .map_err(|err| format!("Unexpected Dropbox response: {err}"))?;
+        Ok(metadata["content_hash"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| content_hash(content.as_bytes())))
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        let path = self.path(key);
+        self.rpc("files/delete_v2", json!({ "path": path })).map(|_| ())
+    }
+
+    fn content_revision(&self, content: &str) -> Option<String> {
+        Some(content_hash(content.as_bytes()))
+    }
+}
+
+fn sync(app: &AppHandle) -> Result<CloudSyncReport, String> {
+    sync_status::syncing(app, "dropbox", 0, 0);
+    let result = read_config(app)
+        .ok_or_else(|| "Dropbox sync is not set up.".to_string())
+        .and_then(|config| {
+            crate::read_only::ensure_writable(app, &config.workspace_path).map_err(String::from)?;
+            let mut dropbox = Dropbox::connect(&config)?;
+            cloud_sync::sync(&config.workspace_path, &mut dropbox, &mut |done, total| {
+                sync_status::syncing(app, "dropbox", done, total)
+            })
+        });
+    sync_status::finish(app, "dropbox", result.as_ref().map_err(String::clone).and_then(CloudSyncReport::outcome));
+    let report = result?;
+    if let Err(err) = app.emit(DROPBOX_SYNC_EVENT, report.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(report)
+}
+
+/// Starts authorization and returns the page where the user approves Hermes.
+#[tauri::command]
+pub fn begin_dropbox_auth(app: AppHandle) -> Result<String, String> {
+    let mut bytes = [0; 48];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let verifier = URL_SAFE_NO_PAD.encode(bytes);
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    *app.state::<DropboxAuth>().0.lock().unwrap() = Some(verifier);
+
+    Ok(format!(
+        "{AUTHORIZE_URL}?client_id={}&response_type=code&token_access_type=offline\
+         &code_challenge={challenge}&code_challenge_method=S256",
+        app_key()?
+    ))
+}
+
+/// Finishes authorization with the code Dropbox showed, then syncs `workspace_path`
+/// with `remote_path` (default `/Hermes`).
+#[tauri::command]
+pub async fn finish_dropbox_auth(
+    app: AppHandle,
+    code: String,
+    workspace_path: String,
+    remote_path: Option<String>,
+) -> Result<CloudSyncReport, String> {
+    let verifier = app
+        .state::<DropboxAuth>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "Start connecting Dropbox again.".to_string())?;
+    let remote_path = remote_path
+        .map(|path| format!("/{}", path.trim().trim_matches('/')))
+        .filter(|path| path != "/")
+        .unwrap_or_else(|| DEFAULT_REMOTE_PATH.to_string());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let response = exchange_token(
+            &Client::new(),
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code.trim()),
+                ("code_verifier", &verifier),
+                ("client_id", app_key()?),
+            ],
+        )?;
+        OAuthTokens::granted(response)?.save(KEYCHAIN_ACCOUNT)?;
+
+        if let Some(previous) = read_config(&app) {
+            cloud_sync::reset(&previous.workspace_path, "dropbox");
+        }
+        let config = DropboxConfig {
+            workspace_path,
+            remote_path,
+        };
+        let value = serde_json::to_value(&config).map_err(|err| format!("Failed encoding Dropbox settings: {err}"))?;
+        crate::write_setting(&app, DROPBOX_SETTING, value)?;
+        sync(&app)
+    })
+    .await
+    .map_err(|err| format!("Dropbox sync task failed: {err}"))?
+}
+
+/// Stops syncing and revokes Hermes' access to the Dropbox account.
+#[tauri::command]
+pub async fn disconnect_dropbox(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(config) = read_config(&app) {
+            if let Ok(mut dropbox) = Dropbox::connect(&config) {
+                if let Err(err) = dropbox.rpc("auth/token/revoke", Value::Null) {
+                    tracing::warn!("{}", err);
+                }
+            }
+            cloud_sync::reset(&config.workspace_path, "dropbox");
+        }
+        keychain::delete(KEYCHAIN_ACCOUNT);
+        crate::write_setting(&app, DROPBOX_SETTING, Value::Null)
+    })
+    .await
+    .map_err(|err| format!("Dropbox task failed: {err}"))?
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub async fn sync_with_dropbox(app: AppHandle) -> Result<CloudSyncReport, String> {
+    tauri::async_runtime::spawn_blocking(move || sync(&app))
+        .await
+        .map_err(|err| format!("Dropbox sync task failed: {err}"))?
+}
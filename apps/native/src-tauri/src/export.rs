@@ -0,0 +1,36 @@
+//! The Export menu's commands, over `hermes_core::export`.
+
+pub use hermes_core::export::{export_to, Exporter, EXPORTERS};
+#[cfg(desktop)]
+pub use hermes_core::export::{render_note, write};
+use hermes_core::redact::Redaction;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn list_exporters() -> Vec<Exporter> {
+    EXPORTERS.to_vec()
+}
+
+/// Exports like `export_to`, then runs the workspace's export hooks.
+#[tauri::command]
+pub fn export_note(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    format: String,
+    destination: String,
+    redaction: Option<Redaction>,
+) -> Result<String, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    let written = export_to(&workspace_path, &tab_key, &format, &destination, redaction)?;
+    #[cfg(desktop)]
+    crate::hooks::fire(&app, crate::hooks::Context {
+        event: crate::hooks::HookEvent::Export,
+        folder: &workspace_path,
+        tab: &tab_key,
+        export_path: Some(&written),
+    });
+    #[cfg(not(desktop))]
+    let _ = app;
+    Ok(written)
+}
@@ -0,0 +1,188 @@
+//! Markdown files handed to Hermes by the OS (file associations and CLI paths).
+//!
+//! Each opened file is resolved against the configured workspace and queued; the
+//! frontend drains the queue with `take_pending_open_files` when it mounts and
+//! whenever an `open-file` event arrives, so cold-launch opens are never lost.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::AppHandle;
+#[cfg(any(desktop, target_os = "ios"))]
+use tauri::{Emitter, Manager};
+
+#[cfg(any(desktop, target_os = "ios"))]
+use crate::focus_main_window;
+use crate::{canonical_path, canonical_workspace, note_path, validate_tab_key};
+
+#[cfg(any(desktop, target_os = "ios"))]
+pub const OPEN_FILE_EVENT: &str = "open-file";
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum OpenFileTarget {
+    /// A tab file inside the configured workspace, or inside one of its projects
+    /// or sub-projects, named by path.
+    Note {
+        workspace_path: String,
+        project: Option<String>,
+        tab: String,
+        file_path: String,
+    },
+    /// A markdown file outside the workspace; the UI can offer `import_markdown_file`.
+    External { file_path: String },
+}
+
+pub struct PendingOpenFiles(pub Mutex<Vec<OpenFileTarget>>);
+
+#[cfg(any(desktop, target_os = "ios"))]
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
+pub fn configured_workspace(app: &AppHandle) -> Option<PathBuf> {
+    let configured = crate::read_setting(app, "workspacePath")
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| crate::get_default_workspace(app.clone()).ok())?;
+    Some(canonical_path(Path::new(configured.trim())))
+}
+
+/// Rewrites a stored workspace path reached through a symlink to its real
+/// location, so the frontend passes the same path every command resolves.
+pub fn canonicalize_setting(app: &AppHandle) {
+    let Some(stored) = crate::read_setting(app, "workspacePath").and_then(|value| value.as_str().map(str::to_string)) else {
+        return;
+    };
+    let canonical = crate::canonical_workspace(&stored);
+    if stored.trim().is_empty() || canonical == stored {
+        return;
+    }
+    if let Err(err) = crate::write_setting(app, "workspacePath", serde_json::json!(canonical)) {
+        tracing::warn!("{}", err);
+    }
+}
+
+/// Works out whether `file_path` is one of the workspace's tab files.
+pub fn resolve(file_path: &Path, workspace: Option<&Path>) -> OpenFileTarget {
+    let file = canonical_path(file_path);
+    let external = OpenFileTarget::External {
+        file_path: file.to_string_lossy().to_string(),
+    };
+
+    let Some(workspace) = workspace.map(canonical_path) else {
+        return external;
+    };
+    let Some(parent) = file.parent() else {
+        return external;
+    };
+    let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+        return external;
+    };
+    let Some(tab) = crate::tab_files::tab_of(&parent.to_string_lossy(), name) else {
+        return external;
+    };
+
+    let project = if parent == workspace {
+        None
+    } else {
+        let Ok(relative) = parent.strip_prefix(&workspace) else {
+            return external;
+        };
+        let name = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if crate::project_list::check_project(&name).is_err() {
+            return external;
+        }
+        Some(name)
+    };
+
+    OpenFileTarget::Note {
+        workspace_path: workspace.to_string_lossy().to_string(),
+        project,
+        tab: tab.to_string(),
+        file_path: file.to_string_lossy().to_string(),
+    }
+}
+
+/// Queues every markdown file in `paths`, focuses the main window and notifies the frontend.
+#[cfg(any(desktop, target_os = "ios"))]
+pub fn handle_paths(app: &AppHandle, paths: impl IntoIterator<Item = PathBuf>) {
+    let workspace = configured_workspace(app);
+    let targets: Vec<OpenFileTarget> = paths
+        .into_iter()
+        .filter(|path| is_markdown(path) && path.is_file())
+        .map(|path| resolve(&path, workspace.as_deref()))
+        .collect();
+
+    if targets.is_empty() {
+        return;
+    }
+
+    let state = app.state::<PendingOpenFiles>();
+    state.0.lock().unwrap().extend(targets.iter().cloned());
+
+    focus_main_window(app);
+    for target in targets {
+        if let Err(err) = app.emit(OPEN_FILE_EVENT, target) {
+            tracing::warn!("Failed to emit event: {err}");
+        }
+    }
+}
+
+/// Paths passed on the command line (Windows/Linux file associations launch `hermes <file>`).
+#[cfg(desktop)]
+pub fn paths_from_args(args: impl IntoIterator<Item = String>) -> Vec<PathBuf> {
+    args.into_iter()
+        .filter(|arg| !arg.starts_with('-'))
+        .map(PathBuf::from)
+        .filter(|path| is_markdown(path))
+        .collect()
+}
+
+#[tauri::command]
+pub fn take_pending_open_files(state: tauri::State<'_, PendingOpenFiles>) -> Vec<OpenFileTarget> {
+    std::mem::take(&mut *state.0.lock().unwrap())
+}
+
+/// Copies an external markdown file into an empty tab of `workspace_path`,
+/// with the local files it links to. The note is saved like an edit, through
+/// the save queue, so it takes the write lock and gets its index row.
+#[tauri::command]
+pub fn import_markdown_file(
+    app: AppHandle,
+    workspace_path: String,
+    file_path: String,
+    tab_key: String,
+) -> Result<(), String> {
+    validate_tab_key(&tab_key)?;
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    // Edits still waiting for the tab count as content.
+    crate::autosave::flush(&app, Some(&workspace_path)).map_err(String::from)?;
+    crate::save_queue::flush(&app, Some(&workspace_path)).map_err(String::from)?;
+    let target = note_path(&workspace_path, &tab_key);
+    if target.exists() {
+        let existing = fs::read_to_string(&target)
+            .map_err(|err| format!("Failed reading {}: {err}", target.display()))?;
+        if !existing.trim().is_empty() {
+            return Err(format!("The {tab_key} tab already has content; pick an empty tab."));
+        }
+    }
+
+    let content = fs::read_to_string(&file_path).map_err(|err| format!("Failed reading {file_path}: {err}"))?;
+    // Images and files the note links to come along into the attachments folder.
+    let content = match Path::new(&file_path).parent() {
+        Some(from_dir) => crate::attachments::import_linked(&content, from_dir, &workspace_path)?,
+        None => content,
+    };
+    crate::save_queue::save(&app, &workspace_path, HashMap::from([(tab_key, content)])).map_err(String::from)
+}
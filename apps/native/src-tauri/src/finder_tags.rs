@@ -0,0 +1,46 @@
+//! Reading and setting the Finder tags of a note from the app.
+
+pub use hermes_core::finder_tags::{index_script, read_tags, write_tags, NOTE_TAGS_SCHEMA};
+
+use crate::{note_path, run_sqlite_script, sqlite_path, validate_tab_key};
+
+#[tauri::command]
+pub fn get_note_finder_tags(workspace_path: String, tab_key: String) -> Result<Vec<String>, String> {
+    validate_tab_key(&tab_key)?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    read_tags(&file_path)
+}
+
+#[tauri::command]
+pub fn set_note_finder_tags(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    validate_tab_key(&tab_key)?;
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    if !file_path.exists() {
+        return Err(format!("{} does not exist yet; save the note first.", file_path.display()));
+    }
+
+    write_tags(&file_path, &tags)?;
+    let applied = read_tags(&file_path)?;
+
+    let db_path = sqlite_path(&workspace_path);
+    if db_path.exists() {
+        let script = format!(
+            "{NOTE_TAGS_SCHEMA}BEGIN IMMEDIATE;\n{}COMMIT;\n",
+            index_script(&tab_key, &applied)
+        );
+        if let Err(err) = run_sqlite_script(&db_path, &script) {
+            tracing::warn!("{}", err);
+        }
+    }
+
+    Ok(applied)
+}
@@ -0,0 +1,276 @@
+//! Tidying notes on save.
+//!
+//! With the `formatOnSave` setting on, markdown notes are normalized as they
+//! are written, so a workspace kept in git only shows the changes that
+//! matter: trailing whitespace goes (two-space line breaks stay), ATX headings
+//! get one space after the `#`s, no closing `#`s and a blank line around them,
+//! `*` and `+` bullets become `-`, runs of blank lines collapse to one, and
+//! reference link definitions move to the end of the note. Front matter and
+//! code blocks are left exactly as they are, and so are plain-text and locked
+//! notes. `format_note` formats one note on demand, or shows the diff.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Serialize;
+use similar::TextDiff;
+use tauri::AppHandle;
+
+use crate::{canonical_workspace, note_lock, note_path, tab_files, validate_tab_key};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedNote {
+    pub changed: bool,
+    /// Unified diff from the note to its formatted version; empty when unchanged.
+    pub diff: String,
+    pub content: String,
+}
+
+/// The opening fence of a code block: its character and length.
+fn fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let ch = trimmed.chars().next().filter(|ch| *ch == '`' || *ch == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == ch).count();
+    // Backtick fences can't have backticks in their info string.
+    let info = &trimmed[length..];
+    (length >= 3 && (ch == '~' || !info.contains('`'))).then_some((ch, length))
+}
+
+fn closes_fence(line: &str, (ch, length): (char, usize)) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    let run = trimmed.chars().take_while(|c| *c == ch).count();
+    line.len() - trimmed.len() <= 3 && run >= length && trimmed[run..].trim().is_empty()
+}
+
+/// An ATX heading with one space after its `#`s and no closing sequence.
+fn heading(line: &str) -> Option<String> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|ch| *ch == '#').count();
+    let rest = &trimmed[level..];
+    // `#tag` at the start of a line is a tag, not a heading.
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let mut text = rest.trim();
+    let closing = text.trim_end_matches('#');
+    if closing.is_empty() || closing.ends_with([' ', '\t']) {
+        text = closing.trim_end();
+    }
+    let hashes = "#".repeat(level);
+    Some(if text.is_empty() { hashes } else { format!("{hashes} {text}") })
+}
+
+/// `line` with a `*` or `+` bullet turned into `-`.
+fn bullet(line: &str) -> Option<String> {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    let marker = trimmed.chars().next().filter(|ch| *ch == '*' || *ch == '+')?;
+    if !trimmed[1..].starts_with([' ', '\t']) {
+        return None;
+    }
+    // `* * *` is a thematic break.
+    let marks = trimmed.chars().filter(|ch| *ch == marker).count();
+    if marks >= 3 && trimmed.chars().all(|ch| ch == marker || ch == ' ' || ch == '\t') {
+        return None;
+    }
+    let indent = &line[..line.len() - trimmed.len()];
+    Some(format!("{indent}-{}", &trimmed[1..]))
+}
+
+/// The label and the rest of a reference link definition like `[label]: url`;
+/// footnotes are left where they are.
+fn reference(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let (label, rest) = trimmed.strip_prefix('[')?.split_once("]:")?;
+    let rest = rest.trim();
+    if label.trim().is_empty() || label.starts_with('^') || label.contains(['[', ']']) || rest.is_empty() {
+        return None;
+    }
+    Some((label, rest))
+}
+
+/// Where the front matter at the start of `lines` ends, if there is any.
+fn front_matter_end(lines: &[&str]) -> usize {
+    if lines.first() != Some(&"---") {
+        return 0;
+    }
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| *line == "---" || *line == "...")
+        .map_or(0, |end| end + 2)
+}
+
+/// `content` tidied up; formatting it again changes nothing.
+pub fn format(content: &str) -> String {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines: Vec<&str> = content.lines().collect();
+    let body_start = front_matter_end(&lines);
+    let mut out: Vec<String> = lines[..body_start].iter().map(|line| line.to_string()).collect();
+    let mut body: Vec<String> = Vec::new();
+    let mut references: Vec<String> = Vec::new();
+    let mut labels = HashSet::new();
+    let mut open_fence = None;
+    let mut after_heading = false;
+    // Definitions can't interrupt a paragraph; inside one they are text.
+    let mut in_paragraph = false;
+    let mut breaks = Vec::new();
+
+    for line in &lines[body_start..] {
+        if let Some(open) = open_fence {
+            if closes_fence(line, open) {
+                open_fence = None;
+            }
+            body.push(line.to_string());
+            continue;
+        }
+        let blank = line.trim().is_empty();
+        if blank {
+            if body.last().is_some_and(|last| !last.is_empty()) {
+                body.push(String::new());
+            }
+            after_heading = false;
+            in_paragraph = false;
+            continue;
+        }
+        if after_heading {
+            body.push(String::new());
+            after_heading = false;
+        }
+        if let Some((label, rest)) = reference(line).filter(|_| !in_paragraph) {
+            // The first definition of a label is the one links use.
+            if labels.insert(label.trim().to_lowercase()) {
+                references.push(format!("[{label}]: {rest}"));
+            }
+            continue;
+        }
+        if let Some(heading) = heading(line) {
+            if body.last().is_some_and(|last| !last.is_empty()) {
+                body.push(String::new());
+            }
+            body.push(heading);
+            after_heading = true;
+            in_paragraph = false;
+            continue;
+        }
+        open_fence = fence(line);
+        if open_fence.is_some() {
+            in_paragraph = false;
+            body.push(line.to_string());
+            continue;
+        }
+        in_paragraph = true;
+        let line = bullet(line).unwrap_or_else(|| line.to_string());
+        let trimmed = line.trim_end();
+        if line[trimmed.len()..].starts_with("  ") {
+            breaks.push(body.len());
+            body.push(format!("{trimmed}  "));
+        } else {
+            body.push(trimmed.to_string());
+        }
+    }
+    // Two trailing spaces are a line break, unless nothing follows in the paragraph.
+    for index in breaks {
+        if body.get(index + 1).is_none_or(|next| next.is_empty()) {
+            let length = body[index].trim_end().len();
+            body[index].truncate(length);
+        }
+    }
+
+    while body.last().is_some_and(|last| last.is_empty()) {
+        body.pop();
+    }
+    if !references.is_empty() {
+        if !body.is_empty() {
+            body.push(String::new());
+        }
+        body.extend(references);
+    }
+    out.extend(body);
+    if out.is_empty() {
+        return String::new();
+    }
+    let mut formatted = out.join(newline);
+    formatted.push_str(newline);
+    formatted
+}
+
+/// Whether notes in `workspace_path` are formatted as they are saved.
+pub fn is_enabled(app: &AppHandle, workspace_path: &str) -> bool {
+    match crate::settings::resolved(app, Some(workspace_path)) {
+        Ok(resolved) => resolved.settings.format_on_save,
+        Err(err) => {
+            tracing::warn!("Failed reading settings for {workspace_path}: {err}");
+            false
+        }
+    }
+}
+
+/// What to write for the note at `path`: `content` formatted when `enabled`
+/// and the note is markdown, otherwise as it is.
+pub fn on_save<'a>(enabled: bool, path: &Path, content: &'a str) -> Cow<'a, str> {
+    let skip = content.trim().is_empty()
+        || tab_files::is_plain_text(path)
+        || note_lock::is_locked(content)
+        || note_lock::is_locked_file(path);
+    if !enabled || skip {
+        return Cow::Borrowed(content);
+    }
+    let formatted = format(content);
+    match formatted == content {
+        true => Cow::Borrowed(content),
+        false => Cow::Owned(formatted),
+    }
+}
+
+/// Formats the note in `tab_key`, whether or not format on save is on, and
+/// returns the result with a unified diff from the note. With `dry_run` the
+/// note is left as it is.
+#[tauri::command]
+pub fn format_note(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    dry_run: Option<bool>,
+) -> Result<FormattedNote, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::autosave::flush(&app, Some(&workspace_path)).map_err(String::from)?;
+    let path = note_path(&workspace_path, &tab_key);
+    if tab_files::is_plain_text(&path) {
+        return Err(format!("The {tab_key} note is plain text."));
+    }
+    let pages = crate::read_workspace_files(&workspace_path).map_err(String::from)?;
+    let content = pages.get(&tab_key).cloned().unwrap_or_default();
+    if note_lock::is_locked(&content) {
+        return Err(format!("The {tab_key} note is locked."));
+    }
+    let formatted = if content.trim().is_empty() { content.clone() } else { format(&content) };
+    if formatted == content {
+        return Ok(FormattedNote { changed: false, diff: String::new(), content });
+    }
+
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| tab_key.clone());
+    let diff = TextDiff::from_lines(&content, &formatted)
+        .unified_diff()
+        .header(&format!("a/{name}"), &format!("b/{name}"))
+        .to_string();
+    if !dry_run.unwrap_or(false) {
+        crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+        let pages = std::collections::HashMap::from([(tab_key.clone(), formatted.clone())]);
+        crate::save_queue::save(&app, &workspace_path, pages).map_err(String::from)?;
+        tracing::info!("Formatted {tab_key} in {workspace_path}");
+    }
+    Ok(FormattedNote { changed: true, diff, content: formatted })
+}
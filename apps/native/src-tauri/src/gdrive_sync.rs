@@ -0,0 +1,568 @@
+//! Workspace sync through Google Drive.
+//!
+//! Hermes signs in with the OAuth device flow: the user enters a short code on
+//! Google's page from any browser while Hermes polls for the grant. Notes live
+//! either in the hidden app data folder or in a folder Hermes creates in My
+//! Drive, with project folders inside it. After a full listing on the first
+//! sync, changes are pulled incrementally with the Drive change token.
+//!
+//! Builds need an OAuth client for TVs and limited-input devices in
+//! `HERMES_GDRIVE_CLIENT_ID` and `HERMES_GDRIVE_CLIENT_SECRET`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cloud_sync::{self, CloudProvider, CloudSyncReport, OAuthTokens, RemoteChange, TokenResponse};
+use crate::{hermes_dir, keychain, sync_status};
+
+/// Settings-store key holding `{ workspacePath, folder }`.
+const GDRIVE_SETTING: &str = "googleDriveSync";
+const KEYCHAIN_ACCOUNT: &str = "google-drive";
+pub const GDRIVE_SYNC_EVENT: &str = "gdrive-sync-completed";
+const CLIENT_ID: Option<&str> = option_env!("HERMES_GDRIVE_CLIENT_ID");
+const CLIENT_SECRET: Option<&str> = option_env!("HERMES_GDRIVE_CLIENT_SECRET");
+const DEVICE_CODE_URL: &str = "https://oauth2.googleapis.com/device/code";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
+const FILES_URL: &str = "https://www.googleapis.com/drive/v3/files";
+const UPLOAD_URL: &str = "https://www.googleapis.com/upload/drive/v3/files";
+const CHANGES_URL: &str = "https://www.googleapis.com/drive/v3/changes";
+/// Only files Hermes created itself, in My Drive and in its app data folder.
+const SCOPES: &str = "https://www.googleapis.com/auth/drive.file https://www.googleapis.com/auth/drive.appdata";
+const FOLDER_MIME: &str = "application/vnd.google-apps.folder";
+const APP_DATA_FOLDER: &str = "appDataFolder";
+const FILE_FIELDS: &str = "id,name,mimeType,parents,md5Checksum,trashed";
+
+/// Device code of the sign-in in progress.
+#[derive(Default)]
+pub struct GoogleDriveAuth(Mutex<Option<DeviceCode>>);
+
+#[derive(Clone, Deserialize)]
+struct DeviceCode {
+    device_code: String,
+    interval: u64,
+    expires_in: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSignIn {
+    user_code: String,
+    verification_url: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleDriveConfig {
+    workspace_path: String,
+    /// Folder in My Drive; `None` keeps notes in the hidden app data folder.
+    folder: Option<String>,
+}
+
+/// Drive ids of the synced folders and notes, which changes report by id.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct DriveIds {
+    root: Option<String>,
+    folders: BTreeMap<String, String>,
+    files: BTreeMap<String, String>,
+}
+
+fn client_credentials() -> Result<(&'static str, &'static str), String> {
+    CLIENT_ID
+        .zip(CLIENT_SECRET)
+        .ok_or_else(|| "This build of Hermes has no Google Drive client.".to_string())
+}
+
+fn read_config(app: &AppHandle) -> Option<GoogleDriveConfig> {
+    serde_json::from_value(crate::read_setting(app, GDRIVE_SETTING)?).ok()
+}
+
+fn ids_path(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("gdrive").join("ids.json")
+}
+
+/// Quotes a value for a Drive search query.
+fn query_value(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, NON_ALPHANUMERIC).to_string()
+}
+
+struct GoogleDrive {
+    client: Client,
+    tokens: OAuthTokens,
+    workspace_path: String,
+    folder: Option<String>,
+    ids: DriveIds,
+}
+
+impl GoogleDrive {
+    fn connect(config: &GoogleDriveConfig) -> Result<Self, String> {
+        let tokens =
+            OAuthTokens::load(KEYCHAIN_ACCOUNT).ok_or_else(|| "Sign in to Google Drive again to keep syncing.".to_string())?;
+        let ids = fs::read_to_string(ids_path(&config.workspace_path))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Ok(GoogleDrive {
+            client: Client::new(),
+            tokens,
+            workspace_path: config.workspace_path.clone(),
+            folder: config.folder.clone(),
+            ids,
+        })
+    }
+
+    fn space(&self) -> &'static str {
+        if self.folder.is_some() {
+            "drive"
+        } else {
+            APP_DATA_FOLDER
+        }
+    }
+
+    fn save_ids(&self) -> Result<(), String> {
+        let path = ids_path(&self.workspace_path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+        }
+        let json = serde_json::to_string(&self.ids).map_err(|err| format!("Failed encoding Drive ids: {err}"))?;
+        fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+    }
+
+    fn access_token(&mut self) -> Result<String, String> {
+        if self.tokens.is_fresh() {
+            return Ok(self.tokens.access_token.clone());
+        }
+        let (client_id, client_secret) = client_credentials()?;
+        let response = cloud_sync::post_form(
+            &self.client,
+            TOKEN_URL,
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &self.tokens.refresh_token),
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+            ],
+        )?;
+        if !response.status().is_success() {
+            return Err(format!("Google refused to refresh the sign-in ({})", response.status()));
+        }
+        let refreshed: TokenResponse = response
+            .json()
+            .map_err(|err| format!("Unexpected Google response: {err}"))?;
+        self.tokens.refresh(refreshed, KEYCHAIN_ACCOUNT)?;
+        Ok(self.tokens.access_token.clone())
+    }
+
+    fn send(&mut self, request: RequestBuilder) -> Result<Response, String> {
+        let response = request
+            .bearer_auth(self.access_token()?)
+            .send()
+            .map_err(|err| format!("Could not reach Google Drive: {err}"))?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        Err(format!("Google Drive request failed ({status}): {body}"))
+    }
+
+    fn json(&mut self, request: RequestBuilder) -> Result<Value, String> {
+        self.send(request)?
+            .json()
+            .map_err(|err| format!("Unexpected Google Drive response: {err}"))
+    }
+
+    /// Files matching `query` in the synced space.
+    fn list(&mut self, query: &str) -> Result<Vec<Value>, String> {
+        let mut files = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = format!(
+                "{FILES_URL}?spaces={}&q={}&fields=nextPageToken,files({FILE_FIELDS})&pageSize=1000",
+                self.space(),
+                encode(query)
+            );
+            if let Some(token) = &page_token {
+                url.push_str(&format!("&pageToken={}", encode(token)));
+            }
+            let page = self.json(self.client.get(url))?;
+            files.extend(page["files"].as_array().cloned().unwrap_or_default());
+            page_token = page["nextPageToken"].as_str().map(str::to_string);
+            if page_token.is_none() {
+                return Ok(files);
+            }
+        }
+    }
+
+    fn create_folder(&mut self, name: &str, parent: &str) -> Result<String, String> {
+        let metadata = json!({ "name": name, "mimeType": FOLDER_MIME, "parents": [parent] });
+        let folder = self.json(self.client.post(format!("{FILES_URL}?fields=id")).json(&metadata))?;
+        folder["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Google Drive did not return the new folder.".to_string())
+    }
+
+    /// Id of the folder notes sync into, created on first use.
+    fn root(&mut self) -> Result<String, String> {
+        if let Some(root) = &self.ids.root {
+            return Ok(root.clone());
+        }
+        let root = match self.folder.clone() {
+            None => APP_DATA_FOLDER.to_string(),
+            Some(name) => {
+                let query = format!(
+                    "name = {} and 'root' in parents and mimeType = '{FOLDER_MIME}' and trashed = false",
+                    query_value(&name)
+                );
+                match self.list(&query)?.first().and_then(|folder| folder["id"].as_str()) {
+                    Some(id) => id.to_string(),
+                    None => self.create_folder(&name, "root")?,
+                }
+            }
+        };
+        self.ids.root = Some(root.clone());
+        Ok(root)
+    }
+
+    fn children(&mut self, parent: &str) -> Result<Vec<Value>, String> {
+        self.list(&format!("{} in parents and trashed = false", query_value(parent)))
+    }
+
+    /// The note key of a Drive file, recording its id; folders are recorded too.
+    fn track(&mut self, file: &Value) -> Option<String> {
+        let id = file["id"].as_str()?.to_string();
+        let name = file["name"].as_str()?;
+        let parent = file["parents"].as_array()?.first()?.as_str()?;
+        let root = self.ids.root.as_deref()?;
+        // App data files report the folder's real id rather than its alias.
+        let in_root = parent == root || (root == APP_DATA_FOLDER && !self.ids.folders.values().any(|id| id == parent));
+
+        if file["mimeType"].as_str() == Some(FOLDER_MIME) {
+            if in_root && !name.starts_with('.') {
+                self.ids.folders.insert(name.to_string(), id);
+            }
+            return None;
+        }
+        let relative = if in_root {
+            name.to_string()
+        } else {
+            let project = self.ids.folders.iter().find(|(_, folder)| *folder == parent)?.0;
+            format!("{project}/{name}")
+        };
+        let key = cloud_sync::key_for_path(&relative)?;
+        self.ids.files.insert(key.clone(), id);
+        Some(key)
+    }
+
+    fn untrack(&mut self, id: &str) -> Option<String> {
+        let key = self.ids.files.iter().find(|(_, file)| *file == id)?.0.clone();
+        self.ids.files.remove(&key);
+        Some(key)
+    }
+
+    fn full_listing(&mut self) -> Result<Vec<RemoteChange>, String> {
+        let root = self.root()?;
+        self.ids.folders.clear();
+        self.ids.files.clear();
+        let mut entries = self.children(&root)?;
+        // Folders first, so the notes inside them resolve to project keys.
+        entries.sort_by_key(|entry| entry["mimeType"].as_str() != Some(FOLDER_MIME));
+        let mut changes = Vec::new();
+        for entry in entries {
+            if entry["mimeType"].as_str() == Some(FOLDER_MIME) {
+                self.track(&entry);
+                let Some(folder_id) = entry["id"].as_str() else {
+                    continue;
+                };
+                for file in self.children(folder_id)? {
+                    if let Some(key) = self.track(&file) {
+                        changes.push(RemoteChange {
+                            key,
+                            revision: file["md5Checksum"].as_str().map(str::to_string),
+                        });
+                    }
+                }
+            } else if let Some(key) = self.track(&entry) {
+                changes.push(RemoteChange {
+                    key,
+                    revision: entry["md5Checksum"].as_str().map(str::to_string),
+                });
+            }
+        }
+        Ok(changes)
+    }
+
+    fn folder_for(&mut self, key: &str) -> Result<String, String> {
+        let root = self.root()?;
+        let Some((project, _)) = key.split_once('/') else {
+            return Ok(root);
+        };
+        if let Some(id) = self.ids.folders.get(project) {
+            return Ok(id.clone());
+        }
+        let id = self.create_folder(project, &root)?;
+        self.ids.folders.insert(project.to_string(), id.clone());
+        Ok(id)
+    }
+
+    fn file_id(&self, key: &str) -> Result<String, String> {
+        self.ids
+            .files
+            .get(key)
+            .cloned()
+            .ok_or_else(|| format!("{key} is not on Google Drive."))
+    }
+}
+
+impl CloudProvider for GoogleDrive {
+    fn id(&self) -> &'static str {
+        "gdrive"
+    }
+
+    fn name(&self) -> &'static str {
+        "Google Drive"
+    }
+
+    fn changes(&mut self, cursor: Option<&str>) -> Result<(Vec<RemoteChange>, String), String> {
+        let Some(cursor) = cursor else {
+            // Take the token before listing, so edits made meanwhile show up next time.
+            let start = self.json(self.client.get(format!("{CHANGES_URL}/startPageToken")))?;
+            let token = start["startPageToken"]
+                .as_str()
+                .ok_or_else(|| "Google Drive did not return a change token.".to_string())?
+                .to_string();
+            let changes = self.full_listing()?;
+            self.save_ids()?;
+            return Ok((changes, token));
+        };
+
+        let mut entries = Vec::new();
+        let mut token = cursor.to_string();
+        let next = loop {
+            let url = format!(
+                "{CHANGES_URL}?pageToken={}&spaces={}&pageSize=1000\
+                 &fields=nextPageToken,newStartPageToken,changes(fileId,removed,file({FILE_FIELDS}))",
+                encode(&token),
+                self.space()
+            );
+            let page = self.json(self.client.get(url))?;
+            entries.extend(page["changes"].as_array().cloned().unwrap_or_default());
+            if let Some(next) = page["nextPageToken"].as_str() {
+                token = next.to_string();
+            } else {
+                break page["newStartPageToken"].as_str().unwrap_or(cursor).to_string();
+            }
+        };
+
+        // Folders first, so notes in a new project folder resolve to project keys.
+        entries.sort_by_key(|entry| entry["file"]["mimeType"].as_str() != Some(FOLDER_MIME));
+        let mut changes = Vec::new();
+        for entry in entries {
+            let id = entry["fileId"].as_str().unwrap_or_default();
+            if entry["removed"].as_bool() == Some(true) || entry["file"]["trashed"].as_bool() == Some(true) {
+                if let Some(key) = self.untrack(id) {
+                    changes.push(RemoteChange { key, revision: None });
+                }
+                continue;
+            }
+            if let Some(key) = self.track(&entry["file"]) {
+                changes.push(RemoteChange {
+                    key,
+                    revision: entry["file"]["md5Checksum"].as_str().map(str::to_string),
+                });
+            }
+        }
+        self.save_ids()?;
+        Ok((changes, next))
+    }
+
+    fn download(&mut self, key: &str) -> Result<String, String> {
+        let id = self.file_id(key)?;
+        self.send(self.client.get(format!("{FILES_URL}/{id}?alt=media")))?
+            .text()
+            .map_err(|err| format!("Failed downloading {key} from Google Drive: {err}"))
+    }
+
+    fn upload(&mut self, key: &str, content: &str) -> Result<String, String> {
+        let id = match self.ids.files.get(key).cloned() {
+            Some(id) => id,
+            None => {
+                let parent = self.folder_for(key)?;
+                let name = format!("{}.md", key.rsplit('/').next().unwrap_or(key));
+                let metadata = json!({ "name": name, "parents": [parent], "mimeType": "text/markdown" });
+                let file = self.json(self.client.post(format!("{FILES_URL}?fields=id")).json(&metadata))?;
+                let id = file["id"]
+                    .as_str()
+                    .ok_or_else(|| "Google Drive did not return the new file.".to_string())?
+                    .to_string();
+                self.ids.files.insert(key.to_string(), id.clone());
+                self.save_ids()?;
+                id
+            }
+        };
+        let request = self
+            .client
+            .patch(format!("{UPLOAD_URL}/{id}?uploadType=media&fields=md5Checksum"))
+            .header("Content-Type", "text/markdown")
+            .body(content.to_string());
+        let file = self.json(request)?;
+        file["md5Checksum"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Google Drive did not return a checksum.".to_string())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        let id = self.file_id(key)?;
+        self.send(self.client.delete(format!("{FILES_URL}/{id}")))?;
+        self.ids.files.remove(key);
+        self.save_ids()
+    }
+}
+
+fn sync(app: &AppHandle) -> Result<CloudSyncReport, String> {
+    sync_status::syncing(app, "gdrive", 0, 0);
+    let result = read_config(app)
+        .ok_or_else(|| "Google Drive sync is not set up.".to_string())
+        .and_then(|config| {
+            crate::read_only::ensure_writable(app, &config.workspace_path).map_err(String::from)?;
+            let mut drive = GoogleDrive::connect(&config)?;
+            cloud_sync::sync(&config.workspace_path, &mut drive, &mut |done, total| {
+                sync_status::syncing(app, "gdrive", done, total)
+            })
+        });
+    sync_status::finish(app, "gdrive", result.as_ref().map_err(String::clone).and_then(CloudSyncReport::outcome));
+    let report = result?;
+    if let Err(err) = app.emit(GDRIVE_SYNC_EVENT, report.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(report)
+}
+
+/// Starts the device sign-in; the user enters `userCode` at `verificationUrl`.
+#[tauri::command]
+pub async fn begin_gdrive_auth(app: AppHandle) -> Result<DeviceSignIn, String> {
+    let (client_id, _) = client_credentials()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let response = cloud_sync::post_form(&Client::new(), DEVICE_CODE_URL, &[("client_id", client_id), ("scope", SCOPES)])?;
+        if !response.status().is_success() {
+            return Err(format!("Google refused the sign-in ({})", response.status()));
+        }
+        let body: Value = response.json().map_err(|err| format!("Unexpected Google response: {err}"))?;
+        let device: DeviceCode =
+            serde_json::from_value(body.clone()).map_err(|err| format!("Unexpected Google response: {err}"))?;
+        *app.state::<GoogleDriveAuth>().0.lock().unwrap() = Some(device);
+        Ok(DeviceSignIn {
+            user_code: body["user_code"].as_str().unwrap_or_default().to_string(),
+            verification_url: body["verification_url"].as_str().unwrap_or_default().to_string(),
+        })
+    })
+    .await
+    .map_err(|err| format!("Google Drive task failed: {err}"))?
+}
+
+/// Waits for the user to approve the sign-in, then syncs `workspace_path` with
+/// `folder` in My Drive, or with the hidden app data folder when it is `None`.
+#[tauri::command]
+pub async fn finish_gdrive_auth(
+    app: AppHandle,
+    workspace_path: String,
+    folder: Option<String>,
+) -> Result<CloudSyncReport, String> {
+    let device = app
+        .state::<GoogleDriveAuth>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "Start signing in to Google Drive again.".to_string())?;
+    let (client_id, client_secret) = client_credentials()?;
+    let folder = folder.map(|name| name.trim().to_string()).filter(|name| !name.is_empty());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let client = Client::new();
+        let deadline = cloud_sync::now_unix() + device.expires_in;
+        let mut interval = device.interval.max(1);
+        let response: TokenResponse = loop {
+            thread::sleep(Duration::from_secs(interval));
+            if cloud_sync::now_unix() > deadline {
+                return Err("The Google sign-in code expired; start again.".to_string());
+            }
+            let response = cloud_sync::post_form(
+                &client,
+                TOKEN_URL,
+                &[
+                    ("client_id", client_id),
+                    ("client_secret", client_secret),
+                    ("device_code", &device.device_code),
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ],
+            )?;
+            if response.status().is_success() {
+                break response.json().map_err(|err| format!("Unexpected Google response: {err}"))?;
+            }
+            let body: Value = response.json().unwrap_or_default();
+            match body["error"].as_str() {
+                Some("authorization_pending") => {}
+                Some("slow_down") => interval += 5,
+                Some("access_denied") => return Err("Google Drive access was declined.".to_string()),
+                _ => return Err("Google refused the sign-in.".to_string()),
+            }
+        };
+        OAuthTokens::granted(response)?.save(KEYCHAIN_ACCOUNT)?;
+
+        if let Some(previous) = read_config(&app) {
+            cloud_sync::reset(&previous.workspace_path, "gdrive");
+        }
+        let config = GoogleDriveConfig { workspace_path, folder };
+        let value =
+            serde_json::to_value(&config).map_err(|err| format!("Failed encoding Google Drive settings: {err}"))?;
+        crate::write_setting(&app, GDRIVE_SETTING, value)?;
+        sync(&app)
+    })
+    .await
+    .map_err(|err| format!("Google Drive sync task failed: {err}"))?
+}
+
+/// Stops syncing and revokes Hermes' access to the Google account.
+#[tauri::command]
+pub async fn disconnect_gdrive(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(tokens) = OAuthTokens::load(KEYCHAIN_ACCOUNT) {
+            if let Err(err) = cloud_sync::post_form(&Client::new(), REVOKE_URL, &[("token", &tokens.refresh_token)]) {
+                tracing::warn!("{}", err);
+            }
+        }
+        if let Some(config) = read_config(&app) {
+            cloud_sync::reset(&config.workspace_path, "gdrive");
+        }
+        keychain::delete(KEYCHAIN_ACCOUNT);
+        crate::write_setting(&app, GDRIVE_SETTING, Value::Null)
+    })
+    .await
+    .map_err(|err| format!("Google Drive task failed: {err}"))?
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub async fn sync_with_gdrive(app: AppHandle) -> Result<CloudSyncReport, String> {
+    tauri::async_runtime::spawn_blocking(move || sync(&app))
+        .await
+        .map_err(|err| format!("Google Drive sync task failed: {err}"))?
+}
@@ -0,0 +1,261 @@
+//! Sync a workspace through a git remote: commit, fetch, merge, push.
+//!
+//! Shells out to the user's `git` so their credentials and config apply. Local
+//! changes are committed first (Hermes' `.hermes` caches and anything matched
+//! by `.hermesignore` are never staged), then the upstream branch is merged. Conflicting markdown files go through the
+//! three-way merge engine; files it cannot merge keep our version and get the
+//! remote one alongside as a conflicted copy, which the conflict UI reports.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::ignore::IGNORE_FILE;
+use crate::{merge, sync_status};
+
+pub const GIT_SYNC_EVENT: &str = "git-sync-completed";
+const DEFAULT_REMOTE: &str = "origin";
+const CACHE_PATHSPEC: &str = ":(glob,exclude)**/.hermes/**";
+const EXCLUDE_BEGIN: &str = "# >>> .hermesignore (managed by Hermes)";
+const EXCLUDE_END: &str = "# <<< .hermesignore";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitSyncReport {
+    workspace_path: String,
+    /// Files the merge brought in from the remote, relative to the repository root.
+    changed: Vec<String>,
+    /// Files that could not be merged cleanly; the remote version sits next to each.
+    conflicts: Vec<String>,
+}
+
+fn git(workspace_path: &str, args: &[&str]) -> Result<Output, String> {
+    Command::new("git")
+        .arg("-C")
+        .arg(workspace_path)
+        .args(args)
+        // Never block on a credential prompt nobody can see.
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|err| format!("Failed to run git: {err}"))
+}
+
+/// Runs git and returns trimmed stdout, or stderr as the error.
+fn git_ok(workspace_path: &str, args: &[&str]) -> Result<String, String> {
+    let output = git(workspace_path, args)?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(format!(
+        "git {} failed: {}",
+        args.first().unwrap_or(&""),
+        if stderr.is_empty() { "unknown error" } else { &stderr }
+    ))
+}
+
+fn lines(text: &str) -> Vec<String> {
+    text.lines().filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
+/// Content of a conflicted file at merge stage 1 (base), 2 (ours) or 3 (theirs).
+fn stage(workspace_path: &str, stage: u8, path: &str) -> Option<String> {
+    git_ok(workspace_path, &["show", &format!(":{stage}:{path}")]).ok()
+}
+
+/// Mirrors `.hermesignore` into the repository's `info/exclude`, which shares
+/// its syntax, so git leaves ignored files untracked.
+fn sync_excludes(workspace_path: &str) -> Result<(), String> {
+    let patterns = fs::read_to_string(Path::new(workspace_path).join(IGNORE_FILE)).unwrap_or_default();
+    let exclude_path = Path::new(workspace_path).join(git_ok(workspace_path, &["rev-parse", "--git-path", "info/exclude"])?);
+    let existing = fs::read_to_string(&exclude_path).unwrap_or_default();
+
+    let mut kept = String::new();
+    let mut in_block = false;
+    for line in existing.lines() {
+        match line {
+            EXCLUDE_BEGIN => in_block = true,
+            EXCLUDE_END => in_block = false,
+            _ if !in_block => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            _ => {}
+        }
+    }
+    if !patterns.trim().is_empty() {
+        kept.push_str(&format!("{EXCLUDE_BEGIN}\n{}\n{EXCLUDE_END}\n", patterns.trim_end()));
+    }
+    if kept == existing {
+        return Ok(());
+    }
+
+    if let Some(dir) = exclude_path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    fs::write(&exclude_path, kept).map_err(|err| format!("Failed writing {}: {err}", exclude_path.display()))
+}
+
+fn commit_local_changes(workspace_path: &str) -> Result<(), String> {
+    sync_excludes(workspace_path)?;
+    git_ok(workspace_path, &["add", "-A", "--", ".", CACHE_PATHSPEC])?;
+    let staged = git(workspace_path, &["diff", "--cached", "--quiet"])?;
+    if staged.status.success() {
+        return Ok(());
+    }
+    let message = format!("Hermes sync {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    git_ok(workspace_path, &["commit", "-m", &message]).map(|_| ())
+}
+
+fn conflicted_copy_path(file: &Path, remote: &str) -> PathBuf {
+    let stem = file.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    file.with_file_name(format!("{stem} ({remote} conflicted copy).md"))
+}
+
+/// Resolves one conflicted markdown file; returns whether it needed a conflicted copy.
+fn resolve_markdown(workspace_path: &str, root: &Path, path: &str, remote: &str) -> Result<bool, String> {
+    let file = root.join(path);
+    let (ours, theirs) = match (stage(workspace_path, 2, path), stage(workspace_path, 3, path)) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        // Modified on one side, deleted on the other: keep the surviving edit.
+        (Some(content), None) | (None, Some(content)) => {
+            fs::write(&file, content).map_err(|err| format!("Failed writing {}: {err}", file.display()))?;
+            git_ok(workspace_path, &["add", "--", &format!(":(top){path}")])?;
+            return Ok(false);
+        }
+        (None, None) => return Err(format!("No merge stages for {path}")),
+    };
+    let base = stage(workspace_path, 1, path).unwrap_or_default();
+
+    let outcome = merge::merge(&base, &ours, &theirs);
+    let content = outcome.merged.as_deref().unwrap_or(&ours);
+    fs::write(&file, content).map_err(|err| format!("Failed writing {}: {err}", file.display()))?;
+    git_ok(workspace_path, &["add", "--", &format!(":(top){path}")])?;
+    if outcome.merged.is_some() {
+        return Ok(false);
+    }
+
+    let copy = conflicted_copy_path(&file, remote);
+    fs::write(&copy, &theirs).map_err(|err| format!("Failed writing {}: {err}", copy.display()))?;
+    git_ok(workspace_path, &["add", "--", &copy.to_string_lossy()])?;
+    Ok(true)
+}
+
+fn merge_upstream(workspace_path: &str, upstream: &str, remote: &str) -> Result<Vec<String>, String> {
+    let merged = git(workspace_path, &["merge", "--no-edit", upstream])?;
+    if merged.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let unmerged = lines(&git_ok(workspace_path, &["diff", "--name-only", "--diff-filter=U"])?);
+    if unmerged.is_empty() {
+        let stderr = String::from_utf8_lossy(&merged.stderr).trim().to_string();
+        return Err(format!("git merge failed: {stderr}"));
+    }
+    if let Some(other) = unmerged.iter().find(|path| !path.ends_with(".md")) {
+        let _ = git(workspace_path, &["merge", "--abort"]);
+        return Err(format!("Cannot sync: {other} conflicts with the remote and is not a note. Resolve it with git."));
+    }
+
+    let root = PathBuf::from(git_ok(workspace_path, &["rev-parse", "--show-toplevel"])?);
+    let mut conflicts = Vec::new();
+    for path in unmerged {
+        match resolve_markdown(workspace_path, &root, &path, remote) {
+            Ok(true) => conflicts.push(path),
+            Ok(false) => {}
+            Err(err) => {
+                let _ = git(workspace_path, &["merge", "--abort"]);
+                return Err(err);
+            }
+        }
+    }
+    git_ok(workspace_path, &["commit", "--no-edit"])?;
+    Ok(conflicts)
+}
+
+/// Reindexes every workspace folder whose notes the merge touched.
+fn reindex(workspace_path: &str, changed: &[String]) {
+    let Ok(prefix) = git_ok(workspace_path, &["rev-parse", "--show-prefix"]) else {
+        return;
+    };
+    let mut folders = BTreeSet::new();
+    for path in changed {
+        let Some(relative) = path.strip_prefix(&prefix) else {
+            continue;
+        };
+        let folder = Path::new(relative).parent().unwrap_or(Path::new(""));
+        folders.insert(Path::new(workspace_path).join(folder));
+    }
+    for folder in folders {
+        if let Err(err) = crate::read_workspace_pages(&folder.to_string_lossy()) {
+            tracing::warn!("{}", String::from(err));
+        }
+    }
+}
+
+fn sync(workspace_path: &str, remote: &str) -> Result<GitSyncReport, String> {
+    git_ok(workspace_path, &["rev-parse", "--is-inside-work-tree"])
+        .map_err(|_| "This workspace is not a git repository.".to_string())?;
+    let branch = git_ok(workspace_path, &["symbolic-ref", "--short", "HEAD"])
+        .map_err(|_| "The workspace repository has no branch checked out.".to_string())?;
+
+    commit_local_changes(workspace_path)?;
+    git_ok(workspace_path, &["fetch", remote])?;
+
+    let upstream = git_ok(workspace_path, &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .unwrap_or_else(|_| format!("{remote}/{branch}"));
+    let has_upstream = git(workspace_path, &["rev-parse", "--verify", "--quiet", &upstream])?
+        .status
+        .success();
+
+    let mut changed = Vec::new();
+    let mut conflicts = Vec::new();
+    if has_upstream {
+        let before = git_ok(workspace_path, &["rev-parse", "HEAD"]).ok();
+        conflicts = merge_upstream(workspace_path, &upstream, remote)?;
+        if let Some(before) = before {
+            changed = lines(&git_ok(workspace_path, &["diff", "--name-only", &before, "HEAD"])?);
+        }
+    }
+
+    git_ok(workspace_path, &["push", "--set-upstream", remote, &format!("HEAD:{branch}")])?;
+    reindex(workspace_path, &changed);
+
+    Ok(GitSyncReport {
+        workspace_path: workspace_path.to_string(),
+        changed,
+        conflicts,
+    })
+}
+
+/// Commits local edits, merges the remote branch and pushes; `remote` defaults to origin.
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub async fn sync_with_git_remote(
+    app: AppHandle,
+    workspace_path: String,
+    remote: Option<String>,
+) -> Result<GitSyncReport, String> {
+    let remote = remote.unwrap_or_else(|| DEFAULT_REMOTE.to_string());
+    if remote.is_empty() || remote.starts_with('-') {
+        return Err(format!("Invalid git remote '{remote}'"));
+    }
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    sync_status::syncing(&app, "git", 0, 0);
+    let result = tauri::async_runtime::spawn_blocking(move || sync(&workspace_path, &remote))
+        .await
+        .map_err(|err| format!("Git sync task failed: {err}"))
+        .and_then(|result| result);
+    sync_status::finish(&app, "git", result.as_ref().map(|report| report.conflicts.clone()).map_err(String::clone));
+    let report = result?;
+
+    if let Err(err) = app.emit(GIT_SYNC_EVENT, report.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(report)
+}
@@ -0,0 +1,181 @@
+//! Saves held back while a workspace's folder is gone.
+//!
+//! A workspace on an ejected drive, or whose folder was deleted or renamed,
+//! can't be written. Saves to it are kept in app data instead, one file per
+//! workspace, and `WORKSPACE_UNAVAILABLE_EVENT` tells the frontend. Once the
+//! folder is back, the next save to it, or the check every `RECHECK_EVERY`,
+//! writes the held tabs under any newer content and sends
+//! `WORKSPACE_AVAILABLE_EVENT`. Emptied tabs aren't held: replaying them
+//! would delete notes, which is worse than keeping one the user cleared.
+//! Locked notes are held sealed. Saves to a workspace marked read-only are
+//! refused rather than held, and held saves wait while a returned folder
+//! can't be written.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{file_open, versions, workspace_move};
+
+pub const WORKSPACE_UNAVAILABLE_EVENT: &str = "workspace://unavailable";
+pub const WORKSPACE_AVAILABLE_EVENT: &str = "workspace://available";
+
+const HELD_SAVES_DIR: &str = "held-saves";
+const RECHECK_EVERY: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeldSaves {
+    workspace_path: String,
+    pages: HashMap<String, String>,
+    held_unix: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceAvailability {
+    workspace_path: String,
+    /// Tabs held in app data until the folder is back, or just written back to it.
+    tabs: Vec<String>,
+}
+
+fn held_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(HELD_SAVES_DIR))
+        .map_err(|err| format!("Failed resolving app data directory: {err}"))
+}
+
+fn held_path(app: &AppHandle, workspace_path: &str) -> Result<PathBuf, String> {
+    let name = &versions::content_hash(workspace_path.as_bytes())[..16];
+    Ok(held_dir(app)?.join(format!("{name}.json")))
+}
+
+fn read_held(path: &Path) -> Option<HeldSaves> {
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn emit(app: &AppHandle, event: &str, workspace_path: &str, tabs: impl IntoIterator<Item = String>) {
+    let mut tabs: Vec<String> = tabs.into_iter().collect();
+    tabs.sort();
+    let payload = WorkspaceAvailability {
+        workspace_path: workspace_path.to_string(),
+        tabs,
+    };
+    if let Err(err) = app.emit(event, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+/// Whether the folder `workspace_path` lives in is there: the configured
+/// workspace root for its projects, else the workspace folder itself.
+pub fn is_available(app: &AppHandle, workspace_path: &str) -> bool {
+    let path = Path::new(workspace_path);
+    match file_open::configured_workspace(app) {
+        Some(root) if path.starts_with(&root) => root.is_dir(),
+        _ => path.is_dir(),
+    }
+}
+
+/// Keeps `pages` in app data until the workspace is back.
+pub fn hold(app: &AppHandle, workspace_path: &str, pages: &HashMap<String, String>) -> Result<(), String> {
+    let path = held_path(app, workspace_path)?;
+    let mut held = read_held(&path).unwrap_or_else(|| HeldSaves {
+        workspace_path: workspace_path.to_string(),
+        pages: HashMap::new(),
+        held_unix: 0,
+    });
+    let first = held.pages.is_empty();
+    held.pages
+        .extend(pages.iter().filter(|(_, content)| !content.trim().is_empty()).map(|(tab, content)| (tab.clone(), content.clone())));
+    held.held_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    fs::create_dir_all(held_dir(app)?).map_err(|err| format!("Failed creating held saves directory: {err}"))?;
+    let json = serde_json::to_vec(&held).map_err(|err| format!("Failed encoding held saves: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+    if first {
+        tracing::warn!("Workspace {workspace_path} is unavailable; holding saves until it returns");
+        emit(app, WORKSPACE_UNAVAILABLE_EVENT, workspace_path, held.pages.into_keys());
+    }
+    Ok(())
+}
+
+/// Saves held for `workspace_path`, if any.
+pub fn held(app: &AppHandle, workspace_path: &str) -> Option<HashMap<String, String>> {
+    let path = held_path(app, workspace_path).ok()?;
+    read_held(&path).map(|held| held.pages)
+}
+
+/// Drops the held saves of `workspace_path` once they are written back.
+pub fn release(app: &AppHandle, workspace_path: &str, tabs: impl IntoIterator<Item = String>) {
+    let Ok(path) = held_path(app, workspace_path) else {
+        return;
+    };
+    if let Err(err) = fs::remove_file(&path) {
+        tracing::warn!("Failed removing {}: {err}", path.display());
+    }
+    tracing::info!("Workspace {workspace_path} is back; wrote held saves");
+    emit(app, WORKSPACE_AVAILABLE_EVENT, workspace_path, tabs);
+}
+
+/// Follows workspaces moved from under `old_path` to `new_path`.
+pub fn relocate(app: &AppHandle, old_path: &str, new_path: &str) {
+    let Ok(dir) = held_dir(app) else {
+        return;
+    };
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let Some(mut held) = read_held(&entry.path()) else {
+            continue;
+        };
+        let Some(rebased) = workspace_move::rebase(&held.workspace_path, old_path, new_path) else {
+            continue;
+        };
+        held.workspace_path = rebased;
+        let moved = held_path(app, &held.workspace_path).and_then(|path| {
+            let json = serde_json::to_vec(&held).map_err(|err| format!("Failed encoding held saves: {err}"))?;
+            fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+            fs::remove_file(entry.path()).map_err(|err| format!("Failed removing {}: {err}", entry.path().display()))
+        });
+        if let Err(err) = moved {
+            tracing::warn!("{}", err);
+        }
+    }
+}
+
+/// Writes held saves back to workspaces that have returned.
+fn replay_returned(app: &AppHandle) {
+    let Ok(dir) = held_dir(app) else {
+        return;
+    };
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let Some(held) = read_held(&entry.path()) else {
+            continue;
+        };
+        // A folder back read-only keeps its held saves until it can be written.
+        if !is_available(app, &held.workspace_path) || crate::read_only::reason(app, &held.workspace_path).is_some() {
+            continue;
+        }
+        // An empty save writes just the held tabs; see `write_pages`.
+        if let Err(err) = crate::save_queue::save(app, &held.workspace_path, HashMap::new()) {
+            tracing::warn!("{}", String::from(err));
+        }
+    }
+}
+
+/// Starts checking for workspaces with held saves coming back.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        replay_returned(&app);
+        thread::sleep(RECHECK_EVERY);
+    });
+}
@@ -0,0 +1,412 @@
+//! Earlier versions of a tab and word-level diffs between them.
+//!
+//! A version is a snapshot kept in `.hermes/history/<tab>/<id>.md`, a git
+//! revision of the tab file when the workspace is in a repository, the file
+//! as it is on disk, or unsaved editor content passed in by the frontend.
+//! Restoring a version snapshots the current file first, so it can be undone.
+//!
+//! Every few minutes notes that changed since their last snapshot are
+//! snapshotted, and old snapshots are thinned out: all are kept for a day, the
+//! newest per hour for a week, the newest per day for a year.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::AppHandle;
+
+use crate::progress::Progress;
+use crate::{
+    app_lock, crdt, file_hashes, file_open, hermes_dir, ignore, merge, note_lock, note_path, read_project_names, read_workspace_pages,
+    tab_files, undo, validate_tab_key, versions, workspace_lock, TAB_KEYS,
+};
+
+/// Settings-store key for the automatic snapshot interval; 0 turns it off.
+const SNAPSHOT_INTERVAL_SETTING: &str = "snapshotIntervalMinutes";
+const DEFAULT_SNAPSHOT_INTERVAL_MINUTES: u64 = 10;
+
+const HOUR_MS: u128 = 60 * 60 * 1000;
+const DAY_MS: u128 = 24 * HOUR_MS;
+
+/// Where one side of a diff comes from.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum VersionSource {
+    Snapshot { id: String },
+    Git { rev: String },
+    Current,
+    Buffer { content: String },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteVersion {
+    kind: &'static str,
+    /// Snapshot ID or git commit hash.
+    id: String,
+    created_unix: i64,
+    summary: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A stretch of the diff. Offsets are UTF-16 positions, as the editor counts them:
+/// where the run starts in the old text and in the new text.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffRun {
+    kind: RunKind,
+    text: String,
+    old_offset: usize,
+    new_offset: usize,
+}
+
+pub fn snapshots_dir(workspace_path: &str, tab: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("history").join(tab)
+}
+
+/// Snapshot IDs are the millisecond timestamps they were taken at.
+fn validate_snapshot_id(id: &str) -> Result<(), String> {
+    if !id.is_empty() && id.chars().all(|ch| ch.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(format!("Invalid snapshot '{id}'"))
+    }
+}
+
+fn git_output(workspace_path: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workspace_path)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Failed to run git: {err}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("git {} failed: {stderr}", args.first().unwrap_or(&"")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn read_version(workspace_path: &str, tab: &str, source: VersionSource) -> Result<String, String> {
+    match source {
+        VersionSource::Snapshot { id } => {
+            validate_snapshot_id(&id)?;
+            let path = snapshots_dir(workspace_path, tab).join(format!("{id}.md"));
+            fs::read_to_string(&path).map_err(|err| format!("Failed reading snapshot {id}: {err}"))
+        }
+        VersionSource::Git { rev } => {
+            if rev.starts_with('-') || rev.contains(':') {
+                return Err(format!("Invalid git revision '{rev}'"));
+            }
+            // `./` resolves the path relative to the workspace rather than the repository root.
+            git_output(workspace_path, &["show", &format!("{rev}:./{}", tab_files::file_name(workspace_path, tab))])
+        }
+        VersionSource::Current => {
+            let path = note_path(workspace_path, tab);
+            if !path.exists() {
+                return Ok(String::new());
+            }
+            fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))
+        }
+        VersionSource::Buffer { content } => Ok(content),
+    }
+}
+
+fn snapshot_versions(dir: &Path) -> Vec<NoteVersion> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let id = name.strip_suffix(".md")?.to_string();
+            let millis: i64 = id.parse().ok()?;
+            Some(NoteVersion {
+                kind: "snapshot",
+                id,
+                created_unix: millis / 1000,
+                summary: None,
+            })
+        })
+        .collect()
+}
+
+fn git_versions(workspace_path: &str, tab: &str) -> Vec<NoteVersion> {
+    let file_name = tab_files::file_name(workspace_path, tab);
+    let Ok(log) = git_output(workspace_path, &["log", "--format=%H%x09%ct%x09%s", "--", &file_name]) else {
+        return Vec::new();
+    };
+    log.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let id = fields.next()?.to_string();
+            let created_unix = fields.next()?.parse().ok()?;
+            Some(NoteVersion {
+                kind: "git",
+                id,
+                created_unix,
+                summary: fields.next().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Word-level diff, with adjacent changes of the same kind joined into one run.
+pub fn word_diff(old: &str, new: &str) -> Vec<DiffRun> {
+    let diff = TextDiff::configure().diff_words(old, new);
+    let mut runs: Vec<DiffRun> = Vec::new();
+    let (mut old_offset, mut new_offset) = (0, 0);
+    for change in diff.iter_all_changes() {
+        let kind = match change.tag() {
+            ChangeTag::Equal => RunKind::Equal,
+            ChangeTag::Insert => RunKind::Insert,
+            ChangeTag::Delete => RunKind::Delete,
+        };
+        let text = change.value();
+        match runs.last_mut() {
+            Some(run) if run.kind == kind => run.text.push_str(text),
+            _ => runs.push(DiffRun {
+                kind,
+                text: text.to_string(),
+                old_offset,
+                new_offset,
+            }),
+        }
+
+        let len = text.encode_utf16().count();
+        if kind != RunKind::Insert {
+            old_offset += len;
+        }
+        if kind != RunKind::Delete {
+            new_offset += len;
+        }
+    }
+    runs
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0)
+}
+
+/// Snapshot IDs of a tab, newest first.
+fn snapshot_ids(dir: &Path) -> Vec<u128> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<u128> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_string_lossy().strip_suffix(".md")?.parse().ok())
+        .collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    ids
+}
+
+/// Saves `content` as a new snapshot of the tab and returns its ID.
+pub fn snapshot(workspace_path: &str, tab: &str, content: &str) -> Result<String, String> {
+    let dir = snapshots_dir(workspace_path, tab);
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+
+    let mut millis = now_millis();
+    while dir.join(format!("{millis}.md")).exists() {
+        millis += 1;
+    }
+    let path = dir.join(format!("{millis}.md"));
+    fs::write(&path, content).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+    Ok(millis.to_string())
+}
+
+/// Snapshots the tab if it differs from its newest snapshot.
+fn snapshot_if_changed(workspace_path: &str, tab: &str) -> Result<(), String> {
+    let path = note_path(workspace_path, tab);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let dir = snapshots_dir(workspace_path, tab);
+    let latest = snapshot_ids(&dir)
+        .first()
+        .and_then(|id| fs::read_to_string(dir.join(format!("{id}.md"))).ok());
+    if latest.as_deref() == Some(content.as_str()) || (latest.is_none() && content.trim().is_empty()) {
+        return Ok(());
+    }
+    snapshot(workspace_path, tab, &content).map(|_| ())
+}
+
+/// Deletes snapshots the retention schedule no longer needs.
+fn compact(dir: &Path, now: u128) {
+    let mut kept_buckets = HashSet::new();
+    for id in snapshot_ids(dir) {
+        let age = now.saturating_sub(id);
+        if age < DAY_MS {
+            continue;
+        }
+        let bucket = if age < 7 * DAY_MS {
+            Some((HOUR_MS, id / HOUR_MS))
+        } else if age < 365 * DAY_MS {
+            Some((DAY_MS, id / DAY_MS))
+        } else {
+            None
+        };
+        // Newest first, so the first snapshot seen in a bucket is the one to keep.
+        if bucket.is_some_and(|bucket| kept_buckets.insert(bucket)) {
+            continue;
+        }
+        let path = dir.join(format!("{id}.md"));
+        if let Err(err) = fs::remove_file(&path) {
+            tracing::warn!("Failed removing {}: {err}", path.display());
+        }
+    }
+}
+
+/// Snapshots changed tabs of the configured workspace and its projects, then compacts.
+fn snapshot_workspace(app: &AppHandle) {
+    let Some(workspace) = file_open::configured_workspace(app) else {
+        return;
+    };
+    let root = workspace.to_string_lossy().to_string();
+    let mut folders = vec![root.clone()];
+    for project in read_project_names(&root).unwrap_or_default() {
+        folders.push(workspace.join(project).to_string_lossy().to_string());
+    }
+
+    let now = now_millis();
+    let progress = Progress::new(app, "backup", None);
+    let total = folders.len();
+    for (done, folder) in folders.into_iter().enumerate() {
+        progress.report("snapshots", done, total);
+        for tab in TAB_KEYS {
+            if ignore::is_ignored(&note_path(&folder, tab)) {
+                continue;
+            }
+            if let Err(err) = snapshot_if_changed(&folder, tab) {
+                tracing::warn!("{}", err);
+            }
+            compact(&snapshots_dir(&folder, tab), now);
+        }
+    }
+    progress.finish(total);
+}
+
+fn snapshot_interval(app: &AppHandle) -> u64 {
+    crate::read_setting(app, SNAPSHOT_INTERVAL_SETTING)
+        .and_then(|value| value.as_u64())
+        .unwrap_or(DEFAULT_SNAPSHOT_INTERVAL_MINUTES)
+}
+
+/// Starts the background snapshot and compaction task.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        // Re-read each round so a changed interval applies without a restart.
+        let minutes = snapshot_interval(&app);
+        thread::sleep(Duration::from_secs(minutes.max(1) * 60));
+        if minutes > 0 {
+            snapshot_workspace(&app);
+        }
+    });
+}
+
+/// Writes through a temporary file so a crash never leaves a half-written note.
+pub fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.restore"));
+    fs::write(&temp, content).map_err(|err| format!("Failed writing {}: {err}", temp.display()))?;
+    fs::rename(&temp, path).map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed replacing {}: {err}", path.display())
+    })
+}
+
+/// Snapshots and git revisions of a tab, newest first.
+#[tauri::command]
+pub fn list_note_versions(app: AppHandle, workspace_path: String, tab_key: String) -> Result<Vec<NoteVersion>, String> {
+    app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let mut versions = snapshot_versions(&snapshots_dir(&workspace_path, &tab_key));
+    versions.extend(git_versions(&workspace_path, &tab_key));
+    versions.sort_by_key(|version| std::cmp::Reverse(version.created_unix));
+    Ok(versions)
+}
+
+#[tauri::command]
+pub fn diff_note_versions(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    a: VersionSource,
+    b: VersionSource,
+) -> Result<Vec<DiffRun>, String> {
+    app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let old = read_version(&workspace_path, &tab_key, a)?;
+    let new = read_version(&workspace_path, &tab_key, b)?;
+    Ok(word_diff(&old, &new))
+}
+
+/// Replaces the tab with an earlier version, snapshotting the current content
+/// first. `version_id` is a snapshot ID or git commit from `list_note_versions`.
+/// Returns the diff from the replaced content to the restored one.
+#[tauri::command]
+pub fn restore_note_version(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    version_id: String,
+) -> Result<Vec<DiffRun>, String> {
+    app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    // Restored plaintext would replace the sealed note.
+    if note_lock::is_locked_file(&file_path) {
+        return Err("Unlock the note before restoring a version.".to_string());
+    }
+    // Edits still waiting in the editor are written, and so snapshotted, first.
+    crate::autosave::flush(&app, Some(&crate::canonical_workspace(&workspace_path))).map_err(String::from)?;
+    let source = if validate_snapshot_id(&version_id).is_ok()
+        && snapshots_dir(&workspace_path, &tab_key).join(format!("{version_id}.md")).exists()
+    {
+        VersionSource::Snapshot { id: version_id }
+    } else {
+        VersionSource::Git { rev: version_id }
+    };
+    let write_lock = workspace_lock::acquire(&workspace_path)?;
+    let restored = read_version(&workspace_path, &tab_key, source)?;
+    let current = read_version(&workspace_path, &tab_key, VersionSource::Current)?;
+    if restored == current {
+        return Ok(word_diff(&current, &restored));
+    }
+
+    snapshot(&workspace_path, &tab_key, &current)?;
+    write_atomically(&file_path, &restored)?;
+
+    // The same bookkeeping as an editor save.
+    file_hashes::remember(&file_path, &restored);
+    if !note_lock::is_locked(&restored) {
+        merge::store_base(&workspace_path, &tab_key, &restored);
+        crdt::record(&workspace_path, &tab_key, &restored);
+        undo::record(&workspace_path, &tab_key, &restored);
+    }
+    versions::record_saves(&app, &workspace_path, &HashMap::from([(tab_key.clone(), restored.clone())]));
+    drop(write_lock);
+    crate::changes::note_saved(&app, &workspace_path, &tab_key);
+    if let Err(err) = read_workspace_pages(&workspace_path) {
+        tracing::warn!("{}", String::from(err));
+    }
+    Ok(word_diff(&current, &restored))
+}
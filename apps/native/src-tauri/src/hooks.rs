@@ -0,0 +1,529 @@
+//! User hooks: commands run after a note is saved or exported.
+//!
+//! A workspace's `.hermes/hooks.toml` lists them:
+//!
+//! ```toml
+//! [[hook]]
+//! event = "save"              # or "export"
+//! command = "./publish.sh"    # a program; relative paths are from the workspace
+//! args = ["--draft"]          # passed before the note path and tab
+//! projects = ["blog"]         # optional; "" is the workspace root
+//! tabs = ["sage"]             # optional
+//! timeout = 30                # seconds, 30 by default
+//!
+//! [[hook]]
+//! event = "save"
+//! shell = "git add -A && git commit -qm \"$2\""   # run by sh, or cmd on Windows
+//! ```
+//!
+//! A hook gets the note path and tab as its last two arguments, or as `$1`
+//! and `$2` in a shell line outside Windows, and `HERMES_EVENT`, `HERMES_WORKSPACE`,
+//! `HERMES_PROJECT`, `HERMES_NOTE_PATH`, `HERMES_TAB` and, for exports,
+//! `HERMES_EXPORT_PATH` in its environment. Hooks run in the background from
+//! the workspace folder, one after another, and are killed at their timeout
+//! along with every process they started.
+//!
+//! The file travels with a synced workspace, so its commands could come from
+//! someone else: nothing runs until the user allows the file in settings, and
+//! any change to it, or to a script in the workspace that it runs, needs
+//! allowing again. Scripts count when they are a hook's `command` or appear
+//! as a relative path with a folder in it, like `./publish.sh`, in a shell
+//! line; what those scripts go on to run is up to them.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+use crate::{canonical_path, hermes_dir, note_path, project_list};
+
+pub const HOOK_FINISHED_EVENT: &str = "hermes://hook-finished";
+const HOOKS_FILE: &str = "hooks.toml";
+/// Settings-store key holding `{ [workspace]: sha256 of the allowed hooks.toml }`.
+const TRUSTED_HOOKS_SETTING: &str = "trustedHooks";
+const MAX_HOOKS_FILE_BYTES: u64 = 64 * 1024;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 600;
+const MAX_OUTPUT_BYTES: usize = 16 * 1024;
+/// How long output is still read after a hook exits, from processes it left running.
+const OUTPUT_GRACE: Duration = Duration::from_secs(1);
+const RECENT_RUNS: usize = 20;
+
+/// The last runs, newest first, for settings to show.
+static RECENT: Mutex<VecDeque<HookRun>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookEvent {
+    Save,
+    Export,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Hook {
+    event: HookEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shell: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    projects: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tabs: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct HooksFile {
+    #[serde(default)]
+    hook: Vec<Hook>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HooksStatus {
+    hooks: Vec<Hook>,
+    /// Whether the file as it is now was allowed to run.
+    trusted: bool,
+    /// Why the file can't be used, when it doesn't parse.
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRun {
+    workspace_path: String,
+    event: HookEvent,
+    command: String,
+    tab: String,
+    /// The exit code; `None` when killed or not started.
+    status: Option<i32>,
+    timed_out: bool,
+    /// Standard output then standard error, cut short when long.
+    output: String,
+    duration_ms: u64,
+    finished_at: String,
+}
+
+/// What a hook is told about the note.
+pub struct Context<'a> {
+    pub event: HookEvent,
+    pub folder: &'a str,
+    pub tab: &'a str,
+    pub export_path: Option<&'a str>,
+}
+
+fn hooks_path(root: &str) -> PathBuf {
+    hermes_dir(root).join(HOOKS_FILE)
+}
+
+/// The workspace holding `folder`, which may be a project's folder.
+fn root_of(folder: &str) -> String {
+    hermes_core::projects::root_of(Path::new(folder))
+        .unwrap_or_else(|| canonical_path(Path::new(folder)))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The file's text, or `None` when there is none.
+fn read_file(root: &str) -> Result<Option<String>, String> {
+    let path = hooks_path(root);
+    let Ok(metadata) = fs::metadata(&path) else {
+        return Ok(None);
+    };
+    if metadata.len() > MAX_HOOKS_FILE_BYTES {
+        return Err(format!("{} is too large.", path.display()));
+    }
+    fs::read_to_string(&path)
+        .map(Some)
+        .map_err(|err| format!("Failed reading {}: {err}", path.display()))
+}
+
+fn parse(text: &str) -> Result<Vec<Hook>, String> {
+    let file: HooksFile = toml::from_str(text).map_err(|err| format!("Invalid {HOOKS_FILE}: {err}"))?;
+    for hook in &file.hook {
+        if hook.command.is_some() == hook.shell.is_some() {
+            return Err(format!("Every hook in {HOOKS_FILE} needs either `command` or `shell`."));
+        }
+    }
+    Ok(file.hook)
+}
+
+/// A program path that is from the workspace: relative, with a folder in it.
+fn workspace_program(root: &str, program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    (path.is_relative() && path.components().count() > 1).then(|| Path::new(root).join(path))
+}
+
+/// What allowing the hooks of `root` covers: `text`, the file, and the
+/// workspace scripts its hooks run.
+fn digest(root: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    let mut scripts: Vec<PathBuf> = parse(text)
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|hook| hook.scripts(root))
+        .collect();
+    scripts.sort();
+    scripts.dedup();
+    for script in scripts {
+        hasher.update(b"\0");
+        hasher.update(script.to_string_lossy().as_bytes());
+        hasher.update(b"\0");
+        match fs::read(&script) {
+            Ok(bytes) => hasher.update(Sha256::digest(&bytes)),
+            Err(_) => hasher.update(b"missing"),
+        }
+    }
+    hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn trusted_digests(app: &AppHandle) -> HashMap<String, String> {
+    crate::read_setting(app, TRUSTED_HOOKS_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// The hooks of `root` for `event`, when the file is allowed as it is now.
+fn trusted_hooks(app: &AppHandle, root: &str, event: HookEvent) -> Vec<Hook> {
+    let text = match read_file(root) {
+        Ok(Some(text)) => text,
+        Ok(None) => return Vec::new(),
+        Err(err) => {
+            tracing::warn!("{err}");
+            return Vec::new();
+        }
+    };
+    if trusted_digests(app).get(root) != Some(&digest(root, &text)) {
+        tracing::info!("Skipping hooks of {root}; they are not allowed yet.");
+        return Vec::new();
+    }
+    match parse(&text) {
+        Ok(hooks) => hooks.into_iter().filter(|hook| hook.event == event).collect(),
+        Err(err) => {
+            tracing::warn!("{err}");
+            Vec::new()
+        }
+    }
+}
+
+impl Hook {
+    fn applies_to(&self, project: &str, tab: &str) -> bool {
+        self.projects.as_ref().is_none_or(|projects| projects.iter().any(|name| name == project))
+            && self.tabs.as_ref().is_none_or(|tabs| tabs.iter().any(|name| name == tab))
+    }
+
+    fn label(&self) -> String {
+        match (&self.command, &self.shell) {
+            (Some(command), _) => std::iter::once(command.as_str()).chain(self.args.iter().map(String::as_str)).collect::<Vec<_>>().join(" "),
+            (None, Some(shell)) => shell.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// The workspace scripts the hook runs, whether or not they exist yet.
+    /// Paths in a shell line only count when they name a file.
+    fn scripts(&self, root: &str) -> Vec<PathBuf> {
+        match (&self.command, &self.shell) {
+            (Some(program), _) => workspace_program(root, program).into_iter().collect(),
+            (None, Some(shell)) => shell
+                .split(|ch: char| ch.is_whitespace() || ";&|()<>\"'`".contains(ch))
+                .filter_map(|word| workspace_program(root, word))
+                .filter(|path| path.is_file())
+                .collect(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    fn command(&self, root: &str, note: &Path, tab: &str) -> Command {
+        let note = note.to_string_lossy().to_string();
+        match (&self.command, &self.shell) {
+            (Some(program), _) => {
+                // A relative path with a folder in it is from the workspace, not from Hermes.
+                let program = workspace_program(root, program).unwrap_or_else(|| PathBuf::from(program));
+                let mut command = Command::new(program);
+                command.args(&self.args).arg(note).arg(tab);
+                command
+            }
+            (None, shell) => {
+                let line = shell.clone().unwrap_or_default();
+                #[cfg(windows)]
+                {
+                    let mut command = Command::new("cmd");
+                    command.arg("/C").arg(line);
+                    command
+                }
+                #[cfg(not(windows))]
+                {
+                    let mut command = Command::new("sh");
+                    command.arg("-c").arg(line).arg("hermes-hook").arg(note).arg(tab);
+                    command
+                }
+            }
+        }
+    }
+}
+
+/// Output read from a pipe by a thread of its own.
+struct Drained {
+    kept: Arc<Mutex<Vec<u8>>>,
+    reader: thread::JoinHandle<()>,
+}
+
+impl Drained {
+    /// What was read, after waiting until `deadline` for the pipe to close.
+    /// Processes the hook left behind may hold it open for good.
+    fn collect(self, deadline: Instant) -> Vec<u8> {
+        while !self.reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        std::mem::take(&mut *self.kept.lock().unwrap())
+    }
+}
+
+/// Reads a pipe to its end, keeping the first `MAX_OUTPUT_BYTES`.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> Drained {
+    let kept = Arc::new(Mutex::new(Vec::new()));
+    let shared = Arc::clone(&kept);
+    let reader = thread::spawn(move || {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut buffer = [0u8; 4096];
+        while let Ok(read) = pipe.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let mut kept = shared.lock().unwrap();
+            let room = MAX_OUTPUT_BYTES.saturating_sub(kept.len());
+            kept.extend_from_slice(&buffer[..read.min(room)]);
+        }
+    });
+    Drained { kept, reader }
+}
+
+/// A hook's process and everything it starts. On Unix the hook leads a
+/// process group of its own; on Windows it is put in a job object.
+struct ProcessTree {
+    #[cfg(windows)]
+    job: Option<windows::Win32::Foundation::HANDLE>,
+}
+
+impl ProcessTree {
+    #[cfg(unix)]
+    fn new(_child: &Child) -> ProcessTree {
+        ProcessTree {}
+    }
+
+    #[cfg(windows)]
+    fn new(child: &Child) -> ProcessTree {
+        use std::os::windows::io::AsRawHandle;
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+        // SAFETY: the child's handle is valid while `child` is alive, and the
+        // job handle is closed once, when the tree is dropped.
+        let job = unsafe {
+            CreateJobObjectW(None, PCWSTR::null()).ok().filter(|job| {
+                let assigned = AssignProcessToJobObject(*job, HANDLE(child.as_raw_handle())).is_ok();
+                if !assigned {
+                    let _ = CloseHandle(*job);
+                }
+                assigned
+            })
+        };
+        if job.is_none() {
+            tracing::warn!("Failed to put a hook in a job object; a timeout only stops the hook itself");
+        }
+        ProcessTree { job }
+    }
+
+    /// Kills the hook and every process it started that is still in its group or job.
+    fn kill(&self, child: &mut Child) {
+        #[cfg(unix)]
+        if let Ok(group) = i32::try_from(child.id()) {
+            // SAFETY: the hook was started as the leader of its own group, so
+            // the group's id is its pid.
+            unsafe { libc::kill(-group, libc::SIGKILL) };
+        }
+        #[cfg(windows)]
+        if let Some(job) = self.job {
+            // SAFETY: the job handle stays open until the tree is dropped.
+            let _ = unsafe { windows::Win32::System::JobObjects::TerminateJobObject(job, 1) };
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if let Some(job) = self.job.take() {
+            // SAFETY: the handle was opened by `new` and is closed only here.
+            let _ = unsafe { windows::Win32::Foundation::CloseHandle(job) };
+        }
+    }
+}
+
+/// Waits for `child` until `timeout`, killing its whole tree then; returns its
+/// exit code and whether it timed out.
+fn wait(child: &mut Child, tree: &ProcessTree, timeout: Duration) -> (Option<i32>, bool) {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return (status.code(), false),
+            Ok(None) if started.elapsed() >= timeout => {
+                tree.kill(child);
+                return (None, true);
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(err) => {
+                tracing::warn!("Failed waiting for hook: {err}");
+                tree.kill(child);
+                return (None, false);
+            }
+        }
+    }
+}
+
+fn run_hook(hook: &Hook, root: &str, project: &str, context: &Context) -> HookRun {
+    let started = Instant::now();
+    let note = note_path(context.folder, context.tab);
+    let mut command = hook.command(root, &note, context.tab);
+    command
+        .current_dir(root)
+        .env("HERMES_EVENT", if context.event == HookEvent::Save { "save" } else { "export" })
+        .env("HERMES_WORKSPACE", root)
+        .env("HERMES_PROJECT", project)
+        .env("HERMES_NOTE_PATH", &note)
+        .env("HERMES_TAB", context.tab)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(export_path) = context.export_path {
+        command.env("HERMES_EXPORT_PATH", export_path);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let (status, timed_out, output) = match command.spawn() {
+        Ok(mut child) => {
+            let tree = ProcessTree::new(&child);
+            let stdout = drain(child.stdout.take());
+            let stderr = drain(child.stderr.take());
+            let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS));
+            let (status, timed_out) = wait(&mut child, &tree, timeout);
+            // After a timeout the tree is dead; anything still holding the pipes escaped it.
+            let deadline = Instant::now() + if timed_out { Duration::ZERO } else { OUTPUT_GRACE };
+            let mut output = stdout.collect(deadline);
+            output.extend(stderr.collect(deadline));
+            (status, timed_out, String::from_utf8_lossy(&output).to_string())
+        }
+        Err(err) => (None, false, format!("Failed starting hook: {err}")),
+    };
+    HookRun {
+        workspace_path: root.to_string(),
+        event: context.event,
+        command: hook.label(),
+        tab: context.tab.to_string(),
+        status,
+        timed_out,
+        output,
+        duration_ms: started.elapsed().as_millis() as u64,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Runs the allowed hooks for a saved or exported note in the background.
+pub fn fire(app: &AppHandle, context: Context) {
+    let root = root_of(context.folder);
+    let hooks = trusted_hooks(app, &root, context.event);
+    let project = project_list::project_of(context.folder).unwrap_or_default();
+    let hooks: Vec<Hook> = hooks.into_iter().filter(|hook| hook.applies_to(&project, context.tab)).collect();
+    if hooks.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    let (event, folder, tab) = (context.event, context.folder.to_string(), context.tab.to_string());
+    let export_path = context.export_path.map(str::to_string);
+    thread::spawn(move || {
+        let context = Context { event, folder: &folder, tab: &tab, export_path: export_path.as_deref() };
+        for hook in &hooks {
+            let run = run_hook(hook, &root, &project, &context);
+            if run.timed_out || run.status != Some(0) {
+                tracing::warn!("Hook '{}' failed ({:?}): {}", run.command, run.status, run.output.trim());
+            } else {
+                tracing::info!("Hook '{}' finished in {} ms", run.command, run.duration_ms);
+            }
+            if let Err(err) = app.emit(HOOK_FINISHED_EVENT, run.clone()) {
+                tracing::warn!("Failed to emit event: {err}");
+            }
+            let mut recent = RECENT.lock().unwrap();
+            recent.push_front(run);
+            recent.truncate(RECENT_RUNS);
+        }
+    });
+}
+
+/// Save hooks for the tabs of a `NotesSaved` change.
+pub fn notes_saved(app: &AppHandle, folder: &str, tabs: &[String]) {
+    for tab in tabs {
+        fire(app, Context { event: HookEvent::Save, folder, tab, export_path: None });
+    }
+}
+
+/// The hooks in a workspace's `hooks.toml` and whether they may run.
+#[tauri::command]
+pub fn get_hooks(app: AppHandle, workspace_path: String) -> HooksStatus {
+    let root = root_of(&workspace_path);
+    let text = match read_file(&root) {
+        Ok(Some(text)) => text,
+        Ok(None) => return HooksStatus { hooks: Vec::new(), trusted: false, error: None },
+        Err(err) => return HooksStatus { hooks: Vec::new(), trusted: false, error: Some(err) },
+    };
+    let trusted = trusted_digests(&app).get(&root) == Some(&digest(&root, &text));
+    match parse(&text) {
+        Ok(hooks) => HooksStatus { hooks, trusted, error: None },
+        Err(err) => HooksStatus { hooks: Vec::new(), trusted: false, error: Some(err) },
+    }
+}
+
+/// Allows the workspace's `hooks.toml` as it is now, or stops allowing it.
+#[tauri::command]
+pub fn trust_hooks(app: AppHandle, workspace_path: String, trusted: bool) -> Result<HooksStatus, String> {
+    let root = root_of(&workspace_path);
+    let mut digests = trusted_digests(&app);
+    if trusted {
+        let text = read_file(&root)?.ok_or_else(|| format!("{} does not exist.", hooks_path(&root).display()))?;
+        parse(&text)?;
+        digests.insert(root.clone(), digest(&root, &text));
+    } else {
+        digests.remove(&root);
+    }
+    crate::write_setting(&app, TRUSTED_HOOKS_SETTING, json!(digests))?;
+    Ok(get_hooks(app, workspace_path))
+}
+
+/// The latest hook runs, newest first, with their output.
+#[tauri::command]
+pub fn recent_hook_runs() -> Vec<HookRun> {
+    RECENT.lock().unwrap().iter().cloned().collect()
+}
@@ -0,0 +1,129 @@
+//! iCloud Drive placeholders.
+//!
+//! When macOS evicts a note to save space, `coral.md` is replaced by a
+//! `.coral.md.icloud` stub. Loading such a workspace starts the download,
+//! tells the frontend the tab is on its way rather than empty, and announces
+//! when the real file has arrived.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{note_path, sync_status, TAB_KEYS};
+
+pub const DOWNLOADING_EVENT: &str = "icloud-downloading";
+pub const DOWNLOADED_EVENT: &str = "icloud-downloaded";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Note files reported as downloading that the frontend hasn't loaded yet.
+#[derive(Default)]
+pub struct PendingDownloads(Mutex<HashSet<PathBuf>>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IcloudTabs {
+    workspace_path: String,
+    tabs: Vec<String>,
+}
+
+fn placeholder_path(file_path: &Path) -> Option<PathBuf> {
+    let name = file_path.file_name()?.to_str()?;
+    Some(file_path.with_file_name(format!(".{name}.icloud")))
+}
+
+fn is_placeholder(file_path: &Path) -> bool {
+    !file_path.exists() && placeholder_path(file_path).is_some_and(|placeholder| placeholder.exists())
+}
+
+/// Whether `file_path` is still downloading, so an empty tab must not be treated as deleted.
+pub fn is_pending(app: &AppHandle, file_path: &Path) -> bool {
+    is_placeholder(file_path) || app.state::<PendingDownloads>().0.lock().unwrap().contains(file_path)
+}
+
+fn request_download(file_path: &Path) {
+    #[cfg(target_os = "macos")]
+    {
+        let result = Command::new("brctl").arg("download").arg(file_path).output();
+        match result {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => tracing::warn!(
+                "brctl download failed for {}: {}",
+                file_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(err) => tracing::warn!("Failed to run brctl: {err}"),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    let _ = file_path;
+}
+
+fn emit(app: &AppHandle, event: &str, payload: IcloudTabs) {
+    if let Err(err) = app.emit(event, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+fn wait_for_download(app: AppHandle, payload: IcloudTabs) {
+    let started = Instant::now();
+    let files: Vec<PathBuf> = payload
+        .tabs
+        .iter()
+        .map(|tab| note_path(&payload.workspace_path, tab))
+        .collect();
+
+    while files.iter().any(|file| !file.exists()) {
+        if started.elapsed() > DOWNLOAD_TIMEOUT {
+            tracing::warn!("Timed out waiting for {} to download", payload.workspace_path);
+            sync_status::finish(&app, "icloud", Err("iCloud Drive has not finished downloading.".to_string()));
+            return;
+        }
+        let done = files.iter().filter(|file| file.exists()).count();
+        sync_status::syncing(&app, "icloud", done, files.len());
+        thread::sleep(POLL_INTERVAL);
+    }
+    sync_status::finish(&app, "icloud", Ok(Vec::new()));
+    emit(&app, DOWNLOADED_EVENT, payload);
+}
+
+/// Starts downloading evicted tabs missing from `loaded` and notifies the frontend.
+pub fn check(app: &AppHandle, workspace_path: &str, loaded: &HashMap<String, String>) {
+    let state = app.state::<PendingDownloads>();
+    let mut pending = state.0.lock().unwrap();
+    for tab in loaded.keys() {
+        pending.remove(&note_path(workspace_path, tab));
+    }
+
+    let tabs: Vec<String> = TAB_KEYS
+        .iter()
+        .filter(|tab| !loaded.contains_key(**tab) && is_placeholder(&note_path(workspace_path, tab)))
+        .map(|tab| tab.to_string())
+        .collect();
+    if tabs.is_empty() {
+        return;
+    }
+
+    for tab in &tabs {
+        let file_path = note_path(workspace_path, tab);
+        request_download(&file_path);
+        pending.insert(file_path);
+    }
+    drop(pending);
+
+    let payload = IcloudTabs {
+        workspace_path: workspace_path.to_string(),
+        tabs,
+    };
+    emit(app, DOWNLOADING_EVENT, payload.clone());
+    let handle = app.clone();
+    thread::spawn(move || wait_for_download(handle, payload));
+}
@@ -0,0 +1,99 @@
+//! Keeps the index's write-ahead log from growing without bound.
+//!
+//! Each index update runs in its own short-lived `sqlite3` process, and SQLite
+//! only truncates the WAL when nothing else has it open, which rarely lines up.
+//! Once an index has been idle for `IDLE_BEFORE_CHECKPOINT`, its WAL is folded
+//! back into the database and truncated. `compact_index` does the same on
+//! demand and vacuums the database as well.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::{file_open, read_project_names, run_sqlite_script, sqlite_path, REBUILD_FTS};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const IDLE_BEFORE_CHECKPOINT: Duration = Duration::from_secs(120);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactReport {
+    bytes_before: u64,
+    bytes_after: u64,
+    reclaimed_bytes: u64,
+}
+
+fn wal_path(db_path: &Path) -> PathBuf {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    PathBuf::from(wal)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+/// Size of the index with its write-ahead log.
+pub fn index_size(db_path: &Path) -> u64 {
+    file_size(db_path) + file_size(&wal_path(db_path))
+}
+
+/// Truncates the WAL of an index left untouched for `IDLE_BEFORE_CHECKPOINT`.
+fn checkpoint_if_idle(workspace_path: &str) {
+    let db_path = sqlite_path(workspace_path);
+    let idle = fs::metadata(wal_path(&db_path))
+        .ok()
+        .filter(|metadata| metadata.len() > 0)
+        .and_then(|metadata| metadata.modified().ok())
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed >= IDLE_BEFORE_CHECKPOINT);
+    if !idle {
+        return;
+    }
+    if let Err(err) = run_sqlite_script(&db_path, "PRAGMA wal_checkpoint(TRUNCATE);\n") {
+        tracing::warn!("{}", err);
+    }
+}
+
+/// Starts checkpointing idle indexes of the workspace root and its projects.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        let Some(workspace) = file_open::configured_workspace(&app) else {
+            continue;
+        };
+        let root = workspace.to_string_lossy().to_string();
+        checkpoint_if_idle(&root);
+        for project in read_project_names(&root).unwrap_or_default() {
+            checkpoint_if_idle(&workspace.join(&project).to_string_lossy());
+        }
+    });
+}
+
+/// Checkpoints and vacuums a workspace's index, reporting the space reclaimed.
+#[tauri::command]
+pub async fn compact_index(workspace_path: String) -> Result<CompactReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db_path = sqlite_path(&workspace_path);
+        let bytes_before = index_size(&db_path);
+        if db_path.exists() {
+            run_sqlite_script(
+                &db_path,
+                &format!("PRAGMA wal_checkpoint(TRUNCATE);\nVACUUM;\n{REBUILD_FTS}PRAGMA wal_checkpoint(TRUNCATE);\n"),
+            )?;
+        }
+        let bytes_after = index_size(&db_path);
+        Ok(CompactReport {
+            bytes_before,
+            bytes_after,
+            reclaimed_bytes: bytes_before.saturating_sub(bytes_after),
+        })
+    })
+    .await
+    .map_err(|err| format!("Index compaction task failed: {err}"))?
+}
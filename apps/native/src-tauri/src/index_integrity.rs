@@ -0,0 +1,198 @@
+//! Detects a corrupt workspace index and rebuilds it from the markdown files.
+//!
+//! Cloud sync services sometimes copy `index.sqlite` mid-write or merge it with
+//! a stale copy. The first time a workspace is opened each session its index
+//! gets a `PRAGMA quick_check`; a corrupt one is moved aside as
+//! `corrupt-index-<unix>.sqlite` and rebuilt. Note versions only live in the
+//! index, so they restart from the next save. `audit_index` compares the
+//! index against the files on demand.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::progress::Progress;
+use crate::{
+    hermes_dir, ignore, note_lock, note_path, read_workspace_pages, run_sqlite_query, run_sqlite_script, sql_escape,
+    sqlite_path, TAB_KEYS,
+};
+
+pub const INDEX_REBUILT_EVENT: &str = "workspace://index-rebuilt";
+
+/// Workspaces whose index was already checked this session.
+#[derive(Default)]
+pub struct CheckedIndexes(Mutex<HashSet<String>>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRebuilt {
+    workspace_path: String,
+    /// What `quick_check` or SQLite reported.
+    problem: String,
+    quarantined_path: String,
+}
+
+/// SQLite's complaint about a corrupt index; `None` when it is fine or could not be checked.
+pub fn corruption(db_path: &Path) -> Option<String> {
+    match run_sqlite_query(db_path, "PRAGMA quick_check;") {
+        Ok(rows) => {
+            let problems: Vec<String> = rows
+                .iter()
+                .filter_map(|row| row.get("quick_check").and_then(|value| value.as_str()))
+                .filter(|result| *result != "ok")
+                .map(str::to_string)
+                .collect();
+            (!problems.is_empty()).then(|| problems.join("; "))
+        }
+        // A missing sqlite3 binary says nothing about the file.
+        Err(err) if ["malformed", "not a database", "corrupt"].iter().any(|sign| err.contains(sign)) => Some(err),
+        Err(_) => None,
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Moves the index and its journal files aside, returning where the index went.
+fn quarantine(workspace_path: &str, db_path: &Path) -> Result<PathBuf, String> {
+    let now_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let target = hermes_dir(workspace_path).join(format!("corrupt-index-{now_unix}.sqlite"));
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let source = with_suffix(db_path, suffix);
+        if source.exists() {
+            fs::rename(&source, with_suffix(&target, suffix))
+                .map_err(|err| format!("Failed moving aside {}: {err}", source.display()))?;
+        }
+    }
+    Ok(target)
+}
+
+/// Checks the index of `workspace_path` once per session, rebuilding it when corrupt.
+pub fn check(app: &AppHandle, workspace_path: &str) {
+    if !app.state::<CheckedIndexes>().0.lock().unwrap().insert(workspace_path.to_string()) {
+        return;
+    }
+    let db_path = sqlite_path(workspace_path);
+    if !db_path.exists() {
+        return;
+    }
+    let Some(problem) = corruption(&db_path) else {
+        return;
+    };
+
+    tracing::error!("Index of {workspace_path} is corrupt: {problem}");
+    let quarantined = match quarantine(workspace_path, &db_path) {
+        Ok(quarantined) => quarantined,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return;
+        }
+    };
+    // Reading the pages recreates the index from the markdown files.
+    let progress = Progress::new(app, "reindex", None);
+    progress.report("rebuilding", 0, 1);
+    if let Err(err) = read_workspace_pages(workspace_path) {
+        tracing::warn!("{}", String::from(err));
+    }
+    progress.finish(1);
+    let payload = IndexRebuilt {
+        workspace_path: workspace_path.to_string(),
+        problem,
+        quarantined_path: quarantined.to_string_lossy().to_string(),
+    };
+    if let Err(err) = app.emit(INDEX_REBUILT_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexAudit {
+    /// Notes on disk without an index row.
+    missing: Vec<String>,
+    /// Index rows whose text differs from the file.
+    stale: Vec<String>,
+    /// Index rows for notes that are gone, ignored or unknown.
+    orphaned: Vec<String>,
+    repaired: bool,
+}
+
+/// The body the index should hold for `tab`; `None` when it should have no row.
+fn expected_body(workspace_path: &str, tab: &str) -> Option<String> {
+    let file_path = note_path(workspace_path, tab);
+    if ignore::is_ignored(&file_path) {
+        return None;
+    }
+    // Locked notes are indexed by title only.
+    if note_lock::is_locked_file(&file_path) {
+        return Some(String::new());
+    }
+    fs::read_to_string(&file_path).ok().filter(|content| !content.trim().is_empty())
+}
+
+/// Compares the index of `workspace_path` with its notes; `repair` rebuilds the
+/// rows that disagree.
+#[tauri::command]
+pub async fn audit_index(workspace_path: String, repair: bool) -> Result<IndexAudit, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db_path = sqlite_path(&workspace_path);
+        let mut rows: HashMap<String, String> = HashMap::new();
+        if db_path.exists() {
+            for row in run_sqlite_query(&db_path, "SELECT tab_key, body FROM note_index;")? {
+                let tab = row.get("tab_key").and_then(|value| value.as_str()).unwrap_or_default();
+                let body = row.get("body").and_then(|value| value.as_str()).unwrap_or_default();
+                rows.insert(tab.to_string(), body.to_string());
+            }
+        }
+
+        let mut audit = IndexAudit {
+            missing: Vec::new(),
+            stale: Vec::new(),
+            orphaned: Vec::new(),
+            repaired: false,
+        };
+        for tab in TAB_KEYS {
+            match (expected_body(&workspace_path, tab), rows.remove(tab)) {
+                (Some(_), None) => audit.missing.push(tab.to_string()),
+                (Some(expected), Some(body)) if expected != body => audit.stale.push(tab.to_string()),
+                (None, Some(_)) => audit.orphaned.push(tab.to_string()),
+                _ => {}
+            }
+        }
+        audit.orphaned.extend(rows.into_keys());
+        audit.orphaned.sort();
+
+        if !repair || (audit.missing.is_empty() && audit.stale.is_empty() && audit.orphaned.is_empty()) {
+            return Ok(audit);
+        }
+        // Rows for unknown tabs are never rewritten by reindexing, so drop them first.
+        let unknown: Vec<&String> = audit.orphaned.iter().filter(|tab| !TAB_KEYS.contains(&tab.as_str())).collect();
+        if !unknown.is_empty() {
+            let mut script = String::from("BEGIN IMMEDIATE;\n");
+            for tab in unknown {
+                let tab = sql_escape(tab);
+                for table in ["note_index", "note_tags", "note_tasks", "note_locks"] {
+                    script.push_str(&format!("DELETE FROM {table} WHERE tab_key = '{tab}';\n"));
+                }
+            }
+            script.push_str("COMMIT;\n");
+            run_sqlite_script(&db_path, &script)?;
+        }
+        read_workspace_pages(&workspace_path).map_err(String::from)?;
+        audit.repaired = true;
+        Ok(audit)
+    })
+    .await
+    .map_err(|err| format!("Index audit task failed: {err}"))?
+}
@@ -0,0 +1,66 @@
+//! SQLite tuning of the workspace index for big workspaces.
+//!
+//! The `indexTuning` setting holds `{ pageSize, cacheSizeKib, mmapSizeMib,
+//! synchronous }`. Indexing also runs from background captures without an
+//! `AppHandle`, so the setting is mirrored in hermes-core at startup and
+//! whenever `set_index_tuning` changes it. A new page size only applies to an
+//! existing index once `optimize_index` has rebuilt it.
+
+pub use hermes_core::index_tuning::{pragmas, IndexTuning};
+use tauri::AppHandle;
+
+use crate::{run_sqlite_script, sqlite_path, volume, REBUILD_FTS};
+
+const INDEX_TUNING_SETTING: &str = "indexTuning";
+
+fn read(app: &AppHandle) -> IndexTuning {
+    crate::read_setting(app, INDEX_TUNING_SETTING)
+        .and_then(|value| serde_json::from_value::<IndexTuning>(value).ok())
+        .filter(|tuning| tuning.validate().is_ok())
+        .unwrap_or_default()
+}
+
+/// Loads the tuning setting for the indexer.
+pub fn init(app: &AppHandle) {
+    hermes_core::index_tuning::set(read(app));
+}
+
+#[tauri::command]
+pub fn get_index_tuning(app: AppHandle) -> IndexTuning {
+    read(&app)
+}
+
+#[tauri::command]
+pub fn set_index_tuning(app: AppHandle, tuning: IndexTuning) -> Result<(), String> {
+    tuning.validate()?;
+    let value = serde_json::to_value(&tuning).map_err(|err| format!("Failed encoding index tuning: {err}"))?;
+    crate::write_setting(&app, INDEX_TUNING_SETTING, value)?;
+    hermes_core::index_tuning::set(tuning);
+    Ok(())
+}
+
+/// Refreshes the query planner statistics and rebuilds the index file, which
+/// also applies a changed page size.
+#[tauri::command]
+pub async fn optimize_index(workspace_path: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db_path = sqlite_path(&workspace_path);
+        if !db_path.exists() {
+            return Ok(());
+        }
+        // A WAL database keeps its page size through VACUUM, so rebuild it in rollback mode.
+        let script = format!(
+            "{}PRAGMA journal_mode=DELETE;\n\
+             VACUUM;\n\
+             {}\
+             PRAGMA journal_mode={};\n\
+             PRAGMA optimize;\n",
+            pragmas(&workspace_path),
+            REBUILD_FTS,
+            volume::journal_mode(&workspace_path),
+        );
+        run_sqlite_script(&db_path, &script)
+    })
+    .await
+    .map_err(|err| format!("Index optimization task failed: {err}"))?
+}
@@ -0,0 +1,195 @@
+//! Background index writes, so saving and loading never wait on SQLite.
+//!
+//! Saves and loads queue the tabs they touched here and return; a worker
+//! thread started in setup writes the index once a workspace has been quiet
+//! for `DEBOUNCE`, with only the latest content of each tab, and then records
+//! the saved tabs' versions. A workspace saved without pause is still indexed
+//! every `MAX_DELAY`. The queue holds at most `MAX_QUEUED_WORKSPACES`; past
+//! that the oldest write is dropped, though its saved tabs' versions are still
+//! recorded since nothing rebuilds them. Writes queued at quit are dropped
+//! too, which is fine: the index is rebuilt from the files the next time a
+//! workspace loads.
+//! Each write ends with an `INDEX_SYNCED_EVENT`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::changes::{self, Change};
+use crate::{tasks, versions};
+
+pub const INDEX_SYNCED_EVENT: &str = "workspace://index-synced";
+
+const DEBOUNCE: Duration = Duration::from_millis(750);
+const MAX_DELAY: Duration = Duration::from_secs(5);
+const MAX_QUEUED_WORKSPACES: usize = 32;
+
+struct QueuedWrite {
+    pages: HashMap<String, String>,
+    /// Tabs written by a save rather than read on load; their versions get bumped.
+    saved: HashSet<String>,
+    first_queued: Instant,
+    last_queued: Instant,
+}
+
+impl QueuedWrite {
+    fn due_at(&self) -> Instant {
+        (self.last_queued + DEBOUNCE).min(self.first_queued + MAX_DELAY)
+    }
+
+    /// The content of the tabs written by a save.
+    fn saved_pages(&self) -> HashMap<String, String> {
+        self.pages
+            .iter()
+            .filter(|(tab, _)| self.saved.contains(*tab))
+            .map(|(tab, content)| (tab.clone(), content.clone()))
+            .collect()
+    }
+}
+
+/// Index writes waiting for their workspace to settle, by workspace path.
+#[derive(Default)]
+pub struct IndexWriter {
+    queue: Mutex<HashMap<String, QueuedWrite>>,
+    wake: Condvar,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSynced {
+    workspace_path: String,
+    tabs: Vec<String>,
+    error: Option<String>,
+}
+
+fn enqueue(app: &AppHandle, workspace_path: &str, pages: HashMap<String, String>, saved: bool) {
+    let now = Instant::now();
+    let state = app.state::<IndexWriter>();
+    let mut queue = state.queue.lock().unwrap();
+    let mut dropped = None;
+    if !queue.contains_key(workspace_path) && queue.len() >= MAX_QUEUED_WORKSPACES {
+        let oldest = queue
+            .iter()
+            .min_by_key(|(_, write)| write.first_queued)
+            .map(|(workspace_path, _)| workspace_path.clone());
+        if let Some(oldest) = oldest {
+            tracing::warn!("Index queue full; dropping pending write for {oldest}");
+            dropped = queue.remove(&oldest).map(|write| (oldest, write.saved_pages()));
+        }
+    }
+    let write = queue.entry(workspace_path.to_string()).or_insert_with(|| QueuedWrite {
+        pages: HashMap::new(),
+        saved: HashSet::new(),
+        first_queued: now,
+        last_queued: now,
+    });
+    if saved {
+        write.saved.extend(pages.keys().cloned());
+    }
+    write.pages.extend(pages);
+    write.last_queued = now;
+    state.wake.notify_one();
+    drop(queue);
+
+    if let Some((workspace_path, saved)) = dropped.filter(|(_, saved)| !saved.is_empty()) {
+        versions::record_saves(app, &workspace_path, &saved);
+    }
+}
+
+/// Queues the index rows of saved `pages` for refresh, by tab; empty content
+/// drops a row. Tabs already queued for `workspace_path` take the newer content.
+pub fn schedule(app: &AppHandle, workspace_path: &str, pages: HashMap<String, String>) {
+    enqueue(app, workspace_path, pages, true);
+}
+
+/// Queues a full reindex of the tabs read on load; tabs missing from `pages` lose their rows.
+pub fn schedule_load(app: &AppHandle, workspace_path: &str, pages: &HashMap<String, String>) {
+    let pages = crate::TAB_KEYS
+        .iter()
+        .map(|tab| (tab.to_string(), pages.get(*tab).cloned().unwrap_or_default()))
+        .collect();
+    enqueue(app, workspace_path, pages, false);
+}
+
+/// Drops the queued write of one tab, for a tab whose rows are being replaced directly.
+pub fn discard(app: &AppHandle, workspace_path: &str, tab: &str) {
+    let state = app.state::<IndexWriter>();
+    let mut queue = state.queue.lock().unwrap();
+    if let Some(write) = queue.get_mut(workspace_path) {
+        write.pages.remove(tab);
+        write.saved.remove(tab);
+        if write.pages.is_empty() {
+            queue.remove(workspace_path);
+        }
+    }
+}
+
+/// Blocks until at least one queued write is due, then takes the due ones.
+fn next_due(state: &IndexWriter) -> Vec<(String, QueuedWrite)> {
+    let mut queue = state.queue.lock().unwrap();
+    loop {
+        let now = Instant::now();
+        let ready: Vec<String> = queue
+            .iter()
+            .filter(|(_, write)| write.due_at() <= now)
+            .map(|(workspace_path, _)| workspace_path.clone())
+            .collect();
+        if !ready.is_empty() {
+            return ready
+                .into_iter()
+                .filter_map(|workspace_path| {
+                    let write = queue.remove(&workspace_path)?;
+                    Some((workspace_path, write))
+                })
+                .collect();
+        }
+        queue = match queue.values().map(QueuedWrite::due_at).min() {
+            Some(due_at) => state.wake.wait_timeout(queue, due_at - now).unwrap().0,
+            None => state.wake.wait(queue).unwrap(),
+        };
+    }
+}
+
+/// Starts the background index writer.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        let writes = next_due(&app.state::<IndexWriter>());
+        for (workspace_path, write) in writes {
+            // Markdown files remain source of truth; index is best-effort metadata/search cache.
+            let tabs = write.pages.iter().map(|(tab, content)| (tab.as_str(), content.as_str()));
+            let error = crate::index_tabs(&workspace_path, tabs).err();
+            if let Some(err) = &error {
+                tracing::warn!("{}", err);
+            }
+            let saved = write.saved_pages();
+            if !saved.is_empty() {
+                versions::record_saves(&app, &workspace_path, &saved);
+            }
+
+            let mut tabs: Vec<String> = write.pages.into_keys().collect();
+            tabs.sort();
+            if error.is_none() {
+                let change = Change::IndexUpdated {
+                    workspace_path: workspace_path.clone(),
+                    tabs: tabs.clone(),
+                };
+                changes::emit(&app, change);
+            }
+            let payload = IndexSynced {
+                workspace_path,
+                tabs,
+                error,
+            };
+            if let Err(err) = app.emit(INDEX_SYNCED_EVENT, payload) {
+                tracing::warn!("Failed to emit event: {err}");
+            }
+        }
+        // The due-task badge counts from the index.
+        tasks::refresh_badge(&app);
+    });
+}
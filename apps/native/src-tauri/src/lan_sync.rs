@@ -0,0 +1,758 @@
+//! Peer-to-peer sync between Hermes installs on the same network.
+//!
+//! Instances advertise `_hermes-sync._tcp` over mDNS. Pairing runs SPAKE2 over a
+//! six-digit code shown on the accepting machine; the resulting channel carries
+//! a random pairing key that both sides keep in the settings store. Every sync
+//! session encrypts its frames with ChaCha20-Poly1305 under a key derived from
+//! the pairing key and fresh nonces.
+//!
+//! Each side sends a content hash per tab; tabs that differ are reconciled by
+//! exchanging CRDT states (see `crdt`). When two devices created a tab's
+//! document independently there is no shared history to merge, so the
+//! responder's version wins and the initiator's text is kept as a conflicted
+//! copy on both machines for the conflict UI to pick up, unless the version
+//! vectors show one side has already seen every edit of the other.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use rand::{Rng, RngCore};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use spake2::{Ed25519Group, Identity, Password, Spake2};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::versions::{self, Relation, VersionVector};
+use crate::{crdt, device, file_open, ignore, note_lock, note_path, notes_dir, read_project_names, sync_status, validate_tab_key, TAB_KEYS};
+
+const SERVICE_TYPE: &str = "_hermes-sync._tcp.local.";
+const SPAKE_IDENTITY: &[u8] = b"hermes-lan-sync";
+/// Settings-store key holding `{ deviceId: { name, key } }` for paired devices.
+const PEERS_SETTING: &str = "lanSyncPeers";
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(300);
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+
+pub const LAN_SYNC_EVENT: &str = "lan-sync-completed";
+
+struct Discovered {
+    fullname: String,
+    name: String,
+    address: SocketAddr,
+}
+
+#[derive(Default)]
+pub struct LanSync {
+    daemon: Mutex<Option<ServiceDaemon>>,
+    discovered: Arc<Mutex<HashMap<String, Discovered>>>,
+    pairing_code: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanPeer {
+    device_id: String,
+    name: String,
+    address: Option<String>,
+    paired: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LanSyncCompleted {
+    peer: String,
+    notes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+enum Hello {
+    Pair { device_id: String, name: String, spake: Vec<u8> },
+    Sync { device_id: String, name: String, nonce: Vec<u8> },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HelloReply {
+    device_id: String,
+    name: String,
+    /// SPAKE2 message when pairing, session nonce when syncing.
+    payload: Vec<u8>,
+}
+
+/// What a device has of one note.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NoteVersion {
+    pub hash: String,
+    #[serde(default)]
+    pub version: VersionVector,
+}
+
+/// Messages sent inside the encrypted channel.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Message {
+    PairingKey { key: Vec<u8> },
+    Ack,
+    /// Hash and version per note key (`tab` or `project/tab`).
+    Manifest { notes: HashMap<String, NoteVersion> },
+    /// CRDT states, with the versions they correspond to.
+    States {
+        states: HashMap<String, Vec<u8>>,
+        versions: HashMap<String, NoteVersion>,
+    },
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn paired_peers(app: &AppHandle) -> Map<String, Value> {
+    crate::read_setting(app, PEERS_SETTING)
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default()
+}
+
+fn peer_key(app: &AppHandle, device_id: &str) -> Option<Vec<u8>> {
+    paired_peers(app)
+        .get(device_id)?
+        .get("key")?
+        .as_str()
+        .and_then(unhex)
+}
+
+fn store_peer(app: &AppHandle, device_id: &str, name: &str, key: &[u8]) -> Result<(), String> {
+    let mut peers = paired_peers(app);
+    peers.insert(
+        device_id.to_string(),
+        serde_json::json!({ "name": name, "key": hex(key) }),
+    );
+    crate::write_setting(app, PEERS_SETTING, Value::Object(peers))
+}
+
+fn write_frame(stream: &mut TcpStream, bytes: &[u8]) -> Result<(), String> {
+    let len = u32::try_from(bytes.len()).map_err(|_| "Frame too large".to_string())?;
+    stream
+        .write_all(&len.to_be_bytes())
+        .and_then(|_| stream.write_all(bytes))
+        .map_err(|err| format!("Failed sending to peer: {err}"))
+}
+
+fn read_frame(stream: &mut TcpStream) -> Result<Vec<u8>, String> {
+    let mut len = [0; 4];
+    stream
+        .read_exact(&mut len)
+        .map_err(|err| format!("Failed reading from peer: {err}"))?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(format!("Peer sent an oversized frame ({len} bytes)"));
+    }
+    let mut bytes = vec![0; len];
+    stream
+        .read_exact(&mut bytes)
+        .map_err(|err| format!("Failed reading from peer: {err}"))?;
+    Ok(bytes)
+}
+
+fn send_plain<T: Serialize>(stream: &mut TcpStream, message: &T) -> Result<(), String> {
+    let bytes = serde_json::to_vec(message).map_err(|err| format!("Failed encoding message: {err}"))?;
+    write_frame(stream, &bytes)
+}
+
+fn recv_plain<T: DeserializeOwned>(stream: &mut TcpStream) -> Result<T, String> {
+    serde_json::from_slice(&read_frame(stream)?).map_err(|err| format!("Invalid message from peer: {err}"))
+}
+
+/// Encrypted, ordered message stream; nonces are a direction byte plus a counter.
+struct Channel {
+    stream: TcpStream,
+    cipher: ChaCha20Poly1305,
+    initiator: bool,
+    sent: u64,
+    received: u64,
+}
+
+impl Channel {
+    fn new(stream: TcpStream, key: &[u8], initiator: bool) -> Self {
+        let key: [u8; 32] = Sha256::digest(key).into();
+        Channel {
+            stream,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+            initiator,
+            sent: 0,
+            received: 0,
+        }
+    }
+
+    fn nonce(from_initiator: bool, counter: u64) -> [u8; 12] {
+        let mut nonce = [0; 12];
+        nonce[0] = u8::from(from_initiator);
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+
+    fn send(&mut self, message: &Message) -> Result<(), String> {
+        let plain = serde_json::to_vec(message).map_err(|err| format!("Failed encoding message: {err}"))?;
+        let nonce = Self::nonce(self.initiator, self.sent);
+        self.sent += 1;
+        let sealed = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plain.as_slice())
+            .map_err(|_| "Failed encrypting message".to_string())?;
+        write_frame(&mut self.stream, &sealed)
+    }
+
+    fn recv(&mut self) -> Result<Message, String> {
+        let sealed = read_frame(&mut self.stream)?;
+        let nonce = Self::nonce(!self.initiator, self.received);
+        self.received += 1;
+        let plain = self
+            .cipher
+            .decrypt(Nonce::from_slice(&nonce), sealed.as_slice())
+            .map_err(|_| "Peer failed authentication (wrong code or unpaired device)".to_string())?;
+        serde_json::from_slice(&plain).map_err(|err| format!("Invalid message from peer: {err}"))
+    }
+}
+
+fn session_key(pairing_key: &[u8], initiator_nonce: &[u8], responder_nonce: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(pairing_key);
+    hasher.update(initiator_nonce);
+    hasher.update(responder_nonce);
+    hasher.finalize().to_vec()
+}
+
+fn connect(address: SocketAddr) -> Result<TcpStream, String> {
+    let stream = TcpStream::connect_timeout(&address, IO_TIMEOUT)
+        .map_err(|err| format!("Failed connecting to {address}: {err}"))?;
+    configure(&stream)?;
+    Ok(stream)
+}
+
+fn configure(stream: &TcpStream) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(IO_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(IO_TIMEOUT)))
+        .map_err(|err| format!("Failed configuring connection: {err}"))
+}
+
+/// Tabs of the configured workspace (root and projects) by note key.
+pub fn local_notes(app: &AppHandle) -> Result<HashMap<String, (String, String)>, String> {
+    let workspace = file_open::configured_workspace(app).ok_or_else(|| "No workspace is configured.".to_string())?;
+    let root = workspace.to_string_lossy().to_string();
+
+    let mut folders = vec![(None, root.clone())];
+    for project in read_project_names(&root).unwrap_or_default() {
+        let path = workspace.join(&project).to_string_lossy().to_string();
+        folders.push((Some(project), path));
+    }
+
+    let mut notes = HashMap::new();
+    for (project, path) in folders {
+        for tab in TAB_KEYS {
+            // Locked notes would merge as ciphertext; they stay on this device,
+            // as do notes the workspace ignores.
+            let file_path = note_path(&path, tab);
+            if !file_path.exists() || note_lock::is_locked_file(&file_path) || ignore::is_ignored(&file_path) {
+                continue;
+            }
+            let key = match &project {
+                Some(project) => format!("{project}/{tab}"),
+                None => tab.to_string(),
+            };
+            notes.insert(key, (path.clone(), tab.to_string()));
+        }
+    }
+    Ok(notes)
+}
+
+/// Maps a note key from a peer to a workspace folder and tab, rejecting unsafe names.
+fn resolve_key(app: &AppHandle, key: &str) -> Result<(String, String), String> {
+    let workspace = file_open::configured_workspace(app).ok_or_else(|| "No workspace is configured.".to_string())?;
+    let (folder, tab) = match key.rsplit_once('/') {
+        Some((project, tab)) => {
+            crate::project_list::check_project(project).map_err(|_| format!("Invalid project in note key '{key}'"))?;
+            (workspace.join(project), tab)
+        }
+        None => (workspace, key),
+    };
+    validate_tab_key(tab)?;
+    Ok((folder.to_string_lossy().to_string(), tab.to_string()))
+}
+
+/// Refuses a note from a peer whose folder here is read-only.
+pub fn ensure_writable_key(app: &AppHandle, key: &str) -> Result<(), String> {
+    let (path, _) = resolve_key(app, key)?;
+    crate::read_only::ensure_writable(app, &path).map_err(String::from)
+}
+
+pub fn manifest(notes: &HashMap<String, (String, String)>) -> HashMap<String, NoteVersion> {
+    let mut recorded: HashMap<String, HashMap<String, VersionVector>> = HashMap::new();
+    notes
+        .iter()
+        .map(|(key, (path, tab))| {
+            let content = fs::read(note_path(path, tab)).unwrap_or_default();
+            let folder = recorded.entry(path.clone()).or_insert_with(|| versions::all(path));
+            let note = NoteVersion {
+                hash: versions::content_hash(&content),
+                version: folder.get(tab).cloned().unwrap_or_default(),
+            };
+            (key.clone(), note)
+        })
+        .collect()
+}
+
+fn conflicted_copy_path(workspace_path: &str, tab: &str, device: &str) -> PathBuf {
+    let device: String = device.chars().filter(|ch| !matches!(ch, '/' | '\\' | '(' | ')')).collect();
+    let stem = crate::tab_files::stem(workspace_path, tab);
+    notes_dir(workspace_path).join(format!("{stem} ({device} conflicted copy).md"))
+}
+
+fn keep_conflicted_copy(workspace_path: &str, tab: &str, device: &str, content: &str) -> Result<(), String> {
+    let path = conflicted_copy_path(workspace_path, tab, device);
+    fs::write(&path, content).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+/// Applies a peer's state for one note. Version vectors settle fast-forwards;
+/// see the module docs for concurrent edits without shared history.
+pub fn apply_remote(
+    app: &AppHandle,
+    key: &str,
+    state: &[u8],
+    remote: &NoteVersion,
+    initiator_name: &str,
+    we_initiated: bool,
+) -> Result<(), String> {
+    let (path, tab) = resolve_key(app, key)?;
+    if note_lock::is_locked_file(&note_path(&path, &tab)) {
+        return Err("The note is locked on this device".to_string());
+    }
+    crate::read_only::ensure_writable(app, &path).map_err(String::from)?;
+    fs::create_dir_all(&path).map_err(|err| format!("Failed creating directory {path}: {err}"))?;
+    // An empty vector comes from a peer that never recorded versions; assume the worst.
+    let relation = if remote.version.is_empty() {
+        Relation::Concurrent
+    } else {
+        versions::version(&path, &tab).compare(&remote.version)
+    };
+    if relation == Relation::Ahead {
+        return Ok(());
+    }
+
+    if crdt::shares_history(&path, &tab, state)? {
+        crdt::merge_note_crdt_update(app.clone(), path.clone(), tab.clone(), state.to_vec())?;
+    } else if relation == Relation::Behind {
+        crdt::replace(&path, &tab, state)?;
+    } else if we_initiated {
+        let ours = fs::read_to_string(note_path(&path, &tab)).unwrap_or_default();
+        keep_conflicted_copy(&path, &tab, initiator_name, &ours)?;
+        crdt::replace(&path, &tab, state)?;
+    } else {
+        keep_conflicted_copy(&path, &tab, initiator_name, &crdt::render(state)?)?;
+    }
+
+    let content = fs::read_to_string(note_path(&path, &tab)).unwrap_or_default();
+    versions::record_sync(app, &path, &tab, &remote.version, &remote.hash, &content)
+}
+
+pub fn local_state(app: &AppHandle, key: &str) -> Result<Vec<u8>, String> {
+    let (path, tab) = resolve_key(app, key)?;
+    crdt::state(&path, &tab)
+}
+
+/// Refreshes the index of every folder holding one of `keys`.
+pub fn reindex(app: &AppHandle, keys: &BTreeSet<String>) {
+    let mut folders = BTreeSet::new();
+    for key in keys {
+        if let Ok((path, _)) = resolve_key(app, key) {
+            folders.insert(path);
+        }
+    }
+    for folder in folders {
+        if let Err(err) = crate::read_workspace_pages(&folder) {
+            tracing::warn!("{}", String::from(err));
+        }
+    }
+}
+
+fn finish_sync(app: &AppHandle, peer: &str, keys: &BTreeSet<String>) {
+    reindex(app, keys);
+    sync_status::finish(app, "lan", Ok(Vec::new()));
+    let payload = LanSyncCompleted {
+        peer: peer.to_string(),
+        notes: keys.iter().cloned().collect(),
+    };
+    if let Err(err) = app.emit(LAN_SYNC_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+pub fn differing(ours: &HashMap<String, NoteVersion>, theirs: &HashMap<String, NoteVersion>) -> BTreeSet<String> {
+    let hash = |notes: &HashMap<String, NoteVersion>, key: &String| notes.get(key).map(|note| note.hash.clone());
+    ours.keys()
+        .chain(theirs.keys())
+        .filter(|key| hash(ours, key) != hash(theirs, key))
+        .cloned()
+        .collect()
+}
+
+fn respond_pairing(app: &AppHandle, mut stream: TcpStream, device_id: String, name: String, spake: Vec<u8>) -> Result<(), String> {
+    let state = app.state::<LanSync>();
+    let code = {
+        let mut pairing = state.pairing_code.lock().unwrap();
+        match pairing.take() {
+            Some((code, issued)) if issued.elapsed() < PAIRING_CODE_TTL => code,
+            _ => return Err(format!("Rejected pairing from {name}: no pairing code is active")),
+        }
+    };
+
+    let (spake_state, outbound) =
+        Spake2::<Ed25519Group>::start_symmetric(&Password::new(code.as_bytes()), &Identity::new(SPAKE_IDENTITY));
+    send_plain(
+        &mut stream,
+        &HelloReply {
+            device_id: device::id(app)?,
+            name: device::name(),
+            payload: outbound,
+        },
+    )?;
+    let shared = spake_state
+        .finish(&spake)
+        .map_err(|err| format!("Pairing with {name} failed: {err:?}"))?;
+
+    let mut channel = Channel::new(stream, &shared, false);
+    let Message::PairingKey { key } = channel.recv()? else {
+        return Err("Unexpected message while pairing".to_string());
+    };
+    store_peer(app, &device_id, &name, &key)?;
+    channel.send(&Message::Ack)
+}
+
+fn respond_sync(app: &AppHandle, mut stream: TcpStream, device_id: String, name: String, nonce: Vec<u8>) -> Result<(), String> {
+    let key = peer_key(app, &device_id).ok_or_else(|| format!("Rejected sync from unpaired device {name}"))?;
+    let our_nonce = random_bytes::<32>();
+    send_plain(
+        &mut stream,
+        &HelloReply {
+            device_id: device::id(app)?,
+            name: device::name(),
+            payload: our_nonce.to_vec(),
+        },
+    )?;
+    let mut channel = Channel::new(stream, &session_key(&key, &nonce, &our_nonce), false);
+
+    let Message::Manifest { notes: theirs } = channel.recv()? else {
+        return Err("Expected a manifest".to_string());
+    };
+    let notes = local_notes(app)?;
+    channel.send(&Message::Manifest { notes: manifest(&notes) })?;
+
+    let Message::States { states, versions } = channel.recv()? else {
+        return Err("Expected note states".to_string());
+    };
+    let wanted = differing(&manifest(&notes), &theirs);
+    let mut synced = BTreeSet::new();
+    let mut reply = HashMap::new();
+    let total = wanted.len();
+    for (done, key) in wanted.into_iter().enumerate() {
+        sync_status::syncing(app, "lan", done, total);
+        if let (Some(state), Some(remote)) = (states.get(&key), versions.get(&key)) {
+            if let Err(err) = apply_remote(app, &key, state, remote, &name, false) {
+                tracing::warn!("{key}: {err}");
+                continue;
+            }
+        }
+        reply.insert(key.clone(), local_state(app, &key)?);
+        synced.insert(key);
+    }
+    let mut current = manifest(&local_notes(app)?);
+    current.retain(|key, _| reply.contains_key(key));
+    channel.send(&Message::States {
+        states: reply,
+        versions: current,
+    })?;
+    finish_sync(app, &name, &synced);
+    Ok(())
+}
+
+fn handle_connection(app: &AppHandle, mut stream: TcpStream) -> Result<(), String> {
+    configure(&stream)?;
+    match recv_plain::<Hello>(&mut stream)? {
+        Hello::Pair { device_id, name, spake } => respond_pairing(app, stream, device_id, name, spake),
+        Hello::Sync { device_id, name, nonce } => respond_sync(app, stream, device_id, name, nonce),
+    }
+}
+
+fn listen(app: AppHandle, listener: TcpListener) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let app = app.clone();
+                thread::spawn(move || {
+                    if let Err(err) = handle_connection(&app, stream) {
+                        tracing::warn!("{}", err);
+                    }
+                });
+            }
+            Err(err) => tracing::warn!("Failed accepting connection: {err}"),
+        }
+    }
+}
+
+fn browse(own_id: String, daemon: &ServiceDaemon, discovered: Arc<Mutex<HashMap<String, Discovered>>>) -> Result<(), String> {
+    let events = daemon
+        .browse(SERVICE_TYPE)
+        .map_err(|err| format!("Failed browsing for peers: {err}"))?;
+    thread::spawn(move || {
+        while let Ok(event) = events.recv() {
+            match event {
+                ServiceEvent::ServiceResolved(service) => {
+                    let Some(id) = service.get_property_val_str("id").map(str::to_string) else {
+                        continue;
+                    };
+                    let Some(ip) = service.get_addresses_v4().into_iter().next() else {
+                        continue;
+                    };
+                    if id == own_id {
+                        continue;
+                    }
+                    let name = service.get_property_val_str("name").unwrap_or(&id).to_string();
+                    discovered.lock().unwrap().insert(
+                        id,
+                        Discovered {
+                            fullname: service.get_fullname().to_string(),
+                            name,
+                            address: SocketAddr::new(ip.into(), service.get_port()),
+                        },
+                    );
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    discovered.lock().unwrap().retain(|_, peer| peer.fullname != fullname);
+                }
+                _ => {}
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Starts listening for peers and advertising this device; idempotent.
+#[tauri::command]
+pub fn start_lan_sync(app: AppHandle, state: tauri::State<'_, LanSync>) -> Result<(), String> {
+    let mut daemon_slot = state.daemon.lock().unwrap();
+    if daemon_slot.is_some() {
+        return Ok(());
+    }
+
+    let id = device::id(&app)?;
+    let listener = TcpListener::bind("0.0.0.0:0").map_err(|err| format!("Failed opening sync port: {err}"))?;
+    let port = listener
+        .local_addr()
+        .map_err(|err| format!("Failed reading sync port: {err}"))?
+        .port();
+
+    let daemon = ServiceDaemon::new().map_err(|err| format!("Failed starting mDNS: {err}"))?;
+    let properties = HashMap::from([("id".to_string(), id.clone()), ("name".to_string(), device::name())]);
+    let service = ServiceInfo::new(SERVICE_TYPE, &id, &format!("hermes-{id}.local."), "", port, properties)
+        .map_err(|err| format!("Failed describing sync service: {err}"))?
+        .enable_addr_auto();
+    daemon
+        .register(service)
+        .map_err(|err| format!("Failed advertising sync service: {err}"))?;
+    browse(id, &daemon, Arc::clone(&state.discovered))?;
+
+    let handle = app.clone();
+    thread::spawn(move || listen(handle, listener));
+    *daemon_slot = Some(daemon);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_lan_peers(app: AppHandle, state: tauri::State<'_, LanSync>) -> Vec<LanPeer> {
+    let paired = paired_peers(&app);
+    let discovered = state.discovered.lock().unwrap();
+
+    let mut peers: Vec<LanPeer> = discovered
+        .iter()
+        .map(|(id, peer)| LanPeer {
+            device_id: id.clone(),
+            name: peer.name.clone(),
+            address: Some(peer.address.to_string()),
+            paired: paired.contains_key(id),
+        })
+        .collect();
+    for (id, peer) in &paired {
+        if !discovered.contains_key(id) {
+            peers.push(LanPeer {
+                device_id: id.clone(),
+                name: peer.get("name").and_then(|name| name.as_str()).unwrap_or(id).to_string(),
+                address: None,
+                paired: true,
+            });
+        }
+    }
+    peers.sort_by(|a, b| a.name.cmp(&b.name));
+    peers
+}
+
+/// Issues the code the other device must enter; valid for one pairing within five minutes.
+#[tauri::command]
+pub fn begin_lan_pairing(state: tauri::State<'_, LanSync>) -> String {
+    let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+    *state.pairing_code.lock().unwrap() = Some((code.clone(), Instant::now()));
+    code
+}
+
+fn peer_address(app: &AppHandle, device_id: &str) -> Result<SocketAddr, String> {
+    app.state::<LanSync>()
+        .discovered
+        .lock()
+        .unwrap()
+        .get(device_id)
+        .map(|peer| peer.address)
+        .ok_or_else(|| "That device is not visible on the network.".to_string())
+}
+
+fn pair(app: &AppHandle, device_id: &str, code: &str) -> Result<(), String> {
+    let mut stream = connect(peer_address(app, device_id)?)?;
+    let (spake_state, outbound) =
+        Spake2::<Ed25519Group>::start_symmetric(&Password::new(code.trim().as_bytes()), &Identity::new(SPAKE_IDENTITY));
+    send_plain(
+        &mut stream,
+        &Hello::Pair {
+            device_id: device::id(app)?,
+            name: device::name(),
+            spake: outbound,
+        },
+    )?;
+    let reply: HelloReply = recv_plain(&mut stream)?;
+    if reply.device_id != device_id {
+        return Err("A different device answered the pairing request.".to_string());
+    }
+    let shared = spake_state
+        .finish(&reply.payload)
+        .map_err(|err| format!("Pairing failed: {err:?}"))?;
+
+    let key = random_bytes::<32>();
+    let mut channel = Channel::new(stream, &shared, true);
+    channel.send(&Message::PairingKey { key: key.to_vec() })?;
+    let Message::Ack = channel.recv()? else {
+        return Err("Unexpected reply while pairing".to_string());
+    };
+    store_peer(app, device_id, &reply.name, &key)
+}
+
+fn sync(app: &AppHandle, device_id: &str) -> Result<Vec<String>, String> {
+    let key = peer_key(app, device_id).ok_or_else(|| "Pair with this device first.".to_string())?;
+    let mut stream = connect(peer_address(app, device_id)?)?;
+    let nonce = random_bytes::<32>();
+    let own_name = device::name();
+    send_plain(
+        &mut stream,
+        &Hello::Sync {
+            device_id: device::id(app)?,
+            name: own_name.clone(),
+            nonce: nonce.to_vec(),
+        },
+    )?;
+    let reply: HelloReply = recv_plain(&mut stream)?;
+    if reply.device_id != device_id {
+        return Err("A different device answered the sync request.".to_string());
+    }
+    let mut channel = Channel::new(stream, &session_key(&key, &nonce, &reply.payload), true);
+
+    let notes = local_notes(app)?;
+    let ours = manifest(&notes);
+    channel.send(&Message::Manifest { notes: ours.clone() })?;
+    let Message::Manifest { notes: theirs } = channel.recv()? else {
+        return Err("Expected a manifest".to_string());
+    };
+
+    let keys = differing(&ours, &theirs);
+    let mut states = HashMap::new();
+    for key in keys.iter().filter(|key| ours.contains_key(*key)) {
+        states.insert(key.clone(), local_state(app, key)?);
+    }
+    channel.send(&Message::States { states, versions: ours })?;
+    let Message::States {
+        states: merged,
+        versions,
+    } = channel.recv()?
+    else {
+        return Err("Expected note states".to_string());
+    };
+
+    let mut synced = BTreeSet::new();
+    let total = merged.len();
+    for (done, (key, state)) in merged.into_iter().enumerate() {
+        sync_status::syncing(app, "lan", done, total);
+        let Some(remote) = versions.get(&key) else {
+            continue;
+        };
+        match apply_remote(app, &key, &state, remote, &own_name, true) {
+            Ok(()) => {
+                synced.insert(key);
+            }
+            Err(err) => tracing::warn!("{key}: {err}"),
+        }
+    }
+    finish_sync(app, &reply.name, &synced);
+    Ok(synced.into_iter().collect())
+}
+
+#[tauri::command]
+pub async fn pair_lan_peer(app: AppHandle, device_id: String, code: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || pair(&app, &device_id, &code))
+        .await
+        .map_err(|err| format!("Pairing task failed: {err}"))?
+}
+
+/// Syncs every tab with a paired device and returns the note keys that changed.
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub async fn sync_lan_peer(app: AppHandle, device_id: String) -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        sync_status::syncing(&app, "lan", 0, 0);
+        let result = sync(&app, &device_id);
+        if let Err(err) = &result {
+            sync_status::finish(&app, "lan", Err(err.clone()));
+        }
+        result
+    })
+    .await
+    .map_err(|err| format!("Sync task failed: {err}"))?
+}
+
+#[tauri::command]
+pub fn forget_lan_peer(app: AppHandle, device_id: String) -> Result<(), String> {
+    let mut peers = paired_peers(&app);
+    peers.remove(&device_id);
+    crate::write_setting(&app, PEERS_SETTING, Value::Object(peers))
+}
@@ -1,174 +1,186 @@
+mod app_lock;
+mod app_state;
+#[cfg(desktop)]
+mod assistant;
+mod attachments;
+mod autosave;
+#[cfg(mobile)]
+mod background_sync;
+mod bookmarks;
+mod capture;
+mod changes;
+#[cfg(desktop)]
+mod clipboard;
+#[cfg(desktop)]
+mod cloud_sync;
+mod conflicts;
+mod crash_reports;
+mod crdt;
+mod deep_link;
+mod device;
+mod diagnostics;
+#[cfg(desktop)]
+mod dictation;
+#[cfg(desktop)]
+mod dropbox_sync;
+mod export;
+mod file_open;
+mod finder_tags;
+mod formatter;
+#[cfg(desktop)]
+mod gdrive_sync;
+#[cfg(desktop)]
+mod git_sync;
+mod held_saves;
+mod history;
+#[cfg(desktop)]
+mod hooks;
+mod icloud;
+mod index_compaction;
+mod index_integrity;
+mod index_tuning;
+mod index_writer;
+mod lan_sync;
+#[cfg(desktop)]
+mod local_api;
+mod logging;
+#[cfg(desktop)]
+mod menu;
+mod merge;
+#[cfg(mobile)]
+mod mobile_storage;
+mod note_chunks;
+mod note_lock;
+mod note_move;
+mod notifications;
+mod permissions;
+#[cfg(desktop)]
+mod plugins;
+mod power;
+mod progress;
+mod project_list;
+mod project_meta;
+mod project_windows;
+mod read_only;
+mod recents;
+mod relay_sync;
+#[cfg(desktop)]
+mod review;
+mod save_queue;
+mod scratchpad;
+mod search;
+mod settings;
+#[cfg(mobile)]
+mod share_intake;
+#[cfg(desktop)]
+mod sftp_workspace;
+#[cfg(desktop)]
+mod shortcuts;
+#[cfg(desktop)]
+mod speech;
+mod spellcheck;
+mod spotlight;
+mod sync_status;
+mod tab_files;
+mod tasks;
+mod templates;
+mod toc;
+#[cfg(desktop)]
+mod translate;
+#[cfg(desktop)]
+mod tray;
+mod undo;
+#[cfg(desktop)]
+mod updater;
+mod versions;
+#[cfg(desktop)]
+mod voice_memo;
+mod volume;
+mod workspace_health;
+mod workspace_merge;
+mod workspace_meta;
+mod workspace_move;
+mod workspace_stats;
+mod workspaces;
+
 use std::collections::HashMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+#[cfg(target_os = "macos")]
 use std::process::Command;
 use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_shell::process::CommandChild;
+#[cfg(desktop)]
 use tauri_plugin_shell::ShellExt;
+use tauri_plugin_store::StoreExt;
+
+use hermes_core::index::{extract_title, run_sqlite_query, run_sqlite_script, sql_escape, REBUILD_FTS};
+#[cfg(desktop)]
+use hermes_core::keychain;
+use hermes_core::projects::read_project_names;
+#[cfg(desktop)]
+use hermes_core::workspace::default_workspace;
+use hermes_core::workspace::{index_tabs, note_path, read_workspace_files, read_workspace_pages};
+use hermes_core::{
+    canonical_path, chat, canonical_workspace, file_hashes, hermes_dir, ignore, notes_dir, path_safety, sqlite_path,
+    text_encoding, validate_tab_key, workspace_lock, TAB_KEYS,
+};
+use permissions::CommandError;
 
 struct ServerProcess(Mutex<Option<CommandChild>>);
-const TAB_KEYS: [&str; 5] = ["coral", "amber", "sage", "sky", "lavender"];
-
-fn notes_dir(workspace_path: &str) -> PathBuf {
-    Path::new(workspace_path).to_path_buf()
-}
-
-fn hermes_dir(workspace_path: &str) -> PathBuf {
-    Path::new(workspace_path).join(".hermes")
-}
-
-fn sqlite_path(workspace_path: &str) -> PathBuf {
-    hermes_dir(workspace_path).join("index.sqlite")
-}
-
-fn sql_escape(value: &str) -> String {
-    value.replace('\'', "''")
-}
-
-fn word_count(content: &str) -> usize {
-    content.split_whitespace().filter(|word| !word.is_empty()).count()
-}
-
-fn extract_title(content: &str) -> String {
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        let without_heading = trimmed.trim_start_matches('#').trim();
-        if without_heading.is_empty() {
-            continue;
-        }
-        return without_heading.chars().take(120).collect();
+// Shared with the frontend's settingsStorage.js.
+const SETTINGS_STORE_FILE: &str = "hermes-settings.json";
+
+fn focus_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        #[cfg(desktop)]
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
     }
-    String::new()
 }
 
-fn run_sqlite_script(path: &Path, script: &str) -> Result<(), String> {
-    let output = Command::new("sqlite3")
-        .arg(path)
-        .arg(script)
-        .output()
-        .map_err(|err| format!("Failed to run sqlite3: {err}"))?;
-
-    if output.status.success() {
-        return Ok(());
-    }
-
-    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-    Err(format!(
-        "sqlite3 error while updating {}: {}",
-        path.display(),
-        if stderr.is_empty() { "unknown error" } else { &stderr }
-    ))
+fn read_setting(app: &tauri::AppHandle, key: &str) -> Option<serde_json::Value> {
+    app.store(SETTINGS_STORE_FILE).ok()?.get(key)
 }
 
-fn sync_workspace_index(workspace_path: &str, pages: &HashMap<String, String>) -> Result<(), String> {
-    let hermes = hermes_dir(workspace_path);
-    fs::create_dir_all(&hermes)
-        .map_err(|err| format!("Failed creating Hermes metadata directory {}: {err}", hermes.display()))?;
-
-    let db_path = sqlite_path(workspace_path);
-    let notes_root = notes_dir(workspace_path);
-    let now_unix = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_secs() as i64)
-        .unwrap_or(0);
-
-    let mut script = String::from(
-        "PRAGMA journal_mode=WAL;\n\
-         CREATE TABLE IF NOT EXISTS note_index (\n\
-           tab_key TEXT PRIMARY KEY,\n\
-           file_path TEXT NOT NULL,\n\
-           title TEXT NOT NULL,\n\
-           body TEXT NOT NULL,\n\
-           word_count INTEGER NOT NULL,\n\
-           char_count INTEGER NOT NULL,\n\
-           updated_unix INTEGER NOT NULL\n\
-         );\n\
-         CREATE INDEX IF NOT EXISTS idx_note_index_updated ON note_index(updated_unix DESC);\n\
-         CREATE VIRTUAL TABLE IF NOT EXISTS note_fts USING fts5(tab_key UNINDEXED, title, body);\n\
-         BEGIN IMMEDIATE;\n",
-    );
-
-    for tab in TAB_KEYS {
-        let content = pages.get(tab).cloned().unwrap_or_default();
-        if content.trim().is_empty() {
-            script.push_str(&format!(
-                "DELETE FROM note_index WHERE tab_key = '{}';\n\
-                 DELETE FROM note_fts WHERE tab_key = '{}';\n",
-                sql_escape(tab),
-                sql_escape(tab),
-            ));
-            continue;
-        }
-
-        let title = extract_title(&content);
-        let file_path = notes_root.join(format!("{tab}.md"));
-        let escaped_tab = sql_escape(tab);
-        let escaped_title = sql_escape(&title);
-        let escaped_body = sql_escape(&content);
-        let escaped_file_path = sql_escape(&file_path.to_string_lossy());
-
-        script.push_str(&format!(
-            "INSERT INTO note_index(tab_key, file_path, title, body, word_count, char_count, updated_unix)\n\
-             VALUES ('{escaped_tab}', '{escaped_file_path}', '{escaped_title}', '{escaped_body}', {}, {}, {})\n\
-             ON CONFLICT(tab_key) DO UPDATE SET\n\
-               file_path=excluded.file_path,\n\
-               title=excluded.title,\n\
-               body=excluded.body,\n\
-               word_count=excluded.word_count,\n\
-               char_count=excluded.char_count,\n\
-               updated_unix=excluded.updated_unix;\n\
-             DELETE FROM note_fts WHERE tab_key = '{escaped_tab}';\n\
-             INSERT INTO note_fts(tab_key, title, body) VALUES ('{escaped_tab}', '{escaped_title}', '{escaped_body}');\n",
-            word_count(&content),
-            content.chars().count(),
-            now_unix,
-        ));
-    }
-
-    script.push_str("COMMIT;\n");
-    run_sqlite_script(&db_path, &script)
+fn write_setting(app: &tauri::AppHandle, key: &str, value: serde_json::Value) -> Result<(), String> {
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("Failed opening settings store: {err}"))?;
+    store.set(key, value);
+    store.save().map_err(|err| format!("Failed saving settings: {err}"))
 }
 
+/// Project paths, sub-projects included, all of them unless `limit` asks for a
+/// page; archived projects only with `include_archived`.
 #[tauri::command]
-fn list_workspace_projects(workspace_path: String) -> Result<Vec<String>, String> {
-    let dir = Path::new(&workspace_path);
-    if !dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut projects = Vec::new();
-    let entries = fs::read_dir(dir)
-        .map_err(|err| format!("Failed reading workspace directory: {err}"))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|err| format!("Failed reading entry: {err}"))?;
-        let path = entry.path();
-        if path.is_dir() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            // Skip hidden directories like .hermes
-            if !name.starts_with('.') {
-                projects.push(name);
-            }
-        }
-    }
-
-    projects.sort();
-    Ok(projects)
+fn list_workspace_projects(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+) -> Result<Vec<String>, CommandError> {
+    let workspace_path = canonical_workspace(&workspace_path);
+    // The frontend lists projects whenever it opens a workspace root.
+    recents::record(&app, &workspace_path);
+    volume::warn_once(&app, &workspace_path);
+    let names = project_list::listed(&app, &workspace_path, include_archived.unwrap_or(false))?;
+    Ok(project_list::page(&names, offset, limit).0)
 }
 
 #[tauri::command]
-fn get_default_workspace() -> Result<String, String> {
-    let home = std::env::var("HOME")
-        .map_err(|_| "Could not determine home directory".to_string())?;
-    let docs = Path::new(&home).join("Documents").join("Hermes");
-    fs::create_dir_all(&docs)
-        .map_err(|err| format!("Failed creating default workspace {}: {err}", docs.display()))?;
-    Ok(docs.to_string_lossy().to_string())
+fn get_default_workspace(app: tauri::AppHandle) -> Result<String, String> {
+    #[cfg(mobile)]
+    return mobile_storage::default_workspace(&app);
+    #[cfg(desktop)]
+    {
+        let _ = app;
+        default_workspace()
+    }
 }
 
 #[tauri::command]
@@ -190,7 +202,7 @@ fn open_in_finder(path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-fn pick_workspace_folder() -> Result<Option<String>, String> {
+fn pick_workspace_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
     #[cfg(target_os = "macos")]
     {
         let output = Command::new("osascript")
@@ -207,91 +219,262 @@ fn pick_workspace_folder() -> Result<Option<String>, String> {
         if selected.is_empty() {
             return Ok(None);
         }
-        return Ok(Some(selected.trim_end_matches('/').to_string()));
+        let selected = canonical_workspace(selected.trim_end_matches('/'));
+        if let Err(err) = bookmarks::remember(&app, &selected) {
+            tracing::warn!("{}", err);
+        }
+        return Ok(Some(selected));
     }
 
     #[cfg(not(target_os = "macos"))]
+    let _ = app;
+    #[cfg(mobile)]
+    return Err("On this device notes are kept in Hermes's own folder; other folders can't be opened.".to_string());
+    #[cfg(all(desktop, not(target_os = "macos")))]
     Err("Workspace folder picker is currently implemented for macOS only.".to_string())
 }
 
 #[tauri::command]
-fn load_workspace_pages(workspace_path: String) -> Result<HashMap<String, String>, String> {
-    let mut pages = HashMap::new();
-    let dir = notes_dir(&workspace_path);
-
-    if dir.exists() {
-        for tab in TAB_KEYS {
-            let file_path = dir.join(format!("{tab}.md"));
-            if !file_path.exists() {
-                continue;
-            }
+#[tracing::instrument(skip_all, fields(workspace = %workspace_path))]
+fn load_workspace_pages(app: tauri::AppHandle, workspace_path: String) -> Result<HashMap<String, String>, CommandError> {
+    app_lock::ensure_unlocked(&app)?;
+    let workspace_path = canonical_workspace(&workspace_path);
+    // Reading a vanished folder would show empty tabs that autosave then keeps.
+    if !held_saves::is_available(&app, &workspace_path) {
+        return Err(CommandError::from(format!(
+            "The workspace folder {workspace_path} is unavailable. Reconnect its drive or restore the folder."
+        )));
+    }
+    index_integrity::check(&app, &workspace_path);
+    // Edits still unsaved for this workspace land before its files are read.
+    autosave::flush(&app, Some(&workspace_path))?;
+    if held_saves::held(&app, &workspace_path).is_some() {
+        write_pages(&app, &workspace_path, &HashMap::new())?;
+    }
+    let mut pages = read_workspace_files(&workspace_path)?;
+    index_writer::schedule_load(&app, &workspace_path, &pages);
+    for (tab, content) in pages.iter_mut() {
+        if note_lock::is_locked(content) {
+            *content = note_lock::reveal(&app, &note_path(&workspace_path, tab), std::mem::take(content));
+            continue;
+        }
+        merge::store_base(&workspace_path, tab, content);
+        crdt::record(&workspace_path, tab, content);
+    }
+    icloud::check(&app, &workspace_path, &pages);
+    conflicts::check(&app, &workspace_path, &pages);
+    tasks::refresh_badge(&app);
+    // Large notes are fetched in chunks instead; see `list_chunked_notes`.
+    pages.retain(|tab, _| !note_chunks::is_chunked_file(&note_path(&workspace_path, tab)));
+    Ok(pages)
+}
 
-            let content = fs::read_to_string(&file_path)
-                .map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?;
-            pages.insert(tab.to_string(), content);
+/// Writes one tab's file; empty content deletes it. Returns whether the file
+/// changed, so unchanged tabs can skip their index update.
+fn save_page(
+    app: &tauri::AppHandle,
+    workspace_path: &str,
+    tab: &str,
+    content: &str,
+    spotlight_enabled: bool,
+) -> Result<bool, CommandError> {
+    let file_path = note_path(workspace_path, tab);
+
+    if note_lock::is_locked_file(&file_path) {
+        // Locked notes are only rewritten, re-encrypted, while unlocked.
+        if let Some(sealed) = note_lock::seal_for_session(app, &file_path, content)? {
+            fs::write(&file_path, sealed).map_err(|err| permissions::io_error("writing", &file_path, err))?;
+            return Ok(true);
         }
+        return Ok(false);
     }
 
-    // Markdown files remain source of truth; index is best-effort metadata/search cache.
-    if let Err(err) = sync_workspace_index(&workspace_path, &pages) {
-        eprintln!("[workspace-index] {}", err);
+    // Binary files named like a tab were skipped on load and stay as they are.
+    if !file_hashes::is_known(&file_path) && text_encoding::is_binary_file(&file_path) {
+        if content.trim().is_empty() {
+            return Ok(false);
+        }
+        return Err(CommandError::from(format!(
+            "{} is not a text file; move or rename it to use this tab",
+            file_path.display()
+        )));
     }
 
-    Ok(pages)
+    if content.trim().is_empty() {
+        // An evicted note looks empty to the frontend until iCloud has downloaded it.
+        if file_path.exists() && !icloud::is_pending(app, &file_path) {
+            fs::remove_file(&file_path).map_err(|err| permissions::io_error("removing", &file_path, err))?;
+            file_hashes::forget(&file_path);
+            merge::remove_base(workspace_path, tab);
+            return Ok(true);
+        }
+        return Ok(false);
+    }
+
+    if file_hashes::is_unchanged(&file_path, content) {
+        return Ok(false);
+    }
+    fs::write(&file_path, content).map_err(|err| permissions::io_error("writing", &file_path, err))?;
+    file_hashes::remember(&file_path, content);
+    merge::store_base(workspace_path, tab, content);
+    crdt::record(workspace_path, tab, content);
+    undo::record(workspace_path, tab, content);
+
+    if spotlight_enabled && !ignore::is_ignored(&file_path) {
+        if let Err(err) = spotlight::write_note_metadata(&file_path, content) {
+            tracing::warn!("{}", err);
+        }
+    }
+    Ok(true)
 }
 
 #[tauri::command]
-fn save_workspace_pages(workspace_path: String, pages: HashMap<String, String>) -> Result<(), String> {
-    let dir = notes_dir(&workspace_path);
-    fs::create_dir_all(&dir)
-        .map_err(|err| format!("Failed creating workspace directory {}: {err}", dir.display()))?;
-
-    for tab in TAB_KEYS {
-        let file_path = dir.join(format!("{tab}.md"));
-        let content = pages.get(tab).cloned().unwrap_or_default();
+#[tracing::instrument(skip_all, fields(workspace = %workspace_path))]
+fn save_workspace_pages(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    pages: HashMap<String, Option<String>>,
+) -> Result<(), CommandError> {
+    read_only::ensure_writable(&app, &workspace_path)?;
+    autosave::discard(&app, &workspace_path, TAB_KEYS.iter().copied());
+    // Tabs missing from `pages` are deleted; `None` leaves a tab as it is, such
+    // as a large note the editor has just saved in chunks, and whatever is
+    // queued for it.
+    let pages: HashMap<String, String> = TAB_KEYS
+        .iter()
+        .filter_map(|tab| match pages.get(*tab) {
+            Some(None) => None,
+            Some(Some(content)) => Some((tab.to_string(), content.clone())),
+            None => Some((tab.to_string(), String::new())),
+        })
+        .collect();
+    save_queue::save(&app, &canonical_workspace(&workspace_path), pages)
+}
 
-        if content.trim().is_empty() {
-            if file_path.exists() {
-                fs::remove_file(&file_path)
-                    .map_err(|err| format!("Failed removing {}: {err}", file_path.display()))?;
-            }
+/// Writes the given tabs' files and queues their index rows; used by `save_queue`.
+fn write_pages(app: &tauri::AppHandle, workspace_path: &str, pages: &HashMap<String, String>) -> Result<(), CommandError> {
+    if !held_saves::is_available(app, workspace_path) {
+        // A missing folder can't be probed for writing, but a read-only mark still refuses.
+        if read_only::is_marked(app, workspace_path) {
+            read_only::ensure_writable(app, workspace_path)?;
+        }
+        // Still-locked tabs are never written, so they aren't held either.
+        let mut sealed = HashMap::new();
+        for (tab, content) in pages.iter().filter(|(_, content)| !note_lock::is_locked(content)) {
+            sealed.insert(tab.clone(), note_lock::seal_for_hold(app, &note_path(workspace_path, tab), content)?);
+        }
+        return held_saves::hold(app, workspace_path, &sealed).map_err(CommandError::from);
+    }
+    read_only::ensure_writable(app, workspace_path)?;
+    let dir = notes_dir(workspace_path);
+    let created = !dir.exists();
+    if created {
+        // A new project folder; its name comes from the project's title.
+        if let Some(name) = dir.file_name() {
+            path_safety::check_folder_name(&name.to_string_lossy())?;
+        }
+    }
+    fs::create_dir_all(&dir).map_err(|err| permissions::io_error("creating workspace directory", &dir, err))?;
+    // Another window or process saving the same workspace finishes first.
+    let _write_lock = workspace_lock::acquire(workspace_path)?;
+    let spotlight_enabled = spotlight::is_enabled(app);
+    let format_on_save = formatter::is_enabled(app, workspace_path);
+
+    // Saves held while the folder was gone go in first, under newer content.
+    let held = held_saves::held(app, workspace_path);
+    let mut all_pages: HashMap<String, String> = held
+        .iter()
+        .flatten()
+        .map(|(tab, content)| (tab.clone(), note_lock::reveal(app, &note_path(workspace_path, tab), content.clone())))
+        .collect();
+    all_pages.extend(pages.iter().map(|(tab, content)| (tab.clone(), content.clone())));
+
+    let mut changed = HashMap::new();
+    for (tab, content) in &all_pages {
+        let path = note_path(workspace_path, tab);
+        if note_lock::is_locked(content) && !pages.contains_key(tab) && note_lock::is_locked_file(&path) {
+            // Held sealed, and relocked since: it goes back as it was sealed.
+            fs::write(&path, content).map_err(|err| permissions::io_error("writing", &path, err))?;
+            changed.insert(tab.clone(), content.clone());
             continue;
         }
-
-        fs::write(&file_path, content)
-            .map_err(|err| format!("Failed writing {}: {err}", file_path.display()))?;
+        let content = toc::on_save(&path, formatter::on_save(format_on_save, &path, content));
+        if save_page(app, workspace_path, tab, &content, spotlight_enabled)? {
+            changed.insert(tab.clone(), content.into_owned());
+        }
+    }
+    if let Some(held) = held {
+        held_saves::release(app, workspace_path, held.into_keys());
     }
+    if created {
+        changes::emit(app, changes::Change::ProjectCreated { workspace_path: workspace_path.to_string() });
+    }
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let (mut deleted, mut tabs): (Vec<String>, Vec<String>) =
+        changed.keys().cloned().partition(|tab| changed[tab].is_empty());
+    tabs.sort();
+    deleted.sort();
+    changes::emit(
+        app,
+        changes::Change::NotesSaved {
+            workspace_path: workspace_path.to_string(),
+            tabs,
+            deleted,
+        },
+    );
+    // Autosave calls this often; consecutive saves share one index transaction,
+    // written off the command path along with the tabs' versions.
+    index_writer::schedule(app, workspace_path, changed);
 
-    // Markdown files remain source of truth; index is best-effort metadata/search cache.
-    if let Err(err) = sync_workspace_index(&workspace_path, &pages) {
-        eprintln!("[workspace-index] {}", err);
+    // The save worker may run this while the main thread waits on a flush, and
+    // menus are built on the main thread, so the refresh is only posted there.
+    #[cfg(desktop)]
+    {
+        let handle = app.clone();
+        if let Err(err) = app.run_on_main_thread(move || tray::refresh(&handle)) {
+            tracing::warn!("Failed to refresh tray: {err}");
+        }
     }
 
     Ok(())
 }
 
+/// Saves a single tab, leaving the other tabs' files and index rows alone.
 #[tauri::command]
-fn load_workspace_chat(workspace_path: String) -> Result<String, String> {
-    let file_path = Path::new(&workspace_path).join("chat.json");
-    if !file_path.exists() {
-        return Ok("[]".to_string());
-    }
-    fs::read_to_string(&file_path)
-        .map_err(|err| format!("Failed reading {}: {err}", file_path.display()))
+#[tracing::instrument(skip_all, fields(workspace = %workspace_path))]
+fn save_workspace_page(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    content: String,
+) -> Result<(), CommandError> {
+    validate_tab_key(&tab_key)?;
+    read_only::ensure_writable(&app, &workspace_path)?;
+    autosave::discard(&app, &workspace_path, [tab_key.as_str()]);
+    save_queue::save(&app, &canonical_workspace(&workspace_path), HashMap::from([(tab_key, content)]))
+}
+
+#[tauri::command]
+fn load_workspace_chat(app: tauri::AppHandle, workspace_path: String) -> Result<String, CommandError> {
+    app_lock::ensure_unlocked(&app)?;
+    chat::load(&workspace_path).map_err(|err| permissions::io_error("reading", &chat::chat_path(&workspace_path), err))
 }
 
 #[tauri::command]
-fn save_workspace_chat(workspace_path: String, chat_json: String) -> Result<(), String> {
-    let dir = Path::new(&workspace_path);
-    fs::create_dir_all(dir)
-        .map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
-    let file_path = dir.join("chat.json");
-    fs::write(&file_path, chat_json)
-        .map_err(|err| format!("Failed writing {}: {err}", file_path.display()))
+fn save_workspace_chat(app: tauri::AppHandle, workspace_path: String, chat_json: String) -> Result<(), CommandError> {
+    read_only::ensure_writable(&app, &workspace_path)?;
+    chat::save(&workspace_path, &chat_json)
+        .map_err(|err| permissions::io_error("writing", &chat::chat_path(&workspace_path), err))?;
+    changes::emit(&app, changes::Change::ChatSaved { workspace_path });
+    Ok(())
 }
 
 #[tauri::command]
-fn trash_project_folder(workspace_path: String, project_name: String) -> Result<(), String> {
+fn trash_project_folder(app: tauri::AppHandle, workspace_path: String, project_name: String) -> Result<(), String> {
+    project_list::check_project(&project_name)?;
+    read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
     let folder = Path::new(&workspace_path).join(&project_name);
     if !folder.exists() {
         return Ok(());
@@ -309,6 +492,12 @@ fn trash_project_folder(workspace_path: String, project_name: String) -> Result<
             .map_err(|err| format!("Failed to move to Trash: {err}"))?;
 
         if status.success() {
+            project_meta::forget(&canonical_workspace(&workspace_path), &project_name);
+            let change = changes::Change::ProjectTrashed {
+                workspace_path: canonical_workspace(&workspace_path),
+                project: project_name,
+            };
+            changes::emit(&app, change);
             return Ok(());
         }
         return Err("Finder failed to move folder to Trash".to_string());
@@ -347,10 +536,38 @@ fn toggle_devtools(window: tauri::WebviewWindow) -> Result<(), String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let app = tauri::Builder::default()
+    let mut builder = tauri::Builder::default();
+
+    // Registered first so a second launch hands its arguments (files, deep links)
+    // to this process and exits before it can spawn a competing sidecar.
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            focus_main_window(app);
+            let paths = file_open::paths_from_args(argv.into_iter().skip(1))
+                .into_iter()
+                .map(|path| Path::new(&cwd).join(path));
+            file_open::handle_paths(app, paths);
+        }));
+        builder = builder.plugin(tauri_plugin_global_shortcut::Builder::new().build());
+        builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
+    }
+
+    #[cfg(desktop)]
+    {
+        builder = builder.menu(menu::build).on_menu_event(menu::handle_event);
+    }
+    #[cfg(mobile)]
+    {
+        builder = builder.plugin(tauri_plugin_hermes_mobile::init());
+    }
+
+    let app = builder
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             has_debug_tools,
             toggle_devtools,
@@ -360,16 +577,324 @@ pub fn run() {
             pick_workspace_folder,
             load_workspace_pages,
             save_workspace_pages,
+            save_workspace_page,
+            note_chunks::list_chunked_notes,
+            note_chunks::get_note_chunk,
+            note_chunks::begin_note_save,
+            note_chunks::append_note_chunk,
+            note_chunks::finish_note_save,
+            note_chunks::cancel_note_save,
+            index_tuning::get_index_tuning,
+            index_tuning::set_index_tuning,
+            index_tuning::optimize_index,
+            index_compaction::compact_index,
+            index_integrity::audit_index,
+            project_list::list_workspace_projects_page,
+            project_list::discover_projects,
+            workspace_stats::get_workspace_stats,
+            workspace_health::check_workspace_health,
+            diagnostics::export_diagnostics,
+            logging::get_recent_logs,
+            logging::get_log_level,
+            logging::set_log_level,
+            crash_reports::get_pending_crash_reports,
+            crash_reports::dismiss_crash_reports,
+            save_queue::flush_pending_saves,
+            autosave::mark_dirty,
+            settings::get_settings,
+            settings::update_settings,
+            templates::list_workspace_templates,
+            templates::create_workspace_from_template,
+            workspaces::open_workspace,
+            workspaces::close_workspace,
+            workspaces::list_open_workspaces,
+            workspace_move::move_workspace,
+            workspace_merge::merge_workspaces,
+            read_only::get_workspace_access,
+            read_only::set_workspace_read_only,
+            workspace_meta::get_workspace_meta,
+            workspace_meta::set_workspace_meta,
+            note_move::move_note,
+            note_move::copy_note,
+            project_list::list_project_tree,
+            search::search_notes,
+            tab_files::get_tab_filenames,
+            tab_files::migrate_tab_filenames,
+            tab_files::get_note_extensions,
+            tab_files::set_note_extensions,
+            attachments::get_attachments_folder,
+            attachments::set_attachments_folder,
+            attachments::save_attachment,
+            project_meta::get_project_meta,
+            project_meta::set_project_meta,
+            project_meta::archive_project,
             load_workspace_chat,
             save_workspace_chat,
-            trash_project_folder
+            trash_project_folder,
+            spotlight::refresh_spotlight_metadata,
+            finder_tags::get_note_finder_tags,
+            finder_tags::set_note_finder_tags,
+            file_open::take_pending_open_files,
+            file_open::import_markdown_file,
+            deep_link::take_pending_navigation,
+            deep_link::get_note_deep_link,
+            capture::show_quick_capture,
+            capture::submit_quick_capture,
+            capture::close_quick_capture,
+            #[cfg(desktop)]
+            shortcuts::list_shortcuts,
+            #[cfg(desktop)]
+            shortcuts::register_shortcut,
+            recents::list_recent_workspaces,
+            recents::pin_workspace,
+            recents::remove_recent,
+            export::list_exporters,
+            export::export_note,
+            scratchpad::toggle_scratchpad_window,
+            scratchpad::load_scratchpad,
+            scratchpad::save_scratchpad,
+            project_windows::open_project_window,
+            project_windows::get_window_context,
+            bookmarks::grant_workspace_access,
+            bookmarks::list_stale_workspace_access,
+            permissions::open_access_settings,
+            notifications::send_notification,
+            app_lock::lock_app,
+            app_lock::unlock_app,
+            app_lock::is_app_locked,
+            app_lock::record_activity,
+            conflicts::resolve_conflict,
+            conflicts::merge_conflict,
+            sync_status::get_sync_status,
+            merge::merge_external_change,
+            crdt::get_note_crdt_state,
+            crdt::merge_note_crdt_update,
+            device::get_device_info,
+            versions::get_note_version,
+            versions::compare_note_versions,
+            note_lock::lock_note,
+            note_lock::unlock_note,
+            note_lock::relock_note,
+            note_lock::remove_note_lock,
+            history::list_note_versions,
+            history::diff_note_versions,
+            history::restore_note_version,
+            undo::get_undo_history,
+            undo::revert_to_point,
+            app_state::export_app_state,
+            app_state::import_app_state,
+            #[cfg(desktop)]
+            updater::check_for_updates,
+            #[cfg(desktop)]
+            updater::install_update,
+            #[cfg(desktop)]
+            updater::get_update_channel,
+            #[cfg(desktop)]
+            updater::set_update_channel,
+            lan_sync::start_lan_sync,
+            lan_sync::list_lan_peers,
+            lan_sync::begin_lan_pairing,
+            lan_sync::pair_lan_peer,
+            lan_sync::sync_lan_peer,
+            lan_sync::forget_lan_peer,
+            #[cfg(desktop)]
+            git_sync::sync_with_git_remote,
+            relay_sync::configure_relay_sync,
+            relay_sync::disable_relay_sync,
+            relay_sync::sync_with_relay,
+            relay_sync::list_devices,
+            relay_sync::revoke_device,
+            #[cfg(desktop)]
+            sftp_workspace::configure_sftp_workspace,
+            #[cfg(desktop)]
+            sftp_workspace::disable_sftp_workspace,
+            #[cfg(desktop)]
+            sftp_workspace::sync_sftp_workspace,
+            #[cfg(desktop)]
+            dropbox_sync::begin_dropbox_auth,
+            #[cfg(desktop)]
+            dropbox_sync::finish_dropbox_auth,
+            #[cfg(desktop)]
+            dropbox_sync::disconnect_dropbox,
+            #[cfg(desktop)]
+            dropbox_sync::sync_with_dropbox,
+            #[cfg(desktop)]
+            gdrive_sync::begin_gdrive_auth,
+            #[cfg(desktop)]
+            gdrive_sync::finish_gdrive_auth,
+            #[cfg(desktop)]
+            gdrive_sync::disconnect_gdrive,
+            #[cfg(desktop)]
+            gdrive_sync::sync_with_gdrive,
+            #[cfg(desktop)]
+            local_api::get_local_api,
+            #[cfg(desktop)]
+            local_api::set_local_api,
+            #[cfg(desktop)]
+            local_api::get_local_api_token,
+            #[cfg(desktop)]
+            local_api::reset_local_api_token,
+            #[cfg(desktop)]
+            plugins::list_plugins,
+            #[cfg(desktop)]
+            plugins::set_plugin_enabled,
+            #[cfg(desktop)]
+            plugins::load_plugin,
+            #[cfg(desktop)]
+            plugins::plugin_list_notes,
+            #[cfg(desktop)]
+            plugins::plugin_read_note,
+            #[cfg(desktop)]
+            plugins::plugin_write_note,
+            #[cfg(desktop)]
+            plugins::plugin_fetch,
+            #[cfg(desktop)]
+            plugins::plugin_export_source,
+            #[cfg(desktop)]
+            plugins::save_plugin_export,
+            #[cfg(desktop)]
+            hooks::get_hooks,
+            #[cfg(desktop)]
+            hooks::trust_hooks,
+            #[cfg(desktop)]
+            hooks::recent_hook_runs,
+            spellcheck::check_text,
+            spellcheck::get_spellcheck_languages,
+            spellcheck::import_dictionary,
+            spellcheck::delete_dictionary,
+            spellcheck::get_dictionary_words,
+            spellcheck::add_dictionary_word,
+            spellcheck::remove_dictionary_word,
+            #[cfg(desktop)]
+            speech::speak_note,
+            #[cfg(desktop)]
+            speech::pause_speaking,
+            #[cfg(desktop)]
+            speech::resume_speaking,
+            #[cfg(desktop)]
+            speech::stop_speaking,
+            #[cfg(desktop)]
+            dictation::get_dictation,
+            #[cfg(desktop)]
+            dictation::set_dictation,
+            #[cfg(desktop)]
+            dictation::download_whisper_model,
+            #[cfg(desktop)]
+            dictation::delete_whisper_model,
+            #[cfg(desktop)]
+            dictation::start_dictation,
+            #[cfg(desktop)]
+            dictation::stop_dictation,
+            #[cfg(desktop)]
+            dictation::transcribe_audio_file,
+            #[cfg(desktop)]
+            voice_memo::start_voice_memo,
+            #[cfg(desktop)]
+            voice_memo::stop_voice_memo,
+            #[cfg(desktop)]
+            review::review_text,
+            #[cfg(desktop)]
+            translate::get_translation,
+            #[cfg(desktop)]
+            translate::set_translation,
+            #[cfg(desktop)]
+            translate::translate_text,
+            #[cfg(desktop)]
+            translate::translate_note,
+            #[cfg(desktop)]
+            clipboard::convert_clipboard_to_markdown,
+            formatter::format_note,
+            toc::insert_toc,
+            #[cfg(mobile)]
+            share_intake::intake_shared_items,
+            #[cfg(mobile)]
+            background_sync::get_background_sync,
+            #[cfg(mobile)]
+            background_sync::set_background_sync
         ])
         .manage(ServerProcess(Mutex::new(None)))
+        .manage(file_open::PendingOpenFiles(Mutex::new(Vec::new())))
+        .manage(deep_link::PendingNavigation(Mutex::new(Vec::new())))
+        .manage(project_windows::WindowContexts::default())
+        .manage(workspaces::WorkspaceManager::default())
+        .manage(read_only::WritableFolders::default())
+        .manage(bookmarks::StaleBookmarks::default())
+        .manage(app_lock::AppLock::default())
+        .manage(app_lock::LastActivity::default())
+        .manage(icloud::PendingDownloads::default())
+        .manage(conflicts::ReportedConflicts::default())
+        .manage(sync_status::SyncStatuses::default())
+        .manage(lan_sync::LanSync::default())
+        .manage(index_writer::IndexWriter::default())
+        .manage(save_queue::SaveQueue::default())
+        .manage(autosave::Autosave::default())
+        .manage(note_chunks::NoteStreams::default())
+        .manage(index_integrity::CheckedIndexes::default())
+        .manage(project_list::ProjectListCache::default())
+        .manage(note_lock::UnlockedNotes::default())
         .setup(|app| {
+            logging::init(app.handle());
+            crash_reports::init(app.handle());
+            // Sandboxed builds must reopen bookmarked folders before touching the workspace.
+            bookmarks::restore(app.handle());
+            file_open::canonicalize_setting(app.handle());
+            #[cfg(mobile)]
+            mobile_storage::repair_setting(app.handle());
+            #[cfg(mobile)]
+            background_sync::init(app.handle());
+            #[cfg(mobile)]
+            {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn_blocking(move || share_intake::drain(&handle));
+            }
+            if let Some(workspace) = file_open::configured_workspace(app.handle()) {
+                project_list::register_root(&workspace.to_string_lossy());
+            }
+            notifications::init(app.handle());
+            app_lock::init(app.handle());
+            history::init(app.handle());
+            index_tuning::init(app.handle());
+            attachments::init(app.handle());
+            index_writer::init(app.handle());
+            save_queue::init(app.handle());
+            autosave::init(app.handle());
+            power::init(app.handle());
+            held_saves::init(app.handle());
+            index_compaction::init(app.handle());
+
             #[cfg(desktop)]
             {
                 let window = app.get_webview_window("main").unwrap();
                 window.set_title("Hermes").unwrap();
+
+                // Windows/Linux file associations pass the opened file as an argument.
+                file_open::handle_paths(app.handle(), file_open::paths_from_args(std::env::args().skip(1)));
+
+                if let Err(err) = tray::init(app.handle()) {
+                    tracing::warn!("Failed to create tray icon: {err}");
+                }
+
+                app.manage(shortcuts::RegisteredShortcuts::default());
+                shortcuts::init(app.handle());
+
+                app.manage(updater::PendingUpdate::default());
+                app.manage(local_api::LocalApi::default());
+                local_api::init(app.handle());
+                sftp_workspace::init(app.handle());
+                app.manage(dropbox_sync::DropboxAuth::default());
+                app.manage(gdrive_sync::GoogleDriveAuth::default());
+
+                // Installs that skipped the bundler (AppImage, dev builds) need the scheme registered at runtime.
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Err(err) = app.deep_link().register_all() {
+                    tracing::warn!("Failed to register URL scheme: {err}");
+                }
+            }
+
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| deep_link::handle_urls(&handle, event.urls()));
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                deep_link::handle_urls(app.handle(), urls);
             }
 
             // Spawn the backend server sidecar
@@ -387,21 +912,25 @@ pub fn run() {
                 let server_state = app.state::<ServerProcess>();
                 *server_state.0.lock().unwrap() = Some(child);
 
-                // Log sidecar stdout/stderr in background
+                // Log sidecar stdout/stderr in background, and to a file for diagnostics
+                let mut server_log = diagnostics::open_server_log(app.handle());
                 tauri::async_runtime::spawn(async move {
                     use tauri_plugin_shell::process::CommandEvent;
                     while let Some(event) = rx.recv().await {
                         match event {
                             CommandEvent::Stdout(line) => {
                                 let text = String::from_utf8_lossy(&line);
-                                eprintln!("[server] {}", text);
+                                tracing::info!(target: "hermes::server", "{}", text);
+                                diagnostics::log_server_line(&mut server_log, &text);
                             }
                             CommandEvent::Stderr(line) => {
                                 let text = String::from_utf8_lossy(&line);
-                                eprintln!("[server] {}", text);
+                                tracing::info!(target: "hermes::server", "{}", text);
+                                diagnostics::log_server_line(&mut server_log, &text);
                             }
                             CommandEvent::Terminated(status) => {
-                                eprintln!("[server] process exited with {:?}", status);
+                                tracing::warn!(target: "hermes::server", "process exited with {:?}", status);
+                                diagnostics::log_server_line(&mut server_log, &format!("process exited with {:?}", status));
                                 break;
                             }
                             _ => {}
@@ -413,8 +942,31 @@ pub fn run() {
             Ok(())
         })
         .on_window_event(|window, event| {
-            // Kill the server when the app window closes
+            if let tauri::WindowEvent::Focused(_) = event {
+                app_lock::touch(window.app_handle());
+            }
+            #[cfg(mobile)]
+            if let tauri::WindowEvent::Suspended = event {
+                background_sync::app_suspended(window.app_handle());
+            }
+
+            // Kill the server once the last editor window closes; auxiliary windows
+            // (quick capture, scratchpad) come and go while the app keeps running.
+            // The window's unsaved edits are written before its webview goes away.
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                if project_windows::is_editor_window(window.label()) {
+                    if let Err(err) = autosave::flush(window.app_handle(), None) {
+                        tracing::warn!("{}", String::from(err));
+                    }
+                }
+            }
+
             if let tauri::WindowEvent::Destroyed = event {
+                workspaces::window_destroyed(window.app_handle(), window.label());
+                let others_open = project_windows::window_destroyed(window.app_handle(), window.label());
+                if !project_windows::is_editor_window(window.label()) || others_open {
+                    return;
+                }
                 let state = window.state::<ServerProcess>();
                 let mut guard = state.0.lock().unwrap();
                 if let Some(child) = guard.take() {
@@ -428,12 +980,24 @@ pub fn run() {
     app.run(|app_handle, event| {
         match event {
             tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit => {
+                if let Err(err) = autosave::flush(app_handle, None) {
+                    tracing::warn!("{}", String::from(err));
+                }
                 let state = app_handle.state::<ServerProcess>();
                 let mut guard = state.0.lock().unwrap();
                 if let Some(child) = guard.take() {
                     let _ = child.kill();
                 }
             }
+            #[cfg(mobile)]
+            tauri::RunEvent::Resumed => {
+                let handle = app_handle.clone();
+                tauri::async_runtime::spawn_blocking(move || share_intake::drain(&handle));
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            tauri::RunEvent::Opened { urls } => {
+                file_open::handle_paths(app_handle, urls.into_iter().filter_map(|url| url.to_file_path().ok()));
+            }
             _ => {}
         }
     });
@@ -0,0 +1,634 @@
+//! An opt-in HTTP API on localhost for scripts, launchers and cron jobs.
+//!
+//! When enabled in settings, a server on `127.0.0.1` answers JSON requests
+//! against the configured workspace:
+//!
+//! - `GET /v1/projects` lists the projects that aren't archived.
+//! - `GET /v1/notes?project=&tab=` returns a note, or without `tab` lists the
+//!   project's notes with their titles.
+//! - `POST /v1/notes` with `{ project?, tab?, content }` writes a new note into
+//!   the given tab, or the first empty one, and creates the project if needed.
+//! - `PUT /v1/notes` with `{ project?, tab, content }` replaces a note.
+//! - `POST /v1/notes/append` with `{ project?, tab?, text }` appends to a note;
+//!   without a project or tab the text goes to the capture inbox.
+//! - `GET /v1/search?q=&project=&limit=` searches like the search panel.
+//! - `POST /v1/rpc` takes the same calls as JSON-RPC 2.0, for editor plugins:
+//!   `projects.list`, `notes.list`, `notes.read`, `notes.create`,
+//!   `notes.write`, `notes.append` and `search`, with the parameters above as
+//!   named params (`query` for `q`). `hermes-cli rpc`, through
+//!   `hermes_core::local_api`, passes newline-delimited JSON-RPC from its
+//!   standard input to this endpoint, for editors that talk to a child process.
+//!
+//! Every request must carry `Authorization: Bearer <token>`. The token is
+//! random, kept in the platform's credential store and shown in settings;
+//! requests a web page sends (those with an `Origin`) are refused, so a site
+//! can't reach the API through the browser. Writes go through the save queue,
+//! like the editor's, so they are indexed and announced on `CHANGED_EVENT`.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use hermes_core::local_api::{rpc_error, DEFAULT_PORT, KEYCHAIN_ACCOUNT};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::lan_sync::{hex, random_bytes};
+use crate::{capture, extract_title, file_open, keychain, note_lock, project_list, read_workspace_files, search, validate_tab_key, TAB_KEYS};
+
+/// Settings-store key holding `{ enabled, port }`.
+const LOCAL_API_SETTING: &str = "localApi";
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HEADER_LINES: usize = 64;
+const MAX_LINE_BYTES: usize = 8 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// The token, once read from the credential store.
+static TOKEN: Mutex<Option<String>> = Mutex::new(None);
+
+struct Server {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl Server {
+    /// Stops accepting and waits until the port is free again.
+    fn shut_down(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wakes the accept loop so it sees the flag.
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+        let _ = self.thread.join();
+    }
+}
+
+/// The running server, if the API is enabled.
+#[derive(Default)]
+pub struct LocalApi(Mutex<Option<Server>>);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiStatus {
+    enabled: bool,
+    port: u16,
+    /// False when enabled but the port could not be opened.
+    running: bool,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    enabled: bool,
+    #[serde(default)]
+    port: Option<u16>,
+}
+
+impl Config {
+    fn port(&self) -> u16 {
+        self.port.unwrap_or(DEFAULT_PORT)
+    }
+}
+
+fn config(app: &AppHandle) -> Config {
+    crate::read_setting(app, LOCAL_API_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// The API token, made on first use.
+fn token() -> Result<String, String> {
+    let mut cached = TOKEN.lock().unwrap();
+    if let Some(token) = cached.as_ref() {
+        return Ok(token.clone());
+    }
+    let token = match keychain::load(KEYCHAIN_ACCOUNT) {
+        Some(token) => token,
+        None => {
+            let token = hex(&random_bytes::<32>());
+            keychain::store(KEYCHAIN_ACCOUNT, &token)?;
+            token
+        }
+    };
+    *cached = Some(token.clone());
+    Ok(token)
+}
+
+/// Compares in constant time, so timing doesn't give the token away.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Response {
+        Response {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+impl From<String> for Response {
+    fn from(message: String) -> Response {
+        Response::error(400, message)
+    }
+}
+
+fn decode(text: &str) -> String {
+    percent_decode_str(&text.replace('+', " ")).decode_utf8_lossy().to_string()
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, Response> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE_BYTES as u64)
+        .read_until(b'\n', &mut line)
+        .map_err(|err| Response::error(400, format!("Failed reading request: {err}")))?;
+    if !line.ends_with(b"\n") {
+        return Err(Response::error(431, "Request line too long."));
+    }
+    Ok(String::from_utf8_lossy(&line).trim_end().to_string())
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "Malformed request line."));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+
+    let mut headers = HashMap::new();
+    for _ in 0..MAX_HEADER_LINES {
+        let line = read_line(&mut reader)?;
+        if line.is_empty() {
+            let length = headers
+                .get("content-length")
+                .map(|length: &String| length.parse::<usize>())
+                .transpose()
+                .map_err(|_| Response::error(400, "Invalid Content-Length."))?
+                .unwrap_or(0);
+            if length > MAX_BODY_BYTES {
+                return Err(Response::error(413, "Request body too large."));
+            }
+            let mut body = vec![0; length];
+            reader
+                .read_exact(&mut body)
+                .map_err(|err| Response::error(400, format!("Failed reading request body: {err}")))?;
+            return Ok(Request {
+                method: method.to_string(),
+                path: path.to_string(),
+                query,
+                headers,
+                body,
+            });
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    Err(Response::error(431, "Too many headers."))
+}
+
+fn write_response(mut stream: &TcpStream, response: Response) {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        423 => "Locked",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let body = response.body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        body.len()
+    );
+    if let Err(err) = stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(body.as_bytes())) {
+        tracing::warn!("Failed writing local API response: {err}");
+    }
+}
+
+/// The configured workspace and the folder of `project` in it; the empty project is the root.
+fn folder_of(app: &AppHandle, project: &str) -> Result<(String, String), Response> {
+    let workspace = file_open::configured_workspace(app).ok_or_else(|| Response::error(409, "No workspace is configured."))?;
+    let workspace = workspace.to_string_lossy().to_string();
+    if project.is_empty() {
+        return Ok((workspace.clone(), workspace));
+    }
+    project_list::check_project(project)?;
+    let folder = Path::new(&workspace).join(project).to_string_lossy().to_string();
+    Ok((workspace, folder))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NoteBody {
+    #[serde(default)]
+    project: String,
+    tab: Option<String>,
+    #[serde(alias = "text")]
+    content: String,
+}
+
+fn parse_body(request: &Request) -> Result<NoteBody, Response> {
+    serde_json::from_slice(&request.body).map_err(|err| Response::error(400, format!("Invalid JSON body: {err}")))
+}
+
+/// The tab's content in `folder`, with edits still waiting in a window saved first.
+fn current_pages(app: &AppHandle, folder: &str) -> Result<HashMap<String, String>, Response> {
+    crate::autosave::flush(app, Some(folder)).map_err(|err| Response::error(500, String::from(err)))?;
+    read_workspace_files(folder).map_err(|err| Response::error(500, String::from(err)))
+}
+
+fn save(app: &AppHandle, folder: &str, tab: &str, content: String) -> Result<(), Response> {
+    crate::read_only::ensure_writable(app, folder).map_err(|err| Response::error(403, String::from(err)))?;
+    crate::save_queue::save(app, folder, HashMap::from([(tab.to_string(), content)]))
+        .map_err(|err| Response::error(500, String::from(err)))
+}
+
+fn list_projects(app: &AppHandle) -> Result<Response, Response> {
+    let (workspace, _) = folder_of(app, "")?;
+    let projects = project_list::listed(app, &workspace, false).map_err(|err| Response::error(500, String::from(err)))?;
+    Ok(Response::ok(json!({ "projects": projects })))
+}
+
+fn list_notes(app: &AppHandle, project: &str, folder: &str) -> Result<Response, Response> {
+    let mut pages: Vec<(String, String)> = current_pages(app, folder)?.into_iter().collect();
+    pages.sort_by(|a, b| a.0.cmp(&b.0));
+    let notes: Vec<Value> = pages
+        .iter()
+        .map(|(tab, content)| {
+            let locked = note_lock::is_locked(content);
+            let title = if locked { String::new() } else { extract_title(content) };
+            json!({ "tab": tab, "title": title, "locked": locked })
+        })
+        .collect();
+    Ok(Response::ok(json!({ "project": project, "notes": notes })))
+}
+
+fn get_note(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    let project = request.query.get("project").cloned().unwrap_or_default();
+    let Some(tab) = request.query.get("tab") else {
+        let (_, folder) = folder_of(app, &project)?;
+        if !Path::new(&folder).is_dir() {
+            return Err(Response::error(404, format!("Project '{project}' does not exist.")));
+        }
+        return list_notes(app, &project, &folder);
+    };
+    validate_tab_key(tab)?;
+    let (_, folder) = folder_of(app, &project)?;
+    if !Path::new(&folder).is_dir() {
+        return Err(Response::error(404, format!("Project '{project}' does not exist.")));
+    }
+    let content = current_pages(app, &folder)?.remove(tab).unwrap_or_default();
+    if note_lock::is_locked(&content) {
+        return Err(Response::error(423, "The note is locked."));
+    }
+    Ok(Response::ok(json!({ "project": project, "tab": tab, "content": content })))
+}
+
+fn create_note(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    let body = parse_body(request)?;
+    if body.content.trim().is_empty() {
+        return Err(Response::error(400, "The note is empty."));
+    }
+    let (_, folder) = folder_of(app, &body.project)?;
+    let pages = current_pages(app, &folder)?;
+    let is_empty = |tab: &str| pages.get(tab).is_none_or(|content| content.trim().is_empty());
+    let tab = match body.tab {
+        Some(tab) => {
+            validate_tab_key(&tab)?;
+            if !is_empty(&tab) {
+                return Err(Response::error(409, format!("The {tab} tab already has content.")));
+            }
+            tab
+        }
+        None => TAB_KEYS
+            .iter()
+            .find(|tab| is_empty(tab))
+            .map(|tab| tab.to_string())
+            .ok_or_else(|| Response::error(409, "Every tab of the project has content."))?,
+    };
+    save(app, &folder, &tab, body.content)?;
+    Ok(Response {
+        status: 201,
+        body: json!({ "project": body.project, "tab": tab }),
+    })
+}
+
+fn replace_note(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    let body = parse_body(request)?;
+    let tab = body.tab.ok_or_else(|| Response::error(400, "Missing 'tab'."))?;
+    validate_tab_key(&tab)?;
+    let (_, folder) = folder_of(app, &body.project)?;
+    if !Path::new(&folder).is_dir() {
+        return Err(Response::error(404, format!("Project '{}' does not exist.", body.project)));
+    }
+    let existing = current_pages(app, &folder)?.remove(&tab).unwrap_or_default();
+    if note_lock::is_locked(&existing) {
+        return Err(Response::error(423, "The note is locked."));
+    }
+    save(app, &folder, &tab, body.content)?;
+    Ok(Response::ok(json!({ "project": body.project, "tab": tab })))
+}
+
+fn append_note(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    let body = parse_body(request)?;
+    if body.content.trim().is_empty() {
+        return Err(Response::error(400, "Nothing to append."));
+    }
+    let Some(tab) = body.tab else {
+        if !body.project.is_empty() {
+            return Err(Response::error(400, "Missing 'tab'."));
+        }
+        capture::append_to_inbox(app, &body.content, Some("local API"))?;
+        return Ok(Response::ok(json!({ "inbox": true })));
+    };
+    validate_tab_key(&tab)?;
+    let (_, folder) = folder_of(app, &body.project)?;
+    if !Path::new(&folder).is_dir() {
+        return Err(Response::error(404, format!("Project '{}' does not exist.", body.project)));
+    }
+    let existing = current_pages(app, &folder)?.remove(&tab).unwrap_or_default();
+    if note_lock::is_locked(&existing) {
+        return Err(Response::error(423, "The note is locked."));
+    }
+    let mut content = existing.trim_end().to_string();
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    content.push_str(body.content.trim_end());
+    content.push('\n');
+    save(app, &folder, &tab, content)?;
+    Ok(Response::ok(json!({ "project": body.project, "tab": tab })))
+}
+
+fn search_notes(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    let query = request.query.get("q").ok_or_else(|| Response::error(400, "Missing 'q'."))?;
+    let scope = request.query.get("project").cloned().filter(|project| !project.is_empty());
+    let limit = request
+        .query
+        .get("limit")
+        .map(|limit| limit.parse::<usize>())
+        .transpose()
+        .map_err(|_| Response::error(400, "Invalid 'limit'."))?;
+    let (workspace, _) = folder_of(app, "")?;
+    let hits = search::search(app, &workspace, query, scope, limit).map_err(String::from)?;
+    Ok(Response::ok(json!({ "hits": hits })))
+}
+
+fn route(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    if request.headers.contains_key("origin") {
+        return Err(Response::error(403, "Requests from web pages are not allowed."));
+    }
+    let token = token().map_err(|err| Response::error(500, err))?;
+    let given = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !same_token(given.trim(), &token) {
+        return Err(Response::error(401, "Missing or wrong API token."));
+    }
+    crate::app_lock::ensure_unlocked(app).map_err(|err| Response::error(423, err))?;
+    if request.path.trim_end_matches('/') == "/v1/rpc" {
+        return match request.method.as_str() {
+            "POST" => Ok(rpc(app, &request.body)),
+            _ => Err(Response::error(405, "Method not allowed.")),
+        };
+    }
+    dispatch(app, request)
+}
+
+fn dispatch(app: &AppHandle, request: &Request) -> Result<Response, Response> {
+    match (request.method.as_str(), request.path.trim_end_matches('/')) {
+        ("GET", "/v1/projects") => list_projects(app),
+        ("GET", "/v1/notes") => get_note(app, request),
+        ("POST", "/v1/notes") => create_note(app, request),
+        ("PUT", "/v1/notes") => replace_note(app, request),
+        ("POST", "/v1/notes/append") => append_note(app, request),
+        ("GET", "/v1/search") => search_notes(app, request),
+        (_, "/v1/projects" | "/v1/notes" | "/v1/notes/append" | "/v1/search") => {
+            Err(Response::error(405, "Method not allowed."))
+        }
+        _ => Err(Response::error(404, "No such endpoint.")),
+    }
+}
+
+/// The endpoint behind each JSON-RPC method.
+fn rpc_endpoint(method: &str) -> Option<(&'static str, &'static str)> {
+    Some(match method {
+        "projects.list" => ("GET", "/v1/projects"),
+        "notes.list" | "notes.read" => ("GET", "/v1/notes"),
+        "notes.create" => ("POST", "/v1/notes"),
+        "notes.write" => ("PUT", "/v1/notes"),
+        "notes.append" => ("POST", "/v1/notes/append"),
+        "search" => ("GET", "/v1/search"),
+        _ => return None,
+    })
+}
+
+/// Answers one JSON-RPC call by running it as the matching request; `None` for a notification.
+fn rpc_call(app: &AppHandle, call: Value) -> Option<Value> {
+    let id = call.get("id").cloned();
+    let reply_id = id.clone().unwrap_or(Value::Null);
+    let Some(method) = call.get("method").and_then(Value::as_str).filter(|_| call["jsonrpc"] == "2.0") else {
+        return Some(rpc_error(reply_id, -32600, "Invalid request.", None));
+    };
+    let Some((http_method, path)) = rpc_endpoint(method) else {
+        return Some(rpc_error(reply_id, -32601, format!("No method '{method}'."), None));
+    };
+    let params = match call.get("params") {
+        None | Some(Value::Null) => serde_json::Map::new(),
+        Some(Value::Object(params)) => params.clone(),
+        Some(_) => return Some(rpc_error(reply_id, -32602, "Params must be an object.", None)),
+    };
+    if method == "notes.read" && !params.contains_key("tab") {
+        return Some(rpc_error(reply_id, -32602, "Missing 'tab'.", None));
+    }
+    let query = params
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| {
+            let key = if key == "query" { "q".to_string() } else { key.clone() };
+            (key, value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()))
+        })
+        .collect();
+    let request = Request {
+        method: http_method.to_string(),
+        path: path.to_string(),
+        query,
+        headers: HashMap::new(),
+        body: Value::Object(params).to_string().into_bytes(),
+    };
+    let response = dispatch(app, &request).unwrap_or_else(|err| err);
+    id.as_ref()?;
+    Some(if response.status < 300 {
+        json!({ "jsonrpc": "2.0", "id": reply_id, "result": response.body })
+    } else {
+        let message = response.body["error"].as_str().unwrap_or("Request failed.").to_string();
+        let code = if response.status == 400 { -32602 } else { -32000 };
+        rpc_error(reply_id, code, message, Some(response.status))
+    })
+}
+
+/// Answers a JSON-RPC request or batch; HTTP errors are only for the transport.
+fn rpc(app: &AppHandle, body: &[u8]) -> Response {
+    let reply = match serde_json::from_slice::<Value>(body) {
+        Err(err) => rpc_error(Value::Null, -32700, format!("Parse error: {err}"), None),
+        Ok(Value::Array(calls)) if calls.is_empty() => rpc_error(Value::Null, -32600, "Empty batch.", None),
+        Ok(Value::Array(calls)) => Value::Array(calls.into_iter().filter_map(|call| rpc_call(app, call)).collect()),
+        Ok(call) => rpc_call(app, call).unwrap_or(Value::Null),
+    };
+    Response::ok(reply)
+}
+
+fn handle_connection(app: &AppHandle, stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    let response = match read_request(&stream) {
+        Ok(request) => {
+            let response = route(app, &request).unwrap_or_else(|err| err);
+            tracing::info!("Local API {} {} -> {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+    write_response(&stream, response);
+}
+
+fn listen(app: AppHandle, listener: TcpListener, stop: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        match stream {
+            Ok(stream) => {
+                let app = app.clone();
+                thread::spawn(move || handle_connection(&app, stream));
+            }
+            Err(err) => tracing::warn!("Failed accepting connection: {err}"),
+        }
+    }
+}
+
+/// Stops the running server, then starts one when `config` enables it.
+fn apply(app: &AppHandle, config: Config) -> Result<(), String> {
+    let state = app.state::<LocalApi>();
+    let mut server = state.0.lock().unwrap();
+    if config.enabled && server.as_ref().is_some_and(|running| running.port == config.port()) {
+        return Ok(());
+    }
+    if let Some(running) = server.take() {
+        running.shut_down();
+    }
+    if !config.enabled {
+        return Ok(());
+    }
+    token()?;
+    let listener = TcpListener::bind(("127.0.0.1", config.port()))
+        .map_err(|err| format!("Failed opening port {} for the local API: {err}", config.port()))?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = app.clone();
+    let flag = Arc::clone(&stop);
+    let thread = thread::spawn(move || listen(handle, listener, flag));
+    *server = Some(Server {
+        port: config.port(),
+        stop,
+        thread,
+    });
+    tracing::info!("Local API listening on 127.0.0.1:{}", config.port());
+    Ok(())
+}
+
+/// Starts the server when the API is enabled.
+pub fn init(app: &AppHandle) {
+    if let Err(err) = apply(app, config(app)) {
+        tracing::warn!("{}", err);
+    }
+}
+
+#[tauri::command]
+pub fn get_local_api(app: AppHandle, state: tauri::State<'_, LocalApi>) -> LocalApiStatus {
+    let config = config(&app);
+    LocalApiStatus {
+        enabled: config.enabled,
+        port: config.port(),
+        running: state.0.lock().unwrap().is_some(),
+    }
+}
+
+/// Turns the API on or off, on `port` when given.
+#[tauri::command]
+pub fn set_local_api(
+    app: AppHandle,
+    state: tauri::State<'_, LocalApi>,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<LocalApiStatus, String> {
+    if port.is_some_and(|port| port < 1024) {
+        return Err("Choose a port from 1024 up.".to_string());
+    }
+    let config = Config {
+        enabled,
+        port: port.or(config(&app).port),
+    };
+    apply(&app, config)?;
+    let value = serde_json::to_value(config).map_err(|err| format!("Failed encoding local API settings: {err}"))?;
+    crate::write_setting(&app, LOCAL_API_SETTING, value)?;
+    Ok(get_local_api(app, state))
+}
+
+/// The token scripts send, made on first use.
+#[tauri::command]
+pub fn get_local_api_token() -> Result<String, String> {
+    token()
+}
+
+/// Replaces the token; scripts using the old one stop working.
+#[tauri::command]
+pub fn reset_local_api_token() -> Result<String, String> {
+    keychain::delete(KEYCHAIN_ACCOUNT);
+    *TOKEN.lock().unwrap() = None;
+    token()
+}
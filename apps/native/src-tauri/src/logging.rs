@@ -0,0 +1,344 @@
+//! Structured logging on top of `tracing`.
+//!
+//! Events go to stderr and to a daily log file in the platform log directory
+//! (`hermes.<date>.log`, the last `KEEP_DAYS` kept), one line each, prefixed
+//! with the spans they happened in. Hermes' own events are filtered by the
+//! `logLevel` setting, which `set_log_level` changes at runtime, falling back
+//! to `HERMES_LOG` and then `info`; dependencies only log warnings and errors.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+
+const LOG_LEVEL_SETTING: &str = "logLevel";
+const LOG_FILE_PREFIX: &str = "hermes.";
+const KEEP_DAYS: usize = 7;
+const DEFAULT_RECENT_LINES: usize = 200;
+const MAX_RECENT_LINES: usize = 5000;
+
+static MAX_LEVEL: RwLock<Level> = RwLock::new(Level::INFO);
+
+thread_local! {
+    /// Spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<Id>> = const { RefCell::new(Vec::new()) };
+}
+
+struct SpanData {
+    name: &'static str,
+    fields: String,
+    parent: Option<Id>,
+    refs: usize,
+}
+
+/// Today's log file, reopened when the date changes.
+struct RollingFile {
+    dir: PathBuf,
+    date: String,
+    file: File,
+}
+
+impl RollingFile {
+    fn open(dir: PathBuf) -> Result<RollingFile, String> {
+        fs::create_dir_all(&dir).map_err(|err| format!("Failed creating log directory {}: {err}", dir.display()))?;
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let path = dir.join(format!("{LOG_FILE_PREFIX}{date}.log"));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| format!("Failed opening log file {}: {err}", path.display()))?;
+        prune(&dir);
+        Ok(RollingFile { dir, date, file })
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if chrono::Local::now().format("%Y-%m-%d").to_string() != self.date {
+            match RollingFile::open(self.dir.clone()) {
+                Ok(next) => *self = next,
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+        let _ = writeln!(self.file, "{line}");
+    }
+}
+
+/// Log files in `dir`, newest first.
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX) && name.ends_with(".log"))
+        })
+        .collect();
+    // Dated names sort chronologically.
+    files.sort();
+    files.reverse();
+    files
+}
+
+fn prune(dir: &Path) {
+    for path in log_files(dir).into_iter().skip(KEEP_DAYS) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Collects an event's or span's fields as `key=value`, keeping the message apart.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.rest, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.rest, " {}={value:?}", field.name());
+        }
+    }
+}
+
+struct Logger {
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, SpanData>>,
+    file: Mutex<Option<RollingFile>>,
+}
+
+impl Logger {
+    fn current(&self) -> Option<Id> {
+        ENTERED.with(|entered| entered.borrow().last().cloned())
+    }
+
+    /// `outer{fields}:inner{fields}` for the span chain ending at `id`.
+    fn context(&self, id: Option<Id>) -> String {
+        let spans = self.spans.lock().unwrap();
+        let mut chain = Vec::new();
+        let mut next = id;
+        while let Some(span) = next.and_then(|id| spans.get(&id.into_u64())) {
+            let fields = span.fields.trim_start();
+            chain.push(if fields.is_empty() {
+                span.name.to_string()
+            } else {
+                format!("{}{{{fields}}}", span.name)
+            });
+            next = span.parent.clone();
+        }
+        chain.reverse();
+        chain.join(":")
+    }
+}
+
+impl Subscriber for Logger {
+    // Re-checked on every call so a changed level applies to existing callsites.
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if metadata.target().starts_with("hermes") {
+            *metadata.level() <= *MAX_LEVEL.read().unwrap()
+        } else {
+            *metadata.level() <= Level::WARN
+        }
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let id = Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let mut fields = Fields::default();
+        attributes.record(&mut fields);
+        let parent = if attributes.is_contextual() {
+            self.current()
+        } else {
+            attributes.parent().cloned()
+        };
+        self.spans.lock().unwrap().insert(
+            id.into_u64(),
+            SpanData {
+                name: attributes.metadata().name(),
+                fields: fields.rest,
+                parent,
+                refs: 1,
+            },
+        );
+        id
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            span.fields.push_str(&fields.rest);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let parent = if event.is_contextual() {
+            self.current()
+        } else {
+            event.parent().cloned()
+        };
+        let context = self.context(parent);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:<5} {}{}{}: {}{}",
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            metadata.level(),
+            metadata.target(),
+            if context.is_empty() { "" } else { " " },
+            context,
+            fields.message,
+            fields.rest
+        );
+        eprintln!("{line}");
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            file.write_line(&line);
+        }
+    }
+
+    fn enter(&self, span: &Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, span: &Id) {
+        ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            if let Some(position) = entered.iter().rposition(|id| id == span) {
+                entered.remove(position);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            data.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let Some(data) = spans.get_mut(&span.into_u64()) else {
+            return false;
+        };
+        data.refs -= 1;
+        if data.refs > 0 {
+            return false;
+        }
+        spans.remove(&span.into_u64());
+        true
+    }
+}
+
+fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_log_dir()
+        .map_err(|err| format!("Failed resolving log directory: {err}"))
+}
+
+/// Installs the logger, writing to the app's log directory from now on.
+pub fn init(app: &AppHandle) {
+    let level = crate::read_setting(app, LOG_LEVEL_SETTING)
+        .and_then(|value| value.as_str().and_then(|level| Level::from_str(level).ok()))
+        .or_else(|| std::env::var("HERMES_LOG").ok().and_then(|level| Level::from_str(&level).ok()));
+    if let Some(level) = level {
+        *MAX_LEVEL.write().unwrap() = level;
+    }
+    let file = match log_dir(app).and_then(RollingFile::open) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!("[logging] {err}");
+            None
+        }
+    };
+    let logger = Logger {
+        next_id: AtomicU64::new(1),
+        spans: Mutex::new(HashMap::new()),
+        file: Mutex::new(file),
+    };
+    if let Err(err) = tracing::subscriber::set_global_default(logger) {
+        eprintln!("[logging] Failed installing logger: {err}");
+    }
+}
+
+/// The last `lines` log lines at `level` or more severe, oldest first.
+#[tauri::command]
+pub async fn get_recent_logs(app: AppHandle, level: Option<String>, lines: Option<usize>) -> Result<Vec<String>, String> {
+    let level = match level {
+        Some(level) => parse_level(&level)?,
+        None => Level::INFO,
+    };
+    let lines = lines.unwrap_or(DEFAULT_RECENT_LINES).min(MAX_RECENT_LINES);
+    let dir = log_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut recent = Vec::new();
+        for path in log_files(&dir) {
+            let text = fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+            for line in text.lines().rev() {
+                // The level is the second field of every line.
+                let line_level = line.split_whitespace().nth(1).and_then(|token| Level::from_str(token).ok());
+                if line_level.is_some_and(|line_level| line_level <= level) {
+                    recent.push(line.to_string());
+                    if recent.len() == lines {
+                        recent.reverse();
+                        return Ok(recent);
+                    }
+                }
+            }
+        }
+        recent.reverse();
+        Ok(recent)
+    })
+    .await
+    .map_err(|err| format!("Log reading task failed: {err}"))?
+}
+
+fn parse_level(level: &str) -> Result<Level, String> {
+    Level::from_str(level).map_err(|_| format!("Unknown log level {level}; use error, warn, info, debug or trace."))
+}
+
+#[tauri::command]
+pub fn get_log_level() -> String {
+    MAX_LEVEL.read().unwrap().to_string().to_lowercase()
+}
+
+/// Changes how much Hermes logs, right away and for later launches.
+#[tauri::command]
+pub fn set_log_level(app: AppHandle, level: String) -> Result<(), String> {
+    let level = parse_level(&level)?;
+    let name = level.to_string().to_lowercase();
+    crate::write_setting(&app, LOG_LEVEL_SETTING, Value::String(name.clone()))?;
+    *MAX_LEVEL.write().unwrap() = level;
+    tracing::info!("Log level set to {name}");
+    Ok(())
+}
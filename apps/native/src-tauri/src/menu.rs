@@ -0,0 +1,138 @@
+//! Native application menu built from backend state.
+//!
+//! Actions the backend cannot complete on its own are forwarded to the frontend
+//! as `menu-action` events.
+
+use serde::Serialize;
+use tauri::menu::{Menu, MenuBuilder, MenuEvent, SubmenuBuilder};
+use tauri::{AppHandle, Emitter};
+
+use crate::{export, focus_main_window, recents, workspace_meta};
+
+pub const MENU_ACTION_EVENT: &str = "menu-action";
+
+const NEW_PROJECT_ID: &str = "menu:new-project";
+const OPEN_WORKSPACE_ID: &str = "menu:open-workspace";
+const SETTINGS_ID: &str = "menu:settings";
+const RECENT_PREFIX: &str = "menu:recent:";
+const EXPORT_PREFIX: &str = "menu:export:";
+
+#[derive(Clone, Serialize)]
+struct MenuAction {
+    action: String,
+    value: Option<String>,
+}
+
+pub fn build(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let recent_paths = recents::list(app);
+    let mut recent = SubmenuBuilder::new(app, "Open Recent");
+    if recent_paths.is_empty() {
+        recent = recent.enabled(false);
+    }
+    for path in &recent_paths {
+        recent = recent.text(format!("{RECENT_PREFIX}{path}"), workspace_meta::label(path));
+    }
+
+    let mut exports = SubmenuBuilder::new(app, "Export");
+    for exporter in export::EXPORTERS {
+        exports = exports.text(format!("{EXPORT_PREFIX}{}", exporter.id), format!("{}…", exporter.label));
+    }
+
+    let file = SubmenuBuilder::new(app, "File")
+        .text(NEW_PROJECT_ID, "New Project…")
+        .text(OPEN_WORKSPACE_ID, "Open Workspace…")
+        .item(&recent.build()?)
+        .separator()
+        .item(&exports.build()?)
+        .separator()
+        .close_window()
+        .build()?;
+
+    let edit = SubmenuBuilder::new(app, "Edit")
+        .undo()
+        .redo()
+        .separator()
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .build()?;
+
+    let window = SubmenuBuilder::new(app, "Window")
+        .minimize()
+        .maximize()
+        .separator()
+        .fullscreen()
+        .build()?;
+
+    #[cfg(target_os = "macos")]
+    let menu = {
+        let app_menu = SubmenuBuilder::new(app, "Hermes")
+            .about(None)
+            .separator()
+            .text(SETTINGS_ID, "Settings…")
+            .separator()
+            .services()
+            .separator()
+            .hide()
+            .hide_others()
+            .show_all()
+            .separator()
+            .quit()
+            .build()?;
+        MenuBuilder::new(app).item(&app_menu)
+    };
+
+    // Without an app menu, Settings lives in File.
+    #[cfg(not(target_os = "macos"))]
+    let menu = {
+        file.append(&tauri::menu::MenuItem::with_id(app, SETTINGS_ID, "Settings…", true, None::<&str>)?)?;
+        MenuBuilder::new(app)
+    };
+
+    menu.item(&file).item(&edit).item(&window).build()
+}
+
+/// Rebuilds the menu after recents, their metadata or exporters change.
+pub fn refresh(app: &AppHandle) {
+    match build(app) {
+        Ok(menu) => {
+            if let Err(err) = app.set_menu(menu) {
+                tracing::warn!("Failed to update menu: {err}");
+            }
+        }
+        Err(err) => tracing::warn!("Failed to build menu: {err}"),
+    }
+}
+
+pub fn handle_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    let action = if let Some(path) = id.strip_prefix(RECENT_PREFIX) {
+        MenuAction {
+            action: "open-recent-workspace".to_string(),
+            value: Some(path.to_string()),
+        }
+    } else if let Some(format) = id.strip_prefix(EXPORT_PREFIX) {
+        MenuAction {
+            action: "export".to_string(),
+            value: Some(format.to_string()),
+        }
+    } else {
+        let action = match id {
+            NEW_PROJECT_ID => "new-project",
+            OPEN_WORKSPACE_ID => "open-workspace",
+            SETTINGS_ID => "open-settings",
+            // Predefined items and other menus (tray) handle themselves.
+            _ => return,
+        };
+        MenuAction {
+            action: action.to_string(),
+            value: None,
+        }
+    };
+
+    focus_main_window(app);
+    if let Err(err) = app.emit(MENU_ACTION_EVENT, action) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
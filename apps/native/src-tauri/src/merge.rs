@@ -0,0 +1,155 @@
+//! Three-way merge of a tab edited both in Hermes and on disk.
+//!
+//! The last content Hermes loaded or saved is kept as the merge base in
+//! `.hermes/base/<tab>.md`. Merging diffs base→ours and base→theirs line by
+//! line (diff3): hunks touched on one side only are applied automatically,
+//! overlapping hunks come back as conflict regions for the UI to resolve.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use tauri::AppHandle;
+
+use crate::{hermes_dir, note_path, validate_tab_key};
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MergeRegion {
+    /// Text both sides agree on, including one-sided changes.
+    Clean { text: String },
+    Conflict { base: String, ours: String, theirs: String },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeOutcome {
+    /// The merged text when no region conflicts.
+    pub merged: Option<String>,
+    pub regions: Vec<MergeRegion>,
+}
+
+fn base_path(workspace_path: &str, tab: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("base").join(format!("{tab}.md"))
+}
+
+/// Records `content` as the version both Hermes and the disk agree on.
+pub fn store_base(workspace_path: &str, tab: &str, content: &str) {
+    let path = base_path(workspace_path, tab);
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, content));
+    if let Err(err) = result {
+        tracing::warn!("Failed writing base {}: {err}", path.display());
+    }
+}
+
+pub fn remove_base(workspace_path: &str, tab: &str) {
+    let _ = fs::remove_file(base_path(workspace_path, tab));
+}
+
+/// For each base line, the matching line index in `other` if it is unchanged.
+fn matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut map = vec![None; base.len()];
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for offset in 0..len {
+                map[old_index + offset] = Some(new_index + offset);
+            }
+        }
+    }
+    map
+}
+
+fn push_clean(regions: &mut Vec<MergeRegion>, lines: &[&str]) {
+    if lines.is_empty() {
+        return;
+    }
+    match regions.last_mut() {
+        Some(MergeRegion::Clean { text }) => text.push_str(&lines.concat()),
+        _ => regions.push(MergeRegion::Clean { text: lines.concat() }),
+    }
+}
+
+fn resolve_chunk(regions: &mut Vec<MergeRegion>, base: &[&str], ours: &[&str], theirs: &[&str]) {
+    if ours == base || ours == theirs {
+        push_clean(regions, theirs);
+    } else if theirs == base {
+        push_clean(regions, ours);
+    } else {
+        regions.push(MergeRegion::Conflict {
+            base: base.concat(),
+            ours: ours.concat(),
+            theirs: theirs.concat(),
+        });
+    }
+}
+
+/// diff3 over lines (line endings preserved).
+pub fn merge(base: &str, ours: &str, theirs: &str) -> MergeOutcome {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let ours_map = matches(&base, &ours);
+    let theirs_map = matches(&base, &theirs);
+
+    let mut regions = Vec::new();
+    let (mut i, mut a, mut b) = (0, 0, 0);
+    loop {
+        // Lines unchanged on both sides.
+        while i < base.len() && ours_map[i] == Some(a) && theirs_map[i] == Some(b) {
+            push_clean(&mut regions, &base[i..=i]);
+            i += 1;
+            a += 1;
+            b += 1;
+        }
+
+        // Next base line both sides kept closes the changed chunk.
+        let next = (i..base.len()).find_map(|j| match (ours_map[j], theirs_map[j]) {
+            (Some(oa), Some(ob)) if oa >= a && ob >= b => Some((j, oa, ob)),
+            _ => None,
+        });
+        match next {
+            Some((j, oa, ob)) => {
+                resolve_chunk(&mut regions, &base[i..j], &ours[a..oa], &theirs[b..ob]);
+                (i, a, b) = (j, oa, ob);
+            }
+            None => {
+                resolve_chunk(&mut regions, &base[i..], &ours[a..], &theirs[b..]);
+                break;
+            }
+        }
+    }
+
+    let merged = regions
+        .iter()
+        .map(|region| match region {
+            MergeRegion::Clean { text } => Some(text.as_str()),
+            MergeRegion::Conflict { .. } => None,
+        })
+        .collect::<Option<String>>();
+    MergeOutcome { merged, regions }
+}
+
+/// Merges unsaved in-app content (`ours`) with the tab file as changed on disk.
+#[tauri::command]
+pub fn merge_external_change(app: AppHandle, workspace_path: String, tab_key: String, ours: String) -> Result<MergeOutcome, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    let theirs = if file_path.exists() {
+        fs::read_to_string(&file_path).map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?
+    } else {
+        String::new()
+    };
+    // Without a recorded base every differing line is a conflict, which is the safe fallback.
+    let base = fs::read_to_string(base_path(&workspace_path, &tab_key)).unwrap_or_default();
+    Ok(merge(&base, &ours, &theirs))
+}
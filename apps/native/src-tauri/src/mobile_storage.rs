@@ -0,0 +1,84 @@
+//! Where workspaces live on iOS and Android.
+//!
+//! A mobile app has no `$HOME/Documents`; it gets a sandbox. The default
+//! workspace is `Hermes` in the app's documents folder. On iOS that folder
+//! shows in the Files app under On My iPhone → Hermes, which
+//! `Info.ios.plist` turns on. On Android it is the app's external files
+//! folder, which file managers and USB can reach.
+//!
+//! Folders outside the sandbox, such as another app's iCloud Drive folder or a
+//! Storage Access Framework tree, are reached through security-scoped or
+//! `content://` URLs that plain file APIs can't open, so they aren't offered
+//! as workspaces. iOS also moves the sandbox when the app is updated, so a
+//! stored workspace path under an old sandbox is moved onto the current one.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+use crate::canonical_path;
+
+const WORKSPACE_FOLDER: &str = "Hermes";
+
+/// Folders the app may keep workspaces in: its documents, then its data.
+fn sandbox_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let paths = app.path();
+    [paths.document_dir(), paths.app_data_dir()]
+        .into_iter()
+        .flatten()
+        .map(|dir| canonical_path(&dir))
+        .collect()
+}
+
+/// `Hermes` in the app's documents folder, created if needed.
+pub fn default_workspace(app: &AppHandle) -> Result<String, String> {
+    let dir = sandbox_dirs(app)
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Could not determine the app's documents folder".to_string())?
+        .join(WORKSPACE_FOLDER);
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed creating default workspace {}: {err}", dir.display()))?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// Whether `path` is inside the app's sandbox, where file APIs can reach it.
+pub fn is_reachable(app: &AppHandle, path: &Path) -> bool {
+    let path = canonical_path(path);
+    sandbox_dirs(app).iter().any(|dir| path.starts_with(dir))
+}
+
+/// The same folder under the current sandbox, for a path stored under an old
+/// one: the part after its `Documents` folder, moved to the current documents.
+fn rehome(app: &AppHandle, stored: &Path) -> Option<PathBuf> {
+    let documents = app.path().document_dir().ok()?;
+    let parts: Vec<_> = stored.components().collect();
+    let at = parts.iter().rposition(|part| part.as_os_str() == "Documents")?;
+    let moved = parts[at + 1..].iter().fold(documents, |path, part| path.join(part));
+    moved.is_dir().then_some(moved)
+}
+
+/// Points the stored workspace at a folder the app can reach: the same
+/// folder under the current sandbox when it moved, else the default.
+pub fn repair_setting(app: &AppHandle) {
+    let Some(stored) = crate::read_setting(app, "workspacePath").and_then(|value| value.as_str().map(str::to_string)) else {
+        return;
+    };
+    if stored.trim().is_empty() || (is_reachable(app, Path::new(&stored)) && Path::new(&stored).is_dir()) {
+        return;
+    }
+    let repaired = match rehome(app, Path::new(&stored)) {
+        Some(path) => path.to_string_lossy().to_string(),
+        None => match default_workspace(app) {
+            Ok(path) => path,
+            Err(err) => {
+                tracing::warn!("{}", err);
+                return;
+            }
+        },
+    };
+    tracing::info!("Moved the workspace setting from {stored} to {repaired}");
+    if let Err(err) = crate::write_setting(app, "workspacePath", serde_json::json!(repaired)) {
+        tracing::warn!("{}", err);
+    }
+}
@@ -0,0 +1,173 @@
+//! Chunked loading and saving of very large notes.
+//!
+//! Sending a multi-megabyte note through IPC on every load and save makes the
+//! editor sluggish. Notes over `CHUNKED_NOTE_BYTES` are left out of
+//! `load_workspace_pages`; the frontend reads them piecewise with
+//! `get_note_chunk` and saves them as a stream of chunks that is only written
+//! once complete. Locked notes always take the regular path.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{app_lock, note_lock, note_path, permissions, text_encoding, validate_tab_key, CommandError};
+
+/// Notes larger than this are loaded and saved in chunks.
+pub const CHUNKED_NOTE_BYTES: u64 = 1024 * 1024;
+/// Largest chunk served at once.
+const MAX_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+
+struct NoteStream {
+    workspace_path: String,
+    tab: String,
+    content: String,
+}
+
+/// Streamed saves in progress, by stream id.
+#[derive(Default)]
+pub struct NoteStreams {
+    next_id: AtomicU64,
+    streams: Mutex<HashMap<u64, NoteStream>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkedNote {
+    tab: String,
+    bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteChunk {
+    content: String,
+    /// Byte offset to ask for next; equals `total_bytes` at the end.
+    next_offset: u64,
+    total_bytes: u64,
+}
+
+/// Whether `file_path` is too large to travel through `load_workspace_pages`.
+/// Binary files and notes in other encodings take the regular path, which
+/// skips or decodes them.
+pub fn is_chunked_file(file_path: &Path) -> bool {
+    fs::metadata(file_path).is_ok_and(|metadata| metadata.len() > CHUNKED_NOTE_BYTES)
+        && !note_lock::is_locked_file(file_path)
+        && text_encoding::is_plain_utf8_file(file_path)
+}
+
+/// Large notes of a workspace, which `load_workspace_pages` leaves out.
+#[tauri::command]
+pub fn list_chunked_notes(workspace_path: String) -> Vec<ChunkedNote> {
+    crate::TAB_KEYS
+        .iter()
+        .map(|tab| (tab, note_path(&workspace_path, tab)))
+        .filter(|(_, file_path)| is_chunked_file(file_path))
+        .filter_map(|(tab, file_path)| {
+            Some(ChunkedNote {
+                tab: tab.to_string(),
+                bytes: fs::metadata(file_path).ok()?.len(),
+            })
+        })
+        .collect()
+}
+
+/// Up to `len` bytes of a note from `offset`, cut at a character boundary.
+#[tauri::command]
+pub fn get_note_chunk(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    offset: u64,
+    len: u64,
+) -> Result<NoteChunk, CommandError> {
+    app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    let mut file = File::open(&file_path).map_err(|err| permissions::io_error("reading", &file_path, err))?;
+    let total_bytes = file
+        .metadata()
+        .map_err(|err| permissions::io_error("reading", &file_path, err))?
+        .len();
+    let offset = offset.min(total_bytes);
+
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.take(len.clamp(4, MAX_CHUNK_BYTES)).read_to_end(&mut bytes))
+        .map_err(|err| permissions::io_error("reading", &file_path, err))?;
+    let at_end = offset + bytes.len() as u64 >= total_bytes;
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        // A chunk may end inside a multi-byte character; it starts the next chunk instead.
+        Err(err) if err.utf8_error().error_len().is_none() && !at_end => {
+            let valid = err.utf8_error().valid_up_to();
+            let mut bytes = err.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).unwrap_or_default()
+        }
+        // The file changed since it was listed; cutting it short would lose the rest on the next save.
+        Err(_) => {
+            return Err(CommandError::from(format!(
+                "{} is no longer UTF-8 text; reopen the workspace to load it.",
+                file_path.display()
+            )))
+        }
+    };
+    Ok(NoteChunk {
+        next_offset: offset + content.len() as u64,
+        content,
+        total_bytes,
+    })
+}
+
+/// Starts a streamed save of one tab and returns its stream id.
+#[tauri::command]
+pub fn begin_note_save(app: AppHandle, workspace_path: String, tab_key: String) -> Result<u64, CommandError> {
+    validate_tab_key(&tab_key)?;
+    crate::read_only::ensure_writable(&app, &workspace_path)?;
+    let state = app.state::<NoteStreams>();
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    state.streams.lock().unwrap().insert(
+        id,
+        NoteStream {
+            workspace_path,
+            tab: tab_key,
+            content: String::new(),
+        },
+    );
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn append_note_chunk(app: AppHandle, stream_id: u64, chunk: String) -> Result<(), String> {
+    let state = app.state::<NoteStreams>();
+    let mut streams = state.streams.lock().unwrap();
+    let stream = streams
+        .get_mut(&stream_id)
+        .ok_or_else(|| "This save was already finished or cancelled.".to_string())?;
+    stream.content.push_str(&chunk);
+    Ok(())
+}
+
+/// Writes the streamed content like `save_workspace_page`.
+#[tauri::command]
+pub fn finish_note_save(app: AppHandle, stream_id: u64) -> Result<(), CommandError> {
+    let stream = app
+        .state::<NoteStreams>()
+        .streams
+        .lock()
+        .unwrap()
+        .remove(&stream_id)
+        .ok_or_else(|| "This save was already finished or cancelled.".to_string())?;
+    crate::save_workspace_page(app, stream.workspace_path, stream.tab, stream.content)
+}
+
+#[tauri::command]
+pub fn cancel_note_save(app: AppHandle, stream_id: u64) {
+    app.state::<NoteStreams>().streams.lock().unwrap().remove(&stream_id);
+}
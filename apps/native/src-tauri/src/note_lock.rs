@@ -0,0 +1,253 @@
+//! Password-protected tabs.
+//!
+//! A locked tab's file keeps its `.md` name but holds a marker line followed by
+//! hex of `salt | nonce | ChaCha20-Poly1305 ciphertext`, keyed with Argon2id
+//! from the passphrase. Unlocking keeps the derived key in memory until the
+//! note or the app is relocked, so saves are re-encrypted transparently. The
+//! index only records that the tab is locked; its text is never indexed, and
+//! merge bases, CRDT documents, undo history and earlier plaintext snapshots
+//! are not kept for it.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+pub use hermes_core::note_lock::{is_locked, is_locked_file, LOCKED_MARKER};
+use rand::RngCore;
+use tauri::{AppHandle, Manager};
+
+use crate::{canonical_workspace, history, note_path, read_workspace_pages, text_encoding, validate_tab_key, workspace_lock};
+
+const SALT_BYTES: usize = 16;
+const NONCE_BYTES: usize = 12;
+
+struct SessionKey {
+    salt: [u8; SALT_BYTES],
+    key: [u8; 32],
+}
+
+/// Keys of notes unlocked this session, by file path.
+#[derive(Default)]
+pub struct UnlockedNotes(Mutex<HashMap<PathBuf, SessionKey>>);
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| format!("Failed deriving note key: {err}"))?;
+    Ok(key)
+}
+
+fn seal(session: &SessionKey, plaintext: &str) -> Result<String, String> {
+    let mut nonce = [0; NONCE_BYTES];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&session.key))
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| "Failed encrypting note".to_string())?;
+
+    let mut payload = session.salt.to_vec();
+    payload.extend_from_slice(&nonce);
+    payload.extend(ciphertext);
+    Ok(format!("{LOCKED_MARKER}\n{}\n", hex(&payload)))
+}
+
+/// Splits a locked file into its salt and sealed body.
+fn parse(content: &str) -> Result<([u8; SALT_BYTES], Vec<u8>), String> {
+    let payload = content
+        .strip_prefix(LOCKED_MARKER)
+        .and_then(|rest| unhex(rest.trim()))
+        .filter(|payload| payload.len() > SALT_BYTES + NONCE_BYTES)
+        .ok_or_else(|| "The locked note is damaged.".to_string())?;
+    let mut salt = [0; SALT_BYTES];
+    salt.copy_from_slice(&payload[..SALT_BYTES]);
+    Ok((salt, payload[SALT_BYTES..].to_vec()))
+}
+
+fn open(key: &[u8; 32], sealed: &[u8]) -> Option<String> {
+    let (nonce, ciphertext) = sealed.split_at(NONCE_BYTES);
+    let plaintext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .ok()?;
+    String::from_utf8(plaintext).ok()
+}
+
+/// The plaintext of a locked note if it is unlocked this session, else the file as stored.
+pub fn reveal(app: &AppHandle, file_path: &Path, content: String) -> String {
+    let unlocked = app.state::<UnlockedNotes>();
+    let unlocked = unlocked.0.lock().unwrap();
+    let Some(session) = unlocked.get(file_path) else {
+        return content;
+    };
+    parse(&content)
+        .ok()
+        .and_then(|(_, sealed)| open(&session.key, &sealed))
+        .unwrap_or(content)
+}
+
+/// File content to save for a locked note: re-encrypted if it is unlocked this
+/// session, `None` if it is still locked and must be left untouched.
+pub fn seal_for_session(app: &AppHandle, file_path: &Path, content: &str) -> Result<Option<String>, String> {
+    let unlocked = app.state::<UnlockedNotes>();
+    let unlocked = unlocked.0.lock().unwrap();
+    match unlocked.get(file_path) {
+        Some(session) if !is_locked(content) => seal(session, content).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// What to keep in held saves for a tab: sealed when it is a locked note
+/// unlocked this session, so its text never reaches app data in the clear.
+pub fn seal_for_hold(app: &AppHandle, file_path: &Path, content: &str) -> Result<String, String> {
+    Ok(seal_for_session(app, file_path, content)?.unwrap_or_else(|| content.to_string()))
+}
+
+/// Forgets every unlocked note's key, e.g. when the app locks.
+pub fn relock_all(app: &AppHandle) {
+    app.state::<UnlockedNotes>().0.lock().unwrap().clear();
+}
+
+fn reindex(workspace_path: &str) {
+    if let Err(err) = read_workspace_pages(workspace_path) {
+        tracing::warn!("{}", String::from(err));
+    }
+}
+
+fn lock(app: &AppHandle, workspace_path: &str, tab_key: &str, passphrase: &str) -> Result<(), String> {
+    // Unlocked keys are kept by path, which must match the one saves use.
+    let workspace_path = canonical_workspace(workspace_path);
+    let file_path = note_path(&workspace_path, tab_key);
+    // Edits still waiting to be saved are sealed with the rest.
+    crate::autosave::flush(app, Some(&workspace_path)).map_err(String::from)?;
+    let mut salt = [0; SALT_BYTES];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let session = SessionKey {
+        salt,
+        key: derive_key(passphrase, &salt)?,
+    };
+
+    let write_lock = workspace_lock::acquire(&workspace_path)?;
+    let bytes = fs::read(&file_path).map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?;
+    // Sealed as the editor shows it, so a note in another encoding isn't lost.
+    let content = match text_encoding::decode(bytes) {
+        text_encoding::Decoded::Text(content) | text_encoding::Decoded::Transcoded(content, _) => content,
+        text_encoding::Decoded::Binary => return Err(format!("{} is not a text file.", file_path.display())),
+    };
+    if is_locked(&content) {
+        return Err("This note is already locked.".to_string());
+    }
+    history::write_atomically(&file_path, &seal(&session, &content)?)?;
+    drop(write_lock);
+
+    // Plaintext copies kept for merging and sync would defeat the lock.
+    crate::merge::remove_base(&workspace_path, tab_key);
+    crate::crdt::remove(&workspace_path, tab_key);
+    crate::undo::remove(&workspace_path, tab_key);
+    let _ = fs::remove_dir_all(history::snapshots_dir(&workspace_path, tab_key));
+    app.state::<UnlockedNotes>().0.lock().unwrap().insert(file_path, session);
+    reindex(&workspace_path);
+    Ok(())
+}
+
+/// Encrypts a tab with `passphrase`; it stays readable this session.
+#[tauri::command]
+pub async fn lock_note(app: AppHandle, workspace_path: String, tab_key: String, passphrase: String) -> Result<(), String> {
+    validate_tab_key(&tab_key)?;
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    if passphrase.is_empty() {
+        return Err("Enter a password to lock the note.".to_string());
+    }
+    // Argon2 takes a noticeable moment; keep it off the main thread.
+    tauri::async_runtime::spawn_blocking(move || lock(&app, &workspace_path, &tab_key, &passphrase))
+        .await
+        .map_err(|err| format!("Lock task failed: {err}"))?
+}
+
+/// Decrypts a locked tab for this session and returns its text.
+#[tauri::command]
+pub async fn unlock_note(app: AppHandle, workspace_path: String, tab_key: String, passphrase: String) -> Result<String, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    // Argon2 takes a noticeable moment; keep it off the main thread.
+    tauri::async_runtime::spawn_blocking(move || {
+        let file_path = note_path(&canonical_workspace(&workspace_path), &tab_key);
+        let content = fs::read_to_string(&file_path)
+            .map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?;
+        if !is_locked(&content) {
+            return Ok(content);
+        }
+
+        let (salt, sealed) = parse(&content)?;
+        let key = derive_key(&passphrase, &salt)?;
+        let plaintext = open(&key, &sealed).ok_or_else(|| "Wrong password.".to_string())?;
+        app.state::<UnlockedNotes>()
+            .0
+            .lock()
+            .unwrap()
+            .insert(file_path, SessionKey { salt, key });
+        Ok(plaintext)
+    })
+    .await
+    .map_err(|err| format!("Unlock task failed: {err}"))?
+}
+
+/// Locks an unlocked tab again without changing its password.
+#[tauri::command]
+pub fn relock_note(app: AppHandle, workspace_path: String, tab_key: String) -> Result<(), String> {
+    validate_tab_key(&tab_key)?;
+    app.state::<UnlockedNotes>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(&note_path(&canonical_workspace(&workspace_path), &tab_key));
+    Ok(())
+}
+
+/// Decrypts a tab back to plain markdown, removing its password.
+#[tauri::command]
+pub async fn remove_note_lock(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    passphrase: String,
+) -> Result<(), String> {
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    // Checks the password and keeps the note's key for this session.
+    unlock_note(app.clone(), workspace_path.clone(), tab_key.clone(), passphrase).await?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    tauri::async_runtime::spawn_blocking(move || {
+        // Saves made since unlocking land first, then the decrypted note replaces the file.
+        crate::autosave::flush(&app, Some(&workspace_path)).map_err(String::from)?;
+        let write_lock = workspace_lock::acquire(&workspace_path)?;
+        let content = fs::read_to_string(&file_path).map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?;
+        let plaintext = reveal(&app, &file_path, content);
+        if is_locked(&plaintext) {
+            return Err("The locked note changed; unlock it again.".to_string());
+        }
+        history::write_atomically(&file_path, &plaintext)?;
+        drop(write_lock);
+        app.state::<UnlockedNotes>().0.lock().unwrap().remove(&file_path);
+        reindex(&workspace_path);
+        Ok(())
+    })
+    .await
+    .map_err(|err| format!("Lock task failed: {err}"))?
+}
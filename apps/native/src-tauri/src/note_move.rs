@@ -0,0 +1,312 @@
+//! Moving and copying a note from one project to another.
+//!
+//! The note goes into the same tab of the other project when that tab is
+//! free there, otherwise into the first free one. Its snapshots come along,
+//! as do files next to the note that it links to or shows, renamed when the
+//! other project has a file of the same name, with the note's references
+//! following them. Both projects' index rows change in one SQLite transaction,
+//! the source index attached to the destination's, so search never finds the
+//! note in both projects or in neither. A copy starts its own version vector.
+
+use std::fs;
+use std::path::{Component, Path};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::workspace_merge::free_name;
+use crate::{
+    canonical_workspace, crdt, file_hashes, history, index_writer, merge, note_lock, note_path, notes_dir,
+    run_sqlite_script, sql_escape, sqlite_path, tab_files, undo, validate_tab_key, workspace_lock, CommandError, TAB_KEYS,
+};
+
+/// Tables keyed by tab whose rows go with the note, with their other columns.
+const TAB_TABLES: [(&str, &str); 5] = [
+    ("note_index", "file_path, title, body, word_count, char_count, updated_unix, project_path"),
+    ("note_tags", "tag, source"),
+    ("note_tasks", "line, text, due"),
+    ("note_versions", "vector, content_hash"),
+    ("note_locks", "locked_unix"),
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRelocated {
+    src_project: String,
+    dest_project: String,
+    src_tab: String,
+    dest_tab: String,
+    /// Linked files copied next to the note in `dest_project`.
+    assets_copied: usize,
+    /// Snapshots carried into the destination's history.
+    snapshots: usize,
+}
+
+/// The folder of the project at `project` in the workspace; the empty path is the workspace root.
+fn project_path(workspace_path: &str, project: &str) -> Result<String, String> {
+    if project.is_empty() {
+        return Ok(workspace_path.to_string());
+    }
+    crate::project_list::check_project(project)?;
+    let path = Path::new(workspace_path).join(project);
+    if !path.is_dir() {
+        return Err(format!("Project '{project}' does not exist in {workspace_path}"));
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Relative link and image targets of `content` that name a file in `dir`.
+pub fn local_targets(content: &str, dir: &Path) -> Vec<String> {
+    let mut targets: Vec<String> = Parser::new(content)
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => Some(dest_url.to_string()),
+            _ => None,
+        })
+        .filter(|url| !url.contains(':') && !url.starts_with('#'))
+        .filter(|url| Path::new(url).components().all(|part| matches!(part, Component::Normal(_))))
+        .filter(|url| dir.join(url).is_file())
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// Copies the files `content` links to from `from` into `to` and returns the
+/// content with references to renamed copies rewritten.
+fn copy_assets(content: &str, from: &Path, to: &Path, copied: &mut usize) -> Result<String, String> {
+    let mut content = content.to_string();
+    for target in local_targets(&content, from) {
+        let source = from.join(&target);
+        let relative = Path::new(&target);
+        let folder = to.join(relative.parent().unwrap_or(Path::new("")));
+        fs::create_dir_all(&folder).map_err(|err| format!("Failed creating {}: {err}", folder.display()))?;
+        let name = relative.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let free = free_name(&name, |candidate| folder.join(candidate).exists());
+        fs::copy(&source, folder.join(&free)).map_err(|err| format!("Failed copying {}: {err}", source.display()))?;
+        *copied += 1;
+        if free != name {
+            let renamed = relative.with_file_name(&free).to_string_lossy().replace('\\', "/");
+            content = content.replace(&format!("]({target}"), &format!("]({renamed}"));
+        }
+    }
+    Ok(content)
+}
+
+/// Copies the snapshots of `src_tab` into the history of `dest_tab`.
+fn carry_snapshots(src: &str, src_tab: &str, dest: &str, dest_tab: &str) -> usize {
+    let from = history::snapshots_dir(src, src_tab);
+    let to = history::snapshots_dir(dest, dest_tab);
+    let Ok(entries) = fs::read_dir(&from) else {
+        return 0;
+    };
+    if let Err(err) = fs::create_dir_all(&to) {
+        tracing::warn!("Failed creating {}: {err}", to.display());
+        return 0;
+    }
+    let mut carried = 0;
+    for entry in entries.flatten() {
+        let target = to.join(entry.file_name());
+        if target.exists() {
+            continue;
+        }
+        match fs::copy(entry.path(), &target) {
+            Ok(_) => carried += 1,
+            Err(err) => tracing::warn!("Failed copying {}: {err}", entry.path().display()),
+        }
+    }
+    carried
+}
+
+/// SQL run against the destination index that gives `dest_tab` the rows of
+/// `src_tab` in the attached source index, dropping those with `remove_source`.
+fn index_script(
+    src_db: &Path,
+    src_tab: &str,
+    dest_tab: &str,
+    dest_file: &str,
+    dest_project: &str,
+    remove_source: bool,
+    now_unix: i64,
+) -> String {
+    let (src_tab, dest_tab, dest_project) = (sql_escape(src_tab), sql_escape(dest_tab), sql_escape(dest_project));
+    let dest_file = sql_escape(dest_file);
+    let mut script = format!(
+        "ATTACH DATABASE '{}' AS source;\nBEGIN IMMEDIATE;\n",
+        sql_escape(&src_db.to_string_lossy())
+    );
+    for (table, columns) in TAB_TABLES {
+        script.push_str(&format!("DELETE FROM main.{table} WHERE tab_key = '{dest_tab}';\n"));
+        // A copy is a new note, with a version history of its own.
+        if table == "note_versions" && !remove_source {
+            continue;
+        }
+        let values = match table {
+            "note_index" => format!("'{dest_file}', title, body, word_count, char_count, {now_unix}, '{dest_project}'"),
+            _ => columns.to_string(),
+        };
+        script.push_str(&format!(
+            "INSERT INTO main.{table}(tab_key, {columns}) SELECT '{dest_tab}', {values} FROM source.{table} WHERE tab_key = '{src_tab}';\n"
+        ));
+    }
+    if remove_source {
+        for (table, _) in TAB_TABLES {
+            script.push_str(&format!("DELETE FROM source.{table} WHERE tab_key = '{src_tab}';\n"));
+        }
+    }
+    script.push_str("COMMIT;\n");
+    script
+}
+
+fn relocate_note(
+    app: &AppHandle,
+    workspace_path: &str,
+    src_project: String,
+    tab_key: String,
+    dest_project: String,
+    dest_tab: Option<String>,
+    remove_source: bool,
+) -> Result<NoteRelocated, CommandError> {
+    validate_tab_key(&tab_key)?;
+    if let Some(dest_tab) = &dest_tab {
+        validate_tab_key(dest_tab)?;
+    }
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    let src = project_path(&workspace_path, &src_project)?;
+    let dest = project_path(&workspace_path, &dest_project)?;
+    if src == dest {
+        return Err("Choose another project to put the note in.".to_string().into());
+    }
+    crate::read_only::ensure_writable(app, &dest)?;
+    if remove_source {
+        crate::read_only::ensure_writable(app, &src)?;
+    }
+
+    // Edits still waiting to be saved go into the note before it leaves.
+    crate::workspaces::flush_under(app, &src)?;
+    crate::workspaces::flush_under(app, &dest)?;
+    let (first, second) = if src < dest { (&src, &dest) } else { (&dest, &src) };
+    let _first_lock = workspace_lock::acquire(first)?;
+    let _second_lock = workspace_lock::acquire(second)?;
+
+    let source = note_path(&src, &tab_key);
+    if !source.is_file() {
+        return Err(format!("There is no note in the {tab_key} tab of '{src_project}'.").into());
+    }
+    let is_free = |tab: &str| !note_path(&dest, tab).exists();
+    let dest_tab = match dest_tab {
+        Some(tab) if is_free(&tab) => tab,
+        Some(tab) => return Err(format!("The {tab} tab of '{dest_project}' already has a note.").into()),
+        None if is_free(&tab_key) => tab_key.clone(),
+        None => TAB_KEYS
+            .iter()
+            .find(|tab| is_free(tab))
+            .map(|tab| tab.to_string())
+            .ok_or_else(|| format!("Every tab of '{dest_project}' has a note; clear one first."))?,
+    };
+    let target = note_path(&dest, &dest_tab);
+
+    // Encrypted notes and other encodings go across as they are.
+    let raw = fs::read(&source).map_err(|err| format!("Failed reading {}: {err}", source.display()))?;
+    let text = String::from_utf8(raw.clone()).ok().filter(|text| !note_lock::is_locked(text));
+    let mut assets_copied = 0;
+    let content = match &text {
+        Some(text) => Some(copy_assets(text, &notes_dir(&src), &notes_dir(&dest), &mut assets_copied)?),
+        None => None,
+    };
+    let rewritten = content.as_ref().filter(|content| Some(*content) != text.as_ref());
+    match rewritten {
+        Some(content) => fs::write(&target, content),
+        // An unchanged file is copied so its Finder tags come along.
+        None => fs::copy(&source, &target).map(|_| ()),
+    }
+    .map_err(|err| format!("Failed writing {}: {err}", target.display()))?;
+
+    index_writer::discard(app, &src, &tab_key);
+    index_writer::discard(app, &dest, &dest_tab);
+    let now_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+    let indexed = crate::index_tabs(&src, std::iter::empty())
+        .and_then(|()| crate::index_tabs(&dest, std::iter::empty()))
+        .and_then(|()| {
+            let dest_file = tab_files::file_name(&dest, &dest_tab);
+            let script = index_script(&sqlite_path(&src), &tab_key, &dest_tab, &dest_file, &dest_project, remove_source, now_unix);
+            run_sqlite_script(&sqlite_path(&dest), &script)
+        });
+    if let Err(err) = indexed {
+        let _ = fs::remove_file(&target);
+        return Err(err.into());
+    }
+    if let Some(content) = rewritten {
+        crate::index_tabs(&dest, [(dest_tab.as_str(), content.as_str())])?;
+    }
+
+    let snapshots = carry_snapshots(&src, &tab_key, &dest, &dest_tab);
+    crdt::remove(&dest, &dest_tab);
+    undo::remove(&dest, &dest_tab);
+    if let Some(content) = &content {
+        file_hashes::remember(&target, content);
+        merge::store_base(&dest, &dest_tab, content);
+        crdt::record(&dest, &dest_tab, content);
+    }
+    if remove_source {
+        fs::remove_file(&source).map_err(|err| format!("Failed removing {}: {err}", source.display()))?;
+        file_hashes::forget(&source);
+        merge::remove_base(&src, &tab_key);
+        crdt::remove(&src, &tab_key);
+        undo::remove(&src, &tab_key);
+        let _ = fs::remove_dir_all(history::snapshots_dir(&src, &tab_key));
+    }
+
+    tracing::info!(
+        "{} note {tab_key} of '{src_project}' to {dest_tab} of '{dest_project}'",
+        if remove_source { "Moved" } else { "Copied" }
+    );
+    let change = crate::changes::Change::NoteRelocated {
+        workspace_path,
+        src_project: src_project.clone(),
+        src_tab: tab_key.clone(),
+        dest_project: dest_project.clone(),
+        dest_tab: dest_tab.clone(),
+        moved: remove_source,
+    };
+    crate::changes::emit(app, change);
+    Ok(NoteRelocated {
+        src_project,
+        dest_project,
+        src_tab: tab_key,
+        dest_tab,
+        assets_copied,
+        snapshots,
+    })
+}
+
+/// Moves the note in `tab_key` of `src_project` to `dest_project`, into
+/// `dest_tab` when given; the empty project name is the workspace root.
+#[tauri::command]
+pub fn move_note(
+    app: AppHandle,
+    workspace_path: String,
+    src_project: String,
+    tab_key: String,
+    dest_project: String,
+    dest_tab: Option<String>,
+) -> Result<NoteRelocated, CommandError> {
+    relocate_note(&app, &workspace_path, src_project, tab_key, dest_project, dest_tab, true)
+}
+
+/// Copies the note in `tab_key` of `src_project` to `dest_project`, leaving the original.
+#[tauri::command]
+pub fn copy_note(
+    app: AppHandle,
+    workspace_path: String,
+    src_project: String,
+    tab_key: String,
+    dest_project: String,
+    dest_tab: Option<String>,
+) -> Result<NoteRelocated, CommandError> {
+    relocate_note(&app, &workspace_path, src_project, tab_key, dest_project, dest_tab, false)
+}
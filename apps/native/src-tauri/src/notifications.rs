@@ -0,0 +1,54 @@
+//! Native notifications for reminders, backup completion and sync conflicts.
+//!
+//! A notification can carry a note deep link; clicking it navigates there the
+//! same way an opened `hermes://` URL does.
+
+use tauri::{AppHandle, Url};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{deep_link, focus_main_window};
+
+/// Extra payload key holding the `hermes://` link of the target note.
+const NOTE_LINK_EXTRA: &str = "noteLink";
+const TAP_ACTION: &str = "tap";
+
+/// Routes notification clicks to their note; call once during setup.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    let registered = app.notification().on_action(move |action| {
+        if action.action_id() != TAP_ACTION {
+            return;
+        }
+        let link = action
+            .notification()
+            .and_then(|notification| notification.extra().get(NOTE_LINK_EXTRA))
+            .and_then(|value| value.as_str())
+            .and_then(|link| Url::parse(link).ok());
+        match link {
+            Some(url) => deep_link::handle_urls(&handle, vec![url]),
+            None => focus_main_window(&handle),
+        }
+    });
+    if let Err(err) = registered {
+        tracing::warn!("Failed to register click handler: {err}");
+    }
+}
+
+/// Shows a notification; `target_note` is a note deep link opened when it is clicked.
+pub fn notify(app: &AppHandle, title: &str, body: &str, target_note: Option<&str>) -> Result<(), String> {
+    let mut builder = app.notification().builder().title(title).body(body);
+    if let Some(link) = target_note {
+        let url = Url::parse(link).map_err(|err| format!("Invalid note link {link}: {err}"))?;
+        deep_link::parse(&url)?;
+        builder = builder.extra(NOTE_LINK_EXTRA, link);
+    }
+    builder
+        .show()
+        .map_err(|err| format!("Failed to show notification: {err}"))
+}
+
+/// Lets the frontend raise notifications for events it detects (e.g. reminders).
+#[tauri::command]
+pub fn send_notification(app: AppHandle, title: String, body: String, target_note: Option<String>) -> Result<(), String> {
+    notify(&app, &title, &body, target_note.as_deref())
+}
@@ -0,0 +1,201 @@
+//! Turns OS permission failures into structured errors the UI can act on.
+//!
+//! A bare "Operation not permitted" gives the user nothing to do; instead the
+//! frontend receives the kind of denial, the path, and the System Settings pane
+//! (or Windows equivalent) where access can be granted.
+
+use std::io;
+use std::path::Path;
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+use serde::Serialize;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessIssue {
+    /// macOS privacy protection for Desktop, Documents, Downloads, iCloud Drive or removable volumes.
+    ProtectedFolder,
+    /// macOS blocked access to a location only Full Disk Access covers.
+    FullDiskAccess,
+    /// Windows Controlled folder access blocked an untrusted app.
+    ControlledFolder,
+    /// Plain file-system permissions (POSIX modes or Windows ACLs).
+    AccessDenied,
+    ReadOnly,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessError {
+    pub issue: AccessIssue,
+    pub path: String,
+    pub message: String,
+    /// Steps the UI can show as-is.
+    pub guidance: String,
+    /// Deep link into the relevant settings pane, opened with `open_access_settings`.
+    pub settings_url: Option<String>,
+}
+
+/// Error type for commands that touch workspace files; plain messages serialize as strings.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum CommandError {
+    Access(AccessError),
+    Message(String),
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Message(message)
+    }
+}
+
+impl From<hermes_core::IoError> for CommandError {
+    fn from(err: hermes_core::IoError) -> Self {
+        io_error(err.action, &err.path, err.source)
+    }
+}
+
+impl From<CommandError> for String {
+    fn from(err: CommandError) -> Self {
+        match err {
+            CommandError::Access(access) => access.message,
+            CommandError::Message(message) => message,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+const FILES_AND_FOLDERS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders";
+#[cfg(target_os = "macos")]
+const FULL_DISK_ACCESS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles";
+#[cfg(windows)]
+const CONTROLLED_FOLDER_URL: &str = "windowsdefender://ransomwareprotection";
+
+/// Folders covered by a per-app privacy prompt rather than Full Disk Access.
+#[cfg(target_os = "macos")]
+fn is_protected_folder(path: &Path) -> bool {
+    if path.starts_with("/Volumes") {
+        return true;
+    }
+    let Ok(home) = std::env::var("HOME") else {
+        return false;
+    };
+    let home = Path::new(&home);
+    ["Desktop", "Documents", "Downloads", "Library/Mobile Documents"]
+        .iter()
+        .any(|folder| path.starts_with(home.join(folder)))
+}
+
+/// Windows Controlled folder access guards the user's library folders.
+#[cfg(windows)]
+fn is_controlled_folder(path: &Path) -> bool {
+    let Ok(profile) = std::env::var("USERPROFILE") else {
+        return false;
+    };
+    let profile = Path::new(&profile);
+    ["Desktop", "Documents", "Pictures", "Music", "Videos", "OneDrive"]
+        .iter()
+        .any(|folder| path.starts_with(profile.join(folder)))
+}
+
+fn classify(path: &Path, err: &io::Error) -> Option<(AccessIssue, String, Option<String>)> {
+    if err.kind() == io::ErrorKind::ReadOnlyFilesystem {
+        return Some((
+            AccessIssue::ReadOnly,
+            "The workspace is on a read-only volume. Move it to a writable location or pick another folder.".to_string(),
+            None,
+        ));
+    }
+    if err.kind() != io::ErrorKind::PermissionDenied {
+        return None;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // Privacy (TCC) denials surface as EPERM; ordinary mode bits as EACCES.
+        const EPERM: i32 = 1;
+        if err.raw_os_error() == Some(EPERM) {
+            if is_protected_folder(path) {
+                return Some((
+                    AccessIssue::ProtectedFolder,
+                    "Open System Settings → Privacy & Security → Files and Folders and allow Hermes to access this folder."
+                        .to_string(),
+                    Some(FILES_AND_FOLDERS_URL.to_string()),
+                ));
+            }
+            return Some((
+                AccessIssue::FullDiskAccess,
+                "Open System Settings → Privacy & Security → Full Disk Access, turn on Hermes, then restart it."
+                    .to_string(),
+                Some(FULL_DISK_ACCESS_URL.to_string()),
+            ));
+        }
+    }
+
+    #[cfg(windows)]
+    if is_controlled_folder(path) {
+        return Some((
+            AccessIssue::ControlledFolder,
+            "If Controlled folder access is on, open Windows Security → Ransomware protection → Allow an app through \
+             Controlled folder access and add Hermes. Otherwise check the folder's Security tab in Properties."
+                .to_string(),
+            Some(CONTROLLED_FOLDER_URL.to_string()),
+        ));
+    }
+
+    let _ = path;
+    let guidance = if cfg!(windows) {
+        "Your account lacks permission for this location. Check the folder's Security tab in Properties, or pick another folder."
+    } else {
+        "Your account lacks permission for this location. Check its owner and permissions in Get Info, or pick another folder."
+    };
+    Some((AccessIssue::AccessDenied, guidance.to_string(), None))
+}
+
+/// Wraps an io error from `action` ("reading", "writing", ...) on `path`.
+pub fn io_error(action: &str, path: &Path, err: io::Error) -> CommandError {
+    let message = format!("Failed {action} {}: {err}", path.display());
+    match classify(path, &err) {
+        Some((issue, guidance, settings_url)) => CommandError::Access(AccessError {
+            issue,
+            path: path.to_string_lossy().to_string(),
+            message,
+            guidance,
+            settings_url,
+        }),
+        None => CommandError::Message(message),
+    }
+}
+
+/// Opens a settings pane named in an `AccessError`.
+#[tauri::command]
+pub fn open_access_settings(url: String) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        if url != FILES_AND_FOLDERS_URL && url != FULL_DISK_ACCESS_URL {
+            return Err(format!("Unsupported settings link {url}"));
+        }
+        Command::new("open")
+            .arg(&url)
+            .spawn()
+            .map_err(|err| format!("Failed to open {url}: {err}"))?;
+        return Ok(());
+    }
+
+    #[cfg(windows)]
+    {
+        if url != CONTROLLED_FOLDER_URL {
+            return Err(format!("Unsupported settings link {url}"));
+        }
+        std::process::Command::new("explorer")
+            .arg(&url)
+            .spawn()
+            .map_err(|err| format!("Failed to open {url}: {err}"))?;
+        return Ok(());
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    Err(format!("Unsupported settings link {url}"))
+}
@@ -0,0 +1,398 @@
+//! Plugins: scripts that add commands, react to changes and export notes.
+//!
+//! A plugin is a folder in `<app data>/plugins/<id>/` with a `plugin.json`
+//! manifest naming its script and the permissions it wants: `readNotes`,
+//! `writeNotes` and `network`, the last limited to the `hosts` it lists. The
+//! frontend runs each enabled plugin's script in its own Web Worker, which
+//! can't reach the Tauri API; everything a plugin does goes through the
+//! `plugin_*` commands here, which check what the user granted when enabling
+//! it. A manifest asking for more later gets nothing extra until the plugin
+//! is enabled again. Plugins only see the configured workspace, the same one
+//! the local API serves, and never locked notes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::{export, extract_title, hooks, file_open, note_lock, project_list, read_workspace_files, validate_tab_key};
+use hermes_core::redact::Redaction;
+
+const PLUGINS_DIR: &str = "plugins";
+const MANIFEST_FILE: &str = "plugin.json";
+/// Settings-store key holding `{ [id]: { permissions, hosts } }` for enabled plugins.
+const PLUGINS_SETTING: &str = "plugins";
+const MAX_SCRIPT_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Permission {
+    ReadNotes,
+    WriteNotes,
+    Network,
+}
+
+impl Permission {
+    fn label(self) -> &'static str {
+        match self {
+            Permission::ReadNotes => "read notes",
+            Permission::WriteNotes => "write notes",
+            Permission::Network => "use the network",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    id: String,
+    name: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_main")]
+    main: String,
+    #[serde(default)]
+    permissions: Vec<Permission>,
+    /// Hosts the `network` permission covers, such as `api.example.com`.
+    #[serde(default)]
+    hosts: Vec<String>,
+}
+
+fn default_main() -> String {
+    "index.js".to_string()
+}
+
+/// What the user allowed when enabling a plugin.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Grant {
+    permissions: Vec<Permission>,
+    hosts: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    id: String,
+    name: String,
+    version: String,
+    description: String,
+    /// Permissions and hosts the manifest asks for.
+    permissions: Vec<Permission>,
+    hosts: Vec<String>,
+    enabled: bool,
+    /// What was granted, which may be less than the manifest now asks for.
+    granted: Vec<Permission>,
+    granted_hosts: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginNote {
+    project: String,
+    tab: String,
+    title: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchResponse {
+    status: u16,
+    body: String,
+}
+
+fn plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(PLUGINS_DIR))
+        .map_err(|err| format!("Failed resolving app data directory: {err}"))
+}
+
+fn check_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > 64 || !id.chars().all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-') {
+        return Err(format!("Invalid plugin ID '{id}'; use lowercase letters, digits and dashes."));
+    }
+    Ok(())
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, String> {
+    let path = dir.join(MANIFEST_FILE);
+    let json = fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+    let manifest: Manifest =
+        serde_json::from_str(&json).map_err(|err| format!("Failed parsing {}: {err}", path.display()))?;
+    check_id(&manifest.id)?;
+    if dir.file_name().is_none_or(|name| name != manifest.id.as_str()) {
+        return Err(format!("Plugin '{}' must be in a folder named after its ID.", manifest.id));
+    }
+    Ok(manifest)
+}
+
+/// Installed plugins with valid manifests, by ID.
+fn manifests(app: &AppHandle) -> Vec<Manifest> {
+    let Ok(dir) = plugins_dir(app) else {
+        return Vec::new();
+    };
+    let mut manifests: Vec<Manifest> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .filter_map(|path| {
+            read_manifest(&path)
+                .map_err(|err| tracing::warn!("Skipping plugin: {err}"))
+                .ok()
+        })
+        .collect();
+    manifests.sort_by(|a, b| a.id.cmp(&b.id));
+    manifests
+}
+
+fn manifest(app: &AppHandle, id: &str) -> Result<Manifest, String> {
+    check_id(id)?;
+    read_manifest(&plugins_dir(app)?.join(id))
+}
+
+fn grants(app: &AppHandle) -> HashMap<String, Grant> {
+    crate::read_setting(app, PLUGINS_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Fails unless `plugin` is enabled with `permission`.
+fn require(app: &AppHandle, plugin: &str, permission: Permission) -> Result<Grant, String> {
+    let grant = grants(app)
+        .remove(plugin)
+        .ok_or_else(|| format!("Plugin '{plugin}' is not enabled."))?;
+    if !grant.permissions.contains(&permission) {
+        return Err(format!("Plugin '{plugin}' is not allowed to {}.", permission.label()));
+    }
+    crate::app_lock::ensure_unlocked(app)?;
+    Ok(grant)
+}
+
+/// The folder of `project` in the configured workspace; the empty project is the root.
+fn folder_of(app: &AppHandle, project: &str) -> Result<String, String> {
+    let workspace = file_open::configured_workspace(app).ok_or_else(|| "No workspace is configured.".to_string())?;
+    if project.is_empty() {
+        return Ok(workspace.to_string_lossy().to_string());
+    }
+    project_list::check_project(project)?;
+    let folder = workspace.join(project);
+    if !folder.is_dir() {
+        return Err(format!("Project '{project}' does not exist."));
+    }
+    Ok(folder.to_string_lossy().to_string())
+}
+
+/// The notes of `folder`, with edits still waiting in a window saved first.
+fn current_pages(app: &AppHandle, folder: &str) -> Result<HashMap<String, String>, String> {
+    crate::autosave::flush(app, Some(folder)).map_err(String::from)?;
+    read_workspace_files(folder).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn list_plugins(app: AppHandle) -> Vec<PluginInfo> {
+    let mut grants = grants(&app);
+    manifests(&app)
+        .into_iter()
+        .map(|manifest| {
+            let grant = grants.remove(&manifest.id);
+            PluginInfo {
+                enabled: grant.is_some(),
+                granted: grant.as_ref().map(|grant| grant.permissions.clone()).unwrap_or_default(),
+                granted_hosts: grant.map(|grant| grant.hosts).unwrap_or_default(),
+                id: manifest.id,
+                name: manifest.name,
+                version: manifest.version,
+                description: manifest.description,
+                permissions: manifest.permissions,
+                hosts: manifest.hosts,
+            }
+        })
+        .collect()
+}
+
+/// Enables a plugin with the permissions its manifest asks for now, or
+/// disables it and forgets what it was granted.
+#[tauri::command]
+pub fn set_plugin_enabled(app: AppHandle, id: String, enabled: bool) -> Result<(), String> {
+    let mut grants = grants(&app);
+    if enabled {
+        let manifest = manifest(&app, &id)?;
+        let hosts = if manifest.permissions.contains(&Permission::Network) {
+            manifest.hosts.iter().map(|host| host.trim().to_ascii_lowercase()).collect()
+        } else {
+            Vec::new()
+        };
+        grants.insert(id, Grant { permissions: manifest.permissions, hosts });
+    } else {
+        grants.remove(&id);
+    }
+    crate::write_setting(&app, PLUGINS_SETTING, json!(grants))
+}
+
+/// The script of an enabled plugin, for the frontend to run in a worker.
+#[tauri::command]
+pub fn load_plugin(app: AppHandle, id: String) -> Result<String, String> {
+    if !grants(&app).contains_key(&id) {
+        return Err(format!("Plugin '{id}' is not enabled."));
+    }
+    let manifest = manifest(&app, &id)?;
+    let main = Path::new(&manifest.main);
+    if main.is_absolute() || main.components().any(|part| !matches!(part, std::path::Component::Normal(_))) {
+        return Err(format!("Plugin '{id}' names a script outside its folder."));
+    }
+    let path = plugins_dir(&app)?.join(&id).join(main);
+    let size = fs::metadata(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?.len();
+    if size > MAX_SCRIPT_BYTES {
+        return Err(format!("The script of plugin '{id}' is too large."));
+    }
+    fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))
+}
+
+/// Notes of `project`, or of every project when `project` is `None`.
+#[tauri::command]
+pub fn plugin_list_notes(app: AppHandle, plugin: String, project: Option<String>) -> Result<Vec<PluginNote>, String> {
+    require(&app, &plugin, Permission::ReadNotes)?;
+    let projects = match project {
+        Some(project) => vec![project],
+        None => {
+            let workspace = folder_of(&app, "")?;
+            let mut projects = vec![String::new()];
+            projects.extend(crate::read_project_names(&workspace).map_err(String::from)?);
+            projects
+        }
+    };
+    let mut notes = Vec::new();
+    for project in projects {
+        let folder = folder_of(&app, &project)?;
+        let mut pages: Vec<(String, String)> = current_pages(&app, &folder)?.into_iter().collect();
+        pages.sort_by(|a, b| a.0.cmp(&b.0));
+        for (tab, content) in pages {
+            let title = if note_lock::is_locked(&content) { String::new() } else { extract_title(&content) };
+            notes.push(PluginNote { project: project.clone(), tab, title });
+        }
+    }
+    Ok(notes)
+}
+
+#[tauri::command]
+pub fn plugin_read_note(app: AppHandle, plugin: String, project: String, tab: String) -> Result<String, String> {
+    require(&app, &plugin, Permission::ReadNotes)?;
+    validate_tab_key(&tab)?;
+    let folder = folder_of(&app, &project)?;
+    let content = current_pages(&app, &folder)?.remove(&tab).unwrap_or_default();
+    if note_lock::is_locked(&content) {
+        return Err("The note is locked.".to_string());
+    }
+    Ok(content)
+}
+
+/// Replaces a note through the save queue, like an edit in the editor.
+#[tauri::command]
+pub fn plugin_write_note(app: AppHandle, plugin: String, project: String, tab: String, content: String) -> Result<(), String> {
+    require(&app, &plugin, Permission::WriteNotes)?;
+    validate_tab_key(&tab)?;
+    let folder = folder_of(&app, &project)?;
+    let existing = current_pages(&app, &folder)?.remove(&tab).unwrap_or_default();
+    if note_lock::is_locked(&existing) {
+        return Err("The note is locked.".to_string());
+    }
+    crate::read_only::ensure_writable(&app, &folder).map_err(String::from)?;
+    crate::save_queue::save(&app, &folder, HashMap::from([(tab, content)])).map_err(String::from)
+}
+
+/// An HTTPS request to one of the hosts the plugin was granted.
+#[tauri::command]
+pub async fn plugin_fetch(
+    app: AppHandle,
+    plugin: String,
+    url: String,
+    method: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    body: Option<String>,
+) -> Result<FetchResponse, String> {
+    let grant = require(&app, &plugin, Permission::Network)?;
+    let url = reqwest::Url::parse(&url).map_err(|err| format!("Invalid URL '{url}': {err}"))?;
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    if url.scheme() != "https" || !grant.hosts.contains(&host) {
+        return Err(format!("Plugin '{plugin}' is not allowed to reach {url}."));
+    }
+    let method = reqwest::Method::from_bytes(method.as_deref().unwrap_or("GET").to_ascii_uppercase().as_bytes())
+        .map_err(|err| format!("Invalid method: {err}"))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        // reqwest is built without a bundled TLS provider; reuse the updater's ring backend.
+        if rustls::crypto::CryptoProvider::get_default().is_none() {
+            let _ = rustls::crypto::ring::default_provider().install_default();
+        }
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            // A redirect could leave the granted hosts.
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|err| format!("Failed creating HTTP client: {err}"))?;
+        let mut request = client.request(method, url);
+        for (name, value) in headers.unwrap_or_default() {
+            request = request.header(name, value);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let mut response = request.send().map_err(|err| format!("Plugin request failed: {err}"))?;
+        let status = response.status().as_u16();
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::Read::take(&mut response, MAX_RESPONSE_BYTES as u64 + 1), &mut bytes)
+            .map_err(|err| format!("Failed reading plugin response: {err}"))?;
+        if bytes.len() > MAX_RESPONSE_BYTES {
+            return Err("The response is too large.".to_string());
+        }
+        Ok(FetchResponse { status, body: String::from_utf8_lossy(&bytes).to_string() })
+    })
+    .await
+    .map_err(|err| format!("Plugin request task failed: {err}"))?
+}
+
+/// The redacted markdown a plugin exporter starts from. Exporting is started
+/// by the user on a note they chose, so it needs no permission.
+#[tauri::command]
+pub fn plugin_export_source(
+    app: AppHandle,
+    plugin: String,
+    project: String,
+    tab: String,
+    redaction: Option<Redaction>,
+) -> Result<String, String> {
+    if !grants(&app).contains_key(&plugin) {
+        return Err(format!("Plugin '{plugin}' is not enabled."));
+    }
+    let folder = folder_of(&app, &project)?;
+    crate::autosave::flush(&app, Some(&folder)).map_err(String::from)?;
+    export::render_note(&folder, &tab, "markdown", redaction.unwrap_or_default())
+}
+
+/// Writes what a plugin exporter made of a note to `destination`, then runs
+/// the export hooks; returns the path that was written.
+#[tauri::command]
+pub fn save_plugin_export(
+    app: AppHandle,
+    plugin: String,
+    project: String,
+    tab: String,
+    destination: String,
+    contents: String,
+) -> Result<String, String> {
+    if !grants(&app).contains_key(&plugin) {
+        return Err(format!("Plugin '{plugin}' is not enabled."));
+    }
+    let folder = folder_of(&app, &project)?;
+    let written = export::write(Path::new(&destination), &contents)?;
+    hooks::fire(&app, hooks::Context { event: hooks::HookEvent::Export, folder: &folder, tab: &tab, export_path: Some(&written) });
+    Ok(written)
+}
@@ -0,0 +1,75 @@
+//! Writing unsaved edits before the system goes to sleep.
+//!
+//! A laptop can run out of battery while asleep, taking edits still waiting on
+//! autosave with it. macOS posts `NSWorkspaceWillSleepNotification` and Windows
+//! calls back through `PowerRegisterSuspendResumeNotification`; both flush
+//! autosave. Linux would need a logind inhibitor over D-Bus, which the build
+//! doesn't include, so there the regular autosave timing has to do.
+
+use tauri::AppHandle;
+
+#[cfg(any(target_os = "macos", windows))]
+fn flush_before_sleep(app: &AppHandle) {
+    tracing::info!("System is going to sleep; writing unsaved edits");
+    if let Err(err) = crate::autosave::flush(app, None) {
+        tracing::warn!("{}", String::from(err));
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn init(app: &AppHandle) {
+    use std::ptr::NonNull;
+
+    use block2::RcBlock;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceWillSleepNotification};
+    use objc2_foundation::NSNotification;
+
+    let app = app.clone();
+    let block = RcBlock::new(move |_: NonNull<NSNotification>| flush_before_sleep(&app));
+    let center = NSWorkspace::sharedWorkspace().notificationCenter();
+    let observer = unsafe {
+        center.addObserverForName_object_queue_usingBlock(Some(NSWorkspaceWillSleepNotification), None, None, &block)
+    };
+    // Observes for the rest of the process.
+    std::mem::forget(observer);
+}
+
+#[cfg(windows)]
+pub fn init(app: &AppHandle) {
+    use std::ffi::c_void;
+
+    use windows::Win32::Foundation::{ERROR_SUCCESS, HANDLE};
+    use windows::Win32::System::Power::{PowerRegisterSuspendResumeNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS};
+    use windows::Win32::UI::WindowsAndMessaging::{DEVICE_NOTIFY_CALLBACK, PBT_APMSUSPEND};
+
+    unsafe extern "system" fn on_power_change(context: *const c_void, kind: u32, _setting: *const c_void) -> u32 {
+        if kind == PBT_APMSUSPEND {
+            let app = unsafe { &*(context as *const AppHandle) };
+            flush_before_sleep(app);
+        }
+        ERROR_SUCCESS.0
+    }
+
+    // Windows calls back with both for the rest of the process.
+    let context = Box::into_raw(Box::new(app.clone()));
+    let parameters = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+        Callback: Some(on_power_change),
+        Context: context.cast(),
+    }));
+    let mut registration = std::ptr::null_mut();
+    let status = unsafe {
+        PowerRegisterSuspendResumeNotification(
+            DEVICE_NOTIFY_CALLBACK,
+            HANDLE((parameters as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS).cast()),
+            &mut registration,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        tracing::warn!("Failed to watch for sleep: error {}", status.0);
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn init(app: &AppHandle) {
+    let _ = app;
+}
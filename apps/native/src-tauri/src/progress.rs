@@ -0,0 +1,69 @@
+//! Progress of long-running jobs, broadcast as `operation://progress` events.
+//!
+//! Commands that start a bulk job take an optional `operation_id` so the
+//! frontend can match events to the bar it shows; background jobs make up
+//! their own. Every job ends with a `done` phase where `done == total`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+pub const OPERATION_PROGRESS_EVENT: &str = "operation://progress";
+
+static NEXT_OPERATION: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationProgress {
+    operation_id: String,
+    /// What kind of job this is: `export`, `import`, `reindex`, `backup`, `merge` or `download`.
+    kind: &'static str,
+    /// The step the job is on, such as `logs` or `indexing`.
+    phase: String,
+    done: usize,
+    total: usize,
+}
+
+/// Reports the progress of one job.
+pub struct Progress {
+    app: AppHandle,
+    operation_id: String,
+    kind: &'static str,
+}
+
+impl Progress {
+    /// Starts reporting for a job of `kind`; without an `operation_id` one is made up.
+    pub fn new(app: &AppHandle, kind: &'static str, operation_id: Option<String>) -> Progress {
+        let operation_id = operation_id.unwrap_or_else(|| {
+            let now_millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis())
+                .unwrap_or(0);
+            format!("{kind}-{now_millis}-{}", NEXT_OPERATION.fetch_add(1, Ordering::Relaxed))
+        });
+        Progress {
+            app: app.clone(),
+            operation_id,
+            kind,
+        }
+    }
+
+    pub fn report(&self, phase: &str, done: usize, total: usize) {
+        let payload = OperationProgress {
+            operation_id: self.operation_id.clone(),
+            kind: self.kind,
+            phase: phase.to_string(),
+            done,
+            total,
+        };
+        if let Err(err) = self.app.emit(OPERATION_PROGRESS_EVENT, payload) {
+            tracing::warn!("Failed to emit event: {err}");
+        }
+    }
+
+    pub fn finish(&self, total: usize) {
+        self.report("done", total, total);
+    }
+}
@@ -0,0 +1,271 @@
+//! Cached, paginated project listing for workspaces with thousands of folders.
+//!
+//! Projects are found by `hermes_core::projects`. Hermes has no file
+//! watcher, so the modification times of the folders walked stand in for one:
+//! adding, removing or renaming a project changes one of them, and the cached
+//! listing is read again. Per-project note counts and last-modified times come
+//! from each project's index, only for the page asked for. `list_project_tree`
+//! returns the projects as a tree; `discover_projects` walks every folder.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use hermes_core::projects::{MAX_PROJECT_DEPTH, SKIPPED_FOLDERS};
+pub use hermes_core::projects::{check_project, in_scope, is_subproject, project_of, register_root, walk_projects};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::ignore::FolderRules;
+use crate::project_meta::{self, ProjectMeta};
+use crate::{note_path, run_sqlite_query, sqlite_path, CommandError, TAB_KEYS};
+
+const DEFAULT_DISCOVERY_DEPTH: usize = 4;
+const MAX_DISCOVERY_DEPTH: usize = MAX_PROJECT_DEPTH;
+
+struct CachedListing {
+    /// Every folder walked for the listing, with its modification time then.
+    folders: Vec<(PathBuf, SystemTime)>,
+    names: Vec<String>,
+}
+
+/// Project names per workspace, with the folder time they were read at.
+#[derive(Default)]
+pub struct ProjectListCache(Mutex<HashMap<String, CachedListing>>);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSummary {
+    /// The project's path below the workspace root.
+    name: String,
+    /// `None` until the project has been opened and indexed.
+    note_count: Option<u64>,
+    last_modified_unix: Option<u64>,
+    #[serde(flatten)]
+    meta: ProjectMeta,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectPage {
+    projects: Vec<ProjectSummary>,
+    total: usize,
+    /// Offset of the next page; `None` after the last one.
+    next_offset: Option<usize>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Project paths of `workspace_path`, sorted, read from disk only when a folder changed.
+pub fn names(app: &AppHandle, workspace_path: &str) -> Result<Vec<String>, CommandError> {
+    register_root(workspace_path);
+    let state = app.state::<ProjectListCache>();
+    if let Some(cached) = state.0.lock().unwrap().get(workspace_path) {
+        if cached.folders.iter().all(|(folder, time)| modified(folder) == Some(*time)) {
+            return Ok(cached.names.clone());
+        }
+    }
+    let (names, walked) = walk_projects(workspace_path)?;
+    let folders = walked
+        .into_iter()
+        .filter_map(|folder| modified(&folder).map(|time| (folder, time)))
+        .collect();
+    state.0.lock().unwrap().insert(
+        workspace_path.to_string(),
+        CachedListing {
+            folders,
+            names: names.clone(),
+        },
+    );
+    Ok(names)
+}
+
+/// Slices `items` to the requested page; no `limit` means everything from `offset`.
+pub fn page<T: Clone>(items: &[T], offset: Option<usize>, limit: Option<usize>) -> (Vec<T>, Option<usize>) {
+    let start = offset.unwrap_or(0).min(items.len());
+    let end = limit.map_or(items.len(), |limit| start.saturating_add(limit).min(items.len()));
+    let next = (end < items.len()).then_some(end);
+    (items[start..end].to_vec(), next)
+}
+
+/// The projects of `workspace_path` as the sidebar lists them, without the
+/// archived ones unless `include_archived`.
+pub fn listed(app: &AppHandle, workspace_path: &str, include_archived: bool) -> Result<Vec<String>, CommandError> {
+    let names = names(app, workspace_path)?;
+    if include_archived {
+        return Ok(names);
+    }
+    let metas = project_meta::read(workspace_path);
+    Ok(names.into_iter().filter(|name| !project_meta::is_archived(&metas, name)).collect())
+}
+
+fn summary(workspace_path: &str, name: &str, metas: &BTreeMap<String, ProjectMeta>) -> ProjectSummary {
+    let db_path = sqlite_path(&Path::new(workspace_path).join(name).to_string_lossy());
+    let stats = if db_path.exists() {
+        run_sqlite_query(
+            &db_path,
+            "SELECT COUNT(*) AS note_count, MAX(updated_unix) AS last_modified_unix FROM note_index;",
+        )
+        .ok()
+        .and_then(|rows| rows.into_iter().next())
+    } else {
+        None
+    };
+    let stat = |column: &str| stats.as_ref().and_then(|row| row.get(column)).and_then(|value| value.as_u64());
+    ProjectSummary {
+        name: name.to_string(),
+        note_count: stat("note_count"),
+        last_modified_unix: stat("last_modified_unix"),
+        meta: project_meta::of(metas, name),
+    }
+}
+
+/// One page of projects with their note counts and last edit from the index,
+/// and their icon, color and archived flag.
+#[tauri::command]
+pub async fn list_workspace_projects_page(
+    app: AppHandle,
+    workspace_path: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+) -> Result<ProjectPage, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let names = listed(&app, &workspace_path, include_archived.unwrap_or(false))?;
+        let metas = project_meta::read(&workspace_path);
+        let (page_names, next_offset) = page(&names, offset, limit);
+        Ok(ProjectPage {
+            projects: page_names.iter().map(|name| summary(&workspace_path, name, &metas)).collect(),
+            total: names.len(),
+            next_offset,
+        })
+    })
+    .await
+    .map_err(|err| CommandError::from(format!("Project listing task failed: {err}")))?
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTreeNode {
+    /// The path below the parent project, usually one folder name.
+    label: String,
+    #[serde(flatten)]
+    summary: ProjectSummary,
+    children: Vec<ProjectTreeNode>,
+}
+
+/// The nearest project `name` is a sub-project of, or `""` for a top-level one.
+fn parent_project<'a>(name: &str, names: &'a [String]) -> &'a str {
+    names
+        .iter()
+        .filter(|other| other.as_str() != name && in_scope(name, other))
+        .max_by_key(|other| other.len())
+        .map_or("", String::as_str)
+}
+
+fn tree(
+    workspace_path: &str,
+    names: &[String],
+    parents: &HashMap<&str, &str>,
+    metas: &BTreeMap<String, ProjectMeta>,
+    parent: &str,
+) -> Vec<ProjectTreeNode> {
+    names
+        .iter()
+        .filter(|name| parents.get(name.as_str()) == Some(&parent))
+        .map(|name| ProjectTreeNode {
+            label: name.strip_prefix(&format!("{parent}/")).unwrap_or(name).to_string(),
+            summary: summary(workspace_path, name, metas),
+            children: tree(workspace_path, names, parents, metas, name),
+        })
+        .collect()
+}
+
+/// Projects as a tree of sub-projects, with their note counts, last edit and
+/// metadata; archived projects only with `include_archived`.
+#[tauri::command]
+pub async fn list_project_tree(
+    app: AppHandle,
+    workspace_path: String,
+    include_archived: Option<bool>,
+) -> Result<Vec<ProjectTreeNode>, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let names = listed(&app, &workspace_path, include_archived.unwrap_or(false))?;
+        let metas = project_meta::read(&workspace_path);
+        let parents: HashMap<&str, &str> = names.iter().map(|name| (name.as_str(), parent_project(name, &names))).collect();
+        Ok(tree(&workspace_path, &names, &parents, &metas, ""))
+    })
+    .await
+    .map_err(|err| CommandError::from(format!("Project listing task failed: {err}")))?
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectNode {
+    name: String,
+    /// Path below the workspace, with `/` separators.
+    path: String,
+    /// Whether the folder holds any tab files itself.
+    has_notes: bool,
+    children: Vec<ProjectNode>,
+}
+
+fn walk(dir: &Path, relative: &str, depth: usize, max_depth: usize, rules: &mut Vec<FolderRules>) -> Vec<ProjectNode> {
+    let pushed = match FolderRules::read(dir) {
+        Some(folder_rules) => {
+            rules.push(folder_rules);
+            true
+        }
+        None => false,
+    };
+
+    let mut nodes = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        // Symlinked folders are skipped so a link cycle can't recurse forever.
+        if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if name.starts_with('.') || SKIPPED_FOLDERS.contains(&name.as_str()) || rules.iter().any(|rules| rules.excludes_dir(&path)) {
+            continue;
+        }
+        let child_relative = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{relative}/{name}")
+        };
+        let children = if depth < max_depth {
+            walk(&path, &child_relative, depth + 1, max_depth, rules)
+        } else {
+            Vec::new()
+        };
+        let folder = path.to_string_lossy();
+        nodes.push(ProjectNode {
+            has_notes: TAB_KEYS.iter().any(|tab| note_path(&folder, tab).exists()),
+            name,
+            path: child_relative,
+            children,
+        });
+    }
+
+    if pushed {
+        rules.pop();
+    }
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+    nodes
+}
+
+/// Project folders of a workspace as a tree, `max_depth` levels deep, honoring
+/// `.gitignore` and `.hermesignore` at every level.
+#[tauri::command]
+pub async fn discover_projects(workspace_path: String, max_depth: Option<usize>) -> Result<Vec<ProjectNode>, String> {
+    let max_depth = max_depth.unwrap_or(DEFAULT_DISCOVERY_DEPTH).clamp(1, MAX_DISCOVERY_DEPTH);
+    tauri::async_runtime::spawn_blocking(move || walk(Path::new(&workspace_path), "", 1, max_depth, &mut Vec::new()))
+        .await
+        .map_err(|err| format!("Project discovery task failed: {err}"))
+}
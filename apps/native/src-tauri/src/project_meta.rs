@@ -0,0 +1,190 @@
+//! Per-project icon, accent color and archived flag.
+//!
+//! They live in the workspace's `.hermes/projects.json`, keyed by project
+//! path, so they travel with the workspace folder. An archived project keeps
+//! its files but drops out of the project lists, and so do its sub-projects,
+//! unless a listing asks for archived projects too.
+//! `PROJECT_META_EVENT` tells every window when the metadata changes.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::{canonical_workspace, changes, hermes_dir, project_list, workspace_meta, CommandError};
+
+pub const PROJECT_META_EVENT: &str = "workspace://project-meta";
+
+const META_FILE: &str = "projects.json";
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectMeta {
+    /// An emoji or a short symbol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    /// `#rrggbb`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectMetaChanged {
+    workspace_path: String,
+    project: String,
+    meta: ProjectMeta,
+}
+
+impl ProjectMeta {
+    fn is_empty(&self) -> bool {
+        self.icon.is_none() && self.color.is_none() && !self.archived
+    }
+
+    /// Trims the icon and color and drops them when empty.
+    fn normalize(self) -> ProjectMeta {
+        let clean = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        ProjectMeta {
+            icon: clean(self.icon),
+            color: clean(self.color).map(|color| color.to_ascii_lowercase()),
+            archived: self.archived,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(icon) = &self.icon {
+            workspace_meta::validate_icon(icon)?;
+        }
+        if let Some(color) = &self.color {
+            workspace_meta::validate_color(color)?;
+        }
+        Ok(())
+    }
+}
+
+fn meta_path(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join(META_FILE)
+}
+
+/// The metadata of every project of `workspace_path` that has some.
+pub fn read(workspace_path: &str) -> BTreeMap<String, ProjectMeta> {
+    let path = meta_path(workspace_path);
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            tracing::warn!("Failed parsing {}: {err}", path.display());
+            BTreeMap::new()
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(err) => {
+            tracing::warn!("Failed reading {}: {err}", path.display());
+            BTreeMap::new()
+        }
+    }
+}
+
+fn write(workspace_path: &str, metas: &BTreeMap<String, ProjectMeta>) -> Result<(), String> {
+    let path = meta_path(workspace_path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(metas).map_err(|err| format!("Failed encoding project metadata: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+/// Whether `project` or a project it belongs to is archived.
+pub fn is_archived(metas: &BTreeMap<String, ProjectMeta>, project: &str) -> bool {
+    metas
+        .iter()
+        .any(|(name, meta)| meta.archived && project_list::in_scope(project, name))
+}
+
+/// The metadata of `project`, empty when it has none.
+pub fn of(metas: &BTreeMap<String, ProjectMeta>, project: &str) -> ProjectMeta {
+    metas.get(project).cloned().unwrap_or_default()
+}
+
+/// Drops the metadata of `project` and its sub-projects, as when its folder goes to the Bin.
+#[cfg(target_os = "macos")]
+pub fn forget(workspace_path: &str, project: &str) {
+    let mut metas = read(workspace_path);
+    let before = metas.len();
+    metas.retain(|name, _| !project_list::in_scope(name, project));
+    if metas.len() != before {
+        if let Err(err) = write(workspace_path, &metas) {
+            tracing::warn!("{}", err);
+        }
+    }
+}
+
+fn update(
+    app: &AppHandle,
+    workspace_path: &str,
+    project: String,
+    change: impl FnOnce(&mut ProjectMeta),
+) -> Result<ProjectMeta, CommandError> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    project_list::check_project(&project)?;
+    if !Path::new(&workspace_path).join(&project).is_dir() {
+        return Err(format!("Project '{project}' does not exist in {workspace_path}").into());
+    }
+    crate::read_only::ensure_writable(app, &workspace_path)?;
+
+    let mut metas = read(&workspace_path);
+    let mut meta = of(&metas, &project);
+    change(&mut meta);
+    let meta = meta.normalize();
+    meta.validate()?;
+    if meta.is_empty() {
+        metas.remove(&project);
+    } else {
+        metas.insert(project.clone(), meta.clone());
+    }
+    write(&workspace_path, &metas)?;
+
+    let change = changes::Change::ProjectMetaChanged {
+        workspace_path: workspace_path.clone(),
+        project: project.clone(),
+    };
+    changes::emit(app, change);
+    let payload = ProjectMetaChanged {
+        workspace_path,
+        project,
+        meta: meta.clone(),
+    };
+    if let Err(err) = app.emit(PROJECT_META_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(meta)
+}
+
+/// Metadata of the projects of `workspace_path`, keyed by project path.
+#[tauri::command]
+pub fn get_project_meta(workspace_path: String) -> BTreeMap<String, ProjectMeta> {
+    read(&canonical_workspace(&workspace_path))
+}
+
+/// Sets the icon and color of `project`, keeping whether it is archived; empty fields are removed.
+#[tauri::command]
+pub fn set_project_meta(
+    app: AppHandle,
+    workspace_path: String,
+    project: String,
+    meta: ProjectMeta,
+) -> Result<ProjectMeta, CommandError> {
+    update(&app, &workspace_path, project, |current| {
+        current.icon = meta.icon;
+        current.color = meta.color;
+    })
+}
+
+/// Archives `project`, or brings it back with `archived` false; its files are left alone.
+#[tauri::command]
+pub fn archive_project(app: AppHandle, workspace_path: String, project: String, archived: bool) -> Result<ProjectMeta, CommandError> {
+    let meta = update(&app, &workspace_path, project.clone(), |current| current.archived = archived)?;
+    tracing::info!("{} project '{project}'", if archived { "Archived" } else { "Unarchived" });
+    Ok(meta)
+}
@@ -0,0 +1,130 @@
+//! Additional editor windows, each pinned to one project of a workspace.
+//!
+//! All editor windows share the single sidecar; it is only stopped once the
+//! last of them has been destroyed.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+
+#[cfg(desktop)]
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+#[cfg(desktop)]
+use tauri::{WebviewUrl, WebviewWindowBuilder};
+
+use crate::file_open;
+
+const PROJECT_WINDOW_PREFIX: &str = "project-";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowContext {
+    pub workspace_path: String,
+    pub project: String,
+}
+
+/// Workspace/project each project window was opened for, keyed by window label.
+#[derive(Default)]
+pub struct WindowContexts(pub Mutex<HashMap<String, WindowContext>>);
+
+/// Windows that edit notes (as opposed to capture/scratchpad utilities).
+pub fn is_editor_window(label: &str) -> bool {
+    label == "main" || label.starts_with(PROJECT_WINDOW_PREFIX)
+}
+
+/// Stable, label-safe identifier for a project path.
+fn window_label(workspace_path: &str, project: &str) -> String {
+    let slug: String = project
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch.to_ascii_lowercase() } else { '-' })
+        .take(32)
+        .collect();
+    let mut hasher = DefaultHasher::new();
+    (workspace_path, project).hash(&mut hasher);
+    format!("{PROJECT_WINDOW_PREFIX}{slug}-{:08x}", hasher.finish() as u32)
+}
+
+/// Drops the context of a destroyed window and reports whether any editor window remains.
+pub fn window_destroyed(app: &AppHandle, label: &str) -> bool {
+    app.state::<WindowContexts>().0.lock().unwrap().remove(label);
+    app.webview_windows()
+        .keys()
+        .any(|other| other != label && is_editor_window(other))
+}
+
+/// Follows workspaces moved from under `old_path` to `new_path`.
+pub fn relocate(app: &AppHandle, old_path: &str, new_path: &str) {
+    for context in app.state::<WindowContexts>().0.lock().unwrap().values_mut() {
+        if let Some(rebased) = crate::workspace_move::rebase(&context.workspace_path, old_path, new_path) {
+            context.workspace_path = rebased;
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_project_window(
+    app: AppHandle,
+    state: tauri::State<'_, WindowContexts>,
+    workspace_path: Option<String>,
+    project: String,
+) -> Result<String, String> {
+    let workspace_path = match workspace_path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => path,
+        None => file_open::configured_workspace(&app)
+            .map(|path| path.to_string_lossy().to_string())
+            .ok_or_else(|| "No workspace is configured.".to_string())?,
+    };
+
+    crate::project_list::check_project(&project)?;
+    if !Path::new(&workspace_path).join(&project).is_dir() {
+        return Err(format!("Project '{project}' does not exist in {workspace_path}"));
+    }
+
+    let label = window_label(&workspace_path, &project);
+    if let Some(window) = app.get_webview_window(&label) {
+        #[cfg(desktop)]
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(label);
+    }
+
+    #[cfg(desktop)]
+    {
+        let url = format!(
+            "index.html?window=project&project={}",
+            utf8_percent_encode(&project, NON_ALPHANUMERIC)
+        );
+        WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(url.into()))
+            .title(format!("{} — {project}", crate::workspace_meta::label(&workspace_path)))
+            .inner_size(1200.0, 800.0)
+            .min_inner_size(640.0, 480.0)
+            .build()
+            .map_err(|err| format!("Failed to open project window: {err}"))?;
+
+        state
+            .0
+            .lock()
+            .unwrap()
+            .insert(label.clone(), WindowContext { workspace_path, project });
+        Ok(label)
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = (state, workspace_path);
+        Err("Multiple windows are only available on desktop.".to_string())
+    }
+}
+
+/// The project a window was opened for; `None` for the main window.
+#[tauri::command]
+pub fn get_window_context(
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, WindowContexts>,
+) -> Option<WindowContext> {
+    state.0.lock().unwrap().get(window.label()).cloned()
+}
@@ -0,0 +1,175 @@
+//! Workspaces Hermes opens without writing to them.
+//!
+//! A workspace is read-only when the user marks it so, which is kept on this
+//! machine rather than in the folder, or when its folder can't be written,
+//! found by creating and removing a probe file at most every `RECHECK_AFTER`.
+//! Every command that writes notes, and LAN sync, refuses with an
+//! `AccessError` whose issue is `read-only`; loading and the index still work,
+//! so the editor keeps up with changes made elsewhere. `WORKSPACE_READ_ONLY_EVENT`
+//! tells the frontend when the flag changes so it can show or drop its banner.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::permissions::{AccessError, AccessIssue};
+use crate::{canonical_workspace, CommandError};
+
+pub const WORKSPACE_READ_ONLY_EVENT: &str = "workspace://read-only";
+
+const READ_ONLY_SETTING: &str = "readOnlyWorkspaces";
+const PROBE_FILE: &str = ".hermes-write-probe";
+const RECHECK_AFTER: Duration = Duration::from_secs(300);
+
+/// Whether each probed folder could be written, and when that was checked.
+#[derive(Default)]
+pub struct WritableFolders(Mutex<HashMap<String, (bool, Instant)>>);
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadOnlyReason {
+    /// The user marked the workspace read-only.
+    Marked,
+    /// The folder can't be written.
+    NotWritable,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceAccess {
+    workspace_path: String,
+    read_only: bool,
+    reason: Option<ReadOnlyReason>,
+}
+
+fn marked(app: &AppHandle) -> Vec<String> {
+    crate::read_setting(app, READ_ONLY_SETTING)
+        .and_then(|value| value.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Whether the user marked `workspace_path`, or the workspace it is a project of, read-only.
+pub fn is_marked(app: &AppHandle, workspace_path: &str) -> bool {
+    let workspace_path = canonical_workspace(workspace_path);
+    marked(app).iter().any(|root| Path::new(&workspace_path).starts_with(root))
+}
+
+/// Keeps the marks of workspaces moved from under `old_path` to `new_path`.
+pub fn relocate(app: &AppHandle, old_path: &str, new_path: &str) {
+    let paths: Vec<String> = marked(app)
+        .into_iter()
+        .map(|path| crate::workspace_move::rebase(&path, old_path, new_path).unwrap_or(path))
+        .collect();
+    if let Err(err) = crate::write_setting(app, READ_ONLY_SETTING, Value::from(paths)) {
+        tracing::warn!("{}", err);
+    }
+}
+
+/// Tries creating a file in the nearest existing folder at or above `path`.
+fn probe(path: &Path) -> bool {
+    let Some(dir) = path.ancestors().find(|dir| dir.is_dir()) else {
+        return true;
+    };
+    let probe = crate::notes_dir(&dir.to_string_lossy()).join(format!("{PROBE_FILE}-{}", std::process::id()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => true,
+        Err(_) => false,
+    }
+}
+
+fn is_writable(app: &AppHandle, workspace_path: &str) -> bool {
+    let state = app.state::<WritableFolders>();
+    if let Some((writable, checked)) = state.0.lock().unwrap().get(workspace_path) {
+        if checked.elapsed() < RECHECK_AFTER {
+            return *writable;
+        }
+    }
+    let writable = probe(Path::new(workspace_path));
+    state
+        .0
+        .lock()
+        .unwrap()
+        .insert(workspace_path.to_string(), (writable, Instant::now()));
+    writable
+}
+
+/// Why `workspace_path`, or the workspace it is a project of, is read-only.
+pub fn reason(app: &AppHandle, workspace_path: &str) -> Option<ReadOnlyReason> {
+    let workspace_path = canonical_workspace(workspace_path);
+    if is_marked(app, &workspace_path) {
+        return Some(ReadOnlyReason::Marked);
+    }
+    (!is_writable(app, &workspace_path)).then_some(ReadOnlyReason::NotWritable)
+}
+
+/// Refuses writing to a read-only workspace.
+pub fn ensure_writable(app: &AppHandle, workspace_path: &str) -> Result<(), CommandError> {
+    let Some(reason) = reason(app, workspace_path) else {
+        return Ok(());
+    };
+    let (message, guidance) = match reason {
+        ReadOnlyReason::Marked => (
+            format!("{workspace_path} is open read-only."),
+            "Turn off read-only for this workspace in Settings to edit it.",
+        ),
+        ReadOnlyReason::NotWritable => (
+            format!("{workspace_path} can't be written to."),
+            "Hermes can show this workspace but not save to it. Copy it somewhere you can write to, or check its permissions.",
+        ),
+    };
+    Err(CommandError::Access(AccessError {
+        issue: AccessIssue::ReadOnly,
+        path: workspace_path.to_string(),
+        message,
+        guidance: guidance.to_string(),
+        settings_url: None,
+    }))
+}
+
+fn access(app: &AppHandle, workspace_path: String) -> WorkspaceAccess {
+    let reason = reason(app, &workspace_path);
+    WorkspaceAccess {
+        workspace_path,
+        read_only: reason.is_some(),
+        reason,
+    }
+}
+
+/// Whether the workspace can be edited, and if not, why.
+#[tauri::command]
+pub fn get_workspace_access(app: AppHandle, workspace_path: String) -> WorkspaceAccess {
+    access(&app, canonical_workspace(&workspace_path))
+}
+
+/// Marks the workspace read-only on this machine, or clears the mark.
+#[tauri::command]
+pub fn set_workspace_read_only(app: AppHandle, workspace_path: String, read_only: bool) -> Result<WorkspaceAccess, String> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    let mut paths = marked(&app);
+    paths.retain(|path| path != &workspace_path);
+    if read_only {
+        // Edits still waiting on autosave are written before the mark goes on.
+        crate::workspaces::flush_under(&app, &workspace_path).map_err(String::from)?;
+        paths.push(workspace_path.clone());
+    }
+    crate::write_setting(&app, READ_ONLY_SETTING, Value::from(paths))?;
+
+    let access = access(&app, workspace_path);
+    if let Err(err) = app.emit(WORKSPACE_READ_ONLY_EVENT, access.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(access)
+}
@@ -0,0 +1,228 @@
+//! Recently opened workspace roots, kept in `recents.json` in app data.
+//!
+//! The Open Recent menu and the workspace switcher both list them from here.
+//! Pinned workspaces come first and are never pushed out by newer ones; the
+//! rest are kept most recent first, up to `MAX_RECENTS`. Workspaces whose
+//! folder is gone stay listed, marked missing, until the user removes them,
+//! since the drive they live on may just be unplugged.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::{bookmarks, file_open, workspace_meta, workspace_move, SETTINGS_STORE_FILE};
+
+const RECENTS_FILE: &str = "recents.json";
+/// Where recents were kept before `RECENTS_FILE`.
+const RECENTS_SETTING: &str = "recentWorkspaces";
+const MAX_RECENTS: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Recent {
+    path: String,
+    last_opened_unix: u64,
+    #[serde(default)]
+    pinned: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentWorkspace {
+    path: String,
+    /// From the workspace's metadata, or its folder name.
+    name: String,
+    icon: Option<String>,
+    color: Option<String>,
+    last_opened_unix: u64,
+    pinned: bool,
+    /// Whether the folder is there; a missing one may be on an unplugged drive.
+    exists: bool,
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn recents_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(RECENTS_FILE))
+        .map_err(|err| format!("Failed resolving app data directory: {err}"))
+}
+
+/// Recents from the settings store, for the first run with `RECENTS_FILE`.
+fn from_store(app: &AppHandle) -> Vec<Recent> {
+    crate::read_setting(app, RECENTS_SETTING)
+        .and_then(|value| value.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .map(|path| Recent {
+            path,
+            last_opened_unix: 0,
+            pinned: false,
+        })
+        .collect()
+}
+
+fn stored(app: &AppHandle) -> Vec<Recent> {
+    let Ok(path) = recents_path(app) else {
+        return Vec::new();
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            tracing::warn!("Failed parsing {}: {err}", path.display());
+            Vec::new()
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => from_store(app),
+        Err(err) => {
+            tracing::warn!("Failed reading {}: {err}", path.display());
+            Vec::new()
+        }
+    }
+}
+
+/// Orders pinned workspaces first, then by last opened, and drops the oldest
+/// unpinned ones past `MAX_RECENTS`.
+fn save(app: &AppHandle, mut recents: Vec<Recent>) -> Result<(), String> {
+    recents.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened_unix.cmp(&a.last_opened_unix)));
+    let pinned = recents.iter().filter(|recent| recent.pinned).count();
+    recents.truncate(pinned.max(MAX_RECENTS));
+    #[cfg(desktop)]
+    let reordered = !stored(app).iter().map(|recent| &recent.path).eq(recents.iter().map(|recent| &recent.path));
+
+    let path = recents_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    }
+    let json = serde_json::to_vec_pretty(&recents).map_err(|err| format!("Failed encoding recents: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+
+    // The old setting only seeds `RECENTS_FILE`.
+    if let Ok(store) = app.store(SETTINGS_STORE_FILE) {
+        if store.delete(RECENTS_SETTING) {
+            if let Err(err) = store.save() {
+                tracing::warn!("Failed saving settings: {err}");
+            }
+        }
+    }
+
+    #[cfg(desktop)]
+    if reordered {
+        crate::menu::refresh(app);
+    }
+    Ok(())
+}
+
+/// Recent workspaces that still exist on disk, pinned first, then most recent.
+pub fn list(app: &AppHandle) -> Vec<String> {
+    stored(app)
+        .into_iter()
+        .map(|recent| recent.path)
+        .filter(|path| Path::new(path).is_dir())
+        .collect()
+}
+
+/// Marks `workspace_path` as just opened.
+pub fn record(app: &AppHandle, workspace_path: &str) {
+    let path = crate::canonical_workspace(workspace_path.trim_end_matches('/'));
+    if path.is_empty() {
+        return;
+    }
+
+    let mut recents = stored(app);
+    let pinned = recents.iter().any(|recent| recent.path == path && recent.pinned);
+    recents.retain(|recent| recent.path != path);
+    recents.push(Recent {
+        path,
+        last_opened_unix: now_unix(),
+        pinned,
+    });
+    if let Err(err) = save(app, recents) {
+        tracing::warn!("{}", err);
+    }
+}
+
+/// Follows a workspace moved from `old_path` to `new_path`, keeping its pin
+/// and last-opened time.
+pub fn relocate(app: &AppHandle, old_path: &str, new_path: &str) {
+    let mut recents = stored(app);
+    let mut changed = false;
+    for recent in &mut recents {
+        if let Some(rebased) = workspace_move::rebase(&recent.path, old_path, new_path) {
+            recent.path = rebased;
+            changed = true;
+        }
+    }
+    if !changed {
+        return;
+    }
+    recents.sort_by(|a, b| a.path.cmp(&b.path).then(b.last_opened_unix.cmp(&a.last_opened_unix)));
+    recents.dedup_by(|later, earlier| later.path == earlier.path);
+    if let Err(err) = save(app, recents) {
+        tracing::warn!("{}", err);
+    }
+}
+
+#[tauri::command]
+pub fn list_recent_workspaces(app: AppHandle) -> Vec<RecentWorkspace> {
+    stored(&app)
+        .into_iter()
+        .map(|recent| {
+            let meta = workspace_meta::read(&recent.path);
+            RecentWorkspace {
+                exists: Path::new(&recent.path).is_dir(),
+                name: workspace_meta::display_name(&recent.path, &meta),
+                icon: meta.icon().map(str::to_string),
+                color: meta.color().map(str::to_string),
+                path: recent.path,
+                last_opened_unix: recent.last_opened_unix,
+                pinned: recent.pinned,
+            }
+        })
+        .collect()
+}
+
+/// Pins `path` to the top of the recents, or unpins it.
+#[tauri::command]
+pub fn pin_workspace(app: AppHandle, path: String, pinned: bool) -> Result<(), String> {
+    let path = crate::canonical_workspace(path.trim_end_matches('/'));
+    let mut recents = stored(&app);
+    match recents.iter_mut().find(|recent| recent.path == path) {
+        Some(recent) => recent.pinned = pinned,
+        None if pinned => {
+            if !Path::new(&path).is_dir() {
+                return Err(format!("{path} is not a folder."));
+            }
+            recents.push(Recent {
+                path,
+                last_opened_unix: 0,
+                pinned,
+            });
+        }
+        None => return Ok(()),
+    }
+    save(&app, recents)
+}
+
+/// Drops `path` from the recents, along with the access bookmark kept for it
+/// unless it is the configured workspace.
+#[tauri::command]
+pub fn remove_recent(app: AppHandle, path: String) -> Result<(), String> {
+    let path = crate::canonical_workspace(path.trim_end_matches('/'));
+    let mut recents = stored(&app);
+    recents.retain(|recent| recent.path != path);
+    save(&app, recents)?;
+    if file_open::configured_workspace(&app).is_some_and(|configured| configured == Path::new(&path)) {
+        return Ok(());
+    }
+    bookmarks::forget(&app, &path)
+}
@@ -0,0 +1,419 @@
+//! End-to-end encrypted sync through a user-hosted relay.
+//!
+//! The relay only stores opaque blobs and a versioned pointer to the current
+//! manifest; it never sees note text or names. Each note's CRDT state is split
+//! into chunks, sealed with ChaCha20-Poly1305 under the workspace key and
+//! addressed by a keyed hash, so unchanged chunks are not uploaded twice. The
+//! manifest (note key → chunk ids and content hash) is itself an encrypted blob.
+//!
+//! Relay API, all JSON unless noted and authenticated with the device token:
+//!   POST   /devices            { deviceId, name, proof } → { token }
+//!   GET    /devices            → [{ deviceId, name, lastSeen }]
+//!   DELETE /devices/{id}
+//!   GET    /manifest           → { version, blob } (404 before the first sync)
+//!   PUT    /manifest           { expectedVersion, blob } (409 if it moved on)
+//!   GET    /blobs/{id}, PUT /blobs/{id}  raw bytes
+//!
+//! `proof` is derived from the workspace key, so only devices that were given
+//! the key can register; revoking a device invalidates its token.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+use crate::{device, sync_status};
+use crate::lan_sync::{self, hex, unhex, NoteVersion};
+
+/// Settings-store key holding `{ url, key, token }`.
+const RELAY_SETTING: &str = "relaySync";
+const CHUNK_BYTES: usize = 256 * 1024;
+const NONCE_BYTES: usize = 12;
+/// Manifest updates retried when another device syncs at the same time.
+const MAX_ATTEMPTS: usize = 3;
+
+pub const RELAY_SYNC_EVENT: &str = "relay-sync-completed";
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RelayConfig {
+    url: String,
+    /// Hex-encoded 32-byte workspace key, shared with every device of the workspace.
+    key: String,
+    token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    notes: HashMap<String, ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    #[serde(flatten)]
+    note: NoteVersion,
+    chunks: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestPointer {
+    version: u64,
+    blob: String,
+}
+
+#[derive(Deserialize)]
+struct Registration {
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayDevice {
+    device_id: String,
+    name: String,
+    last_seen: Option<String>,
+    #[serde(default)]
+    current: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RelaySyncCompleted {
+    notes: Vec<String>,
+}
+
+struct Relay {
+    client: Client,
+    url: String,
+    key: [u8; 32],
+    token: String,
+}
+
+fn read_config(app: &AppHandle) -> Option<RelayConfig> {
+    serde_json::from_value(crate::read_setting(app, RELAY_SETTING)?).ok()
+}
+
+fn save_config(app: &AppHandle, config: &RelayConfig) -> Result<(), String> {
+    let value = serde_json::to_value(config).map_err(|err| format!("Failed encoding relay settings: {err}"))?;
+    crate::write_setting(app, RELAY_SETTING, value)
+}
+
+fn parse_key(text: &str) -> Result<[u8; 32], String> {
+    unhex(text.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "The workspace key must be 64 hex characters.".to_string())
+}
+
+fn derive(key: &[u8; 32], purpose: &[u8], data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(purpose);
+    hasher.update(key);
+    hasher.update(data);
+    hex(&hasher.finalize())
+}
+
+fn client() -> Result<Client, String> {
+    // reqwest is built without a bundled TLS provider; reuse the updater's ring backend.
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|err| format!("Failed creating HTTP client: {err}"))
+}
+
+fn check(response: Result<Response, reqwest::Error>) -> Result<Response, String> {
+    let response = response.map_err(|err| format!("Relay request failed: {err}"))?;
+    match response.status() {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Err("The relay rejected this device. It may have been revoked.".to_string())
+        }
+        status if !status.is_success() => Err(format!("Relay returned {status}")),
+        _ => Ok(response),
+    }
+}
+
+impl Relay {
+    fn connect(app: &AppHandle) -> Result<Self, String> {
+        let mut config = read_config(app).ok_or_else(|| "Relay sync is not configured.".to_string())?;
+        let key = parse_key(&config.key)?;
+        let client = client()?;
+        let token = match config.token.clone() {
+            Some(token) => token,
+            None => {
+                let registration: Registration = check(
+                    client
+                        .post(format!("{}/devices", config.url))
+                        .json(&serde_json::json!({
+                            "deviceId": device::id(app)?,
+                            "name": device::name(),
+                            "proof": derive(&key, b"hermes-relay-auth", &[]),
+                        }))
+                        .send(),
+                )?
+                .json()
+                .map_err(|err| format!("Invalid relay response: {err}"))?;
+                config.token = Some(registration.token.clone());
+                save_config(app, &config)?;
+                registration.token
+            }
+        };
+        Ok(Relay { client, url: config.url, key, token })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{path}", self.url))
+            .bearer_auth(&self.token)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+
+    fn seal(&self, plain: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = lan_sync::random_bytes::<NONCE_BYTES>();
+        let mut sealed = nonce.to_vec();
+        sealed.extend(
+            self.cipher()
+                .encrypt(Nonce::from_slice(&nonce), plain)
+                .map_err(|_| "Failed encrypting blob".to_string())?,
+        );
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        if sealed.len() < NONCE_BYTES {
+            return Err("Relay blob is truncated".to_string());
+        }
+        let (nonce, body) = sealed.split_at(NONCE_BYTES);
+        self.cipher()
+            .decrypt(Nonce::from_slice(nonce), body)
+            .map_err(|_| "Failed decrypting relay blob; is the workspace key correct?".to_string())
+    }
+
+    fn put_blob(&self, plain: &[u8]) -> Result<String, String> {
+        let id = derive(&self.key, b"hermes-relay-blob", plain);
+        check(self.request(reqwest::Method::PUT, &format!("/blobs/{id}")).body(self.seal(plain)?).send())?;
+        Ok(id)
+    }
+
+    fn get_blob(&self, id: &str) -> Result<Vec<u8>, String> {
+        let sealed = check(self.request(reqwest::Method::GET, &format!("/blobs/{id}")).send())?
+            .bytes()
+            .map_err(|err| format!("Failed downloading blob: {err}"))?;
+        self.open(&sealed)
+    }
+
+    fn manifest(&self) -> Result<(u64, Manifest), String> {
+        let response = self
+            .request(reqwest::Method::GET, "/manifest")
+            .send()
+            .map_err(|err| format!("Relay request failed: {err}"))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok((0, Manifest::default()));
+        }
+        let pointer: ManifestPointer = check(Ok(response))?
+            .json()
+            .map_err(|err| format!("Invalid relay response: {err}"))?;
+        let manifest = serde_json::from_slice(&self.get_blob(&pointer.blob)?)
+            .map_err(|err| format!("Invalid relay manifest: {err}"))?;
+        Ok((pointer.version, manifest))
+    }
+
+    /// Publishes `manifest`; false when another device updated it first.
+    fn publish(&self, expected_version: u64, manifest: &Manifest) -> Result<bool, String> {
+        let bytes = serde_json::to_vec(manifest).map_err(|err| format!("Failed encoding manifest: {err}"))?;
+        let blob = self.put_blob(&bytes)?;
+        let response = self
+            .request(reqwest::Method::PUT, "/manifest")
+            .json(&serde_json::json!({ "expectedVersion": expected_version, "blob": blob }))
+            .send();
+        if matches!(&response, Ok(response) if response.status() == StatusCode::CONFLICT) {
+            return Ok(false);
+        }
+        check(response).map(|_| true)
+    }
+
+    /// Uploads a note's chunks, skipping ids the relay already holds.
+    fn upload_note(&self, state: &[u8], known: &BTreeSet<String>) -> Result<Vec<String>, String> {
+        state
+            .chunks(CHUNK_BYTES)
+            .map(|chunk| {
+                let id = derive(&self.key, b"hermes-relay-blob", chunk);
+                if known.contains(&id) {
+                    return Ok(id);
+                }
+                self.put_blob(chunk)
+            })
+            .collect()
+    }
+
+    fn download_note(&self, entry: &ManifestEntry) -> Result<Vec<u8>, String> {
+        let mut state = Vec::new();
+        for id in &entry.chunks {
+            state.extend(self.get_blob(id)?);
+        }
+        Ok(state)
+    }
+}
+
+/// One pass: pull notes that changed remotely, then publish every note that differs.
+fn sync_once(app: &AppHandle, relay: &Relay, synced: &mut BTreeSet<String>) -> Result<bool, String> {
+    let (version, mut manifest) = relay.manifest()?;
+    let own_name = device::name();
+
+    let local = lan_sync::manifest(&lan_sync::local_notes(app)?);
+    for (key, entry) in &manifest.notes {
+        if local.get(key).map(|note| &note.hash) == Some(&entry.note.hash) {
+            continue;
+        }
+        // Read-only folders still publish their notes but take nothing in.
+        if let Err(err) = lan_sync::ensure_writable_key(app, key) {
+            tracing::info!("{key}: {err}");
+            continue;
+        }
+        let state = relay.download_note(entry)?;
+        match lan_sync::apply_remote(app, key, &state, &entry.note, &own_name, true) {
+            Ok(()) => {
+                synced.insert(key.clone());
+            }
+            Err(err) => tracing::warn!("{key}: {err}"),
+        }
+    }
+
+    let local = lan_sync::manifest(&lan_sync::local_notes(app)?);
+    let known: BTreeSet<String> = manifest
+        .notes
+        .values()
+        .flat_map(|entry| entry.chunks.iter().cloned())
+        .collect();
+    let mut changed = false;
+    for (key, note) in local {
+        if manifest.notes.get(&key).map(|entry| &entry.note.hash) == Some(&note.hash) {
+            continue;
+        }
+        let chunks = relay.upload_note(&lan_sync::local_state(app, &key)?, &known)?;
+        synced.insert(key.clone());
+        manifest.notes.insert(key, ManifestEntry { note, chunks });
+        changed = true;
+    }
+
+    if !changed {
+        return Ok(true);
+    }
+    relay.publish(version, &manifest)
+}
+
+fn sync(app: &AppHandle) -> Result<Vec<String>, String> {
+    let relay = Relay::connect(app)?;
+    let mut synced = BTreeSet::new();
+    let mut published = false;
+    for _ in 0..MAX_ATTEMPTS {
+        if sync_once(app, &relay, &mut synced)? {
+            published = true;
+            break;
+        }
+    }
+
+    lan_sync::reindex(app, &synced);
+    let payload = RelaySyncCompleted {
+        notes: synced.iter().cloned().collect(),
+    };
+    if let Err(err) = app.emit(RELAY_SYNC_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    if !published {
+        return Err("Other devices kept syncing at the same time; try again.".to_string());
+    }
+    Ok(synced.into_iter().collect())
+}
+
+/// Points this install at a relay. Omitting `workspace_key` creates a new one;
+/// the returned key must be entered on every other device of the workspace.
+#[tauri::command]
+pub fn configure_relay_sync(app: AppHandle, url: String, workspace_key: Option<String>) -> Result<String, String> {
+    let url = url.trim().trim_end_matches('/').to_string();
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err("The relay URL must start with https://".to_string());
+    }
+    let key = match workspace_key {
+        Some(key) => parse_key(&key)?,
+        None => lan_sync::random_bytes::<32>(),
+    };
+    let config = RelayConfig {
+        url,
+        key: hex(&key),
+        token: None,
+    };
+    save_config(&app, &config)?;
+    Ok(config.key)
+}
+
+#[tauri::command]
+pub fn disable_relay_sync(app: AppHandle) -> Result<(), String> {
+    crate::write_setting(&app, RELAY_SETTING, Value::Null)
+}
+
+#[cfg(mobile)]
+pub fn is_configured(app: &AppHandle) -> bool {
+    read_config(app).is_some()
+}
+
+/// Syncs the workspace with the relay, reporting progress to the sync
+/// indicator, and returns the note keys that changed.
+pub fn run(app: &AppHandle) -> Result<Vec<String>, String> {
+    sync_status::syncing(app, "relay", 0, 0);
+    let result = sync(app);
+    sync_status::finish(app, "relay", result.as_ref().map(|_| Vec::new()).map_err(String::clone));
+    result
+}
+
+/// Syncs the workspace with the relay and returns the note keys that changed.
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub async fn sync_with_relay(app: AppHandle) -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || run(&app))
+        .await
+        .map_err(|err| format!("Relay sync task failed: {err}"))?
+}
+
+#[tauri::command]
+pub async fn list_devices(app: AppHandle) -> Result<Vec<RelayDevice>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let relay = Relay::connect(&app)?;
+        let own_id = device::id(&app)?;
+        let mut devices: Vec<RelayDevice> = check(relay.request(reqwest::Method::GET, "/devices").send())?
+            .json()
+            .map_err(|err| format!("Invalid relay response: {err}"))?;
+        for device in &mut devices {
+            device.current = device.device_id == own_id;
+        }
+        Ok(devices)
+    })
+    .await
+    .map_err(|err| format!("Relay task failed: {err}"))?
+}
+
+/// Revokes a device's access to the relay. Its copy of the workspace key still
+/// decrypts blobs it already downloaded, so move the
+/// remaining devices to a new key when one is lost.
+#[tauri::command]
+pub async fn revoke_device(app: AppHandle, device_id: String) -> Result<(), String> {
+    if device_id.is_empty() || !device_id.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid device ID '{device_id}'"));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let relay = Relay::connect(&app)?;
+        check(relay.request(reqwest::Method::DELETE, &format!("/devices/{device_id}")).send()).map(|_| ())
+    })
+    .await
+    .map_err(|err| format!("Relay task failed: {err}"))?
+}
@@ -0,0 +1,227 @@
+//! Grammar and style suggestions for a selection, from the assistant.
+//!
+//! The selection is split into paragraphs and the ones not reviewed before in
+//! the same style go to the assistant in one request, numbered, with a prompt
+//! asking for JSON. Each suggestion quotes the words it would change rather
+//! than giving offsets, which models get wrong; the quote is found in its
+//! paragraph to place it. Reviews are kept per paragraph and style while the
+//! app runs, so reviewing a note again only sends the paragraphs that changed.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::assistant;
+
+const DEFAULT_STYLE: &str = "clear and plain";
+const MAX_STYLE_CHARS: usize = 200;
+const MAX_TEXT_CHARS: usize = 20_000;
+/// Paragraph reviews kept before the cache starts over.
+const MAX_CACHED: usize = 2_000;
+
+/// Suggestions per style and paragraph, with offsets into the paragraph.
+type Reviews = HashMap<(String, String), Vec<Found>>;
+
+static CACHE: Mutex<Option<Reviews>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuggestionKind {
+    Grammar,
+    Spelling,
+    Punctuation,
+    #[serde(other)]
+    Style,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    /// UTF-16 offsets into the reviewed text, as the editor counts.
+    pub start: usize,
+    pub end: usize,
+    pub original: String,
+    pub replacement: String,
+    pub reason: String,
+    pub kind: SuggestionKind,
+}
+
+/// A suggestion placed in its paragraph, by byte offsets.
+#[derive(Clone)]
+struct Found {
+    start: usize,
+    end: usize,
+    replacement: String,
+    reason: String,
+    kind: SuggestionKind,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    #[serde(default)]
+    suggestions: Vec<Proposed>,
+}
+
+#[derive(Deserialize)]
+struct Proposed {
+    paragraph: usize,
+    original: String,
+    replacement: String,
+    #[serde(default)]
+    reason: String,
+    kind: Option<SuggestionKind>,
+}
+
+/// The paragraphs of `text` worth reviewing, with their byte offsets; code
+/// blocks are left out.
+fn paragraphs(text: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    let mut in_fence = false;
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let at = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        let fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if fence {
+            in_fence = !in_fence;
+        }
+        if trimmed.is_empty() || fence || in_fence {
+            if let Some(begin) = start.take() {
+                found.push((begin, text[begin..at].trim_end()));
+            }
+            continue;
+        }
+        start.get_or_insert(at);
+    }
+    if let Some(begin) = start {
+        found.push((begin, text[begin..].trim_end()));
+    }
+    found
+}
+
+fn prompt(style: &str, pending: &[(usize, &str)]) -> String {
+    let numbered: Vec<String> = pending
+        .iter()
+        .enumerate()
+        .map(|(number, (_, paragraph))| format!("[{}]\n{paragraph}", number + 1))
+        .collect();
+    format!(
+        "Review the numbered paragraphs below for grammar, spelling, punctuation and style. \
+         The writer wants the style to be: {style}. Leave markdown syntax, names and quotations alone, \
+         and don't rewrite what is already fine.\n\n\
+         Reply with only a JSON object, no other text: \
+         {{\"suggestions\": [{{\"paragraph\": <number>, \"original\": \"<exact words to change, copied from the paragraph>\", \
+         \"replacement\": \"<new words>\", \"reason\": \"<one short sentence>\", \
+         \"kind\": \"grammar\" | \"spelling\" | \"punctuation\" | \"style\"}}]}}. \
+         Keep each original short, just the words that change. Reply with an empty list if nothing needs changing.\n\n{}",
+        numbered.join("\n\n")
+    )
+}
+
+/// Places each proposed change in its paragraph, dropping the ones whose quote isn't there.
+fn place(pending: &[(usize, &str)], reply: Reply) -> Vec<Vec<Found>> {
+    let mut placed: Vec<Vec<Found>> = vec![Vec::new(); pending.len()];
+    for proposed in reply.suggestions {
+        let Some((_, paragraph)) = proposed.paragraph.checked_sub(1).and_then(|index| pending.get(index)) else {
+            continue;
+        };
+        if proposed.original.is_empty() || proposed.original == proposed.replacement {
+            continue;
+        }
+        let found = &mut placed[proposed.paragraph - 1];
+        // The first occurrence not already taken by an earlier suggestion.
+        let start = paragraph
+            .match_indices(&proposed.original)
+            .map(|(start, _)| start)
+            .find(|start| !found.iter().any(|other| *start < other.end && other.start < start + proposed.original.len()));
+        if let Some(start) = start {
+            found.push(Found {
+                start,
+                end: start + proposed.original.len(),
+                replacement: proposed.replacement,
+                reason: proposed.reason.trim().to_string(),
+                kind: proposed.kind.unwrap_or(SuggestionKind::Style),
+            });
+        }
+    }
+    placed
+}
+
+fn review(app: &AppHandle, text: &str, style: &str, workspace_path: Option<&str>) -> Result<Vec<Suggestion>, String> {
+    if text.chars().count() > MAX_TEXT_CHARS {
+        return Err(format!("Select at most {MAX_TEXT_CHARS} characters to review."));
+    }
+    let paragraphs = paragraphs(text);
+    let mut reviews: HashMap<&str, Vec<Found>> = HashMap::new();
+    if let Some(cache) = CACHE.lock().unwrap().as_ref() {
+        for (_, paragraph) in &paragraphs {
+            if let Some(found) = cache.get(&(style.to_string(), paragraph.to_string())) {
+                reviews.insert(paragraph, found.clone());
+            }
+        }
+    }
+    let mut asked = HashSet::new();
+    let pending: Vec<(usize, &str)> = paragraphs
+        .iter()
+        .copied()
+        .filter(|(_, paragraph)| !reviews.contains_key(paragraph) && asked.insert(*paragraph))
+        .collect();
+
+    if !pending.is_empty() {
+        let reply = assistant::ask(app, workspace_path, &prompt(style, &pending))?;
+        let reply: Reply = serde_json::from_value(assistant::json_in(&reply)?)
+            .map_err(|err| format!("The assistant's suggestions weren't in the expected shape: {err}"))?;
+        let mut cache = CACHE.lock().unwrap();
+        let cache = cache.get_or_insert_with(HashMap::new);
+        if cache.len() + pending.len() > MAX_CACHED {
+            cache.clear();
+        }
+        for ((_, paragraph), found) in pending.iter().zip(place(&pending, reply)) {
+            cache.insert((style.to_string(), paragraph.to_string()), found.clone());
+            reviews.insert(paragraph, found);
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for (offset, paragraph) in paragraphs {
+        let mut found = reviews.get(paragraph).cloned().unwrap_or_default();
+        found.sort_by_key(|found| found.start);
+        for found in found {
+            let start = text[..offset + found.start].encode_utf16().count();
+            let original = &paragraph[found.start..found.end];
+            suggestions.push(Suggestion {
+                start,
+                end: start + original.encode_utf16().count(),
+                original: original.to_string(),
+                replacement: found.replacement,
+                reason: found.reason,
+                kind: found.kind,
+            });
+        }
+    }
+    Ok(suggestions)
+}
+
+/// Grammar and style suggestions for `text`, in the `style` the writer
+/// describes (plain and clear when not given), using the model set for
+/// `workspace_path`.
+#[tauri::command]
+pub async fn review_text(
+    app: AppHandle,
+    text: String,
+    style: Option<String>,
+    workspace_path: Option<String>,
+) -> Result<Vec<Suggestion>, String> {
+    let style = style.map(|style| style.trim().to_string()).filter(|style| !style.is_empty());
+    let style = style.unwrap_or_else(|| DEFAULT_STYLE.to_string());
+    if style.chars().count() > MAX_STYLE_CHARS {
+        return Err(format!("Describe the style in at most {MAX_STYLE_CHARS} characters."));
+    }
+    tauri::async_runtime::spawn_blocking(move || review(&app, &text, &style, workspace_path.as_deref()))
+        .await
+        .map_err(|err| format!("Review task failed: {err}"))?
+}
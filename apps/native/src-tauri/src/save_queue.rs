@@ -0,0 +1,213 @@
+//! Coalesces saves that arrive faster than the disk keeps up.
+//!
+//! A save to a workspace that hasn't been written in the last `WINDOW` goes
+//! straight to disk, so its errors reach the caller. Saves arriving within
+//! `WINDOW` of the last write, or while one is running, are queued per tab with
+//! the newest content winning, and written together once the window is over;
+//! failures of those writes are logged and sent as `SAVE_FAILED_EVENT`.
+//! `flush_pending_saves` writes everything queued right away; the frontend
+//! calls it when a window loses focus or closes, and loading a workspace
+//! flushes it first so the editor never reads older files than it saved.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::CommandError;
+
+pub const SAVE_FAILED_EVENT: &str = "workspace://save-failed";
+
+const WINDOW: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+struct WorkspaceQueue {
+    pending: HashMap<String, String>,
+    /// When the queued tabs should be written; `None` when nothing is queued.
+    due: Option<Instant>,
+    last_write: Option<Instant>,
+    writing: bool,
+}
+
+/// Saves waiting to be written, by workspace path.
+#[derive(Default)]
+pub struct SaveQueue {
+    queues: Mutex<HashMap<String, WorkspaceQueue>>,
+    /// Signalled when saves are queued or a write finishes.
+    changed: Condvar,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveFailed {
+    workspace_path: String,
+    tabs: Vec<String>,
+    error: String,
+}
+
+/// Takes the queued tabs of `workspace_path` and marks it as being written.
+fn take(queues: &mut HashMap<String, WorkspaceQueue>, workspace_path: &str) -> HashMap<String, String> {
+    let queue = queues.entry(workspace_path.to_string()).or_default();
+    queue.writing = true;
+    queue.due = None;
+    std::mem::take(&mut queue.pending)
+}
+
+/// Writes `pages` outside the lock, then records the write.
+fn write(app: &AppHandle, state: &SaveQueue, workspace_path: &str, pages: &HashMap<String, String>) -> Result<(), CommandError> {
+    let result = crate::write_pages(app, workspace_path, pages);
+    let mut queues = state.queues.lock().unwrap();
+    if let Some(queue) = queues.get_mut(workspace_path) {
+        queue.writing = false;
+        queue.last_write = Some(Instant::now());
+    }
+    state.changed.notify_all();
+    result
+}
+
+/// Saves `pages` now, or queues them when the workspace was written less than `WINDOW` ago.
+pub fn save(app: &AppHandle, workspace_path: &str, pages: HashMap<String, String>) -> Result<(), CommandError> {
+    let state = app.state::<SaveQueue>();
+    let mut queues = state.queues.lock().unwrap();
+    let queue = queues.entry(workspace_path.to_string()).or_default();
+    let now = Instant::now();
+    let busy = queue.writing || queue.last_write.is_some_and(|last| now - last < WINDOW);
+    queue.pending.extend(pages);
+    if busy {
+        queue.due.get_or_insert(now + WINDOW);
+        state.changed.notify_all();
+        return Ok(());
+    }
+    let pages = take(&mut queues, workspace_path);
+    drop(queues);
+    write(app, &state, workspace_path, &pages)
+}
+
+/// Waits out running writes of the given workspaces, or of all when `None`.
+fn wait_idle<'a>(
+    state: &'a SaveQueue,
+    mut queues: MutexGuard<'a, HashMap<String, WorkspaceQueue>>,
+    workspace_path: Option<&str>,
+) -> MutexGuard<'a, HashMap<String, WorkspaceQueue>> {
+    while queues
+        .iter()
+        .any(|(path, queue)| queue.writing && workspace_path.is_none_or(|workspace_path| workspace_path == path))
+    {
+        queues = state.changed.wait(queues).unwrap();
+    }
+    queues
+}
+
+/// Writes every queued save of `workspace_path`, or of all workspaces, returning the first error.
+pub fn flush(app: &AppHandle, workspace_path: Option<&str>) -> Result<(), CommandError> {
+    let state = app.state::<SaveQueue>();
+    let mut queues = wait_idle(&state, state.queues.lock().unwrap(), workspace_path);
+    let targets: Vec<String> = queues
+        .iter()
+        .filter(|(path, queue)| {
+            !queue.pending.is_empty() && workspace_path.is_none_or(|workspace_path| workspace_path == path.as_str())
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+    let writes: Vec<(String, HashMap<String, String>)> = targets
+        .into_iter()
+        .map(|path| {
+            let pages = take(&mut queues, &path);
+            (path, pages)
+        })
+        .collect();
+    drop(queues);
+
+    let mut result = Ok(());
+    for (path, pages) in writes {
+        if let Err(err) = write(app, &state, &path, &pages) {
+            if result.is_ok() {
+                result = Err(err);
+            }
+        }
+    }
+    result
+}
+
+/// Workspaces with saves queued or being written.
+pub fn pending_workspaces(app: &AppHandle) -> Vec<String> {
+    let state = app.state::<SaveQueue>();
+    let queues = state.queues.lock().unwrap();
+    queues
+        .iter()
+        .filter(|(_, queue)| queue.writing || !queue.pending.is_empty())
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+/// Drops what is kept for idle workspaces under `root`.
+pub fn forget_under(app: &AppHandle, root: &Path) {
+    let state = app.state::<SaveQueue>();
+    state
+        .queues
+        .lock()
+        .unwrap()
+        .retain(|path, queue| queue.writing || !queue.pending.is_empty() || !Path::new(path).starts_with(root));
+}
+
+/// Blocks until some workspace's queued saves are due and takes them.
+fn next_due(state: &SaveQueue) -> (String, HashMap<String, String>) {
+    let mut queues = state.queues.lock().unwrap();
+    loop {
+        let now = Instant::now();
+        let ready = queues
+            .iter()
+            .find(|(_, queue)| !queue.writing && queue.due.is_some_and(|due| due <= now))
+            .map(|(path, _)| path.clone());
+        if let Some(path) = ready {
+            let pages = take(&mut queues, &path);
+            return (path, pages);
+        }
+        let next = queues.values().filter(|queue| !queue.writing).filter_map(|queue| queue.due).min();
+        queues = match next {
+            Some(due) => state.changed.wait_timeout(queues, due - now).unwrap().0,
+            None => state.changed.wait(queues).unwrap(),
+        };
+    }
+}
+
+/// Starts writing queued saves once their window is over.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        let state = app.state::<SaveQueue>();
+        let (workspace_path, pages) = next_due(&state);
+        if pages.is_empty() {
+            continue;
+        }
+        if let Err(err) = write(&app, &state, &workspace_path, &pages) {
+            report_failure(&app, workspace_path, pages.into_keys().collect(), String::from(err));
+        }
+    });
+}
+
+/// Logs a save that failed with no caller to return the error to, and tells the frontend.
+pub fn report_failure(app: &AppHandle, workspace_path: String, mut tabs: Vec<String>, error: String) {
+    tracing::warn!("{}", error);
+    tabs.sort();
+    let payload = SaveFailed {
+        workspace_path,
+        tabs,
+        error,
+    };
+    if let Err(err) = app.emit(SAVE_FAILED_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+/// Writes unsaved edits and saves still queued, for a window losing focus or closing.
+#[tauri::command]
+pub async fn flush_pending_saves(app: AppHandle, workspace_path: Option<String>) -> Result<(), CommandError> {
+    tauri::async_runtime::spawn_blocking(move || crate::autosave::flush(&app, workspace_path.as_deref()))
+        .await
+        .map_err(|err| CommandError::from(format!("Save flush task failed: {err}")))?
+}
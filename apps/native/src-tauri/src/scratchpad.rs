@@ -0,0 +1,124 @@
+//! Floating scratchpad: a small always-on-top window bound to one scratch note.
+//!
+//! The note lives in app data rather than a workspace so it follows the user
+//! across workspaces; the window position is remembered in the settings store.
+
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+#[cfg(desktop)]
+use tauri::{WebviewUrl, WebviewWindowBuilder, WindowEvent};
+#[cfg(desktop)]
+use tauri_plugin_store::StoreExt;
+
+#[cfg(desktop)]
+pub const SCRATCHPAD_LABEL: &str = "scratchpad";
+#[cfg(desktop)]
+const BOUNDS_SETTING: &str = "scratchpadBounds";
+
+fn scratch_note_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Could not resolve app data directory: {err}"))?;
+    Ok(dir.join("scratchpad.md"))
+}
+
+/// Logical `[x, y, width, height]` of the scratchpad the last time it moved.
+#[cfg(desktop)]
+fn saved_bounds(app: &AppHandle) -> Option<[f64; 4]> {
+    let value = crate::read_setting(app, BOUNDS_SETTING)?;
+    let field = |name: &str| value.get(name).and_then(|v| v.as_f64());
+    Some([field("x")?, field("y")?, field("width")?, field("height")?])
+}
+
+#[cfg(desktop)]
+fn persist_bounds(window: &tauri::WebviewWindow) {
+    let (Ok(position), Ok(size), Ok(scale)) = (window.outer_position(), window.inner_size(), window.scale_factor()) else {
+        return;
+    };
+    let position = position.to_logical::<f64>(scale);
+    let size = size.to_logical::<f64>(scale);
+
+    // The store debounces writes, so frequent move events are cheap.
+    if let Ok(store) = window.app_handle().store(crate::SETTINGS_STORE_FILE) {
+        store.set(
+            BOUNDS_SETTING,
+            serde_json::json!({ "x": position.x, "y": position.y, "width": size.width, "height": size.height }),
+        );
+    }
+}
+
+#[cfg(desktop)]
+fn open_window(app: &AppHandle) -> Result<(), String> {
+    let mut builder = WebviewWindowBuilder::new(
+        app,
+        SCRATCHPAD_LABEL,
+        WebviewUrl::App("index.html?window=scratchpad".into()),
+    )
+    .title("Scratchpad")
+    .decorations(false)
+    .always_on_top(true)
+    .visible_on_all_workspaces(true)
+    .skip_taskbar(true)
+    .min_inner_size(220.0, 160.0);
+
+    builder = match saved_bounds(app) {
+        Some([x, y, width, height]) => builder.position(x, y).inner_size(width, height),
+        None => builder.inner_size(320.0, 360.0),
+    };
+
+    let window = builder
+        .build()
+        .map_err(|err| format!("Failed to open scratchpad: {err}"))?;
+
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_)) {
+            persist_bounds(&tracked);
+        }
+    });
+    Ok(())
+}
+
+/// Opens the scratchpad, or closes it if it is already showing. Returns whether it is now open.
+#[tauri::command]
+pub fn toggle_scratchpad_window(app: AppHandle) -> Result<bool, String> {
+    #[cfg(desktop)]
+    {
+        if let Some(window) = app.get_webview_window(SCRATCHPAD_LABEL) {
+            window
+                .close()
+                .map_err(|err| format!("Failed to close scratchpad: {err}"))?;
+            Ok(false)
+        } else {
+            open_window(&app).map(|_| true)
+        }
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = app;
+        Err("The scratchpad window is only available on desktop.".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn load_scratchpad(app: AppHandle) -> Result<String, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    let path = scratch_note_path(&app)?;
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))
+}
+
+#[tauri::command]
+pub fn save_scratchpad(app: AppHandle, content: String) -> Result<(), String> {
+    let path = scratch_note_path(&app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    fs::write(&path, content).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
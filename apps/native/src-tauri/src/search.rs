@@ -0,0 +1,36 @@
+//! The search panel's search, over `hermes_core::search`.
+
+use hermes_core::search::{search_projects, SearchHit};
+use tauri::AppHandle;
+
+use crate::{canonical_workspace, project_list, CommandError};
+
+/// Notes containing every word of `query`, best matches first, in the whole
+/// workspace or in the project at `scope` and its sub-projects. Refused while
+/// the app is locked.
+pub fn search(
+    app: &AppHandle,
+    workspace_path: &str,
+    query: &str,
+    scope: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, CommandError> {
+    crate::app_lock::ensure_unlocked(app)?;
+    let workspace_path = canonical_workspace(workspace_path);
+    let projects = project_list::names(app, &workspace_path)?;
+    search_projects(&workspace_path, projects, query, scope, limit).map_err(CommandError::from)
+}
+
+/// `search` off the main thread.
+#[tauri::command]
+pub async fn search_notes(
+    app: AppHandle,
+    workspace_path: String,
+    query: String,
+    scope: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || search(&app, &workspace_path, &query, scope, limit))
+        .await
+        .map_err(|err| CommandError::from(format!("Search task failed: {err}")))?
+}
@@ -0,0 +1,333 @@
+//! Typed app preferences, read and written by the backend for every window.
+//!
+//! Settings come in layers: built-in defaults, the user's global settings in
+//! `settings.json` in app data, and a workspace's overrides in its
+//! `.hermes/settings.json`, which a team can commit and share. Each layer
+//! holds only the fields it sets; `get_settings` resolves them and says where
+//! each value came from. `update_settings` checks a change before writing it
+//! to one layer and sends `SETTINGS_CHANGED_EVENT`, so every window applies it.
+//! API keys and the workspace path stay in the settings store: they are
+//! per-machine and must never end up in a workspace folder.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::hermes_dir;
+
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+
+const SETTINGS_FILE: &str = "settings.json";
+const THEMES: [&str; 4] = ["system", "light", "dusk", "dawn"];
+const FIELDS: [&str; 6] = ["theme", "appIcon", "model", "autosaveIdleMs", "autosaveMaxMs", "formatOnSave"];
+const AUTOSAVE_IDLE_MS: std::ops::RangeInclusive<u64> = 200..=60_000;
+const AUTOSAVE_MAX_MS: std::ops::RangeInclusive<u64> = 1_000..=300_000;
+// Shared with the frontend's settingsStorage.js.
+const DEFAULT_MODEL: &str = "claude-sonnet-4-6";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// `system`, `light`, `dusk` or `dawn`.
+    pub theme: String,
+    pub app_icon: String,
+    /// Chat model ID.
+    pub model: String,
+    /// How long typing has to pause before edits are saved.
+    pub autosave_idle_ms: u64,
+    /// Longest edits wait to be saved while typing goes on.
+    pub autosave_max_ms: u64,
+    /// Whether notes are tidied up as they are saved.
+    pub format_on_save: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            theme: "system".to_string(),
+            app_icon: "wing".to_string(),
+            model: DEFAULT_MODEL.to_string(),
+            autosave_idle_ms: 1_000,
+            autosave_max_ms: 10_000,
+            format_on_save: false,
+        }
+    }
+}
+
+/// The fields one layer sets; also the shape of a change to a layer.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsLayer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    app_icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autosave_idle_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autosave_max_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format_on_save: Option<bool>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+    Default,
+    Global,
+    Workspace,
+}
+
+/// Where each effective value came from.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sources {
+    theme: Source,
+    app_icon: Source,
+    model: Source,
+    autosave_idle_ms: Source,
+    autosave_max_ms: Source,
+    format_on_save: Source,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedSettings {
+    pub settings: Settings,
+    sources: Sources,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChanged {
+    /// The workspace whose overrides changed, or `None` for the global settings.
+    workspace_path: Option<String>,
+    #[serde(flatten)]
+    resolved: ResolvedSettings,
+}
+
+impl SettingsLayer {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(theme) = &self.theme {
+            if !THEMES.contains(&theme.as_str()) {
+                return Err(format!("Unknown theme '{theme}'"));
+            }
+        }
+        if let Some(app_icon) = &self.app_icon {
+            if app_icon.is_empty() || !app_icon.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(format!("Invalid app icon '{app_icon}'"));
+            }
+        }
+        if let Some(model) = &self.model {
+            if model.trim().is_empty() {
+                return Err("Choose a model.".to_string());
+            }
+        }
+        if let Some(idle_ms) = self.autosave_idle_ms {
+            if !AUTOSAVE_IDLE_MS.contains(&idle_ms) {
+                return Err(format!(
+                    "The autosave pause must be {} to {} ms.",
+                    AUTOSAVE_IDLE_MS.start(),
+                    AUTOSAVE_IDLE_MS.end()
+                ));
+            }
+        }
+        if let Some(max_ms) = self.autosave_max_ms {
+            if !AUTOSAVE_MAX_MS.contains(&max_ms) {
+                return Err(format!(
+                    "The longest autosave wait must be {} to {} ms.",
+                    AUTOSAVE_MAX_MS.start(),
+                    AUTOSAVE_MAX_MS.end()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes the fields `other` sets.
+    fn merge(&mut self, other: SettingsLayer) {
+        if other.theme.is_some() {
+            self.theme = other.theme;
+        }
+        if other.app_icon.is_some() {
+            self.app_icon = other.app_icon;
+        }
+        if let Some(model) = other.model {
+            self.model = Some(model.trim().to_string());
+        }
+        if other.autosave_idle_ms.is_some() {
+            self.autosave_idle_ms = other.autosave_idle_ms;
+        }
+        if other.autosave_max_ms.is_some() {
+            self.autosave_max_ms = other.autosave_max_ms;
+        }
+        if other.format_on_save.is_some() {
+            self.format_on_save = other.format_on_save;
+        }
+    }
+
+    fn clear(&mut self, field: &str) {
+        match field {
+            "theme" => self.theme = None,
+            "appIcon" => self.app_icon = None,
+            "model" => self.model = None,
+            "autosaveIdleMs" => self.autosave_idle_ms = None,
+            "autosaveMaxMs" => self.autosave_max_ms = None,
+            "formatOnSave" => self.format_on_save = None,
+            _ => {}
+        }
+    }
+}
+
+/// Lays `global` and then `workspace` over the defaults.
+fn resolve(global: &SettingsLayer, workspace: &SettingsLayer) -> ResolvedSettings {
+    fn pick<T: Clone>(default: T, global: &Option<T>, workspace: &Option<T>) -> (T, Source) {
+        match (workspace, global) {
+            (Some(value), _) => (value.clone(), Source::Workspace),
+            (None, Some(value)) => (value.clone(), Source::Global),
+            (None, None) => (default, Source::Default),
+        }
+    }
+    let defaults = Settings::default();
+    let (theme, theme_source) = pick(defaults.theme, &global.theme, &workspace.theme);
+    let (app_icon, app_icon_source) = pick(defaults.app_icon, &global.app_icon, &workspace.app_icon);
+    let (model, model_source) = pick(defaults.model, &global.model, &workspace.model);
+    let (autosave_idle_ms, autosave_idle_ms_source) =
+        pick(defaults.autosave_idle_ms, &global.autosave_idle_ms, &workspace.autosave_idle_ms);
+    let (autosave_max_ms, autosave_max_ms_source) =
+        pick(defaults.autosave_max_ms, &global.autosave_max_ms, &workspace.autosave_max_ms);
+    let (format_on_save, format_on_save_source) =
+        pick(defaults.format_on_save, &global.format_on_save, &workspace.format_on_save);
+    ResolvedSettings {
+        settings: Settings {
+            theme,
+            app_icon,
+            model,
+            autosave_idle_ms,
+            autosave_max_ms,
+            format_on_save,
+        },
+        sources: Sources {
+            theme: theme_source,
+            app_icon: app_icon_source,
+            model: model_source,
+            autosave_idle_ms: autosave_idle_ms_source,
+            autosave_max_ms: autosave_max_ms_source,
+            format_on_save: format_on_save_source,
+        },
+    }
+}
+
+fn global_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|err| format!("Failed resolving app data directory: {err}"))
+}
+
+fn workspace_settings_path(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join(SETTINGS_FILE)
+}
+
+fn read(path: &Path) -> Result<Option<SettingsLayer>, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|err| format!("Failed parsing {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Failed reading {}: {err}", path.display())),
+    }
+}
+
+fn write(path: &Path, layer: &SettingsLayer) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(layer).map_err(|err| format!("Failed encoding settings: {err}"))?;
+    fs::write(path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+/// Preferences the frontend used to keep in the settings store, for the first
+/// run with a settings file.
+fn from_store(app: &AppHandle) -> SettingsLayer {
+    let text = |key: &str| {
+        crate::read_setting(app, key)
+            .and_then(|value| value.as_str().map(str::to_string))
+            .filter(|value| !value.is_empty())
+    };
+    SettingsLayer {
+        theme: text("theme").filter(|theme| THEMES.contains(&theme.as_str())),
+        app_icon: text("appIcon"),
+        model: text("model"),
+        ..SettingsLayer::default()
+    }
+}
+
+fn global_layer(app: &AppHandle) -> Result<SettingsLayer, String> {
+    Ok(read(&global_path(app)?)?.unwrap_or_else(|| from_store(app)))
+}
+
+fn workspace_layer(workspace_path: Option<&str>) -> Result<SettingsLayer, String> {
+    match workspace_path {
+        Some(workspace_path) => Ok(read(&workspace_settings_path(workspace_path))?.unwrap_or_default()),
+        None => Ok(SettingsLayer::default()),
+    }
+}
+
+/// Writes `layer` as the overrides of a workspace that has none yet.
+pub fn seed_workspace(workspace_path: &str, layer: &SettingsLayer) -> Result<(), String> {
+    layer.validate()?;
+    write(&workspace_settings_path(workspace_path), layer)
+}
+
+/// Effective settings for `workspace_path`, or the global ones when `None`.
+pub fn resolved(app: &AppHandle, workspace_path: Option<&str>) -> Result<ResolvedSettings, String> {
+    Ok(resolve(&global_layer(app)?, &workspace_layer(workspace_path)?))
+}
+
+/// Effective settings and where each value came from, for a workspace when
+/// `workspace_path` is given.
+#[tauri::command]
+pub fn get_settings(app: AppHandle, workspace_path: Option<String>) -> Result<ResolvedSettings, String> {
+    resolved(&app, workspace_path.as_deref())
+}
+
+/// Checks and saves `update` to the global settings, or to a workspace's
+/// overrides when `workspace_path` is given; fields named in `reset` are
+/// removed from that layer so the one below shows through.
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    update: SettingsLayer,
+    workspace_path: Option<String>,
+    reset: Option<Vec<String>>,
+) -> Result<ResolvedSettings, String> {
+    update.validate()?;
+    let reset = reset.unwrap_or_default();
+    if let Some(field) = reset.iter().find(|field| !FIELDS.contains(&field.as_str())) {
+        return Err(format!("Unknown setting '{field}'"));
+    }
+    let (path, mut layer) = match &workspace_path {
+        Some(workspace_path) => (workspace_settings_path(workspace_path), workspace_layer(Some(workspace_path))?),
+        None => (global_path(&app)?, global_layer(&app)?),
+    };
+    for field in &reset {
+        layer.clear(field);
+    }
+    layer.merge(update);
+    write(&path, &layer)?;
+
+    let resolved = resolved(&app, workspace_path.as_deref())?;
+    let payload = SettingsChanged {
+        workspace_path,
+        resolved: resolved.clone(),
+    };
+    if let Err(err) = app.emit(SETTINGS_CHANGED_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(resolved)
+}
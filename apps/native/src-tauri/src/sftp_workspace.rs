@@ -0,0 +1,406 @@
+//! Workspaces that live on an SSH server.
+//!
+//! The local workspace folder doubles as the cache: Hermes reads and saves it
+//! as usual, and this module reconciles it with the remote folder over SFTP.
+//! The content last agreed with the server is kept per note under
+//! `.hermes/sftp/base`, so a sync can tell which side changed; edits made while
+//! the server is unreachable stay queued as local changes until a later sync
+//! gets through. Notes changed on both sides go through the three-way merge and
+//! fall back to a conflicted copy of the remote version.
+//!
+//! Shells out to the user's `sftp`, so their SSH config, agent and keychain
+//! supply the credentials; Hermes never stores a password.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter};
+
+use crate::{hermes_dir, ignore, merge, note_lock, note_path, read_project_names, sync_status, workspace_lock, TAB_KEYS};
+
+/// Settings-store key holding `{ local workspace path: SftpConfig }`.
+const SFTP_SETTING: &str = "sftpWorkspaces";
+pub const SFTP_SYNC_EVENT: &str = "sftp-sync-completed";
+const SYNC_INTERVAL: Duration = Duration::from_secs(120);
+const DEFAULT_PORT: u16 = 22;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SftpConfig {
+    host: String,
+    #[serde(default)]
+    user: String,
+    #[serde(default = "default_port")]
+    port: u16,
+    remote_path: String,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+#[derive(Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpSyncReport {
+    workspace_path: String,
+    /// The server could not be reached; `pending` waits for the next attempt.
+    offline: bool,
+    pending: Vec<String>,
+    uploaded: Vec<String>,
+    downloaded: Vec<String>,
+    conflicts: Vec<String>,
+}
+
+fn configs(app: &AppHandle) -> Map<String, Value> {
+    crate::read_setting(app, SFTP_SETTING)
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default()
+}
+
+fn config(app: &AppHandle, workspace_path: &str) -> Option<SftpConfig> {
+    serde_json::from_value(configs(app).get(workspace_path)?.clone()).ok()
+}
+
+/// Quotes an argument for an sftp batch file.
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Runs an sftp batch and returns stdout. Commands prefixed with `-` may fail
+/// without aborting the batch.
+fn sftp(config: &SftpConfig, batch: &str) -> Result<String, String> {
+    let destination = if config.user.is_empty() {
+        config.host.clone()
+    } else {
+        format!("{}@{}", config.user, config.host)
+    };
+    let mut child = Command::new("sftp")
+        .args(["-b", "-", "-o", "BatchMode=yes", "-o", "ConnectTimeout=10", "-P"])
+        .arg(config.port.to_string())
+        .arg("--")
+        .arg(destination)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to run sftp: {err}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(batch.as_bytes())
+            .map_err(|err| format!("Failed sending commands to sftp: {err}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to run sftp: {err}"))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(format!(
+        "sftp to {} failed: {}",
+        config.host,
+        if stderr.is_empty() { "unknown error" } else { &stderr }
+    ))
+}
+
+fn remote_file(config: &SftpConfig, key: &str) -> String {
+    format!("{}/{key}.md", config.remote_path.trim_end_matches('/'))
+}
+
+/// Project folders on the server, from `ls -l` output.
+fn remote_projects(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter(|line| line.starts_with('d'))
+        .filter_map(|line| {
+            let name = line.split_whitespace().skip(8).collect::<Vec<_>>().join(" ");
+            let name = name.rsplit('/').next()?.to_string();
+            (!name.is_empty() && !name.starts_with('.')).then_some(name)
+        })
+        .collect()
+}
+
+fn state_dir(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("sftp")
+}
+
+/// Local file of a note key, `tab` or `project/tab`.
+fn local_file(workspace_path: &str, key: &str) -> PathBuf {
+    match key.split_once('/') {
+        Some((project, tab)) => note_path(&Path::new(workspace_path).join(project).to_string_lossy(), tab),
+        None => note_path(workspace_path, key),
+    }
+}
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    fs::write(path, content).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+fn set_base(workspace_path: &str, key: &str, content: Option<&str>) -> Result<(), String> {
+    let path = state_dir(workspace_path).join("base").join(format!("{key}.md"));
+    match content {
+        Some(content) => write(&path, content),
+        None => {
+            let _ = fs::remove_file(path);
+            Ok(())
+        }
+    }
+}
+
+/// Note keys of the workspace and its projects that may be synced.
+fn note_keys(workspace_path: &str, projects: &BTreeSet<String>) -> Vec<String> {
+    let mut keys: Vec<String> = TAB_KEYS.iter().map(|tab| tab.to_string()).collect();
+    for project in projects {
+        keys.extend(TAB_KEYS.iter().map(|tab| format!("{project}/{tab}")));
+    }
+    // Locked notes would sync as ciphertext the merge can't handle.
+    keys.retain(|key| {
+        let file = local_file(workspace_path, key);
+        !ignore::is_ignored(&file) && !note_lock::is_locked_file(&file)
+    });
+    keys
+}
+
+/// Keys whose local content differs from what the server last had.
+fn pending(workspace_path: &str) -> Vec<String> {
+    let projects = read_project_names(workspace_path).unwrap_or_default().into_iter().collect();
+    let base_dir = state_dir(workspace_path).join("base");
+    note_keys(workspace_path, &projects)
+        .into_iter()
+        .filter(|key| read(&local_file(workspace_path, key)) != read(&base_dir.join(format!("{key}.md"))))
+        .collect()
+}
+
+fn conflicted_copy_path(file: &Path, host: &str) -> PathBuf {
+    let stem = file.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    file.with_file_name(format!("{stem} ({host} conflicted copy).md"))
+}
+
+fn sync(workspace_path: &str, config: &SftpConfig) -> Result<SftpSyncReport, String> {
+    let mut report = SftpSyncReport {
+        workspace_path: workspace_path.to_string(),
+        ..Default::default()
+    };
+    let remote_root = config.remote_path.trim_end_matches('/');
+
+    // Pull: list the server's projects, then fetch every note into a staging folder.
+    let listing = match sftp(config, &format!("-mkdir {}\nls -l {}\n", quote(remote_root), quote(remote_root))) {
+        Ok(listing) => listing,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            report.offline = true;
+            report.pending = pending(workspace_path);
+            return Ok(report);
+        }
+    };
+    let mut projects: BTreeSet<String> = read_project_names(workspace_path).unwrap_or_default().into_iter().collect();
+    projects.extend(remote_projects(&listing));
+    let keys = note_keys(workspace_path, &projects);
+
+    let incoming = state_dir(workspace_path).join("incoming");
+    let _ = fs::remove_dir_all(&incoming);
+    let mut batch = String::new();
+    for key in &keys {
+        let staged = incoming.join(format!("{key}.md"));
+        if let Some(dir) = staged.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+        }
+        batch.push_str(&format!(
+            "-get {} {}\n",
+            quote(&remote_file(config, key)),
+            quote(&staged.to_string_lossy())
+        ));
+    }
+    sftp(config, &batch)?;
+
+    // Reconcile each note against the content both sides last agreed on.
+    let base_dir = state_dir(workspace_path).join("base");
+    let mut uploads: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let mut changed_folders = BTreeSet::new();
+    {
+        let _write_lock = workspace_lock::acquire(workspace_path)?;
+        for key in &keys {
+            let file = local_file(workspace_path, key);
+            let local = read(&file);
+            let remote = read(&incoming.join(format!("{key}.md")));
+            let base = read(&base_dir.join(format!("{key}.md")));
+
+            if local == remote {
+                set_base(workspace_path, key, local.as_deref())?;
+                continue;
+            }
+            if remote == base {
+                uploads.insert(key.clone(), local);
+                continue;
+            }
+            changed_folders.insert(file.parent().map(Path::to_path_buf).unwrap_or_default());
+            if local == base {
+                match &remote {
+                    Some(content) => write(&file, content)?,
+                    None => {
+                        let _ = fs::remove_file(&file);
+                    }
+                }
+                set_base(workspace_path, key, remote.as_deref())?;
+                report.downloaded.push(key.clone());
+                continue;
+            }
+
+            // Changed on both sides.
+            let kept = match (local, remote) {
+                (Some(local), Some(remote)) => {
+                    let outcome = merge::merge(base.as_deref().unwrap_or_default(), &local, &remote);
+                    match outcome.merged {
+                        Some(merged) => merged,
+                        None => {
+                            write(&conflicted_copy_path(&file, &config.host), &remote)?;
+                            report.conflicts.push(key.clone());
+                            local
+                        }
+                    }
+                }
+                // Edited on one side, deleted on the other: keep the surviving edit.
+                (Some(content), None) | (None, Some(content)) => content,
+                (None, None) => continue,
+            };
+            write(&file, &kept)?;
+            uploads.insert(key.clone(), Some(kept));
+        }
+    }
+    let _ = fs::remove_dir_all(&incoming);
+
+    // Push local changes; their bases only move once the server has them.
+    if !uploads.is_empty() {
+        let mut batch = String::new();
+        for (key, content) in &uploads {
+            let remote = remote_file(config, key);
+            match content {
+                Some(_) => {
+                    if let Some((project, _)) = key.split_once('/') {
+                        batch.push_str(&format!("-mkdir {}\n", quote(&format!("{remote_root}/{project}"))));
+                    }
+                    let local = local_file(workspace_path, key);
+                    batch.push_str(&format!("put {} {}\n", quote(&local.to_string_lossy()), quote(&remote)));
+                }
+                None => batch.push_str(&format!("-rm {}\n", quote(&remote))),
+            }
+        }
+        if let Err(err) = sftp(config, &batch) {
+            tracing::warn!("{}", err);
+            report.offline = true;
+            report.pending = uploads.into_keys().collect();
+            return Ok(report);
+        }
+        for (key, content) in uploads {
+            set_base(workspace_path, &key, content.as_deref())?;
+            report.uploaded.push(key);
+        }
+    }
+
+    for folder in changed_folders {
+        if let Err(err) = crate::read_workspace_pages(&folder.to_string_lossy()) {
+            tracing::warn!("{}", String::from(err));
+        }
+    }
+    Ok(report)
+}
+
+fn sync_and_report(app: &AppHandle, workspace_path: &str) -> Result<SftpSyncReport, String> {
+    sync_status::syncing(app, "sftp", 0, 0);
+    let result = config(app, workspace_path)
+        .ok_or_else(|| "This workspace is not on an SFTP server.".to_string())
+        .and_then(|config| sync(workspace_path, &config));
+    let outcome = match &result {
+        Ok(report) if report.offline => Err(format!(
+            "Offline; {} changes wait for the next sync.",
+            report.pending.len()
+        )),
+        Ok(report) => Ok(report.conflicts.clone()),
+        Err(err) => Err(err.clone()),
+    };
+    sync_status::finish(app, "sftp", outcome);
+    let report = result?;
+    if let Err(err) = app.emit(SFTP_SYNC_EVENT, report.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(report)
+}
+
+/// Keeps every SFTP workspace in sync in the background, retrying queued changes.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(SYNC_INTERVAL);
+        for workspace_path in configs(&app).keys() {
+            if let Err(err) = sync_and_report(&app, workspace_path) {
+                tracing::warn!("{}", err);
+            }
+        }
+    });
+}
+
+/// Backs `workspace_path` with `remote_path` on an SSH server and syncs it once.
+#[tauri::command]
+pub async fn configure_sftp_workspace(
+    app: AppHandle,
+    workspace_path: String,
+    host: String,
+    user: Option<String>,
+    port: Option<u16>,
+    remote_path: String,
+) -> Result<SftpSyncReport, String> {
+    let host = host.trim().to_string();
+    if host.is_empty() || host.starts_with('-') {
+        return Err("Enter the server's host name.".to_string());
+    }
+    if remote_path.trim().is_empty() {
+        return Err("Enter the folder on the server.".to_string());
+    }
+    let config = SftpConfig {
+        host,
+        user: user.unwrap_or_default().trim().to_string(),
+        port: port.unwrap_or(DEFAULT_PORT),
+        remote_path: remote_path.trim().to_string(),
+    };
+
+    let mut all = configs(&app);
+    let value = serde_json::to_value(&config).map_err(|err| format!("Failed encoding SFTP settings: {err}"))?;
+    all.insert(workspace_path.clone(), value);
+    crate::write_setting(&app, SFTP_SETTING, Value::Object(all))?;
+
+    tauri::async_runtime::spawn_blocking(move || sync_and_report(&app, &workspace_path))
+        .await
+        .map_err(|err| format!("SFTP sync task failed: {err}"))?
+}
+
+/// Stops syncing `workspace_path`; its local copy stays as a regular workspace.
+#[tauri::command]
+pub fn disable_sftp_workspace(app: AppHandle, workspace_path: String) -> Result<(), String> {
+    let mut all = configs(&app);
+    all.remove(&workspace_path);
+    let _ = fs::remove_dir_all(state_dir(&workspace_path));
+    crate::write_setting(&app, SFTP_SETTING, Value::Object(all))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub async fn sync_sftp_workspace(app: AppHandle, workspace_path: String) -> Result<SftpSyncReport, String> {
+    tauri::async_runtime::spawn_blocking(move || sync_and_report(&app, &workspace_path))
+        .await
+        .map_err(|err| format!("SFTP sync task failed: {err}"))?
+}
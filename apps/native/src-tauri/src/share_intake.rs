@@ -0,0 +1,189 @@
+//! Text, links, images and files shared to Hermes from other apps on iOS and
+//! Android.
+//!
+//! Shares are queued in a `ShareInbox` folder as a `<id>.json` describing
+//! each and, for an image or file, its contents next to it, written first.
+//! On iOS the share extension writes them to the app group container both
+//! targets are entitled to, since it runs in its own process and can't reach
+//! the app's sandbox. On Android `ShareActivity`, from the `hermes-mobile`
+//! plugin, handles `ACTION_SEND` and `ACTION_SEND_MULTIPLE`, copying each
+//! `content://` stream into the queue in the app's data folder, one item per
+//! stream, and opens the app. The app takes them in on launch and whenever it
+//! comes back to the foreground,
+//! appending each to the inbox note with where it came from and removing it
+//! from the queue. Images and files are copied into the attachments first, as
+//! a desktop paste would be. A share that can't be appended yet, say because
+//! no workspace is set up, stays queued.
+//!
+//! The iOS share extension's sources are in the `hermes-mobile` plugin, which
+//! also adds the app group entitlement to the app. The generated Xcode project
+//! isn't kept in the tree, so its `project.yml` has to include the
+//! extension's target, as described in `ShareExtension/project.yml`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use crate::{attachments, capture, path_safety};
+
+/// App group shared with the share extension.
+#[cfg(target_os = "ios")]
+const APP_GROUP: &str = "group.com.dearhermes.app";
+const QUEUE_FOLDER: &str = "ShareInbox";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+enum Shared {
+    Text { text: String },
+    Url { url: String, title: Option<String> },
+    Image { file: String, name: Option<String>, caption: Option<String> },
+    File { file: String, name: Option<String>, caption: Option<String> },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SharedItem {
+    #[serde(flatten)]
+    content: Shared,
+    /// Name of the app the share came from, when the system tells.
+    source_app: Option<String>,
+    /// Milliseconds since the epoch.
+    shared_at: Option<i64>,
+}
+
+/// The queue folder in the app group container, if the app is entitled to it.
+#[cfg(target_os = "ios")]
+fn queue_dir(_app: &AppHandle) -> Option<PathBuf> {
+    use objc2_foundation::{NSFileManager, NSString};
+
+    let container = NSFileManager::defaultManager()
+        .containerURLForSecurityApplicationGroupIdentifier(&NSString::from_str(APP_GROUP))?;
+    let path = container.path()?.to_string();
+    Some(Path::new(&path).join(QUEUE_FOLDER))
+}
+
+/// The queue folder in the app's data folder, where `ShareActivity` copies shares.
+#[cfg(target_os = "android")]
+fn queue_dir(app: &AppHandle) -> Option<PathBuf> {
+    use tauri::Manager;
+
+    app.path().app_data_dir().ok().map(|dir| dir.join(QUEUE_FOLDER))
+}
+
+/// `_via …_` line of the entry: the share sheet, the app and when it was shared.
+fn provenance(item: &SharedItem) -> String {
+    let mut source = "share sheet".to_string();
+    if let Some(app) = item.source_app.as_deref().map(str::trim).filter(|app| !app.is_empty()) {
+        source.push_str(&format!(" from {app}"));
+    }
+    if let Some(shared_at) = item.shared_at.and_then(chrono::DateTime::from_timestamp_millis) {
+        let shared_at = shared_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+        source.push_str(&format!(", shared {shared_at}"));
+    }
+    source
+}
+
+/// Copies a queued image or file into the attachments of the workspace root,
+/// where the inbox note lives, and returns the path to link it by.
+fn attach(app: &AppHandle, dir: &Path, file: &str, name: Option<&str>) -> Result<String, String> {
+    path_safety::check_file_name(file)?;
+    let (workspace_path, _) = capture::inbox_location(app)?;
+    let name = name.map(str::trim).filter(|name| !name.is_empty()).unwrap_or(file);
+    attachments::import_file(&workspace_path, &dir.join(file), name)
+}
+
+fn with_caption(body: String, caption: Option<&str>) -> String {
+    match caption.map(str::trim).filter(|caption| !caption.is_empty()) {
+        Some(caption) => format!("{caption}\n\n{body}"),
+        None => body,
+    }
+}
+
+/// The markdown appended for one share.
+fn entry_text(app: &AppHandle, dir: &Path, content: &Shared) -> Result<String, String> {
+    match content {
+        Shared::Text { text } => Ok(text.clone()),
+        Shared::Url { url, title } => {
+            let url = tauri::Url::parse(url.trim()).map_err(|err| format!("Invalid shared link {url}: {err}"))?;
+            Ok(match title.as_deref().map(str::trim).filter(|title| !title.is_empty()) {
+                Some(title) => format!("[{}]({url})", title.replace(['[', ']'], "")),
+                None => format!("<{url}>"),
+            })
+        }
+        Shared::Image { file, name, caption } => {
+            let link = attach(app, dir, file, name.as_deref())?;
+            Ok(with_caption(format!("![]({link})"), caption.as_deref()))
+        }
+        Shared::File { file, name, caption } => {
+            let link = attach(app, dir, file, name.as_deref())?;
+            let label = link.rsplit('/').next().unwrap_or(&link).replace(['[', ']'], "");
+            Ok(with_caption(format!("[{label}]({link})"), caption.as_deref()))
+        }
+    }
+}
+
+fn remove(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        tracing::warn!("Failed removing {}: {err}", path.display());
+    }
+}
+
+/// Appends one queued share to the inbox, removing it once it is in.
+fn take(app: &AppHandle, dir: &Path, path: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+    let item: SharedItem = match serde_json::from_str(&text) {
+        Ok(item) => item,
+        Err(err) => {
+            // It will never parse; keeping it would retry it on every launch.
+            remove(path);
+            return Err(format!("Dropped unreadable share {}: {err}", path.display()));
+        }
+    };
+    let entry = entry_text(app, dir, &item.content)?;
+    capture::append_to_inbox(app, &entry, Some(&provenance(&item)))?;
+    remove(path);
+    if let Shared::Image { file, .. } | Shared::File { file, .. } = &item.content {
+        remove(&dir.join(file));
+    }
+    Ok(())
+}
+
+/// Appends every queued share to the inbox in the order they were shared and
+/// returns how many were taken in.
+pub fn drain(app: &AppHandle) -> usize {
+    let Some(dir) = queue_dir(app) else {
+        return 0;
+    };
+    let mut queued: Vec<(SystemTime, PathBuf)> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .map(|path| (fs::metadata(&path).and_then(|metadata| metadata.modified()).unwrap_or(UNIX_EPOCH), path))
+        .collect();
+    queued.sort();
+
+    let mut taken = 0;
+    for (_, path) in queued {
+        match take(app, &dir, &path) {
+            Ok(()) => taken += 1,
+            Err(err) => tracing::warn!("{}", err),
+        }
+    }
+    if taken > 0 {
+        tracing::info!("Took {taken} shared items into the inbox");
+    }
+    taken
+}
+
+/// Takes in whatever the share extension queued since the app last looked.
+#[tauri::command]
+pub async fn intake_shared_items(app: AppHandle) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || drain(&app))
+        .await
+        .map_err(|err| format!("Share intake task failed: {err}"))
+}
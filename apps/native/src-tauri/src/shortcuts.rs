@@ -0,0 +1,193 @@
+//! System-wide keyboard shortcuts, persisted in the settings store.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_store::StoreExt;
+
+use crate::{capture, focus_main_window, SETTINGS_STORE_FILE};
+
+/// Settings-store key holding `{ action: accelerator }`; an empty accelerator disables the action.
+const SHORTCUTS_SETTING: &str = "shortcuts";
+pub const SHORTCUT_TRIGGERED_EVENT: &str = "shortcut-triggered";
+
+/// Actions that can be bound, with their default accelerators.
+const ACTIONS: [(&str, &str); 3] = [
+    ("toggle-window", "CommandOrControl+Shift+H"),
+    ("quick-capture", "CommandOrControl+Shift+Space"),
+    ("new-daily-note", "CommandOrControl+Shift+D"),
+];
+
+/// Accelerators currently registered with the OS, by action.
+#[derive(Default)]
+pub struct RegisteredShortcuts(Mutex<HashMap<String, String>>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutBinding {
+    action: String,
+    accelerator: String,
+    registered: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct ShortcutTriggered {
+    action: String,
+}
+
+fn configured_bindings(app: &AppHandle) -> HashMap<String, String> {
+    let stored = crate::read_setting(app, SHORTCUTS_SETTING)
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default();
+
+    ACTIONS
+        .iter()
+        .map(|(action, default)| {
+            let accelerator = stored
+                .get(*action)
+                .and_then(|value| value.as_str())
+                .unwrap_or(default)
+                .to_string();
+            (action.to_string(), accelerator)
+        })
+        .collect()
+}
+
+fn run_action(app: &AppHandle, action: &str) {
+    match action {
+        "toggle-window" => {
+            let Some(window) = app.get_webview_window("main") else {
+                return;
+            };
+            let visible = window.is_visible().unwrap_or(false);
+            let focused = window.is_focused().unwrap_or(false);
+            if visible && focused {
+                let _ = window.hide();
+            } else {
+                focus_main_window(app);
+            }
+        }
+        "quick-capture" => {
+            if let Err(err) = capture::open_quick_capture_window(app) {
+                tracing::warn!("{}", err);
+            }
+        }
+        _ => {
+            // Editor actions (e.g. new daily note) are carried out by the frontend.
+            focus_main_window(app);
+            let payload = ShortcutTriggered {
+                action: action.to_string(),
+            };
+            if let Err(err) = app.emit(SHORTCUT_TRIGGERED_EVENT, payload) {
+                tracing::warn!("Failed to emit event: {err}");
+            }
+        }
+    }
+}
+
+fn bind(app: &AppHandle, action: &str, accelerator: &str) -> Result<(), String> {
+    let action_name = action.to_string();
+    app.global_shortcut()
+        .on_shortcut(accelerator, move |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                run_action(app, &action_name);
+            }
+        })
+        .map_err(|err| format!("Failed to register {accelerator}: {err}"))
+}
+
+fn unbind(app: &AppHandle, accelerator: &str) {
+    if let Err(err) = app.global_shortcut().unregister(accelerator) {
+        tracing::warn!("Failed to unregister {accelerator}: {err}");
+    }
+}
+
+/// Registers every configured shortcut; conflicts are logged and reported by `list_shortcuts`.
+pub fn init(app: &AppHandle) {
+    let state = app.state::<RegisteredShortcuts>();
+    let mut registered = state.0.lock().unwrap();
+    for (action, accelerator) in configured_bindings(app) {
+        if accelerator.trim().is_empty() {
+            continue;
+        }
+        match bind(app, &action, &accelerator) {
+            Ok(()) => {
+                registered.insert(action, accelerator);
+            }
+            Err(err) => tracing::warn!("{}", err),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_shortcuts(app: AppHandle, state: tauri::State<'_, RegisteredShortcuts>) -> Vec<ShortcutBinding> {
+    let registered = state.0.lock().unwrap();
+    let bindings = configured_bindings(&app);
+    ACTIONS
+        .iter()
+        .map(|(action, _)| {
+            let accelerator = bindings.get(*action).cloned().unwrap_or_default();
+            ShortcutBinding {
+                action: action.to_string(),
+                registered: registered.get(*action) == Some(&accelerator) && !accelerator.is_empty(),
+                accelerator,
+            }
+        })
+        .collect()
+}
+
+/// Binds `action` to `accelerator` (empty to disable) and persists the choice.
+#[tauri::command]
+pub fn register_shortcut(
+    app: AppHandle,
+    state: tauri::State<'_, RegisteredShortcuts>,
+    action: String,
+    accelerator: String,
+) -> Result<(), String> {
+    if !ACTIONS.iter().any(|(name, _)| *name == action) {
+        return Err(format!("Unknown shortcut action '{action}'"));
+    }
+    let accelerator = accelerator.trim().to_string();
+
+    let mut registered = state.0.lock().unwrap();
+    if !accelerator.is_empty() {
+        if let Some((other, _)) = registered
+            .iter()
+            .find(|(other, existing)| **other != action && existing.eq_ignore_ascii_case(&accelerator))
+        {
+            return Err(format!("{accelerator} is already used by '{other}'"));
+        }
+    }
+
+    let previous = registered.remove(&action);
+    if let Some(previous) = &previous {
+        unbind(&app, previous);
+    }
+
+    if !accelerator.is_empty() {
+        if let Err(err) = bind(&app, &action, &accelerator) {
+            // Restore the old binding so a typo doesn't leave the action unbound.
+            if let Some(previous) = previous {
+                if bind(&app, &action, &previous).is_ok() {
+                    registered.insert(action, previous);
+                }
+            }
+            return Err(err);
+        }
+        registered.insert(action.clone(), accelerator.clone());
+    }
+
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("Failed opening settings store: {err}"))?;
+    let mut stored = store
+        .get(SHORTCUTS_SETTING)
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default();
+    stored.insert(action, serde_json::Value::String(accelerator));
+    store.set(SHORTCUTS_SETTING, serde_json::Value::Object(stored));
+    store.save().map_err(|err| format!("Failed saving settings: {err}"))
+}
@@ -0,0 +1,407 @@
+//! Reading a note aloud with the system voice, so it can be proofread by ear.
+//!
+//! The note is spoken as plain text: markdown syntax and code blocks are left
+//! out. Each word the voice reaches is reported as a `speech://word` event with
+//! its range in the note, in UTF-16 units like the editor's, and playback
+//! changes as `speech://state`. macOS speaks through `NSSpeechSynthesizer`,
+//! whose delegate is called on the main thread. Windows speaks through SAPI,
+//! driven by a PowerShell script that reads pause, resume and stop from stdin
+//! and prints where each word starts. Linux has no voice to ask.
+
+use std::fs;
+use std::sync::Mutex;
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::{note_lock, note_path, validate_tab_key};
+
+#[cfg(any(target_os = "macos", windows))]
+pub const WORD_EVENT: &str = "speech://word";
+pub const STATE_EVENT: &str = "speech://state";
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpeakOptions {
+    /// A voice identifier on macOS or voice name on Windows; the system's default otherwise.
+    pub voice: Option<String>,
+    /// Words per minute.
+    pub rate: Option<f32>,
+}
+
+#[cfg(any(target_os = "macos", windows))]
+#[derive(Clone, Serialize)]
+struct WordSpoken {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct SpeechState {
+    state: &'static str,
+}
+
+/// For each UTF-16 unit of the text being spoken, where it came from in the note.
+static SPOKEN: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+/// The note as it should sound, with the note offset of each UTF-16 unit.
+fn script(content: &str) -> (String, Vec<usize>) {
+    // UTF-16 offset of every byte boundary, so ranges convert in one lookup.
+    let mut units = vec![0; content.len() + 1];
+    let mut count = 0;
+    for (index, ch) in content.char_indices() {
+        units[index] = count;
+        count += ch.len_utf16();
+    }
+    units[content.len()] = count;
+
+    let mut text = String::new();
+    let mut offsets = Vec::new();
+    let mut push = |piece: &str, start: usize, end: usize| {
+        let (from, to) = (units[start], units[end].max(units[start] + 1));
+        for (index, _) in piece.encode_utf16().enumerate() {
+            offsets.push((from + index).min(to - 1));
+        }
+        text.push_str(piece);
+    };
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut in_code_block = false;
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(piece) | Event::Code(piece) if !in_code_block => push(&piece, range.start, range.end),
+            Event::SoftBreak => push(" ", range.start, range.end),
+            Event::HardBreak
+            | Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell) => {
+                push("\n", range.end, range.end)
+            }
+            _ => {}
+        }
+    }
+    (text, offsets)
+}
+
+fn emit_state(app: &AppHandle, state: &'static str) {
+    if let Err(err) = app.emit(STATE_EVENT, SpeechState { state }) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+/// Reports the word at `start..start + length` of the spoken text by its range in the note.
+#[cfg(any(target_os = "macos", windows))]
+fn word(app: &AppHandle, start: usize, length: usize) {
+    let spoken = SPOKEN.lock().unwrap();
+    let (Some(&from), Some(&last)) = (spoken.get(start), spoken.get(start + length.max(1) - 1)) else {
+        return;
+    };
+    if let Err(err) = app.emit(WORD_EVENT, WordSpoken { start: from, end: last + 1 }) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn finished(app: &AppHandle) {
+    emit_state(app, "stopped");
+}
+
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+mod native {
+    use std::cell::RefCell;
+    use std::sync::mpsc;
+
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2::{define_class, msg_send, AnyThread, DefinedClass, MainThreadMarker, MainThreadOnly};
+    use objc2_app_kit::{NSSpeechBoundary, NSSpeechSynthesizer, NSSpeechSynthesizerDelegate};
+    use objc2_foundation::{NSObject, NSObjectProtocol, NSRange, NSString};
+    use tauri::AppHandle;
+
+    use super::SpeakOptions;
+
+    struct Ivars {
+        app: AppHandle,
+    }
+
+    define_class!(
+        #[unsafe(super(NSObject))]
+        #[thread_kind = MainThreadOnly]
+        #[name = "HermesSpeechDelegate"]
+        #[ivars = Ivars]
+        struct Delegate;
+
+        unsafe impl NSObjectProtocol for Delegate {}
+
+        unsafe impl NSSpeechSynthesizerDelegate for Delegate {
+            #[unsafe(method(speechSynthesizer:willSpeakWord:ofString:))]
+            fn will_speak_word(&self, _sender: &NSSpeechSynthesizer, range: NSRange, _text: &NSString) {
+                super::word(&self.ivars().app, range.location, range.length);
+            }
+
+            #[unsafe(method(speechSynthesizer:didFinishSpeaking:))]
+            fn did_finish_speaking(&self, _sender: &NSSpeechSynthesizer, _finished: bool) {
+                super::finished(&self.ivars().app);
+            }
+        }
+    );
+
+    impl Delegate {
+        fn new(app: AppHandle, mtm: MainThreadMarker) -> Retained<Self> {
+            let this = Self::alloc(mtm).set_ivars(Ivars { app });
+            unsafe { msg_send![super(this), init] }
+        }
+    }
+
+    type Session = (Retained<NSSpeechSynthesizer>, Retained<Delegate>);
+
+    thread_local! {
+        // Only touched on the main thread, where the delegate is called.
+        static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+    }
+
+    /// Runs `action` on the main thread and waits for its result.
+    fn on_main<T: Send + 'static>(
+        app: &AppHandle,
+        action: impl FnOnce(MainThreadMarker) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        let (sender, receiver) = mpsc::channel();
+        app.run_on_main_thread(move || {
+            let mtm = MainThreadMarker::new().expect("run_on_main_thread runs on the main thread");
+            let _ = sender.send(action(mtm));
+        })
+        .map_err(|err| format!("Failed reaching the main thread: {err}"))?;
+        receiver
+            .recv()
+            .map_err(|_| "The main thread dropped the speech request".to_string())?
+    }
+
+    pub fn speak(app: &AppHandle, text: String, options: SpeakOptions) -> Result<(), String> {
+        let handle = app.clone();
+        on_main(app, move |mtm| {
+            stop_session();
+            let voice = options.voice.as_deref().map(NSString::from_str);
+            let synthesizer = NSSpeechSynthesizer::initWithVoice(NSSpeechSynthesizer::alloc(), voice.as_deref())
+                .ok_or_else(|| "That voice isn't installed.".to_string())?;
+            if let Some(rate) = options.rate {
+                synthesizer.setRate(rate);
+            }
+            let delegate = Delegate::new(handle, mtm);
+            synthesizer.setDelegate(Some(ProtocolObject::from_ref(&*delegate)));
+            if !synthesizer.startSpeakingString(&NSString::from_str(&text)) {
+                return Err("The system voice couldn't start speaking.".to_string());
+            }
+            SESSION.with(|session| *session.borrow_mut() = Some((synthesizer, delegate)));
+            Ok(())
+        })
+    }
+
+    fn stop_session() {
+        if let Some((synthesizer, _)) = SESSION.with(|session| session.borrow_mut().take()) {
+            synthesizer.setDelegate(None);
+            synthesizer.stopSpeaking();
+        }
+    }
+
+    pub fn control(app: &AppHandle, command: &'static str) -> Result<(), String> {
+        on_main(app, move |_| {
+            if command == "stop" {
+                stop_session();
+                return Ok(());
+            }
+            SESSION.with(|session| {
+                let session = session.borrow();
+                let (synthesizer, _) = session.as_ref().ok_or_else(|| "Nothing is being read aloud.".to_string())?;
+                match command {
+                    "pause" => synthesizer.pauseSpeakingAtBoundary(NSSpeechBoundary::WordBoundary),
+                    _ => synthesizer.continueSpeaking(),
+                }
+                Ok(())
+            })
+        })
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::windows::process::CommandExt;
+    use std::process::{Child, ChildStdin, Command, Stdio};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+
+    use base64::Engine;
+    use tauri::AppHandle;
+
+    use super::SpeakOptions;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    /// Reads the text (base64 UTF-8), voice and rate from the first three
+    /// lines of stdin, then one command per line; prints "<position> <length>"
+    /// for each word. Events are polled because PowerShell can't run script
+    /// handlers on SAPI's threads.
+    const SCRIPT: &str = r#"
+Add-Type -AssemblyName System.Speech
+$in = [Console]::In
+$text = [Text.Encoding]::UTF8.GetString([Convert]::FromBase64String($in.ReadLine()))
+$voice = $in.ReadLine()
+$rate = [int]$in.ReadLine()
+$synth = New-Object System.Speech.Synthesis.SpeechSynthesizer
+if ($voice) { $synth.SelectVoice($voice) }
+$synth.Rate = $rate
+Register-ObjectEvent $synth SpeakProgress -SourceIdentifier word | Out-Null
+Register-ObjectEvent $synth SpeakCompleted -SourceIdentifier done | Out-Null
+$synth.SpeakAsync($text) | Out-Null
+$command = $in.ReadLineAsync()
+while ($true) {
+  Start-Sleep -Milliseconds 50
+  foreach ($raised in @(Get-Event)) {
+    if ($raised.SourceIdentifier -eq 'done') { exit }
+    $progress = $raised.SourceEventArgs
+    [Console]::Out.WriteLine("$($progress.CharacterPosition) $($progress.CharacterCount)")
+    Remove-Event -EventIdentifier $raised.EventIdentifier
+  }
+  if ($command.IsCompleted) {
+    switch ($command.Result) {
+      'pause' { $synth.Pause() }
+      'resume' { $synth.Resume() }
+      default { $synth.SpeakAsyncCancelAll(); exit }
+    }
+    $command = $in.ReadLineAsync()
+  }
+}
+"#;
+
+    static SESSION: Mutex<Option<(Child, ChildStdin)>> = Mutex::new(None);
+    /// Bumped whenever a reading starts or is stopped, so a script that is
+    /// still winding down doesn't report into the next one.
+    static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+    /// SAPI's rate runs from -10 to 10 around a default of about 180 words per minute.
+    fn sapi_rate(words_per_minute: f32) -> i32 {
+        ((words_per_minute - 180.0) / 20.0).round().clamp(-10.0, 10.0) as i32
+    }
+
+    pub fn speak(app: &AppHandle, text: String, options: SpeakOptions) -> Result<(), String> {
+        stop_session();
+        let mut child = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn()
+            .map_err(|err| format!("Failed to start PowerShell: {err}"))?;
+        let mut stdin = child.stdin.take().ok_or_else(|| "PowerShell has no stdin".to_string())?;
+        let stdout = child.stdout.take().ok_or_else(|| "PowerShell has no stdout".to_string())?;
+        let voice = options.voice.unwrap_or_default().replace(['\r', '\n'], "");
+        let rate = options.rate.map(sapi_rate).unwrap_or(0);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+        writeln!(stdin, "{encoded}\n{voice}\n{rate}").map_err(|err| format!("Failed sending text to PowerShell: {err}"))?;
+
+        let app = app.clone();
+        let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        let current = move || GENERATION.load(Ordering::SeqCst) == generation;
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let mut parts = line.split_whitespace().map(str::parse::<usize>);
+                if let (Some(Ok(start)), Some(Ok(length)), true) = (parts.next(), parts.next(), current()) {
+                    super::word(&app, start, length);
+                }
+            }
+            if current() {
+                super::finished(&app);
+            }
+        });
+        *SESSION.lock().unwrap() = Some((child, stdin));
+        Ok(())
+    }
+
+    fn stop_session() {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+        if let Some((mut child, mut stdin)) = SESSION.lock().unwrap().take() {
+            let _ = writeln!(stdin, "stop");
+            drop(stdin);
+            let _ = child.wait();
+        }
+    }
+
+    pub fn control(_app: &AppHandle, command: &'static str) -> Result<(), String> {
+        if command == "stop" {
+            stop_session();
+            return Ok(());
+        }
+        let mut session = SESSION.lock().unwrap();
+        let (_, stdin) = session.as_mut().ok_or_else(|| "Nothing is being read aloud.".to_string())?;
+        writeln!(stdin, "{command}").map_err(|err| format!("Failed sending {command} to PowerShell: {err}"))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod native {
+    use tauri::AppHandle;
+
+    use super::SpeakOptions;
+
+    const UNSUPPORTED: &str = "Reading aloud is available on macOS and Windows.";
+
+    pub fn speak(_app: &AppHandle, _text: String, _options: SpeakOptions) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn control(_app: &AppHandle, _command: &'static str) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+/// Reads the tab aloud from the top, replacing anything already being read.
+/// The commands are async so they don't wait on the main thread from it.
+#[tauri::command]
+pub async fn speak_note(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    options: Option<SpeakOptions>,
+) -> Result<(), String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    crate::autosave::flush(&app, Some(&workspace_path)).map_err(String::from)?;
+    let path = note_path(&workspace_path, &tab_key);
+    let content = fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+    if note_lock::is_locked(&content) {
+        return Err(format!("The {tab_key} note is locked."));
+    }
+    let (text, offsets) = script(&content);
+    if text.trim().is_empty() {
+        return Err("There is nothing to read in this note.".to_string());
+    }
+    *SPOKEN.lock().unwrap() = offsets;
+    native::speak(&app, text, options.unwrap_or_default())?;
+    emit_state(&app, "speaking");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pause_speaking(app: AppHandle) -> Result<(), String> {
+    native::control(&app, "pause")?;
+    emit_state(&app, "paused");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_speaking(app: AppHandle) -> Result<(), String> {
+    native::control(&app, "resume")?;
+    emit_state(&app, "speaking");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_speaking(app: AppHandle) -> Result<(), String> {
+    native::control(&app, "stop")?;
+    emit_state(&app, "stopped");
+    Ok(())
+}
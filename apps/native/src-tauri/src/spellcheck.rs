@@ -0,0 +1,626 @@
+//! Spell checking against Hunspell dictionaries.
+//!
+//! A language is a pair of Hunspell files, `<lang>.dic` and `<lang>.aff`,
+//! imported into `<app data>/dictionaries` or installed where the system keeps
+//! them (`/usr/share/hunspell` on Linux, `Library/Spelling` on macOS). The
+//! reader covers what checking single words needs: the word list expanded by
+//! its prefix and suffix rules, the three flag styles, `NEEDAFFIX` and
+//! `FORBIDDENWORD`, and `TRY` and `REP` for suggestions. Compounding isn't
+//! supported, so in languages that build words from others the compounds are
+//! reported until they are added to a dictionary.
+//!
+//! Words added from a misspelling go in the workspace's
+//! `.hermes/dictionary.txt`, one per line, and count as spelled right in every
+//! language.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{canonical_workspace, hermes_dir};
+
+const DICTIONARIES_FOLDER: &str = "dictionaries";
+const WORKSPACE_DICTIONARY: &str = "dictionary.txt";
+#[cfg(target_os = "linux")]
+const SYSTEM_DIRS: &[&str] = &["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"];
+#[cfg(target_os = "macos")]
+const SYSTEM_DIRS: &[&str] = &["/Library/Spelling"];
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const SYSTEM_DIRS: &[&str] = &[];
+/// Letters tried in suggestions when the `.aff` has no `TRY`.
+const DEFAULT_TRY: &str = "esianrtolcdugmphbyfvkwzqxj'";
+const MAX_SUGGESTIONS: usize = 5;
+const MAX_WORD_CHARS: usize = 64;
+
+/// Loaded dictionaries by `.dic` path; expanding one takes a moment.
+static LOADED: Mutex<Option<HashMap<PathBuf, Arc<Dictionary>>>> = Mutex::new(None);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Misspelling {
+    /// UTF-16 offsets into the checked text, as the editor counts.
+    pub start: usize,
+    pub end: usize,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellLanguage {
+    pub lang: String,
+    /// Imported into the app's data folder, so it can be deleted from Hermes.
+    pub imported: bool,
+}
+
+#[derive(Clone, Copy)]
+enum FlagStyle {
+    Char,
+    Long,
+    Num,
+}
+
+fn parse_flags(style: FlagStyle, flags: &str) -> Vec<String> {
+    match style {
+        FlagStyle::Char => flags.chars().map(String::from).collect(),
+        FlagStyle::Long => flags
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| pair.iter().collect())
+            .collect(),
+        FlagStyle::Num => flags.split(',').map(|flag| flag.trim().to_string()).collect(),
+    }
+}
+
+enum Condition {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl Condition {
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::Char(expected) => *expected == ch,
+            Condition::Set { chars, negated } => chars.contains(&ch) != *negated,
+        }
+    }
+}
+
+/// A condition like `[^aeiou]y`: one entry per character it matches.
+fn parse_condition(condition: &str) -> Vec<Condition> {
+    let mut parsed = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(ch) = chars.next() {
+        parsed.push(match ch {
+            '.' => Condition::Any,
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|ch| *ch != ']').collect();
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0);
+                }
+                Condition::Set { chars: set, negated }
+            }
+            ch => Condition::Char(ch),
+        });
+    }
+    parsed
+}
+
+struct Affix {
+    prefix: bool,
+    /// Whether it combines with an affix of the other kind.
+    cross: bool,
+    strip: Vec<char>,
+    add: String,
+    condition: Vec<Condition>,
+}
+
+impl Affix {
+    /// `root` with the affix applied, if its condition allows.
+    fn apply(&self, root: &[char]) -> Option<String> {
+        if root.len() < self.condition.len() || root.len() <= self.strip.len() {
+            return None;
+        }
+        let (tested, stem) = if self.prefix {
+            (&root[..self.condition.len()], root.strip_prefix(self.strip.as_slice())?)
+        } else {
+            (&root[root.len() - self.condition.len()..], root.strip_suffix(self.strip.as_slice())?)
+        };
+        if !self.condition.iter().zip(tested).all(|(condition, ch)| condition.matches(*ch)) {
+            return None;
+        }
+        let stem: String = stem.iter().collect();
+        Some(match self.prefix {
+            true => format!("{}{stem}", self.add),
+            false => format!("{stem}{}", self.add),
+        })
+    }
+}
+
+struct Dictionary {
+    words: HashSet<String>,
+    try_chars: Vec<char>,
+    /// Common misspellings and their fixes, tried first for suggestions.
+    replacements: Vec<(String, String)>,
+}
+
+/// Decodes a dictionary file in the `SET` encoding of its `.aff`.
+fn decode(bytes: &[u8], latin1: bool) -> String {
+    match latin1 {
+        true => bytes.iter().map(|byte| char::from(*byte)).collect(),
+        false => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|err| format!("Failed reading {}: {err}", path.display()))
+}
+
+fn load(dic: &Path) -> Result<Dictionary, String> {
+    let aff_bytes = read_bytes(&dic.with_extension("aff"))?;
+    let latin1 = String::from_utf8_lossy(&aff_bytes).lines().any(|line| {
+        let mut parts = line.split_whitespace();
+        parts.next() == Some("SET") && parts.next().is_some_and(|set| set.eq_ignore_ascii_case("ISO8859-1"))
+    });
+
+    let mut style = FlagStyle::Char;
+    let mut affixes: HashMap<String, Vec<Affix>> = HashMap::new();
+    let mut cross: HashMap<(bool, String), bool> = HashMap::new();
+    let mut need_affix = None;
+    let mut forbidden = None;
+    let mut try_chars: Vec<char> = DEFAULT_TRY.chars().collect();
+    let mut replacements = Vec::new();
+    for line in decode(&aff_bytes, latin1).lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["FLAG", "long", ..] => style = FlagStyle::Long,
+            ["FLAG", "num", ..] => style = FlagStyle::Num,
+            ["TRY", chars, ..] => try_chars = chars.chars().collect(),
+            ["REP", from, to, ..] => replacements.push((from.replace('_', " "), to.replace('_', " "))),
+            ["NEEDAFFIX", flag, ..] => need_affix = Some(flag.to_string()),
+            ["FORBIDDENWORD", flag, ..] => forbidden = Some(flag.to_string()),
+            [kind @ ("PFX" | "SFX"), flag, combines @ ("Y" | "N"), count] if count.parse::<usize>().is_ok() => {
+                cross.insert((*kind == "PFX", flag.to_string()), *combines == "Y");
+            }
+            [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                let prefix = *kind == "PFX";
+                let none = |value: &str| if value == "0" { String::new() } else { value.to_string() };
+                let add = add.split('/').next().unwrap_or_default();
+                affixes.entry(flag.to_string()).or_default().push(Affix {
+                    prefix,
+                    cross: cross.get(&(prefix, flag.to_string())).copied().unwrap_or(false),
+                    strip: none(strip).chars().collect(),
+                    add: none(add),
+                    condition: parse_condition(rest.first().copied().unwrap_or(".")),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut words = HashSet::new();
+    let mut forbidden_words = Vec::new();
+    for line in decode(&read_bytes(dic)?, latin1).lines().skip(1) {
+        let Some(entry) = line.split_whitespace().next() else {
+            continue;
+        };
+        let (word, flags) = match entry.split_once('/') {
+            Some((word, flags)) => (word, parse_flags(style, flags)),
+            None => (entry, Vec::new()),
+        };
+        if forbidden.as_ref().is_some_and(|flag| flags.contains(flag)) {
+            forbidden_words.push(word.to_string());
+            continue;
+        }
+        if !need_affix.as_ref().is_some_and(|flag| flags.contains(flag)) {
+            words.insert(word.to_string());
+        }
+        let root: Vec<char> = word.chars().collect();
+        let rules: Vec<&Affix> = flags.iter().filter_map(|flag| affixes.get(flag)).flatten().collect();
+        for suffix in rules.iter().filter(|affix| !affix.prefix) {
+            let Some(form) = suffix.apply(&root) else {
+                continue;
+            };
+            if suffix.cross {
+                let form: Vec<char> = form.chars().collect();
+                for prefix in rules.iter().filter(|affix| affix.prefix && affix.cross) {
+                    words.extend(prefix.apply(&form));
+                }
+            }
+            words.insert(form);
+        }
+        for prefix in rules.iter().filter(|affix| affix.prefix) {
+            words.extend(prefix.apply(&root));
+        }
+    }
+    for word in forbidden_words {
+        words.remove(&word);
+    }
+    Ok(Dictionary {
+        words,
+        try_chars,
+        replacements,
+    })
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl Dictionary {
+    /// Whether `word` is spelled right, allowing a capital at the start of a
+    /// sentence and words written in capitals.
+    fn knows(&self, word: &str) -> bool {
+        if self.words.contains(word) {
+            return true;
+        }
+        let lower = word.to_lowercase();
+        if word.chars().skip(1).all(|ch| !ch.is_uppercase()) {
+            return self.words.contains(&lower);
+        }
+        word.chars().all(|ch| !ch.is_lowercase()) && (self.words.contains(&lower) || self.words.contains(&capitalize(&lower)))
+    }
+
+    /// Spellings one edit away from `word`, or made of two known words, with
+    /// the `REP` fixes first.
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let chars: Vec<char> = lower.chars().collect();
+        let mut candidates = Vec::new();
+        for (from, to) in &self.replacements {
+            for (at, _) in lower.match_indices(from.as_str()) {
+                candidates.push(format!("{}{to}{}", &lower[..at], &lower[at + from.len()..]));
+            }
+        }
+        let join = |parts: &[&[char]]| parts.iter().flat_map(|part| part.iter()).collect::<String>();
+        for at in 0..chars.len() {
+            for ch in &self.try_chars {
+                candidates.push(join(&[&chars[..at], &[*ch], &chars[at + 1..]]));
+            }
+            candidates.push(join(&[&chars[..at], &chars[at + 1..]]));
+            if at + 1 < chars.len() {
+                candidates.push(join(&[&chars[..at], &[chars[at + 1], chars[at]], &chars[at + 2..]]));
+            }
+        }
+        for at in 0..=chars.len() {
+            for ch in &self.try_chars {
+                candidates.push(join(&[&chars[..at], &[*ch], &chars[at..]]));
+            }
+        }
+        for at in 1..chars.len() {
+            candidates.push(join(&[&chars[..at], &[' '], &chars[at..]]));
+        }
+
+        let all_caps = word.chars().all(|ch| !ch.is_lowercase());
+        let capital = word.chars().next().is_some_and(char::is_uppercase);
+        let mut suggestions: Vec<String> = Vec::new();
+        for candidate in candidates {
+            if candidate == lower || !candidate.split(' ').all(|part| !part.is_empty() && self.knows(part)) {
+                continue;
+            }
+            let candidate = match (all_caps && chars.len() > 1, capital) {
+                (true, _) => candidate.to_uppercase(),
+                (false, true) => capitalize(&candidate),
+                (false, false) => candidate,
+            };
+            if !suggestions.contains(&candidate) {
+                suggestions.push(candidate);
+            }
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+        suggestions
+    }
+}
+
+fn dictionaries_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(DICTIONARIES_FOLDER))
+        .map_err(|err| format!("Failed resolving app data folder: {err}"))
+}
+
+/// Where dictionaries are looked for: imported ones first, then the system's.
+fn search_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dictionaries_dir(app).into_iter().collect();
+    #[cfg(target_os = "macos")]
+    if let Ok(home) = app.path().home_dir() {
+        dirs.push(home.join("Library/Spelling"));
+    }
+    dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+/// The languages with both dictionary files in `dir`.
+fn languages_in(dir: &Path) -> Vec<String> {
+    let mut languages: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "dic") && path.with_extension("aff").is_file())
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .collect();
+    languages.sort();
+    languages
+}
+
+fn check_lang(lang: &str) -> Result<String, String> {
+    let lang = lang.trim().replace('-', "_");
+    if lang.is_empty() || lang.len() > 32 || !lang.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_') {
+        return Err(format!("'{lang}' is not a dictionary language; use a code like en_US."));
+    }
+    Ok(lang)
+}
+
+/// The `.dic` for `lang`, or failing that the first for a region of it, so
+/// `en` finds `en_US`.
+fn find(app: &AppHandle, lang: &str) -> Option<PathBuf> {
+    let dirs = search_dirs(app);
+    let exact = dirs.iter().find_map(|dir| languages_in(dir).into_iter().find(|found| found == lang).map(|found| (dir, found)));
+    let regional = || {
+        let prefix = format!("{lang}_");
+        dirs.iter()
+            .find_map(|dir| languages_in(dir).into_iter().find(|found| found.starts_with(&prefix)).map(|found| (dir, found)))
+    };
+    exact.or_else(regional).map(|(dir, found)| dir.join(format!("{found}.dic")))
+}
+
+fn dictionary(app: &AppHandle, lang: &str) -> Result<Arc<Dictionary>, String> {
+    let lang = check_lang(lang)?;
+    let dic = find(app, &lang).ok_or_else(|| format!("No {lang} dictionary is installed."))?;
+    if let Some(loaded) = LOADED.lock().unwrap().get_or_insert_with(HashMap::new).get(&dic) {
+        return Ok(loaded.clone());
+    }
+    let loaded = Arc::new(load(&dic)?);
+    tracing::info!("Loaded {} words for {lang} from {}", loaded.words.len(), dic.display());
+    LOADED.lock().unwrap().get_or_insert_with(HashMap::new).insert(dic, loaded.clone());
+    Ok(loaded)
+}
+
+fn workspace_dictionary_path(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join(WORKSPACE_DICTIONARY)
+}
+
+/// The words added to the workspace's dictionary, sorted.
+fn read_words(workspace_path: &str) -> Vec<String> {
+    let mut words: Vec<String> = fs::read_to_string(workspace_dictionary_path(workspace_path))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect();
+    words.sort();
+    words.dedup();
+    words
+}
+
+fn write_words(workspace_path: &str, words: &[String]) -> Result<(), String> {
+    let path = workspace_dictionary_path(workspace_path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    }
+    let mut text = words.join("\n");
+    text.push('\n');
+    fs::write(&path, text).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+fn check_word(word: &str) -> Result<String, String> {
+    let word = word.trim();
+    if word.is_empty() || word.chars().count() > MAX_WORD_CHARS || word.chars().any(char::is_whitespace) {
+        return Err(format!("'{word}' can't be added to the dictionary."));
+    }
+    Ok(word.to_string())
+}
+
+/// Whether the whitespace-separated `chunk` is a link, address or path
+/// rather than prose.
+fn is_link_like(chunk: &str) -> bool {
+    let inner = chunk.trim_matches(|ch: char| !ch.is_alphanumeric());
+    chunk.contains("://") || chunk.contains('@') || inner.starts_with("www.") || inner.contains(['/', '\\', '.', '_'])
+}
+
+/// The words of `text` worth checking, with their byte ranges. Code blocks,
+/// inline code, links and words with digits or inner capitals are skipped.
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut in_code = false;
+        let mut chunk_start = 0;
+        for chunk in line.split_inclusive(char::is_whitespace) {
+            let at = start + chunk_start;
+            chunk_start += chunk.len();
+            if is_link_like(chunk.trim()) {
+                in_code ^= chunk.matches('`').count() % 2 == 1;
+                continue;
+            }
+            let mut word: Option<usize> = None;
+            let mut chars = chunk.char_indices().peekable();
+            while let Some((index, ch)) = chars.next() {
+                if ch == '`' {
+                    in_code = !in_code;
+                }
+                let inner_apostrophe = matches!(ch, '\'' | '’')
+                    && word.is_some()
+                    && chars.peek().is_some_and(|(_, next)| next.is_alphabetic());
+                if !in_code && (ch.is_alphanumeric() || inner_apostrophe) {
+                    word.get_or_insert(index);
+                    continue;
+                }
+                if let Some(word_start) = word.take() {
+                    found.push((at + word_start, at + index));
+                }
+            }
+            if let Some(word_start) = word {
+                found.push((at + word_start, at + chunk.len()));
+            }
+        }
+    }
+    found.retain(|(start, end)| {
+        let word = &text[*start..*end];
+        let mixed = word.chars().skip(1).any(char::is_uppercase) && word.chars().any(char::is_lowercase);
+        word.chars().count() > 1 && !word.chars().any(|ch| ch.is_numeric()) && !mixed
+    });
+    found
+}
+
+fn check(app: &AppHandle, text: &str, lang: &str, workspace_path: Option<&str>) -> Result<Vec<Misspelling>, String> {
+    let dictionary = dictionary(app, lang)?;
+    let added: HashSet<String> = workspace_path
+        .map(|path| read_words(&canonical_workspace(path)))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let mut suggestions: HashMap<String, Vec<String>> = HashMap::new();
+    let mut misspellings = Vec::new();
+    let (mut byte, mut utf16) = (0, 0);
+    for (start, end) in words(text) {
+        let word = text[start..end].replace('’', "'");
+        if added.contains(&word.to_lowercase()) || dictionary.knows(&word) {
+            continue;
+        }
+        utf16 += text[byte..start].encode_utf16().count();
+        let length = text[start..end].encode_utf16().count();
+        byte = start;
+        let suggested = suggestions.entry(word.clone()).or_insert_with(|| dictionary.suggest(&word)).clone();
+        misspellings.push(Misspelling {
+            start: utf16,
+            end: utf16 + length,
+            word,
+            suggestions: suggested,
+        });
+    }
+    Ok(misspellings)
+}
+
+/// Misspelled words of `text` in `lang`, such as `en_US`, with up to five
+/// suggestions each. Words in the dictionary of `workspace_path` are accepted.
+#[tauri::command]
+pub async fn check_text(
+    app: AppHandle,
+    text: String,
+    lang: String,
+    workspace_path: Option<String>,
+) -> Result<Vec<Misspelling>, String> {
+    tauri::async_runtime::spawn_blocking(move || check(&app, &text, &lang, workspace_path.as_deref()))
+        .await
+        .map_err(|err| format!("Spell check task failed: {err}"))?
+}
+
+/// Every language with a dictionary, imported or from the system.
+#[tauri::command]
+pub fn get_spellcheck_languages(app: AppHandle) -> Vec<SpellLanguage> {
+    let imported_dir = dictionaries_dir(&app).ok();
+    let mut languages: Vec<SpellLanguage> = Vec::new();
+    for dir in search_dirs(&app) {
+        let imported = imported_dir.as_ref() == Some(&dir);
+        for lang in languages_in(&dir) {
+            if !languages.iter().any(|language| language.lang == lang) {
+                languages.push(SpellLanguage { lang, imported });
+            }
+        }
+    }
+    languages.sort_by(|a, b| a.lang.cmp(&b.lang));
+    languages
+}
+
+/// Copies the `.dic` at `path` and the `.aff` beside it into the app's
+/// dictionaries, named after the file, and returns the language.
+#[tauri::command]
+pub async fn import_dictionary(app: AppHandle, path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let source = PathBuf::from(&path);
+        if source.extension().is_none_or(|extension| extension != "dic") {
+            return Err("Choose the dictionary's .dic file; its .aff file must be next to it.".to_string());
+        }
+        let lang = check_lang(&source.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default())?;
+        // Fails early on files that aren't a dictionary.
+        load(&source)?;
+        let dir = dictionaries_dir(&app)?;
+        fs::create_dir_all(&dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+        for extension in ["dic", "aff"] {
+            let from = source.with_extension(extension);
+            let to = dir.join(format!("{lang}.{extension}"));
+            fs::copy(&from, &to).map_err(|err| format!("Failed copying {}: {err}", from.display()))?;
+        }
+        LOADED.lock().unwrap().get_or_insert_with(HashMap::new).remove(&dir.join(format!("{lang}.dic")));
+        tracing::info!("Imported the {lang} dictionary");
+        Ok(lang)
+    })
+    .await
+    .map_err(|err| format!("Dictionary import task failed: {err}"))?
+}
+
+/// Deletes an imported dictionary; the system's are left alone.
+#[tauri::command]
+pub fn delete_dictionary(app: AppHandle, lang: String) -> Result<(), String> {
+    let lang = check_lang(&lang)?;
+    let dir = dictionaries_dir(&app)?;
+    for extension in ["dic", "aff"] {
+        let path = dir.join(format!("{lang}.{extension}"));
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed deleting {}: {err}", path.display()));
+            }
+            _ => {}
+        }
+    }
+    LOADED.lock().unwrap().get_or_insert_with(HashMap::new).remove(&dir.join(format!("{lang}.dic")));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_dictionary_words(workspace_path: String) -> Vec<String> {
+    read_words(&canonical_workspace(&workspace_path))
+}
+
+#[tauri::command]
+pub fn add_dictionary_word(app: AppHandle, workspace_path: String, word: String) -> Result<Vec<String>, String> {
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let word = check_word(&word)?;
+    let mut words = read_words(&workspace_path);
+    if !words.contains(&word) {
+        words.push(word);
+        words.sort();
+        write_words(&workspace_path, &words)?;
+    }
+    Ok(words)
+}
+
+#[tauri::command]
+pub fn remove_dictionary_word(app: AppHandle, workspace_path: String, word: String) -> Result<Vec<String>, String> {
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let mut words = read_words(&workspace_path);
+    words.retain(|existing| existing != word.trim());
+    write_words(&workspace_path, &words)?;
+    Ok(words)
+}
@@ -0,0 +1,99 @@
+//! Opt-in Spotlight metadata for note files.
+//!
+//! When enabled, every saved note gets `kMDItemTitle`/`kMDItemKeywords` extended
+//! attributes so it shows up in system search under its real title instead of `coral.md`.
+
+use std::path::Path;
+
+use tauri::AppHandle;
+
+use crate::{extract_title, ignore, note_path, TAB_KEYS};
+
+/// Settings-store key for the opt-in toggle.
+pub const SPOTLIGHT_SETTING: &str = "spotlightMetadata";
+
+#[cfg(target_os = "macos")]
+const TITLE_ATTR: &str = "com.apple.metadata:kMDItemTitle";
+#[cfg(target_os = "macos")]
+const KEYWORDS_ATTR: &str = "com.apple.metadata:kMDItemKeywords";
+
+pub fn is_enabled(app: &AppHandle) -> bool {
+    crate::read_setting(app, SPOTLIGHT_SETTING)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// Collects `#tags` from note content, ignoring markdown headings.
+pub fn extract_keywords(content: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for token in content.split_whitespace() {
+        let Some(tag) = token.strip_prefix('#') else {
+            continue;
+        };
+        let tag: String = tag
+            .chars()
+            .take_while(|ch| ch.is_alphanumeric() || *ch == '-' || *ch == '_' || *ch == '/')
+            .collect();
+        if tag.is_empty() || tag.chars().all(|ch| ch.is_ascii_digit()) {
+            continue;
+        }
+        if !keywords.iter().any(|existing| existing.eq_ignore_ascii_case(&tag)) {
+            keywords.push(tag);
+        }
+    }
+    keywords
+}
+
+pub fn write_note_metadata(file_path: &Path, content: &str) -> Result<(), String> {
+    let title = extract_title(content);
+    let keywords = extract_keywords(content);
+
+    #[cfg(target_os = "macos")]
+    {
+        if title.is_empty() {
+            hermes_core::xattr::remove(file_path, TITLE_ATTR)?;
+        } else {
+            hermes_core::xattr::write_plist(file_path, TITLE_ATTR, &serde_json::json!(title))?;
+        }
+
+        if keywords.is_empty() {
+            hermes_core::xattr::remove(file_path, KEYWORDS_ATTR)?;
+        } else {
+            hermes_core::xattr::write_plist(file_path, KEYWORDS_ATTR, &serde_json::json!(keywords))?;
+        }
+        return Ok(());
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (file_path, title, keywords);
+        Ok(())
+    }
+}
+
+fn refresh_workspace(workspace_path: &str) -> Result<usize, String> {
+    let mut written = 0;
+
+    for tab in TAB_KEYS {
+        let file_path = note_path(workspace_path, tab);
+        if !file_path.exists() || ignore::is_ignored(&file_path) {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(&file_path)
+            .map_err(|err| format!("Failed reading {}: {err}", file_path.display()))?;
+        write_note_metadata(&file_path, &content)?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Rewrites Spotlight metadata for every note in the workspace, returning how many were tagged.
+#[tauri::command]
+pub fn refresh_spotlight_metadata(workspace_path: String) -> Result<usize, String> {
+    if !cfg!(target_os = "macos") {
+        return Err("Spotlight metadata is only available on macOS.".to_string());
+    }
+    refresh_workspace(&workspace_path)
+}
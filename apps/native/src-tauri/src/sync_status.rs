@@ -0,0 +1,102 @@
+//! One sync indicator for every backend.
+//!
+//! Each backend reports when it starts, how far it got and how it ended; the
+//! latest status per backend is kept for `get_sync_status` and broadcast as a
+//! `sync://status` event.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const SYNC_STATUS_EVENT: &str = "sync://status";
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncState {
+    Idle,
+    Syncing,
+    /// Synced, but some notes kept a conflicted copy to resolve.
+    Conflict,
+    Error,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    /// `git`, `lan`, `relay`, `sftp`, `dropbox`, `gdrive` or `icloud`.
+    backend: String,
+    state: SyncState,
+    /// Notes handled so far while syncing; `total` is 0 when not known up front.
+    done: usize,
+    total: usize,
+    conflicts: Vec<String>,
+    error: Option<String>,
+    last_synced_unix: Option<u64>,
+}
+
+/// Latest status of each backend used this session.
+#[derive(Default)]
+pub struct SyncStatuses(Mutex<BTreeMap<String, SyncStatus>>);
+
+fn publish(app: &AppHandle, backend: &str, update: impl FnOnce(&mut SyncStatus)) {
+    let statuses = app.state::<SyncStatuses>();
+    let mut statuses = statuses.0.lock().unwrap();
+    let status = statuses.entry(backend.to_string()).or_insert_with(|| SyncStatus {
+        backend: backend.to_string(),
+        state: SyncState::Idle,
+        done: 0,
+        total: 0,
+        conflicts: Vec::new(),
+        error: None,
+        last_synced_unix: None,
+    });
+    update(status);
+    if let Err(err) = app.emit(SYNC_STATUS_EVENT, status.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
+
+/// Marks `backend` as syncing, `done` of `total` notes in.
+pub fn syncing(app: &AppHandle, backend: &str, done: usize, total: usize) {
+    publish(app, backend, |status| {
+        status.state = SyncState::Syncing;
+        status.done = done;
+        status.total = total;
+        status.error = None;
+    });
+}
+
+/// Records how a sync of `backend` ended: the notes left in conflict, or the error.
+pub fn finish(app: &AppHandle, backend: &str, outcome: Result<Vec<String>, String>) {
+    publish(app, backend, |status| {
+        status.done = 0;
+        status.total = 0;
+        match outcome {
+            Ok(conflicts) => {
+                status.state = if conflicts.is_empty() {
+                    SyncState::Idle
+                } else {
+                    SyncState::Conflict
+                };
+                status.conflicts = conflicts;
+                status.error = None;
+                status.last_synced_unix = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .ok();
+            }
+            Err(err) => {
+                status.state = SyncState::Error;
+                status.error = Some(err);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_sync_status(app: AppHandle) -> Vec<SyncStatus> {
+    app.state::<SyncStatuses>().0.lock().unwrap().values().cloned().collect()
+}
@@ -0,0 +1,281 @@
+//! Changing the file names and extensions of the tab notes.
+//!
+//! The names themselves come from `hermes_core::tab_files`, which `note_path`
+//! goes through, so loading, saving and indexing all use the mapped names;
+//! `migrate_tab_filenames` changes the map and renames the notes already on
+//! disk to match.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hermes_core::tab_files::{config, read_config, write_config, TabConfig, CONFIG_FILE, DEFAULT_EXTENSIONS};
+pub use hermes_core::tab_files::{file_name, is_plain_text, note_stem, stem, tab_of};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::{
+    canonical_workspace, file_hashes, hermes_dir, note_path, notes_dir, path_safety, project_list, run_sqlite_script, sql_escape,
+    sqlite_path, workspace_lock, CommandError, TAB_KEYS,
+};
+
+const MAX_NAME_CHARS: usize = 64;
+const MAX_EXTENSION_CHARS: usize = 10;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedNote {
+    /// Path of the project below the workspace root; empty for the root.
+    project: String,
+    tab: String,
+    from: String,
+    to: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabFilesMigrated {
+    /// The file name of every tab, without its extension.
+    filenames: BTreeMap<String, String>,
+    renamed: Vec<RenamedNote>,
+}
+
+/// Refuses the path of a project, whose tab config is its workspace's.
+fn check_workspace_root(workspace_path: &str) -> Result<(), String> {
+    if !Path::new(workspace_path).is_dir() {
+        return Err(format!("{workspace_path} is not a folder."));
+    }
+    if project_list::project_of(workspace_path).is_some_and(|project| !project.is_empty()) {
+        return Err("Change tab file names from the workspace, not one of its projects.".to_string());
+    }
+    Ok(())
+}
+
+/// The workspace root and its projects, each with its folder.
+fn folders(app: &AppHandle, workspace_path: &str) -> Result<Vec<(String, String)>, CommandError> {
+    let mut projects = vec![String::new()];
+    projects.extend(project_list::names(app, workspace_path)?);
+    Ok(projects
+        .into_iter()
+        .map(|project| {
+            let folder = match project.as_str() {
+                "" => workspace_path.to_string(),
+                project => Path::new(workspace_path).join(project).to_string_lossy().to_string(),
+            };
+            (project, folder)
+        })
+        .collect())
+}
+
+/// Checks `filenames` and returns the names that differ from their tab's key,
+/// trimmed and without `.md`.
+fn normalize(filenames: BTreeMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+    let mut map = BTreeMap::new();
+    for (tab, name) in filenames {
+        crate::validate_tab_key(&tab)?;
+        let name = name.trim();
+        let name = name.strip_suffix(".md").unwrap_or(name).trim();
+        if name.is_empty() || name == tab {
+            continue;
+        }
+        if name.starts_with('.') || name.chars().count() > MAX_NAME_CHARS {
+            return Err(format!("Invalid file name '{name}' for the {tab} tab."));
+        }
+        path_safety::check_file_name(&format!("{name}.md"))?;
+        map.insert(tab, name.to_string());
+    }
+    // Case-insensitive volumes would give two tabs one file.
+    let mut taken = HashMap::new();
+    for tab in TAB_KEYS {
+        let name = map.get(tab).map_or(tab, String::as_str);
+        if let Some(other) = taken.insert(name.to_lowercase(), tab) {
+            return Err(format!("The {other} and {tab} tabs can't both be saved as {name}.md."));
+        }
+    }
+    Ok(map)
+}
+
+/// The file name of every tab of `workspace_path`, without its extension.
+#[tauri::command]
+pub fn get_tab_filenames(workspace_path: String) -> BTreeMap<String, String> {
+    let workspace_path = canonical_workspace(&workspace_path);
+    TAB_KEYS.iter().map(|tab| (tab.to_string(), stem(&workspace_path, tab))).collect()
+}
+
+/// Gives the tabs of `workspace_path` the file names in `filenames` and
+/// renames the notes of the workspace and every project to match. Nothing is
+/// renamed when any new name is taken by a file that isn't moving away.
+#[tauri::command]
+pub fn migrate_tab_filenames(
+    app: AppHandle,
+    workspace_path: String,
+    filenames: BTreeMap<String, String>,
+) -> Result<TabFilesMigrated, CommandError> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    check_workspace_root(&workspace_path)?;
+    let map = normalize(filenames)?;
+    crate::read_only::ensure_writable(&app, &workspace_path)?;
+    // So its projects read the new names from this workspace's config.
+    project_list::register_root(&workspace_path);
+    let folders = folders(&app, &workspace_path)?;
+
+    // Edits still waiting to be saved go into the files under their old names.
+    crate::workspaces::flush_under(&app, &workspace_path)?;
+    let _locks = folders
+        .iter()
+        .map(|(_, folder)| workspace_lock::acquire(folder))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut moves = Vec::new();
+    for (project, folder) in &folders {
+        let renames: Vec<(&str, PathBuf, PathBuf)> = TAB_KEYS
+            .iter()
+            .map(|tab| {
+                let old = note_path(folder, tab);
+                // The note keeps its extension.
+                let extension = old.extension().map(|extension| extension.to_string_lossy().to_string()).unwrap_or_default();
+                let new_name = format!("{}.{extension}", map.get(*tab).map_or(*tab, String::as_str));
+                (*tab, old, notes_dir(folder).join(new_name))
+            })
+            .filter(|(_, old, new)| old != new && old.exists())
+            .collect();
+        for (tab, old, new) in &renames {
+            let moving_away = renames.iter().any(|(_, other, _)| other == new);
+            let same_file = old.to_string_lossy().eq_ignore_ascii_case(&new.to_string_lossy());
+            if new.exists() && !moving_away && !same_file {
+                return Err(format!(
+                    "Can't rename the {tab} note of '{}' to {}: that file already exists.",
+                    if project.is_empty() { "the workspace" } else { project },
+                    new.display()
+                )
+                .into());
+            }
+        }
+        moves.extend(renames.into_iter().map(|(tab, old, new)| (project.clone(), folder.clone(), tab, old, new)));
+    }
+
+    // Through a temporary name first, so tabs can swap names and a name can change case.
+    let staged = |old: &Path| {
+        let name = old.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        old.with_file_name(format!("{name}.hermes-rename"))
+    };
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let undo = |done: &[(PathBuf, PathBuf)]| {
+        for (from, to) in done.iter().rev() {
+            if let Err(err) = fs::rename(to, from) {
+                tracing::warn!("Failed restoring {}: {err}", from.display());
+            }
+        }
+    };
+    let steps = moves
+        .iter()
+        .map(|(_, _, _, old, _)| (old.clone(), staged(old)))
+        .chain(moves.iter().map(|(_, _, _, old, new)| (staged(old), new.clone())));
+    for (from, to) in steps {
+        if let Err(err) = fs::rename(&from, &to) {
+            undo(&done);
+            return Err(format!("Failed renaming {}: {err}", from.display()).into());
+        }
+        done.push((from, to));
+    }
+
+    let config_file = hermes_dir(&workspace_path).join(CONFIG_FILE);
+    let config = TabConfig {
+        filenames: map,
+        ..read_config(&config_file)
+    };
+    if let Err(err) = write_config(&config_file, &config) {
+        undo(&done);
+        return Err(err.into());
+    }
+
+    let mut renamed = Vec::new();
+    for (project, folder, tab, old, new) in moves {
+        file_hashes::forget(&old);
+        if let Ok(content) = fs::read_to_string(&new) {
+            file_hashes::remember(&new, &content);
+        }
+        let db_path = sqlite_path(&folder);
+        let to = file_name(&folder, tab);
+        if db_path.exists() {
+            let script = format!(
+                "UPDATE note_index SET file_path = '{}' WHERE tab_key = '{}';",
+                sql_escape(&to),
+                sql_escape(tab)
+            );
+            if let Err(err) = run_sqlite_script(&db_path, &script) {
+                tracing::warn!("{}", err);
+            }
+        }
+        renamed.push(RenamedNote {
+            project,
+            tab: tab.to_string(),
+            from: old.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+            to,
+        });
+    }
+
+    tracing::info!("Renamed {} notes in {workspace_path} to new tab file names", renamed.len());
+    let change = crate::changes::Change::TabFilesRenamed {
+        workspace_path: workspace_path.clone(),
+    };
+    crate::changes::emit(&app, change);
+    Ok(TabFilesMigrated {
+        filenames: get_tab_filenames(workspace_path),
+        renamed,
+    })
+}
+
+/// The note extensions of `workspace_path`, the one new notes get first.
+#[tauri::command]
+pub fn get_note_extensions(workspace_path: String) -> Vec<String> {
+    config(&canonical_workspace(&workspace_path)).extensions().into_iter().map(str::to_string).collect()
+}
+
+/// Sets the note extensions of `workspace_path`, refusing to drop one a note
+/// of the workspace or its projects still has.
+#[tauri::command]
+pub fn set_note_extensions(app: AppHandle, workspace_path: String, extensions: Vec<String>) -> Result<Vec<String>, CommandError> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    check_workspace_root(&workspace_path)?;
+    let mut list: Vec<String> = Vec::new();
+    for extension in extensions {
+        let extension = extension.trim().trim_start_matches('.').to_ascii_lowercase();
+        if extension.is_empty() || list.contains(&extension) {
+            continue;
+        }
+        if extension.len() > MAX_EXTENSION_CHARS || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid note extension '{extension}'.").into());
+        }
+        list.push(extension);
+    }
+    if list.is_empty() {
+        return Err("Keep at least one note extension.".to_string().into());
+    }
+    crate::read_only::ensure_writable(&app, &workspace_path)?;
+    project_list::register_root(&workspace_path);
+
+    for (project, folder) in folders(&app, &workspace_path)? {
+        for tab in TAB_KEYS {
+            let path = note_path(&folder, tab);
+            let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+            if path.exists() && !list.contains(&extension) {
+                return Err(format!(
+                    "The {tab} note of '{}' is a .{extension} file; rename it before removing .{extension}.",
+                    if project.is_empty() { "the workspace" } else { &project }
+                )
+                .into());
+            }
+        }
+    }
+
+    let config_file = hermes_dir(&workspace_path).join(CONFIG_FILE);
+    let mut config = read_config(&config_file);
+    config.extensions = match list.iter().eq(DEFAULT_EXTENSIONS.iter()) {
+        true => Vec::new(),
+        false => list.clone(),
+    };
+    write_config(&config_file, &config)?;
+    tracing::info!("Note extensions of {workspace_path} are now {}", list.join(", "));
+    Ok(list)
+}
@@ -0,0 +1,83 @@
+//! The dock badge counting due and overdue tasks, from the `note_tasks`
+//! table `hermes_core::tasks` keeps in each index.
+
+use tauri::AppHandle;
+#[cfg(desktop)]
+use tauri::Manager;
+
+#[cfg(desktop)]
+use crate::{file_open, read_project_names, run_sqlite_query, sqlite_path};
+
+#[cfg(desktop)]
+fn due_count_in(workspace_path: &str, today: &str) -> usize {
+    let db_path = sqlite_path(workspace_path);
+    if !db_path.exists() {
+        return 0;
+    }
+    // Older indexes may predate the tasks table; treat that as no tasks.
+    match run_sqlite_query(&db_path, &format!("SELECT COUNT(*) AS due FROM note_tasks WHERE due <= '{today}';")) {
+        Ok(rows) => rows
+            .first()
+            .and_then(|row| row.get("due"))
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0) as usize,
+        Err(_) => 0,
+    }
+}
+
+/// Due and overdue tasks across the workspace root and its projects.
+#[cfg(desktop)]
+fn due_count(app: &AppHandle) -> usize {
+    let Some(workspace) = file_open::configured_workspace(app) else {
+        return 0;
+    };
+    let root = workspace.to_string_lossy().to_string();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let mut count = due_count_in(&root, &today);
+    for project in read_project_names(&root).unwrap_or_default() {
+        count += due_count_in(&workspace.join(&project).to_string_lossy(), &today);
+    }
+    count
+}
+
+/// Red dot used as the taskbar overlay, since Windows has no numeric badge.
+#[cfg(windows)]
+fn overlay_icon() -> tauri::image::Image<'static> {
+    const SIZE: u32 = 16;
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    let rgba = (0..SIZE * SIZE)
+        .flat_map(|index| {
+            let (x, y) = ((index % SIZE) as f32, (index / SIZE) as f32);
+            let inside = (x - center).powi(2) + (y - center).powi(2) <= (SIZE as f32 / 2.0).powi(2);
+            if inside {
+                [0xE5, 0x48, 0x4D, 0xFF]
+            } else {
+                [0, 0, 0, 0]
+            }
+        })
+        .collect();
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
+/// Shows the number of due/overdue tasks on the dock icon (taskbar overlay on Windows).
+#[cfg(desktop)]
+pub fn refresh_badge(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let count = due_count(app);
+
+    #[cfg(windows)]
+    let result = window.set_overlay_icon((count > 0).then(overlay_icon));
+    #[cfg(not(windows))]
+    let result = window.set_badge_count((count > 0).then_some(count as i64));
+
+    if let Err(err) = result {
+        tracing::warn!("Failed to update badge: {err}");
+    }
+}
+
+/// Mobile badges come with push notifications, which Hermes doesn't send.
+#[cfg(mobile)]
+pub fn refresh_badge(_app: &AppHandle) {}
@@ -0,0 +1,200 @@
+//! New workspaces scaffolded from a template.
+//!
+//! A template names the starter notes of the workspace root, its project
+//! folders with their notes and subfolders, and optionally workspace settings
+//! overrides. Writer, Researcher and GTD are bundled; JSON files of the same
+//! shape in `templates/` in app data add more, and one named like a bundled
+//! template replaces it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::{self, SettingsLayer};
+use crate::{canonical_workspace, note_path, path_safety, recents, validate_tab_key};
+
+const TEMPLATES_DIR: &str = "templates";
+const BUNDLED: [(&str, &str); 3] = [
+    ("writer", include_str!("../templates/writer.json")),
+    ("researcher", include_str!("../templates/researcher.json")),
+    ("gtd", include_str!("../templates/gtd.json")),
+];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Template {
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Notes of the workspace root, by tab.
+    #[serde(default)]
+    notes: BTreeMap<String, String>,
+    #[serde(default)]
+    projects: Vec<ProjectTemplate>,
+    #[serde(default)]
+    settings: Option<SettingsLayer>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectTemplate {
+    name: String,
+    /// Folders inside the project, such as `sources`.
+    #[serde(default)]
+    folders: Vec<String>,
+    #[serde(default)]
+    notes: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    id: String,
+    name: String,
+    description: String,
+    bundled: bool,
+}
+
+fn parse(id: &str, json: &str) -> Result<Template, String> {
+    serde_json::from_str(json).map_err(|err| format!("Failed parsing template '{id}': {err}"))
+}
+
+fn user_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(TEMPLATES_DIR))
+        .map_err(|err| format!("Failed resolving app data directory: {err}"))
+}
+
+/// User templates by id, the file name without `.json`.
+fn user_templates(app: &AppHandle) -> Vec<(String, PathBuf)> {
+    let Ok(dir) = user_dir(app) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| Some((path.file_stem()?.to_string_lossy().to_string(), path)))
+        .collect();
+    templates.sort();
+    templates
+}
+
+fn load(app: &AppHandle, id: &str) -> Result<Template, String> {
+    if let Some((_, path)) = user_templates(app).into_iter().find(|(user_id, _)| user_id == id) {
+        let json = fs::read_to_string(&path).map_err(|err| format!("Failed reading {}: {err}", path.display()))?;
+        return parse(id, &json);
+    }
+    match BUNDLED.iter().find(|(bundled_id, _)| *bundled_id == id) {
+        Some((_, json)) => parse(id, json),
+        None => Err(format!("Unknown template '{id}'")),
+    }
+}
+
+/// Refuses names and tabs the template can't create before anything is written.
+fn check(template: &Template) -> Result<(), String> {
+    for tab in template.notes.keys() {
+        validate_tab_key(tab)?;
+    }
+    for project in &template.projects {
+        path_safety::check_folder_name(&project.name)?;
+        for tab in project.notes.keys() {
+            validate_tab_key(tab)?;
+        }
+        for folder in &project.folders {
+            folder.split('/').try_for_each(path_safety::check_folder_name)?;
+        }
+    }
+    if let Some(layer) = &template.settings {
+        layer.validate()?;
+    }
+    Ok(())
+}
+
+fn write_notes(dir: &Path, notes: &BTreeMap<String, String>) -> Result<(), String> {
+    let dir = dir.to_string_lossy();
+    for (tab, content) in notes {
+        let path = note_path(&dir, tab);
+        fs::write(&path, content).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Bundled and user templates, bundled ones first unless a user one replaces them.
+#[tauri::command]
+pub fn list_workspace_templates(app: AppHandle) -> Vec<TemplateInfo> {
+    let user = user_templates(&app);
+    let bundled = BUNDLED
+        .iter()
+        .filter(|(id, _)| !user.iter().any(|(user_id, _)| user_id == id))
+        .map(|(id, json)| (id.to_string(), parse(id, json), true));
+    let user = user.iter().map(|(id, path)| {
+        let template = fs::read_to_string(path)
+            .map_err(|err| format!("Failed reading {}: {err}", path.display()))
+            .and_then(|json| parse(id, &json));
+        (id.clone(), template, false)
+    });
+    bundled
+        .chain(user)
+        .filter_map(|(id, template, bundled)| match template {
+            Ok(template) => Some(TemplateInfo {
+                id,
+                name: template.name,
+                description: template.description,
+                bundled,
+            }),
+            Err(err) => {
+                tracing::warn!("{}", err);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Scaffolds a new workspace at `dest` from the template `template` and
+/// returns its path. `dest` must not exist yet or be an empty folder.
+#[tauri::command]
+pub fn create_workspace_from_template(app: AppHandle, dest: String, template: String) -> Result<String, String> {
+    let dest = dest.trim().trim_end_matches(['/', '\\']);
+    let dest_path = Path::new(dest);
+    if !dest_path.is_absolute() {
+        return Err(format!("Choose a full path for the new workspace, not '{dest}'."));
+    }
+    if dest_path.exists() {
+        let mut entries = fs::read_dir(dest_path).map_err(|err| format!("Failed reading {dest}: {err}"))?;
+        if entries.next().is_some() {
+            return Err(format!("{dest} already has files in it; choose an empty or new folder."));
+        }
+    } else if let Some(name) = dest_path.file_name() {
+        path_safety::check_folder_name(&name.to_string_lossy())?;
+    }
+    let template = load(&app, &template)?;
+    check(&template)?;
+
+    let root = crate::notes_dir(dest);
+    fs::create_dir_all(&root).map_err(|err| format!("Failed creating {dest}: {err}"))?;
+    let workspace_path = canonical_workspace(dest);
+    write_notes(Path::new(&workspace_path), &template.notes)?;
+    for project in &template.projects {
+        let project_dir = Path::new(&workspace_path).join(&project.name);
+        let long_dir = path_safety::long_path(&project_dir);
+        fs::create_dir_all(&long_dir).map_err(|err| format!("Failed creating {}: {err}", project_dir.display()))?;
+        for folder in &project.folders {
+            let folder_dir = folder.split('/').fold(long_dir.clone(), |dir, part| dir.join(part));
+            fs::create_dir_all(&folder_dir).map_err(|err| format!("Failed creating {}: {err}", folder_dir.display()))?;
+        }
+        write_notes(&project_dir, &project.notes)?;
+    }
+    if let Some(layer) = &template.settings {
+        settings::seed_workspace(&workspace_path, layer)?;
+    }
+
+    recents::record(&app, &workspace_path);
+    Ok(workspace_path)
+}
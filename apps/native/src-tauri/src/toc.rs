@@ -0,0 +1,209 @@
+//! Tables of contents inside notes.
+//!
+//! A table of contents is a list of links to the note's headings between
+//! `<!--toc-->` and `<!--/toc-->` comments, which markdown previews hide.
+//! `insert_toc` adds the markers under the note's title and fills them in;
+//! after that every save rebuilds the list, so it follows the headings as they
+//! change. Links use GitHub's heading anchors, so they also work on the forge.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use tauri::AppHandle;
+
+use crate::{canonical_workspace, note_lock, note_path, tab_files, validate_tab_key};
+
+const START_MARKER: &str = "<!--toc-->";
+const END_MARKER: &str = "<!--/toc-->";
+
+struct Heading {
+    level: usize,
+    text: String,
+    range: Range<usize>,
+}
+
+/// What the markdown parser found that a table of contents is built from.
+struct Outline {
+    headings: Vec<Heading>,
+    start: Option<Range<usize>>,
+    end: Option<Range<usize>>,
+    /// Where the body starts, after any front matter.
+    body: usize,
+}
+
+fn outline(content: &str) -> Outline {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    let mut outline = Outline { headings: Vec::new(), start: None, end: None, body: 0 };
+    let mut current: Option<Heading> = None;
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => outline.body = range.end,
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some(Heading { level: level as usize, text: String::new(), range });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = current.as_mut() {
+                    heading.text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => outline.headings.extend(current.take()),
+            // Comments in code blocks are text, so only real markers get here.
+            Event::Html(html) => {
+                let marker: String = html.split_whitespace().collect();
+                if marker == START_MARKER && outline.start.is_none() {
+                    outline.start = Some(range);
+                } else if marker == END_MARKER && outline.start.is_some() && outline.end.is_none() {
+                    outline.end = Some(range);
+                }
+            }
+            _ => {}
+        }
+    }
+    outline
+}
+
+/// GitHub's anchor for a heading: lowercase, spaces as dashes, punctuation dropped.
+fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|ch| match ch {
+            ' ' => Some('-'),
+            ch if ch.is_alphanumeric() || ch == '-' || ch == '_' => Some(ch),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The list of links to `headings`, nested by level.
+fn render(headings: &[&Heading], newline: &str) -> String {
+    let top = headings.iter().map(|heading| heading.level).min().unwrap_or(1);
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut list = String::new();
+    for heading in headings {
+        let base = slug(&heading.text);
+        let count = seen.entry(base.clone()).or_default();
+        // Repeated headings get `-1`, `-2` and so on, as on GitHub.
+        let anchor = if *count == 0 { base } else { format!("{base}-{count}") };
+        *count += 1;
+        let text = heading.text.trim().replace('[', "\\[").replace(']', "\\]");
+        let indent = "  ".repeat(heading.level - top);
+        list.push_str(&format!("{indent}- [{text}](#{anchor}){newline}"));
+    }
+    list
+}
+
+/// `content` with its table of contents rebuilt, or `None` when it has no
+/// `<!--toc-->` marker.
+pub fn refresh(content: &str) -> Option<String> {
+    if !content.contains("<!--") {
+        return None;
+    }
+    let outline = outline(content);
+    let start = outline.start?;
+    let mut block = start.start..outline.end.map_or(start.end, |end| end.end);
+    // Up to the end of the marker's line, whose line break the parser may leave out.
+    if !content[..block.end].ends_with('\n') {
+        block.end = content[block.end..].find('\n').map_or(content.len(), |index| block.end + index + 1);
+    }
+    // The title above the table isn't listed in it.
+    let title = outline
+        .headings
+        .first()
+        .filter(|heading| heading.level == 1 && heading.range.start < block.start)
+        .map(|heading| heading.range.start);
+    let headings: Vec<&Heading> = outline
+        .headings
+        .iter()
+        .filter(|heading| Some(heading.range.start) != title && !block.contains(&heading.range.start))
+        .collect();
+
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut refreshed = content[..block.start].to_string();
+    refreshed.push_str(START_MARKER);
+    refreshed.push_str(newline);
+    refreshed.push_str(&render(&headings, newline));
+    refreshed.push_str(END_MARKER);
+    refreshed.push_str(newline);
+    refreshed.push_str(&content[block.end..]);
+    Some(refreshed)
+}
+
+/// `content` with empty markers added under its title, or at the top when it
+/// has none.
+fn with_markers(content: &str) -> String {
+    let outline = outline(content);
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let title = outline
+        .headings
+        .first()
+        .filter(|heading| heading.level == 1 && content[outline.body..heading.range.start].trim().is_empty());
+    let markers = format!("{START_MARKER}{newline}{END_MARKER}{newline}");
+    let Some(title) = title else {
+        return format!("{}{markers}{newline}{}", &content[..outline.body], &content[outline.body..]);
+    };
+    // The end of the title's line.
+    let at = content[title.range.end.saturating_sub(1)..]
+        .find('\n')
+        .map_or(content.len(), |index| title.range.end.saturating_sub(1) + index + 1);
+    let (head, rest) = content.split_at(at);
+    let mut marked = head.to_string();
+    if !head.ends_with('\n') {
+        marked.push_str(newline);
+    }
+    marked.push_str(newline);
+    marked.push_str(&markers);
+    if !rest.is_empty() && !rest.trim_start_matches([' ', '\t']).starts_with(['\n', '\r']) {
+        marked.push_str(newline);
+    }
+    marked.push_str(rest);
+    marked
+}
+
+/// What to write for the note at `path`: `content` with its table of contents
+/// rebuilt when it has one.
+pub fn on_save<'a>(path: &Path, content: Cow<'a, str>) -> Cow<'a, str> {
+    if tab_files::is_plain_text(path) || note_lock::is_locked(&content) {
+        return content;
+    }
+    match refresh(&content) {
+        Some(refreshed) if refreshed != *content && !note_lock::is_locked_file(path) => Cow::Owned(refreshed),
+        _ => content,
+    }
+}
+
+/// Adds a table of contents under the title of the note in `tab_key`, or
+/// rebuilds the one it has, and returns the note's new content.
+#[tauri::command]
+pub fn insert_toc(app: AppHandle, workspace_path: String, tab_key: String) -> Result<String, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    crate::autosave::flush(&app, Some(&workspace_path)).map_err(String::from)?;
+    if tab_files::is_plain_text(&note_path(&workspace_path, &tab_key)) {
+        return Err(format!("The {tab_key} note is plain text."));
+    }
+    let pages = crate::read_workspace_files(&workspace_path).map_err(String::from)?;
+    let content = pages.get(&tab_key).cloned().unwrap_or_default();
+    if note_lock::is_locked(&content) {
+        return Err(format!("The {tab_key} note is locked."));
+    }
+    if content.trim().is_empty() {
+        return Err(format!("The {tab_key} note is empty."));
+    }
+    let content = match refresh(&content) {
+        Some(refreshed) => refreshed,
+        None => refresh(&with_markers(&content)).unwrap_or(content),
+    };
+    let pages = std::collections::HashMap::from([(tab_key.clone(), content.clone())]);
+    crate::save_queue::save(&app, &workspace_path, pages).map_err(String::from)?;
+    tracing::info!("Inserted a table of contents in {tab_key} in {workspace_path}");
+    Ok(content)
+}
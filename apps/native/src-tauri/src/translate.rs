@@ -0,0 +1,227 @@
+//! Translating selections and notes.
+//!
+//! Text is translated by the assistant in the sidecar, or by DeepL when the
+//! `translation` setting picks it; the DeepL API key is kept in the platform's
+//! credential store, never in the settings file. `translate_note` writes the
+//! translation of a note into the first empty tab beside it and ends each of
+//! the two notes with a deep link to the other.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::{
+    assistant, canonical_workspace, deep_link, history, keychain, note_lock, note_path, validate_tab_key, workspace_lock, TAB_KEYS,
+};
+
+const TRANSLATION_SETTING: &str = "translation";
+const KEYCHAIN_ACCOUNT: &str = "deepl";
+const MAX_TEXT_CHARS: usize = 100_000;
+const MAX_LANG_CHARS: usize = 40;
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Provider {
+    #[default]
+    Assistant,
+    Deepl,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Settings {
+    provider: Provider,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationStatus {
+    provider: Provider,
+    deepl_key_set: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslatedNote {
+    /// The tab the translation was written to.
+    tab: String,
+    content: String,
+}
+
+fn read(app: &AppHandle) -> Settings {
+    crate::read_setting(app, TRANSLATION_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// A language code like `de` or `pt-BR`, or a name the assistant understands.
+fn check_lang(lang: &str) -> Result<String, String> {
+    let lang = lang.trim();
+    let plain = lang.chars().all(|ch| ch.is_alphabetic() || ch == '-' || ch == '_' || ch == ' ');
+    if lang.is_empty() || lang.chars().count() > MAX_LANG_CHARS || !plain {
+        return Err(format!("'{lang}' is not a language to translate into."));
+    }
+    Ok(lang.to_string())
+}
+
+fn with_deepl(text: &str, target_lang: &str) -> Result<String, String> {
+    let key = keychain::load(KEYCHAIN_ACCOUNT).ok_or_else(|| "Add your DeepL API key in Settings first.".to_string())?;
+    // Keys of the free plan end in `:fx` and only work against the free API.
+    let host = if key.ends_with(":fx") { "api-free.deepl.com" } else { "api.deepl.com" };
+    let client = Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|err| format!("Failed creating HTTP client: {err}"))?;
+    let response = client
+        .post(format!("https://{host}/v2/translate"))
+        .header("Authorization", format!("DeepL-Auth-Key {key}"))
+        .json(&json!({
+            "text": [text],
+            "target_lang": target_lang.replace('_', "-").to_uppercase(),
+            "preserve_formatting": true,
+        }))
+        .send()
+        .map_err(|err| format!("Failed reaching DeepL: {err}"))?;
+    let status = response.status();
+    let text = response.text().map_err(|err| format!("Failed reading DeepL's reply: {err}"))?;
+    let body: Value = serde_json::from_str(&text).unwrap_or_default();
+    if !status.is_success() {
+        let message = body.get("message").and_then(Value::as_str).map(str::to_string).unwrap_or_else(|| status.to_string());
+        return Err(format!("DeepL failed: {message}"));
+    }
+    body.pointer("/translations/0/text")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "DeepL sent no translation.".to_string())
+}
+
+fn with_assistant(app: &AppHandle, workspace_path: Option<&str>, text: &str, target_lang: &str) -> Result<String, String> {
+    let prompt = format!(
+        "Translate the markdown below into {target_lang}. Keep the markdown syntax, links, code and names as they are. \
+         Reply with only the translation, no notes or explanations.\n\n{text}"
+    );
+    Ok(assistant::ask(app, workspace_path, &prompt)?.trim().to_string())
+}
+
+fn translate(app: &AppHandle, workspace_path: Option<&str>, text: &str, target_lang: &str) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to translate.".to_string());
+    }
+    if text.chars().count() > MAX_TEXT_CHARS {
+        return Err(format!("Translate at most {MAX_TEXT_CHARS} characters at a time."));
+    }
+    let target_lang = check_lang(target_lang)?;
+    match read(app).provider {
+        Provider::Assistant => with_assistant(app, workspace_path, text, &target_lang),
+        Provider::Deepl => with_deepl(text, &target_lang),
+    }
+}
+
+/// The note in `tab_key` and the first empty tab beside it, as they are on disk.
+fn read_tabs(workspace_path: &str, tab_key: &str) -> Result<(String, String), String> {
+    let pages = crate::read_workspace_files(workspace_path).map_err(String::from)?;
+    let source = pages.get(tab_key).cloned().unwrap_or_default();
+    if note_lock::is_locked(&source) {
+        return Err(format!("The {tab_key} note is locked."));
+    }
+    let tab = TAB_KEYS
+        .iter()
+        .find(|tab| **tab != tab_key && pages.get(**tab).is_none_or(|content| content.trim().is_empty()))
+        .ok_or_else(|| "Every tab has content; clear one for the translation.".to_string())?
+        .to_string();
+    Ok((source, tab))
+}
+
+fn translate_tab(app: &AppHandle, workspace_path: &str, tab_key: &str, target_lang: &str) -> Result<TranslatedNote, String> {
+    validate_tab_key(tab_key)?;
+    crate::read_only::ensure_writable(app, workspace_path).map_err(String::from)?;
+    crate::autosave::flush(app, Some(workspace_path)).map_err(String::from)?;
+    let (source, tab) = read_tabs(workspace_path, tab_key)?;
+    let translation = translate(app, Some(workspace_path), &source, target_lang)?;
+
+    // The note may have been edited while it was being translated; the link
+    // goes under what it holds now, and the translation into a tab still empty.
+    crate::autosave::flush(app, Some(workspace_path)).map_err(String::from)?;
+    let write_lock = workspace_lock::acquire(workspace_path)?;
+    let (source, current_tab) = read_tabs(workspace_path, tab_key)?;
+    if current_tab != tab {
+        return Err(format!("The {tab} tab was filled while translating; try again."));
+    }
+    let source_link = deep_link::get_note_deep_link(workspace_path.to_string(), tab_key.to_string(), None)?;
+    let translation_link = deep_link::get_note_deep_link(workspace_path.to_string(), tab.clone(), None)?;
+    let content = format!("{translation}\n\n[Original]({source_link})\n");
+    let source = format!("{}\n\n[Translation into {}]({translation_link})\n", source.trim_end(), target_lang.trim());
+    history::write_atomically(&note_path(workspace_path, &tab), &content)?;
+    history::write_atomically(&note_path(workspace_path, tab_key), &source)?;
+    let pages = HashMap::from([(tab.clone(), content.clone()), (tab_key.to_string(), source)]);
+    for (tab, content) in &pages {
+        crate::merge::store_base(workspace_path, tab, content);
+        crate::crdt::record(workspace_path, tab, content);
+    }
+    crate::versions::record_saves(app, workspace_path, &pages);
+    drop(write_lock);
+
+    for tab in pages.keys() {
+        crate::changes::note_saved(app, workspace_path, tab);
+    }
+    if let Err(err) = crate::read_workspace_pages(workspace_path) {
+        tracing::warn!("{}", String::from(err));
+    }
+    tracing::info!("Translated {tab_key} into {tab} in {workspace_path}");
+    Ok(TranslatedNote { tab, content })
+}
+
+#[tauri::command]
+pub fn get_translation(app: AppHandle) -> TranslationStatus {
+    TranslationStatus {
+        provider: read(&app).provider,
+        deepl_key_set: keychain::load(KEYCHAIN_ACCOUNT).is_some(),
+    }
+}
+
+/// Picks the translation provider, and saves the DeepL key when one is
+/// given; an empty key removes it.
+#[tauri::command]
+pub fn set_translation(app: AppHandle, provider: Provider, deepl_key: Option<String>) -> Result<TranslationStatus, String> {
+    match deepl_key.as_deref().map(str::trim) {
+        Some("") => keychain::delete(KEYCHAIN_ACCOUNT),
+        Some(key) => keychain::store(KEYCHAIN_ACCOUNT, key)?,
+        None => {}
+    }
+    let value = serde_json::to_value(Settings { provider }).map_err(|err| format!("Failed encoding translation settings: {err}"))?;
+    crate::write_setting(&app, TRANSLATION_SETTING, value)?;
+    Ok(get_translation(app))
+}
+
+/// `text` translated into `target_lang`, such as `de` or `pt-BR`.
+#[tauri::command]
+pub async fn translate_text(
+    app: AppHandle,
+    text: String,
+    target_lang: String,
+    workspace_path: Option<String>,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || translate(&app, workspace_path.as_deref(), &text, &target_lang))
+        .await
+        .map_err(|err| format!("Translation task failed: {err}"))?
+}
+
+/// Translates the note in `tab_key` into the first empty tab of
+/// `workspace_path` and links the two notes to each other.
+#[tauri::command]
+pub async fn translate_note(
+    app: AppHandle,
+    workspace_path: String,
+    tab_key: String,
+    target_lang: String,
+) -> Result<TranslatedNote, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    let workspace_path = canonical_workspace(&workspace_path);
+    tauri::async_runtime::spawn_blocking(move || translate_tab(&app, &workspace_path, &tab_key, &target_lang))
+        .await
+        .map_err(|err| format!("Translation task failed: {err}"))?
+}
@@ -0,0 +1,165 @@
+//! Menu-bar / system tray icon with quick capture and recent notes.
+
+use tauri::menu::{Menu, MenuBuilder, MenuEvent, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Url};
+
+use crate::{
+    capture, deep_link, file_open, focus_main_window, read_project_names, run_sqlite_query, sqlite_path, workspace_meta,
+};
+
+const TRAY_ID: &str = "hermes-tray";
+const RECENT_LIMIT: usize = 8;
+
+const QUICK_CAPTURE_ID: &str = "tray:quick-capture";
+const OPEN_WORKSPACE_ID: &str = "tray:open-workspace";
+const QUIT_ID: &str = "tray:quit";
+/// Recent-note items carry their deep link after this prefix.
+const RECENT_PREFIX: &str = "tray:recent:";
+
+struct RecentNote {
+    title: String,
+    link: String,
+    updated_unix: i64,
+}
+
+fn recent_notes_in(workspace_path: &str) -> Vec<RecentNote> {
+    let db_path = sqlite_path(workspace_path);
+    if !db_path.exists() {
+        return Vec::new();
+    }
+
+    let rows = match run_sqlite_query(
+        &db_path,
+        &format!("SELECT tab_key, title, updated_unix FROM note_index ORDER BY updated_unix DESC LIMIT {RECENT_LIMIT};"),
+    ) {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Vec::new();
+        }
+    };
+
+    rows.into_iter()
+        .filter_map(|row| {
+            let tab = row.get("tab_key")?.as_str()?.to_string();
+            let title = row.get("title")?.as_str().unwrap_or_default().to_string();
+            let updated_unix = row.get("updated_unix")?.as_i64().unwrap_or(0);
+            let link = deep_link::get_note_deep_link(workspace_path.to_string(), tab.clone(), None).ok()?;
+            Some(RecentNote {
+                title: if title.is_empty() { tab } else { title },
+                link,
+                updated_unix,
+            })
+        })
+        .collect()
+}
+
+/// Most recently edited notes across the workspace root and its projects.
+fn recent_notes(app: &AppHandle) -> Vec<RecentNote> {
+    let Some(workspace) = file_open::configured_workspace(app) else {
+        return Vec::new();
+    };
+    let root = workspace.to_string_lossy().to_string();
+
+    let mut notes = recent_notes_in(&root);
+    for project in read_project_names(&root).unwrap_or_default() {
+        let project_path = workspace.join(&project).to_string_lossy().to_string();
+        notes.extend(recent_notes_in(&project_path));
+    }
+
+    notes.sort_by_key(|note| std::cmp::Reverse(note.updated_unix));
+    notes.truncate(RECENT_LIMIT);
+    notes
+}
+
+/// The configured workspace as menus show it.
+fn workspace_label(app: &AppHandle) -> Option<String> {
+    file_open::configured_workspace(app).map(|workspace| workspace_meta::label(&workspace.to_string_lossy()))
+}
+
+fn tooltip(app: &AppHandle) -> String {
+    match workspace_label(app) {
+        Some(label) => format!("Hermes — {label}"),
+        None => "Hermes".to_string(),
+    }
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let notes = recent_notes(app);
+    let mut recent = SubmenuBuilder::new(app, "Recent notes");
+    if notes.is_empty() {
+        recent = recent.enabled(false);
+    }
+    for note in notes {
+        let label: String = note.title.chars().take(48).collect();
+        recent = recent.text(format!("{RECENT_PREFIX}{}", note.link), label);
+    }
+
+    let open_label = match workspace_label(app) {
+        Some(label) => format!("Open {label}"),
+        None => "Open workspace".to_string(),
+    };
+    MenuBuilder::new(app)
+        .text(QUICK_CAPTURE_ID, "Quick capture")
+        .text(OPEN_WORKSPACE_ID, open_label)
+        .separator()
+        .item(&recent.build()?)
+        .separator()
+        .text(QUIT_ID, "Quit Hermes")
+        .build()
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    match id {
+        QUICK_CAPTURE_ID => {
+            if let Err(err) = capture::open_quick_capture_window(app) {
+                tracing::warn!("{}", err);
+            }
+        }
+        OPEN_WORKSPACE_ID => focus_main_window(app),
+        QUIT_ID => app.exit(0),
+        _ => {
+            if let Some(link) = id.strip_prefix(RECENT_PREFIX) {
+                match Url::parse(link) {
+                    Ok(url) => deep_link::handle_urls(app, vec![url]),
+                    Err(err) => tracing::warn!("Invalid note link {link}: {err}"),
+                }
+            }
+        }
+    }
+}
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app)?;
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(app))
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
+
+/// Rebuilds the tray menu so the recent-notes list reflects the latest saves
+/// and the workspace label its metadata.
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    if let Err(err) = tray.set_tooltip(Some(tooltip(app))) {
+        tracing::warn!("Failed to update tray tooltip: {err}");
+    }
+    match build_menu(app) {
+        Ok(menu) => {
+            if let Err(err) = tray.set_menu(Some(menu)) {
+                tracing::warn!("Failed to update menu: {err}");
+            }
+        }
+        Err(err) => tracing::warn!("Failed to build menu: {err}"),
+    }
+}
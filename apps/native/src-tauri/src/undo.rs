@@ -0,0 +1,220 @@
+//! Undo history that survives restarts, kept in `.hermes/undo/<tab>.json`.
+//!
+//! Every saved change to a tab appends a step holding the character edits from
+//! the previous content. The oldest steps are folded into the log's base text
+//! once the edits exceed a size cap. Reverting to a point writes that content
+//! as a new step, so a revert can itself be undone.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use tauri::AppHandle;
+
+use crate::{app_lock, hermes_dir, note_lock, note_path, validate_tab_key, workspace_lock};
+
+/// Bytes of inserted text kept across all steps before old steps are folded away.
+const MAX_LOG_BYTES: usize = 512 * 1024;
+
+/// Replaces `delete` characters at `at` (a character offset in the previous text).
+#[derive(Clone, Serialize, Deserialize)]
+struct Edit {
+    at: usize,
+    delete: usize,
+    insert: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Step {
+    id: u64,
+    at_unix_ms: u64,
+    edits: Vec<Edit>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct UndoLog {
+    /// Content before the first remaining step.
+    base: String,
+    /// Content after the last step, to diff the next save against.
+    head: String,
+    steps: Vec<Step>,
+    next_id: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoPoint {
+    id: u64,
+    at_unix_ms: u64,
+    inserted_chars: usize,
+    deleted_chars: usize,
+}
+
+fn log_path(workspace_path: &str, tab: &str) -> PathBuf {
+    hermes_dir(workspace_path).join("undo").join(format!("{tab}.json"))
+}
+
+fn load(workspace_path: &str, tab: &str) -> UndoLog {
+    fs::read_to_string(log_path(workspace_path, tab))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save(workspace_path: &str, tab: &str, log: &UndoLog) -> Result<(), String> {
+    let path = log_path(workspace_path, tab);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating directory {}: {err}", dir.display()))?;
+    }
+    let json = serde_json::to_string(log).map_err(|err| format!("Failed encoding undo history: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))
+}
+
+fn diff(old: &str, new: &str) -> Vec<Edit> {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    capture_diff_slices(Algorithm::Myers, &old, &new)
+        .into_iter()
+        .filter_map(|op| match op {
+            DiffOp::Equal { .. } => None,
+            DiffOp::Delete { old_index, old_len, .. } => Some(Edit {
+                at: old_index,
+                delete: old_len,
+                insert: String::new(),
+            }),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => Some(Edit {
+                at: old_index,
+                delete: 0,
+                insert: new[new_index..new_index + new_len].iter().collect(),
+            }),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => Some(Edit {
+                at: old_index,
+                delete: old_len,
+                insert: new[new_index..new_index + new_len].iter().collect(),
+            }),
+        })
+        .collect()
+}
+
+fn apply(text: &str, edits: &[Edit]) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    // Offsets refer to the previous text, so apply back to front.
+    for edit in edits.iter().rev() {
+        let end = (edit.at + edit.delete).min(chars.len());
+        let at = edit.at.min(end);
+        chars.splice(at..end, edit.insert.chars());
+    }
+    chars.into_iter().collect()
+}
+
+fn log_bytes(log: &UndoLog) -> usize {
+    log.steps
+        .iter()
+        .flat_map(|step| &step.edits)
+        .map(|edit| edit.insert.len())
+        .sum()
+}
+
+fn push(log: &mut UndoLog, content: &str) {
+    if log.steps.is_empty() && log.head.is_empty() && log.base.is_empty() {
+        log.base = content.to_string();
+        log.head = content.to_string();
+        return;
+    }
+    if log.head == content {
+        return;
+    }
+
+    let at_unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0);
+    log.steps.push(Step {
+        id: log.next_id,
+        at_unix_ms,
+        edits: diff(&log.head, content),
+    });
+    log.next_id += 1;
+    log.head = content.to_string();
+
+    while log.steps.len() > 1 && log_bytes(log) > MAX_LOG_BYTES {
+        let oldest = log.steps.remove(0);
+        log.base = apply(&log.base, &oldest.edits);
+    }
+}
+
+/// Appends a step if `content` differs from the last recorded content (best effort).
+pub fn record(workspace_path: &str, tab: &str, content: &str) {
+    let mut log = load(workspace_path, tab);
+    push(&mut log, content);
+    if let Err(err) = save(workspace_path, tab, &log) {
+        tracing::warn!("{}", err);
+    }
+}
+
+pub fn remove(workspace_path: &str, tab: &str) {
+    let _ = fs::remove_file(log_path(workspace_path, tab));
+}
+
+/// Recorded undo points of a tab, oldest first.
+#[tauri::command]
+pub fn get_undo_history(workspace_path: String, tab_key: String) -> Result<Vec<UndoPoint>, String> {
+    validate_tab_key(&tab_key)?;
+    let log = load(&workspace_path, &tab_key);
+    Ok(log
+        .steps
+        .iter()
+        .map(|step| UndoPoint {
+            id: step.id,
+            at_unix_ms: step.at_unix_ms,
+            inserted_chars: step.edits.iter().map(|edit| edit.insert.chars().count()).sum(),
+            deleted_chars: step.edits.iter().map(|edit| edit.delete).sum(),
+        })
+        .collect())
+}
+
+/// Restores the tab to its content right after step `point_id` and returns it.
+#[tauri::command]
+pub fn revert_to_point(app: AppHandle, workspace_path: String, tab_key: String, point_id: u64) -> Result<String, String> {
+    app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    let file_path = note_path(&workspace_path, &tab_key);
+    if note_lock::is_locked_file(&file_path) {
+        return Err("Unlock the note before reverting it.".to_string());
+    }
+
+    let write_lock = workspace_lock::acquire(&workspace_path)?;
+    let mut log = load(&workspace_path, &tab_key);
+    let index = log
+        .steps
+        .iter()
+        .position(|step| step.id == point_id)
+        .ok_or_else(|| format!("Undo point {point_id} is no longer in the history."))?;
+    let content = log.steps[..=index]
+        .iter()
+        .fold(log.base.clone(), |text, step| apply(&text, &step.edits));
+
+    fs::write(&file_path, &content).map_err(|err| format!("Failed writing {}: {err}", file_path.display()))?;
+    push(&mut log, &content);
+    save(&workspace_path, &tab_key, &log)?;
+    crate::merge::store_base(&workspace_path, &tab_key, &content);
+    crate::crdt::record(&workspace_path, &tab_key, &content);
+    drop(write_lock);
+    if let Err(err) = crate::read_workspace_pages(&workspace_path) {
+        tracing::warn!("{}", String::from(err));
+    }
+    Ok(content)
+}
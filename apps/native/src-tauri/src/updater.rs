@@ -0,0 +1,137 @@
+//! In-app updates from GitHub releases, on a stable or beta channel.
+//!
+//! Release builds must be compiled with `HERMES_UPDATER_PUBKEY` set to the
+//! minisign public key the release artifacts are signed with; without it the
+//! update commands report that updates are unavailable.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_store::StoreExt;
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::SETTINGS_STORE_FILE;
+
+/// Settings-store key holding `"stable"` or `"beta"`.
+const CHANNEL_SETTING: &str = "updateChannel";
+const CHANNELS: [(&str, &str); 2] = [
+    ("stable", "https://github.com/inosaint/hermes/releases/latest/download/latest.json"),
+    ("beta", "https://github.com/inosaint/hermes/releases/download/beta/latest.json"),
+];
+const PUBKEY: Option<&str> = option_env!("HERMES_UPDATER_PUBKEY");
+
+pub const UPDATE_PROGRESS_EVENT: &str = "update-progress";
+
+/// Update found by the last `check_for_updates`, kept for `install_update`.
+#[derive(Default)]
+pub struct PendingUpdate(Mutex<Option<Update>>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    version: String,
+    current_version: String,
+    notes: Option<String>,
+    date: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProgress {
+    downloaded: usize,
+    total: Option<u64>,
+}
+
+fn channel(app: &AppHandle) -> String {
+    crate::read_setting(app, CHANNEL_SETTING)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|channel| CHANNELS.iter().any(|(name, _)| name == channel))
+        .unwrap_or_else(|| CHANNELS[0].0.to_string())
+}
+
+fn endpoint(channel: &str) -> Url {
+    let (_, url) = CHANNELS
+        .iter()
+        .find(|(name, _)| *name == channel)
+        .unwrap_or(&CHANNELS[0]);
+    Url::parse(url).expect("channel endpoints are valid URLs")
+}
+
+#[tauri::command]
+pub async fn check_for_updates(
+    app: AppHandle,
+    state: tauri::State<'_, PendingUpdate>,
+) -> Result<Option<UpdateInfo>, String> {
+    let pubkey = PUBKEY.ok_or_else(|| "Updates are not available in this build.".to_string())?;
+    let updater = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![endpoint(&channel(&app))])
+        .and_then(|builder| builder.build())
+        .map_err(|err| format!("Failed to set up updater: {err}"))?;
+
+    let update = updater
+        .check()
+        .await
+        .map_err(|err| format!("Failed checking for updates: {err}"))?;
+
+    let info = update.as_ref().map(|update| UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone(),
+        date: update.date.map(|date| date.to_string()),
+    });
+    *state.0.lock().unwrap() = update;
+    Ok(info)
+}
+
+/// Downloads and installs the update found by `check_for_updates`, then restarts.
+#[tauri::command]
+pub async fn install_update(app: AppHandle, state: tauri::State<'_, PendingUpdate>) -> Result<(), String> {
+    let update = state
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "No update to install; check for updates first.".to_string())?;
+    // The Windows installer ends the app as soon as it starts, so edits are written first.
+    crate::autosave::flush(&app, None).map_err(String::from)?;
+
+    let mut downloaded = 0;
+    let progress_app = app.clone();
+    update
+        .download_and_install(
+            move |chunk, total| {
+                downloaded += chunk;
+                let payload = UpdateProgress { downloaded, total };
+                if let Err(err) = progress_app.emit(UPDATE_PROGRESS_EVENT, payload) {
+                    tracing::warn!("Failed to emit event: {err}");
+                }
+            },
+            || {},
+        )
+        .await
+        .map_err(|err| format!("Failed installing update: {err}"))?;
+
+    // Restarting skips the exit handlers, so edits made during the download are written here.
+    crate::autosave::flush(&app, None).map_err(String::from)?;
+    app.restart()
+}
+
+#[tauri::command]
+pub fn get_update_channel(app: AppHandle) -> String {
+    channel(&app)
+}
+
+#[tauri::command]
+pub fn set_update_channel(app: AppHandle, channel: String) -> Result<(), String> {
+    if !CHANNELS.iter().any(|(name, _)| *name == channel) {
+        return Err(format!("Unknown update channel '{channel}'"));
+    }
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("Failed opening settings store: {err}"))?;
+    store.set(CHANNEL_SETTING, serde_json::Value::String(channel));
+    store.save().map_err(|err| format!("Failed saving settings: {err}"))
+}
@@ -0,0 +1,184 @@
+//! Per-note version vectors, kept in `note_versions` in the workspace index.
+//!
+//! Every edit saved on this device bumps this device's counter for the tab.
+//! Sync transports send their vector along with a note; comparing it with the
+//! local one tells a fast-forward (one side has seen everything the other has)
+//! from concurrent edits that need merging, without trusting file mtimes.
+
+use std::collections::{BTreeMap, HashMap};
+
+pub use hermes_core::file_hashes::content_hash;
+pub use hermes_core::index::NOTE_VERSIONS_SCHEMA;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{device, run_sqlite_query, run_sqlite_script, sql_escape, sqlite_path, validate_tab_key};
+
+/// Edit counter per device ID.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionVector(BTreeMap<String, u64>);
+
+/// How the local version relates to another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Relation {
+    Equal,
+    /// The other version includes every local edit: fast-forward to it.
+    Behind,
+    /// The local version includes every edit of the other one: nothing to take.
+    Ahead,
+    Concurrent,
+}
+
+impl VersionVector {
+    fn get(&self, device: &str) -> u64 {
+        self.0.get(device).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn compare(&self, other: &VersionVector) -> Relation {
+        let devices = self.0.keys().chain(other.0.keys());
+        let (mut behind, mut ahead) = (false, false);
+        for device in devices {
+            let (ours, theirs) = (self.get(device), other.get(device));
+            behind |= ours < theirs;
+            ahead |= ours > theirs;
+        }
+        match (behind, ahead) {
+            (false, false) => Relation::Equal,
+            (true, false) => Relation::Behind,
+            (false, true) => Relation::Ahead,
+            (true, true) => Relation::Concurrent,
+        }
+    }
+
+    /// Pointwise maximum: the version that has seen both.
+    pub fn merge(&mut self, other: &VersionVector) {
+        for (device, &count) in &other.0 {
+            let entry = self.0.entry(device.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+    }
+
+    fn bump(&mut self, device: &str) {
+        *self.0.entry(device.to_string()).or_insert(0) += 1;
+    }
+}
+
+struct Recorded {
+    vector: VersionVector,
+    content_hash: String,
+}
+
+fn read_all(workspace_path: &str) -> HashMap<String, Recorded> {
+    let db_path = sqlite_path(workspace_path);
+    if !db_path.exists() {
+        return HashMap::new();
+    }
+    // The table is created lazily, so a missing one just means no versions yet.
+    let rows = run_sqlite_query(&db_path, "SELECT tab_key, vector, content_hash FROM note_versions;").unwrap_or_default();
+    rows.into_iter()
+        .filter_map(|row| {
+            let tab = row.get("tab_key")?.as_str()?.to_string();
+            let vector = serde_json::from_str(row.get("vector")?.as_str()?).ok()?;
+            let content_hash = row.get("content_hash")?.as_str()?.to_string();
+            Some((tab, Recorded { vector, content_hash }))
+        })
+        .collect()
+}
+
+fn write_script(tab: &str, vector: &VersionVector, content_hash: &str) -> String {
+    let vector = serde_json::to_string(vector).unwrap_or_else(|_| "{}".to_string());
+    format!(
+        "INSERT INTO note_versions(tab_key, vector, content_hash) VALUES ('{}', '{}', '{}')\n\
+         ON CONFLICT(tab_key) DO UPDATE SET vector=excluded.vector, content_hash=excluded.content_hash;\n",
+        sql_escape(tab),
+        sql_escape(&vector),
+        sql_escape(content_hash),
+    )
+}
+
+fn run(workspace_path: &str, statements: &str) -> Result<(), String> {
+    let hermes = crate::hermes_dir(workspace_path);
+    std::fs::create_dir_all(&hermes)
+        .map_err(|err| format!("Failed creating Hermes metadata directory {}: {err}", hermes.display()))?;
+    let script = format!("{NOTE_VERSIONS_SCHEMA}BEGIN IMMEDIATE;\n{statements}COMMIT;\n");
+    run_sqlite_script(&sqlite_path(workspace_path), &script)
+}
+
+/// Recorded versions of the tabs in one workspace folder.
+pub fn all(workspace_path: &str) -> HashMap<String, VersionVector> {
+    read_all(workspace_path)
+        .into_iter()
+        .map(|(tab, recorded)| (tab, recorded.vector))
+        .collect()
+}
+
+/// The recorded version of a tab; empty if it was never saved or synced.
+pub fn version(workspace_path: &str, tab: &str) -> VersionVector {
+    all(workspace_path).remove(tab).unwrap_or_default()
+}
+
+/// Bumps this device's counter for every tab whose content changed since it was last recorded.
+pub fn record_saves(app: &AppHandle, workspace_path: &str, pages: &HashMap<String, String>) {
+    let device = match device::id(app) {
+        Ok(device) => device,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return;
+        }
+    };
+    let mut recorded = read_all(workspace_path);
+
+    let mut statements = String::new();
+    for (tab, content) in pages {
+        let hash = content_hash(content.as_bytes());
+        let entry = recorded.remove(tab);
+        let unchanged = match &entry {
+            Some(entry) => entry.content_hash == hash,
+            None => content.is_empty(),
+        };
+        if unchanged {
+            continue;
+        }
+        let mut vector = entry.map(|entry| entry.vector).unwrap_or_default();
+        vector.bump(&device);
+        statements.push_str(&write_script(tab, &vector, &hash));
+    }
+
+    if statements.is_empty() {
+        return;
+    }
+    if let Err(err) = run(workspace_path, &statements) {
+        tracing::warn!("{}", err);
+    }
+}
+
+/// Records a tab after a transport applied `remote` to it. The result has seen
+/// both versions; it counts as a new local edit unless it equals the remote text.
+pub fn record_sync(app: &AppHandle, workspace_path: &str, tab: &str, remote: &VersionVector, remote_hash: &str, content: &str) -> Result<(), String> {
+    let mut vector = version(workspace_path, tab);
+    vector.merge(remote);
+    let hash = content_hash(content.as_bytes());
+    if hash != remote_hash {
+        vector.bump(&device::id(app)?);
+    }
+    run(workspace_path, &write_script(tab, &vector, &hash))
+}
+
+#[tauri::command]
+pub fn get_note_version(app: AppHandle, workspace_path: String, tab_key: String) -> Result<VersionVector, String> {
+    crate::app_lock::ensure_unlocked(&app)?;
+    validate_tab_key(&tab_key)?;
+    Ok(version(&workspace_path, &tab_key))
+}
+
+/// Compares the local version of a tab with one received from another device.
+#[tauri::command]
+pub fn compare_note_versions(workspace_path: String, tab_key: String, remote: VersionVector) -> Result<Relation, String> {
+    validate_tab_key(&tab_key)?;
+    Ok(version(&workspace_path, &tab_key).compare(&remote))
+}
@@ -0,0 +1,169 @@
+//! Voice memos recorded into a note.
+//!
+//! SoX's `sox` records the default microphone to a 16 kHz mono WAV in the
+//! attachments folder, which is what whisper.cpp reads. Like whisper.cpp it
+//! isn't bundled; it is looked up on `PATH` or, on macOS, in Homebrew's
+//! folders. When recording stops the memo is linked at the end of the note
+//! and, if dictation is set up, transcribed into a quote under the link, so
+//! the search index finds the memo by what was said.
+
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::{attachments, canonical_workspace, capture, dictation, validate_tab_key};
+
+/// Length of the header SoX writes before the samples of a WAV file.
+const WAV_HEADER_LEN: u64 = 44;
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+struct Recording {
+    child: Child,
+    workspace_path: String,
+    tab_key: String,
+    path: PathBuf,
+    link: String,
+}
+
+/// The memo being recorded, if any.
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceMemo {
+    /// Path the note links the recording by.
+    pub link: String,
+    pub transcript: Option<String>,
+}
+
+/// Asks SoX to finish the file and waits for it. SoX writes the sizes into
+/// the header when interrupted; killed on Windows, it can't, so `fix_header`
+/// fills them in.
+fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: the pid is of a child that hasn't been waited for, so it is still ours.
+        unsafe {
+            libc::kill(child.id() as libc::pid_t, libc::SIGINT);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Writes the RIFF and data chunk sizes from the file's length.
+fn fix_header(path: &Path) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let len = file.metadata()?.len();
+    if len < WAV_HEADER_LEN {
+        return Ok(());
+    }
+    let riff = u32::try_from(len - 8).unwrap_or(u32::MAX);
+    let data = u32::try_from(len - WAV_HEADER_LEN).unwrap_or(u32::MAX);
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&riff.to_le_bytes())?;
+    file.seek(SeekFrom::Start(WAV_HEADER_LEN - 4))?;
+    file.write_all(&data.to_le_bytes())
+}
+
+/// The transcript of the memo, when dictation is set up and heard anything.
+fn transcript(app: &AppHandle, path: &Path) -> Option<String> {
+    if !dictation::is_available(app) {
+        return None;
+    }
+    match dictation::transcribe(app, path) {
+        Ok(text) => Some(text).filter(|text| !text.trim().is_empty()),
+        Err(err) => {
+            tracing::warn!("Failed transcribing voice memo {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+fn finish(app: &AppHandle, recording: Recording) -> Result<VoiceMemo, String> {
+    let Recording {
+        workspace_path,
+        tab_key,
+        path,
+        link,
+        ..
+    } = recording;
+    fix_header(&path).map_err(|err| format!("Failed finishing {}: {err}", path.display()))?;
+    let transcript = transcript(app, &path);
+
+    let mut entry = format!("[Voice memo]({link})");
+    if let Some(text) = &transcript {
+        entry.push_str("\n\n");
+        entry.push_str(&text.lines().map(|line| format!("> {}", line.trim())).collect::<Vec<_>>().join("\n"));
+    }
+    // Edits still waiting in the editor go first, or its next save would drop the memo.
+    crate::autosave::flush(app, Some(&workspace_path)).map_err(String::from)?;
+    capture::append_entry(&workspace_path, &tab_key, &entry, None)?;
+    crate::changes::note_saved(app, &workspace_path, &tab_key);
+    tracing::info!("Saved voice memo {link} in {workspace_path}");
+    Ok(VoiceMemo { link, transcript })
+}
+
+/// Starts recording a memo for the note in `tab_key`.
+#[tauri::command]
+pub fn start_voice_memo(app: AppHandle, workspace_path: String, tab_key: String) -> Result<(), String> {
+    validate_tab_key(&tab_key)?;
+    let workspace_path = canonical_workspace(&workspace_path);
+    crate::read_only::ensure_writable(&app, &workspace_path).map_err(String::from)?;
+    let mut recording = RECORDING.lock().unwrap();
+    if recording.is_some() {
+        return Err("A voice memo is already recording.".to_string());
+    }
+    let sox = hermes_core::programs::find_program(None, "sox")
+        .ok_or_else(|| "SoX was not found; install it to record voice memos.".to_string())?;
+    let name = format!("voice-memo-{}.wav", chrono::Local::now().format("%Y-%m-%d-%H%M%S"));
+    let (path, link) = attachments::create_file(&workspace_path, &name)?;
+    let mut command = Command::new(sox);
+    command
+        .args(["-q", "-d", "-c", "1", "-r", "16000", "-b", "16"])
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let child = command.spawn().map_err(|err| {
+        let _ = std::fs::remove_file(&path);
+        format!("Failed to start sox: {err}")
+    })?;
+    *recording = Some(Recording {
+        child,
+        workspace_path,
+        tab_key,
+        path,
+        link,
+    });
+    Ok(())
+}
+
+/// Stops recording, links the memo into its note with a transcript when
+/// dictation is set up, and returns both.
+#[tauri::command]
+pub async fn stop_voice_memo(app: AppHandle) -> Result<VoiceMemo, String> {
+    let mut recording = RECORDING
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "No voice memo is recording.".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        stop(&mut recording.child);
+        finish(&app, recording)
+    })
+    .await
+    .map_err(|err| format!("Voice memo task failed: {err}"))?
+}
@@ -0,0 +1,45 @@
+//! Telling the user, once per workspace, that it sits on a network or cloud
+//! volume, where `hermes_core::volume` gives the index a rollback journal.
+
+pub use hermes_core::volume::{free_bytes, journal_mode, kind, VolumeKind};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+pub const VOLUME_WARNING_EVENT: &str = "workspace-volume-warning";
+/// Settings-store key listing workspaces the user was already warned about.
+const WARNED_SETTING: &str = "volumeWarnings";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeWarning {
+    workspace_path: String,
+    kind: VolumeKind,
+}
+
+/// Tells the frontend, once per workspace, that it sits on a network or cloud volume.
+pub fn warn_once(app: &AppHandle, workspace_path: &str) {
+    let kind = kind(workspace_path);
+    if kind == VolumeKind::Local {
+        return;
+    }
+
+    let mut warned: Vec<Value> = crate::read_setting(app, WARNED_SETTING)
+        .and_then(|value| value.as_array().cloned())
+        .unwrap_or_default();
+    if warned.iter().any(|path| path.as_str() == Some(workspace_path)) {
+        return;
+    }
+    warned.push(Value::String(workspace_path.to_string()));
+    if let Err(err) = crate::write_setting(app, WARNED_SETTING, Value::Array(warned)) {
+        tracing::warn!("{}", err);
+    }
+
+    let warning = VolumeWarning {
+        workspace_path: workspace_path.to_string(),
+        kind,
+    };
+    if let Err(err) = app.emit(VOLUME_WARNING_EVENT, warning) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+}
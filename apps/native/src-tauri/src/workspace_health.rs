@@ -0,0 +1,206 @@
+//! One-shot health report of a workspace for the settings screen.
+//!
+//! Each check is independent and never fails the command; a problem shows up
+//! as a warning or error on that check, with an `AccessError` attached when
+//! the OS denied access so the UI can offer the same guidance as elsewhere.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::permissions::{io_error, AccessError};
+use crate::{
+    conflicts, hermes_dir, index_integrity, note_path, read_project_names, run_sqlite_query, sqlite_path, CommandError,
+    TAB_KEYS,
+};
+
+const LOW_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+const CRITICAL_DISK_BYTES: u64 = 100 * 1024 * 1024;
+/// Tables every index created by this version of Hermes holds.
+const INDEX_TABLES: &[&str] = &["note_index", "note_fts", "note_tags", "note_tasks", "note_versions", "note_locks"];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    id: &'static str,
+    status: HealthStatus,
+    message: String,
+    access: Option<AccessError>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    workspace_path: String,
+    /// The worst status of any check.
+    status: HealthStatus,
+    checks: Vec<HealthCheck>,
+}
+
+fn result(id: &'static str, status: HealthStatus, message: impl Into<String>) -> HealthCheck {
+    HealthCheck {
+        id,
+        status,
+        message: message.into(),
+        access: None,
+    }
+}
+
+fn failure(id: &'static str, err: CommandError) -> HealthCheck {
+    match err {
+        CommandError::Access(access) => HealthCheck {
+            id,
+            status: HealthStatus::Error,
+            message: access.message.clone(),
+            access: Some(access),
+        },
+        CommandError::Message(message) => result(id, HealthStatus::Error, message),
+    }
+}
+
+fn check_writable(workspace_path: &str) -> HealthCheck {
+    let hermes = hermes_dir(workspace_path);
+    if let Err(err) = fs::create_dir_all(&hermes) {
+        return failure("writable", io_error("creating", &hermes, err));
+    }
+    let probe = hermes.join(".health-check");
+    if let Err(err) = fs::write(&probe, b"ok") {
+        return failure("writable", io_error("writing", &probe, err));
+    }
+    if let Err(err) = fs::remove_file(&probe) {
+        return failure("writable", io_error("removing", &probe, err));
+    }
+    result("writable", HealthStatus::Ok, "The workspace folder is writable.")
+}
+
+fn check_disk_space(workspace_path: &str) -> HealthCheck {
+    let Some(free) = crate::volume::free_bytes(Path::new(workspace_path)) else {
+        return result("disk-space", HealthStatus::Warning, "Free disk space could not be determined.");
+    };
+    let megabytes = free / (1024 * 1024);
+    let status = if free < CRITICAL_DISK_BYTES {
+        HealthStatus::Error
+    } else if free < LOW_DISK_BYTES {
+        HealthStatus::Warning
+    } else {
+        HealthStatus::Ok
+    };
+    result("disk-space", status, format!("{megabytes} MB free on the workspace's volume."))
+}
+
+/// Version of the `sqlite3` binary the index relies on.
+pub fn sqlite_version() -> Result<String, String> {
+    let output = Command::new("sqlite3")
+        .arg("-version")
+        .output()
+        .map_err(|err| format!("sqlite3 is not available, so search and the index won't work: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("sqlite3 failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.split_whitespace().next().unwrap_or("unknown").to_string())
+}
+
+fn check_sqlite() -> HealthCheck {
+    match sqlite_version() {
+        Ok(version) => result("sqlite", HealthStatus::Ok, format!("sqlite3 {version} is available.")),
+        Err(err) => result("sqlite", HealthStatus::Error, err),
+    }
+}
+
+fn check_index_schema(workspace_path: &str) -> HealthCheck {
+    let db_path = sqlite_path(workspace_path);
+    if !db_path.exists() {
+        return result("index-schema", HealthStatus::Warning, "The workspace hasn't been indexed yet.");
+    }
+    if let Some(problem) = index_integrity::corruption(&db_path) {
+        return result("index-schema", HealthStatus::Error, format!("The index is corrupt: {problem}"));
+    }
+    let rows = match run_sqlite_query(&db_path, "SELECT name FROM sqlite_master WHERE type = 'table';") {
+        Ok(rows) => rows,
+        Err(err) => return result("index-schema", HealthStatus::Error, err),
+    };
+    let tables: Vec<&str> = rows.iter().filter_map(|row| row.get("name").and_then(|value| value.as_str())).collect();
+    let missing: Vec<&str> = INDEX_TABLES.iter().copied().filter(|table| !tables.contains(table)).collect();
+    if missing.is_empty() {
+        result("index-schema", HealthStatus::Ok, "The index schema is up to date.")
+    } else {
+        // The next save creates the missing tables.
+        result(
+            "index-schema",
+            HealthStatus::Warning,
+            format!("The index predates this version and lacks {}.", missing.join(", ")),
+        )
+    }
+}
+
+fn check_conflicts(folders: &[String]) -> HealthCheck {
+    let count: usize = folders.iter().map(|folder| conflicts::find_conflicts(folder).len()).sum();
+    match count {
+        0 => result("conflicts", HealthStatus::Ok, "No conflicted copies found."),
+        1 => result("conflicts", HealthStatus::Warning, "1 conflicted copy needs resolving."),
+        count => result("conflicts", HealthStatus::Warning, format!("{count} conflicted copies need resolving.")),
+    }
+}
+
+/// Lists each folder and reads each of its notes, reporting the first denial.
+fn check_permissions(folders: &[String]) -> HealthCheck {
+    for folder in folders {
+        if let Err(err) = fs::read_dir(folder) {
+            return failure("permissions", io_error("reading", Path::new(folder), err));
+        }
+        for tab in TAB_KEYS {
+            let file_path = note_path(folder, tab);
+            match fs::read(&file_path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    return failure("permissions", io_error("reading", &file_path, err));
+                }
+                _ => {}
+            }
+        }
+    }
+    result("permissions", HealthStatus::Ok, "All notes are readable.")
+}
+
+/// Checks writability, disk space, sqlite, the index schema, conflicted copies
+/// and file permissions of a workspace and its projects.
+#[tauri::command]
+pub async fn check_workspace_health(workspace_path: String) -> Result<HealthReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&workspace_path);
+        let mut folders = vec![workspace_path.clone()];
+        folders.extend(
+            read_project_names(&workspace_path)
+                .unwrap_or_default()
+                .iter()
+                .map(|project| root.join(project).to_string_lossy().to_string()),
+        );
+
+        let checks = vec![
+            check_writable(&workspace_path),
+            check_disk_space(&workspace_path),
+            check_sqlite(),
+            check_index_schema(&workspace_path),
+            check_conflicts(&folders),
+            check_permissions(&folders),
+        ];
+        let status = checks.iter().map(|check| check.status).max().unwrap_or(HealthStatus::Ok);
+        HealthReport {
+            workspace_path,
+            status,
+            checks,
+        }
+    })
+    .await
+    .map_err(|err| format!("Workspace health task failed: {err}"))
+}
@@ -0,0 +1,295 @@
+//! Importing one workspace into another.
+//!
+//! `merge_workspaces` copies every project, note and asset of `src` into
+//! `dest` and leaves `src` as it was. A project only `src` has is copied
+//! whole. A project both have is, with `Rename`, copied next to the other
+//! under a free name such as `Essays (2)`; with `Append`, each tab both have
+//! written gets the `src` text below the `dest` text after `SEPARATOR`, and
+//! assets are copied in, renamed when a file of the same name is there. The
+//! notes of the workspace root are handled like a project named after `src`.
+//! Encrypted notes can't be joined, so those collisions are skipped and
+//! listed in the report.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::attachments;
+use crate::progress::Progress;
+use crate::{
+    canonical_workspace, held_saves, note_lock, note_path, path_safety, read_project_names, read_workspace_files,
+    write_pages, CommandError, TAB_KEYS,
+};
+
+const SEPARATOR: &str = "\n\n---\n\n";
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    /// Same-named projects are copied in under a new name.
+    Rename,
+    /// Same-named projects are merged tab by tab.
+    Append,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedProject {
+    /// Empty for the notes of the workspace root.
+    from: String,
+    to: String,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReport {
+    /// Projects copied whole, under their own name.
+    added: Vec<String>,
+    renamed: Vec<RenamedProject>,
+    /// Tabs with text on both sides that were joined, as `project/tab`.
+    appended: Vec<String>,
+    /// Tabs left out, as `project/tab`, with why.
+    skipped: Vec<String>,
+    assets_copied: usize,
+    /// Assets copied in under a new name, relative to `dest`.
+    assets_renamed: Vec<String>,
+}
+
+/// `name`, or `name (2)`, `name (3)` and so on, whichever `taken` doesn't refuse.
+pub fn free_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(name) {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| format!("{stem} ({n}){ext}"))
+        .find(|candidate| !taken(candidate))
+        .expect("some numbered name is free")
+}
+
+/// Where in a workspace `copy_assets` is copying from.
+#[derive(Clone, Copy, PartialEq)]
+enum Level {
+    /// The workspace root: its folders are projects, copied on their own.
+    Root,
+    Project,
+    Nested,
+}
+
+fn is_tab_file(folder: &Path, name: &str) -> bool {
+    crate::tab_files::tab_of(&folder.to_string_lossy(), name).is_some()
+}
+
+/// Copies the files under `from` into `to`, leaving out hidden entries other
+/// than the attachments and tab files; files whose name is taken get a free one.
+fn copy_assets(from: &Path, to: &Path, level: Level, dest_root: &Path, report: &mut MergeReport) -> Result<(), String> {
+    let entries = fs::read_dir(from).map_err(|err| format!("Failed reading {}: {err}", from.display()))?;
+    // The attachments folder goes along even when it is hidden or looks like a project.
+    let attachments = from.join(attachments::relative());
+    if level != Level::Nested && attachments.is_dir() {
+        let target = to.join(attachments::relative());
+        fs::create_dir_all(&target).map_err(|err| format!("Failed creating {}: {err}", target.display()))?;
+        copy_assets(&attachments, &target, Level::Nested, dest_root, report)?;
+    }
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let skipped = level != Level::Nested && (is_tab_file(from, &name) || attachments::is_top_folder(&name));
+        if name.starts_with('.') || skipped {
+            continue;
+        }
+        let source = entry.path();
+        if source.is_dir() {
+            // Sub-projects are merged on their own, like projects.
+            if level == Level::Root || crate::project_list::is_subproject(&source) {
+                continue;
+            }
+            let target = to.join(&name);
+            fs::create_dir_all(&target).map_err(|err| format!("Failed creating {}: {err}", target.display()))?;
+            copy_assets(&source, &target, Level::Nested, dest_root, report)?;
+            continue;
+        }
+        let free = free_name(&name, |candidate| to.join(candidate).exists());
+        let target = to.join(&free);
+        fs::copy(&source, &target).map_err(|err| format!("Failed copying {}: {err}", source.display()))?;
+        report.assets_copied += 1;
+        if free != name {
+            let relative = target.strip_prefix(dest_root).unwrap_or(&target);
+            report.assets_renamed.push(relative.to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
+
+/// How the tabs of one folder go into another.
+#[derive(Default)]
+struct TabMerge {
+    /// Content to save, joined below what was there where both had text.
+    pages: HashMap<String, String>,
+    /// Encrypted tabs with nothing to join, copied as they are.
+    copies: Vec<String>,
+    appended: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl TabMerge {
+    fn collides(&self) -> bool {
+        !self.appended.is_empty() || !self.skipped.is_empty()
+    }
+
+    /// Writes the merge into `to` and adds it to `report`.
+    fn apply(self, app: &AppHandle, from: &str, to: &str, report: &mut MergeReport) -> Result<(), CommandError> {
+        write_pages(app, to, &self.pages)?;
+        for tab in &self.copies {
+            let source = note_path(from, tab);
+            fs::copy(&source, note_path(to, tab)).map_err(|err| format!("Failed copying {}: {err}", source.display()))?;
+        }
+        report.appended.extend(self.appended);
+        report.skipped.extend(self.skipped);
+        Ok(())
+    }
+}
+
+/// Works out how the tabs of `from` go into `to`; `label` names the project in the report.
+fn merge_tabs(from: &str, to: &str, label: &str) -> Result<TabMerge, CommandError> {
+    let incoming = read_workspace_files(from)?;
+    let existing = read_workspace_files(to)?;
+    let mut merge = TabMerge::default();
+    for (tab, content) in incoming {
+        if content.trim().is_empty() {
+            continue;
+        }
+        let name = if label.is_empty() { tab.clone() } else { format!("{label}/{tab}") };
+        let current = existing.get(&tab).map(String::as_str).unwrap_or("");
+        let locked = note_lock::is_locked_file(&note_path(from, &tab)) || note_lock::is_locked_file(&note_path(to, &tab));
+        match (current.trim().is_empty(), locked) {
+            (true, false) => {
+                merge.pages.insert(tab, content);
+            }
+            (true, true) => merge.copies.push(tab),
+            (false, true) => merge.skipped.push(format!("{name}: encrypted")),
+            (false, false) => {
+                merge.pages.insert(tab, format!("{}{SEPARATOR}{}", current.trim_end(), content.trim_start()));
+                merge.appended.push(name);
+            }
+        }
+    }
+    Ok(merge)
+}
+
+/// Copies the tabs and assets of `from` into the new folder `to`.
+fn copy_project(from: &Path, to: &Path, level: Level, dest_root: &Path, report: &mut MergeReport) -> Result<(), String> {
+    let long_to = path_safety::long_path(to);
+    fs::create_dir_all(&long_to).map_err(|err| format!("Failed creating {}: {err}", to.display()))?;
+    for tab in TAB_KEYS {
+        let source = note_path(&from.to_string_lossy(), tab);
+        if source.exists() {
+            let target = note_path(&to.to_string_lossy(), tab);
+            fs::copy(&source, &target).map_err(|err| format!("Failed copying {}: {err}", source.display()))?;
+        }
+    }
+    copy_assets(&path_safety::long_path(from), &long_to, level, &path_safety::long_path(dest_root), report)
+}
+
+/// Imports every project, note and asset of the workspace `src` into `dest`
+/// and reports what was renamed, joined or left out.
+#[tauri::command]
+pub fn merge_workspaces(
+    app: AppHandle,
+    src: String,
+    dest: String,
+    strategy: MergeStrategy,
+    operation_id: Option<String>,
+) -> Result<MergeReport, CommandError> {
+    let src = canonical_workspace(src.trim_end_matches(['/', '\\']));
+    let dest = canonical_workspace(dest.trim_end_matches(['/', '\\']));
+    if !Path::new(&src).is_dir() {
+        return Err(format!("{src} is not a folder.").into());
+    }
+    if !held_saves::is_available(&app, &dest) {
+        return Err(format!("{dest} is not available.").into());
+    }
+    if Path::new(&src).starts_with(&dest) || Path::new(&dest).starts_with(&src) {
+        return Err("Choose two separate workspaces; one is inside the other.".to_string().into());
+    }
+    crate::read_only::ensure_writable(&app, &dest)?;
+    // What the user is still typing in `dest` goes in before it is merged into.
+    crate::workspaces::release(&app, &dest)?;
+
+    let dest_root = PathBuf::from(&dest);
+    let src_name = Path::new(&src)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported".to_string());
+    let projects = read_project_names(&src)?;
+    let total = projects.len() + 1;
+    let progress = Progress::new(&app, "merge", operation_id);
+    let mut report = MergeReport::default();
+
+    for (done, project) in projects.iter().enumerate() {
+        progress.report("projects", done, total);
+        let from = Path::new(&src).join(project);
+        let to = dest_root.join(project);
+        if !to.exists() {
+            copy_project(&from, &to, Level::Project, &dest_root, &mut report)?;
+            report.added.push(project.clone());
+            continue;
+        }
+        match strategy {
+            MergeStrategy::Rename => {
+                let free = free_name(project, |candidate| dest_root.join(candidate).exists());
+                copy_project(&from, &dest_root.join(&free), Level::Project, &dest_root, &mut report)?;
+                report.renamed.push(RenamedProject {
+                    from: project.clone(),
+                    to: free,
+                });
+            }
+            MergeStrategy::Append => {
+                let (from, to) = (from.to_string_lossy().to_string(), to.to_string_lossy().to_string());
+                merge_tabs(&from, &to, project)?.apply(&app, &from, &to, &mut report)?;
+                copy_assets(
+                    &path_safety::long_path(Path::new(&from)),
+                    &path_safety::long_path(Path::new(&to)),
+                    Level::Project,
+                    &path_safety::long_path(&dest_root),
+                    &mut report,
+                )?;
+            }
+        }
+    }
+
+    // The root's notes join the root's, or become a project when they'd collide under `Rename`.
+    progress.report("notes", projects.len(), total);
+    let root_merge = merge_tabs(&src, &dest, "")?;
+    if matches!(strategy, MergeStrategy::Rename) && root_merge.collides() {
+        let free = free_name(&src_name, |candidate| dest_root.join(candidate).exists());
+        path_safety::check_folder_name(&free)?;
+        copy_project(Path::new(&src), &dest_root.join(&free), Level::Root, &dest_root, &mut report)?;
+        report.renamed.push(RenamedProject {
+            from: String::new(),
+            to: free,
+        });
+    } else {
+        root_merge.apply(&app, &src, &dest, &mut report)?;
+        copy_assets(
+            &path_safety::long_path(Path::new(&src)),
+            &path_safety::long_path(&dest_root),
+            Level::Root,
+            &path_safety::long_path(&dest_root),
+            &mut report,
+        )?;
+    }
+    progress.finish(total);
+    tracing::info!("Merged workspace {src} into {dest}");
+    let change = crate::changes::Change::WorkspaceMerged {
+        workspace_path: dest,
+        from: src,
+    };
+    crate::changes::emit(&app, change);
+    Ok(report)
+}
@@ -0,0 +1,196 @@
+//! A workspace's display name, icon and accent color.
+//!
+//! They live in the workspace's `.hermes/workspace.json`, so they travel with
+//! the folder like its settings. Window titles, the recents list, the Open
+//! Recent menu and the tray show `label` instead of the folder path; a
+//! workspace without metadata is labelled with its folder name.
+//! `WORKSPACE_META_EVENT` tells every window when the metadata changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+use crate::project_windows::WindowContexts;
+use crate::{canonical_workspace, hermes_dir, CommandError};
+
+pub const WORKSPACE_META_EVENT: &str = "workspace://meta";
+
+const META_FILE: &str = "workspace.json";
+const MAX_NAME_CHARS: usize = 80;
+/// Enough for an emoji built from several code points, such as a flag.
+const MAX_ICON_CHARS: usize = 8;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMeta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// An emoji or a short symbol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    /// `#rrggbb`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaChanged {
+    workspace_path: String,
+    meta: WorkspaceMeta,
+    label: String,
+}
+
+impl WorkspaceMeta {
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    pub fn color(&self) -> Option<&str> {
+        self.color.as_deref()
+    }
+
+    /// Trims every field and drops the empty ones.
+    fn normalize(self) -> WorkspaceMeta {
+        let clean = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        WorkspaceMeta {
+            name: clean(self.name),
+            icon: clean(self.icon),
+            color: clean(self.color).map(|color| color.to_ascii_lowercase()),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(name) = &self.name {
+            if name.chars().count() > MAX_NAME_CHARS {
+                return Err(format!("Keep the workspace name under {MAX_NAME_CHARS} characters."));
+            }
+        }
+        if let Some(icon) = &self.icon {
+            validate_icon(icon)?;
+        }
+        if let Some(color) = &self.color {
+            validate_color(color)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `icon` is one emoji or a short symbol.
+pub fn validate_icon(icon: &str) -> Result<(), String> {
+    if icon.chars().count() > MAX_ICON_CHARS || icon.chars().any(char::is_whitespace) {
+        return Err(format!("Invalid icon '{icon}'; use one emoji."));
+    }
+    Ok(())
+}
+
+/// Checks that `color` is written `#rrggbb`.
+pub fn validate_color(color: &str) -> Result<(), String> {
+    let hex = color.strip_prefix('#').unwrap_or_default();
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid color '{color}'; use #rrggbb."));
+    }
+    Ok(())
+}
+
+fn meta_path(workspace_path: &str) -> PathBuf {
+    hermes_dir(workspace_path).join(META_FILE)
+}
+
+/// The metadata of `workspace_path`, empty when it has none or it can't be read.
+pub fn read(workspace_path: &str) -> WorkspaceMeta {
+    let path = meta_path(workspace_path);
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            tracing::warn!("Failed parsing {}: {err}", path.display());
+            WorkspaceMeta::default()
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => WorkspaceMeta::default(),
+        Err(err) => {
+            tracing::warn!("Failed reading {}: {err}", path.display());
+            WorkspaceMeta::default()
+        }
+    }
+}
+
+/// The workspace's name, or its folder name when it has none.
+pub fn display_name(workspace_path: &str, meta: &WorkspaceMeta) -> String {
+    meta.name.clone().unwrap_or_else(|| {
+        Path::new(workspace_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| workspace_path.to_string())
+    })
+}
+
+/// Icon and name, as menus and window titles show the workspace.
+pub fn label(workspace_path: &str) -> String {
+    let meta = read(workspace_path);
+    let name = display_name(workspace_path, &meta);
+    match meta.icon {
+        Some(icon) => format!("{icon} {name}"),
+        None => name,
+    }
+}
+
+/// Titles a window after the workspace it shows, and the project for a project window.
+pub fn retitle(app: &AppHandle, window: &WebviewWindow, workspace_path: &str) {
+    let label = label(workspace_path);
+    let title = match app.state::<WindowContexts>().0.lock().unwrap().get(window.label()) {
+        Some(context) => format!("{label} — {}", context.project),
+        None => format!("Hermes — {label}"),
+    };
+    if let Err(err) = window.set_title(&title) {
+        tracing::warn!("Failed to set window title: {err}");
+    }
+}
+
+#[tauri::command]
+pub fn get_workspace_meta(workspace_path: String) -> WorkspaceMeta {
+    read(&canonical_workspace(&workspace_path))
+}
+
+/// Replaces the metadata of `workspace_path`; empty fields are removed.
+#[tauri::command]
+pub fn set_workspace_meta(app: AppHandle, workspace_path: String, meta: WorkspaceMeta) -> Result<WorkspaceMeta, CommandError> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    if !Path::new(&workspace_path).is_dir() {
+        return Err(format!("{workspace_path} is not a folder.").into());
+    }
+    let meta = meta.normalize();
+    meta.validate()?;
+    crate::read_only::ensure_writable(&app, &workspace_path)?;
+
+    let path = meta_path(&workspace_path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed creating {}: {err}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(&meta).map_err(|err| format!("Failed encoding workspace metadata: {err}"))?;
+    fs::write(&path, json).map_err(|err| format!("Failed writing {}: {err}", path.display()))?;
+
+    for label in crate::workspaces::windows_with(&app, &workspace_path) {
+        if let Some(window) = app.get_webview_window(&label) {
+            retitle(&app, &window, &workspace_path);
+        }
+    }
+    #[cfg(desktop)]
+    {
+        crate::menu::refresh(&app);
+        crate::tray::refresh(&app);
+    }
+    let change = crate::changes::Change::WorkspaceMetaChanged {
+        workspace_path: workspace_path.clone(),
+    };
+    crate::changes::emit(&app, change);
+    let payload = MetaChanged {
+        label: label(&workspace_path),
+        workspace_path,
+        meta: meta.clone(),
+    };
+    if let Err(err) = app.emit(WORKSPACE_META_EVENT, payload) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(meta)
+}
@@ -0,0 +1,129 @@
+//! Moving a workspace folder without leaving its metadata behind.
+//!
+//! `move_workspace` moves the folder, or adopts one already moved in Finder,
+//! then points everything that names the old location at the new one: the
+//! configured workspace, recents, the macOS access bookmark, held saves, the
+//! read-only mark, the windows that have it open and project windows. Rows in
+//! the indexes predating relative `file_path`s are rewritten to the relative
+//! form. Moving across drives isn't a rename, so it is refused; moving the
+//! folder by hand and adopting it covers that.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::{
+    bookmarks, canonical_workspace, file_open, held_saves, project_windows, read_only, recents, run_sqlite_script, sqlite_path,
+    workspaces, CommandError,
+};
+
+pub const WORKSPACE_MOVED_EVENT: &str = "workspace://moved";
+
+/// Points index rows written with absolute paths at their tab file.
+const RELATIVE_FILE_PATHS: &str = "UPDATE note_index SET file_path = tab_key || '.md' WHERE file_path <> tab_key || '.md';\n";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMoved {
+    old_path: String,
+    new_path: String,
+    /// False when the folder had already been moved and was only adopted.
+    moved: bool,
+    /// Project indexes whose rows were rewritten, the workspace root's included.
+    indexes: usize,
+}
+
+/// `path` with the `old` prefix swapped for `new`, if it is under `old`.
+pub fn rebase(path: &str, old: &str, new: &str) -> Option<String> {
+    let rest = Path::new(path).strip_prefix(old).ok()?;
+    if rest.as_os_str().is_empty() {
+        return Some(new.to_string());
+    }
+    Some(Path::new(new).join(rest).to_string_lossy().to_string())
+}
+
+/// Rewrites the index of the workspace root and of every project in it.
+fn rewrite_indexes(workspace_path: &str) -> usize {
+    let projects = fs::read_dir(workspace_path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir() && !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path().to_string_lossy().to_string());
+    let mut rewritten = 0;
+    for path in std::iter::once(workspace_path.to_string()).chain(projects) {
+        let db_path = sqlite_path(&path);
+        if !db_path.exists() {
+            continue;
+        }
+        match run_sqlite_script(&db_path, RELATIVE_FILE_PATHS) {
+            Ok(()) => rewritten += 1,
+            Err(err) => tracing::warn!("{}", err),
+        }
+    }
+    rewritten
+}
+
+/// Moves the workspace at `old_path` to `new_path`, or adopts `new_path` when
+/// the folder is already there, and updates everything that named the old path.
+#[tauri::command]
+pub fn move_workspace(app: AppHandle, old_path: String, new_path: String) -> Result<WorkspaceMoved, CommandError> {
+    let old_path = canonical_workspace(old_path.trim_end_matches(['/', '\\']));
+    let new_path = new_path.trim().trim_end_matches(['/', '\\']).to_string();
+    if old_path.is_empty() || new_path.is_empty() {
+        return Err("Choose both the workspace and where to move it.".to_string().into());
+    }
+    if !Path::new(&new_path).is_absolute() {
+        return Err(format!("Choose a full path to move the workspace to, not '{new_path}'.").into());
+    }
+
+    let moved = if Path::new(&old_path).is_dir() {
+        if Path::new(&new_path).exists() {
+            return Err(format!("{new_path} already exists; choose a new location.").into());
+        }
+        if let Some(name) = Path::new(&new_path).file_name() {
+            crate::path_safety::check_folder_name(&name.to_string_lossy())?;
+        }
+        // Nothing may still be on its way to the old location.
+        workspaces::release(&app, &old_path)?;
+        fs::rename(&old_path, &new_path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::CrossesDevices => format!(
+                "{new_path} is on another drive. Move the folder there yourself, then choose it to update Hermes."
+            ),
+            _ => format!("Failed moving {old_path} to {new_path}: {err}"),
+        })?;
+        true
+    } else if Path::new(&new_path).is_dir() {
+        false
+    } else {
+        return Err(format!("Neither {old_path} nor {new_path} is a folder.").into());
+    };
+    let new_path = canonical_workspace(&new_path);
+    tracing::info!("Workspace {old_path} is now at {new_path}");
+
+    let indexes = rewrite_indexes(&new_path);
+    if let Some(configured) = file_open::configured_workspace(&app) {
+        if let Some(rebased) = rebase(&configured.to_string_lossy(), &old_path, &new_path) {
+            crate::write_setting(&app, "workspacePath", serde_json::json!(rebased))?;
+        }
+    }
+    recents::relocate(&app, &old_path, &new_path);
+    bookmarks::relocate(&app, &old_path, &new_path);
+    held_saves::relocate(&app, &old_path, &new_path);
+    read_only::relocate(&app, &old_path, &new_path);
+    workspaces::relocate(&app, &old_path, &new_path);
+    project_windows::relocate(&app, &old_path, &new_path);
+
+    let report = WorkspaceMoved {
+        old_path,
+        new_path,
+        moved,
+        indexes,
+    };
+    if let Err(err) = app.emit(WORKSPACE_MOVED_EVENT, report.clone()) {
+        tracing::warn!("Failed to emit event: {err}");
+    }
+    Ok(report)
+}
@@ -0,0 +1,171 @@
+//! Note counts, word counts and disk usage of a workspace.
+//!
+//! Counts come from each folder's index rather than from reading every note;
+//! `refresh` reindexes the workspace first when the index may lag behind
+//! edits made outside Hermes. Assets are any other files next to the notes.
+//! A `scope` limits the stats to one project and its sub-projects.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::progress::Progress;
+use crate::{index_compaction, project_list, read_project_names, read_workspace_pages, run_sqlite_query, sqlite_path};
+
+const LARGEST_NOTES: usize = 10;
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderStats {
+    /// The project's path, `None` for the workspace root.
+    project: Option<String>,
+    note_count: u64,
+    word_count: u64,
+    asset_bytes: u64,
+    index_bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSize {
+    project: Option<String>,
+    tab: String,
+    title: String,
+    char_count: u64,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStats {
+    note_count: u64,
+    word_count: u64,
+    asset_bytes: u64,
+    index_bytes: u64,
+    largest_notes: Vec<NoteSize>,
+    /// The root first, then each project.
+    folders: Vec<FolderStats>,
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+/// Bytes of files in `dir` that aren't tab notes; hidden entries and
+/// sub-projects are skipped and subfolders only counted when `recursive`.
+fn asset_bytes(dir: &Path, recursive: bool) -> u64 {
+    let mut total = 0;
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if recursive && !project_list::is_subproject(&entry.path()) {
+                total += asset_bytes(&entry.path(), true);
+            }
+        } else if file_type.is_file() && crate::tab_files::tab_of(&dir.to_string_lossy(), &name).is_none() {
+            total += file_size(&entry.path());
+        }
+    }
+    total
+}
+
+fn folder_stats(folder: &Path, project: Option<&str>, largest: &mut Vec<NoteSize>) -> FolderStats {
+    let folder_path = folder.to_string_lossy().to_string();
+    let db_path = sqlite_path(&folder_path);
+    let mut stats = FolderStats {
+        project: project.map(str::to_string),
+        // Projects own their subfolders; the root's subfolders are projects.
+        asset_bytes: asset_bytes(folder, project.is_some()),
+        index_bytes: index_compaction::index_size(&db_path),
+        ..Default::default()
+    };
+    if !db_path.exists() {
+        return stats;
+    }
+    let rows = match run_sqlite_query(&db_path, "SELECT tab_key, title, word_count, char_count FROM note_index;") {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return stats;
+        }
+    };
+    for row in rows {
+        let text = |column: &str| row.get(column).and_then(|value| value.as_str()).unwrap_or_default().to_string();
+        let number = |column: &str| row.get(column).and_then(|value| value.as_u64()).unwrap_or(0);
+        stats.note_count += 1;
+        stats.word_count += number("word_count");
+        largest.push(NoteSize {
+            project: project.map(str::to_string),
+            tab: text("tab_key"),
+            title: text("title"),
+            char_count: number("char_count"),
+        });
+    }
+    stats
+}
+
+/// Totals for the workspace root and its projects, or for the project at
+/// `scope` and its sub-projects; `refresh` reindexes first, reporting
+/// progress per folder.
+#[tauri::command]
+pub async fn get_workspace_stats(
+    app: AppHandle,
+    workspace_path: String,
+    refresh: Option<bool>,
+    scope: Option<String>,
+    operation_id: Option<String>,
+) -> Result<WorkspaceStats, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&workspace_path);
+        let scope = scope.unwrap_or_default();
+        if !scope.is_empty() {
+            project_list::check_project(&scope)?;
+        }
+        let projects = read_project_names(&workspace_path).map_err(String::from)?;
+        let mut folders = Vec::new();
+        if scope.is_empty() {
+            folders.push((root.to_path_buf(), None));
+        }
+        folders.extend(
+            projects
+                .iter()
+                .filter(|project| project_list::in_scope(project, &scope))
+                .map(|project| (root.join(project), Some(project.as_str()))),
+        );
+
+        let refresh = refresh.unwrap_or(false);
+        let progress = refresh.then(|| Progress::new(&app, "reindex", operation_id));
+        let total = folders.len();
+        let mut stats = WorkspaceStats::default();
+        let mut largest = Vec::new();
+        for (done, (folder, project)) in folders.into_iter().enumerate() {
+            if let Some(progress) = &progress {
+                progress.report("indexing", done, total);
+                if let Err(err) = read_workspace_pages(&folder.to_string_lossy()) {
+                    tracing::warn!("{}", String::from(err));
+                }
+            }
+            let folder_stats = folder_stats(&folder, project, &mut largest);
+            stats.note_count += folder_stats.note_count;
+            stats.word_count += folder_stats.word_count;
+            stats.asset_bytes += folder_stats.asset_bytes;
+            stats.index_bytes += folder_stats.index_bytes;
+            stats.folders.push(folder_stats);
+        }
+        if let Some(progress) = &progress {
+            progress.finish(total);
+        }
+        largest.sort_by_key(|note| std::cmp::Reverse(note.char_count));
+        largest.truncate(LARGEST_NOTES);
+        stats.largest_notes = largest;
+        Ok(stats)
+    })
+    .await
+    .map_err(|err| format!("Workspace stats task failed: {err}"))?
+}
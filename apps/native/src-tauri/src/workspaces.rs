@@ -0,0 +1,156 @@
+//! The workspaces each window has open.
+//!
+//! Commands still name the workspace they act on, so two windows can work in
+//! different workspaces at once. The manager records which workspace roots
+//! each window opened with `open_workspace`, so what Hermes keeps per
+//! workspace lives only while some window uses it: once the last window
+//! closes a workspace, or is destroyed, its unsaved edits and queued saves are
+//! written and its remembered file hashes dropped. Hermes has no file watcher
+//! and reaches the index through one `sqlite3` call at a time, and the write
+//! lock is taken per write, so there is nothing else to hold open.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::{
+    autosave, canonical_workspace, file_hashes, held_saves, notes_dir, project_list, recents, save_queue, volume, workspace_meta, workspace_move,
+    CommandError,
+};
+
+/// Workspace roots open in each window, keyed by window label.
+#[derive(Default)]
+pub struct WorkspaceManager(Mutex<HashMap<String, BTreeSet<String>>>);
+
+impl WorkspaceManager {
+    fn is_open_elsewhere(&self, label: &str, workspace_path: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(other, open)| other != label && open.contains(workspace_path))
+    }
+}
+
+/// Writes the unsaved edits and queued saves of `workspace_path` and its projects.
+pub fn flush_under(app: &AppHandle, workspace_path: &str) -> Result<(), CommandError> {
+    let root = Path::new(workspace_path);
+    let pending: BTreeSet<String> = autosave::dirty_workspaces(app)
+        .into_iter()
+        .chain(save_queue::pending_workspaces(app))
+        .filter(|path| Path::new(path).starts_with(root))
+        .collect();
+    let mut result = Ok(());
+    for path in pending {
+        if let Err(err) = autosave::flush(app, Some(&path)) {
+            if result.is_ok() {
+                result = Err(err);
+            }
+        }
+    }
+    result
+}
+
+/// Writes what is pending for a workspace no window has open any more and
+/// drops what was kept for it.
+pub fn release(app: &AppHandle, workspace_path: &str) -> Result<(), CommandError> {
+    let result = flush_under(app, workspace_path);
+    save_queue::forget_under(app, Path::new(workspace_path));
+    file_hashes::forget_under(&notes_dir(workspace_path));
+    result
+}
+
+/// Labels of the windows that have `workspace_path` open.
+pub fn windows_with(app: &AppHandle, workspace_path: &str) -> Vec<String> {
+    app.state::<WorkspaceManager>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, open)| open.contains(workspace_path))
+        .map(|(label, _)| label.clone())
+        .collect()
+}
+
+/// Follows workspaces moved from under `old_path` to `new_path` in every window.
+pub fn relocate(app: &AppHandle, old_path: &str, new_path: &str) {
+    for open in app.state::<WorkspaceManager>().0.lock().unwrap().values_mut() {
+        *open = std::mem::take(open)
+            .into_iter()
+            .map(|path| workspace_move::rebase(&path, old_path, new_path).unwrap_or(path))
+            .collect();
+    }
+}
+
+/// Closes every workspace of a destroyed window.
+pub fn window_destroyed(app: &AppHandle, label: &str) {
+    let state = app.state::<WorkspaceManager>();
+    let Some(open) = state.0.lock().unwrap().remove(label) else {
+        return;
+    };
+    for workspace_path in open {
+        if state.is_open_elsewhere(label, &workspace_path) {
+            continue;
+        }
+        if let Err(err) = release(app, &workspace_path) {
+            tracing::warn!("{}", String::from(err));
+        }
+    }
+}
+
+/// Opens the workspace at `workspace_path` in the calling window and returns
+/// the path Hermes knows it by.
+#[tauri::command]
+pub fn open_workspace(app: AppHandle, window: WebviewWindow, workspace_path: String) -> Result<String, CommandError> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    if workspace_path.is_empty() {
+        return Err("No workspace folder was given.".to_string().into());
+    }
+    if !held_saves::is_available(&app, &workspace_path) {
+        return Err(format!("{workspace_path} is not available; reconnect its drive or choose another folder.").into());
+    }
+    app.state::<WorkspaceManager>()
+        .0
+        .lock()
+        .unwrap()
+        .entry(window.label().to_string())
+        .or_default()
+        .insert(workspace_path.clone());
+    recents::record(&app, &workspace_path);
+    project_list::register_root(&workspace_path);
+    workspace_meta::retitle(&app, &window, &workspace_path);
+    volume::warn_once(&app, &workspace_path);
+    Ok(workspace_path)
+}
+
+/// Closes `workspace_path` in the calling window, writing its pending edits
+/// when no other window has it open.
+#[tauri::command]
+pub fn close_workspace(app: AppHandle, window: WebviewWindow, workspace_path: String) -> Result<(), CommandError> {
+    let workspace_path = canonical_workspace(workspace_path.trim_end_matches(['/', '\\']));
+    let state = app.state::<WorkspaceManager>();
+    let was_open = state
+        .0
+        .lock()
+        .unwrap()
+        .get_mut(window.label())
+        .is_some_and(|open| open.remove(&workspace_path));
+    if !was_open || state.is_open_elsewhere(window.label(), &workspace_path) {
+        return Ok(());
+    }
+    release(&app, &workspace_path)
+}
+
+/// Workspace roots open in the calling window.
+#[tauri::command]
+pub fn list_open_workspaces(app: AppHandle, window: WebviewWindow) -> Vec<String> {
+    app.state::<WorkspaceManager>()
+        .0
+        .lock()
+        .unwrap()
+        .get(window.label())
+        .map(|open| open.iter().cloned().collect())
+        .unwrap_or_default()
+}
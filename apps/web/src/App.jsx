@@ -1,8 +1,16 @@
-import { useEffect } from 'react';
+import { useEffect, useState } from 'react';
 import { Toaster } from 'react-hot-toast';
 import styles from './App.module.css';
 import FocusPage from './pages/FocusPage/FocusPage';
+import LockPage from './pages/LockPage/LockPage';
+import QuickCapturePage from './pages/QuickCapturePage/QuickCapturePage';
+import ScratchpadPage from './pages/ScratchpadPage/ScratchpadPage';
 import { loadSettings } from './lib/settingsStorage';
+import { IS_TAURI } from './lib/platform';
+import { startPlugins } from './lib/plugins';
+
+// Secondary Tauri windows load the same bundle with a ?window= marker.
+const WINDOW_KIND = new URLSearchParams(window.location.search).get('window');
 
 function applyTheme(theme) {
   const root = document.documentElement;
@@ -18,23 +26,101 @@ function applyTheme(theme) {
 }
 
 export default function App() {
+  const [locked, setLocked] = useState(false);
+
+  // Editor windows swap to the lock screen while the backend holds the app lock.
   useEffect(() => {
-    let cleanup;
+    if (!IS_TAURI || WINDOW_KIND === 'quick-capture' || WINDOW_KIND === 'scratchpad') return;
+    let unlisteners = [];
     (async () => {
-      const settings = await loadSettings();
-      const theme = settings.theme || 'system';
+      const { invoke } = await import('@tauri-apps/api/core');
+      const { listen } = await import('@tauri-apps/api/event');
+      unlisteners = await Promise.all([
+        listen('app-locked', () => setLocked(true)),
+        listen('app-unlocked', () => setLocked(false)),
+      ]);
+      setLocked(await invoke('is_app_locked'));
+    })();
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  }, []);
+
+  // Report input to the backend's idle timer, at most every 30 seconds.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let lastReport = 0;
+    const report = () => {
+      const now = Date.now();
+      if (now - lastReport < 30_000) return;
+      lastReport = now;
+      void import('@tauri-apps/api/core').then(({ invoke }) => invoke('record_activity'));
+    };
+    window.addEventListener('keydown', report);
+    window.addEventListener('pointerdown', report);
+    return () => {
+      window.removeEventListener('keydown', report);
+      window.removeEventListener('pointerdown', report);
+    };
+  }, []);
+
+  // Plugins run once, in the main window, and stop while the app is locked.
+  useEffect(() => {
+    if (!IS_TAURI || WINDOW_KIND || locked) return undefined;
+    let cancelled = false;
+    let stopPlugins;
+    void startPlugins().then((stop) => {
+      if (cancelled) stop();
+      else stopPlugins = stop;
+    });
+    return () => {
+      cancelled = true;
+      stopPlugins?.();
+    };
+  }, [locked]);
+
+  useEffect(() => {
+    let cleanupTheme;
+    let unlistenSettings;
+    const watchTheme = (theme) => {
+      cleanupTheme?.();
+      cleanupTheme = undefined;
       applyTheme(theme);
 
       if (theme === 'system') {
         const mq = window.matchMedia('(prefers-color-scheme: dark)');
         const handler = () => applyTheme('system');
         mq.addEventListener('change', handler);
-        cleanup = () => mq.removeEventListener('change', handler);
+        cleanupTheme = () => mq.removeEventListener('change', handler);
+      }
+    };
+    (async () => {
+      const settings = await loadSettings();
+      watchTheme(settings.theme || 'system');
+
+      // A theme picked in any window applies to all of them.
+      if (IS_TAURI) {
+        const { listen } = await import('@tauri-apps/api/event');
+        unlistenSettings = await listen('settings://changed', (event) => {
+          if (!event.payload.workspacePath) watchTheme(event.payload.settings.theme);
+        });
       }
     })();
-    return () => cleanup?.();
+    return () => {
+      cleanupTheme?.();
+      unlistenSettings?.();
+    };
   }, []);
 
+  if (WINDOW_KIND === 'quick-capture') {
+    return <QuickCapturePage />;
+  }
+  if (WINDOW_KIND === 'scratchpad') {
+    return <ScratchpadPage />;
+  }
+
+  if (locked) {
+    return <LockPage />;
+  }
+
   return (
     <div className={styles.app}>
       <FocusPage />
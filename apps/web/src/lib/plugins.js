@@ -0,0 +1,193 @@
+import { IS_TAURI } from './platform';
+import { onWorkspaceChange } from './workspaceStorage';
+
+// Plugins run in Web Workers, which have no Tauri API. A plugin asks the host
+// through `hermes.*` calls, and the host passes them on to the plugin_*
+// commands with the plugin's ID; the backend checks the permissions granted
+// in settings, so a plugin can't do more than the user allowed.
+
+// Runs before the plugin's script: takes away the worker's own ways out.
+const PRELUDE = `
+for (const name of ['fetch', 'XMLHttpRequest', 'WebSocket', 'EventSource', 'WebTransport', 'importScripts', 'Worker', 'SharedWorker', 'indexedDB', 'caches']) {
+  try { Object.defineProperty(self, name, { value: undefined, writable: false, configurable: false }); } catch {}
+}
+const hermes = (() => {
+  const post = self.postMessage.bind(self);
+  const handlers = { command: new Map(), exporter: new Map() };
+  const changeHandlers = [];
+  const pending = new Map();
+  let nextCall = 0;
+  const call = (method, args) => new Promise((resolve, reject) => {
+    const callId = ++nextCall;
+    pending.set(callId, { resolve, reject });
+    post({ type: 'call', callId, method, args });
+  });
+  self.onmessage = async ({ data }) => {
+    if (data.type === 'result') {
+      const waiting = pending.get(data.callId);
+      pending.delete(data.callId);
+      if (data.error !== undefined) waiting?.reject(new Error(data.error));
+      else waiting?.resolve(data.result);
+    } else if (data.type === 'run') {
+      try {
+        const handler = handlers[data.kind].get(data.id);
+        if (!handler) throw new Error('No such ' + data.kind + ' ' + data.id);
+        post({ type: 'reply', replyId: data.replyId, result: await handler(...data.args) });
+      } catch (err) {
+        post({ type: 'reply', replyId: data.replyId, error: String(err?.message ?? err) });
+      }
+    } else if (data.type === 'change') {
+      for (const handler of changeHandlers) {
+        try { await handler(data.change); } catch (err) { console.error(err); }
+      }
+    }
+  };
+  return Object.freeze({
+    registerCommand(id, title, handler) {
+      handlers.command.set(String(id), handler);
+      post({ type: 'register', kind: 'command', id: String(id), title: String(title) });
+    },
+    registerExporter({ id, label, extension }, handler) {
+      handlers.exporter.set(String(id), handler);
+      post({ type: 'register', kind: 'exporter', id: String(id), label: String(label), extension: String(extension) });
+    },
+    onChange(handler) { changeHandlers.push(handler); },
+    notes: Object.freeze({
+      list: (project) => call('notes.list', { project: project ?? null }),
+      read: (project, tab) => call('notes.read', { project, tab }),
+      write: (project, tab, content) => call('notes.write', { project, tab, content }),
+    }),
+    fetch: (url, { method, headers, body } = {}) => call('fetch', { url, method, headers, body }),
+  });
+})();
+`;
+
+// The commands each plugin call may reach, with the arguments it may pass.
+const CALLS = {
+  'notes.list': (args) => ['plugin_list_notes', { project: args.project }],
+  'notes.read': (args) => ['plugin_read_note', { project: args.project ?? '', tab: args.tab }],
+  'notes.write': (args) => ['plugin_write_note', { project: args.project ?? '', tab: args.tab, content: String(args.content ?? '') }],
+  'fetch': (args) => ['plugin_fetch', { url: args.url, method: args.method, headers: args.headers, body: args.body }],
+};
+
+// Running plugins by ID: { plugin, url, worker, commands, exporters, replies }.
+const running = new Map();
+let started = false;
+let nextReply = 0;
+
+// Installed plugins with what they ask for and what was granted.
+export async function listPlugins() {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('list_plugins');
+}
+
+// Enabling grants what the plugin's manifest asks for now, and (re)starts it
+// when plugins are running in this window.
+export async function setPluginEnabled(id, enabled) {
+  if (!IS_TAURI) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('set_plugin_enabled', { id, enabled });
+  stop(id);
+  if (!enabled || !started) return;
+  const plugin = (await listPlugins()).find((candidate) => candidate.id === id);
+  if (plugin) start(plugin, await invoke('load_plugin', { id }), invoke);
+}
+
+function start(plugin, source, invoke) {
+  const url = URL.createObjectURL(new Blob([`${PRELUDE}\n${source}`], { type: 'text/javascript' }));
+  const worker = new Worker(url, { name: `plugin:${plugin.id}` });
+  const entry = { plugin, url, worker, commands: new Map(), exporters: new Map(), replies: new Map() };
+  worker.onmessage = async ({ data }) => {
+    if (data?.type === 'register' && data.kind === 'command') {
+      entry.commands.set(data.id, { pluginId: plugin.id, id: data.id, title: data.title });
+    } else if (data?.type === 'register' && data.kind === 'exporter') {
+      entry.exporters.set(data.id, { pluginId: plugin.id, id: data.id, label: data.label, extension: data.extension });
+    } else if (data?.type === 'reply') {
+      const reply = entry.replies.get(data.replyId);
+      entry.replies.delete(data.replyId);
+      if (data.error !== undefined) reply?.reject(new Error(data.error));
+      else reply?.resolve(data.result);
+    } else if (data?.type === 'call') {
+      const toCommand = Object.hasOwn(CALLS, data.method) ? CALLS[data.method] : null;
+      try {
+        if (!toCommand) throw new Error(`Unknown call ${data.method}`);
+        const [command, args] = toCommand(data.args ?? {});
+        worker.postMessage({ type: 'result', callId: data.callId, result: await invoke(command, { plugin: plugin.id, ...args }) });
+      } catch (err) {
+        worker.postMessage({ type: 'result', callId: data.callId, error: String(err?.message ?? err) });
+      }
+    }
+  };
+  worker.onerror = (event) => console.error(`Plugin ${plugin.id}:`, event.message);
+  running.set(plugin.id, entry);
+}
+
+function stop(id) {
+  const entry = running.get(id);
+  if (!entry) return;
+  entry.worker.terminate();
+  URL.revokeObjectURL(entry.url);
+  for (const reply of entry.replies.values()) reply.reject(new Error('The plugin stopped.'));
+  running.delete(id);
+}
+
+function runInPlugin(pluginId, kind, id, args) {
+  const entry = running.get(pluginId);
+  if (!entry) return Promise.reject(new Error(`Plugin ${pluginId} is not running.`));
+  const replyId = ++nextReply;
+  return new Promise((resolve, reject) => {
+    entry.replies.set(replyId, { resolve, reject });
+    entry.worker.postMessage({ type: 'run', replyId, kind, id, args });
+  });
+}
+
+// Starts every enabled plugin; resolves to a function that stops them all.
+export async function startPlugins() {
+  if (!IS_TAURI || typeof Worker === 'undefined') return () => {};
+  const { invoke } = await import('@tauri-apps/api/core');
+  started = true;
+  for (const plugin of await listPlugins()) {
+    if (!plugin.enabled || running.has(plugin.id)) continue;
+    try {
+      start(plugin, await invoke('load_plugin', { id: plugin.id }), invoke);
+    } catch (err) {
+      console.error(`Failed starting plugin ${plugin.id}:`, err);
+    }
+  }
+  // Changes name notes, so only plugins that may read notes hear about them.
+  const unlisten = await onWorkspaceChange((change) => {
+    for (const { plugin, worker } of running.values()) {
+      if (plugin.granted.includes('readNotes')) worker.postMessage({ type: 'change', change });
+    }
+  });
+  return () => {
+    unlisten();
+    started = false;
+    for (const id of [...running.keys()]) stop(id);
+  };
+}
+
+// Commands the running plugins added: [{ pluginId, id, title }].
+export function listPluginCommands() {
+  return [...running.values()].flatMap((entry) => [...entry.commands.values()]);
+}
+
+export function runPluginCommand(pluginId, id) {
+  return runInPlugin(pluginId, 'command', id, []);
+}
+
+// Exporters the running plugins added: [{ pluginId, id, label, extension }].
+export function listPluginExporters() {
+  return [...running.values()].flatMap((entry) => [...entry.exporters.values()]);
+}
+
+// Renders a note with a plugin's exporter and writes it to `destination`;
+// resolves to the path written. The exporter gets the redacted markdown.
+export async function exportWithPlugin(pluginId, id, { project = '', tab, destination, redaction }) {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  const markdown = await invoke('plugin_export_source', { plugin: pluginId, project, tab, redaction });
+  const contents = await runInPlugin(pluginId, 'exporter', id, [markdown, { project, tab }]);
+  return invoke('save_plugin_export', { plugin: pluginId, project, tab, destination, contents: String(contents ?? '') });
+}
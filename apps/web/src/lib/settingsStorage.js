@@ -2,6 +2,7 @@ import { IS_TAURI } from './platform';
 
 const SETTINGS_KEY = 'hermes-settings';
 const SETTINGS_STORE_FILE = 'hermes-settings.json';
+// Shared with DEFAULT_MODEL in settings.rs.
 const DEFAULT_MODEL = 'claude-sonnet-4-6';
 
 let tauriStorePromise = null;
@@ -60,16 +61,17 @@ export async function loadSettings() {
   const store = await getTauriStore();
   if (!store) return loadLocalSettings();
 
-  const [anthropicApiKey, openaiApiKey, model, workspacePath, theme, appIcon] = await Promise.all([
+  // Preferences are typed and validated by the backend; keys and the workspace stay in the store.
+  const { invoke } = await import('@tauri-apps/api/core');
+  const [anthropicApiKey, openaiApiKey, workspacePath, resolved] = await Promise.all([
     store.get('anthropicApiKey'),
     store.get('openaiApiKey'),
-    store.get('model'),
     store.get('workspacePath'),
-    store.get('theme'),
-    store.get('appIcon'),
+    invoke('get_settings').catch(() => null),
   ]);
+  const preferences = resolved?.settings || {};
 
-  const settings = normalizeSettings({ anthropicApiKey, openaiApiKey, model, workspacePath, theme, appIcon });
+  const settings = normalizeSettings({ anthropicApiKey, openaiApiKey, workspacePath, ...preferences });
 
   const legacy = loadLocalSettings();
   const hasLegacyKeys = !!legacy.anthropicApiKey || !!legacy.openaiApiKey;
@@ -110,13 +112,18 @@ export async function saveSettings(next) {
     return;
   }
 
+  const { invoke } = await import('@tauri-apps/api/core');
   await Promise.all([
     store.set('anthropicApiKey', settings.anthropicApiKey || ''),
     store.set('openaiApiKey', settings.openaiApiKey || ''),
-    store.set('model', settings.model || DEFAULT_MODEL),
     store.set('workspacePath', settings.workspacePath || ''),
-    store.set('theme', settings.theme || 'system'),
-    store.set('appIcon', settings.appIcon || 'wing'),
   ]);
   await store.save();
+  await invoke('update_settings', {
+    update: {
+      theme: settings.theme || 'system',
+      appIcon: settings.appIcon || 'wing',
+      model: settings.model || DEFAULT_MODEL,
+    },
+  });
 }
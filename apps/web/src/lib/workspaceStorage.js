@@ -1,6 +1,10 @@
-import { IS_TAURI } from './platform';
+import { IS_DESKTOP, IS_MOBILE, IS_TAURI } from './platform';
 
 const TAB_KEYS = ['coral', 'amber', 'sage', 'sky', 'lavender'];
+// Matches CHUNKED_NOTE_BYTES in note_chunks.rs.
+const CHUNKED_NOTE_BYTES = 1024 * 1024;
+const CHUNK_BYTES = 256 * 1024;
+const CHUNK_CHARS = 128 * 1024;
 
 export function normalizePages(rawPages) {
   const pages = Object.fromEntries(TAB_KEYS.map((key) => [key, '']));
@@ -22,6 +26,21 @@ export async function listWorkspaceProjects(workspacePath) {
   return invoke('list_workspace_projects', { workspacePath });
 }
 
+// Projects as a tree: { label, name, noteCount, lastModifiedUnix, icon, color, children },
+// where `name` is the project's path below the workspace, e.g. 'clients/acme'.
+export async function listProjectTree(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('list_project_tree', { workspacePath });
+}
+
+// Hits are { project, tab, title, snippet }; `scope` limits them to one project and its sub-projects.
+export async function searchNotes(workspacePath, query, scope) {
+  if (!IS_TAURI || !workspacePath || !query.trim()) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('search_notes', { workspacePath, query, scope: scope || null });
+}
+
 export async function getDefaultWorkspace() {
   if (!IS_TAURI) return null;
   const { invoke } = await import('@tauri-apps/api/core');
@@ -34,22 +53,129 @@ export async function pickWorkspaceFolder() {
   return invoke('pick_workspace_folder');
 }
 
+export function isLargeNote(content) {
+  // UTF-8 is at most 3 bytes per UTF-16 unit, so short strings skip the encode.
+  if (!content || content.length * 3 <= CHUNKED_NOTE_BYTES) return false;
+  return new TextEncoder().encode(content).length > CHUNKED_NOTE_BYTES;
+}
+
+async function loadChunkedNote(invoke, workspacePath, tabKey) {
+  let content = '';
+  let offset = 0;
+  for (;;) {
+    const chunk = await invoke('get_note_chunk', { workspacePath, tabKey, offset, len: CHUNK_BYTES });
+    content += chunk.content;
+    if (chunk.nextOffset >= chunk.totalBytes) return content;
+    // Returning part of the note would truncate it on the next save.
+    if (chunk.nextOffset <= offset) throw new Error(`Failed reading the ${tabKey} note past byte ${offset}`);
+    offset = chunk.nextOffset;
+  }
+}
+
+async function saveChunkedNote(invoke, workspacePath, tabKey, content) {
+  const streamId = await invoke('begin_note_save', { workspacePath, tabKey });
+  try {
+    for (let start = 0; start < content.length;) {
+      let end = Math.min(start + CHUNK_CHARS, content.length);
+      // Never split a surrogate pair across chunks.
+      const last = content.charCodeAt(end - 1);
+      if (end < content.length && last >= 0xd800 && last <= 0xdbff) end -= 1;
+      await invoke('append_note_chunk', { streamId, chunk: content.slice(start, end) });
+      start = end;
+    }
+  } catch (error) {
+    await invoke('cancel_note_save', { streamId });
+    throw error;
+  }
+  await invoke('finish_note_save', { streamId });
+}
+
+// Tells the backend this window works in the workspace; resolves to its canonical path.
+export async function openWorkspace(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return workspacePath;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('open_workspace', { workspacePath });
+}
+
+// Writes the workspace's pending edits if no other window has it open.
+export async function closeWorkspace(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('close_workspace', { workspacePath });
+}
+
 export async function loadWorkspacePages(workspacePath) {
   if (!IS_TAURI || !workspacePath) return normalizePages(null);
   const { invoke } = await import('@tauri-apps/api/core');
   const rawPages = await invoke('load_workspace_pages', { workspacePath });
+  // Very large notes are left out of the bulk load and fetched piecewise.
+  const chunked = await invoke('list_chunked_notes', { workspacePath });
+  for (const { tab } of chunked) {
+    rawPages[tab] = await loadChunkedNote(invoke, workspacePath, tab);
+  }
   return normalizePages(rawPages);
 }
 
 export async function saveWorkspacePages(workspacePath, pages) {
   if (!IS_TAURI || !workspacePath) return;
   const { invoke } = await import('@tauri-apps/api/core');
+  const normalized = normalizePages(pages);
+  const large = TAB_KEYS.filter((key) => isLargeNote(normalized[key]));
+  for (const key of large) {
+    await saveChunkedNote(invoke, workspacePath, key, normalized[key]);
+    // null tells the backend to keep the chunked note as saved.
+    normalized[key] = null;
+  }
   await invoke('save_workspace_pages', {
     workspacePath,
-    pages: normalizePages(pages),
+    pages: normalized,
+  });
+}
+
+export async function saveWorkspacePage(workspacePath, tabKey, content) {
+  if (!IS_TAURI || !workspacePath) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  if (isLargeNote(content)) {
+    await saveChunkedNote(invoke, workspacePath, tabKey, content);
+    return;
+  }
+  await invoke('save_workspace_page', {
+    workspacePath,
+    tabKey,
+    content: content || '',
   });
 }
 
+// Reports an edit; autosave.rs decides when it is written. Large notes go
+// through saveWorkspacePage instead, since they are sent in chunks.
+export async function markDirty(workspacePath, tabKey, content) {
+  if (!IS_TAURI || !workspacePath) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('mark_dirty', { workspacePath, tabKey, content: content || '' });
+}
+
+// Writes saves the backend is still coalescing; call when the window blurs or closes.
+export async function flushPendingSaves(workspacePath) {
+  if (!IS_TAURI) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('flush_pending_saves', { workspacePath: workspacePath || null });
+}
+
+// Moves a note to another project of the workspace; '' is the workspace root.
+// Without `destTab` it keeps its tab when that one is free there. Resolves to
+// { srcTab, destTab, assetsCopied, snapshots }.
+export async function moveNote(workspacePath, srcProject, tabKey, destProject, destTab) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('move_note', { workspacePath, srcProject, tabKey, destProject, destTab: destTab || null });
+}
+
+export async function copyNote(workspacePath, srcProject, tabKey, destProject, destTab) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('copy_note', { workspacePath, srcProject, tabKey, destProject, destTab: destTab || null });
+}
+
 export async function loadWorkspaceChat(workspacePath) {
   if (!IS_TAURI || !workspacePath) return [];
   const { invoke } = await import('@tauri-apps/api/core');
@@ -76,3 +202,436 @@ export async function saveWorkspaceChat(workspacePath, messages) {
     chatJson: JSON.stringify(messages),
   });
 }
+
+// Pinned first, then most recently opened; `exists` is false for missing folders.
+// `name`, `icon` and `color` come from each workspace's metadata.
+export async function listRecentWorkspaces() {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('list_recent_workspaces');
+}
+
+export async function pinWorkspace(path, pinned) {
+  if (!IS_TAURI || !path) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('pin_workspace', { path, pinned });
+}
+
+export async function removeRecentWorkspace(path) {
+  if (!IS_TAURI || !path) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('remove_recent', { path });
+}
+
+// { readOnly, reason } where reason is 'marked' or 'notWritable'.
+export async function getWorkspaceAccess(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return { readOnly: false, reason: null };
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_workspace_access', { workspacePath });
+}
+
+export async function setWorkspaceReadOnly(workspacePath, readOnly) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_workspace_read_only', { workspacePath, readOnly });
+}
+
+// { name, icon, color } from the workspace's .hermes/workspace.json; unset fields are missing.
+export async function getWorkspaceMeta(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return {};
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_workspace_meta', { workspacePath });
+}
+
+export async function setWorkspaceMeta(workspacePath, meta) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_workspace_meta', { workspacePath, meta });
+}
+
+// { [tab]: fileName } without `.md`, from the workspace's .hermes/tabs.json.
+export async function getTabFilenames(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return {};
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_tab_filenames', { workspacePath });
+}
+
+// Saves the map and renames existing notes in the workspace and its projects to match.
+export async function migrateTabFilenames(workspacePath, filenames) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('migrate_tab_filenames', { workspacePath, filenames });
+}
+
+// Saves a pasted file into the project's attachments folder; resolves to the path to link it by.
+export async function saveAttachment(workspacePath, fileName, blob) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const bytes = Array.from(new Uint8Array(await blob.arrayBuffer()));
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('save_attachment', { workspacePath, fileName, bytes });
+}
+
+// Relative to each project, e.g. '.hermes/assets' or 'attachments'.
+export async function getAttachmentsFolder() {
+  if (!IS_TAURI) return '';
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_attachments_folder');
+}
+
+export async function setAttachmentsFolder(folder) {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_attachments_folder', { folder });
+}
+
+// The localhost API for scripts: { enabled, port, running }.
+export async function getLocalApi() {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_local_api');
+}
+
+export async function setLocalApi(enabled, port) {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_local_api', { enabled, port });
+}
+
+export async function getLocalApiToken() {
+  if (!IS_TAURI) return '';
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_local_api_token');
+}
+
+export async function resetLocalApiToken() {
+  if (!IS_TAURI) return '';
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('reset_local_api_token');
+}
+
+// Relay sync while backgrounded, mobile only: { enabled, unmeteredOnly, chargingOnly, intervalMinutes }.
+export async function getBackgroundSync() {
+  if (!IS_MOBILE) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_background_sync');
+}
+
+export async function setBackgroundSync(settings) {
+  if (!IS_MOBILE) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_background_sync', { settings });
+}
+
+// The workspace's .hermes/hooks.toml: { hooks, trusted, error }. Hooks only run once trusted.
+export async function getHooks(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_hooks', { workspacePath });
+}
+
+// Allows hooks.toml as it is now; any later edit needs allowing again.
+export async function trustHooks(workspacePath, trusted) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('trust_hooks', { workspacePath, trusted });
+}
+
+// The latest hook runs, newest first: [{ command, tab, status, timedOut, output, ... }].
+export async function recentHookRuns() {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('recent_hook_runs');
+}
+
+// Note extensions such as ['md', 'markdown', 'txt']; new notes get the first.
+export async function getNoteExtensions(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_note_extensions', { workspacePath });
+}
+
+export async function setNoteExtensions(workspacePath, extensions) {
+  if (!IS_TAURI || !workspacePath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_note_extensions', { workspacePath, extensions });
+}
+
+// { [project]: { icon, color, archived } } from the workspace's .hermes/projects.json.
+export async function getProjectMeta(workspacePath) {
+  if (!IS_TAURI || !workspacePath) return {};
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_project_meta', { workspacePath });
+}
+
+export async function setProjectMeta(workspacePath, project, meta) {
+  if (!IS_TAURI || !workspacePath || !project) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_project_meta', { workspacePath, project, meta });
+}
+
+// Archived projects drop out of the project lists; their folders stay.
+export async function archiveProject(workspacePath, project, archived = true) {
+  if (!IS_TAURI || !workspacePath || !project) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('archive_project', { workspacePath, project, archived });
+}
+
+// Moves the workspace folder, or adopts one already moved to `newPath`.
+export async function moveWorkspace(oldPath, newPath) {
+  if (!IS_TAURI || !oldPath || !newPath) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('move_workspace', { oldPath, newPath });
+}
+
+// Imports `src` into `dest`; strategy is 'rename' or 'append'. Resolves to the merge report.
+export async function mergeWorkspaces(src, dest, strategy) {
+  if (!IS_TAURI || !src || !dest) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('merge_workspaces', { src, dest, strategy });
+}
+
+export async function listWorkspaceTemplates() {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('list_workspace_templates');
+}
+
+// Scaffolds a new workspace folder at `dest`; resolves to its path.
+export async function createWorkspaceFromTemplate(dest, template) {
+  if (!IS_TAURI || !dest || !template) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('create_workspace_from_template', { dest, template });
+}
+
+export async function grantWorkspaceAccess(path) {
+  if (!IS_TAURI || !path) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('grant_workspace_access', { path });
+}
+
+export async function listStaleWorkspaceAccess() {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('list_stale_workspace_access');
+}
+
+// Workspace commands reject with `{ issue, path, message, guidance, settingsUrl }`
+// when the OS blocked access, and with a plain string otherwise.
+export function isAccessError(err) {
+  return Boolean(err && typeof err === 'object' && err.issue && err.guidance);
+}
+
+export async function openAccessSettings(url) {
+  if (!IS_TAURI || !url) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('open_access_settings', { url });
+}
+
+// Calls `callback` with every change the backend makes, from any window:
+// { kind: 'notesSaved' | 'indexUpdated' | 'projectCreated' | ..., workspacePath, ... }.
+// Resolves to a function that stops listening.
+export async function onWorkspaceChange(callback) {
+  if (!IS_TAURI) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen('hermes://changed', (event) => callback(event.payload));
+}
+
+// Reads a tab aloud with the system voice. options: { voice, rate } (words per minute).
+export async function speakNote(workspacePath, tabKey, options) {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('speak_note', { workspacePath, tabKey, options });
+}
+
+export async function controlSpeaking(action) {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke(`${action}_speaking`);
+}
+
+// Calls `onWord({ start, end })` with each word's UTF-16 range in the note as it is
+// read, and `onState({ state })` with 'speaking' | 'paused' | 'stopped'.
+// Resolves to a function that stops listening.
+export async function onSpeech(onWord, onState) {
+  if (!IS_DESKTOP) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const stops = await Promise.all([
+    listen('speech://word', (event) => onWord(event.payload)),
+    listen('speech://state', (event) => onState(event.payload)),
+  ]);
+  return () => stops.forEach((stop) => stop());
+}
+
+// Local whisper.cpp dictation: { available, whisperFolder, model, language, models, listening }.
+export async function getDictation() {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_dictation');
+}
+
+export async function setDictation({ model, language, whisperFolder }) {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_dictation', { model, language, whisperFolder });
+}
+
+// Progress arrives as operation://progress events of kind 'download', in bytes.
+export async function downloadWhisperModel(name, operationId) {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('download_whisper_model', { name, operationId });
+}
+
+export async function deleteWhisperModel(name) {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('delete_whisper_model', { name });
+}
+
+export async function startDictation() {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('start_dictation');
+}
+
+export async function stopDictation() {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('stop_dictation');
+}
+
+export async function transcribeAudioFile(path) {
+  if (!IS_DESKTOP) return '';
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('transcribe_audio_file', { path });
+}
+
+// Calls `onSegment({ text })` for each dictated phrase and `onStopped()` when
+// listening ends. Resolves to a function that stops listening for events.
+export async function onDictation(onSegment, onStopped) {
+  if (!IS_DESKTOP) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const stops = await Promise.all([
+    listen('dictation://segment', (event) => onSegment(event.payload)),
+    listen('dictation://stopped', () => onStopped()),
+  ]);
+  return () => stops.forEach((stop) => stop());
+}
+
+export async function startVoiceMemo(workspacePath, tabKey) {
+  if (!IS_DESKTOP) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('start_voice_memo', { workspacePath, tabKey });
+}
+
+// Resolves to `{ link, transcript }` once the memo is linked into its note;
+// `transcript` is null when dictation isn't set up.
+export async function stopVoiceMemo() {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('stop_voice_memo');
+}
+
+// Resolves to the misspelled words of `text` as `{ start, end, word, suggestions }`,
+// offsets in UTF-16 code units. Words in the workspace's dictionary are accepted.
+export async function checkText(text, lang, workspacePath) {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('check_text', { text, lang, workspacePath });
+}
+
+export async function getSpellcheckLanguages() {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_spellcheck_languages');
+}
+
+// `path` is a Hunspell `.dic` file with its `.aff` beside it; resolves to the language.
+export async function importDictionary(path) {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('import_dictionary', { path });
+}
+
+export async function deleteDictionary(lang) {
+  if (!IS_TAURI) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('delete_dictionary', { lang });
+}
+
+export async function getDictionaryWords(workspacePath) {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_dictionary_words', { workspacePath });
+}
+
+export async function addDictionaryWord(workspacePath, word) {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('add_dictionary_word', { workspacePath, word });
+}
+
+export async function removeDictionaryWord(workspacePath, word) {
+  if (!IS_TAURI) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('remove_dictionary_word', { workspacePath, word });
+}
+
+// Resolves to `{ start, end, original, replacement, reason, kind }` suggestions,
+// offsets in UTF-16 code units; `kind` is grammar, spelling, punctuation or style.
+export async function reviewText(text, style, workspacePath) {
+  if (!IS_DESKTOP) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('review_text', { text, style, workspacePath });
+}
+
+// { provider: 'assistant' | 'deepl', deeplKeySet }.
+export async function getTranslation() {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('get_translation');
+}
+
+// Leave `deeplKey` undefined to keep the saved key; an empty string removes it.
+export async function setTranslation(provider, deeplKey) {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('set_translation', { provider, deeplKey });
+}
+
+export async function translateText(text, targetLang, workspacePath) {
+  if (!IS_DESKTOP) return text;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('translate_text', { text, targetLang, workspacePath });
+}
+
+// Resolves to `{ tab, content }`: the tab the translation went into.
+export async function translateNote(workspacePath, tabKey, targetLang) {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('translate_note', { workspacePath, tabKey, targetLang });
+}
+
+// Resolves to the clipboard's HTML as markdown, or null when it has none and
+// the plain text should be pasted instead.
+export async function convertClipboardToMarkdown() {
+  if (!IS_DESKTOP) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('convert_clipboard_to_markdown');
+}
+
+// Resolves to `{ changed, diff, content }`; with `dryRun` the note is left as
+// it is and `diff` shows what formatting would change.
+export async function formatNote(workspacePath, tabKey, dryRun = false) {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('format_note', { workspacePath, tabKey, dryRun });
+}
+
+// Adds a table of contents under the note's title, or rebuilds the one it has;
+// resolves to the note's new content. Saves keep it up to date afterwards.
+export async function insertToc(workspacePath, tabKey) {
+  if (!IS_TAURI) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke('insert_toc', { workspacePath, tabKey });
+}
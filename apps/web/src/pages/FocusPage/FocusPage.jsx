@@ -8,7 +8,7 @@ import { Markdown } from '@tiptap/markdown';
 import { Slice } from '@tiptap/pm/model';
 import { IS_MOBILE, IS_TAURI } from '../../lib/platform';
 import { loadSettings, saveSettings } from '../../lib/settingsStorage';
-import { getDefaultWorkspace, listWorkspaceProjects, loadWorkspacePages, saveWorkspacePages, trashProjectFolder } from '../../lib/workspaceStorage';
+import { archiveProject, closeWorkspace, flushPendingSaves, getDefaultWorkspace, getProjectMeta, getWorkspaceAccess, isLargeNote, listWorkspaceProjects, loadWorkspacePages, markDirty, openWorkspace, saveAttachment, saveWorkspacePage, saveWorkspacePages, trashProjectFolder } from '../../lib/workspaceStorage';
 import {
   loadProjectRegistry,
   saveProjectRegistry,
@@ -35,6 +35,13 @@ function looksLikeMarkdown(text) {
   return /(?:^|\n)(#{1,6}\s|[-*+]\s|\d+\.\s|>\s|```|---|\*\*|__|\[.+\]\()/.test(text);
 }
 
+// A project is hidden when it or a project it sits inside is archived.
+function isArchived(projectMeta, name) {
+  return Object.entries(projectMeta).some(
+    ([project, meta]) => meta.archived && (name === project || name.startsWith(`${project}/`)),
+  );
+}
+
 function getWordCount(text) {
   const trimmed = text.trim();
   if (!trimmed) return 0;
@@ -79,8 +86,10 @@ export default function FocusPage() {
   const [activeTab, setActiveTab] = useState('coral');
   const [pages, setPages] = useState({ ...EMPTY_PAGES });
   const [workspacePath, setWorkspacePath] = useState('');
+  const [readOnly, setReadOnly] = useState(null);
   const [initialLoaded, setInitialLoaded] = useState(false);
   const [projectRegistry, setProjectRegistry] = useState(null);
+  const [projectMeta, setProjectMeta] = useState({});
   const saveTimerRef = useRef(null);
   const registryUpdateTimerRef = useRef(null);
   const switchingRef = useRef(false);
@@ -149,10 +158,12 @@ export default function FocusPage() {
     };
   }, []);
 
-  // Load project registry on mount
+  // Load project registry on mount; project windows are pinned to the project in their URL
   useEffect(() => {
     const registry = loadProjectRegistry();
-    setProjectRegistry(registry);
+    const windowProject = new URLSearchParams(window.location.search).get('project');
+    const pinned = windowProject && registry.projects.find((p) => p.name === windowProject);
+    setProjectRegistry(pinned ? { ...registry, activeProjectId: pinned.id } : registry);
   }, []);
 
   const activeProject = projectRegistry?.projects.find((p) => p.id === projectRegistry.activeProjectId) || null;
@@ -209,6 +220,30 @@ export default function FocusPage() {
         autocapitalize: 'off',
         spellcheck: 'false',
       },
+      // Pasted images go into the attachments folder, linked where they were pasted
+      handlePaste(view, event) {
+        const files = Array.from(event.clipboardData?.files || []).filter((file) => file.type.startsWith('image/'));
+        const projectPath = projectWorkspacePathRef.current;
+        if (!IS_TAURI || !projectPath || files.length === 0) return false;
+        event.preventDefault();
+        void (async () => {
+          for (const file of files) {
+            const name = file.name || 'image.png';
+            try {
+              const link = await saveAttachment(projectPath, name, file);
+              if (!link) continue;
+              editor?.chain().focus().insertContent({
+                type: 'text',
+                text: name,
+                marks: [{ type: 'link', attrs: { href: link } }],
+              }).run();
+            } catch {
+              // Nothing is inserted for an image that couldn't be saved
+            }
+          }
+        })();
+        return true;
+      },
       clipboardTextParser(text, $context, plainText) {
         if (plainText || !looksLikeMarkdown(text)) {
           return null;
@@ -240,14 +275,26 @@ export default function FocusPage() {
         return next;
       });
 
-      // Debounced persistence (workspace files on desktop, localStorage fallback elsewhere)
+      // The backend schedules workspace writes itself; it only needs each edit.
+      const editedProjectPath = projectWorkspacePathRef.current;
+      const reportsEdits = IS_TAURI && editedProjectPath && !isLargeNote(md);
+      if (reportsEdits) {
+        void markDirty(editedProjectPath, tab, md).catch(() => {
+          savePagesToLocalStorage(pagesRef.current);
+        });
+      }
+
+      // Debounced persistence (large notes on desktop, localStorage fallback elsewhere)
       if (saveTimerRef.current) clearTimeout(saveTimerRef.current);
       saveTimerRef.current = setTimeout(() => {
         const currentProjectPath = projectWorkspacePathRef.current;
         if (IS_TAURI && currentProjectPath) {
-          void saveWorkspacePages(currentProjectPath, pagesRef.current).catch(() => {
-            savePagesToLocalStorage(pagesRef.current);
-          });
+          // Only the edited tab changed; leave the other files alone.
+          if (!reportsEdits) {
+            void saveWorkspacePage(currentProjectPath, tab, pagesRef.current[tab]).catch(() => {
+              savePagesToLocalStorage(pagesRef.current);
+            });
+          }
         } else {
           savePagesToLocalStorage(pagesRef.current);
         }
@@ -482,7 +529,7 @@ export default function FocusPage() {
       clearTimeout(saveTimerRef.current);
       const currentProjectPath = projectWorkspacePathRef.current;
       if (IS_TAURI && currentProjectPath) {
-        void saveWorkspacePages(currentProjectPath, pagesRef.current).catch(() => {
+        void saveWorkspacePage(currentProjectPath, activeTab, pagesRef.current[activeTab]).catch(() => {
           savePagesToLocalStorage(pagesRef.current);
         });
       } else {
@@ -624,6 +671,16 @@ export default function FocusPage() {
     }
   }, [editor, activeProjectId, projectRegistry, clearHighlight]);
 
+  const handleProjectArchive = useCallback(async (projectId) => {
+    const project = projectRegistry.projects.find((p) => p.id === projectId);
+    if (!project || !workspacePathRef.current) return;
+    try {
+      await archiveProject(workspacePathRef.current, project.name);
+    } catch {
+      // The project stays listed
+    }
+  }, [projectRegistry]);
+
   const handleSettingsSaved = useCallback(async (settings) => {
     const nextWorkspacePath = settings?.workspacePath?.trim() || '';
     if (nextWorkspacePath === workspacePathRef.current) return;
@@ -652,6 +709,136 @@ export default function FocusPage() {
     setInitialLoaded(false);
   }, []);
 
+  // Keep the backend's record of this window's workspace current
+  useEffect(() => {
+    if (!workspacePath) return undefined;
+    void openWorkspace(workspacePath).catch(() => {});
+    return () => {
+      void closeWorkspace(workspacePath).catch(() => {});
+    };
+  }, [workspacePath]);
+
+  // Read-only workspaces show a banner and can't be edited
+  useEffect(() => {
+    if (!IS_TAURI || !workspacePath) return undefined;
+    let cancelled = false;
+    let unlisten = () => {};
+    void getWorkspaceAccess(workspacePath)
+      .then((access) => {
+        if (!cancelled) setReadOnly(access?.readOnly ? access.reason : null);
+      })
+      .catch(() => {});
+    (async () => {
+      const { listen } = await import('@tauri-apps/api/event');
+      unlisten = await listen('workspace://read-only', (event) => {
+        if (event.payload.workspacePath !== workspacePathRef.current) return;
+        setReadOnly(event.payload.readOnly ? event.payload.reason : null);
+      });
+    })();
+    return () => {
+      cancelled = true;
+      unlisten();
+    };
+  }, [workspacePath]);
+
+  useEffect(() => {
+    editor?.setEditable(!readOnly);
+  }, [editor, readOnly]);
+
+  // Project icons, colors and archived flags, kept current across windows
+  useEffect(() => {
+    setProjectMeta({});
+    if (!IS_TAURI || !workspacePath) return undefined;
+    let cancelled = false;
+    let unlisten = () => {};
+    void getProjectMeta(workspacePath)
+      .then((meta) => {
+        if (!cancelled) setProjectMeta(meta || {});
+      })
+      .catch(() => {});
+    (async () => {
+      const { listen } = await import('@tauri-apps/api/event');
+      unlisten = await listen('workspace://project-meta', (event) => {
+        if (event.payload.workspacePath !== workspacePathRef.current) return;
+        setProjectMeta((current) => ({ ...current, [event.payload.project]: event.payload.meta }));
+      });
+    })();
+    return () => {
+      cancelled = true;
+      unlisten();
+    };
+  }, [workspacePath]);
+
+  // Captures appended to the inbox note on disk are appended here too, so the
+  // next save of this window's copy keeps them
+  useEffect(() => {
+    if (!IS_TAURI || !editor) return undefined;
+    let unlisten = () => {};
+    (async () => {
+      const { listen } = await import('@tauri-apps/api/event');
+      unlisten = await listen('inbox-updated', (event) => {
+        const { workspacePath: inboxPath, tab, entry } = event.payload;
+        const currentProjectPath = projectWorkspacePathRef.current;
+        if (!currentProjectPath || inboxPath !== currentProjectPath) return;
+        const existing = (pagesRef.current[tab] || '').trimEnd();
+        const content = existing ? `${existing}\n\n${entry}` : entry;
+        const next = { ...pagesRef.current, [tab]: content };
+        pagesRef.current = next;
+        setPages(next);
+        if (tab === activeTabRef.current) {
+          // The entry goes at the end, so the selection before it stays put.
+          const { from, to } = editor.state.selection;
+          switchingRef.current = true;
+          editor.commands.setContent(content, { contentType: 'markdown' });
+          const end = editor.state.doc.content.size;
+          editor.commands.setTextSelection({ from: Math.min(from, end), to: Math.min(to, end) });
+          switchingRef.current = false;
+          setWordCount(getWordCount(editor.getText()));
+        }
+        void saveWorkspacePage(currentProjectPath, tab, content).catch(() => {
+          savePagesToLocalStorage(pagesRef.current);
+        });
+      });
+    })();
+    return () => unlisten();
+  }, [editor]);
+
+  // Follow the workspace when it is moved, from this window or another
+  useEffect(() => {
+    if (!IS_TAURI) return undefined;
+    let unlisten = () => {};
+    (async () => {
+      const { listen } = await import('@tauri-apps/api/event');
+      unlisten = await listen('workspace://moved', async (event) => {
+        if (event.payload.oldPath !== workspacePathRef.current) return;
+        handleSettingsSaved(await loadSettings());
+      });
+    })();
+    return () => unlisten();
+  }, [handleSettingsSaved]);
+
+  // Leaving the window: send the debounced edit now and make the backend write it
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    function handleLeave() {
+      const currentProjectPath = projectWorkspacePathRef.current;
+      if (!currentProjectPath) return;
+      // The debounce timer still fires; the backend collapses the repeated save.
+      const pending = saveTimerRef.current
+        ? saveWorkspacePage(currentProjectPath, activeTabRef.current, pagesRef.current[activeTabRef.current])
+        : Promise.resolve();
+      void pending.then(() => flushPendingSaves(currentProjectPath)).catch(() => {
+        savePagesToLocalStorage(pagesRef.current);
+      });
+    }
+    window.addEventListener('blur', handleLeave);
+    window.addEventListener('pagehide', handleLeave);
+    return () => {
+      window.removeEventListener('blur', handleLeave);
+      window.removeEventListener('pagehide', handleLeave);
+    };
+  }, []);
+
   // Close shortcuts popover on click outside
   useEffect(() => {
     if (!shortcutsOpen) return;
@@ -735,6 +922,14 @@ export default function FocusPage() {
 
   return (
     <div className={styles.page}>
+      {readOnly && (
+        <div className={styles.readOnlyBanner} role="status">
+          {readOnly === 'marked'
+            ? 'Read-only — turn it off in Settings to edit this workspace.'
+            : 'Read-only — Hermes can’t write to this folder.'}
+        </div>
+      )}
+
       {/* Floating toggle — only visible when bar is hidden */}
       {!settingsVisible && (
         <button
@@ -765,12 +960,14 @@ export default function FocusPage() {
             {projectRegistry && (
               <ProjectDropdown
                 activeProject={activeProject}
-                projects={projectRegistry.projects}
+                projects={projectRegistry.projects.filter((p) => !isArchived(projectMeta, p.name))}
                 activeProjectId={activeProjectId}
+                projectMeta={projectMeta}
                 onSelect={handleProjectSelect}
                 onCreate={handleProjectCreate}
                 onRename={handleProjectRename}
                 onDelete={handleProjectDelete}
+                onArchive={IS_TAURI && workspacePath && !readOnly ? handleProjectArchive : undefined}
               />
             )}
           </div>
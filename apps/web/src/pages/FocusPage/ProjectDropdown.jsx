@@ -16,10 +16,12 @@ export default function ProjectDropdown({
   activeProject,
   projects,
   activeProjectId,
+  projectMeta = {},
   onSelect,
   onCreate,
   onRename,
   onDelete,
+  onArchive,
 }) {
   const [open, setOpen] = useState(false);
   const [renamingId, setRenamingId] = useState(null);
@@ -91,6 +93,11 @@ export default function ProjectDropdown({
     setTrashFolder(true);
   }, []);
 
+  const handleArchive = useCallback((e, project) => {
+    e.stopPropagation();
+    onArchive(project.id);
+  }, [onArchive]);
+
   const handleConfirmDelete = useCallback(() => {
     if (!confirmDelete) return;
     onDelete(confirmDelete.id, trashFolder);
@@ -117,6 +124,12 @@ export default function ProjectDropdown({
     </svg>
   );
 
+  const archiveIcon = (
+    <svg width="12" height="12" viewBox="0 0 16 16" fill="none" stroke="currentColor" strokeWidth="1.5" strokeLinecap="round" strokeLinejoin="round">
+      <path d="M1.5 2.5h13v3h-13zM2.5 5.5v8h11v-8M6.5 8.5h3" />
+    </svg>
+  );
+
   const trashIcon = (
     <svg width="12" height="12" viewBox="0 0 16 16" fill="none" stroke="currentColor" strokeWidth="1.5" strokeLinecap="round">
       <path d="M2 4h12M5 4V3a1 1 0 0 1 1-1h4a1 1 0 0 1 1 1v1M6.5 7v5M9.5 7v5M3.5 4l.5 9a1 1 0 0 0 1 1h6a1 1 0 0 0 1-1l.5-9" />
@@ -152,10 +165,12 @@ export default function ProjectDropdown({
           <div className={styles.list}>
             {projects.map((project) => {
               const isActive = project.id === activeProjectId;
+              const meta = projectMeta[project.name] || {};
               return (
                 <div
                   key={project.id}
                   className={`${styles.item} ${isActive ? styles.itemActive : ''}`}
+                  style={meta.color ? { borderLeftColor: meta.color } : undefined}
                   onClick={() => handleSelect(project.id)}
                 >
                   {renamingId === project.id ? (
@@ -171,7 +186,10 @@ export default function ProjectDropdown({
                   ) : (
                     <>
                       <div className={styles.itemInfo}>
-                        <div className={styles.itemName}>{project.name}</div>
+                        <div className={styles.itemName}>
+                          {meta.icon && <span className={styles.itemIcon}>{meta.icon}</span>}
+                          {project.name}
+                        </div>
                         <div className={styles.itemMeta}>Updated {timeAgo(project.updatedAt)}</div>
                       </div>
                       <div className={styles.itemActions}>
@@ -182,6 +200,15 @@ export default function ProjectDropdown({
                         >
                           {pencilIcon}
                         </button>
+                        {onArchive && (
+                          <button
+                            className={styles.iconBtn}
+                            onClick={(e) => handleArchive(e, project)}
+                            title="Archive"
+                          >
+                            {archiveIcon}
+                          </button>
+                        )}
                         <button
                           className={`${styles.iconBtn} ${styles.iconBtnDanger}`}
                           onClick={(e) => handleDelete(e, project)}
@@ -1,9 +1,35 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
 import styles from './SettingsPanel.module.css';
 import { loadSettings, saveSettings } from '../../lib/settingsStorage';
-import { IS_TAURI } from '../../lib/platform';
+import { IS_MOBILE, IS_TAURI } from '../../lib/platform';
+
+import { getAttachmentsFolder, getBackgroundSync, getDefaultWorkspace, getHooks, getLocalApi, getLocalApiToken, getNoteExtensions, getTabFilenames, getWorkspaceAccess, getWorkspaceMeta, listRecentWorkspaces, migrateTabFilenames, pickWorkspaceFolder, pinWorkspace, recentHookRuns, removeRecentWorkspace, resetLocalApiToken, setAttachmentsFolder, setBackgroundSync, setLocalApi, setNoteExtensions, setWorkspaceMeta, setWorkspaceReadOnly, trustHooks } from '../../lib/workspaceStorage';
+import { listPluginCommands, listPlugins, runPluginCommand, setPluginEnabled } from '../../lib/plugins';
+import { TAB_KEYS } from './PageTabs';
+
+const PERMISSION_LABELS = {
+  readNotes: 'read notes',
+  writeNotes: 'write notes',
+  network: 'use the network',
+};
+
+// "Can read notes and reach api.example.com" for a plugin's manifest.
+function describePermissions(plugin) {
+  const abilities = plugin.permissions
+    .filter((permission) => permission !== 'network')
+    .map((permission) => PERMISSION_LABELS[permission]);
+  if (plugin.permissions.includes('network')) {
+    abilities.push(plugin.hosts.length ? `reach ${plugin.hosts.join(', ')}` : PERMISSION_LABELS.network);
+  }
+  if (!abilities.length) return 'Needs no permissions.';
+  const last = abilities.pop();
+  return `Can ${abilities.length ? `${abilities.join(', ')} and ${last}` : last}.`;
+}
 
-import { getDefaultWorkspace, pickWorkspaceFolder } from '../../lib/workspaceStorage';
+function asksForMore(plugin) {
+  return plugin.permissions.some((permission) => !plugin.granted.includes(permission))
+    || (plugin.permissions.includes('network') && plugin.hosts.some((host) => !plugin.grantedHosts.includes(host.toLowerCase())));
+}
 
 const THEME_OPTIONS = [
   { value: 'light', label: 'Light' },
@@ -132,6 +158,24 @@ export default function SettingsPanel({ isOpen, onClose, onSettingsSaved }) {
   const [theme, setTheme] = useState('system');
   const [originalKeys, setOriginalKeys] = useState({ anthropic: '', openai: '', workspace: '' });
   const [pickingWorkspace, setPickingWorkspace] = useState(false);
+  const [recentWorkspaces, setRecentWorkspaces] = useState([]);
+  const [workspaceAccess, setWorkspaceAccess] = useState(null);
+  const [workspaceMeta, setWorkspaceMetaFields] = useState({ name: '', icon: '', color: '' });
+  const [savedMeta, setSavedMeta] = useState({ name: '', icon: '', color: '' });
+  const [tabFilenames, setTabFilenameFields] = useState({});
+  const [savedFilenames, setSavedFilenames] = useState({});
+  const [noteExtensions, setNoteExtensionsField] = useState('');
+  const [savedExtensions, setSavedExtensions] = useState('');
+  const [attachmentsFolder, setAttachmentsFolderField] = useState('');
+  const [savedAttachmentsFolder, setSavedAttachmentsFolder] = useState('');
+  const [localApi, setLocalApiStatus] = useState(null);
+  const [localApiPort, setLocalApiPort] = useState('');
+  const [localApiToken, setLocalApiToken] = useState('');
+  const [backgroundSync, setBackgroundSyncStatus] = useState(null);
+  const [hooks, setHooks] = useState(null);
+  const [lastHookRun, setLastHookRun] = useState(null);
+  const [plugins, setPlugins] = useState([]);
+  const [pluginCommands, setPluginCommands] = useState([]);
   const [devtoolsMessage, setDevtoolsMessage] = useState('');
   const [hasDebugTools, setHasDebugTools] = useState(false);
   const panelRef = useRef(null);
@@ -175,6 +219,51 @@ export default function SettingsPanel({ isOpen, onClose, onSettingsSaved }) {
         }
       }
 
+      try {
+        const recents = await listRecentWorkspaces();
+        if (!cancelled) setRecentWorkspaces(recents);
+      } catch {
+        // not available
+      }
+
+      try {
+        const folder = await getAttachmentsFolder();
+        if (!cancelled) {
+          setAttachmentsFolderField(folder);
+          setSavedAttachmentsFolder(folder);
+        }
+      } catch {
+        // not available
+      }
+
+      try {
+        const status = await getLocalApi();
+        if (!cancelled && status) {
+          setLocalApiStatus(status);
+          setLocalApiPort(String(status.port));
+          if (status.enabled) setLocalApiToken(await getLocalApiToken());
+        }
+      } catch {
+        // not available
+      }
+
+      try {
+        const settings = await getBackgroundSync();
+        if (!cancelled && settings) setBackgroundSyncStatus(settings);
+      } catch {
+        // not available
+      }
+
+      try {
+        const installed = await listPlugins();
+        if (!cancelled) {
+          setPlugins(installed);
+          setPluginCommands(listPluginCommands());
+        }
+      } catch {
+        // not available
+      }
+
       // Check if debug tools are available
       if (IS_TAURI) {
         try {
@@ -223,6 +312,173 @@ export default function SettingsPanel({ isOpen, onClose, onSettingsSaved }) {
     }
   }, [saveField]);
 
+  const handleSwitchWorkspace = useCallback(async (path) => {
+    setWorkspacePath(path);
+    await saveField('workspacePath', path);
+  }, [saveField]);
+
+  const handlePinWorkspace = useCallback(async (path, pinned) => {
+    await pinWorkspace(path, pinned);
+    setRecentWorkspaces(await listRecentWorkspaces());
+  }, []);
+
+  const handleRemoveRecent = useCallback(async (path) => {
+    await removeRecentWorkspace(path);
+    setRecentWorkspaces(await listRecentWorkspaces());
+  }, []);
+
+  // The mark only covers workspaces Hermes could write; unwritable folders stay read-only.
+  useEffect(() => {
+    if (!isOpen || !IS_TAURI || !workspacePath) return undefined;
+    let cancelled = false;
+    void getWorkspaceAccess(workspacePath)
+      .then((access) => {
+        if (!cancelled) setWorkspaceAccess(access);
+      })
+      .catch(() => {});
+    void getWorkspaceMeta(workspacePath)
+      .then((meta) => {
+        if (cancelled) return;
+        const fields = { name: meta.name || '', icon: meta.icon || '', color: meta.color || '' };
+        setWorkspaceMetaFields(fields);
+        setSavedMeta(fields);
+      })
+      .catch(() => {});
+    void getTabFilenames(workspacePath)
+      .then((filenames) => {
+        if (cancelled) return;
+        setTabFilenameFields(filenames);
+        setSavedFilenames(filenames);
+      })
+      .catch(() => {});
+    void getNoteExtensions(workspacePath)
+      .then((extensions) => {
+        if (cancelled) return;
+        setNoteExtensionsField(extensions.join(', '));
+        setSavedExtensions(extensions.join(', '));
+      })
+      .catch(() => {});
+    void getHooks(workspacePath)
+      .then((status) => {
+        if (!cancelled) setHooks(status);
+      })
+      .catch(() => {});
+    void recentHookRuns()
+      .then((runs) => {
+        if (!cancelled) setLastHookRun(runs[0] ?? null);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [isOpen, workspacePath]);
+
+  const handleToggleHooks = useCallback(async () => {
+    if (!workspacePath || !hooks) return;
+    if (!hooks.trusted) {
+      const commands = hooks.hooks.map((hook) => `• ${hook.event}: ${hook.shell ?? [hook.command, ...hook.args].join(' ')}`).join('\n');
+      if (!window.confirm(`Allow these hooks to run on this computer?\n\n${commands}`)) return;
+    }
+    try {
+      setHooks(await trustHooks(workspacePath, !hooks.trusted));
+    } catch (err) {
+      setHooks({ ...hooks, error: String(err) });
+    }
+  }, [workspacePath, hooks]);
+
+  const handleToggleReadOnly = useCallback(async () => {
+    if (!workspacePath || !workspaceAccess) return;
+    setWorkspaceAccess(await setWorkspaceReadOnly(workspacePath, !workspaceAccess.readOnly));
+  }, [workspacePath, workspaceAccess]);
+
+  const handleSaveMeta = useCallback(async (field, value) => {
+    if (!workspacePath) return;
+    const meta = await setWorkspaceMeta(workspacePath, { ...savedMeta, [field]: value });
+    const fields = { name: meta?.name || '', icon: meta?.icon || '', color: meta?.color || '' };
+    setWorkspaceMetaFields(fields);
+    setSavedMeta(fields);
+    setRecentWorkspaces(await listRecentWorkspaces());
+  }, [workspacePath, savedMeta]);
+
+  // Renames the tab's note in the workspace and every project to match.
+  const handleSaveFilename = useCallback(async (tab, value) => {
+    if (!workspacePath) return;
+    const result = await migrateTabFilenames(workspacePath, { ...savedFilenames, [tab]: value });
+    if (!result) return;
+    setTabFilenameFields(result.filenames);
+    setSavedFilenames(result.filenames);
+  }, [workspacePath, savedFilenames]);
+
+  const handleSaveExtensions = useCallback(async (value) => {
+    if (!workspacePath) return;
+    const extensions = await setNoteExtensions(workspacePath, value.split(','));
+    if (!extensions) return;
+    setNoteExtensionsField(extensions.join(', '));
+    setSavedExtensions(extensions.join(', '));
+  }, [workspacePath]);
+
+  const handleSaveAttachmentsFolder = useCallback(async (value) => {
+    const folder = await setAttachmentsFolder(value);
+    if (folder === null) return;
+    setAttachmentsFolderField(folder);
+    setSavedAttachmentsFolder(folder);
+  }, []);
+
+  const handleToggleLocalApi = useCallback(async () => {
+    if (!localApi) return;
+    try {
+      const status = await setLocalApi(!localApi.enabled, localApi.port);
+      setLocalApiStatus(status);
+      if (status.enabled) setLocalApiToken(await getLocalApiToken());
+    } catch {
+      setLocalApiStatus(await getLocalApi());
+    }
+  }, [localApi]);
+
+  const handleSaveLocalApiPort = useCallback(async (value) => {
+    const port = Number(value);
+    if (!localApi || !Number.isInteger(port)) return;
+    try {
+      const status = await setLocalApi(localApi.enabled, port);
+      setLocalApiStatus(status);
+      setLocalApiPort(String(status.port));
+    } catch {
+      setLocalApiPort(String(localApi.port));
+    }
+  }, [localApi]);
+
+  const handleResetLocalApiToken = useCallback(async () => {
+    setLocalApiToken(await resetLocalApiToken());
+  }, []);
+
+  const handleChangeBackgroundSync = useCallback(async (changes) => {
+    if (!backgroundSync) return;
+    try {
+      setBackgroundSyncStatus(await setBackgroundSync({ ...backgroundSync, ...changes }));
+    } catch {
+      setBackgroundSyncStatus(await getBackgroundSync());
+    }
+  }, [backgroundSync]);
+
+  const handleSetPluginEnabled = useCallback(async (plugin, enable) => {
+    if (enable && !window.confirm(`Enable ${plugin.name}? ${describePermissions(plugin)}`)) return;
+    try {
+      await setPluginEnabled(plugin.id, enable);
+    } finally {
+      setPlugins(await listPlugins());
+      // Give a started plugin a moment to register its commands.
+      setTimeout(() => setPluginCommands(listPluginCommands()), 500);
+    }
+  }, []);
+
+  const handleRunPluginCommand = useCallback(async (command) => {
+    try {
+      await runPluginCommand(command.pluginId, command.id);
+    } catch (err) {
+      console.error(`Plugin command ${command.id} failed:`, err);
+    }
+  }, []);
+
   const handleOpenWorkspace = useCallback(async () => {
     if (!IS_TAURI || !workspacePath) return;
     try {
@@ -359,7 +615,13 @@ export default function SettingsPanel({ isOpen, onClose, onSettingsSaved }) {
                 onSave={(v) => saveField('workspacePath', v)}
               />
 
-              {IS_TAURI && (
+              {IS_MOBILE && (
+                <span className={styles.hint}>
+                  On this device notes stay in Hermes's own folder, which the Files app shows.
+                </span>
+              )}
+
+              {IS_TAURI && !IS_MOBILE && (
                 <div className={styles.workspaceActions}>
                   <button className={styles.secondaryBtn} onClick={handlePickWorkspace} type="button" disabled={pickingWorkspace}>
                     {pickingWorkspace ? 'Selecting...' : 'Select folder'}
@@ -370,6 +632,221 @@ export default function SettingsPanel({ isOpen, onClose, onSettingsSaved }) {
                 </div>
               )}
 
+              {IS_TAURI && (
+                <InlineSaveInput
+                  label="Attachments folder"
+                  type="text"
+                  value={attachmentsFolder}
+                  onChange={(e) => setAttachmentsFolderField(e.target.value)}
+                  placeholder=".hermes/assets, or attachments for Obsidian"
+                  originalValue={savedAttachmentsFolder}
+                  onSave={handleSaveAttachmentsFolder}
+                />
+              )}
+
+              {IS_TAURI && localApi && (
+                <>
+                  <span className={styles.themeLabel}>Local API</span>
+                  <span className={styles.hint}>
+                    {localApi.enabled && !localApi.running
+                      ? `Port ${localApi.port} is in use; choose another.`
+                      : <>Scripts can read, add and search notes at <code>http://127.0.0.1:{localApi.port}/v1</code> with the token below.</>}
+                  </span>
+                  <button className={styles.secondaryBtn} onClick={handleToggleLocalApi} type="button">
+                    {localApi.enabled ? 'Turn off local API' : 'Turn on local API'}
+                  </button>
+                  {localApi.enabled && (
+                    <>
+                      <InlineSaveInput
+                        label="Port"
+                        type="text"
+                        value={localApiPort}
+                        onChange={(e) => setLocalApiPort(e.target.value)}
+                        placeholder="47615"
+                        originalValue={String(localApi.port)}
+                        onSave={handleSaveLocalApiPort}
+                      />
+                      <label className={styles.label}>
+                        Token
+                        <div className={styles.inputWrap}>
+                          <input className={styles.input} type="text" value={localApiToken} readOnly spellCheck={false} />
+                        </div>
+                      </label>
+                      <button className={styles.secondaryBtn} onClick={handleResetLocalApiToken} type="button">
+                        New token
+                      </button>
+                    </>
+                  )}
+                </>
+              )}
+
+              {IS_MOBILE && backgroundSync && (
+                <>
+                  <span className={styles.themeLabel}>Background sync</span>
+                  <span className={styles.hint}>
+                    {backgroundSync.enabled
+                      ? `Syncs with the relay about every ${backgroundSync.intervalMinutes} minutes while Hermes is in the background, when the system allows.`
+                      : 'Sync with the relay while Hermes is in the background.'}
+                  </span>
+                  <button className={styles.secondaryBtn} onClick={() => handleChangeBackgroundSync({ enabled: !backgroundSync.enabled })} type="button">
+                    {backgroundSync.enabled ? 'Turn off background sync' : 'Turn on background sync'}
+                  </button>
+                  {backgroundSync.enabled && (
+                    <>
+                      <button className={styles.secondaryBtn} onClick={() => handleChangeBackgroundSync({ unmeteredOnly: !backgroundSync.unmeteredOnly })} type="button">
+                        {backgroundSync.unmeteredOnly ? 'Wi-Fi only: on' : 'Wi-Fi only: off'}
+                      </button>
+                      <button className={styles.secondaryBtn} onClick={() => handleChangeBackgroundSync({ chargingOnly: !backgroundSync.chargingOnly })} type="button">
+                        {backgroundSync.chargingOnly ? 'Only while charging: on' : 'Only while charging: off'}
+                      </button>
+                    </>
+                  )}
+                </>
+              )}
+
+              {IS_TAURI && plugins.length > 0 && (
+                <>
+                  <span className={styles.themeLabel}>Plugins</span>
+                  {plugins.map((plugin) => (
+                    <div key={plugin.id}>
+                      <span className={styles.hint}>
+                        {plugin.name}{plugin.version ? ` ${plugin.version}` : ''}
+                        {plugin.description ? ` — ${plugin.description}` : ''}
+                        {' '}{describePermissions(plugin)}
+                        {plugin.enabled && asksForMore(plugin) ? ' It now asks for more; enable it again to grant that.' : ''}
+                      </span>
+                      {(!plugin.enabled || asksForMore(plugin)) && (
+                        <button className={styles.secondaryBtn} onClick={() => handleSetPluginEnabled(plugin, true)} type="button">
+                          {plugin.enabled ? 'Enable again' : 'Enable'}
+                        </button>
+                      )}
+                      {plugin.enabled && (
+                        <button className={styles.secondaryBtn} onClick={() => handleSetPluginEnabled(plugin, false)} type="button">
+                          Disable
+                        </button>
+                      )}
+                      {pluginCommands.filter((command) => command.pluginId === plugin.id).map((command) => (
+                        <button key={command.id} className={styles.secondaryBtn} onClick={() => handleRunPluginCommand(command)} type="button">
+                          {command.title}
+                        </button>
+                      ))}
+                    </div>
+                  ))}
+                </>
+              )}
+
+              {IS_TAURI && workspacePath && (
+                <>
+                  <InlineSaveInput
+                    label="Name"
+                    type="text"
+                    value={workspaceMeta.name}
+                    onChange={(e) => setWorkspaceMetaFields((meta) => ({ ...meta, name: e.target.value }))}
+                    placeholder="Folder name"
+                    originalValue={savedMeta.name}
+                    onSave={(v) => handleSaveMeta('name', v)}
+                  />
+                  <InlineSaveInput
+                    label="Icon"
+                    type="text"
+                    value={workspaceMeta.icon}
+                    onChange={(e) => setWorkspaceMetaFields((meta) => ({ ...meta, icon: e.target.value }))}
+                    placeholder="One emoji"
+                    originalValue={savedMeta.icon}
+                    onSave={(v) => handleSaveMeta('icon', v)}
+                  />
+                  <InlineSaveInput
+                    label="Color"
+                    type="text"
+                    value={workspaceMeta.color}
+                    onChange={(e) => setWorkspaceMetaFields((meta) => ({ ...meta, color: e.target.value }))}
+                    placeholder="#rrggbb"
+                    originalValue={savedMeta.color}
+                    onSave={(v) => handleSaveMeta('color', v)}
+                  />
+                </>
+              )}
+
+              {IS_TAURI && workspacePath && Object.keys(savedFilenames).length > 0 && (
+                <>
+                  <span className={styles.themeLabel}>Tab file names</span>
+                  {TAB_KEYS.map((tab) => (
+                    <InlineSaveInput
+                      key={tab}
+                      label={tab}
+                      type="text"
+                      value={tabFilenames[tab] ?? ''}
+                      onChange={(e) => setTabFilenameFields((names) => ({ ...names, [tab]: e.target.value }))}
+                      placeholder={tab}
+                      originalValue={savedFilenames[tab]}
+                      onSave={(v) => handleSaveFilename(tab, v)}
+                    />
+                  ))}
+                  <InlineSaveInput
+                    label="Note extensions"
+                    type="text"
+                    value={noteExtensions}
+                    onChange={(e) => setNoteExtensionsField(e.target.value)}
+                    placeholder="md, markdown, txt"
+                    originalValue={savedExtensions}
+                    onSave={handleSaveExtensions}
+                  />
+                </>
+              )}
+
+              {IS_TAURI && hooks && (hooks.hooks.length > 0 || hooks.error) && (
+                <>
+                  <span className={styles.themeLabel}>Hooks</span>
+                  <span className={styles.hint}>
+                    {hooks.error
+                      ?? `${hooks.hooks.length} ${hooks.hooks.length === 1 ? 'hook' : 'hooks'} in .hermes/hooks.toml${hooks.trusted ? ' run after saves and exports.' : ' are not allowed to run yet.'}`}
+                  </span>
+                  {!hooks.error && (
+                    <button className={styles.secondaryBtn} onClick={handleToggleHooks} type="button">
+                      {hooks.trusted ? 'Stop hooks' : 'Allow hooks'}
+                    </button>
+                  )}
+                  {lastHookRun && (
+                    <span className={styles.hint}>
+                      Last run: {lastHookRun.command} — {lastHookRun.timedOut ? 'timed out' : `exit ${lastHookRun.status ?? '?'}`}
+                      {lastHookRun.output.trim() ? `: ${lastHookRun.output.trim().slice(0, 200)}` : ''}
+                    </span>
+                  )}
+                </>
+              )}
+
+              {IS_TAURI && workspaceAccess && workspaceAccess.reason !== 'notWritable' && (
+                <button className={styles.secondaryBtn} onClick={handleToggleReadOnly} type="button">
+                  {workspaceAccess.readOnly ? 'Allow editing' : 'Make read-only'}
+                </button>
+              )}
+
+              {recentWorkspaces.length > 0 && (
+                <div className={styles.recentList}>
+                  <span className={styles.themeLabel}>Recent</span>
+                  {recentWorkspaces.map((recent) => (
+                    <div key={recent.path} className={styles.recentRow}>
+                      <button
+                        className={styles.recentPath}
+                        onClick={() => handleSwitchWorkspace(recent.path)}
+                        type="button"
+                        disabled={!recent.exists || recent.path === workspacePath}
+                        title={recent.exists ? recent.path : `${recent.path} (missing)`}
+                        style={recent.color ? { borderLeftColor: recent.color } : undefined}
+                      >
+                        {recent.icon ? `${recent.icon} ${recent.name}` : recent.name}
+                      </button>
+                      <button className={styles.recentAction} onClick={() => handlePinWorkspace(recent.path, !recent.pinned)} type="button">
+                        {recent.pinned ? 'Unpin' : 'Pin'}
+                      </button>
+                      <button className={styles.recentAction} onClick={() => handleRemoveRecent(recent.path)} type="button">
+                        Remove
+                      </button>
+                    </div>
+                  ))}
+                </div>
+              )}
+
               {hasDebugTools && (
                 <>
                   <button className={styles.debugBtn} onClick={handleToggleDevtools} type="button">
@@ -0,0 +1,37 @@
+import { useEffect, useState } from 'react';
+import styles from './LockPage.module.css';
+
+export default function LockPage() {
+  const [error, setError] = useState('');
+  const [unlocking, setUnlocking] = useState(false);
+
+  const unlock = async () => {
+    if (unlocking) return;
+    setUnlocking(true);
+    setError('');
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke('unlock_app');
+    } catch (err) {
+      setError(typeof err === 'string' ? err : 'Could not unlock Hermes');
+    } finally {
+      setUnlocking(false);
+    }
+  };
+
+  // Prompt right away; the button stays for retries after a cancel.
+  useEffect(() => {
+    void unlock();
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, []);
+
+  return (
+    <main className={styles.page}>
+      <p className={styles.title}>Hermes is locked</p>
+      <button type="button" className={styles.button} onClick={unlock} disabled={unlocking}>
+        {unlocking ? 'Waiting for authentication…' : 'Unlock'}
+      </button>
+      {error && <p className={styles.error}>{error}</p>}
+    </main>
+  );
+}
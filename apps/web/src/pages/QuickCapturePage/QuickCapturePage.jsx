@@ -0,0 +1,57 @@
+import { useEffect, useRef, useState } from 'react';
+import styles from './QuickCapturePage.module.css';
+
+async function invokeCapture(command, args) {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke(command, args);
+}
+
+export default function QuickCapturePage() {
+  const [text, setText] = useState('');
+  const [error, setError] = useState('');
+  const [saving, setSaving] = useState(false);
+  const inputRef = useRef(null);
+
+  useEffect(() => {
+    inputRef.current?.focus();
+  }, []);
+
+  const submit = async () => {
+    if (!text.trim() || saving) return;
+    setSaving(true);
+    setError('');
+    try {
+      await invokeCapture('submit_quick_capture', { text });
+      setText('');
+    } catch (err) {
+      setError(typeof err === 'string' ? err : 'Could not save capture');
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleKeyDown = (e) => {
+    if (e.key === 'Escape') {
+      e.preventDefault();
+      void invokeCapture('close_quick_capture');
+    } else if (e.key === 'Enter' && !e.shiftKey) {
+      e.preventDefault();
+      void submit();
+    }
+  };
+
+  return (
+    <main className={styles.page} data-tauri-drag-region>
+      <textarea
+        ref={inputRef}
+        className={styles.input}
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        onKeyDown={handleKeyDown}
+        placeholder="Capture a thought… (Enter to save, Esc to close)"
+        disabled={saving}
+      />
+      {error && <p className={styles.error}>{error}</p>}
+    </main>
+  );
+}
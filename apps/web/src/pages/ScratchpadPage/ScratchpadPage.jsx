@@ -0,0 +1,68 @@
+import { useEffect, useRef, useState } from 'react';
+import styles from './ScratchpadPage.module.css';
+
+const SAVE_DELAY_MS = 400;
+
+async function invokeScratchpad(command, args) {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke(command, args);
+}
+
+export default function ScratchpadPage() {
+  const [content, setContent] = useState('');
+  const [loaded, setLoaded] = useState(false);
+  const saveTimerRef = useRef(null);
+  const contentRef = useRef('');
+
+  useEffect(() => {
+    invokeScratchpad('load_scratchpad')
+      .then((text) => {
+        contentRef.current = text;
+        setContent(text);
+      })
+      .catch(() => {})
+      .finally(() => setLoaded(true));
+
+    return () => {
+      if (saveTimerRef.current) {
+        clearTimeout(saveTimerRef.current);
+        void invokeScratchpad('save_scratchpad', { content: contentRef.current }).catch(() => {});
+      }
+    };
+  }, []);
+
+  const handleChange = (e) => {
+    const next = e.target.value;
+    contentRef.current = next;
+    setContent(next);
+
+    if (saveTimerRef.current) clearTimeout(saveTimerRef.current);
+    saveTimerRef.current = setTimeout(() => {
+      saveTimerRef.current = null;
+      void invokeScratchpad('save_scratchpad', { content: next }).catch(() => {});
+    }, SAVE_DELAY_MS);
+  };
+
+  return (
+    <main className={styles.page}>
+      <div className={styles.titleBar} data-tauri-drag-region>
+        <span className={styles.title} data-tauri-drag-region>Scratchpad</span>
+        <button
+          className={styles.closeBtn}
+          onClick={() => void invokeScratchpad('toggle_scratchpad_window')}
+          aria-label="Close scratchpad"
+        >
+          ×
+        </button>
+      </div>
+      <textarea
+        className={styles.input}
+        value={content}
+        onChange={handleChange}
+        placeholder={loaded ? 'Jot something down…' : ''}
+        disabled={!loaded}
+        autoFocus
+      />
+    </main>
+  );
+}